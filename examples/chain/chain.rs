@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use lychee_lib::{chain::RequestChain, ChainResult, ClientBuilder, Handler, Result, Status};
+use lychee_lib::{chain::ChainBuilder, ChainResult, ClientBuilder, Handler, Result, Status};
 use reqwest::{Method, Request};
 
 #[derive(Debug)]
@@ -9,7 +9,7 @@ struct MyHandler {}
 impl Handler<Request, Status> for MyHandler {
     async fn handle(&mut self, mut request: Request) -> ChainResult<Request, Status> {
         // Handle special case of some website (fictional example)
-        if request.url().domain() == Some("wikipedia.org") && request.url().path() == "/home" {
+        if request.url().path() == "/home" {
             request.url_mut().set_path("/foo-bar");
             *request.method_mut() = Method::PUT;
         }
@@ -20,15 +20,26 @@ impl Handler<Request, Status> for MyHandler {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let chain = RequestChain::new(vec![Box::new(MyHandler {})]);
+    // Only apply the handler to requests to wikipedia.org
+    let chain = ChainBuilder::new()
+        .when(
+            |request: &Request| request.url().domain() == Some("wikipedia.org"),
+            MyHandler {},
+        )
+        .build();
 
     let client = ClientBuilder::builder()
-        .plugin_request_chain(chain)
+        .plugin_request_chain(chain.clone())
         .build()
         .client()?;
 
     let result = client.check("https://wikipedia.org/home").await;
     println!("{:?}", result);
 
+    // Time spent in each handler of the chain
+    for stats in chain.stats().await {
+        println!("{stats}");
+    }
+
     Ok(())
 }
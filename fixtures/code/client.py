@@ -0,0 +1,13 @@
+"""Client for the example API.
+
+See https://example.com/docs for the API reference.
+"""
+
+import requests
+
+# Endpoints are described at https://example.org/endpoints
+BASE_URL = "https://api.example.com/v1/{resource}"
+
+
+def get(resource):
+    return requests.get(BASE_URL.format(resource=resource))
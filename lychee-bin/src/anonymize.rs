@@ -0,0 +1,314 @@
+//! Anonymized reports (`--anonymize`), which can be shared outside of an
+//! organization, e.g. to debug issues with many links, without exposing the
+//! internal link structure.
+//!
+//! URLs and input sources are replaced with keyed hashes (HMAC-SHA256),
+//! while statuses and counts are kept. Links to the same host share their
+//! host hash. The key is random for each run, so hashes can't be reversed by
+//! hashing guessed URLs; with `--anonymize-salt`, the same link has the same
+//! hash in the reports of different runs.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use lychee_lib::{InputSource, Redirects, ResponseBody, Status, Uri};
+use reqwest::Url;
+use ring::hmac;
+use ring::rand::SystemRandom;
+
+use crate::archive::{HistoricalState, HistoricalStatus, Suggestion};
+use crate::stats::{ResponseStats, Warning};
+
+/// Replaces URLs and input sources with hashes
+#[derive(Debug)]
+pub(crate) struct Anonymizer {
+    /// Key of the hashes
+    key: hmac::Key,
+    /// All anonymized values and their replacements, to remove them from
+    /// the rest of the report, e.g. from error messages
+    replacements: HashMap<String, String>,
+}
+
+impl Anonymizer {
+    /// Create an anonymizer whose hashes are keyed with `salt`, or with a
+    /// random key if there is no salt
+    pub(crate) fn new(salt: Option<&str>) -> Self {
+        let key = match salt {
+            Some(salt) => hmac::Key::new(hmac::HMAC_SHA256, salt.as_bytes()),
+            None => hmac::Key::generate(hmac::HMAC_SHA256, &SystemRandom::new())
+                .expect("system random number generator is available"),
+        };
+        Self {
+            key,
+            replacements: HashMap::new(),
+        }
+    }
+
+    /// Replace all URLs and input sources in `stats`
+    pub(crate) fn anonymize(&mut self, stats: ResponseStats) -> ResponseStats {
+        ResponseStats {
+            success_map: self.map(stats.success_map, Self::body),
+            error_map: self.map(stats.error_map, Self::body),
+            suggestion_map: self.map(stats.suggestion_map, |a, suggestion| Suggestion {
+                original: a.url(&suggestion.original),
+                suggestion: a.url(&suggestion.suggestion),
+            }),
+            history_map: self.map(stats.history_map, |a, status| HistoricalStatus {
+                url: a.url(&status.url),
+                state: match status.state {
+                    HistoricalState::AliveThen {
+                        snapshot,
+                        timestamp,
+                    } => HistoricalState::AliveThen {
+                        snapshot: a.url(&snapshot),
+                        timestamp,
+                    },
+                    HistoricalState::BrokenThen {
+                        snapshot,
+                        timestamp,
+                    } => HistoricalState::BrokenThen {
+                        snapshot: a.url(&snapshot),
+                        timestamp,
+                    },
+                    HistoricalState::NeverArchived => HistoricalState::NeverArchived,
+                },
+            }),
+            excluded_map: self.map(stats.excluded_map, Self::body),
+            generated_error_map: self.map(stats.generated_error_map, Self::body),
+            warning_map: self.map(stats.warning_map, |a, warning| Warning {
+                uri: a.uri(&warning.uri),
+                reason: warning.reason,
+            }),
+            host_map: stats
+                .host_map
+                .into_iter()
+                .map(|(host, host_stats)| (self.host(&host), host_stats))
+                .collect(),
+            host_timings: stats
+                .host_timings
+                .into_iter()
+                .map(|(host, timings)| (self.host(&host), timings))
+                .collect(),
+            directory_map: stats
+                .directory_map
+                .into_iter()
+                .map(|(directory, counts)| (self.directory(&directory), counts))
+                .collect(),
+            skipped_binaries: stats
+                .skipped_binaries
+                .iter()
+                .map(|source| self.source(source))
+                .collect(),
+            reference_issues: stats
+                .reference_issues
+                .into_iter()
+                .map(|(source, issues)| (self.source(&source), issues))
+                .collect(),
+            ..stats
+        }
+    }
+
+    /// Replace all anonymized values which are left in `text`, e.g. in the
+    /// details of an error
+    pub(crate) fn scrub(&self, text: &str) -> String {
+        // Longer values first, as they may contain shorter ones (e.g. hosts)
+        let mut replacements: Vec<_> = self.replacements.iter().collect();
+        replacements.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then(a.cmp(b)));
+        replacements
+            .into_iter()
+            .fold(text.to_string(), |text, (original, replacement)| {
+                text.replace(original.as_str(), replacement)
+            })
+    }
+
+    fn map<T: Eq + Hash>(
+        &mut self,
+        map: HashMap<InputSource, HashSet<T>>,
+        mut anonymize: impl FnMut(&mut Self, T) -> T,
+    ) -> HashMap<InputSource, HashSet<T>> {
+        let mut anonymized: HashMap<InputSource, HashSet<T>> = HashMap::new();
+        for (source, items) in map {
+            let source = self.source(&source);
+            let items: Vec<T> = items
+                .into_iter()
+                .map(|item| anonymize(self, item))
+                .collect();
+            anonymized.entry(source).or_default().extend(items);
+        }
+        anonymized
+    }
+
+    fn body(&mut self, body: ResponseBody) -> ResponseBody {
+        ResponseBody {
+            uri: self.uri(&body.uri),
+            status: self.status(body.status),
+            span: body.span,
+            label: body.label,
+        }
+    }
+
+    fn status(&mut self, status: Status) -> Status {
+        match status {
+            // The hops of the redirect chain would need to be anonymized as
+            // well, but the chain isn't needed to see that links are redirected
+            Status::Redirected(code, _) => Status::Redirected(code, Redirects::default()),
+            Status::CanonicalMismatch {
+                code,
+                url,
+                canonical,
+            } => Status::CanonicalMismatch {
+                code,
+                url: Box::new(self.url(&url)),
+                canonical: Box::new(self.url(&canonical)),
+            },
+            status => status,
+        }
+    }
+
+    fn uri(&mut self, uri: &Uri) -> Uri {
+        let url = Url::parse(uri.as_str()).expect("URI is a valid URL");
+        Uri::from(self.url(&url))
+    }
+
+    /// Replace the host and the rest of `url` with separate hashes, so that
+    /// links to the same host can still be grouped
+    fn url(&mut self, url: &Url) -> Url {
+        let anonymized = match url.host_str() {
+            Some(host) => format!(
+                "{}://{}/{}",
+                url.scheme(),
+                self.host(host),
+                self.hash(url.as_str())
+            ),
+            None => format!("{}:{}", url.scheme(), self.hash(url.as_str())),
+        };
+        let anonymized = Url::parse(&anonymized).expect("Anonymized URL is valid");
+        self.replacements
+            .insert(url.to_string(), anonymized.to_string());
+        anonymized
+    }
+
+    fn host(&mut self, host: &str) -> String {
+        let anonymized = format!("host-{}", self.hash(host));
+        // Other (short) hosts like `localhost` are too likely to be part of
+        // unrelated text
+        if host.contains('.') {
+            self.replacements
+                .insert(host.to_string(), anonymized.clone());
+        }
+        anonymized
+    }
+
+    fn directory(&mut self, directory: &str) -> String {
+        let anonymized = format!("directory-{}", self.hash(directory));
+        self.replacements
+            .insert(directory.to_string(), anonymized.clone());
+        anonymized
+    }
+
+    fn source(&mut self, source: &InputSource) -> InputSource {
+        if *source == InputSource::Stdin {
+            return InputSource::Stdin;
+        }
+        let original = source.to_string();
+        let anonymized = format!("source-{}", self.hash(&original));
+        self.replacements.insert(original, anonymized.clone());
+        InputSource::String(anonymized)
+    }
+
+    /// The first 64 bits of the HMAC-SHA256 of `value`, as hex
+    fn hash(&self, value: &str) -> String {
+        let tag = hmac::sign(&self.key, value.as_bytes());
+        tag.as_ref()[..8]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use http::StatusCode;
+    use lychee_lib::ErrorKind;
+
+    use super::*;
+
+    fn response(uri: &str, status: Status) -> ResponseBody {
+        ResponseBody {
+            uri: Uri::try_from(uri).unwrap(),
+            status,
+            span: None,
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_hash() {
+        let salted = Anonymizer::new(Some("salt"));
+        assert_eq!(salted.hash("a").len(), 16);
+        assert_eq!(salted.hash("a"), Anonymizer::new(Some("salt")).hash("a"));
+        assert_ne!(salted.hash("a"), Anonymizer::new(Some("pepper")).hash("a"));
+        assert_ne!(salted.hash("a"), salted.hash("b"));
+        // Without a salt, the key is random
+        assert_ne!(
+            Anonymizer::new(None).hash("a"),
+            Anonymizer::new(None).hash("a")
+        );
+    }
+
+    #[test]
+    fn test_anonymize() {
+        let source = InputSource::FsPath(PathBuf::from("internal/docs/README.md"));
+        let mut stats = ResponseStats {
+            total: 3,
+            errors: 2,
+            successful: 1,
+            ..ResponseStats::default()
+        };
+        stats.error_map.insert(
+            source.clone(),
+            HashSet::from([
+                response(
+                    "https://intranet.example.com/secret-project",
+                    Status::Ok(StatusCode::NOT_FOUND),
+                ),
+                response(
+                    "https://intranet.example.com/other",
+                    Status::Error(ErrorKind::InvalidStatusCode(1000)),
+                ),
+            ]),
+        );
+        stats.success_map.insert(
+            InputSource::Stdin,
+            HashSet::from([response(
+                "mailto:user@example.com",
+                Status::Ok(StatusCode::OK),
+            )]),
+        );
+
+        let mut anonymizer = Anonymizer::new(None);
+        let stats = anonymizer.anonymize(stats);
+        assert_eq!((stats.total, stats.errors, stats.successful), (3, 2, 1));
+
+        let (source, errors) = stats.error_map.iter().next().unwrap();
+        assert!(source.to_string().starts_with("source-"));
+        let hosts: HashSet<_> = errors.iter().map(|body| body.uri.domain()).collect();
+        assert_eq!(hosts.len(), 1);
+        assert!(errors
+            .iter()
+            .any(|body| body.status == Status::Ok(StatusCode::NOT_FOUND)));
+
+        let success = &stats.success_map[&InputSource::Stdin];
+        let uri = &success.iter().next().unwrap().uri;
+        assert!(uri.is_mail());
+        assert!(!uri.as_str().contains("user"));
+
+        let text = anonymizer.scrub(
+            "internal/docs/README.md: error sending request for url (https://intranet.example.com/other)",
+        );
+        assert!(!text.contains("internal"));
+        assert!(!text.contains("intranet"));
+    }
+}
@@ -1,12 +1,18 @@
+use anyhow::anyhow;
 use reqwest::{Error, Url};
 use serde::{Deserialize, Serialize};
-use std::{fmt::Display, time::Duration};
+use std::{fmt::Display, str::FromStr, time::Duration};
 use strum::{Display, EnumIter, EnumString, VariantNames};
 
-use crate::color::{color, GREEN, PINK};
+use crate::color::{color, GREEN, PINK, YELLOW};
 
 mod wayback;
 
+/// Maximum number of days between the date given with `--as-of` and the
+/// closest snapshot. Snapshots which are further away don't tell whether the
+/// link worked at that date.
+const MAX_SNAPSHOT_DISTANCE_DAYS: i64 = 365;
+
 #[derive(Debug, Serialize, Eq, Hash, PartialEq)]
 pub(crate) struct Suggestion {
     pub(crate) original: Url,
@@ -22,6 +28,144 @@ impl Display for Suggestion {
     }
 }
 
+/// A date in the past (`YYYY-MM-DD`) at which broken links are looked up in
+/// the web archive (`--as-of`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub(crate) struct ArchiveDate {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl ArchiveDate {
+    /// The date in the timestamp format of the Wayback Machine, e.g. `20230101`
+    fn timestamp(self) -> String {
+        format!("{:04}{:02}{:02}", self.year, self.month, self.day)
+    }
+
+    /// The date of a Wayback Machine timestamp, e.g. `20230101120000`
+    fn from_timestamp(timestamp: &str) -> Option<Self> {
+        let date = timestamp.get(..8).filter(|date| date.is_ascii())?;
+        format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..])
+            .parse()
+            .ok()
+    }
+
+    /// Number of days since 1970-01-01, see
+    /// <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>
+    fn days_since_epoch(self) -> i64 {
+        let month = i64::from(self.month);
+        let year = i64::from(self.year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year =
+            (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// Number of days between this date and `other`
+    fn distance(self, other: Self) -> i64 {
+        (self.days_since_epoch() - other.days_since_epoch()).abs()
+    }
+}
+
+/// Number of days of `month` in `year`
+const fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl FromStr for ArchiveDate {
+    type Err = anyhow::Error;
+
+    fn from_str(date: &str) -> Result<Self, Self::Err> {
+        let invalid = || anyhow!("Invalid date `{date}`, expected `YYYY-MM-DD`");
+        let [year, month, day] = date.split('-').collect::<Vec<_>>()[..] else {
+            return Err(invalid());
+        };
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return Err(invalid());
+        }
+        let date = Self {
+            year: year.parse().map_err(|_| invalid())?,
+            month: month.parse().map_err(|_| invalid())?,
+            day: day.parse().map_err(|_| invalid())?,
+        };
+        if !(1..=12).contains(&date.month)
+            || !(1..=days_in_month(date.year, date.month)).contains(&date.day)
+        {
+            return Err(invalid());
+        }
+        Ok(date)
+    }
+}
+
+impl TryFrom<String> for ArchiveDate {
+    type Error = anyhow::Error;
+
+    fn try_from(date: String) -> Result<Self, Self::Error> {
+        date.parse()
+    }
+}
+
+impl Display for ArchiveDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Whether a broken link worked at the date given with `--as-of`, according
+/// to the snapshot of the web archive which is closest to that date
+#[derive(Debug, Serialize, Eq, Hash, PartialEq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub(crate) enum HistoricalState {
+    /// The snapshot was archived successfully, so the link rotted since then
+    AliveThen { snapshot: Url, timestamp: String },
+    /// The snapshot has an error status, so the link may have always been broken
+    BrokenThen { snapshot: Url, timestamp: String },
+    /// The link wasn't archived within a year of the date
+    NeverArchived,
+}
+
+/// The [`HistoricalState`] of a broken link
+#[derive(Debug, Serialize, Eq, Hash, PartialEq)]
+pub(crate) struct HistoricalStatus {
+    pub(crate) url: Url,
+    #[serde(flatten)]
+    pub(crate) state: HistoricalState,
+}
+
+impl Display for HistoricalStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        color!(f, PINK, "{}", self.url)?;
+        match &self.state {
+            HistoricalState::AliveThen {
+                snapshot,
+                timestamp,
+            } => {
+                write!(f, " was alive then (snapshot of {timestamp}: ")?;
+                color!(f, GREEN, "{}", snapshot)?;
+                write!(f, ")")
+            }
+            HistoricalState::BrokenThen {
+                snapshot,
+                timestamp,
+            } => {
+                write!(f, " was already broken then (snapshot of {timestamp}: ")?;
+                color!(f, YELLOW, "{}", snapshot)?;
+                write!(f, ")")
+            }
+            HistoricalState::NeverArchived => write!(f, " was not archived around that date"),
+        }
+    }
+}
+
 #[non_exhaustive]
 #[derive(Debug, Deserialize, Default, Clone, Display, EnumIter, EnumString, VariantNames)]
 pub(crate) enum Archive {
@@ -43,4 +187,83 @@ impl Archive {
 
         function(original, timeout).await
     }
+
+    /// Look up whether `original` worked at `date`
+    pub(crate) async fn get_state_at(
+        &self,
+        original: &Url,
+        date: ArchiveDate,
+        timeout: Duration,
+    ) -> Result<HistoricalState, Error> {
+        let snapshot = match self {
+            Archive::WaybackMachine => {
+                wayback::get_wayback_snapshot(original, Some(&date.timestamp()), timeout).await?
+            }
+        };
+
+        // The closest snapshot may be years away from the date
+        let snapshot = snapshot.filter(|closest| {
+            ArchiveDate::from_timestamp(&closest.timestamp)
+                .is_some_and(|taken| taken.distance(date) <= MAX_SNAPSHOT_DISTANCE_DAYS)
+        });
+
+        Ok(match snapshot {
+            Some(closest) if closest.available && closest.status.is_success() => {
+                HistoricalState::AliveThen {
+                    snapshot: closest.url,
+                    timestamp: closest.timestamp,
+                }
+            }
+            Some(closest) => HistoricalState::BrokenThen {
+                snapshot: closest.url,
+                timestamp: closest.timestamp,
+            },
+            None => HistoricalState::NeverArchived,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_date() {
+        let date: ArchiveDate = "2023-01-31".parse().unwrap();
+        assert_eq!(date.timestamp(), "20230131");
+        assert_eq!(date.to_string(), "2023-01-31");
+
+        for invalid in [
+            "2023-1-31",
+            "2023-13-01",
+            "2023-01-00",
+            "2023-02-29",
+            "2023-04-31",
+            "01-31-2023",
+            "yesterday",
+        ] {
+            assert!(invalid.parse::<ArchiveDate>().is_err(), "{invalid}");
+        }
+        assert!("2024-02-29".parse::<ArchiveDate>().is_ok());
+    }
+
+    #[test]
+    fn test_snapshot_distance() {
+        let date: ArchiveDate = "2023-01-31".parse().unwrap();
+        assert_eq!(date.days_since_epoch(), 19_388);
+
+        for (timestamp, distance) in [
+            ("20230131000000", Some(0)),
+            ("20230301120000", Some(29)),
+            ("20220131", Some(365)),
+            ("20200229", Some(1067)),
+            ("2023", None),
+        ] {
+            assert_eq!(
+                ArchiveDate::from_timestamp(timestamp).map(|taken| taken.distance(date)),
+                distance,
+                "{timestamp}"
+            );
+        }
+    }
 }
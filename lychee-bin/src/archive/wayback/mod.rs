@@ -10,8 +10,26 @@ static WAYBACK_URL: Lazy<Url> =
     Lazy::new(|| Url::parse("https://archive.org/wayback/available").unwrap());
 
 pub(crate) async fn get_wayback_link(url: &Url, timeout: Duration) -> Result<Option<Url>, Error> {
+    Ok(get_wayback_snapshot(url, None, timeout)
+        .await?
+        .map(|closest| closest.url))
+}
+
+/// Get the snapshot of `url` which is closest to `timestamp` (`YYYYMMDD`),
+/// or the most recent snapshot if no timestamp is given
+pub(crate) async fn get_wayback_snapshot(
+    url: &Url,
+    timestamp: Option<&str>,
+    timeout: Duration,
+) -> Result<Option<Closest>, Error> {
     let mut archive_url: Url = WAYBACK_URL.clone();
-    archive_url.set_query(Some(&format!("url={url}")));
+    {
+        let mut query = archive_url.query_pairs_mut();
+        query.append_pair("url", url.as_str());
+        if let Some(timestamp) = timestamp {
+            query.append_pair("timestamp", timestamp);
+        }
+    }
 
     let response = Client::builder()
         .timeout(timeout)
@@ -22,10 +40,7 @@ pub(crate) async fn get_wayback_link(url: &Url, timeout: Duration) -> Result<Opt
         .json::<InternetArchiveResponse>()
         .await?;
 
-    Ok(response
-        .archived_snapshots
-        .closest
-        .map(|closest| closest.url))
+    Ok(response.archived_snapshots.closest)
 }
 
 #[derive(Debug, Deserialize, Eq, PartialEq)]
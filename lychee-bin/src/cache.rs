@@ -1,15 +1,89 @@
+use crate::formatters::duration;
+use crate::options::{LYCHEE_ANCHOR_CACHE_FILE, LYCHEE_CACHE_FILE};
+use crate::redact;
 use crate::time::{self, timestamp, Timestamp};
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use dashmap::DashMap;
-use lychee_lib::{CacheStatus, Status, Uri};
+use log::{info, warn};
+use lychee_lib::{CacheStatus, ErrorKind, Status, Uri};
+use reqwest::StatusCode;
+use reqwest::Url;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use std::{fs, io};
+
+/// Timeout for downloading and uploading a shared cache
+const HTTP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Category of a failed request, which determines how long the failure is
+/// cached (see [`MaxAge`])
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ErrorCategory {
+    /// The host name could not be resolved
+    Dns,
+    /// The request timed out
+    Timeout,
+    /// The server responded with a client error (`4xx`)
+    #[serde(rename = "4xx")]
+    ClientError,
+    /// The server responded with a server error (`5xx`)
+    #[serde(rename = "5xx")]
+    ServerError,
+}
+
+impl ErrorCategory {
+    /// The category of a failed request, if it is one of the categories
+    pub(crate) fn from_status(status: &Status) -> Option<Self> {
+        match status {
+            Status::Timeout(_) => Some(Self::Timeout),
+            Status::Error(ErrorKind::NetworkRequest(e)) if e.is_timeout() => Some(Self::Timeout),
+            Status::Error(ErrorKind::NetworkRequest(e)) if is_dns_error(e) => Some(Self::Dns),
+            _ => match CacheStatus::from(status) {
+                CacheStatus::Error(Some(400..=499)) => Some(Self::ClientError),
+                CacheStatus::Error(Some(500..=599)) => Some(Self::ServerError),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Returns `true` if the request failed because the host name could not be
+/// resolved. The resolver error is only exposed as a message further down
+/// the chain of error sources.
+fn is_dns_error(e: &(dyn Error + 'static)) -> bool {
+    let mut source = Some(e);
+    while let Some(e) = source {
+        if e.to_string().starts_with("dns error") {
+            return true;
+        }
+        source = e.source();
+    }
+    false
+}
 
 /// Describes a response status that can be serialized to disk
 #[derive(Serialize, Deserialize)]
 pub(crate) struct CacheValue {
     pub(crate) status: CacheStatus,
     pub(crate) timestamp: Timestamp,
+    /// Missing in cache files of older versions
+    pub(crate) category: Option<ErrorCategory>,
+    /// Details of a failure, e.g. the error message.
+    /// Missing in cache files of older versions
+    pub(crate) details: Option<String>,
+}
+
+impl CacheValue {
+    /// The status of the cached response, interpreted with the `accepted`
+    /// status codes of the current run
+    pub(crate) fn status(&self, accepted: &HashSet<u16>) -> Status {
+        Status::from_cache_status(self.status, accepted).with_cached_details(self.details.clone())
+    }
 }
 
 impl From<&Status> for CacheValue {
@@ -18,7 +92,51 @@ impl From<&Status> for CacheValue {
         CacheValue {
             status: s.into(),
             timestamp,
+            category: ErrorCategory::from_status(s),
+            details: if s.is_error() { s.details() } else { None },
+        }
+    }
+}
+
+/// Maximum age of cache entries, which can be set per [`ErrorCategory`]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MaxAge {
+    default: Duration,
+    categories: HashMap<ErrorCategory, Duration>,
+}
+
+impl MaxAge {
+    /// Use `default` for all entries without a more specific max age
+    pub(crate) fn new(default: Duration) -> Self {
+        Self {
+            default,
+            categories: HashMap::new(),
+        }
+    }
+
+    /// Set the max age of entries of the given `category`, if any
+    #[must_use]
+    pub(crate) fn category(mut self, category: ErrorCategory, max_age: Option<Duration>) -> Self {
+        if let Some(max_age) = max_age {
+            self.categories.insert(category, max_age);
         }
+        self
+    }
+
+    /// The max age of entries of the given `category`
+    pub(crate) fn get(&self, category: Option<ErrorCategory>) -> Duration {
+        category
+            .and_then(|category| self.categories.get(&category))
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// The longest max age of any entry
+    pub(crate) fn longest(&self) -> Duration {
+        self.categories
+            .values()
+            .copied()
+            .fold(self.default, Duration::max)
     }
 }
 
@@ -32,36 +150,388 @@ pub(crate) trait StoreExt {
     /// Store the cache under the given path. Update access timestamps
     fn store<T: AsRef<Path>>(&self, path: T) -> Result<()>;
 
-    /// Load cache from path. Discard entries older than `max_age_secs`
-    fn load<T: AsRef<Path>>(path: T, max_age_secs: u64) -> Result<Cache>;
+    /// Load cache from path. Discard entries older than their `max_age`
+    fn load<T: AsRef<Path>>(path: T, max_age: &MaxAge) -> Result<Cache>;
 }
 
 impl StoreExt for Cache {
     fn store<T: AsRef<Path>>(&self, path: T) -> Result<()> {
-        let mut wtr = csv::WriterBuilder::new()
-            .has_headers(false)
-            .from_path(path)?;
-        for result in self {
-            wtr.serialize((result.key(), result.value()))?;
+        write_csv(self, fs::File::create(path)?)
+    }
+
+    fn load<T: AsRef<Path>>(path: T, max_age: &MaxAge) -> Result<Cache> {
+        read_csv(fs::File::open(path)?, max_age)
+    }
+}
+
+fn write_csv(cache: &Cache, writer: impl io::Write) -> Result<()> {
+    let mut wtr = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(writer);
+    let redactor = redact::get();
+    for result in cache {
+        // Entries of previous runs (or versions) may still contain secrets
+        let value = result.value();
+        let value = CacheValue {
+            details: value
+                .details
+                .as_deref()
+                .map(|details| redactor.redact(details).into_owned()),
+            ..*value
+        };
+        wtr.serialize((redactor.redact_uri(result.key()), value))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+fn read_csv(reader: impl io::Read, max_age: &MaxAge) -> Result<Cache> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(reader);
+
+    let map = DashMap::new();
+    let current_ts = timestamp();
+    for result in rdr.deserialize() {
+        let (uri, value): (Uri, CacheValue) = result?;
+        // Discard entries older than their max age.
+        // This allows gradually updating the cache over multiple runs.
+        if current_ts - value.timestamp < max_age.get(value.category).as_secs() {
+            map.insert(uri, value);
+        }
+    }
+    Ok(map)
+}
+
+/// Where the cache is kept between runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum CacheBackend {
+    /// A local file, `.lycheecache` by default
+    File(PathBuf),
+    /// A cache file shared between runners (e.g. of different CI jobs), which
+    /// is downloaded with `GET` and uploaded with `PUT`
+    Http(Url),
+}
+
+impl Default for CacheBackend {
+    fn default() -> Self {
+        Self::File(PathBuf::from(LYCHEE_CACHE_FILE))
+    }
+}
+
+impl FromStr for CacheBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(backend: &str) -> Result<Self> {
+        let Ok(url) = Url::parse(backend) else {
+            return Ok(Self::File(PathBuf::from(backend)));
+        };
+        match url.scheme() {
+            "http" | "https" => Ok(Self::Http(url)),
+            "file" => url
+                .to_file_path()
+                .map(Self::File)
+                .map_err(|()| anyhow!("Invalid cache file URL `{backend}`")),
+            // Windows paths like `C:\cache` parse as URLs with a single letter scheme
+            scheme if scheme.len() == 1 => Ok(Self::File(PathBuf::from(backend))),
+            scheme => Err(anyhow!(
+                "Unsupported cache backend `{scheme}`, use a file path or an HTTP(S) URL"
+            )),
+        }
+    }
+}
+
+impl CacheBackend {
+    /// Path of the anchor cache, which is kept next to a cache file and in
+    /// the current directory for a shared cache
+    pub(crate) fn anchor_cache_path(&self) -> PathBuf {
+        match self {
+            Self::File(path) => path.with_file_name(LYCHEE_ANCHOR_CACHE_FILE),
+            Self::Http(_) => PathBuf::from(LYCHEE_ANCHOR_CACHE_FILE),
+        }
+    }
+
+    /// Load the cache, if there is one. Discard entries older than their
+    /// `max_age`
+    pub(crate) async fn load(&self, max_age: &MaxAge) -> Result<Option<Cache>> {
+        match self {
+            Self::File(path) => {
+                // Discard entire cache if it hasn't been updated since the
+                // longest max age. This is an optimization, which avoids
+                // iterating over the file and checking the age of each entry.
+                let Ok(metadata) = fs::metadata(path) else {
+                    // No cache found; silently start with empty cache
+                    return Ok(None);
+                };
+                let Some(elapsed) = metadata.modified().ok().and_then(|m| m.elapsed().ok()) else {
+                    return Ok(None);
+                };
+                if elapsed > max_age.longest() {
+                    warn!(
+                        "Cache is too old (age: {}, max age: {}). Discarding and recreating.",
+                        duration::Duration::from_secs(elapsed.as_secs()),
+                        duration::Duration::from_secs(max_age.longest().as_secs())
+                    );
+                    return Ok(None);
+                }
+                info!(
+                    "Cache is recent (age: {}, max age: {}). Using.",
+                    duration::Duration::from_secs(elapsed.as_secs()),
+                    duration::Duration::from_secs(max_age.longest().as_secs())
+                );
+                Cache::load(path, max_age).map(Some)
+            }
+            Self::Http(url) => {
+                let cache = download(&http_client()?, url, max_age).await?;
+                if let Some(cache) = &cache {
+                    info!("Using shared cache with {} entries", cache.len());
+                }
+                Ok(cache)
+            }
         }
-        Ok(())
-    }
-
-    fn load<T: AsRef<Path>>(path: T, max_age_secs: u64) -> Result<Cache> {
-        let mut rdr = csv::ReaderBuilder::new()
-            .has_headers(false)
-            .from_path(path)?;
-
-        let map = DashMap::new();
-        let current_ts = timestamp();
-        for result in rdr.deserialize() {
-            let (uri, value): (Uri, CacheValue) = result?;
-            // Discard entries older than `max_age_secs`.
-            // This allows gradually updating the cache over multiple runs.
-            if current_ts - value.timestamp < max_age_secs {
-                map.insert(uri, value);
+    }
+
+    /// Store the cache.
+    ///
+    /// A shared cache is merged with the entries which other runners uploaded
+    /// in the meantime, keeping the most recent entry of each URI.
+    pub(crate) async fn store(&self, cache: &Cache, max_age: &MaxAge) -> Result<()> {
+        match self {
+            Self::File(path) => cache.store(path),
+            Self::Http(url) => {
+                let client = http_client()?;
+                for (uri, value) in download(&client, url, max_age).await?.unwrap_or_default() {
+                    let outdated = cache
+                        .get(&uri)
+                        .map_or(true, |current| current.timestamp < value.timestamp);
+                    if outdated {
+                        cache.insert(uri, value);
+                    }
+                }
+                let mut body = Vec::new();
+                write_csv(cache, &mut body)?;
+                client
+                    .put(url.clone())
+                    .body(body)
+                    .send()
+                    .await
+                    .and_then(reqwest::Response::error_for_status)
+                    .with_context(|| format!("Cannot upload cache to `{url}`"))?;
+                Ok(())
             }
         }
-        Ok(map)
+    }
+}
+
+fn http_client() -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder().timeout(HTTP_TIMEOUT).build()?)
+}
+
+/// Download a shared cache, or `None` if it doesn't exist yet
+async fn download(client: &reqwest::Client, url: &Url, max_age: &MaxAge) -> Result<Option<Cache>> {
+    let response = client
+        .get(url.clone())
+        .send()
+        .await
+        .with_context(|| format!("Cannot download cache from `{url}`"))?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let body = response
+        .error_for_status()
+        .with_context(|| format!("Cannot download cache from `{url}`"))?
+        .bytes()
+        .await?;
+    read_csv(&*body, max_age).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    #[test]
+    fn test_error_category() {
+        let category = |status| ErrorCategory::from_status(&status);
+        assert_eq!(
+            category(Status::Timeout(None)),
+            Some(ErrorCategory::Timeout)
+        );
+        assert_eq!(
+            category(Status::Cached(CacheStatus::Error(Some(404)), None)),
+            Some(ErrorCategory::ClientError)
+        );
+        assert_eq!(
+            category(Status::Cached(CacheStatus::Error(Some(502)), None)),
+            Some(ErrorCategory::ServerError)
+        );
+        assert_eq!(
+            category(Status::Cached(CacheStatus::Error(None), None)),
+            None
+        );
+        assert_eq!(category(Status::Ok(StatusCode::OK)), None);
+        assert_eq!(category(Status::Excluded), None);
+    }
+
+    #[test]
+    fn test_load_with_max_age_per_category() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache");
+        let hour_ago = timestamp() - 3600;
+        fs::write(
+            &path,
+            format!(
+                "https://example.com/dns,,{hour_ago},dns\n\
+                 https://example.com/missing,404,{hour_ago},4xx\n\
+                 https://example.com/ok,200,{hour_ago},\n"
+            ),
+        )
+        .unwrap();
+
+        let day = Duration::from_secs(24 * 60 * 60);
+        let max_age = MaxAge::new(day)
+            .category(ErrorCategory::Dns, Some(Duration::from_secs(60)))
+            .category(ErrorCategory::ClientError, None);
+        assert_eq!(max_age.longest(), day);
+
+        let cache = Cache::load(&path, &max_age).unwrap();
+        let mut uris: Vec<_> = cache.iter().map(|entry| entry.key().to_string()).collect();
+        uris.sort();
+        assert_eq!(
+            uris,
+            ["https://example.com/missing", "https://example.com/ok"]
+        );
+    }
+
+    #[test]
+    fn test_load_old_cache_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache");
+        fs::write(&path, format!("https://example.com/,200,{}\n", timestamp())).unwrap();
+
+        let cache = Cache::load(&path, &MaxAge::new(Duration::from_secs(60))).unwrap();
+        let value = cache
+            .get(&Uri::try_from("https://example.com/").unwrap())
+            .unwrap();
+        assert_eq!(value.status, CacheStatus::Ok(200));
+        assert_eq!(value.category, None);
+        assert_eq!(value.details, None);
+    }
+
+    #[test]
+    fn test_store_and_load_details() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache");
+        let uri = Uri::try_from("https://example.com/missing").unwrap();
+        let cache = Cache::new();
+        cache.insert(
+            uri.clone(),
+            CacheValue {
+                status: CacheStatus::Error(Some(404)),
+                timestamp: timestamp(),
+                category: Some(ErrorCategory::ClientError),
+                details: Some("Not Found, see https://example.com/help".to_string()),
+            },
+        );
+        cache.store(&path).unwrap();
+
+        let cache = Cache::load(&path, &MaxAge::new(Duration::from_secs(60))).unwrap();
+        let status = cache.get(&uri).unwrap().status(&HashSet::new());
+        assert_eq!(
+            status,
+            Status::Cached(
+                CacheStatus::Error(Some(404)),
+                Some("Not Found, see https://example.com/help".to_string())
+            )
+        );
+        assert!(status.is_error());
+
+        // The details of an accepted status are dropped
+        let accepted = HashSet::from([404]);
+        let status = cache.get(&uri).unwrap().status(&accepted);
+        assert_eq!(status, Status::Cached(CacheStatus::Ok(404), None));
+    }
+
+    #[test]
+    fn test_cache_backend() {
+        assert_eq!(
+            "ci/.lycheecache".parse::<CacheBackend>().unwrap(),
+            CacheBackend::File(PathBuf::from("ci/.lycheecache"))
+        );
+        assert_eq!(
+            "https://cache.example.com/lychee.csv"
+                .parse::<CacheBackend>()
+                .unwrap(),
+            CacheBackend::Http(Url::parse("https://cache.example.com/lychee.csv").unwrap())
+        );
+        assert!("redis://localhost:6379".parse::<CacheBackend>().is_err());
+        assert_eq!(
+            CacheBackend::File(PathBuf::from("ci/.lycheecache")).anchor_cache_path(),
+            PathBuf::from("ci/.lycheeanchors")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_backend() {
+        let server = MockServer::start().await;
+        let backend =
+            CacheBackend::Http(Url::parse(&format!("{}/cache.csv", server.uri())).unwrap());
+        let max_age = MaxAge::new(Duration::from_secs(3600));
+        let now = timestamp();
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        assert!(backend.load(&max_age).await.unwrap().is_none());
+
+        // Another runner stored the cache in the meantime
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+                "https://example.com/a,404,{now},4xx\nhttps://example.com/b,404,{},4xx\n",
+                now - 60
+            )))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let a = Uri::try_from("https://example.com/a").unwrap();
+        let b = Uri::try_from("https://example.com/b").unwrap();
+        let cache = Cache::new();
+        for uri in [&a, &b] {
+            cache.insert(
+                uri.clone(),
+                CacheValue {
+                    status: CacheStatus::Ok(200),
+                    timestamp: now - 30,
+                    category: None,
+                    details: None,
+                },
+            );
+        }
+        backend.store(&cache, &max_age).await.unwrap();
+
+        // The most recent entries are kept
+        assert_eq!(cache.get(&a).unwrap().status, CacheStatus::Error(Some(404)));
+        assert_eq!(cache.get(&b).unwrap().status, CacheStatus::Ok(200));
+
+        let requests = server.received_requests().await.unwrap();
+        let upload = requests
+            .iter()
+            .find(|request| request.method == http::Method::PUT)
+            .unwrap();
+        let uploaded = read_csv(&*upload.body, &max_age).unwrap();
+        assert_eq!(uploaded.len(), 2);
+        assert_eq!(
+            uploaded.get(&a).unwrap().status,
+            CacheStatus::Error(Some(404))
+        );
     }
 }
@@ -1,23 +1,99 @@
-use crate::options::Config;
-use crate::parse::{parse_duration_secs, parse_headers, parse_remaps};
-use anyhow::{Context, Result};
+use crate::options::{Config, FragmentStyle};
+use crate::parse::{
+    parse_dns_overrides, parse_duration_secs, parse_headers, parse_host_header_overrides,
+    parse_host_headers, parse_remaps,
+};
+use anyhow::{bail, Context, Result};
+use http::header::{HeaderValue, CACHE_CONTROL, HOST, PRAGMA};
 use http::StatusCode;
-use lychee_lib::{Client, ClientBuilder};
-use regex::RegexSet;
+use lychee_lib::{
+    path_mapping::PathMappings, proxy_pac::ProxyPac, ratelimit::HostConfig, AnchorCache, Base,
+    CheckObserver, Client, ClientBuilder, ContentAssertion, HttpVersion, Input, InputSource,
+    LycheeIgnore, OAuth2Config, PlatformConfig, VersionedDocs, DEFAULT_DOCS_VERSION,
+};
+use regex::{Regex, RegexSet};
+use reqwest::Response;
 use reqwest_cookie_store::CookieStoreMutex;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::{collections::HashSet, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    time::Instant,
+};
+use url::Url;
+
+/// Hosts of the checked site: `--internal-host` and the host of a remote
+/// `--base`
+fn internal_hosts(cfg: &Config) -> Vec<String> {
+    let mut hosts = cfg.internal_host.clone();
+    if let Some(Base::Remote(base)) = &cfg.base {
+        hosts.extend(base.host_str().map(ToString::to_string));
+    }
+    hosts
+}
+
+/// Directories of the local inputs, in which the `.lycheeignore` files
+/// apply. Globs are searched below their directory without wildcards.
+pub(crate) fn input_dirs(inputs: &[Input]) -> Vec<PathBuf> {
+    inputs
+        .iter()
+        .filter_map(|input| match &input.source {
+            InputSource::FsPath(path) if path.is_dir() => Some(path.clone()),
+            InputSource::FsPath(path) => path.parent().map(Path::to_path_buf),
+            InputSource::FsGlob { pattern, .. } => Some(
+                Path::new(pattern)
+                    .components()
+                    .take_while(|c| {
+                        !c.as_os_str()
+                            .to_string_lossy()
+                            .contains(['*', '?', '[', '{'])
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        })
+        .map(|dir: PathBuf| {
+            if dir.as_os_str().is_empty() {
+                PathBuf::from(".")
+            } else {
+                dir
+            }
+        })
+        .collect()
+}
 
 /// Creates a client according to the command-line config
-pub(crate) fn create(cfg: &Config, cookie_jar: Option<&Arc<CookieStoreMutex>>) -> Result<Client> {
-    let headers = parse_headers(&cfg.header)?;
+pub(crate) fn create(
+    cfg: &Config,
+    cookie_jar: Option<&Arc<CookieStoreMutex>>,
+    observer: Option<Arc<dyn CheckObserver>>,
+    proxy_pac: Option<ProxyPac>,
+    anchor_cache: Option<AnchorCache>,
+    input_dirs: &[PathBuf],
+) -> Result<Client> {
+    let mut headers = parse_headers(&cfg.header)?;
+    if cfg.bypass_caches {
+        headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+        headers.insert(PRAGMA, HeaderValue::from_static("no-cache"));
+    }
     let timeout = parse_duration_secs(cfg.timeout);
     let retry_wait_time = parse_duration_secs(cfg.retry_wait_time);
+    let mail_dns_timeout = parse_duration_secs(cfg.mail_dns_timeout);
+    let internal_timeout = cfg.internal_timeout.map(parse_duration_secs);
+    // The runtime budget starts with the creation of the client
+    let deadline = cfg
+        .max_runtime
+        .map(|max_runtime| Instant::now() + max_runtime);
     let method: reqwest::Method = reqwest::Method::from_str(&cfg.method.to_uppercase())?;
 
     let remaps = parse_remaps(&cfg.remap)?;
+    let path_mappings = path_mappings(cfg)?;
+    let dns_overrides = parse_dns_overrides(&cfg.resolve)?;
     let includes = RegexSet::new(&cfg.include)?;
     let excludes = RegexSet::new(&cfg.exclude)?;
+    let lycheeignore = LycheeIgnore::discover(Path::new("."), input_dirs)
+        .context("Failed to load `.lycheeignore` files")?;
 
     // Offline mode overrides the scheme
     let schemes = if cfg.offline {
@@ -34,6 +110,18 @@ pub(crate) fn create(cfg: &Config, cookie_jar: Option<&Arc<CookieStoreMutex>>) -
         .map(|value| StatusCode::from_u16(*value))
         .collect::<Result<HashSet<_>, _>>()?;
 
+    let host_configs = cfg
+        .hosts
+        .iter()
+        .map(|(host, limits)| (host.to_lowercase(), HostConfig::from(*limits)))
+        .collect::<HashMap<_, _>>();
+    let mut host_headers = parse_host_headers(&cfg.header_host)?;
+    for (host, value) in parse_host_header_overrides(&cfg.host_header)? {
+        host_headers.entry(host).or_default().insert(HOST, value);
+    }
+
+    let oauth2 = oauth2_config(cfg)?;
+
     // `exclude_mail` will be removed in 1.0. Until then, we need to support it.
     // Therefore, we need to check if both `include_mail` and `exclude_mail` are set to `true`
     // and return an error if that's the case.
@@ -53,32 +141,228 @@ pub(crate) fn create(cfg: &Config, cookie_jar: Option<&Arc<CookieStoreMutex>>) -
         cfg.include_mail
     };
 
-    ClientBuilder::builder()
+    let builder = ClientBuilder::builder()
         .remaps(remaps)
         .base(cfg.base.clone())
         .includes(includes)
         .excludes(excludes)
+        .lycheeignore(lycheeignore)
+        .versioned_docs(cfg.skip_versioned_docs.then(|| {
+            VersionedDocs::new(cfg.docs_version.as_deref().unwrap_or(DEFAULT_DOCS_VERSION))
+        }))
         .exclude_all_private(cfg.exclude_all_private)
         .exclude_private_ips(cfg.exclude_private)
         .exclude_link_local_ips(cfg.exclude_link_local)
         .exclude_loopback_ips(cfg.exclude_loopback)
         .include_mail(include_mail)
+        .include_tel(cfg.include_tel)
+        .tel_patterns(tel_patterns(cfg)?)
+        .mail_check_level(cfg.mail_check_level)
+        .mail_dns_timeout(mail_dns_timeout)
+        .mail_dns_max_retries(cfg.mail_dns_max_retries)
         .max_redirects(cfg.max_redirects)
+        .track_redirects(
+            cfg.verbose_redirects
+                || cfg.fail_on_redirects
+                || cfg.hint_permanent_redirects.is_some(),
+        )
         .user_agent(cfg.user_agent.clone())
         .allow_insecure(cfg.insecure)
+        .min_tls_version(cfg.min_tls.map(Into::into))
+        .http_version(if cfg.http2_prior_knowledge {
+            HttpVersion::Http2PriorKnowledge
+        } else {
+            HttpVersion::Auto
+        })
         .custom_headers(headers)
         .method(method)
         .timeout(timeout)
         .retry_wait_time(retry_wait_time)
         .max_retries(cfg.max_retries)
+        .proxy_pac(proxy_pac)
+        .internal_hosts(internal_hosts(cfg))
+        .internal_max_concurrency(cfg.internal_max_concurrency.unwrap_or(cfg.max_concurrency))
+        .external_max_concurrency(cfg.max_concurrency)
+        .internal_timeout(internal_timeout)
+        .internal_max_retries(cfg.internal_max_retries)
+        .deadline(deadline)
+        .cache_bust_param(cfg.cache_bust_param.clone())
+        .accept_languages(cfg.accept_language.clone())
         .github_token(cfg.github_token.clone())
+        .gitlab_token(cfg.gitlab_token.clone())
+        .gitlab_hosts(cfg.gitlab_host.clone())
+        .oauth2(oauth2)
+        .platforms(
+            cfg.platforms
+                .iter()
+                .map(|(host, platform)| (host.clone(), PlatformConfig::from(platform.clone())))
+                .collect::<HashMap<_, _>>(),
+        )
         .schemes(HashSet::from_iter(schemes))
+        .denied_schemes(
+            cfg.deny_scheme
+                .iter()
+                .map(|scheme| scheme.to_lowercase())
+                .collect::<HashSet<_>>(),
+        )
         .accepted(accepted)
         .require_https(cfg.require_https)
         .cookie_jar(cookie_jar.cloned())
         .include_fragments(cfg.include_fragments)
+        .check_canonical(cfg.check_canonical)
+        .render_js_hosts(cfg.render_js.clone())
+        .content_assertions(content_assertions(cfg)?)
+        .error_page_snippet(cfg.error_page_snippet)
+        .fragment_style(fragment_style(cfg)?)
+        .anchor_cache(anchor_cache)
+        .host_configs(host_configs)
+        .host_headers(host_headers)
+        .dns_overrides(dns_overrides)
         .fallback_extensions(cfg.fallback_extensions.clone())
+        .path_mappings(path_mappings)
+        .observer(observer);
+    cfg.ca_cert
+        .iter()
+        .fold(builder, |builder, path| {
+            builder.add_root_certificate(path.clone())
+        })
         .build()
         .client()
         .context("Failed to create request client")
 }
+
+/// Loads the proxy auto-config file of `--proxy-pac` from its URL or path
+pub(crate) async fn load_proxy_pac(cfg: &Config) -> Result<Option<ProxyPac>> {
+    let Some(location) = &cfg.proxy_pac else {
+        return Ok(None);
+    };
+    let script = match Url::parse(location) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {
+            let response = pac_client(cfg)?
+                .get(url)
+                .send()
+                .await
+                .and_then(Response::error_for_status);
+            match response {
+                Ok(response) => response.text().await,
+                Err(e) => Err(e),
+            }
+            .map_err(anyhow::Error::from)
+        }
+        _ => tokio::fs::read_to_string(location)
+            .await
+            .map_err(anyhow::Error::from),
+    }
+    .with_context(|| format!("Cannot load proxy auto-config from `{location}`"))?;
+    let pac = ProxyPac::parse(&script)?;
+    Ok(Some(pac))
+}
+
+/// Client to download the proxy auto-config file with the user agent and
+/// TLS options of the checks (`--insecure` and `--ca-cert`)
+fn pac_client(cfg: &Config) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .user_agent(cfg.user_agent.clone())
+        .danger_accept_invalid_certs(cfg.insecure);
+    for path in &cfg.ca_cert {
+        let certificates = std::fs::read(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|pem| Ok(reqwest::Certificate::from_pem_bundle(&pem)?))
+            .with_context(|| format!("Cannot read CA certificate `{}`", path.display()))?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    Ok(builder.build()?)
+}
+
+/// Compiles the regexes of the `[tel_patterns]` table of the config file
+fn tel_patterns(cfg: &Config) -> Result<HashMap<String, Regex>> {
+    cfg.tel_patterns
+        .iter()
+        .map(|(code, pattern)| {
+            let regex = Regex::new(pattern)
+                .with_context(|| format!("Invalid phone number pattern of `{code}`"))?;
+            Ok((code.clone(), regex))
+        })
+        .collect()
+}
+
+/// Compiles the regexes of the `[[assertions]]` entries of the config file
+fn content_assertions(cfg: &Config) -> Result<Vec<ContentAssertion>> {
+    let regex = |pattern: &str| {
+        Regex::new(pattern).with_context(|| format!("Invalid regex `{pattern}` of an assertion"))
+    };
+    cfg.assertions
+        .iter()
+        .map(|assertion| {
+            if assertion.contains.is_none() && assertion.not_contains.is_none() {
+                bail!(
+                    "Assertion of `{}` needs `contains` or `not_contains`",
+                    assertion.url
+                );
+            }
+            Ok(ContentAssertion {
+                url: regex(&assertion.url)?,
+                contains: assertion.contains.as_deref().map(regex).transpose()?,
+                not_contains: assertion.not_contains.as_deref().map(regex).transpose()?,
+            })
+        })
+        .collect()
+}
+
+/// Creates the path mappings from `--path-mapping`, relative to the root
+/// directory (`--root-dir`, a local `--base` or the current directory)
+fn path_mappings(cfg: &Config) -> Result<Option<PathMappings>> {
+    if cfg.path_mapping.is_empty() {
+        return Ok(None);
+    }
+    let root = match (&cfg.root_dir, &cfg.base) {
+        (Some(root_dir), _) => root_dir.clone(),
+        (None, Some(Base::Local(base))) => base.clone(),
+        _ => PathBuf::new(),
+    };
+    let root = std::env::current_dir()?.join(root);
+    Ok(Some(PathMappings::parse(root, &cfg.path_mapping)?))
+}
+
+/// Creates the fragment style from `--fragment-style` and `--fragment-regex`
+fn fragment_style(cfg: &Config) -> Result<lychee_lib::FragmentStyle> {
+    Ok(match cfg.fragment_style {
+        FragmentStyle::Github => lychee_lib::FragmentStyle::Github,
+        FragmentStyle::Gitlab => lychee_lib::FragmentStyle::Gitlab,
+        FragmentStyle::Kramdown => lychee_lib::FragmentStyle::Kramdown,
+        FragmentStyle::CustomRegex => {
+            let regex = cfg
+                .fragment_regex
+                .as_deref()
+                .context("`--fragment-style custom-regex` requires `--fragment-regex`")?;
+            lychee_lib::FragmentStyle::CustomRegex(
+                Regex::new(regex).context("Invalid `--fragment-regex`")?,
+            )
+        }
+    })
+}
+
+/// Creates the OAuth2 configuration from `--oauth2-token-url` and the
+/// related options, if a token URL is set
+fn oauth2_config(cfg: &Config) -> Result<Option<OAuth2Config>> {
+    let Some(token_url) = &cfg.oauth2_token_url else {
+        return Ok(None);
+    };
+    let token_url = Url::parse(token_url).context("Invalid `--oauth2-token-url`")?;
+    if token_url.scheme() != "https" {
+        bail!("`--oauth2-token-url` must be an https URL");
+    }
+    let client_id = cfg
+        .oauth2_client_id
+        .as_deref()
+        .context("`--oauth2-token-url` requires `--oauth2-client-id`")?;
+    let client_secret = cfg
+        .oauth2_client_secret
+        .clone()
+        .context("`--oauth2-token-url` requires `--oauth2-client-secret`")?;
+    Ok(Some(
+        OAuth2Config::new(token_url, client_id, client_secret).with_hosts(cfg.oauth2_host.clone()),
+    ))
+}
@@ -1,30 +1,51 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::io::{self, Write};
-use std::sync::{Arc, Mutex};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Duration;
 
-use futures::StreamExt;
-use indicatif::ProgressBar;
-use indicatif::ProgressStyle;
+use futures::{stream, StreamExt};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use reqwest::Url;
-use tokio::sync::mpsc;
+use ring::digest::{digest, SHA256};
+use tokio::sync::{mpsc, watch, OnceCell};
 use tokio_stream::wrappers::ReceiverStream;
 
-use lychee_lib::{Client, ErrorKind, Request, Response, Uri};
+use lychee_lib::lint::{DEFAULT_DISPOSABLE_MAIL_DOMAINS, DEFAULT_ROLE_MAIL_ACCOUNTS};
+use lychee_lib::{Client, ErrorKind, MailLint, PermalinkLint, Request, Response, Uri};
 use lychee_lib::{InputSource, Result};
-use lychee_lib::{ResponseBody, Status};
+use lychee_lib::{ResponseBody, SkipReason, Status};
 
-use crate::archive::{Archive, Suggestion};
+use crate::archive::{Archive, ArchiveDate, HistoricalStatus, Suggestion};
+use crate::default_branch::suggest_default_branches;
 use crate::formatters::get_response_formatter;
 use crate::formatters::response::ResponseFormatter;
+use crate::history;
+use crate::labels::Labels;
+use crate::options::{Config, ProgressMode, ReportSection};
 use crate::parse::parse_duration_secs;
+use crate::permalink::suggest_permalinks;
+use crate::progress::HostProgress;
+use crate::progress_socket::ProgressSocket;
+use crate::redact;
+use crate::stats::{LintReason, Warning};
+use crate::time::timestamp;
 use crate::verbosity::Verbosity;
-use crate::{cache::Cache, stats::ResponseStats, ExitCode};
+use crate::{
+    cache::{Cache, CacheValue, ErrorCategory},
+    stats::ResponseStats,
+    ExitCode,
+};
 
 use super::CommandParams;
 
+/// Check all links of `params.requests`.
+///
+/// Once `interrupt` turns `true`, the outstanding checks are cancelled and
+/// the links which were checked so far are reported.
 pub(crate) async fn check<S>(
     params: CommandParams<S>,
+    interrupt: watch::Receiver<bool>,
 ) -> Result<(ResponseStats, Arc<Cache>, ExitCode)>
 where
     S: futures::Stream<Item = Result<Request>>,
@@ -33,18 +54,40 @@ where
     let (send_req, recv_req) = mpsc::channel(params.cfg.max_concurrency);
     let (send_resp, recv_resp) = mpsc::channel(params.cfg.max_concurrency);
     let max_concurrency = params.cfg.max_concurrency;
+    let internal_max_concurrency = params
+        .cfg
+        .internal_max_concurrency
+        .unwrap_or(max_concurrency);
 
     // Measure check time
     let start = std::time::Instant::now();
 
-    let stats = if params.cfg.verbose.log_level() >= log::Level::Info {
+    let mut stats = if params.cfg.verbose.log_level() >= log::Level::Info {
         ResponseStats::extended()
     } else {
         ResponseStats::default()
     };
+    stats.fail_on_redirects = params.cfg.fail_on_redirects;
+    stats.permanent_redirects = params.cfg.hint_permanent_redirects;
+    stats.default_branch = params.cfg.suggest_default_branch;
+    stats.host_stats = params.cfg.report_sections.contains(&ReportSection::Hosts);
+    stats.directory_stats = params
+        .cfg
+        .report_sections
+        .contains(&ReportSection::Directories);
+    stats.record_outcomes = params.cfg.history.is_some();
+    stats.tag_max_errors = params.tag_max_errors;
     let cache_ref = params.cache.clone();
 
+    let mail_lint = mail_lint(&params.cfg);
+    let permalink_lint = params
+        .cfg
+        .lint_permalinks
+        .then(|| PermalinkLint::new(&params.cfg.gitlab_host));
+    let labels = Labels::new(&params.cfg.labels);
     let client = params.client;
+    // Reports the warm-up and request times of the hosts after the check
+    let timings_client = stats.host_stats.then(|| client.clone());
     let cache = params.cache;
     let cache_exclude_status = params.cfg.cache_exclude_status.into_set();
     let accept = params.cfg.accept.into_set();
@@ -55,15 +98,50 @@ where
         Some(init_progress_bar("Extracting links"))
     };
 
+    // `--progress per-host` shows the active hosts below the progress bar
+    let host_progress = match &pb {
+        Some(pb) if params.cfg.progress == ProgressMode::PerHost => {
+            let multi = MultiProgress::new();
+            multi.add(pb.clone());
+            Some(HostProgress::spawn(multi, client.clone()))
+        }
+        _ => None,
+    };
+
+    // `--progress-socket` sends the progress to other programs
+    let progress_socket = params
+        .cfg
+        .progress_socket
+        .as_deref()
+        .map(ProgressSocket::bind)
+        .transpose()?
+        .map(Arc::new);
+
+    let warm_up = params.cfg.warm_up.map(|connections| WarmUp {
+        client: client.clone(),
+        connections,
+        window: max_concurrency,
+    });
+
+    let sampled_out = Arc::<OnceLock<HashSet<Uri>>>::default();
+    let sample = params.cfg.sample_per_host.map(|per_host| Sample {
+        client: client.clone(),
+        per_host,
+        skipped: sampled_out.clone(),
+    });
+
     // Start receiving requests
     tokio::spawn(request_channel_task(
         recv_req,
         send_resp,
         max_concurrency,
+        internal_max_concurrency,
+        sampled_out,
         client,
         cache,
         cache_exclude_status,
         accept,
+        interrupt.clone(),
     ));
 
     let formatter = get_response_formatter(&params.cfg.mode);
@@ -72,29 +150,124 @@ where
         recv_resp,
         params.cfg.verbose,
         pb.clone(),
+        progress_socket.clone(),
         formatter,
+        mail_lint,
+        permalink_lint.clone(),
+        labels,
         stats,
     ));
 
     // Wait until all messages are sent
-    send_inputs_loop(params.requests, send_req, pb).await?;
+    send_inputs_loop(
+        params.requests,
+        send_req,
+        pb,
+        progress_socket.clone(),
+        interrupt.clone(),
+        sample,
+        warm_up,
+    )
+    .await?;
 
     // Wait until all responses are received
     let result = show_results_task.await?;
     let (pb, mut stats) = result?;
 
+    // The other references were dropped by the finished tasks
+    if let Some(socket) = progress_socket.and_then(Arc::into_inner) {
+        socket.finish().await;
+    }
+
     // Store elapsed time in stats
     stats.duration_secs = start.elapsed().as_secs();
+    if let Some(client) = timings_client {
+        stats.add_host_timings(&client.host_activity());
+    }
+
+    if let Some(host_progress) = host_progress {
+        host_progress.stop().await;
+    }
+
+    let interrupted = *interrupt.borrow();
 
     // Note that print statements may interfere with the progress bar, so this
     // must go before printing the stats
     if let Some(pb) = &pb {
-        pb.finish_with_message("Finished extracting links");
+        if interrupted {
+            pb.abandon_with_message("Interrupted");
+        } else {
+            pb.finish_with_message("Finished extracting links");
+        }
+    }
+
+    if stats.deadline_skipped > 0 {
+        log::warn!(
+            "Reached the maximum runtime, {} links were skipped",
+            stats.deadline_skipped
+        );
+    }
+
+    if interrupted {
+        // Only report the links which were checked so far
+        return Ok((stats, cache_ref, ExitCode::Interrupted));
     }
 
-    if params.cfg.suggest {
+    if let Some(path) = &params.cfg.history {
+        if let Err(e) = history::record(
+            path.clone(),
+            timestamp(),
+            std::mem::take(&mut stats.outcomes),
+        )
+        .await
+        {
+            log::warn!("Cannot record run in history: {e:#}");
+        }
+    }
+
+    // `--fix` replaces broken links with their archived versions
+    if params.cfg.suggest || params.cfg.fix {
         suggest_archived_links(
+            params.cfg.archive.clone().unwrap_or_default(),
+            &mut stats,
+            !params.cfg.no_progress,
+            max_concurrency,
+            parse_duration_secs(params.cfg.timeout),
+        )
+        .await;
+    }
+
+    // `--suggest-default-branch` looks up the default branches of GitHub
+    // repositories whose `master` branch is linked
+    if let Some(suggestion) = params.cfg.suggest_default_branch {
+        suggest_default_branches(
+            suggestion,
+            params.cfg.github_token.as_ref(),
+            &params.cfg.user_agent,
+            &mut stats,
+            parse_duration_secs(params.cfg.timeout),
+        )
+        .await;
+    }
+
+    // `--fix` pins the links flagged by `--lint-permalinks` to commits
+    if let Some(lint) = permalink_lint.filter(|_| params.cfg.suggest || params.cfg.fix) {
+        suggest_permalinks(
+            &lint,
+            params.cfg.github_token.as_ref(),
+            params.cfg.gitlab_token.as_ref(),
+            &params.cfg.user_agent,
+            &mut stats,
+            parse_duration_secs(params.cfg.timeout),
+        )
+        .await;
+    }
+
+    // `--as-of` looks up whether broken links worked at that date
+    if let Some(date) = params.cfg.as_of {
+        check_archived_history(
             params.cfg.archive.unwrap_or_default(),
+            date,
             &mut stats,
             !params.cfg.no_progress,
             max_concurrency,
@@ -103,14 +276,65 @@ where
         .await;
     }
 
-    let code = if stats.is_success() {
-        ExitCode::Success
+    let code = if !stats.is_success() {
+        if params.cfg.granular_exit_codes {
+            failure_exit_code(&stats)
+        } else {
+            ExitCode::LinkCheckFailure
+        }
+    } else if stats.deadline_skipped > 0 {
+        // Links which were never checked must not pass CI silently
+        ExitCode::Incomplete
     } else {
-        ExitCode::LinkCheckFailure
+        ExitCode::Success
     };
     Ok((stats, cache_ref, code))
 }
 
+/// The exit code of a failed run with `--granular-exit-codes`.
+///
+/// If all failed links belong to the same [`ErrorCategory`], e.g. they all
+/// timed out, its exit code is used, so that CI pipelines can decide whether
+/// to retry. Otherwise, the generic [`ExitCode::LinkCheckFailure`] is used.
+fn failure_exit_code(stats: &ResponseStats) -> ExitCode {
+    let categories: HashSet<Option<ErrorCategory>> = stats
+        .error_map
+        .values()
+        .flatten()
+        .map(|body| ErrorCategory::from_status(&body.status))
+        .collect();
+    match categories.into_iter().collect::<Vec<_>>().as_slice() {
+        [Some(category)] => ExitCode::from(*category),
+        _ => ExitCode::LinkCheckFailure,
+    }
+}
+
+/// Create the mail lint if `--lint-mail` is enabled.
+///
+/// Lists which were not configured fall back to the built-in defaults.
+fn mail_lint(cfg: &Config) -> Option<MailLint> {
+    if !cfg.lint_mail {
+        return None;
+    }
+    let disposable_domains = if cfg.lint_mail_disposable_domains.is_empty() {
+        DEFAULT_DISPOSABLE_MAIL_DOMAINS
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    } else {
+        cfg.lint_mail_disposable_domains.clone()
+    };
+    let role_accounts = if cfg.lint_mail_role_accounts.is_empty() {
+        DEFAULT_ROLE_MAIL_ACCOUNTS
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    } else {
+        cfg.lint_mail_role_accounts.clone()
+    };
+    Some(MailLint::new(disposable_domains, role_accounts))
+}
+
 async fn suggest_archived_links(
     archive: Archive,
     stats: &mut ResponseStats,
@@ -118,41 +342,113 @@ async fn suggest_archived_links(
     max_concurrency: usize,
     timeout: Duration,
 ) {
-    let failed_urls = &get_failed_urls(stats);
+    let archive = &archive;
+    let suggestions = lookup_failed_urls(
+        stats,
+        show_progress,
+        max_concurrency,
+        (
+            "Searching for alternatives",
+            "Finished searching for alternatives",
+        ),
+        |url| async move { archive.get_link(&url, timeout).await.ok().flatten() },
+    )
+    .await;
+
+    for (input, original, suggestion) in suggestions {
+        stats
+            .suggestion_map
+            .entry(input)
+            .or_default()
+            .insert(Suggestion {
+                suggestion,
+                original,
+            });
+    }
+}
+
+async fn check_archived_history(
+    archive: Archive,
+    date: ArchiveDate,
+    stats: &mut ResponseStats,
+    show_progress: bool,
+    max_concurrency: usize,
+    timeout: Duration,
+) {
+    let archive = &archive;
+    let states = lookup_failed_urls(
+        stats,
+        show_progress,
+        max_concurrency,
+        (
+            "Looking up links in the web archive",
+            "Finished looking up links in the web archive",
+        ),
+        |url| async move {
+            archive
+                .get_state_at(&url, date, timeout)
+                .await
+                .map_err(|e| log::warn!("Cannot look up {url} in the web archive: {e}"))
+                .ok()
+        },
+    )
+    .await;
+
+    for (input, url, state) in states {
+        stats
+            .history_map
+            .entry(input)
+            .or_default()
+            .insert(HistoricalStatus { url, state });
+    }
+}
+
+/// Run `lookup` concurrently for every failed URL and collect the URLs it
+/// returned a result for, along with their input.
+///
+/// `messages` are shown on the progress bar while running and once finished.
+async fn lookup_failed_urls<T, F, Fut>(
+    stats: &mut ResponseStats,
+    show_progress: bool,
+    max_concurrency: usize,
+    messages: (&'static str, &'static str),
+    lookup: F,
+) -> Vec<(InputSource, Url, T)>
+where
+    F: Fn(Url) -> Fut,
+    Fut: std::future::Future<Output = Option<T>>,
+{
+    let failed_urls = get_failed_urls(stats);
+    let (running, finished) = messages;
     let bar = if show_progress {
-        let bar = init_progress_bar("Searching for alternatives");
+        let bar = init_progress_bar(running);
         bar.set_length(failed_urls.len() as u64);
         Some(bar)
     } else {
         None
     };
 
-    let suggestions = Mutex::new(&mut stats.suggestion_map);
-
-    futures::stream::iter(failed_urls)
-        .map(|(input, url)| (input, url, archive.get_link(url, timeout)))
-        .for_each_concurrent(max_concurrency, |(input, url, future)| async {
-            if let Ok(Some(suggestion)) = future.await {
-                suggestions
-                    .lock()
-                    .unwrap()
-                    .entry(input.clone())
-                    .or_default()
-                    .insert(Suggestion {
-                        suggestion,
-                        original: url.clone(),
-                    });
-            }
-
-            if let Some(bar) = &bar {
-                bar.inc(1);
+    let results: Vec<_> = futures::stream::iter(failed_urls)
+        .map(|(input, url)| {
+            let bar = &bar;
+            let future = lookup(url.clone());
+            async move {
+                let result = future.await;
+                if let Some(bar) = bar {
+                    bar.inc(1);
+                }
+                result.map(|result| (input, url, result))
             }
         })
+        .buffer_unordered(max_concurrency)
+        .filter_map(|result| async move { result })
+        .collect()
         .await;
 
     if let Some(bar) = &bar {
-        bar.finish_with_message("Finished searching for alternatives");
+        bar.finish_with_message(finished);
     }
+    results
 }
 
 // drops the `send_req` channel on exit
@@ -162,17 +458,44 @@ async fn send_inputs_loop<S>(
     requests: S,
     send_req: mpsc::Sender<Result<Request>>,
     bar: Option<ProgressBar>,
+    progress_socket: Option<Arc<ProgressSocket>>,
+    interrupt: watch::Receiver<bool>,
+    sample: Option<Sample>,
+    warm_up: Option<WarmUp>,
 ) -> Result<()>
 where
     S: futures::Stream<Item = Result<Request>>,
 {
     tokio::pin!(requests);
-    while let Some(request) = requests.next().await {
+    let all_requests = match sample {
+        Some(sample) => sample.run(&mut requests).await?,
+        None => Vec::new(),
+    };
+    let requests = stream::iter(all_requests.into_iter().map(Ok)).chain(requests);
+    tokio::pin!(requests);
+    let first_requests = match warm_up {
+        Some(warm_up) => warm_up.run(&mut requests).await?,
+        None => Vec::new(),
+    };
+    let requests = stream::iter(first_requests.into_iter().map(Ok)).chain(requests);
+    tokio::pin!(requests);
+    loop {
+        let request = tokio::select! {
+            biased;
+            () = interrupted(interrupt.clone()) => break,
+            request = requests.next() => request,
+        };
+        let Some(request) = request else {
+            break;
+        };
         let request = request?;
         if let Some(pb) = &bar {
             pb.inc_length(1);
-            pb.set_message(request.to_string());
+            pb.set_message(redact::redact(&request.to_string()));
         };
+        if let Some(socket) = &progress_socket {
+            socket.collected(request.uri.as_str());
+        }
         send_req
             .send(Ok(request))
             .await
@@ -181,15 +504,144 @@ where
     Ok(())
 }
 
+/// Checks a sample of the links of hosts with many distinct URLs
+/// (`--sample-per-host`). The other links of these hosts are reported as
+/// skipped.
+struct Sample {
+    client: Client,
+    /// Maximum number of distinct URLs checked per host
+    per_host: usize,
+    /// Links which are not checked, set once all links are collected
+    skipped: Arc<OnceLock<HashSet<Uri>>>,
+}
+
+impl Sample {
+    /// Collect all links of `requests`, choose the links which are not in
+    /// the sample of their host and return all links, which are checked (or
+    /// skipped) afterwards
+    async fn run<S>(&self, requests: &mut Pin<&mut S>) -> Result<Vec<Request>>
+    where
+        S: futures::Stream<Item = Result<Request>>,
+    {
+        let mut all_requests = Vec::new();
+        while let Some(request) = requests.next().await {
+            all_requests.push(request?);
+        }
+
+        let mut uris_per_host: HashMap<String, HashSet<Uri>> = HashMap::new();
+        for request in &all_requests {
+            let mut uri = request.uri.clone();
+            if self.client.remap(&mut uri).is_err()
+                || self.client.is_excluded_from(&uri, &request.source)
+            {
+                continue;
+            }
+            let Some(host) = uri.host_str().map(str::to_lowercase) else {
+                continue;
+            };
+            uris_per_host
+                .entry(host)
+                .or_default()
+                .insert(request.uri.clone());
+        }
+
+        let skipped = sample_out(uris_per_host, self.per_host);
+        log::info!("Skipping {} links which are not sampled", skipped.len());
+        // The sample is only chosen once per run
+        let _ = self.skipped.set(skipped);
+        Ok(all_requests)
+    }
+}
+
+/// The URIs of the hosts with more than `per_host` distinct URIs which are
+/// not in the sample of their host.
+///
+/// The sample consists of the URIs with the smallest hashes, so it is random
+/// but the same links are sampled in every run.
+fn sample_out(uris_per_host: HashMap<String, HashSet<Uri>>, per_host: usize) -> HashSet<Uri> {
+    uris_per_host
+        .into_values()
+        .filter(|uris| uris.len() > per_host)
+        .flat_map(|uris| {
+            let mut uris: Vec<_> = uris.into_iter().collect();
+            uris.sort_by_cached_key(|uri| {
+                digest(&SHA256, uri.as_str().as_bytes()).as_ref().to_vec()
+            });
+            uris.into_iter().skip(per_host)
+        })
+        .collect()
+}
+
+/// Opens connections to the hosts with several links among the first links
+/// before their links are checked (`--warm-up`)
+struct WarmUp {
+    client: Client,
+    /// Maximum number of connections per host
+    connections: usize,
+    /// Number of links among which the hosts are counted
+    window: usize,
+}
+
+impl WarmUp {
+    /// Collect the first links of `requests`, start warming up the hosts
+    /// with more than one of these links with their first link and return
+    /// the links. Only the links of these hosts wait for the warm-up.
+    async fn run<S>(&self, requests: &mut Pin<&mut S>) -> Result<Vec<Request>>
+    where
+        S: futures::Stream<Item = Result<Request>>,
+    {
+        let mut first_requests = Vec::new();
+        while first_requests.len() < self.window {
+            match requests.next().await {
+                Some(request) => first_requests.push(request?),
+                None => break,
+            }
+        }
+
+        let mut links_per_host: HashMap<String, (usize, Uri)> = HashMap::new();
+        for request in &first_requests {
+            let mut uri = request.uri.clone();
+            if self.client.remap(&mut uri).is_err()
+                || self.client.is_excluded_from(&uri, &request.source)
+            {
+                continue;
+            }
+            let Some(host) = uri.host_str().map(str::to_lowercase) else {
+                continue;
+            };
+            links_per_host.entry(host).or_insert((0, uri)).0 += 1;
+        }
+
+        let warming_up = links_per_host
+            .into_values()
+            .filter(|(links, uri)| {
+                *links > 1 && self.client.warm_up(uri, (*links).min(self.connections))
+            })
+            .count();
+        log::info!("Warming up the connections to {warming_up} hosts");
+        Ok(first_requests)
+    }
+}
+
 /// Reads from the request channel and updates the progress bar status
+#[allow(clippy::too_many_arguments)]
 async fn progress_bar_task(
     mut recv_resp: mpsc::Receiver<Response>,
     verbose: Verbosity,
     pb: Option<ProgressBar>,
-    formatter: Box<dyn ResponseFormatter>,
+    progress_socket: Option<Arc<ProgressSocket>>,
+    formatter: Arc<dyn ResponseFormatter>,
+    mail_lint: Option<MailLint>,
+    permalink_lint: Option<PermalinkLint>,
+    labels: Labels,
     mut stats: ResponseStats,
 ) -> Result<(Option<ProgressBar>, ResponseStats)> {
     while let Some(response) = recv_resp.recv().await {
+        let label = labels.label(response.status());
+        let response = response.with_label(label);
+        if let Some(socket) = &progress_socket {
+            socket.checked(response.body().uri.as_str(), response.status().is_error());
+        }
         show_progress(
             &mut io::stderr(),
             pb.as_ref(),
@@ -197,6 +649,24 @@ async fn progress_bar_task(
             formatter.as_ref(),
             &verbose,
         )?;
+        let uri = &response.body().uri;
+        let reason = mail_lint
+            .as_ref()
+            .and_then(|lint| lint.check(uri))
+            .map(LintReason::from)
+            .or_else(|| {
+                permalink_lint
+                    .as_ref()
+                    .and_then(|lint| lint.check(uri))
+                    .map(LintReason::from)
+            });
+        if let Some(reason) = reason {
+            let warning = Warning {
+                uri: uri.clone(),
+                reason,
+            };
+            stats.add_warning(response.source().clone(), warning);
+        }
         stats.add(response);
     }
     Ok((pb, stats))
@@ -215,28 +685,50 @@ fn init_progress_bar(initial_message: &'static str) -> ProgressBar {
     bar
 }
 
+/// Checks the requests of `recv_req` and sends the responses to `send_resp`.
+///
+/// Internal links (see `--internal-host`) and external links are limited
+/// separately by the lanes of the host pool of the client, so there is room
+/// for the checks of both lanes at the same time.
+#[allow(clippy::too_many_arguments)]
 async fn request_channel_task(
     recv_req: mpsc::Receiver<Result<Request>>,
     send_resp: mpsc::Sender<Response>,
     max_concurrency: usize,
+    internal_max_concurrency: usize,
+    sampled_out: Arc<OnceLock<HashSet<Uri>>>,
     client: Client,
     cache: Arc<Cache>,
     cache_exclude_status: HashSet<u16>,
     accept: HashSet<u16>,
+    interrupt: watch::Receiver<bool>,
 ) {
+    let checks = Checks {
+        sampled_out,
+        ..Checks::default()
+    };
     StreamExt::for_each_concurrent(
         ReceiverStream::new(recv_req),
-        max_concurrency,
+        max_concurrency + internal_max_concurrency,
         |request: Result<Request>| async {
             let request = request.expect("cannot read request");
-            let response = handle(
-                &client,
-                cache.clone(),
-                cache_exclude_status.clone(),
-                request,
-                accept.clone(),
-            )
-            .await;
+            // Outstanding checks are cancelled once the run is interrupted,
+            // and the remaining requests are drained without checking them
+            let response = tokio::select! {
+                biased;
+                () = interrupted(interrupt.clone()) => return,
+                response = checks.handle(
+                    &client,
+                    &cache,
+                    &cache_exclude_status,
+                    request,
+                    &accept,
+                ) => response,
+            };
+            // The link was already reported for the same input
+            let Some(response) = response else {
+                return;
+            };
 
             send_resp
                 .send(response)
@@ -247,6 +739,117 @@ async fn request_channel_task(
     .await;
 }
 
+/// Checks of a run of [`check`], to request each URL only once.
+///
+/// A URL which is linked from several inputs is requested once. While it is
+/// in flight, the other requests for it wait for its result. Every input
+/// linking the URL still gets its own response with the shared result, so
+/// each pair of input and URL is reported exactly once.
+#[derive(Default)]
+struct Checks {
+    /// Results by URI, set once the first request for the URI is done.
+    /// `None` if the result can't be shared, e.g. for local files.
+    results: Mutex<HashMap<Uri, Arc<OnceCell<Option<CacheValue>>>>>,
+    /// Pairs of input and URI which were reported already
+    reported: Mutex<HashSet<(InputSource, Uri)>>,
+    /// URIs which are not in the sample of `--sample-per-host`
+    sampled_out: Arc<OnceLock<HashSet<Uri>>>,
+}
+
+impl Checks {
+    /// Check `request` like [`handle`], unless its URI was reported for the
+    /// same input already
+    async fn handle(
+        &self,
+        client: &Client,
+        cache: &Arc<Cache>,
+        cache_exclude_status: &HashSet<u16>,
+        request: Request,
+        accept: &HashSet<u16>,
+    ) -> Option<Response> {
+        let first = self
+            .reported
+            .lock()
+            .unwrap()
+            .insert((request.source.clone(), request.uri.clone()));
+        if !first {
+            return None;
+        }
+
+        let sampled_out = self
+            .sampled_out
+            .get()
+            .is_some_and(|uris| uris.contains(&request.uri));
+        if sampled_out && !client.is_excluded_from(&request.uri, &request.source) {
+            return Some(Response::from_request(
+                request,
+                Status::Skipped(SkipReason::Sampled),
+            ));
+        }
+
+        let result = self
+            .results
+            .lock()
+            .unwrap()
+            .entry(request.uri.clone())
+            .or_default()
+            .clone();
+        // The first request for the URI checks it, the others wait for it
+        let mut response = None;
+        let (first_response, pending) = (&mut response, &request);
+        let shared = result
+            .get_or_init(move || async move {
+                let checked = handle(
+                    client,
+                    cache.clone(),
+                    cache_exclude_status.clone(),
+                    pending.clone(),
+                    accept.clone(),
+                )
+                .await;
+                let status = checked.status();
+                let shared = (!ignore_cache(&pending.uri, status, cache_exclude_status))
+                    .then(|| status.into());
+                *first_response = Some(checked);
+                shared
+            })
+            .await;
+        if response.is_some() {
+            return response;
+        }
+
+        // Exclusions depend on the input, so they are checked for each one
+        let response = match shared {
+            Some(value) if !client.is_excluded_from(&request.uri, &request.source) => {
+                Response::from_request(request, value.status(accept))
+            }
+            _ => {
+                handle(
+                    client,
+                    cache.clone(),
+                    cache_exclude_status.clone(),
+                    request,
+                    accept.clone(),
+                )
+                .await
+            }
+        };
+        Some(response)
+    }
+}
+
+/// Resolves once the run is interrupted, see [`check`]
+async fn interrupted(mut interrupt: watch::Receiver<bool>) {
+    if interrupt
+        .wait_for(|interrupted| *interrupted)
+        .await
+        .is_err()
+    {
+        // Without a signal handler, the run can't be interrupted
+        std::future::pending::<()>().await;
+    }
+}
+
 /// Check a URL and return a response.
 ///
 /// # Errors
@@ -254,20 +857,16 @@ async fn request_channel_task(
 /// This can fail when the URL could not be parsed to a URI.
 async fn check_url(client: &Client, request: Request) -> Response {
     // Request was not cached; run a normal check
-    let uri = request.uri.clone();
-    let source = request.source.clone();
+    let fallback = request.clone();
     client.check(request).await.unwrap_or_else(|e| {
+        let uri = fallback.uri.clone();
         log::error!("Error checking URL {}: Cannot parse URL to URI: {}", uri, e);
-        Response::new(
-            uri.clone(),
-            Status::Error(ErrorKind::InvalidURI(uri.clone())),
-            source,
-        )
+        Response::from_request(fallback, Status::Error(ErrorKind::InvalidURI(uri)))
     })
 }
 
 /// Handle a single request
-async fn handle(
+pub(crate) async fn handle(
     client: &Client,
     cache: Arc<Cache>,
     cache_exclude_status: HashSet<u16>,
@@ -275,20 +874,31 @@ async fn handle(
     accept: HashSet<u16>,
 ) -> Response {
     let uri = request.uri.clone();
+    // Secrets in the URI are only redacted when the cache is stored, so that
+    // URIs which differ only in their secrets are cached separately
     if let Some(v) = cache.get(&uri) {
         // Found a cached request
         // Overwrite cache status in case the URI is excluded in the
         // current run
-        let status = if client.is_excluded(&uri) {
+        let status = if client.is_excluded_from(&uri, &request.source) {
             Status::Excluded
         } else {
             // Can't impl `Status::from(v.value().status)` here because the
             // `accepted` status codes might have changed from the previous run
             // and they may have an impact on the interpretation of the status
             // code.
-            Status::from_cache_status(v.value().status, &accept)
+            v.value().status(&accept)
         };
-        return Response::new(uri.clone(), status, request.source);
+        return Response::from_request(request, status);
+    }
+
+    // After the deadline of `--max-runtime`, no new requests are sent.
+    // Local files and excluded links are still reported as usual.
+    if client.is_past_deadline()
+        && !uri.is_file()
+        && !client.is_excluded_from(&uri, &request.source)
+    {
+        return Response::from_request(request, Status::Skipped(SkipReason::Deadline));
     }
 
     // Request was not cached; run a normal check
@@ -316,6 +926,7 @@ async fn handle(
 /// - The status is excluded.
 /// - The status is unsupported.
 /// - The status is unknown.
+/// - The link was skipped.
 /// - The status code is excluded from the cache.
 fn ignore_cache(uri: &Uri, status: &Status, cache_exclude_status: &HashSet<u16>) -> bool {
     let status_code_excluded = status
@@ -326,6 +937,7 @@ fn ignore_cache(uri: &Uri, status: &Status, cache_exclude_status: &HashSet<u16>)
         || status.is_excluded()
         || status.is_unsupported()
         || status.is_unknown()
+        || status.is_skipped()
         || status_code_excluded
 }
 
@@ -345,6 +957,7 @@ fn show_progress(
     } else {
         formatter.format_response(response.body())
     };
+    let out = redact::redact(&out);
 
     if let Some(pb) = progress_bar {
         pb.inc(1);
@@ -366,7 +979,10 @@ fn get_failed_urls(stats: &mut ResponseStats) -> Vec<(InputSource, Url)> {
         .iter()
         .flat_map(|(source, set)| {
             set.iter()
-                .map(move |ResponseBody { uri, status: _ }| (source, uri))
+                // Redirects which only fail due to `--fail-on-redirects` or
+                // `--hint-permanent-redirects=fail` still work
+                .filter(|body| !body.status.is_success())
+                .map(move |ResponseBody { uri, .. }| (source, uri))
         })
         .filter_map(|(source, uri)| {
             if uri.is_data() || uri.is_mail() || uri.is_file() {
@@ -390,12 +1006,35 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_failure_exit_code() {
+        let response = |url: &str, status: Status| {
+            Response::new(Uri::try_from(url).unwrap(), status, InputSource::Stdin)
+        };
+        let mut stats = ResponseStats::default();
+        stats.add(response(
+            "https://example.com/a",
+            Status::Cached(CacheStatus::Error(Some(404)), None),
+        ));
+        stats.add(response(
+            "https://example.com/b",
+            Status::Cached(CacheStatus::Error(Some(410)), None),
+        ));
+        assert_eq!(failure_exit_code(&stats), ExitCode::ClientError);
+
+        stats.add(response(
+            "https://example.com/c",
+            Status::Cached(CacheStatus::Error(Some(503)), None),
+        ));
+        assert_eq!(failure_exit_code(&stats), ExitCode::LinkCheckFailure);
+    }
+
     #[test]
     fn test_skip_cached_responses_in_progress_output() {
         let mut buf = Vec::new();
         let response = Response::new(
             Uri::try_from("http://127.0.0.1").unwrap(),
-            Status::Cached(CacheStatus::Ok(200)),
+            Status::Cached(CacheStatus::Ok(200), None),
             InputSource::Stdin,
         );
         let formatter = get_response_formatter(&options::OutputMode::Plain);
@@ -417,7 +1056,7 @@ mod tests {
         let mut buf = Vec::new();
         let response = Response::new(
             Uri::try_from("http://127.0.0.1").unwrap(),
-            Status::Cached(CacheStatus::Ok(200)),
+            Status::Cached(CacheStatus::Ok(200), None),
             InputSource::Stdin,
         );
         let formatter = get_response_formatter(&options::OutputMode::Plain);
@@ -446,6 +1085,144 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn test_checks_request_each_url_once() {
+        let mock_server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::any())
+            .respond_with(wiremock::ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::builder().build().client().unwrap();
+        let cache = Arc::new(Cache::new());
+        let checks = Checks::default();
+        let uri = Uri::try_from(mock_server.uri()).unwrap();
+        let request = |source: &str, element: &str| {
+            Request::new(
+                uri.clone(),
+                InputSource::String(source.to_string()),
+                Some(element.to_string()),
+                None,
+                None,
+            )
+        };
+        let no_codes = HashSet::new();
+        let (checks, client, cache, no_codes) = (&checks, &client, &cache, &no_codes);
+        let check = move |request| checks.handle(client, cache, no_codes, request, no_codes);
+
+        let (a, b) = tokio::join!(check(request("a", "a")), check(request("b", "a")));
+        assert_eq!(a.unwrap().status(), &Status::Ok(StatusCode::OK));
+        assert_eq!(
+            b.unwrap().status(),
+            &Status::Cached(CacheStatus::Ok(200), None)
+        );
+
+        // The same URL is only reported once for each input
+        assert!(check(request("a", "img")).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_checks_share_error_details() {
+        let mock_server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::any())
+            .respond_with(wiremock::ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::builder().build().client().unwrap();
+        let cache = Arc::new(Cache::new());
+        let checks = Checks::default();
+        let uri = Uri::try_from(mock_server.uri()).unwrap();
+        let request = |source: &str| {
+            Request::new(
+                uri.clone(),
+                InputSource::String(source.to_string()),
+                None,
+                None,
+                None,
+            )
+        };
+        let no_codes = HashSet::new();
+        let (checks, client, cache, no_codes) = (&checks, &client, &cache, &no_codes);
+        let check = move |request| checks.handle(client, cache, no_codes, request, no_codes);
+
+        let a = check(request("a")).await.unwrap();
+        let b = check(request("b")).await.unwrap();
+        assert!(a.status().details().is_some());
+        assert!(matches!(b.status(), Status::Cached(..)));
+        assert_eq!(b.status().details(), a.status().details());
+
+        // The details are kept in the cache for the next run, too
+        let c = handle(
+            client,
+            cache.clone(),
+            HashSet::new(),
+            request("c"),
+            HashSet::new(),
+        )
+        .await;
+        assert_eq!(c.status().details(), a.status().details());
+    }
+
+    #[tokio::test]
+    async fn test_cache_by_unredacted_uri() {
+        let mock_server = wiremock::MockServer::start().await;
+        for (token, code) in [("a", 200), ("b", 404)] {
+            wiremock::Mock::given(wiremock::matchers::query_param("token", token))
+                .respond_with(wiremock::ResponseTemplate::new(code))
+                .mount(&mock_server)
+                .await;
+        }
+
+        let client = ClientBuilder::builder().build().client().unwrap();
+        let cache = Arc::new(Cache::new());
+        let check = |token: &str| {
+            let uri = Uri::try_from(format!("{}/?token={token}", mock_server.uri())).unwrap();
+            let request = Request::new(uri, InputSource::Stdin, None, None, None);
+            handle(
+                &client,
+                cache.clone(),
+                HashSet::new(),
+                request,
+                HashSet::new(),
+            )
+        };
+
+        assert!(check("a").await.status().is_success());
+        // The URIs are the same once redacted, but they are cached separately
+        assert!(check("b").await.status().is_error());
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_sample_out() {
+        let uris = |host: &str, n: usize| -> HashSet<Uri> {
+            (0..n)
+                .map(|i| Uri::try_from(format!("https://{host}/{i}").as_str()).unwrap())
+                .collect()
+        };
+        let uris_per_host = HashMap::from([
+            (
+                "large.example.com".to_string(),
+                uris("large.example.com", 10),
+            ),
+            (
+                "small.example.com".to_string(),
+                uris("small.example.com", 3),
+            ),
+        ]);
+
+        let skipped = sample_out(uris_per_host.clone(), 3);
+        assert_eq!(skipped.len(), 7);
+        assert!(skipped
+            .iter()
+            .all(|uri| uri.host_str() == Some("large.example.com")));
+        // The same links are sampled every time
+        assert_eq!(sample_out(uris_per_host, 3), skipped);
+    }
+
     #[test]
     fn test_cache_by_default() {
         assert!(!ignore_cache(
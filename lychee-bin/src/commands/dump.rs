@@ -1,11 +1,21 @@
 use log::error;
+use lychee_lib::extract::Extractor;
+use lychee_lib::CacheStatus;
+use lychee_lib::FileType;
+use lychee_lib::InputContent;
+use lychee_lib::InputSource;
 use lychee_lib::Request;
 use lychee_lib::Result;
+use serde::Serialize;
 use std::fs;
 use std::io::{self, Write};
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use tokio_stream::StreamExt;
 
+use crate::cache::Cache;
+use crate::options::{DumpFilter, StatsFormat};
+use crate::redact;
 use crate::verbosity::Verbosity;
 use crate::ExitCode;
 
@@ -29,7 +39,10 @@ fn create_writer(output: Option<PathBuf>) -> Result<Box<dyn Write>> {
     Ok(out)
 }
 
-/// Dump all detected links to stdout without checking them
+/// Dump all detected links to stdout without checking them.
+///
+/// With `--format json`, every link is written as a JSON object on its own
+/// line (JSON Lines), including excluded links.
 pub(crate) async fn dump<S>(params: CommandParams<S>) -> Result<ExitCode>
 where
     S: futures::Stream<Item = Result<Request>>,
@@ -41,6 +54,7 @@ where
         fs::File::create(out_file)?;
     }
 
+    let json = params.cfg.format == StatsFormat::Json;
     let mut writer = create_writer(params.cfg.output)?;
 
     while let Some(request) = requests.next().await {
@@ -49,13 +63,25 @@ where
         // Apply URI remappings (if any)
         params.client.remap(&mut request.uri)?;
 
-        let excluded = params.client.is_excluded(&request.uri);
+        if let Some(filter) = params.cfg.only {
+            if !matches_filter(filter, &request, &params.cache) {
+                continue;
+            }
+        }
+
+        let excluded = params
+            .client
+            .is_excluded_from(&request.uri, &request.source);
 
-        if excluded && params.cfg.verbose.log_level() < log::Level::Info {
+        let result = if json {
+            write_json(&mut writer, &request, excluded)
+        } else if excluded && params.cfg.verbose.log_level() < log::Level::Info {
             continue;
-        }
+        } else {
+            write(&mut writer, &request, &params.cfg.verbose, excluded)
+        };
 
-        if let Err(e) = write(&mut writer, &request, &params.cfg.verbose, excluded) {
+        if let Err(e) = result {
             // Avoid panic on broken pipe.
             // See https://github.com/rust-lang/rust/issues/46016
             // This can occur when piping the output of lychee
@@ -90,19 +116,107 @@ where
     while let Some(source) = sources.next().await {
         let source = source?;
 
-        let excluded = excluded_paths
-            .iter()
-            .any(|path| source.starts_with(path.to_string_lossy().as_ref()));
-        if excluded {
+        if is_excluded_source(&source, excluded_paths) {
             continue;
         }
 
-        writeln!(writer, "{source}")?;
+        writeln!(writer, "{}", redact::redact(&source))?;
     }
 
     Ok(ExitCode::Success)
 }
 
+/// Summary of a single input for `--dump-inputs --format json`
+#[derive(Debug, Serialize)]
+struct InputSummary {
+    source: String,
+    file_type: FileType,
+    /// Size of the content in bytes
+    size: usize,
+    /// Number of links found in the content
+    links: usize,
+}
+
+/// Dump an inventory of all inputs as JSON, including the file type, size and
+/// number of links of each input. Links get extracted, but not checked.
+pub(crate) async fn dump_inputs_json<S>(
+    contents: S,
+    extractor: Extractor,
+    output: Option<&PathBuf>,
+    excluded_paths: &[PathBuf],
+) -> Result<ExitCode>
+where
+    S: futures::Stream<Item = Result<InputContent>>,
+{
+    let mut summaries = Vec::new();
+
+    tokio::pin!(contents);
+    while let Some(content) = contents.next().await {
+        let content = content?;
+        let source = content.source.to_string();
+
+        if is_excluded_source(&source, excluded_paths) {
+            continue;
+        }
+
+        summaries.push(InputSummary {
+            links: extractor.extract(&content).len(),
+            size: content.content.len(),
+            file_type: content.file_type,
+            source,
+        });
+    }
+
+    // Inputs are collected concurrently; sort them for a stable output
+    summaries.sort_by(|a, b| a.source.cmp(&b.source));
+
+    if let Some(out_file) = output {
+        fs::File::create(out_file)?;
+    }
+    let mut writer = create_writer(output.cloned())?;
+    let json = serde_json::to_string_pretty(&summaries).map_err(io::Error::from)?;
+    writeln!(writer, "{}", redact::redact(&json))?;
+
+    Ok(ExitCode::Success)
+}
+
+fn is_excluded_source(source: &str, excluded_paths: &[PathBuf]) -> bool {
+    excluded_paths
+        .iter()
+        .any(|path| source.starts_with(path.to_string_lossy().as_ref()))
+}
+
+/// Check if the request belongs to the subset of links selected with `--only`
+fn matches_filter(filter: DumpFilter, request: &Request, cache: &Cache) -> bool {
+    let uri = &request.uri;
+    match filter {
+        DumpFilter::New => !cache.contains_key(uri),
+        DumpFilter::Broken => cache
+            .get(uri)
+            .is_some_and(|value| matches!(value.status, CacheStatus::Error(_))),
+        DumpFilter::External => is_website(request) && !is_internal(request),
+        DumpFilter::Internal => is_internal(request),
+    }
+}
+
+fn is_website(request: &Request) -> bool {
+    matches!(request.uri.scheme(), "http" | "https")
+}
+
+/// Links are internal if they point to a local file or to the same host as
+/// the website they were found in
+fn is_internal(request: &Request) -> bool {
+    if request.uri.is_file() {
+        return true;
+    }
+    match &request.source {
+        InputSource::RemoteUrl(url) | InputSource::Sitemap(url) => {
+            is_website(request) && url.host_str() == request.uri.host_str()
+        }
+        _ => false,
+    }
+}
+
 /// Dump request to stdout
 fn write(
     writer: &mut Box<dyn Write>,
@@ -133,16 +247,166 @@ fn write(
     write_out(writer, &out_str)
 }
 
+/// A link of `--dump --format json`
+#[derive(Debug, Serialize)]
+struct DumpedLink<'a> {
+    uri: &'a str,
+    /// See [`lychee_lib::UriKind`]
+    kind: String,
+    source: &'a InputSource,
+    line: Option<NonZeroUsize>,
+    column: Option<NonZeroUsize>,
+    element: Option<&'a str>,
+    attribute: Option<&'a str>,
+    excluded: bool,
+}
+
+impl<'a> DumpedLink<'a> {
+    fn new(request: &'a Request, excluded: bool) -> Self {
+        Self {
+            uri: request.uri.as_str(),
+            kind: request.uri.kind().to_string(),
+            source: &request.source,
+            line: request.span.map(|span| span.line),
+            column: request.span.and_then(|span| span.column),
+            element: request.element.as_deref(),
+            attribute: request.attribute.as_deref(),
+            excluded,
+        }
+    }
+}
+
+/// Dump request as a single line of JSON
+fn write_json(writer: &mut Box<dyn Write>, request: &Request, excluded: bool) -> io::Result<()> {
+    let json = serde_json::to_string(&DumpedLink::new(request, excluded))?;
+    write_out(writer, &json)
+}
+
 fn write_out(writer: &mut Box<dyn Write>, out_str: &str) -> io::Result<()> {
-    writeln!(writer, "{out_str}")
+    writeln!(writer, "{}", redact::redact(out_str))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use futures::stream;
+    use lychee_lib::{RawUriSpan, Uri};
+    use reqwest::Url;
     use tempfile::NamedTempFile;
 
+    use crate::cache::CacheValue;
+
+    fn request(uri: &str, source: InputSource) -> Request {
+        Request::new(Uri::try_from(uri).unwrap(), source, None, None, None)
+    }
+
+    fn remote_source(url: &str) -> InputSource {
+        InputSource::RemoteUrl(Box::new(Url::parse(url).unwrap()))
+    }
+
+    #[test]
+    fn test_filter_internal_external() {
+        let cache = Cache::new();
+        let same_host = request(
+            "https://example.com/about",
+            remote_source("https://example.com/"),
+        );
+        let other_host = request("https://github.com/", remote_source("https://example.com/"));
+        let from_file = request(
+            "https://example.com/",
+            InputSource::FsPath(PathBuf::from("README.md")),
+        );
+        let file = request(
+            "file:///README.md",
+            InputSource::FsPath(PathBuf::from("index.html")),
+        );
+        let mail = request(
+            "mailto:user@example.com",
+            remote_source("https://example.com/"),
+        );
+
+        for internal in [&same_host, &file] {
+            assert!(matches_filter(DumpFilter::Internal, internal, &cache));
+            assert!(!matches_filter(DumpFilter::External, internal, &cache));
+        }
+        for external in [&other_host, &from_file] {
+            assert!(matches_filter(DumpFilter::External, external, &cache));
+            assert!(!matches_filter(DumpFilter::Internal, external, &cache));
+        }
+        assert!(!matches_filter(DumpFilter::Internal, &mail, &cache));
+        assert!(!matches_filter(DumpFilter::External, &mail, &cache));
+    }
+
+    #[test]
+    fn test_filter_new_broken() {
+        let cache = Cache::new();
+        let ok = request("https://example.com/ok", InputSource::Stdin);
+        let broken = request("https://example.com/broken", InputSource::Stdin);
+        let new = request("https://example.com/new", InputSource::Stdin);
+        cache.insert(
+            ok.uri.clone(),
+            CacheValue {
+                status: CacheStatus::Ok(200),
+                timestamp: 0,
+                category: None,
+                details: None,
+            },
+        );
+        cache.insert(
+            broken.uri.clone(),
+            CacheValue {
+                status: CacheStatus::Error(Some(404)),
+                timestamp: 0,
+                category: None,
+                details: None,
+            },
+        );
+
+        assert!(matches_filter(DumpFilter::New, &new, &cache));
+        assert!(!matches_filter(DumpFilter::New, &ok, &cache));
+        assert!(!matches_filter(DumpFilter::New, &broken, &cache));
+
+        assert!(matches_filter(DumpFilter::Broken, &broken, &cache));
+        assert!(!matches_filter(DumpFilter::Broken, &ok, &cache));
+        assert!(!matches_filter(DumpFilter::Broken, &new, &cache));
+    }
+
+    #[test]
+    fn test_write_json() {
+        let link = Request::new(
+            Uri::try_from("https://example.com/").unwrap(),
+            InputSource::FsPath(PathBuf::from("index.html")),
+            Some("a".to_string()),
+            Some("href".to_string()),
+            None,
+        )
+        .with_span(Some(RawUriSpan {
+            line: NonZeroUsize::new(3).unwrap(),
+            column: NonZeroUsize::new(7),
+        }));
+
+        let json = serde_json::to_value(DumpedLink::new(&link, true)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "uri": "https://example.com/",
+                "kind": "web",
+                "source": "index.html",
+                "line": 3,
+                "column": 7,
+                "element": "a",
+                "attribute": "href",
+                "excluded": true,
+            })
+        );
+
+        let mail = request("mailto:user@example.com", InputSource::Stdin);
+        let json = serde_json::to_value(DumpedLink::new(&mail, false)).unwrap();
+        assert_eq!(json["kind"], "mail");
+        assert_eq!(json["line"], serde_json::Value::Null);
+        assert_eq!(json["element"], serde_json::Value::Null);
+    }
+
     #[tokio::test]
     async fn test_dump_inputs_basic() -> Result<()> {
         // Create temp file for output
@@ -219,6 +483,54 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_dump_inputs_json() -> Result<()> {
+        let temp_file = NamedTempFile::new()?;
+        let output_path = temp_file.path().to_path_buf();
+
+        let contents = vec![
+            Ok(InputContent {
+                source: InputSource::FsPath(PathBuf::from("b.md")),
+                file_type: FileType::Markdown,
+                content: "[a](https://example.com) [b](https://example.org)".to_string(),
+                stream: None,
+            }),
+            Ok(InputContent {
+                source: InputSource::FsPath(PathBuf::from("a.html")),
+                file_type: FileType::Html,
+                content: "<p>no links</p>".to_string(),
+                stream: None,
+            }),
+            Ok(InputContent {
+                source: InputSource::FsPath(PathBuf::from("excluded/c.md")),
+                file_type: FileType::Markdown,
+                content: "https://example.com".to_string(),
+                stream: None,
+            }),
+        ];
+
+        let excluded = vec![PathBuf::from("excluded")];
+        let result = dump_inputs_json(
+            stream::iter(contents),
+            Extractor::default(),
+            Some(&output_path),
+            &excluded,
+        )
+        .await?;
+        assert_eq!(result, ExitCode::Success);
+
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output_path)?)
+            .expect("output should be valid JSON");
+        assert_eq!(
+            json,
+            serde_json::json!([
+                { "source": "a.html", "file_type": "html", "size": 15, "links": 0 },
+                { "source": "b.md", "file_type": "markdown", "size": 49, "links": 2 },
+            ])
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_dump_inputs_to_stdout() -> Result<()> {
         // When output path is None, should write to stdout
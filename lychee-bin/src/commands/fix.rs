@@ -0,0 +1,242 @@
+//! Rewrite links in the input files with their suggested replacements
+//! (`--fix`), e.g. archived versions of broken links or the targets of
+//! permanent redirects.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use lychee_lib::InputSource;
+use reqwest::Url;
+
+use crate::archive::Suggestion;
+use crate::redact;
+
+/// Replacements of a link in a single file
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Fix {
+    pub(crate) path: PathBuf,
+    pub(crate) original: Url,
+    pub(crate) replacement: Url,
+    /// Number of replaced occurrences of the link
+    pub(crate) count: usize,
+}
+
+/// Replace the links of `suggestions` in all local input files.
+/// With `dry_run`, the files are left as is.
+///
+/// # Errors
+///
+/// Fails if a file can't be read or written.
+pub(crate) fn fix(
+    suggestions: &HashMap<InputSource, HashSet<Suggestion>>,
+    dry_run: bool,
+) -> Result<Vec<Fix>> {
+    let mut fixes = Vec::new();
+    for (source, suggestions) in suggestions {
+        // Remote inputs can't be changed and archive entries are unpacked
+        // into temporary files
+        let InputSource::FsPath(path) = source else {
+            continue;
+        };
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Cannot read `{}` to fix links", path.display()))?;
+
+        // Apply the replacements in a stable order
+        let mut suggestions: Vec<_> = suggestions.iter().collect();
+        suggestions.sort_by(|a, b| a.original.cmp(&b.original));
+
+        let mut fixed = content.clone();
+        for Suggestion {
+            original,
+            suggestion,
+        } in suggestions
+        {
+            let count = replace_link(&mut fixed, original, suggestion);
+            if count > 0 {
+                fixes.push(Fix {
+                    path: path.clone(),
+                    original: original.clone(),
+                    replacement: suggestion.clone(),
+                    count,
+                });
+            }
+        }
+
+        if !dry_run && fixed != content {
+            fs::write(path, fixed)
+                .with_context(|| format!("Cannot write fixed links to `{}`", path.display()))?;
+        }
+    }
+    fixes.sort_by(|a, b| (&a.path, &a.original).cmp(&(&b.path, &b.original)));
+    Ok(fixes)
+}
+
+/// Replace all occurrences of the link `original` in `content`, returning the
+/// number of replacements
+fn replace_link(content: &mut String, original: &Url, replacement: &Url) -> usize {
+    // Parsing adds a trailing slash to URLs without a path, which isn't
+    // necessarily in the file
+    let mut candidates = vec![original.as_str()];
+    if original.path() == "/" && original.query().is_none() && original.fragment().is_none() {
+        candidates.push(original.as_str().trim_end_matches('/'));
+    }
+
+    for candidate in candidates {
+        let mut replaced = String::with_capacity(content.len());
+        let mut count = 0;
+        let mut rest = content.as_str();
+        while let Some(start) = rest.find(candidate) {
+            let end = start + candidate.len();
+            replaced.push_str(&rest[..start]);
+            // The link may also be part of another one, e.g. of an archived
+            // version like `https://web.archive.org/web/2020/https://example.com`
+            let starts_link = !replaced.chars().next_back().is_some_and(is_link_char);
+            if starts_link && ends_link(&rest[end..]) {
+                replaced.push_str(replacement.as_str());
+                count += 1;
+            } else {
+                replaced.push_str(candidate);
+            }
+            rest = &rest[end..];
+        }
+        if count > 0 {
+            replaced.push_str(rest);
+            *content = replaced;
+            return count;
+        }
+    }
+    0
+}
+
+/// Returns `true` if a link ends right before `rest`, i.e. the link isn't
+/// only the start of a longer one, like `https://example.com/a` in
+/// `https://example.com/about`
+fn ends_link(rest: &str) -> bool {
+    let mut chars = rest.chars();
+    match chars.next() {
+        None => true,
+        // Punctuation at the end of a sentence
+        Some('.' | ',' | ';' | ':' | '!' | '?') => chars.next().map_or(true, |c| !is_link_char(c)),
+        Some(c) => !is_link_char(c),
+    }
+}
+
+fn is_link_char(c: char) -> bool {
+    c.is_alphanumeric() || "-._~:/?#@!$&*+,;=%".contains(c)
+}
+
+/// Print a summary of the `fixes`
+pub(crate) fn write_fixes(out: &mut dyn Write, fixes: &[Fix], dry_run: bool) -> io::Result<()> {
+    for fix in fixes {
+        let line = format!(
+            "{}: {} -> {}{}",
+            fix.path.display(),
+            fix.original,
+            fix.replacement,
+            if fix.count > 1 {
+                format!(" ({} times)", fix.count)
+            } else {
+                String::new()
+            }
+        );
+        writeln!(out, "{}", redact::redact(&line))?;
+    }
+    let links: usize = fixes.iter().map(|fix| fix.count).sum();
+    let files = fixes
+        .iter()
+        .map(|fix| &fix.path)
+        .collect::<HashSet<_>>()
+        .len();
+    let verb = if dry_run { "Would fix" } else { "Fixed" };
+    writeln!(out, "{verb} {links} link(s) in {files} file(s)")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::tempdir;
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn test_replace_link() {
+        let mut content =
+            "[a](https://example.com/a) https://example.com/about, https://example.com/a."
+                .to_string();
+        let count = replace_link(
+            &mut content,
+            &url("https://example.com/a"),
+            &url("https://web.archive.org/web/2020/https://example.com/a"),
+        );
+        assert_eq!(count, 2);
+        assert_eq!(
+            content,
+            "[a](https://web.archive.org/web/2020/https://example.com/a) https://example.com/about, \
+             https://web.archive.org/web/2020/https://example.com/a."
+        );
+
+        // Links without a path are usually written without a trailing slash
+        let mut content = r#"<a href="http://example.com">"#.to_string();
+        let count = replace_link(
+            &mut content,
+            &url("http://example.com"),
+            &url("https://example.com/"),
+        );
+        assert_eq!(count, 1);
+        assert_eq!(content, r#"<a href="https://example.com/">"#);
+    }
+
+    #[test]
+    fn test_fix() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("README.md");
+        fs::write(&path, "See [docs](https://example.com/old).\n")?;
+        let suggestions = HashMap::from([
+            (
+                InputSource::FsPath(path.clone()),
+                HashSet::from([Suggestion {
+                    original: url("https://example.com/old"),
+                    suggestion: url("https://example.com/new"),
+                }]),
+            ),
+            // Remote inputs are skipped
+            (
+                InputSource::RemoteUrl(Box::new(url("https://example.com/"))),
+                HashSet::from([Suggestion {
+                    original: url("https://example.com/a"),
+                    suggestion: url("https://example.com/b"),
+                }]),
+            ),
+        ]);
+
+        let fixes = fix(&suggestions, true)?;
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "See [docs](https://example.com/old).\n"
+        );
+
+        let mut out = Vec::new();
+        write_fixes(&mut out, &fixes, true)?;
+        assert_eq!(
+            String::from_utf8(out)?,
+            format!(
+                "{}: https://example.com/old -> https://example.com/new\nWould fix 1 link(s) in 1 file(s)\n",
+                path.display()
+            )
+        );
+
+        fix(&suggestions, false)?;
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "See [docs](https://example.com/new).\n"
+        );
+        Ok(())
+    }
+}
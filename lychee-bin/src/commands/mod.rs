@@ -1,10 +1,16 @@
 pub(crate) mod check;
 pub(crate) mod dump;
+pub(crate) mod fix;
+pub(crate) mod serve;
 
 pub(crate) use check::check;
 pub(crate) use dump::dump;
 pub(crate) use dump::dump_inputs;
+pub(crate) use dump::dump_inputs_json;
+pub(crate) use fix::fix;
+pub(crate) use serve::serve;
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::cache::Cache;
@@ -18,4 +24,6 @@ pub(crate) struct CommandParams<S: futures::Stream<Item = Result<Request>>> {
     pub(crate) cache: Arc<Cache>,
     pub(crate) requests: S,
     pub(crate) cfg: Config,
+    /// Number of errors which are allowed per tag (from `--tag-max-errors`)
+    pub(crate) tag_max_errors: HashMap<String, usize>,
 }
@@ -0,0 +1,516 @@
+//! `lychee serve`: check links over HTTP.
+//!
+//! A long-lived server keeps its client, the rate limits of the hosts and the
+//! cache between requests, so that teams can check links in one place
+//! instead of running lychee for every document. The API has two endpoints:
+//!
+//! - `GET /healthz` answers with `ok`
+//! - `POST /check` checks the given URLs and the links in the given documents
+//!   and answers with the same JSON object as `lychee --format json`:
+//!
+//! ```json
+//! {
+//!   "urls": ["https://example.com/"],
+//!   "documents": [
+//!     {"content": "[Docs](docs/)", "file_type": "markdown", "base": "https://example.com/", "name": "README.md"}
+//!   ]
+//! }
+//! ```
+//!
+//! The server doesn't check local files: `file:` links and local bases are
+//! rejected. To keep web pages from using the server through the browser of
+//! a user, `POST /check` requires `Content-Type: application/json` and the
+//! `Host` header must be `localhost`, an IP address or a host given with
+//! `--allowed-host`.
+
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::error::Error as StdError;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use futures::{StreamExt, TryStreamExt};
+use http::uri::Authority;
+use http_body_util::{BodyExt, Full, Limited};
+use hyper::body::{Body, Bytes};
+use hyper::header::{CONTENT_TYPE, HOST};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, StatusCode};
+use hyper_util::rt::TokioIo;
+use log::{debug, info, warn};
+use lychee_lib::{Base, Client, Collector, FileType, Input, InputSource, Request};
+use serde::Deserialize;
+use tokio::net::TcpListener;
+
+use super::check::handle;
+use crate::cache::Cache;
+use crate::options::{Config, DefaultBranchSuggestion, PermanentRedirects, ServeArgs};
+use crate::stats::ResponseStats;
+
+/// Largest accepted body of `POST /check` (16 MiB)
+const MAX_BODY: usize = 16 * 1024 * 1024;
+
+type HttpResponse = hyper::Response<Full<Bytes>>;
+
+/// Body of `POST /check`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CheckRequest {
+    /// Links which are checked as they are
+    urls: Vec<String>,
+    /// Documents whose links are checked
+    documents: Vec<Document>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Document {
+    content: String,
+    /// The format of the content, which is guessed if missing
+    #[serde(default)]
+    file_type: Option<FileType>,
+    /// Base for relative links, overriding `--base`
+    #[serde(default)]
+    base: Option<String>,
+    /// Name of the document in the report, e.g. its path
+    #[serde(default)]
+    name: Option<String>,
+}
+
+/// What is shared between the requests to the server
+#[derive(Debug)]
+pub(crate) struct ServeState {
+    client: Client,
+    cache: Arc<Cache>,
+    cache_exclude_status: HashSet<u16>,
+    accept: HashSet<u16>,
+    max_concurrency: usize,
+    base: Option<Base>,
+    include_verbatim: bool,
+    fail_on_redirects: bool,
+    permanent_redirects: Option<PermanentRedirects>,
+    default_branch: Option<DefaultBranchSuggestion>,
+    /// Accepted `Host` headers besides `localhost` and IP addresses
+    allowed_hosts: Vec<String>,
+}
+
+impl ServeState {
+    pub(crate) fn new(cfg: &Config, args: &ServeArgs, client: Client, cache: Arc<Cache>) -> Self {
+        let base = cfg.base.clone().filter(|base| {
+            let remote = !is_local_base(base);
+            if !remote {
+                warn!("Ignoring the local base `{base:?}`, the server doesn't check local files");
+            }
+            remote
+        });
+        Self {
+            client,
+            cache,
+            cache_exclude_status: cfg.cache_exclude_status.clone().into_set(),
+            accept: cfg.accept.clone().into_set(),
+            max_concurrency: cfg.max_concurrency,
+            base,
+            include_verbatim: cfg.include_verbatim,
+            fail_on_redirects: cfg.fail_on_redirects,
+            permanent_redirects: cfg.hint_permanent_redirects,
+            default_branch: cfg.suggest_default_branch,
+            allowed_hosts: args.allowed_host.clone(),
+        }
+    }
+
+    /// Empty stats of a request, configured like those of a run
+    fn stats(&self) -> ResponseStats {
+        let mut stats = ResponseStats::default();
+        stats.fail_on_redirects = self.fail_on_redirects;
+        stats.permanent_redirects = self.permanent_redirects;
+        stats.default_branch = self.default_branch;
+        stats
+    }
+
+    /// Whether the `Host` header of a request names this server.
+    ///
+    /// Other names are rejected, so that a web page on a domain which
+    /// resolves to this machine (DNS rebinding) can't use the server.
+    fn is_allowed_host(&self, host: &str) -> bool {
+        let Ok(authority) = host.parse::<Authority>() else {
+            return false;
+        };
+        let host = authority.host();
+        host.eq_ignore_ascii_case("localhost")
+            || host
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<IpAddr>()
+                .is_ok()
+            || self
+                .allowed_hosts
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(host))
+    }
+}
+
+/// Serve the API at `addr` until Ctrl+C is pressed or SIGTERM is received
+pub(crate) async fn serve(addr: SocketAddr, state: ServeState) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Cannot listen at `{addr}`"))?;
+    info!("Listening at http://{}", listener.local_addr()?);
+
+    let state = Arc::new(state);
+    let shutdown = shutdown();
+    tokio::pin!(shutdown);
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    warn!("Cannot accept connection: {e}");
+                    continue;
+                }
+            },
+            () = &mut shutdown => break,
+        };
+        let state = Arc::clone(&state);
+        tokio::spawn(async move {
+            let service = service_fn(move |request| route(Arc::clone(&state), request));
+            if let Err(e) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                debug!("Connection failed: {e}");
+            }
+        });
+    }
+    info!("Stopped listening");
+    Ok(())
+}
+
+/// Wait for Ctrl+C or, on Unix, SIGTERM, which is how service managers and
+/// container runtimes stop the server
+async fn shutdown() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {},
+                    _ = terminate.recv() => {},
+                }
+                return;
+            }
+            Err(e) => warn!("Cannot listen for SIGTERM: {e}"),
+        }
+    }
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        warn!("Cannot listen for Ctrl+C: {e}");
+        std::future::pending::<()>().await;
+    }
+}
+
+async fn route<B>(
+    state: Arc<ServeState>,
+    request: hyper::Request<B>,
+) -> Result<HttpResponse, Infallible>
+where
+    B: Body<Data = Bytes>,
+    B::Error: Into<Box<dyn StdError + Send + Sync>>,
+{
+    let host = request
+        .headers()
+        .get(HOST)
+        .and_then(|host| host.to_str().ok());
+    if !host.is_some_and(|host| state.is_allowed_host(host)) {
+        return Ok(error(StatusCode::MISDIRECTED_REQUEST, "Host not allowed"));
+    }
+    let response = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/healthz") => response(StatusCode::OK, "text/plain", "ok".into()),
+        (&Method::POST, "/check") if !is_json(&request) => error(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Expected `Content-Type: application/json`",
+        ),
+        (&Method::POST, "/check") => match check(&state, request.into_body()).await {
+            Ok(stats) => match serde_json::to_string_pretty(&stats) {
+                Ok(json) => response(StatusCode::OK, "application/json", json),
+                Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+            },
+            Err(e) => error(StatusCode::BAD_REQUEST, &format!("{e:#}")),
+        },
+        (_, "/healthz" | "/check") => error(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed"),
+        _ => error(StatusCode::NOT_FOUND, "Not found"),
+    };
+    Ok(response)
+}
+
+/// Whether the body of `request` is declared as JSON.
+///
+/// Browsers only send other types like `text/plain` without a CORS
+/// preflight, so web pages can't send requests to the server this way.
+fn is_json<B>(request: &hyper::Request<B>) -> bool {
+    request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .and_then(|content_type| content_type.split(';').next())
+        .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("application/json"))
+}
+
+/// Whether `base` points to local files
+fn is_local_base(base: &Base) -> bool {
+    match base {
+        Base::Local(_) => true,
+        Base::Remote(url) => url.scheme() == "file",
+    }
+}
+
+/// Check the links of a `POST /check` request
+async fn check<B>(state: &ServeState, body: B) -> Result<ResponseStats>
+where
+    B: Body<Data = Bytes>,
+    B::Error: Into<Box<dyn StdError + Send + Sync>>,
+{
+    let body = Limited::new(body, MAX_BODY)
+        .collect()
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+        .context("Cannot read request body")?
+        .to_bytes();
+    let check_request: CheckRequest =
+        serde_json::from_slice(&body).context("Invalid request body")?;
+
+    let mut requests = check_request
+        .urls
+        .iter()
+        .map(|url| {
+            let request =
+                Request::try_from(url.as_str()).with_context(|| format!("Invalid URL `{url}`"))?;
+            if request.uri.scheme() == "file" {
+                anyhow::bail!("Local files are not checked: `{url}`");
+            }
+            Ok(request)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    for document in check_request.documents {
+        requests.extend(links(state, document).await?);
+    }
+
+    let responses = futures::stream::iter(requests)
+        .map(|request| {
+            handle(
+                &state.client,
+                Arc::clone(&state.cache),
+                state.cache_exclude_status.clone(),
+                request,
+                state.accept.clone(),
+            )
+        })
+        .buffer_unordered(state.max_concurrency)
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut stats = state.stats();
+    for response in responses {
+        stats.add(response);
+    }
+    Ok(stats)
+}
+
+/// The links in `document`, without links to local files
+async fn links(state: &ServeState, document: Document) -> Result<Vec<Request>> {
+    let base = match &document.base {
+        Some(value) => {
+            let base = Base::try_from(value.as_str())
+                .with_context(|| format!("Invalid base `{value}`"))?;
+            if is_local_base(&base) {
+                anyhow::bail!("Local bases are not supported: `{value}`");
+            }
+            Some(base)
+        }
+        None => state.base.clone(),
+    };
+    let mut input = Input::from_source(InputSource::String(document.content));
+    input.file_type_hint = document.file_type;
+    let mut requests: Vec<Request> = Collector::new(None, base)?
+        .include_verbatim(state.include_verbatim)
+        .collect_links(vec![input])
+        .try_collect()
+        .await?;
+    requests.retain(|request| {
+        let remote = request.uri.scheme() != "file";
+        if !remote {
+            debug!("Skipping local file `{}`", request.uri);
+        }
+        remote
+    });
+    if let Some(name) = document.name {
+        for request in &mut requests {
+            request.source = InputSource::String(name.clone());
+        }
+    }
+    Ok(requests)
+}
+
+fn response(status: StatusCode, content_type: &'static str, body: String) -> HttpResponse {
+    let mut response = hyper::Response::new(Full::new(Bytes::from(body)));
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        content_type.parse().expect("valid content type"),
+    );
+    response
+}
+
+fn error(status: StatusCode, message: &str) -> HttpResponse {
+    let body = serde_json::json!({ "error": message }).to_string();
+    response(status, "application/json", body)
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode as HttpStatus;
+    use lychee_lib::ClientBuilder;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn state() -> Arc<ServeState> {
+        let cfg = Config {
+            max_concurrency: 4,
+            ..Config::default()
+        };
+        let args = ServeArgs {
+            listen: "127.0.0.1:0".parse().unwrap(),
+            allowed_host: vec!["lychee.internal".into()],
+        };
+        let client = ClientBuilder::default().client().unwrap();
+        Arc::new(ServeState::new(&cfg, &args, client, Arc::default()))
+    }
+
+    async fn send(method: Method, path: &str, body: &str) -> (StatusCode, serde_json::Value) {
+        let headers = [
+            ("host", "127.0.0.1:8080"),
+            ("content-type", "application/json"),
+        ];
+        send_with(method, path, body, &headers).await
+    }
+
+    async fn send_with(
+        method: Method,
+        path: &str,
+        body: &str,
+        headers: &[(&str, &str)],
+    ) -> (StatusCode, serde_json::Value) {
+        let mut request = hyper::Request::builder().method(method).uri(path);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let request = request
+            .body(Full::new(Bytes::from(body.to_string())))
+            .unwrap();
+        let response = route(state(), request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body = serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null);
+        (status, body)
+    }
+
+    #[tokio::test]
+    async fn test_healthz() {
+        let (status, _) = send(Method::GET, "/healthz", "").await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(Method::POST, "/healthz", "").await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        let (status, _) = send(Method::GET, "/missing", "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_check() {
+        let mock_server = wiremock::MockServer::start().await;
+        for (path, status) in [("/", HttpStatus::OK), ("/broken", HttpStatus::NOT_FOUND)] {
+            wiremock::Mock::given(wiremock::matchers::path(path))
+                .respond_with(wiremock::ResponseTemplate::new(status))
+                .mount(&mock_server)
+                .await;
+        }
+        let server = mock_server.uri();
+
+        let body = serde_json::json!({
+            "urls": [server],
+            "documents": [{
+                "content": "[Broken](/broken)",
+                "file_type": "markdown",
+                "base": server,
+                "name": "README.md",
+            }],
+        });
+        let (status, stats) = send(Method::POST, "/check", &body.to_string()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(stats["total"], 2);
+        assert_eq!(stats["errors"], 1);
+        assert_eq!(
+            stats["error_map"]["README.md"][0]["url"],
+            format!("{server}/broken")
+        );
+
+        let (status, error) = send(Method::POST, "/check", r#"{"urls": ["no url"]}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(error["error"].as_str().unwrap().contains("Invalid URL"));
+    }
+
+    #[tokio::test]
+    async fn test_check_rejects_local_files() {
+        let (status, error) = send(
+            Method::POST,
+            "/check",
+            r#"{"urls": ["file:///etc/passwd"]}"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(error["error"].as_str().unwrap().contains("Local files"));
+
+        let body = serde_json::json!({
+            "documents": [{"content": "[Passwords](passwd)", "file_type": "markdown", "base": "/etc/"}],
+        });
+        let (status, error) = send(Method::POST, "/check", &body.to_string()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(error["error"].as_str().unwrap().contains("Local bases"));
+
+        let body = serde_json::json!({
+            "documents": [{"content": "[Passwords](file:///etc/passwd)", "file_type": "markdown"}],
+        });
+        let (status, stats) = send(Method::POST, "/check", &body.to_string()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(stats["total"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_check_rejects_cross_site_requests() {
+        let body = r#"{"urls": []}"#;
+        for content_type in ["text/plain", "application/x-www-form-urlencoded"] {
+            let headers = [("host", "localhost:8080"), ("content-type", content_type)];
+            let (status, _) = send_with(Method::POST, "/check", body, &headers).await;
+            assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        }
+
+        for host in ["attacker.example.com", "attacker.example.com:8080"] {
+            let headers = [("host", host), ("content-type", "application/json")];
+            let (status, _) = send_with(Method::POST, "/check", body, &headers).await;
+            assert_eq!(status, StatusCode::MISDIRECTED_REQUEST);
+        }
+        let (status, _) = send_with(Method::GET, "/healthz", "", &[]).await;
+        assert_eq!(status, StatusCode::MISDIRECTED_REQUEST);
+
+        for host in ["localhost", "[::1]:8080", "LYCHEE.internal:8080"] {
+            let headers = [
+                ("host", host),
+                ("content-type", "application/json; charset=utf-8"),
+            ];
+            let (status, _) = send_with(Method::POST, "/check", body, &headers).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+    }
+}
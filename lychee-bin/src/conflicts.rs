@@ -0,0 +1,193 @@
+//! Detection of options which silently cancel each other out, e.g.
+//! `--offline` with remote inputs.
+//!
+//! Options are often spread over the command line, the config file and
+//! presets, so each conflict lists where the options involved were set.
+
+use std::fmt;
+
+use lychee_lib::{Input, InputSource};
+
+use crate::options::{Config, DumpFilter, OptionSource};
+
+/// Options or inputs which cancel each other out
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Conflict {
+    /// The conflicting options (or inputs) and where they were set
+    pub(crate) origins: Vec<(String, Option<OptionSource>)>,
+    /// What happens because of the conflict
+    pub(crate) effect: String,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (origin, source)) in self.origins.iter().enumerate() {
+            if i > 0 {
+                write!(f, " and ")?;
+            }
+            write!(f, "{origin}")?;
+            if let Some(source) = source {
+                write!(f, " ({source})")?;
+            }
+        }
+        write!(f, " conflict: {}", self.effect)
+    }
+}
+
+/// Find all conflicts between the options of `cfg` and the `inputs`
+pub(crate) fn find(cfg: &Config, inputs: &[Input]) -> Vec<Conflict> {
+    let option = |name: &str| {
+        (
+            format!("`--{}`", name.replace('_', "-")),
+            cfg.sources.get(name).cloned(),
+        )
+    };
+    let mut conflicts = Vec::new();
+
+    // Offline mode overrides the scheme, see `client::create`
+    let (schemes, scheme_option) = if cfg.offline {
+        (vec!["file".to_string()], "offline")
+    } else {
+        (cfg.scheme.clone(), "scheme")
+    };
+    if !schemes.is_empty() {
+        for pattern in &cfg.include {
+            let Some(pattern_schemes) = pattern_schemes(pattern) else {
+                continue;
+            };
+            if pattern_schemes
+                .iter()
+                .all(|scheme| !schemes.contains(scheme))
+            {
+                conflicts.push(Conflict {
+                    origins: vec![option("include"), option(scheme_option)],
+                    effect: format!(
+                        "links matching `{pattern}` are never checked, as only links with the scheme {} are checked",
+                        schemes.join(", ")
+                    ),
+                });
+            }
+        }
+    }
+
+    if cfg.offline {
+        let remote = inputs.iter().find_map(|input| match &input.source {
+            InputSource::RemoteUrl(url) | InputSource::Sitemap(url) | InputSource::Bucket(url) => {
+                Some(url)
+            }
+            _ => None,
+        });
+        if let Some(url) = remote {
+            conflicts.push(Conflict {
+                origins: vec![
+                    option("offline"),
+                    (
+                        format!("remote input `{url}`"),
+                        Some(OptionSource::CommandLine),
+                    ),
+                ],
+                effect:
+                    "remote inputs are still downloaded, but only links to local files are checked"
+                        .to_string(),
+            });
+        }
+    }
+
+    if cfg.cache && cfg.dump && !cfg.only.is_some_and(DumpFilter::uses_cache) {
+        conflicts.push(Conflict {
+            origins: vec![option("cache"), option("dump")],
+            effect: "links are not checked with `--dump`, so the cache is neither used nor updated"
+                .to_string(),
+        });
+    }
+
+    conflicts
+}
+
+/// The schemes of all URLs which can match `pattern`, if it is anchored to
+/// the start of the URL, e.g. `http` and `https` for `^https?://`
+fn pattern_schemes(pattern: &str) -> Option<Vec<String>> {
+    let rest = pattern.strip_prefix('^')?;
+    let end = rest.find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '?')))?;
+    if !rest[end..].starts_with(':') {
+        return None;
+    }
+    let scheme = rest[..end].to_ascii_lowercase();
+    match scheme.strip_suffix('?') {
+        // An optional last character, like `https?`
+        Some(required) if !required.is_empty() && !required.contains('?') => Some(vec![
+            required[..required.len() - 1].to_string(),
+            required.to_string(),
+        ]),
+        Some(_) => None,
+        None if scheme.contains('?') => None,
+        None => Some(vec![scheme]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_pattern_schemes() {
+        assert_eq!(
+            pattern_schemes("^http://internal"),
+            Some(vec!["http".to_string()])
+        );
+        assert_eq!(
+            pattern_schemes("^https?://example"),
+            Some(vec!["http".to_string(), "https".to_string()])
+        );
+        assert_eq!(
+            pattern_schemes("^mailto:"),
+            Some(vec!["mailto".to_string()])
+        );
+        assert_eq!(pattern_schemes("example\\.com"), None);
+        assert_eq!(pattern_schemes("^(http|ftp)://"), None);
+    }
+
+    #[test]
+    fn test_find() {
+        let config_file = OptionSource::ConfigFile(PathBuf::from("lychee.toml"));
+        let cfg = Config {
+            offline: true,
+            include: vec!["^https?://internal".to_string(), "docs".to_string()],
+            sources: HashMap::from([
+                ("offline".to_string(), OptionSource::CommandLine),
+                ("include".to_string(), config_file.clone()),
+            ]),
+            ..Default::default()
+        };
+        let inputs = [Input::new("https://example.com", None, false, None).unwrap()];
+
+        let conflicts = find(&cfg, &inputs);
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(
+            conflicts[0].origins,
+            [
+                ("`--include`".to_string(), Some(config_file)),
+                ("`--offline`".to_string(), Some(OptionSource::CommandLine)),
+            ]
+        );
+        assert_eq!(
+            conflicts[1].to_string(),
+            "`--offline` (command line) and remote input `https://example.com/` (command line) conflict: \
+             remote inputs are still downloaded, but only links to local files are checked"
+        );
+
+        let cfg = Config {
+            cache: true,
+            dump: true,
+            scheme: vec!["https".to_string()],
+            include: vec!["^https://internal".to_string()],
+            ..Default::default()
+        };
+        let conflicts = find(&cfg, &[]);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].origins[0], ("`--cache`".to_string(), None));
+    }
+}
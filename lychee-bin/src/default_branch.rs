@@ -0,0 +1,255 @@
+//! Suggest replacements for links into the `master` branch of GitHub
+//! repositories whose default branch was renamed (`--suggest-default-branch`).
+//!
+//! GitHub redirects links into a renamed branch, so these links keep working
+//! until a new `master` branch is pushed. The default branch of each
+//! repository is looked up once via the GitHub API.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use reqwest::{header, Client, RequestBuilder, Url};
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+
+use crate::archive::Suggestion;
+use crate::options::DefaultBranchSuggestion;
+use crate::stats::ResponseStats;
+
+/// The branch which is assumed to be renamed
+const MASTER: &str = "master";
+
+/// Pages of a repository which are followed by a branch in the path
+const BRANCH_PAGES: [&str; 5] = ["blob", "tree", "raw", "edit", "commits"];
+
+/// Base URL of the GitHub API
+const API_URL: &str = "https://api.github.com";
+
+/// A GitHub repository, e.g. `lycheeverse/lychee`
+type Repo = (String, String);
+
+#[derive(Debug, Deserialize)]
+struct RepoResponse {
+    default_branch: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitResponse {
+    sha: String,
+}
+
+/// The repository of `url` if it is a link into its `master` branch, e.g.
+/// `https://github.com/owner/repo/blob/master/README.md`
+pub(crate) fn master_branch_repo(url: &Url) -> Option<Repo> {
+    if !matches!(url.host_str(), Some("github.com" | "www.github.com")) {
+        return None;
+    }
+    let segments: Vec<_> = url.path_segments()?.collect();
+    match segments.as_slice() {
+        [owner, repo, page, MASTER, ..] if BRANCH_PAGES.contains(page) => {
+            Some(((*owner).to_string(), (*repo).to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Replace the `master` branch in `url` with `reference`, i.e. another
+/// branch or a commit
+fn rewrite(url: &Url, reference: &str) -> Option<Url> {
+    let mut segments: Vec<_> = url.path_segments()?.collect();
+    *segments.get_mut(3)? = reference;
+    let mut rewritten = url.clone();
+    rewritten.set_path(&format!("/{}", segments.join("/")));
+    Some(rewritten)
+}
+
+/// Suggest the default branch (or a permalink to its latest commit) as a
+/// replacement for the links into `master` of `stats` whose repository has
+/// another default branch
+pub(crate) async fn suggest_default_branches(
+    suggestion: DefaultBranchSuggestion,
+    github_token: Option<&SecretString>,
+    user_agent: &str,
+    stats: &mut ResponseStats,
+    timeout: Duration,
+) {
+    let client = match Client::builder()
+        .timeout(timeout)
+        .user_agent(user_agent)
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            log::warn!("Cannot look up default branches: {e}");
+            return;
+        }
+    };
+    let api = GithubApi::new(client, github_token);
+
+    let repos: HashSet<Repo> = stats
+        .master_branch_links
+        .values()
+        .flatten()
+        .filter_map(master_branch_repo)
+        .collect();
+    let mut references: HashMap<Repo, String> = HashMap::new();
+    for repo in repos {
+        let reference = match api.reference(&repo, suggestion).await {
+            Ok(Some(reference)) => reference,
+            Ok(None) => continue,
+            Err(e) => {
+                log::warn!(
+                    "Cannot look up default branch of {}/{}: {e}",
+                    repo.0,
+                    repo.1
+                );
+                continue;
+            }
+        };
+        references.insert(repo, reference);
+    }
+
+    for (source, links) in &stats.master_branch_links {
+        for original in links {
+            let Some(suggestion) = master_branch_repo(original)
+                .and_then(|repo| references.get(&repo))
+                .and_then(|reference| rewrite(original, reference))
+            else {
+                continue;
+            };
+            stats
+                .suggestion_map
+                .entry(source.clone())
+                .or_default()
+                .insert(Suggestion {
+                    original: original.clone(),
+                    suggestion,
+                });
+        }
+    }
+}
+
+/// Requests to the GitHub API, authenticated with `--github-token` (if given)
+pub(crate) struct GithubApi<'a> {
+    client: Client,
+    token: Option<&'a SecretString>,
+}
+
+impl<'a> GithubApi<'a> {
+    pub(crate) const fn new(client: Client, token: Option<&'a SecretString>) -> Self {
+        Self { client, token }
+    }
+
+    fn get(&self, path: &str) -> RequestBuilder {
+        let request = self
+            .client
+            .get(format!("{API_URL}{path}"))
+            .header(header::ACCEPT, "application/vnd.github+json");
+        match self.token {
+            Some(token) => request.bearer_auth(token.expose_secret()),
+            None => request,
+        }
+    }
+
+    /// What the links into `master` of `repo` should point to instead, or
+    /// `None` if `master` is still the default branch
+    async fn reference(
+        &self,
+        (owner, repo): &Repo,
+        suggestion: DefaultBranchSuggestion,
+    ) -> Result<Option<String>, reqwest::Error> {
+        let RepoResponse { default_branch } = self
+            .get(&format!("/repos/{owner}/{repo}"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if default_branch == MASTER {
+            return Ok(None);
+        }
+        match suggestion {
+            DefaultBranchSuggestion::Branch => Ok(Some(default_branch)),
+            DefaultBranchSuggestion::Permalink => self
+                .commit_sha(&format!("{owner}/{repo}"), &default_branch)
+                .await
+                .map(Some),
+        }
+    }
+
+    /// The SHA of the commit which `reference` (e.g. a branch) of the
+    /// repository `repo` (e.g. `owner/repo`) currently points to
+    pub(crate) async fn commit_sha(
+        &self,
+        repo: &str,
+        reference: &str,
+    ) -> Result<String, reqwest::Error> {
+        let CommitResponse { sha } = self
+            .get(&format!("/repos/{repo}/commits/{reference}"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(sha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn test_master_branch_repo() {
+        let repo = Some(("lycheeverse".to_string(), "lychee".to_string()));
+        assert_eq!(
+            master_branch_repo(&url(
+                "https://github.com/lycheeverse/lychee/blob/master/README.md"
+            )),
+            repo
+        );
+        assert_eq!(
+            master_branch_repo(&url("https://github.com/lycheeverse/lychee/tree/master")),
+            repo
+        );
+        assert_eq!(
+            master_branch_repo(&url(
+                "https://github.com/lycheeverse/lychee/blob/main/README.md"
+            )),
+            None
+        );
+        assert_eq!(
+            master_branch_repo(&url("https://github.com/lycheeverse/master")),
+            None
+        );
+        assert_eq!(
+            master_branch_repo(&url(
+                "https://gitlab.com/lycheeverse/lychee/blob/master/README.md"
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rewrite() {
+        let original = url("https://github.com/lycheeverse/lychee/blob/master/README.md#usage");
+        assert_eq!(
+            rewrite(&original, "main"),
+            Some(url(
+                "https://github.com/lycheeverse/lychee/blob/main/README.md#usage"
+            ))
+        );
+        assert_eq!(
+            rewrite(&original, "0123abc"),
+            Some(url(
+                "https://github.com/lycheeverse/lychee/blob/0123abc/README.md#usage"
+            ))
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Comparison of the broken links of a run with a previous JSON report
+//! (`--diff`).
+//!
+//! Large sites often have links which have been broken for a long time and
+//! are already tracked elsewhere. With `--diff`, only links which broke since
+//! the previous report fail the run.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{self, Display};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::redact;
+use crate::stats::ResponseStats;
+
+/// The part of a JSON report (`--format json`) which is compared
+#[derive(Deserialize)]
+struct PreviousReport {
+    #[serde(default)]
+    error_map: HashMap<String, Vec<PreviousLink>>,
+}
+
+#[derive(Deserialize)]
+struct PreviousLink {
+    url: String,
+}
+
+/// A broken link and the input it was found in
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub(crate) struct BrokenLink {
+    pub(crate) source: String,
+    pub(crate) url: String,
+}
+
+impl Display for BrokenLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.url, self.source)
+    }
+}
+
+/// Read the broken links of a previous JSON report
+pub(crate) fn load_previous(path: &Path) -> Result<BTreeSet<BrokenLink>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Cannot read previous report `{}`", path.display()))?;
+    let report: PreviousReport = serde_json::from_str(&content).with_context(|| {
+        format!(
+            "Cannot parse previous report `{}`, it must be written with `--format json`",
+            path.display()
+        )
+    })?;
+    Ok(report
+        .error_map
+        .into_iter()
+        .flat_map(|(source, links)| {
+            links.into_iter().map(move |link| BrokenLink {
+                source: source.clone(),
+                url: link.url,
+            })
+        })
+        .collect())
+}
+
+/// Broken links of the current run compared to a previous report
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub(crate) struct ReportDiff {
+    /// Links which are broken now, but weren't in the previous report
+    pub(crate) new: Vec<BrokenLink>,
+    /// Links which were broken in the previous report, but aren't anymore
+    /// (or were removed)
+    pub(crate) fixed: Vec<BrokenLink>,
+    /// Links which were broken in both runs
+    pub(crate) still_broken: Vec<BrokenLink>,
+}
+
+impl ReportDiff {
+    /// Compare the errors of `stats` with the `previous` broken links
+    pub(crate) fn new(previous: &BTreeSet<BrokenLink>, stats: &ResponseStats) -> Self {
+        // Previous reports are redacted, so the current links are as well
+        let current: BTreeSet<BrokenLink> = stats
+            .error_map
+            .iter()
+            .flat_map(|(source, bodies)| {
+                bodies.iter().map(move |body| BrokenLink {
+                    source: redact::redact(&source.to_string()),
+                    url: redact::redact(body.uri.as_str()),
+                })
+            })
+            .collect();
+
+        Self {
+            new: current.difference(previous).cloned().collect(),
+            fixed: previous.difference(&current).cloned().collect(),
+            still_broken: current.intersection(previous).cloned().collect(),
+        }
+    }
+
+    /// Whether any links broke since the previous report
+    pub(crate) fn has_new(&self) -> bool {
+        !self.new.is_empty()
+    }
+
+    /// Format the diff as JSON
+    pub(crate) fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Cannot format diff as JSON")
+    }
+}
+
+impl Display for ReportDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (title, links) in [
+            ("Newly broken", &self.new),
+            ("Fixed", &self.fixed),
+            ("Still broken", &self.still_broken),
+        ] {
+            writeln!(f, "{title} ({}):", links.len())?;
+            for link in links {
+                writeln!(f, "  {link}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use lychee_lib::{ErrorKind, InputSource, Response, Status, Uri};
+    use pretty_assertions::assert_eq;
+    use tempfile::NamedTempFile;
+
+    use super::*;
+
+    fn broken_link(source: &str, url: &str) -> BrokenLink {
+        BrokenLink {
+            source: source.to_string(),
+            url: url.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut previous_report = NamedTempFile::new().unwrap();
+        write!(
+            previous_report,
+            r#"{{
+                "total": 2,
+                "error_map": {{
+                    "stdin": [
+                        {{ "url": "https://example.com/old", "status": {{ "text": "404 Not Found" }} }},
+                        {{ "url": "https://example.com/fixed", "status": {{ "text": "Timeout" }} }}
+                    ]
+                }}
+            }}"#
+        )
+        .unwrap();
+        let previous = load_previous(previous_report.path()).unwrap();
+
+        let mut stats = ResponseStats::default();
+        for url in ["https://example.com/old", "https://example.com/new"] {
+            stats.add(Response::new(
+                Uri::try_from(url).unwrap(),
+                Status::Error(ErrorKind::InvalidStatusCode(1000)),
+                InputSource::Stdin,
+            ));
+        }
+
+        let diff = ReportDiff::new(&previous, &stats);
+        assert_eq!(
+            diff,
+            ReportDiff {
+                new: vec![broken_link("stdin", "https://example.com/new")],
+                fixed: vec![broken_link("stdin", "https://example.com/fixed")],
+                still_broken: vec![broken_link("stdin", "https://example.com/old")],
+            }
+        );
+        assert!(diff.has_new());
+    }
+
+    #[test]
+    fn test_invalid_previous_report() {
+        let mut previous_report = NamedTempFile::new().unwrap();
+        write!(previous_report, "# Summary").unwrap();
+        assert!(load_previous(previous_report.path()).is_err());
+    }
+}
@@ -1,15 +1,29 @@
 use env_logger::{Builder, Env};
 use log::LevelFilter;
-use std::io::Write;
+use std::io::{self, Write};
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
 
 use crate::{
     formatters::{self, response::MAX_RESPONSE_OUTPUT_WIDTH},
-    options::OutputMode,
+    options::{OutputMode, TraceFormat},
+    redact,
     verbosity::Verbosity,
 };
 
 /// Initialize the logging system with the given verbosity level.
-pub(crate) fn init_logging(verbose: &Verbosity, mode: &OutputMode) {
+///
+/// With a `trace_format`, a `tracing` subscriber is installed instead, which
+/// also writes the spans of the library.
+pub(crate) fn init_logging(
+    verbose: &Verbosity,
+    mode: &OutputMode,
+    trace_format: Option<TraceFormat>,
+) {
+    if let Some(format) = trace_format {
+        init_tracing(verbose, format);
+        return;
+    }
+
     // Set a base level for all modules to `warn`, which is a reasonable default.
     // It will be overridden by RUST_LOG if it's set.
     let env = Env::default().filter_or("RUST_LOG", "warn");
@@ -40,10 +54,18 @@ pub(crate) fn init_logging(verbose: &Verbosity, mode: &OutputMode) {
         .max()
         .unwrap_or(0);
 
-    // Customize the log message format according to the output mode
+    // Customize the log message format according to the output mode.
+    // Log messages can contain URLs or request headers, so secrets get redacted.
     if mode.is_plain() {
         // Explicitly disable colors for plain output
-        builder.format(move |buf, record| writeln!(buf, "[{}] {}", record.level(), record.args()));
+        builder.format(move |buf, record| {
+            writeln!(
+                buf,
+                "[{}] {}",
+                record.level(),
+                redact::redact(&record.args().to_string())
+            )
+        });
     } else if mode.is_emoji() {
         // Disable padding, keep colors
         builder.format(move |buf, record| {
@@ -53,7 +75,7 @@ pub(crate) fn init_logging(verbose: &Verbosity, mode: &OutputMode) {
                 buf,
                 "{} {}",
                 color.apply_to(format!("[{level}]")),
-                record.args()
+                redact::redact(&record.args().to_string())
             )
         });
     } else {
@@ -73,7 +95,7 @@ pub(crate) fn init_logging(verbose: &Verbosity, mode: &OutputMode) {
                 "{:<padding$}{} {}",
                 "",
                 colored_level,
-                record.args(),
+                redact::redact(&record.args().to_string()),
                 padding = padding
             )
         });
@@ -81,3 +103,42 @@ pub(crate) fn init_logging(verbose: &Verbosity, mode: &OutputMode) {
 
     builder.init();
 }
+
+/// Install a `tracing` subscriber which writes events and closed spans (with
+/// their durations) in `format` to stderr. Log messages of `log` are
+/// forwarded to it.
+fn init_tracing(verbose: &Verbosity, format: TraceFormat) {
+    // Same defaults as for plain log messages
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+        let level = verbose.log_level_filter().as_str().to_lowercase();
+        EnvFilter::new(format!("info,lychee={level},lychee_lib={level}"))
+    });
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(|| RedactingWriter(io::stderr()));
+    let result = match format {
+        TraceFormat::Text => builder.try_init(),
+        TraceFormat::Json => builder.json().try_init(),
+    };
+    if let Err(e) = result {
+        eprintln!("Cannot initialize tracing: {e}");
+    }
+}
+
+/// Writes to stderr with secrets redacted.
+///
+/// Each event is written at once, so secrets are never split across writes.
+struct RedactingWriter(io::Stderr);
+
+impl Write for RedactingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        self.0.write_all(redact::redact(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
@@ -4,8 +4,13 @@ pub(crate) mod log;
 pub(crate) mod response;
 pub(crate) mod stats;
 
+use std::sync::Arc;
+
 use self::{response::ResponseFormatter, stats::StatsFormatter};
 use crate::options::{OutputMode, StatsFormat};
+use crate::stats::ResponseStats;
+use lychee_lib::formatters::FormatterRegistry;
+use strum::IntoEnumIterator;
 use supports_color::Stream;
 
 /// Detects whether a terminal supports color, and gives details about that
@@ -14,28 +19,69 @@ fn supports_color() -> bool {
     supports_color::on(Stream::Stdout).is_some()
 }
 
+/// All built-in output formats, registered under the names of their
+/// `--format` and `--mode` values
+pub(crate) fn builtin_formatters(mode: &OutputMode) -> FormatterRegistry<ResponseStats> {
+    let mut registry = FormatterRegistry::new();
+    registry
+        .register_response_formatter(OutputMode::Plain.to_string(), response::PlainFormatter)
+        .register_response_formatter(OutputMode::Color.to_string(), response::ColorFormatter)
+        .register_response_formatter(OutputMode::Emoji.to_string(), response::EmojiFormatter);
+    for format in StatsFormat::iter() {
+        let name = format.to_string();
+        match format {
+            StatsFormat::Compact => {
+                registry.register_stats_formatter(name, stats::Compact::new(mode.clone()))
+            }
+            StatsFormat::Detailed => {
+                registry.register_stats_formatter(name, stats::Detailed::new(mode.clone()))
+            }
+            StatsFormat::Json => registry.register_stats_formatter(name, stats::Json::new()),
+            StatsFormat::Markdown => {
+                registry.register_stats_formatter(name, stats::Markdown::new())
+            }
+            StatsFormat::Raw => registry.register_stats_formatter(name, stats::Raw::new()),
+        };
+    }
+    registry
+}
+
 /// Create a stats formatter based on the given format option
 pub(crate) fn get_stats_formatter(
     format: &StatsFormat,
     mode: &OutputMode,
-) -> Box<dyn StatsFormatter> {
-    match format {
-        StatsFormat::Compact => Box::new(stats::Compact::new(mode.clone())),
-        StatsFormat::Detailed => Box::new(stats::Detailed::new(mode.clone())),
-        StatsFormat::Json => Box::new(stats::Json::new()),
-        StatsFormat::Markdown => Box::new(stats::Markdown::new()),
-        StatsFormat::Raw => Box::new(stats::Raw::new()),
-    }
+) -> Arc<dyn StatsFormatter<ResponseStats>> {
+    builtin_formatters(mode)
+        .stats_formatter(&format.to_string())
+        .expect("All stats formats are registered")
 }
 
 /// Create a response formatter based on the given format option
-pub(crate) fn get_response_formatter(mode: &OutputMode) -> Box<dyn ResponseFormatter> {
-    if !supports_color() {
-        return Box::new(response::PlainFormatter);
-    }
-    match mode {
-        OutputMode::Plain => Box::new(response::PlainFormatter),
-        OutputMode::Color => Box::new(response::ColorFormatter),
-        OutputMode::Emoji => Box::new(response::EmojiFormatter),
+pub(crate) fn get_response_formatter(mode: &OutputMode) -> Arc<dyn ResponseFormatter> {
+    let mode = if supports_color() {
+        mode
+    } else {
+        &OutputMode::Plain
+    };
+    builtin_formatters(mode)
+        .response_formatter(&mode.to_string())
+        .expect("All output modes are registered")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_formatters() {
+        let registry = builtin_formatters(&OutputMode::default());
+        assert_eq!(
+            registry.stats_formatter_names().collect::<Vec<_>>(),
+            ["compact", "detailed", "json", "markdown", "raw"]
+        );
+        assert_eq!(
+            registry.response_formatter_names().collect::<Vec<_>>(),
+            ["color", "emoji", "plain"]
+        );
     }
 }
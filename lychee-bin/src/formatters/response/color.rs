@@ -15,13 +15,16 @@ impl ColorFormatter {
     /// response.
     fn status_color(status: &Status) -> &'static once_cell::sync::Lazy<console::Style> {
         match status {
-            Status::Ok(_) | Status::Cached(CacheStatus::Ok(_)) => &GREEN,
+            Status::Ok(_) | Status::Cached(CacheStatus::Ok(_), _) => &GREEN,
             Status::Excluded
+            | Status::ExcludedScheme(_)
             | Status::Unsupported(_)
-            | Status::Cached(CacheStatus::Excluded | CacheStatus::Unsupported) => &DIM,
-            Status::Redirected(_) => &NORMAL,
-            Status::UnknownStatusCode(_) | Status::Timeout(_) => &YELLOW,
-            Status::Error(_) | Status::Cached(CacheStatus::Error(_)) => &PINK,
+            | Status::Cached(CacheStatus::Excluded | CacheStatus::Unsupported, _) => &DIM,
+            Status::Redirected(..) | Status::Cached(CacheStatus::Redirected { .. }, _) => &NORMAL,
+            Status::UnknownStatusCode(_) | Status::Timeout(_) | Status::Skipped(_) => &YELLOW,
+            Status::Error(_)
+            | Status::Cached(CacheStatus::Error(_), _)
+            | Status::CanonicalMismatch { .. } => &PINK,
         }
     }
 
@@ -84,6 +87,8 @@ mod tests {
         ResponseBody {
             uri: Uri::try_from(uri).unwrap(),
             status,
+            span: None,
+            label: None,
         }
     }
 
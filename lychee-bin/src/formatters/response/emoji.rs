@@ -13,13 +13,17 @@ impl EmojiFormatter {
     /// response.
     const fn emoji_for_status(status: &Status) -> &'static str {
         match status {
-            Status::Ok(_) | Status::Cached(CacheStatus::Ok(_)) => "✅",
+            Status::Ok(_) | Status::Cached(CacheStatus::Ok(_), _) => "✅",
             Status::Excluded
+            | Status::ExcludedScheme(_)
             | Status::Unsupported(_)
-            | Status::Cached(CacheStatus::Excluded | CacheStatus::Unsupported) => "🚫",
-            Status::Redirected(_) => "↪️",
+            | Status::Cached(CacheStatus::Excluded | CacheStatus::Unsupported, _) => "🚫",
+            Status::Redirected(..) | Status::Cached(CacheStatus::Redirected { .. }, _) => "↪️",
             Status::UnknownStatusCode(_) | Status::Timeout(_) => "⚠️",
-            Status::Error(_) | Status::Cached(CacheStatus::Error(_)) => "❌",
+            Status::Skipped(_) => "⏩",
+            Status::Error(_)
+            | Status::Cached(CacheStatus::Error(_), _)
+            | Status::CanonicalMismatch { .. } => "❌",
         }
     }
 }
@@ -40,13 +44,15 @@ impl ResponseFormatter for EmojiFormatter {
 mod emoji_tests {
     use super::*;
     use http::StatusCode;
-    use lychee_lib::{ErrorKind, Status, Uri};
+    use lychee_lib::{ErrorKind, Redirects, Status, Uri};
 
     // Helper function to create a ResponseBody with a given status and URI
     fn mock_response_body(status: Status, uri: &str) -> ResponseBody {
         ResponseBody {
             uri: Uri::try_from(uri).unwrap(),
             status,
+            span: None,
+            label: None,
         }
     }
 
@@ -84,7 +90,7 @@ mod emoji_tests {
     fn test_format_response_with_redirect_status() {
         let formatter = EmojiFormatter;
         let body = mock_response_body(
-            Status::Redirected(StatusCode::MOVED_PERMANENTLY),
+            Status::Redirected(StatusCode::MOVED_PERMANENTLY, Redirects::default()),
             "https://example.com/redirect",
         );
         assert_eq!(
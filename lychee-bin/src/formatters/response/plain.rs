@@ -22,13 +22,15 @@ impl ResponseFormatter for PlainFormatter {
 mod plain_tests {
     use super::*;
     use http::StatusCode;
-    use lychee_lib::{ErrorKind, Status, Uri};
+    use lychee_lib::{ErrorKind, Redirects, Status, Uri};
 
     // Helper function to create a ResponseBody with a given status and URI
     fn mock_response_body(status: Status, uri: &str) -> ResponseBody {
         ResponseBody {
             uri: Uri::try_from(uri).unwrap(),
             status,
+            span: None,
+            label: None,
         }
     }
 
@@ -55,6 +57,22 @@ mod plain_tests {
         );
     }
 
+    #[test]
+    fn test_format_response_with_label() {
+        let formatter = PlainFormatter;
+        let body = ResponseBody {
+            label: Some("requires VPN".to_string()),
+            ..mock_response_body(
+                Status::Error(ErrorKind::InvalidUrlHost),
+                "https://intranet.example.com",
+            )
+        };
+        assert_eq!(
+            formatter.format_response(&body),
+            "[ERROR] https://intranet.example.com/ | requires VPN (URL is missing a host)"
+        );
+    }
+
     #[test]
     fn test_format_response_with_excluded_status() {
         let formatter = PlainFormatter;
@@ -69,7 +87,7 @@ mod plain_tests {
     fn test_format_response_with_redirect_status() {
         let formatter = PlainFormatter;
         let body = mock_response_body(
-            Status::Redirected(StatusCode::MOVED_PERMANENTLY),
+            Status::Redirected(StatusCode::MOVED_PERMANENTLY, Redirects::default()),
             "https://example.com/redirect",
         );
         assert_eq!(
@@ -9,7 +9,7 @@ use std::{
 use crate::formatters::color::{color, BOLD_GREEN, BOLD_PINK, BOLD_YELLOW, DIM, NORMAL};
 use crate::{formatters::get_response_formatter, options, stats::ResponseStats};
 
-use super::StatsFormatter;
+use super::{FormatterError, StatsFormatter};
 
 struct CompactResponseStats {
     stats: ResponseStats,
@@ -54,6 +54,71 @@ impl Display for CompactResponseStats {
                 }
             }
 
+            if let Some(history) = &stats.history_map.get(source) {
+                writeln!(f, "\n\u{1f570} In the web archive")?;
+                for status in *history {
+                    writeln!(f, "{status}")?;
+                }
+            }
+
+            writeln!(f)?;
+        }
+
+        // Suggestions for inputs without errors, e.g. for permanent redirects
+        for (source, suggestions) in &stats.suggestion_map {
+            if stats.error_map.contains_key(source) {
+                continue;
+            }
+            color!(f, BOLD_YELLOW, "[{}] Suggestions:\n", source)?;
+            for suggestion in suggestions {
+                writeln!(f, "{suggestion}")?;
+            }
+            writeln!(f)?;
+        }
+
+        for (source, responses) in &stats.generated_error_map {
+            color!(
+                f,
+                BOLD_YELLOW,
+                "[{}] Errors in generated regions:\n",
+                source
+            )?;
+            for response in responses {
+                writeln!(
+                    f,
+                    "{}",
+                    response_formatter.format_detailed_response(response)
+                )?;
+            }
+            writeln!(f)?;
+        }
+
+        for (source, warnings) in &stats.warning_map {
+            color!(f, BOLD_YELLOW, "[{}] Warnings:\n", source)?;
+            for warning in warnings {
+                writeln!(f, "{warning}")?;
+            }
+            writeln!(f)?;
+        }
+
+        for (source, issues) in &stats.reference_issues {
+            color!(f, BOLD_PINK, "[{}] Reference issues:\n", source)?;
+            for issue in issues {
+                writeln!(f, "{issue}")?;
+            }
+            writeln!(f)?;
+        }
+
+        if !stats.skipped_binaries.is_empty() {
+            color!(
+                f,
+                BOLD_YELLOW,
+                "Skipped {} binary inputs:\n",
+                stats.skipped_binaries.len()
+            )?;
+            for source in &stats.skipped_binaries {
+                writeln!(f, "{source}")?;
+            }
             writeln!(f)?;
         }
 
@@ -73,6 +138,15 @@ impl Display for CompactResponseStats {
         write_if_any(stats.unknown, "❓", "Unknown", &BOLD_PINK, f)?;
         write_if_any(stats.excludes, "👻", "Excluded", &BOLD_YELLOW, f)?;
         write_if_any(stats.timeouts, "⏳", "Timeouts", &BOLD_YELLOW, f)?;
+        write_if_any(stats.skipped, "⏩", "Skipped", &BOLD_YELLOW, f)?;
+        write_if_any(stats.warnings, "⚠️", "Warnings", &BOLD_YELLOW, f)?;
+        write_if_any(
+            stats.generated_errors,
+            "🤖",
+            "Errors in generated regions",
+            &BOLD_YELLOW,
+            f,
+        )?;
 
         Ok(())
     }
@@ -101,8 +175,8 @@ impl Compact {
     }
 }
 
-impl StatsFormatter for Compact {
-    fn format(&self, stats: ResponseStats) -> Result<Option<String>> {
+impl StatsFormatter<ResponseStats> for Compact {
+    fn format(&self, stats: ResponseStats) -> Result<Option<String>, FormatterError> {
         let compact = CompactResponseStats {
             stats,
             mode: self.mode.clone(),
@@ -132,17 +206,23 @@ mod tests {
             HashSet::from_iter(vec![ResponseBody {
                 uri: Uri::from(Url::parse("https://example.com").unwrap()),
                 status: Status::Ok(StatusCode::OK),
+                span: None,
+                label: None,
             }]),
         );
 
         let err1 = ResponseBody {
             uri: Uri::try_from("https://github.com/mre/idiomatic-rust-doesnt-exist-man").unwrap(),
             status: Status::Ok(StatusCode::NOT_FOUND),
+            span: None,
+            label: None,
         };
 
         let err2 = ResponseBody {
             uri: Uri::try_from("https://github.com/mre/boom").unwrap(),
             status: Status::Ok(StatusCode::INTERNAL_SERVER_ERROR),
+            span: None,
+            label: None,
         };
 
         let mut error_map: HashMap<InputSource, HashSet<ResponseBody>> = HashMap::new();
@@ -155,16 +235,38 @@ mod tests {
             errors: 2,
             unknown: 0,
             excludes: 0,
+            skipped: 0,
             timeouts: 0,
             duration_secs: 0,
             error_map,
             suggestion_map: HashMap::default(),
+            history_map: HashMap::default(),
             unsupported: 0,
             redirects: 0,
             cached: 0,
+            warnings: 0,
+            generated_errors: 0,
             success_map,
             excluded_map: HashMap::default(),
+            warning_map: HashMap::default(),
+            generated_error_map: HashMap::default(),
+            host_map: HashMap::default(),
+            host_timings: HashMap::default(),
+            directory_map: HashMap::default(),
+            source_type_map: HashMap::default(),
+            tag_map: HashMap::default(),
+            skipped_binaries: HashSet::default(),
+            reference_issues: HashMap::default(),
             detailed_stats: false,
+            fail_on_redirects: false,
+            permanent_redirects: None,
+            default_branch: None,
+            master_branch_links: HashMap::default(),
+            host_stats: false,
+            directory_stats: false,
+            outcomes: Vec::new(),
+            record_outcomes: false,
+            tag_max_errors: HashMap::default(),
         };
 
         let formatter = Compact::new(OutputMode::Plain);
@@ -1,4 +1,4 @@
-use super::StatsFormatter;
+use super::{FormatterError, StatsFormatter};
 use crate::{formatters::get_response_formatter, options, stats::ResponseStats};
 
 use anyhow::Result;
@@ -44,6 +44,7 @@ impl Display for DetailedResponseStats {
         write_stat(f, "\u{23f3} Timeouts", stats.timeouts, true)?; // ⏳
         write_stat(f, "\u{1f500} Redirected", stats.redirects, true)?; // 🔀
         write_stat(f, "\u{1f47b} Excluded", stats.excludes, true)?; // 👻
+        write_stat(f, "\u{23e9} Skipped", stats.skipped, true)?; // ⏩
         write_stat(f, "\u{2753} Unknown", stats.unknown, true)?; //❓
         write_stat(f, "\u{1f6ab} Errors", stats.errors, false)?; // 🚫
 
@@ -61,6 +62,11 @@ impl Display for DetailedResponseStats {
                     response_formatter.format_detailed_response(response)
                 )?;
 
+                // Point to the position of the link, e.g. `README.md:42:17`
+                if let Some(span) = response.span {
+                    write!(f, " ({source}:{span})")?;
+                }
+
                 if let Some(suggestions) = &stats.suggestion_map.get(source) {
                     writeln!(f, "\nSuggestions in {source}")?;
                     for suggestion in *suggestions {
@@ -68,6 +74,118 @@ impl Display for DetailedResponseStats {
                     }
                 }
             }
+
+            if let Some(history) = &stats.history_map.get(source) {
+                write!(f, "\n\nIn the web archive")?;
+                for status in *history {
+                    write!(f, "\n{status}")?;
+                }
+            }
+        }
+
+        if !stats.host_map.is_empty() {
+            write!(f, "\n\nHosts")?;
+            let mut hosts: Vec<_> = stats.host_map.iter().collect();
+            hosts.sort_by_key(|(host, _)| *host);
+            for (host, host_stats) in hosts {
+                write!(
+                    f,
+                    "\n{host}: {} total, {} errors",
+                    host_stats.total, host_stats.errors
+                )?;
+                if let Some(timings) = stats.host_timings.get(host) {
+                    if let Some(warm_up_ms) = timings.warm_up_ms {
+                        write!(f, ", warm-up {warm_up_ms}ms")?;
+                    }
+                    if let Some(mean_request_ms) = timings.mean_request_ms {
+                        write!(f, ", {mean_request_ms}ms per request")?;
+                    }
+                }
+            }
+        }
+
+        let source_types = stats.mixed_source_types();
+        if !source_types.is_empty() {
+            write!(f, "\n\nInputs by type")?;
+            for (source_type, counts) in source_types {
+                write!(
+                    f,
+                    "\n{source_type}: {} total, {} errors",
+                    counts.total, counts.errors
+                )?;
+            }
+        }
+
+        let tags = stats.tags();
+        if !tags.is_empty() {
+            write!(f, "\n\nInputs by tag")?;
+            for (tag, counts, max_errors) in tags {
+                write!(
+                    f,
+                    "\n{tag}: {} total, {} errors",
+                    counts.total, counts.errors
+                )?;
+                if let Some(max_errors) = max_errors {
+                    write!(f, " ({max_errors} allowed)")?;
+                }
+            }
+        }
+
+        let directories = stats.worst_directories();
+        if !directories.is_empty() {
+            write!(f, "\n\nDirectories with the most errors")?;
+            for (directory, counts) in directories {
+                write!(
+                    f,
+                    "\n{directory}: {}% errors ({} of {} links)",
+                    counts.error_percentage(),
+                    counts.errors,
+                    counts.total
+                )?;
+            }
+        }
+
+        // Suggestions for inputs without errors, e.g. for permanent redirects
+        for (source, suggestions) in &stats.suggestion_map {
+            if stats.error_map.contains_key(source) {
+                continue;
+            }
+            write!(f, "\n\nSuggestions in {source}")?;
+            for suggestion in suggestions {
+                write!(f, "\n{suggestion}")?;
+            }
+        }
+
+        for (source, responses) in &stats.generated_error_map {
+            write!(f, "\n\nErrors in generated regions of {source}")?;
+            for response in responses {
+                write!(
+                    f,
+                    "\n{}",
+                    response_formatter.format_detailed_response(response)
+                )?;
+            }
+        }
+
+        for (source, warnings) in &stats.warning_map {
+            write!(f, "\n\nWarnings in {source}")?;
+            for warning in warnings {
+                write!(f, "\n{warning}")?;
+            }
+        }
+
+        for (source, issues) in &stats.reference_issues {
+            write!(f, "\n\nReference issues in {source}")?;
+            for issue in issues {
+                write!(f, "\n{issue}")?;
+            }
+        }
+
+        if !stats.skipped_binaries.is_empty() {
+            write!(f, "\n\nSkipped binaries")?;
+            for source in &stats.skipped_binaries {
+                write!(f, "\n{source}")?;
+            }
         }
 
         Ok(())
@@ -84,8 +202,8 @@ impl Detailed {
     }
 }
 
-impl StatsFormatter for Detailed {
-    fn format(&self, stats: ResponseStats) -> Result<Option<String>> {
+impl StatsFormatter<ResponseStats> for Detailed {
+    fn format(&self, stats: ResponseStats) -> Result<Option<String>, FormatterError> {
         let detailed = DetailedResponseStats {
             stats,
             mode: self.mode.clone(),
@@ -99,8 +217,10 @@ mod tests {
     use super::*;
     use crate::options::OutputMode;
     use http::StatusCode;
-    use lychee_lib::{InputSource, ResponseBody, Status, Uri};
+    use lychee_lib::{InputSource, RawUriSpan, Response, ResponseBody, Status, Uri};
     use std::collections::{HashMap, HashSet};
+    use std::num::NonZeroUsize;
+    use std::path::PathBuf;
     use url::Url;
 
     #[test]
@@ -108,11 +228,15 @@ mod tests {
         let err1 = ResponseBody {
             uri: Uri::try_from("https://github.com/mre/idiomatic-rust-doesnt-exist-man").unwrap(),
             status: Status::Ok(StatusCode::NOT_FOUND),
+            span: None,
+            label: None,
         };
 
         let err2 = ResponseBody {
             uri: Uri::try_from("https://github.com/mre/boom").unwrap(),
             status: Status::Ok(StatusCode::INTERNAL_SERVER_ERROR),
+            span: None,
+            label: None,
         };
 
         let mut error_map: HashMap<InputSource, HashSet<ResponseBody>> = HashMap::new();
@@ -125,16 +249,38 @@ mod tests {
             errors: 2,
             unknown: 0,
             excludes: 0,
+            skipped: 0,
             timeouts: 0,
             duration_secs: 0,
             unsupported: 0,
             redirects: 0,
             cached: 0,
+            warnings: 0,
+            generated_errors: 0,
             suggestion_map: HashMap::default(),
+            history_map: HashMap::default(),
             success_map: HashMap::default(),
             error_map,
             excluded_map: HashMap::default(),
+            warning_map: HashMap::default(),
+            generated_error_map: HashMap::default(),
+            host_map: HashMap::default(),
+            host_timings: HashMap::default(),
+            directory_map: HashMap::default(),
+            source_type_map: HashMap::default(),
+            tag_map: HashMap::default(),
+            skipped_binaries: HashSet::default(),
+            reference_issues: HashMap::default(),
             detailed_stats: true,
+            fail_on_redirects: false,
+            permanent_redirects: None,
+            default_branch: None,
+            master_branch_links: HashMap::default(),
+            host_stats: false,
+            directory_stats: false,
+            outcomes: Vec::new(),
+            record_outcomes: false,
+            tag_max_errors: HashMap::default(),
         };
 
         let formatter = Detailed::new(OutputMode::Plain);
@@ -154,4 +300,56 @@ mod tests {
             .contains("https://github.com/mre/idiomatic-rust-doesnt-exist-man | 404 Not Found"));
         assert!(result.contains("https://github.com/mre/boom | 500 Internal Server Error"));
     }
+
+    #[test]
+    fn test_detailed_formatter_span() {
+        let err = ResponseBody {
+            uri: Uri::try_from("https://github.com/mre/boom").unwrap(),
+            status: Status::Ok(StatusCode::NOT_FOUND),
+            span: Some(RawUriSpan {
+                line: NonZeroUsize::new(42).unwrap(),
+                column: NonZeroUsize::new(17),
+            }),
+            label: None,
+        };
+
+        let mut error_map: HashMap<InputSource, HashSet<ResponseBody>> = HashMap::new();
+        let source = InputSource::FsPath(PathBuf::from("README.md"));
+        error_map.insert(source, HashSet::from_iter(vec![err]));
+
+        let stats = ResponseStats {
+            total: 1,
+            errors: 1,
+            error_map,
+            ..ResponseStats::default()
+        };
+
+        let formatter = Detailed::new(OutputMode::Plain);
+        let result = formatter.format(stats).unwrap().unwrap();
+
+        assert!(result.contains(
+            "[404] https://github.com/mre/boom | 404 Not Found: Not Found (README.md:42:17)"
+        ));
+    }
+
+    #[test]
+    fn test_detailed_formatter_source_types() {
+        let mut stats = ResponseStats::default();
+        for source in [
+            InputSource::Stdin,
+            InputSource::FsPath(PathBuf::from("README.md")),
+        ] {
+            stats.add(Response::new(
+                Uri::try_from("https://example.com").unwrap(),
+                Status::Ok(StatusCode::OK),
+                source,
+            ));
+        }
+
+        let formatter = Detailed::new(OutputMode::Plain);
+        let result = formatter.format(stats).unwrap().unwrap();
+        assert!(
+            result.contains("Inputs by type\nfile: 1 total, 0 errors\nstdin: 1 total, 0 errors")
+        );
+    }
 }
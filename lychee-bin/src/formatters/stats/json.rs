@@ -1,6 +1,6 @@
 use anyhow::{Context, Result};
 
-use super::StatsFormatter;
+use super::{FormatterError, StatsFormatter};
 use crate::stats::ResponseStats;
 
 pub(crate) struct Json;
@@ -11,11 +11,10 @@ impl Json {
     }
 }
 
-impl StatsFormatter for Json {
+impl StatsFormatter<ResponseStats> for Json {
     /// Format stats as JSON object
-    fn format(&self, stats: ResponseStats) -> Result<Option<String>> {
-        serde_json::to_string_pretty(&stats)
-            .map(Some)
-            .context("Cannot format stats as JSON")
+    fn format(&self, stats: ResponseStats) -> Result<Option<String>, FormatterError> {
+        let json = serde_json::to_string_pretty(&stats).context("Cannot format stats as JSON")?;
+        Ok(Some(json))
     }
 }
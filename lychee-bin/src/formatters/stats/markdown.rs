@@ -3,7 +3,7 @@ use std::{
     fmt::{self, Display},
 };
 
-use super::StatsFormatter;
+use super::{FormatterError, StatsFormatter};
 use anyhow::Result;
 use http::StatusCode;
 use lychee_lib::{InputSource, ResponseBody, Status};
@@ -13,7 +13,8 @@ use tabled::{
     Table, Tabled,
 };
 
-use crate::stats::ResponseStats;
+use crate::archive::{HistoricalState, HistoricalStatus};
+use crate::stats::{LinkCounts, ResponseStats};
 
 #[derive(Tabled)]
 struct StatsTableEntry {
@@ -45,6 +46,10 @@ fn stats_table(stats: &ResponseStats) -> String {
             status: "\u{1f47b} Excluded",
             count: stats.excludes,
         },
+        StatsTableEntry {
+            status: "\u{23e9} Skipped",
+            count: stats.skipped,
+        },
         StatsTableEntry {
             status: "\u{2753} Unknown",
             count: stats.unknown,
@@ -62,6 +67,91 @@ fn stats_table(stats: &ResponseStats) -> String {
         .to_string()
 }
 
+#[derive(Tabled)]
+struct HostsTableEntry<'a> {
+    #[tabled(rename = "Host")]
+    host: &'a str,
+    #[tabled(rename = "Total")]
+    total: usize,
+    #[tabled(rename = "Errors")]
+    errors: usize,
+}
+
+fn hosts_table(stats: &ResponseStats) -> String {
+    let mut hosts: Vec<_> = stats
+        .host_map
+        .iter()
+        .map(|(host, host_stats)| HostsTableEntry {
+            host,
+            total: host_stats.total,
+            errors: host_stats.errors,
+        })
+        .collect();
+    hosts.sort_by_key(|entry| entry.host);
+
+    Table::new(hosts)
+        .with(Modify::new(Segment::all()).with(Alignment::left()))
+        .with(Style::markdown())
+        .to_string()
+}
+
+#[derive(Tabled)]
+struct HostTimingsTableEntry<'a> {
+    #[tabled(rename = "Host")]
+    host: &'a str,
+    #[tabled(rename = "Warm-up")]
+    warm_up: String,
+    #[tabled(rename = "Per request")]
+    mean_request: String,
+}
+
+fn host_timings_table(stats: &ResponseStats) -> String {
+    let millis = |ms: Option<u64>| ms.map(|ms| format!("{ms}ms")).unwrap_or_default();
+    let mut hosts: Vec<_> = stats
+        .host_timings
+        .iter()
+        .map(|(host, timings)| HostTimingsTableEntry {
+            host,
+            warm_up: millis(timings.warm_up_ms),
+            mean_request: millis(timings.mean_request_ms),
+        })
+        .collect();
+    hosts.sort_by_key(|entry| entry.host);
+
+    Table::new(hosts)
+        .with(Modify::new(Segment::all()).with(Alignment::left()))
+        .with(Style::markdown())
+        .to_string()
+}
+
+#[derive(Tabled)]
+struct DirectoriesTableEntry<'a> {
+    #[tabled(rename = "Directory")]
+    directory: &'a str,
+    #[tabled(rename = "Total")]
+    total: usize,
+    #[tabled(rename = "Errors")]
+    errors: usize,
+    #[tabled(rename = "Error rate")]
+    error_rate: String,
+}
+
+fn directories_table(directories: Vec<(&str, &LinkCounts)>) -> String {
+    let directories = directories
+        .into_iter()
+        .map(|(directory, counts)| DirectoriesTableEntry {
+            directory,
+            total: counts.total,
+            errors: counts.errors,
+            error_rate: format!("{}%", counts.error_percentage()),
+        });
+
+    Table::new(directories)
+        .with(Modify::new(Segment::all()).with(Alignment::left()))
+        .with(Style::markdown())
+        .to_string()
+}
+
 /// Helper function to format single response body as markdown
 ///
 /// Optional details get added if available.
@@ -73,7 +163,7 @@ fn markdown_response(response: &ResponseBody) -> Result<String> {
         response.uri,
     );
 
-    if let Status::Ok(StatusCode::OK) = response.status {
+    if response.label.is_none() && matches!(response.status, Status::Ok(StatusCode::OK)) {
         // Don't print anything else if the status code is 200.
         // The output gets too verbose then.
         return Ok(formatted);
@@ -82,14 +172,40 @@ fn markdown_response(response: &ResponseBody) -> Result<String> {
     // Add a separator between the URI and the additional details below.
     // Note: To make the links clickable in some terminals,
     // we add a space before the separator.
-    write!(formatted, " | {}", response.status)?;
-
+    let mut status = response.status.to_string();
     if let Some(details) = response.status.details() {
-        write!(formatted, ": {details}")?;
+        write!(status, ": {details}")?;
+    }
+    match &response.label {
+        Some(label) => write!(formatted, " | {label} ({status})")?,
+        None => write!(formatted, " | {status}")?,
     }
     Ok(formatted)
 }
 
+/// Format the state of a broken link at the date given with `--as-of`
+fn markdown_history(status: &HistoricalStatus) -> String {
+    match &status.state {
+        HistoricalState::AliveThen {
+            snapshot,
+            timestamp,
+        } => format!(
+            "* <{}> was alive then ([snapshot of {timestamp}]({snapshot}))",
+            status.url
+        ),
+        HistoricalState::BrokenThen {
+            snapshot,
+            timestamp,
+        } => format!(
+            "* <{}> was already broken then ([snapshot of {timestamp}]({snapshot}))",
+            status.url
+        ),
+        HistoricalState::NeverArchived => {
+            format!("* <{}> was not archived around that date", status.url)
+        }
+    }
+}
+
 struct MarkdownResponseStats(ResponseStats);
 
 impl Display for MarkdownResponseStats {
@@ -111,6 +227,54 @@ impl Display for MarkdownResponseStats {
             ))
         })?;
 
+        write_stats_per_input(f, "In the web archive", &stats.history_map, |status| {
+            Ok(markdown_history(status))
+        })?;
+
+        if !stats.host_map.is_empty() {
+            writeln!(f, "\n## Hosts\n")?;
+            writeln!(f, "{}", hosts_table(stats))?;
+        }
+
+        if !stats.host_timings.is_empty() {
+            writeln!(f, "\n## Host timings\n")?;
+            writeln!(f, "{}", host_timings_table(stats))?;
+        }
+
+        let directories = stats.worst_directories();
+        if !directories.is_empty() {
+            writeln!(f, "\n## Directories with the most errors\n")?;
+            writeln!(f, "{}", directories_table(directories))?;
+        }
+
+        write_stats_per_input(
+            f,
+            "Errors in generated regions",
+            &stats.generated_error_map,
+            |response| markdown_response(response).map_err(|_e| fmt::Error),
+        )?;
+
+        write_stats_per_input(f, "Warnings", &stats.warning_map, |warning| {
+            Ok(format!("* {warning}"))
+        })?;
+
+        if !stats.reference_issues.is_empty() {
+            writeln!(f, "\n## Reference issues per input")?;
+        }
+        for (source, issues) in &stats.reference_issues {
+            writeln!(f, "\n### Reference issues in {source}\n")?;
+            for issue in issues {
+                writeln!(f, "* {issue}")?;
+            }
+        }
+
+        if !stats.skipped_binaries.is_empty() {
+            writeln!(f, "\n## Skipped binaries\n")?;
+            for source in &stats.skipped_binaries {
+                writeln!(f, "* {source}")?;
+            }
+        }
+
         Ok(())
     }
 }
@@ -146,8 +310,8 @@ impl Markdown {
     }
 }
 
-impl StatsFormatter for Markdown {
-    fn format(&self, stats: ResponseStats) -> Result<Option<String>> {
+impl StatsFormatter<ResponseStats> for Markdown {
+    fn format(&self, stats: ResponseStats) -> Result<Option<String>, FormatterError> {
         let markdown = MarkdownResponseStats(stats);
         Ok(Some(markdown.to_string()))
     }
@@ -156,11 +320,14 @@ impl StatsFormatter for Markdown {
 #[cfg(test)]
 mod tests {
 
+    use std::path::PathBuf;
+
     use http::StatusCode;
     use lychee_lib::{CacheStatus, InputSource, Response, ResponseBody, Status, Uri};
     use reqwest::Url;
 
     use crate::archive::Suggestion;
+    use crate::stats::HostTimings;
 
     use super::*;
 
@@ -169,6 +336,8 @@ mod tests {
         let response = ResponseBody {
             uri: Uri::try_from("http://example.com").unwrap(),
             status: Status::Ok(StatusCode::OK),
+            span: None,
+            label: None,
         };
         let markdown = markdown_response(&response).unwrap();
         assert_eq!(
@@ -181,7 +350,9 @@ mod tests {
     fn test_markdown_response_cached_ok() {
         let response = ResponseBody {
             uri: Uri::try_from("http://example.com").unwrap(),
-            status: Status::Cached(CacheStatus::Ok(200)),
+            status: Status::Cached(CacheStatus::Ok(200), None),
+            span: None,
+            label: None,
         };
         let markdown = markdown_response(&response).unwrap();
         assert_eq!(
@@ -194,7 +365,9 @@ mod tests {
     fn test_markdown_response_cached_err() {
         let response = ResponseBody {
             uri: Uri::try_from("http://example.com").unwrap(),
-            status: Status::Cached(CacheStatus::Error(Some(400))),
+            status: Status::Cached(CacheStatus::Error(Some(400)), None),
+            span: None,
+            label: None,
         };
         let markdown = markdown_response(&response).unwrap();
         assert_eq!(
@@ -214,17 +387,103 @@ mod tests {
 | ⏳ Timeouts   | 0     |
 | 🔀 Redirected | 0     |
 | 👻 Excluded   | 0     |
+| ⏩ Skipped    | 0     |
 | ❓ Unknown    | 0     |
 | 🚫 Errors     | 0     |";
         assert_eq!(table, expected.to_string());
     }
 
+    #[test]
+    fn test_render_hosts() {
+        let mut stats = ResponseStats {
+            host_stats: true,
+            ..ResponseStats::default()
+        };
+        for (uri, status) in [
+            ("https://example.com/a", Status::Ok(StatusCode::OK)),
+            (
+                "https://example.com/b",
+                Status::Cached(CacheStatus::Error(Some(404)), None),
+            ),
+            ("http://127.0.0.1", Status::Ok(StatusCode::OK)),
+        ] {
+            stats.add(Response::new(
+                Uri::try_from(uri).unwrap(),
+                status,
+                InputSource::Stdin,
+            ));
+        }
+        let expected = "| Host        | Total | Errors |
+|-------------|-------|--------|
+| 127.0.0.1   | 1     | 0      |
+| example.com | 2     | 1      |";
+        assert_eq!(hosts_table(&stats), expected);
+    }
+
+    #[test]
+    fn test_render_host_timings() {
+        let stats = ResponseStats {
+            host_timings: HashMap::from([
+                (
+                    "example.com".to_string(),
+                    HostTimings {
+                        warm_up_ms: Some(120),
+                        mean_request_ms: Some(45),
+                    },
+                ),
+                (
+                    "127.0.0.1".to_string(),
+                    HostTimings {
+                        warm_up_ms: None,
+                        mean_request_ms: Some(3),
+                    },
+                ),
+            ]),
+            ..ResponseStats::default()
+        };
+        let expected = "| Host        | Warm-up | Per request |
+|-------------|---------|-------------|
+| 127.0.0.1   |         | 3ms         |
+| example.com | 120ms   | 45ms        |";
+        assert_eq!(host_timings_table(&stats), expected);
+    }
+
+    #[test]
+    fn test_render_directories() {
+        let mut stats = ResponseStats {
+            directory_stats: true,
+            ..ResponseStats::default()
+        };
+        for (file, status) in [
+            ("docs/a.md", Status::Ok(StatusCode::OK)),
+            (
+                "docs/b.md",
+                Status::Cached(CacheStatus::Error(Some(404)), None),
+            ),
+            (
+                "docs/c.md",
+                Status::Cached(CacheStatus::Error(Some(404)), None),
+            ),
+            ("blog/a.md", Status::Ok(StatusCode::OK)),
+        ] {
+            stats.add(Response::new(
+                Uri::try_from("https://example.com").unwrap(),
+                status,
+                InputSource::FsPath(PathBuf::from(file)),
+            ));
+        }
+        let expected = "| Directory | Total | Errors | Error rate |
+|-----------|-------|--------|------------|
+| docs      | 3     | 2      | 66%        |";
+        assert_eq!(directories_table(stats.worst_directories()), expected);
+    }
+
     #[test]
     fn test_render_summary() {
         let mut stats = ResponseStats::default();
         let response = Response::new(
             Uri::try_from("http://127.0.0.1").unwrap(),
-            Status::Cached(CacheStatus::Error(Some(404))),
+            Status::Cached(CacheStatus::Error(Some(404)), None),
             InputSource::Stdin,
         );
         stats.add(response);
@@ -246,6 +505,7 @@ mod tests {
 | ⏳ Timeouts   | 0     |
 | 🔀 Redirected | 0     |
 | 👻 Excluded   | 0     |
+| ⏩ Skipped    | 0     |
 | ❓ Unknown    | 0     |
 | 🚫 Errors     | 1     |
 
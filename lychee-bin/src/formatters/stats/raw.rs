@@ -1,6 +1,6 @@
 use anyhow::Result;
 
-use super::StatsFormatter;
+use super::{FormatterError, StatsFormatter};
 use crate::stats::ResponseStats;
 pub(crate) struct Raw;
 
@@ -10,9 +10,9 @@ impl Raw {
     }
 }
 
-impl StatsFormatter for Raw {
+impl StatsFormatter<ResponseStats> for Raw {
     /// Don't print stats in raw mode
-    fn format(&self, _stats: ResponseStats) -> Result<Option<String>> {
+    fn format(&self, _stats: ResponseStats) -> Result<Option<String>, FormatterError> {
         Ok(None)
     }
 }
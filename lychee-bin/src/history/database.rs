@@ -0,0 +1,287 @@
+//! The SQLite database of `--history`
+
+use std::fmt::{self, Display};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OpenFlags};
+use url::Url;
+
+use super::{Outcome, DEFAULT_HISTORY_FILE};
+use crate::options::HistoryArgs;
+use crate::redact;
+use crate::time::Timestamp;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    timestamp INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS outcomes (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    url TEXT NOT NULL,
+    source TEXT NOT NULL,
+    status TEXT NOT NULL,
+    success INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS outcomes_url ON outcomes(url);
+";
+
+/// Outcome of a link in a recorded run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RecordedOutcome {
+    pub(crate) timestamp: Timestamp,
+    pub(crate) source: String,
+    pub(crate) status: String,
+    pub(crate) success: bool,
+}
+
+impl Display for RecordedOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(self.timestamp);
+        let result = if self.success { "ok" } else { "failed" };
+        write!(
+            f,
+            "{} {result} ({}) in {}",
+            humantime::format_rfc3339_seconds(time),
+            self.status,
+            self.source
+        )
+    }
+}
+
+/// A link which both succeeded and failed in the recorded runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FlakyLink {
+    pub(crate) url: String,
+    /// Number of runs which checked the link
+    pub(crate) runs: u64,
+    /// Number of runs in which the link failed
+    pub(crate) failures: u64,
+}
+
+impl Display for FlakyLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (failed in {} of {} runs)",
+            self.url, self.failures, self.runs
+        )
+    }
+}
+
+/// The history database
+#[derive(Debug)]
+pub(crate) struct History {
+    connection: Connection,
+}
+
+impl History {
+    /// Open the database at `path` for recording, creating it if necessary
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        let connection = Connection::open(path)
+            .with_context(|| format!("Cannot open history `{}`", path.display()))?;
+        connection
+            .execute_batch(SCHEMA)
+            .with_context(|| format!("Cannot create tables in history `{}`", path.display()))?;
+        Ok(Self { connection })
+    }
+
+    /// Open an existing database at `path` for queries
+    pub(crate) fn open_read_only(path: &Path) -> Result<Self> {
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .with_context(|| {
+                format!(
+                    "Cannot open history `{}`, record one with `--history`",
+                    path.display()
+                )
+            })?;
+        Ok(Self { connection })
+    }
+
+    /// Append a run at `timestamp` with the given outcomes
+    pub(crate) fn record(&mut self, timestamp: Timestamp, outcomes: &[Outcome]) -> Result<()> {
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "INSERT INTO runs (timestamp) VALUES (?1)",
+            params![timestamp],
+        )?;
+        let run_id = transaction.last_insert_rowid();
+        {
+            let mut insert = transaction.prepare(
+                "INSERT INTO outcomes (run_id, url, source, status, success)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for outcome in outcomes {
+                insert.execute(params![
+                    run_id,
+                    outcome.url,
+                    outcome.source,
+                    outcome.status,
+                    outcome.success
+                ])?;
+            }
+        }
+        transaction.commit().context("Cannot record run in history")
+    }
+
+    /// All recorded outcomes of `url`, oldest first
+    pub(crate) fn outcomes(&self, url: &str) -> Result<Vec<RecordedOutcome>> {
+        // Stored URLs are normalized, e.g. `https://example.com/`, and
+        // redacted
+        let url = redact::redact(&Url::parse(url).map_or_else(|_| url.to_string(), String::from));
+        let mut query = self.connection.prepare(
+            "SELECT runs.timestamp, outcomes.source, outcomes.status, outcomes.success
+             FROM outcomes JOIN runs ON runs.id = outcomes.run_id
+             WHERE outcomes.url = ?1
+             ORDER BY runs.timestamp, outcomes.source",
+        )?;
+        let outcomes = query
+            .query_map(params![url], |row| {
+                Ok(RecordedOutcome {
+                    timestamp: row.get(0)?,
+                    source: row.get(1)?,
+                    status: row.get(2)?,
+                    success: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(outcomes)
+    }
+
+    /// Links which both succeeded and failed in the recorded runs, most
+    /// failures first
+    pub(crate) fn flaky(&self) -> Result<Vec<FlakyLink>> {
+        let mut query = self.connection.prepare(
+            "SELECT url,
+                    COUNT(DISTINCT run_id) AS runs,
+                    COUNT(DISTINCT CASE WHEN success = 0 THEN run_id END) AS failures
+             FROM outcomes
+             GROUP BY url
+             HAVING failures > 0 AND failures < runs
+             ORDER BY failures DESC, url",
+        )?;
+        let links = query
+            .query_map([], |row| {
+                Ok(FlakyLink {
+                    url: row.get(0)?,
+                    runs: row.get(1)?,
+                    failures: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(links)
+    }
+}
+
+/// Run `lychee history` and print the results
+pub(crate) fn query(path: Option<&Path>, args: &HistoryArgs) -> Result<()> {
+    let path = path.map_or_else(|| PathBuf::from(DEFAULT_HISTORY_FILE), Path::to_path_buf);
+    let history = History::open_read_only(&path)?;
+
+    if args.flaky {
+        let links = history.flaky()?;
+        if links.is_empty() {
+            println!("No flaky links in {}", path.display());
+        }
+        for link in links {
+            println!("{link}");
+        }
+    } else if let Some(url) = &args.url {
+        let outcomes = history.outcomes(url)?;
+        if outcomes.is_empty() {
+            println!("No recorded outcomes of {url}");
+        }
+        for outcome in outcomes {
+            println!("{outcome}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn outcome(url: &str, status: &str, success: bool) -> Outcome {
+        Outcome {
+            url: url.to_string(),
+            source: "README.md".to_string(),
+            status: status.to_string(),
+            success,
+        }
+    }
+
+    #[test]
+    fn test_history() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("history.sqlite");
+
+        let mut history = History::open(&path).unwrap();
+        history
+            .record(
+                1,
+                &[
+                    outcome("https://example.com/", "200", true),
+                    outcome("https://example.com/flaky", "200", true),
+                    outcome("https://example.com/broken", "404", false),
+                ],
+            )
+            .unwrap();
+        history
+            .record(
+                2,
+                &[
+                    outcome("https://example.com/", "200", true),
+                    outcome("https://example.com/flaky", "TIMEOUT", false),
+                    outcome("https://example.com/broken", "404", false),
+                    outcome("https://example.com/?token=REDACTED", "200", true),
+                ],
+            )
+            .unwrap();
+        drop(history);
+
+        let history = History::open_read_only(&path).unwrap();
+        assert_eq!(
+            history.flaky().unwrap(),
+            [FlakyLink {
+                url: "https://example.com/flaky".to_string(),
+                runs: 2,
+                failures: 1,
+            }]
+        );
+
+        let outcomes = history.outcomes("https://example.com/flaky").unwrap();
+        assert_eq!(
+            outcomes
+                .iter()
+                .map(|outcome| (outcome.timestamp, outcome.success))
+                .collect::<Vec<_>>(),
+            [(1, true), (2, false)]
+        );
+        assert_eq!(
+            outcomes[1].to_string(),
+            "1970-01-01T00:00:02Z failed (TIMEOUT) in README.md"
+        );
+        // URLs are normalized before the lookup
+        assert_eq!(history.outcomes("https://example.com").unwrap().len(), 2);
+        // Stored URLs are redacted, so the lookup is redacted as well
+        assert_eq!(
+            history
+                .outcomes("https://example.com/?token=secret")
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_missing_history() {
+        let dir = tempdir().unwrap();
+        assert!(History::open_read_only(&dir.path().join("missing.sqlite")).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+//! Outcomes of links across runs (`--history`).
+//!
+//! Each run with `--history` appends the outcome of every checked link to a
+//! SQLite database. `lychee history` queries the database, e.g. to find links
+//! which fail every now and then and are worth excluding or fixing at the
+//! source.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use lychee_lib::{Response, Status};
+
+use crate::redact;
+use crate::time::Timestamp;
+
+#[cfg(feature = "history")]
+mod database;
+
+#[cfg(feature = "history")]
+pub(crate) use database::query;
+
+/// Database which `lychee history` reads if `--history` is not set
+pub(crate) const DEFAULT_HISTORY_FILE: &str = ".lycheehistory.sqlite";
+
+/// Outcome of checking a link in the current run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Outcome {
+    pub(crate) url: String,
+    pub(crate) source: String,
+    pub(crate) status: String,
+    pub(crate) success: bool,
+}
+
+impl Outcome {
+    /// The outcome of `response`, or `None` if the link was not checked in
+    /// this run, e.g. because it was excluded or cached
+    pub(crate) fn new(response: &Response, success: bool) -> Option<Self> {
+        let status = response.status();
+        if status.is_excluded()
+            || status.is_unsupported()
+            || status.is_skipped()
+            || matches!(status, Status::Cached(..))
+        {
+            return None;
+        }
+        Some(Self {
+            url: redact::redact(response.body().uri.as_str()),
+            source: redact::redact(&response.source().to_string()),
+            status: status.code_as_string(),
+            success,
+        })
+    }
+}
+
+/// Append a run at `timestamp` with the given outcomes to the database at
+/// `path`, creating it if necessary
+#[cfg(feature = "history")]
+pub(crate) async fn record(
+    path: PathBuf,
+    timestamp: Timestamp,
+    outcomes: Vec<Outcome>,
+) -> Result<()> {
+    // SQLite blocks, so keep it off the async runtime
+    tokio::task::spawn_blocking(move || {
+        database::History::open(&path)?.record(timestamp, &outcomes)
+    })
+    .await?
+}
+
+#[cfg(not(feature = "history"))]
+pub(crate) async fn record(
+    _path: PathBuf,
+    _timestamp: Timestamp,
+    _outcomes: Vec<Outcome>,
+) -> Result<()> {
+    anyhow::bail!("lychee was built without the `history` feature")
+}
+
+/// Run `lychee history` and print the results
+#[cfg(not(feature = "history"))]
+pub(crate) fn query(
+    _path: Option<&std::path::Path>,
+    _args: &crate::options::HistoryArgs,
+) -> Result<()> {
+    anyhow::bail!("`lychee history` requires lychee to be built with the `history` feature")
+}
@@ -0,0 +1,166 @@
+//! Commands which are run before and after checking links (`[hooks]` table of
+//! the config file), e.g. to send notifications or to clean up.
+//!
+//! The commands are run with `sh -c` (`cmd /C` on Windows). Once the run is
+//! done, its outcome is passed to the commands in environment variables:
+//!
+//! - `LYCHEE_EXIT_CODE`: exit code of lychee
+//! - `LYCHEE_REPORT_FILE`: path of the report of `--report-file` (if any)
+//! - `LYCHEE_OUTPUT`: path of the output of `--output` (if any)
+//! - `LYCHEE_TOTAL`, `LYCHEE_SUCCESSFUL`, `LYCHEE_ERRORS`, `LYCHEE_WARNINGS`:
+//!   numbers of links by outcome (if links were checked)
+//!
+//! Hooks are only taken from config files which are passed with `--config`.
+//! A `lychee.toml` in the current directory might belong to an untrusted
+//! checkout (e.g. of a pull request from a fork), so its hooks are ignored.
+
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use log::info;
+use serde::Deserialize;
+
+use crate::options::Config;
+use crate::report::Counts;
+
+/// Commands of the `[hooks]` table of the config file
+#[derive(Debug, Default, Clone, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Hooks {
+    /// Run before checking links. If it fails, no links are checked
+    pub(crate) before: Option<String>,
+    /// Run after checking links
+    pub(crate) after: Option<String>,
+    /// Run after checking links if the run failed, before `after`
+    pub(crate) on_failure: Option<String>,
+}
+
+impl Hooks {
+    /// Runs the `before` hook (if any)
+    ///
+    /// # Errors
+    ///
+    /// Fails if the command can't be started or exits with an error.
+    pub(crate) fn before(&self) -> Result<()> {
+        match &self.before {
+            Some(command) => run("before", command, &[]),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs the `on_failure` hook if `exit_code` is an error and the `after`
+    /// hook, with the outcome of the run in environment variables.
+    ///
+    /// The `after` hook is run even if the `on_failure` hook fails.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a command can't be started or exits with an error.
+    pub(crate) fn after(
+        &self,
+        cfg: &Config,
+        exit_code: i32,
+        counts: Option<&Counts>,
+    ) -> Result<()> {
+        let env = outcome(cfg, exit_code, counts);
+        let on_failure = match &self.on_failure {
+            Some(command) if exit_code != 0 => run("on_failure", command, &env),
+            _ => Ok(()),
+        };
+        let after = match &self.after {
+            Some(command) => run("after", command, &env),
+            None => Ok(()),
+        };
+        on_failure.and(after)
+    }
+}
+
+/// Environment variables with the outcome of the run
+fn outcome(cfg: &Config, exit_code: i32, counts: Option<&Counts>) -> Vec<(&'static str, String)> {
+    let mut env = vec![("LYCHEE_EXIT_CODE", exit_code.to_string())];
+    if let Some(path) = &cfg.report_file {
+        env.push(("LYCHEE_REPORT_FILE", path.display().to_string()));
+    }
+    if let Some(path) = &cfg.output {
+        env.push(("LYCHEE_OUTPUT", path.display().to_string()));
+    }
+    if let Some(counts) = counts {
+        env.extend([
+            ("LYCHEE_TOTAL", counts.total.to_string()),
+            ("LYCHEE_SUCCESSFUL", counts.successful.to_string()),
+            ("LYCHEE_ERRORS", counts.errors.to_string()),
+            ("LYCHEE_WARNINGS", counts.warnings.to_string()),
+        ]);
+    }
+    env
+}
+
+/// Runs the `command` of the hook `name` in a shell
+fn run(name: &str, command: &str, env: &[(&str, String)]) -> Result<()> {
+    info!("Running {name} hook: {command}");
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let status = shell
+        .arg(command)
+        .envs(env.iter().map(|(key, value)| (*key, value)))
+        .status()
+        .with_context(|| format!("Cannot run {name} hook `{command}`"))?;
+    if !status.success() {
+        bail!("The {name} hook `{command}` failed ({status})");
+    }
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::Hooks;
+    use crate::options::Config;
+
+    #[test]
+    fn test_hooks() {
+        let dir = tempdir().unwrap();
+        let log = dir.path().join("log");
+        let hooks = Hooks {
+            before: Some(format!("echo before >> {}", log.display())),
+            after: Some(format!("echo after $LYCHEE_EXIT_CODE >> {}", log.display())),
+            on_failure: Some(format!("echo failure >> {}", log.display())),
+        };
+
+        hooks.before().unwrap();
+        hooks.after(&Config::default(), 0, None).unwrap();
+        hooks.after(&Config::default(), 2, None).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&log).unwrap(),
+            "before\nafter 0\nfailure\nafter 2\n"
+        );
+
+        let failing = Hooks {
+            before: Some("exit 1".to_string()),
+            ..Hooks::default()
+        };
+        assert!(failing.before().is_err());
+    }
+
+    #[test]
+    fn test_after_runs_when_on_failure_fails() {
+        let dir = tempdir().unwrap();
+        let log = dir.path().join("log");
+        let hooks = Hooks {
+            after: Some(format!("echo after >> {}", log.display())),
+            on_failure: Some("exit 1".to_string()),
+            ..Hooks::default()
+        };
+
+        assert!(hooks.after(&Config::default(), 2, None).is_err());
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "after\n");
+    }
+}
@@ -0,0 +1,74 @@
+//! Custom labels of statuses (`[labels]` in the config file), e.g.
+//! `"403" = "requires VPN"`, which make recurring failures in a specific
+//! environment self-explanatory.
+//!
+//! Labels are reported in place of the status text in all output formats,
+//! while the status itself is kept.
+
+use std::collections::HashMap;
+
+use lychee_lib::Status;
+
+/// Custom labels by status code (e.g. `403`), by the short form of the
+/// status in the output (e.g. `TIMEOUT` or `ERROR`) or by the status text
+/// (e.g. `Cannot find fragment`). Keys are matched case-insensitively.
+#[derive(Debug, Default)]
+pub(crate) struct Labels(HashMap<String, String>);
+
+impl Labels {
+    pub(crate) fn new(labels: &HashMap<String, String>) -> Self {
+        Self(
+            labels
+                .iter()
+                .map(|(key, label)| (key.trim().to_lowercase(), label.clone()))
+                .collect(),
+        )
+    }
+
+    /// The label of `status`, if any. Labels of the status code take
+    /// precedence over labels of the status text.
+    pub(crate) fn label(&self, status: &Status) -> Option<String> {
+        if self.0.is_empty() {
+            return None;
+        }
+        [status.code_as_string(), status.to_string()]
+            .iter()
+            .find_map(|key| self.0.get(&key.to_lowercase()))
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+    use lychee_lib::{CacheStatus, ErrorKind, Uri};
+
+    use super::*;
+
+    #[test]
+    fn test_label() {
+        let labels = Labels::new(&HashMap::from([
+            ("403".to_string(), "requires VPN".to_string()),
+            ("timeout".to_string(), "slow intranet".to_string()),
+            (
+                "Cannot find fragment".to_string(),
+                "anchor was renamed".to_string(),
+            ),
+        ]));
+
+        let uri = Uri::try_from("https://example.com/#top").unwrap();
+        assert_eq!(
+            labels.label(&Status::Error(ErrorKind::InvalidFragment(uri))),
+            Some("anchor was renamed".to_string())
+        );
+        assert_eq!(
+            labels.label(&Status::Timeout(None)),
+            Some("slow intranet".to_string())
+        );
+        assert_eq!(
+            labels.label(&Status::Cached(CacheStatus::Error(Some(403)), None)),
+            Some("requires VPN".to_string())
+        );
+        assert_eq!(labels.label(&Status::Ok(StatusCode::OK)), None);
+    }
+}
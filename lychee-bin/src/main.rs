@@ -59,43 +59,80 @@
 #![deny(missing_docs)]
 
 use std::fs::{self, File};
-use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::io::{self, BufReader, ErrorKind, Read, Write};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
 
-use anyhow::{bail, Context, Error, Result};
-use clap::Parser;
+use anyhow::{anyhow, Context, Error, Result};
+use clap::{CommandFactory, FromArgMatches};
 use commands::CommandParams;
 use formatters::{get_stats_formatter, log::init_logging};
 use log::{error, info, warn};
+use secrecy::ExposeSecret;
+use tokio::sync::watch;
 
 #[cfg(feature = "native-tls")]
 use openssl_sys as _; // required for vendored-openssl feature
 
+use hooks::Hooks;
 use options::LYCHEE_CONFIG_FILE;
 use ring as _; // required for apple silicon
 
+use lychee_lib::collector::DEFAULT_STREAM_THRESHOLD;
+use lychee_lib::filter::parse_patterns;
+use lychee_lib::AnchorCache;
 use lychee_lib::BasicAuthExtractor;
+use lychee_lib::CheckObserver;
 use lychee_lib::Collector;
 use lychee_lib::CookieJar;
+use lychee_lib::FileExtensions;
+use lychee_lib::GeneratedMarkers;
+use lychee_lib::Netrc;
+use lychee_lib::SameDomainPolicy;
+use lychee_lib::{DEFAULT_GENERATED_END, DEFAULT_GENERATED_START};
 
+mod anonymize;
 mod archive;
 mod cache;
 mod client;
 mod commands;
+mod conflicts;
+mod default_branch;
+mod diff;
 mod formatters;
+mod history;
+mod hooks;
+mod labels;
+mod metrics;
 mod options;
 mod parse;
+mod permalink;
+mod preflight;
+mod progress;
+mod progress_socket;
+mod redact;
+mod report;
 mod stats;
 mod time;
+mod validate;
 mod verbosity;
+mod watch;
 
 use crate::formatters::color;
-use crate::formatters::duration::Duration;
 use crate::{
-    cache::{Cache, StoreExt},
+    anonymize::Anonymizer,
+    cache::{Cache, CacheBackend, ErrorCategory, MaxAge},
+    diff::ReportDiff,
     formatters::stats::StatsFormatter,
-    options::{Config, LycheeOptions, LYCHEE_CACHE_FILE, LYCHEE_IGNORE_FILE},
+    metrics::Metrics,
+    options::{
+        Command, Config, DumpFilter, LycheeOptions, OptionSource, ReportSection, ServeArgs,
+        StatsFormat, LYCHEE_IGNORE_FILE,
+    },
+    parse::{parse_duration_secs, parse_tag_max_errors},
+    report::{Counts, Report},
+    stats::ResponseStats,
 };
 
 /// A C-like enum that can be cast to `i32` and used as process exit code.
@@ -110,10 +147,44 @@ enum ExitCode {
     UnexpectedFailure = 1,
     LinkCheckFailure = 2,
     ConfigFile = 3,
+    // Failures of a single class (with `--granular-exit-codes`)
+    Timeout = 4,
+    DnsFailure = 5,
+    ClientError = 6,
+    ServerError = 7,
+    // The network connection failed the self-test (`--preflight`)
+    Offline = 8,
+    // Links were skipped because the maximum runtime (`--max-runtime`) was
+    // reached, but all checked links succeeded
+    Incomplete = 9,
+    // Like shells, 128 + SIGINT
+    Interrupted = 130,
 }
 
-/// Ignore lines starting with this marker in `.lycheeignore` files
-const LYCHEEIGNORE_COMMENT_MARKER: &str = "#";
+impl ExitCode {
+    /// Whether links failed the check
+    const fn is_link_check_failure(self) -> bool {
+        matches!(
+            self,
+            Self::LinkCheckFailure
+                | Self::Timeout
+                | Self::DnsFailure
+                | Self::ClientError
+                | Self::ServerError
+        )
+    }
+}
+
+impl From<ErrorCategory> for ExitCode {
+    fn from(category: ErrorCategory) -> Self {
+        match category {
+            ErrorCategory::Timeout => Self::Timeout,
+            ErrorCategory::Dns => Self::DnsFailure,
+            ErrorCategory::ClientError => Self::ClientError,
+            ErrorCategory::ServerError => Self::ServerError,
+        }
+    }
+}
 
 fn main() -> Result<()> {
     // std::process::exit doesn't guarantee that all destructors will be run,
@@ -124,47 +195,69 @@ fn main() -> Result<()> {
     std::process::exit(exit_code);
 }
 
-/// Read lines from file; ignore empty lines
+/// Read lines from file; ignore empty lines and comments
 fn read_lines(file: &File) -> Result<Vec<String>> {
-    let lines: Vec<_> = BufReader::new(file).lines().collect::<Result<_, _>>()?;
-    Ok(lines
-        .into_iter()
-        .filter(|line| {
-            !line.is_empty() && !line.trim_start().starts_with(LYCHEEIGNORE_COMMENT_MARKER)
-        })
-        .collect())
+    let mut content = String::new();
+    BufReader::new(file).read_to_string(&mut content)?;
+    Ok(parse_patterns(&content))
 }
 
 /// Merge all provided config options into one This includes a potential config
 /// file, command-line- and environment variables
-fn load_config() -> Result<LycheeOptions> {
-    let mut opts = LycheeOptions::parse();
-
-    init_logging(&opts.config.verbose, &opts.config.mode);
-
-    // Load a potentially existing config file and merge it into the config from
-    // the CLI
-    if let Some(config_file) = &opts.config_file {
-        match Config::load_from_file(config_file) {
-            Ok(c) => opts.config.merge(c),
-            Err(e) => {
-                bail!(
-                    "Cannot load configuration file `{}`: {e:?}",
-                    config_file.display()
-                );
-            }
-        }
-    } else {
+fn load_config(opts: &mut LycheeOptions) -> Result<()> {
+    init_logging(
+        &opts.config.verbose,
+        &opts.config.mode,
+        opts.config.trace_format,
+    );
+
+    // Load potentially existing config files and merge them into the config
+    // from the CLI. Options which are already set take precedence, so later
+    // config files are merged first, each followed by the configs it extends.
+    if opts.config_file.is_empty() {
         // If no config file was explicitly provided, we try to load the default
         // config file from the current directory, but it's not an error if it
         // doesn't exist.
-        if let Ok(c) = Config::load_from_file(&PathBuf::from(LYCHEE_CONFIG_FILE)) {
-            opts.config.merge(c);
+        let config_file = PathBuf::from(LYCHEE_CONFIG_FILE);
+        if let Ok(configs) = Config::load_with_bases(&config_file) {
+            for (path, mut c) in configs {
+                // The config file might come from an untrusted checkout,
+                // so its commands are never run
+                if c.hooks != Hooks::default() {
+                    warn!(
+                        "Ignoring `[hooks]` of `{}`; hooks are only run from config files passed with `--config`",
+                        path.display()
+                    );
+                    c.hooks = Hooks::default();
+                }
+                opts.config.merge(c, &OptionSource::ConfigFile(path));
+            }
+        }
+    } else {
+        for config_file in opts.config_file.iter().rev() {
+            let configs = Config::load_with_bases(config_file).map_err(|e| {
+                anyhow!(
+                    "Cannot load configuration file `{}`: {e:?}",
+                    config_file.display()
+                )
+            })?;
+            for (path, c) in configs {
+                opts.config.merge(c, &OptionSource::ConfigFile(path));
+            }
         }
     }
 
+    // Presets only fill in options which are still unset
+    opts.config.merge_presets();
+    redact::init(&opts.config);
+
+    // Collect all invalid regexes to report them at once
+    let mut invalid_regexes = validate::regexes(&opts.config);
+
     if let Ok(lycheeignore) = File::open(LYCHEE_IGNORE_FILE) {
-        opts.config.exclude.append(&mut read_lines(&lycheeignore)?);
+        let mut patterns = read_lines(&lycheeignore)?;
+        invalid_regexes.extend(validate::patterns(LYCHEE_IGNORE_FILE, &patterns));
+        opts.config.exclude.append(&mut patterns);
     }
 
     // TODO: Remove this warning and the parameter with 1.0
@@ -177,13 +270,19 @@ fn load_config() -> Result<LycheeOptions> {
         warn!("WARNING: `--exclude-mail` is deprecated and will soon be removed; E-Mail is no longer checked by default. Use `--include-mail` to enable E-Mail checking.");
     }
 
+    if opts.config.only.is_some() && !opts.config.dump {
+        warn!("WARNING: `--only` has no effect without `--dump`.");
+    }
+
     // Load excludes from file
     for path in &opts.config.exclude_file {
         let file = File::open(path)?;
-        opts.config.exclude.append(&mut read_lines(&file)?);
+        let mut patterns = read_lines(&file)?;
+        invalid_regexes.extend(validate::patterns(path, &patterns));
+        opts.config.exclude.append(&mut patterns);
     }
 
-    Ok(opts)
+    validate::check(&invalid_regexes)
 }
 
 /// Load cookie jar from path (if exists)
@@ -194,65 +293,125 @@ fn load_cookie_jar(cfg: &Config) -> Result<Option<CookieJar>> {
     }
 }
 
-#[must_use]
+/// The max age of cache entries, which can differ per error category
+fn cache_max_age(cfg: &Config) -> MaxAge {
+    MaxAge::new(cfg.max_cache_age)
+        .category(ErrorCategory::Dns, cfg.cache_max_age_dns)
+        .category(ErrorCategory::Timeout, cfg.cache_max_age_timeout)
+        .category(ErrorCategory::ClientError, cfg.cache_max_age_4xx)
+        .category(ErrorCategory::ServerError, cfg.cache_max_age_5xx)
+}
+
 /// Load cache (if exists and is still valid)
 /// This returns an `Option` as starting without a cache is a common scenario
 /// and we silently discard errors on purpose
-fn load_cache(cfg: &Config) -> Option<Cache> {
-    if !cfg.cache {
+async fn load_cache(cfg: &Config, backend: &CacheBackend) -> Option<Cache> {
+    // `--dump --only new|broken` compares against the previous run
+    let needs_cache = cfg.dump && cfg.only.is_some_and(DumpFilter::uses_cache);
+    if !cfg.cache && !needs_cache {
         return None;
     }
 
-    // Discard entire cache if it hasn't been updated since `max_cache_age`.
-    // This is an optimization, which avoids iterating over the file and
-    // checking the age of each entry.
-    match fs::metadata(LYCHEE_CACHE_FILE) {
-        Err(_e) => {
-            // No cache found; silently start with empty cache
-            return None;
-        }
-        Ok(metadata) => {
-            let modified = metadata.modified().ok()?;
-            let elapsed = modified.elapsed().ok()?;
-            if elapsed > cfg.max_cache_age {
-                warn!(
-                    "Cache is too old (age: {}, max age: {}). Discarding and recreating.",
-                    Duration::from_secs(elapsed.as_secs()),
-                    Duration::from_secs(cfg.max_cache_age.as_secs())
-                );
-                return None;
-            }
-            info!(
-                "Cache is recent (age: {}, max age: {}). Using.",
-                Duration::from_secs(elapsed.as_secs()),
-                Duration::from_secs(cfg.max_cache_age.as_secs())
-            );
+    match backend.load(&cache_max_age(cfg)).await {
+        Ok(cache) => cache,
+        Err(e) => {
+            warn!("Error while loading cache: {e:#}. Continuing without.");
+            None
         }
     }
+}
 
-    let cache = Cache::load(LYCHEE_CACHE_FILE, cfg.max_cache_age.as_secs());
-    match cache {
-        Ok(cache) => Some(cache),
+/// Load the cache of the fragments of local files, which is kept next to the
+/// response cache. It is only used with `--cache` and `--include-fragments`.
+/// Like the response cache, a cache which can't be loaded is discarded.
+fn load_anchor_cache(cfg: &Config, backend: &CacheBackend) -> Option<AnchorCache> {
+    if !cfg.cache || !cfg.include_fragments {
+        return None;
+    }
+    match AnchorCache::load(&backend.anchor_cache_path()) {
+        Ok(anchor_cache) => Some(anchor_cache),
         Err(e) => {
-            warn!("Error while loading cache: {e}. Continuing without.");
-            None
+            warn!("Error while loading anchor cache: {e}. Continuing without.");
+            Some(AnchorCache::default())
         }
     }
 }
 
-/// Set up runtime and call lychee entrypoint
-fn run_main() -> Result<i32> {
-    use std::process::exit;
+/// Store the anchor cache (if any), evicting the least recently used entries
+/// beyond `--max-anchor-cache-entries`
+fn store_anchor_cache(
+    cfg: &Config,
+    backend: &CacheBackend,
+    anchor_cache: Option<&AnchorCache>,
+) -> Result<()> {
+    if let Some(anchor_cache) = anchor_cache {
+        anchor_cache
+            .store(&backend.anchor_cache_path(), cfg.max_anchor_cache_entries)
+            .context("Cannot store anchor cache")?;
+    }
+    Ok(())
+}
 
-    let opts = match load_config() {
-        Ok(opts) => opts,
+/// Load the config, run lychee and write the report (if requested)
+fn run_main() -> Result<i32> {
+    let start = Instant::now();
+    let matches = LycheeOptions::command().get_matches();
+    let mut opts = LycheeOptions::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    opts.config.record_sources(&matches);
+    let mut report = Report::default();
+
+    let result = match load_config(&mut opts) {
+        Ok(()) => match &opts.command {
+            Some(Command::History(args)) => history::query(opts.config.history.as_deref(), args)
+                .map(|()| ExitCode::Success as i32),
+            Some(Command::Serve(args)) => {
+                serve_with_runtime(&opts, args).map(|()| ExitCode::Success as i32)
+            }
+            None => opts
+                .config
+                .hooks
+                .before()
+                .and_then(|()| run_with_runtime(&opts, &mut report)),
+        },
         Err(e) => {
             error!("Error while loading config: {e}");
-            exit(ExitCode::ConfigFile as i32);
+            report.config_error = Some(redact::redact(&format!("{e:#}")));
+            Ok(ExitCode::ConfigFile as i32)
         }
     };
 
-    let runtime = match opts.config.threads {
+    if let Some(report_file) = &opts.config.report_file {
+        match &result {
+            Ok(exit_code) => report.exit_code = *exit_code,
+            Err(e) => {
+                report.exit_code = ExitCode::UnexpectedFailure as i32;
+                report.error = Some(redact::redact(&format!("{e:#}")));
+            }
+        }
+        report.duration_secs = start.elapsed().as_secs_f64();
+        if let Err(e) = report.write(report_file) {
+            error!("{e:#}");
+        }
+    }
+
+    // Hooks only apply to link checks, not to subcommands
+    if opts.command.is_none() {
+        let exit_code = *result
+            .as_ref()
+            .unwrap_or(&(ExitCode::UnexpectedFailure as i32));
+        let counts = report.counts.as_ref();
+        if let Err(e) = opts.config.hooks.after(&opts.config, exit_code, counts) {
+            error!("{e:#}");
+        }
+    }
+
+    // Errors are printed by `main`, outside of the logger
+    result.map_err(redact::error)
+}
+
+/// Set up the runtime with the number of threads of `--threads`
+fn runtime(cfg: &Config) -> Result<tokio::runtime::Runtime> {
+    let runtime = match cfg.threads {
         Some(threads) => {
             // We define our own runtime instead of the `tokio::main` attribute
             // since we want to make the number of threads configurable
@@ -263,15 +422,90 @@ fn run_main() -> Result<i32> {
         }
         None => tokio::runtime::Runtime::new()?,
     };
+    Ok(runtime)
+}
 
-    match runtime.block_on(run(&opts)) {
+/// Set up runtime and call lychee entrypoint
+fn run_with_runtime(opts: &LycheeOptions, report: &mut Report) -> Result<i32> {
+    let runtime = runtime(&opts.config)?;
+
+    let result = runtime.block_on(run(opts, report));
+
+    match result {
         Err(e) if Some(ErrorKind::BrokenPipe) == underlying_io_error_kind(&e) => {
-            exit(ExitCode::Success as i32);
+            Ok(ExitCode::Success as i32)
         }
         res => res,
     }
 }
 
+/// Set up runtime and run `lychee serve`
+fn serve_with_runtime(opts: &LycheeOptions, args: &ServeArgs) -> Result<()> {
+    runtime(&opts.config)?.block_on(serve(opts, args))
+}
+
+/// Serve the API of `lychee serve` until Ctrl+C or SIGTERM, then save the
+/// cache and cookies like a run
+async fn serve(opts: &LycheeOptions, args: &ServeArgs) -> Result<()> {
+    let cache_backend: CacheBackend = match &opts.config.cache_backend {
+        Some(backend) => backend.parse()?,
+        None => CacheBackend::default(),
+    };
+    let cache = Arc::new(
+        load_cache(&opts.config, &cache_backend)
+            .await
+            .unwrap_or_default(),
+    );
+    let cookie_jar = load_cookie_jar(&opts.config).context("Cannot load cookie jar")?;
+    let proxy_pac = client::load_proxy_pac(&opts.config).await?;
+    let client = client::create(
+        &opts.config,
+        cookie_jar.as_deref(),
+        None,
+        proxy_pac,
+        None,
+        &[],
+    )?;
+
+    let state = commands::serve::ServeState::new(&opts.config, args, client, Arc::clone(&cache));
+    commands::serve(args.listen, state).await?;
+
+    if opts.config.cache {
+        cache_backend
+            .store(&cache, &cache_max_age(&opts.config))
+            .await?;
+    }
+    if let Some(cookie_jar) = cookie_jar.as_ref() {
+        info!("Saving cookie jar");
+        cookie_jar.save().context("Cannot save cookie jar")?;
+    }
+    Ok(())
+}
+
+/// Listen for Ctrl+C.
+///
+/// The first Ctrl+C cancels the outstanding checks, so that the links which
+/// were checked so far are still reported and the cache is saved. The second
+/// one exits immediately.
+fn handle_interrupts() -> watch::Receiver<bool> {
+    let (sender, receiver) = watch::channel(false);
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            warn!("Cannot listen for Ctrl+C");
+            return;
+        }
+        warn!(
+            "Interrupted, cancelling outstanding checks. Press Ctrl+C again to exit immediately."
+        );
+        // Nobody is listening anymore if the checks are done already
+        let _ = sender.send(true);
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(ExitCode::Interrupted as i32);
+        }
+    });
+    receiver
+}
+
 /// Check if the given error can be traced back to an `io::ErrorKind`
 /// This is helpful for troubleshooting the root cause of an error.
 /// Code is taken from the anyhow documentation.
@@ -285,39 +519,123 @@ fn underlying_io_error_kind(error: &Error) -> Option<io::ErrorKind> {
 }
 
 /// Run lychee on the given inputs
-async fn run(opts: &LycheeOptions) -> Result<i32> {
+async fn run(opts: &LycheeOptions, report: &mut Report) -> Result<i32> {
     let inputs = opts.inputs()?;
+    for conflict in conflicts::find(&opts.config, &inputs) {
+        warn!("WARNING: {conflict}");
+    }
+    let input_encoding = opts
+        .config
+        .input_encoding
+        .as_deref()
+        .map(|label| {
+            encoding_rs::Encoding::for_label(label.as_bytes())
+                .with_context(|| format!("Unknown input encoding `{label}`"))
+        })
+        .transpose()?;
+
+    let tag_max_errors = parse_tag_max_errors(&opts.config.tag_max_errors)?;
+
+    let generated_markers = if opts.config.generated_regions {
+        Some(GeneratedMarkers::new(
+            opts.config
+                .generated_start
+                .as_deref()
+                .unwrap_or(DEFAULT_GENERATED_START),
+            opts.config
+                .generated_end
+                .as_deref()
+                .unwrap_or(DEFAULT_GENERATED_END),
+        )?)
+    } else {
+        None
+    };
 
     let mut collector = Collector::new(opts.config.root_dir.clone(), opts.config.base.clone())?
         .skip_missing_inputs(opts.config.skip_missing)
         .skip_hidden(!opts.config.hidden)
         .skip_ignored(!opts.config.no_ignore)
+        .follow_symlinks(opts.config.follow_symlinks)
         .include_verbatim(opts.config.include_verbatim)
+        .include_notebook_outputs(opts.config.include_notebook_outputs)
+        .include_lazy_images(opts.config.include_lazy_images)
+        .extract_comments(opts.config.extract_comments)
+        .generated_markers(generated_markers)
+        .file_extensions(
+            FileExtensions::new(opts.config.filetypes.clone())
+                .extensionless(opts.config.extensionless_file_type.file_type()),
+        )
+        .max_files(opts.config.max_files)
+        .max_links(opts.config.max_links)
+        .input_encoding(input_encoding)
+        .include_binary(opts.config.include_binary)
+        .check_references(opts.config.check_references)
+        .stream_threshold(if opts.config.stream_large_files {
+            0
+        } else {
+            DEFAULT_STREAM_THRESHOLD
+        })
         // File a bug if you rely on this envvar! It's going to go away eventually.
         .use_html5ever(std::env::var("LYCHEE_USE_HTML5EVER").map_or(false, |x| x == "1"));
 
+    if opts.config.recursive {
+        collector = collector
+            .recurse(
+                opts.config.depth.unwrap_or(usize::MAX),
+                SameDomainPolicy::SameHost,
+            )
+            .crawl_state(opts.config.crawl_state.clone());
+    }
+
     if opts.config.dump_inputs {
-        let sources = collector.collect_sources(inputs);
-        let exit_code = commands::dump_inputs(
-            sources,
-            opts.config.output.as_ref(),
-            &opts.config.exclude_path,
-        )
-        .await?;
+        let exit_code = if opts.config.format == StatsFormat::Json {
+            let extractor = collector.extractor();
+            commands::dump_inputs_json(
+                collector.collect_contents(inputs),
+                extractor,
+                opts.config.output.as_ref(),
+                &opts.config.exclude_path,
+            )
+            .await?
+        } else {
+            commands::dump_inputs(
+                collector.collect_sources(inputs),
+                opts.config.output.as_ref(),
+                &opts.config.exclude_path,
+            )
+            .await?
+        };
 
         return Ok(exit_code as i32);
     }
 
-    collector = if let Some(ref basic_auth) = opts.config.basic_auth {
-        collector.basic_auth_extractor(BasicAuthExtractor::new(basic_auth)?)
+    let netrc = if opts.config.no_netrc {
+        None
     } else {
-        collector
+        Netrc::discover()?
+    };
+    collector = match (&opts.config.basic_auth, netrc) {
+        (None, None) => collector,
+        (basic_auth, netrc) => {
+            let mut extractor = BasicAuthExtractor::new(basic_auth.as_deref().unwrap_or_default())?;
+            if let Some(netrc) = netrc {
+                extractor = extractor.with_netrc(netrc);
+            }
+            collector.basic_auth_extractor(extractor)
+        }
     };
 
-    let requests = collector.collect_links(inputs);
+    let skipped_binaries = collector.skipped_binaries();
+    let reference_issues = collector.reference_issues();
 
-    let cache = load_cache(&opts.config).unwrap_or_default();
-    let cache = Arc::new(cache);
+    let cache_backend: CacheBackend = match &opts.config.cache_backend {
+        Some(backend) => backend.parse()?,
+        None => CacheBackend::default(),
+    };
+    let cache = load_cache(&opts.config, &cache_backend).await;
+    report.cache_used = cache.is_some();
+    let cache = Arc::new(cache.unwrap_or_default());
+    let anchor_cache = load_anchor_cache(&opts.config, &cache_backend);
 
     let cookie_jar = load_cookie_jar(&opts.config).with_context(|| {
         format!(
@@ -329,19 +647,78 @@ async fn run(opts: &LycheeOptions) -> Result<i32> {
         )
     })?;
 
-    let client = client::create(&opts.config, cookie_jar.as_deref())?;
+    let metrics = (opts.config.metrics_file.is_some() || opts.config.metrics_listen.is_some())
+        .then(|| Arc::new(Metrics::default()));
+    if let (Some(addr), Some(metrics)) = (opts.config.metrics_listen, &metrics) {
+        metrics::listen(addr, Arc::clone(metrics)).await?;
+    }
+
+    let proxy_pac = client::load_proxy_pac(&opts.config).await?;
+    let client = client::create(
+        &opts.config,
+        cookie_jar.as_deref(),
+        metrics
+            .clone()
+            .map(|metrics| metrics as Arc<dyn CheckObserver>),
+        proxy_pac,
+        anchor_cache.clone(),
+        &client::input_dirs(&inputs),
+    )?;
+
+    // Local files can be checked without network access. The connection is
+    // tested with the client, so that its proxies and TLS options apply.
+    if opts.config.preflight && !opts.config.offline && !opts.config.dump {
+        let max_duration = parse_duration_secs(opts.config.timeout);
+        if let Err(e) = preflight::run(&client, max_duration).await {
+            error!("Network connection failed the preflight check: {e:#}");
+            return Ok(ExitCode::Offline as i32);
+        }
+    }
+
+    if opts.config.watch {
+        let exit_code =
+            watch::watch(collector, inputs, client, Arc::clone(&cache), &opts.config).await?;
+        if opts.config.cache {
+            cache_backend
+                .store(&cache, &cache_max_age(&opts.config))
+                .await?;
+        }
+        store_anchor_cache(&opts.config, &cache_backend, anchor_cache.as_ref())?;
+        return Ok(exit_code as i32);
+    }
 
+    let duplicate_fragments = client.duplicate_fragments();
+    let requests = collector.collect_links(inputs);
     let params = CommandParams {
         client,
         cache,
         requests,
         cfg: opts.config.clone(),
+        tag_max_errors,
     };
 
     let exit_code = if opts.config.dump {
         commands::dump(params).await?
     } else {
-        let (stats, cache, exit_code) = commands::check(params).await?;
+        let (mut stats, cache, mut exit_code) =
+            commands::check(params, handle_interrupts()).await?;
+        if let (Some(path), Some(metrics)) = (&opts.config.metrics_file, &metrics) {
+            metrics.write(path)?;
+        }
+        stats.skipped_binaries = std::mem::take(&mut *skipped_binaries.lock().unwrap());
+        stats.reference_issues = std::mem::take(&mut *reference_issues.lock().unwrap());
+        stats.add_duplicate_fragments(std::mem::take(&mut *duplicate_fragments.lock().unwrap()));
+        // Broken references are errors of the documents, not of their links
+        if !stats.reference_issues.is_empty() && exit_code == ExitCode::Success {
+            exit_code = ExitCode::LinkCheckFailure;
+        }
+        report.counts = Some(Counts::from(&stats));
+
+        // Compare against the previous report before sections are removed
+        let diff = match &opts.config.diff {
+            Some(previous) => Some(ReportDiff::new(&diff::load_previous(previous)?, &stats)),
+            None => None,
+        };
 
         let github_issues = stats
             .error_map
@@ -349,11 +726,46 @@ async fn run(opts: &LycheeOptions) -> Result<i32> {
             .flatten()
             .any(|body| body.uri.domain() == Some("github.com"));
 
-        let stats_formatter: Box<dyn StatsFormatter> =
+        // Suggestions may be removed from the report below
+        let fixes = if opts.config.fix {
+            commands::fix(&stats.suggestion_map, opts.config.dry_run)?
+        } else {
+            Vec::new()
+        };
+
+        if opts.config.report_sections.is_empty() {
+            stats.retain_sections(&ReportSection::DEFAULT);
+        } else {
+            stats.retain_sections(&opts.config.report_sections);
+        }
+
+        let mut anonymizer = None;
+        if opts.config.anonymize {
+            let salt = opts
+                .config
+                .anonymize_salt
+                .as_ref()
+                .map(ExposeSecret::expose_secret);
+            let mut stats_anonymizer = Anonymizer::new(salt);
+            stats = stats_anonymizer.anonymize(stats);
+            anonymizer = Some(stats_anonymizer);
+        }
+
+        let stats_formatter: Arc<dyn StatsFormatter<ResponseStats>> =
             get_stats_formatter(&opts.config.format, &opts.config.mode);
 
         let is_empty = stats.is_empty();
-        let formatted_stats = stats_formatter.format(stats)?;
+        let formatted_stats = match &diff {
+            // The diff replaces the status report
+            Some(diff) if opts.config.format == StatsFormat::Json => Some(diff.to_json()?),
+            Some(diff) => Some(diff.to_string().trim_end().to_string()),
+            None => stats_formatter.format(stats).map_err(|e| anyhow!(e))?,
+        }
+        .map(|formatted_stats| match &anonymizer {
+            // Error messages may still contain URLs or paths
+            Some(anonymizer) => redact::redact(&anonymizer.scrub(&formatted_stats)),
+            None => redact::redact(&formatted_stats),
+        });
 
         if let Some(formatted_stats) = formatted_stats {
             if let Some(output) = &opts.config.output {
@@ -369,20 +781,31 @@ async fn run(opts: &LycheeOptions) -> Result<i32> {
             }
         }
 
+        if opts.config.fix {
+            commands::fix::write_fixes(&mut io::stdout(), &fixes, opts.config.dry_run)?;
+        }
+
         if github_issues && opts.config.github_token.is_none() {
             warn!("There were issues with GitHub URLs. You could try setting a GitHub token and running lychee again.",);
         }
 
         if opts.config.cache {
-            cache.store(LYCHEE_CACHE_FILE)?;
+            cache_backend
+                .store(&cache, &cache_max_age(&opts.config))
+                .await?;
         }
+        store_anchor_cache(&opts.config, &cache_backend, anchor_cache.as_ref())?;
 
         if let Some(cookie_jar) = cookie_jar.as_ref() {
             info!("Saving cookie jar");
             cookie_jar.save().context("Cannot save cookie jar")?;
         }
 
-        exit_code
+        match diff {
+            // Links which were broken before don't fail the run
+            Some(diff) if exit_code.is_link_check_failure() && !diff.has_new() => ExitCode::Success,
+            _ => exit_code,
+        }
     };
 
     Ok(exit_code as i32)
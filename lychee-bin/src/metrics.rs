@@ -0,0 +1,337 @@
+//! Prometheus metrics of a run (`--metrics-file` and `--metrics-listen`).
+//!
+//! The metrics are recorded from the events of the client, so they cover the
+//! links which were checked in this run, but not the cached ones. Scheduled
+//! runs can write them for the textfile collector of the node exporter, while
+//! long runs can be scraped over HTTP.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use lychee_lib::{CheckEvent, CheckObserver, Status};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Upper bounds of the buckets of the check durations in seconds
+const DURATION_BUCKETS: [f64; 9] = [0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+/// Number of checks and errors of a host
+#[derive(Debug, Default)]
+struct HostCounts {
+    checks: u64,
+    errors: u64,
+}
+
+/// Durations of the checks
+#[derive(Debug, Default)]
+struct Histogram {
+    /// Number of checks per bucket of [`DURATION_BUCKETS`], not cumulative
+    buckets: [u64; DURATION_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        if let Some(bucket) = DURATION_BUCKETS.iter().position(|le| seconds <= *le) {
+            self.buckets[bucket] += 1;
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+#[derive(Debug, Default)]
+struct Recorded {
+    /// Checks by status class, e.g. `2xx` or `timeout`
+    checks: BTreeMap<&'static str, u64>,
+    hosts: BTreeMap<String, HostCounts>,
+    retries: u64,
+    durations: Histogram,
+}
+
+/// Records the metrics of a run from the events of the client
+#[derive(Debug)]
+pub(crate) struct Metrics {
+    start: Instant,
+    recorded: Mutex<Recorded>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            recorded: Mutex::default(),
+        }
+    }
+}
+
+impl CheckObserver for Metrics {
+    fn on_event(&self, event: &CheckEvent<'_>) {
+        let mut recorded = self.recorded.lock().unwrap();
+        match event {
+            CheckEvent::Retry { .. } => recorded.retries += 1,
+            CheckEvent::CheckCompleted { response, duration } => {
+                let uri = &response.body().uri;
+                let status = response.status();
+                *recorded.checks.entry(status_class(status)).or_default() += 1;
+                if let Some(host) = uri
+                    .domain()
+                    .map(ToString::to_string)
+                    .or_else(|| uri.host_ip().map(|ip| ip.to_string()))
+                {
+                    let host = recorded.hosts.entry(host).or_default();
+                    host.checks += 1;
+                    if status.is_error() {
+                        host.errors += 1;
+                    }
+                }
+                if let Some(duration) = duration {
+                    recorded.durations.observe(duration.as_secs_f64());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Metrics {
+    /// The metrics in the Prometheus text format
+    pub(crate) fn render(&self) -> String {
+        let recorded = self.recorded.lock().unwrap();
+        let mut out = String::new();
+
+        header(
+            &mut out,
+            "lychee_checks_total",
+            "counter",
+            "Links checked in this run by status class",
+        );
+        for (class, count) in &recorded.checks {
+            let _ = writeln!(out, "lychee_checks_total{{class=\"{class}\"}} {count}");
+        }
+
+        header(
+            &mut out,
+            "lychee_host_checks_total",
+            "counter",
+            "Links checked in this run by host",
+        );
+        for (host, counts) in &recorded.hosts {
+            let host = escape(host);
+            let _ = writeln!(
+                out,
+                "lychee_host_checks_total{{host=\"{host}\"}} {}",
+                counts.checks
+            );
+        }
+        header(
+            &mut out,
+            "lychee_host_errors_total",
+            "counter",
+            "Failed links in this run by host",
+        );
+        for (host, counts) in &recorded.hosts {
+            let host = escape(host);
+            let _ = writeln!(
+                out,
+                "lychee_host_errors_total{{host=\"{host}\"}} {}",
+                counts.errors
+            );
+        }
+
+        header(
+            &mut out,
+            "lychee_retries_total",
+            "counter",
+            "Retried requests",
+        );
+        let _ = writeln!(out, "lychee_retries_total {}", recorded.retries);
+
+        header(
+            &mut out,
+            "lychee_check_duration_seconds",
+            "histogram",
+            "Durations of the checks of links, including retries",
+        );
+        let durations = &recorded.durations;
+        let mut cumulative = 0;
+        for (le, count) in DURATION_BUCKETS.iter().zip(durations.buckets) {
+            cumulative += count;
+            let _ = writeln!(
+                out,
+                "lychee_check_duration_seconds_bucket{{le=\"{le}\"}} {cumulative}"
+            );
+        }
+        let _ = writeln!(
+            out,
+            "lychee_check_duration_seconds_bucket{{le=\"+Inf\"}} {}",
+            durations.count
+        );
+        let _ = writeln!(out, "lychee_check_duration_seconds_sum {}", durations.sum);
+        let _ = writeln!(
+            out,
+            "lychee_check_duration_seconds_count {}",
+            durations.count
+        );
+
+        header(
+            &mut out,
+            "lychee_run_duration_seconds",
+            "gauge",
+            "Duration of the run so far",
+        );
+        let _ = writeln!(
+            out,
+            "lychee_run_duration_seconds {}",
+            self.start.elapsed().as_secs_f64()
+        );
+        out
+    }
+
+    /// Write the metrics to `path`, replacing it at once, so that collectors
+    /// never read a partial file
+    pub(crate) fn write(&self, path: &Path) -> Result<()> {
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        std::fs::write(&temporary, self.render())
+            .and_then(|()| std::fs::rename(&temporary, path))
+            .with_context(|| format!("Cannot write metrics to `{}`", path.display()))
+    }
+}
+
+/// Serve the metrics over HTTP at `addr` for the rest of the run
+pub(crate) async fn listen(addr: SocketAddr, metrics: Arc<Metrics>) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Cannot serve metrics at `{addr}`"))?;
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                continue;
+            };
+            let metrics = Arc::clone(&metrics);
+            tokio::spawn(async move {
+                // Every path serves the metrics, so the request is not parsed
+                let mut request = [0; 1024];
+                if stream.read(&mut request).await.is_err() {
+                    return;
+                }
+                let body = metrics.render();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    Ok(())
+}
+
+/// The class of `status` in `lychee_checks_total`
+fn status_class(status: &Status) -> &'static str {
+    match status {
+        Status::Excluded | Status::ExcludedScheme(_) => "excluded",
+        Status::Unsupported(_) => "unsupported",
+        Status::Skipped(_) => "skipped",
+        Status::Timeout(_) => "timeout",
+        Status::Cached(..) => "cached",
+        _ => match status.code().map(|code| code.as_u16() / 100) {
+            Some(1) => "1xx",
+            Some(2) => "2xx",
+            Some(3) => "3xx",
+            Some(4) => "4xx",
+            Some(5) => "5xx",
+            _ => "error",
+        },
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// Escape a label value
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use http::StatusCode;
+    use lychee_lib::{ErrorKind, InputSource, Response, Uri};
+
+    use super::*;
+
+    fn response(url: &str, status: Status) -> Response {
+        Response::new(Uri::try_from(url).unwrap(), status, InputSource::Stdin)
+    }
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::default();
+        let ok = response("https://example.com/", Status::Ok(StatusCode::OK));
+        let broken = response(
+            "https://example.com/broken",
+            Status::Error(ErrorKind::InvalidStatusCode(1000)),
+        );
+        let timeout = response("https://example.org/", Status::Timeout(None));
+
+        metrics.on_event(&CheckEvent::CheckCompleted {
+            response: &ok,
+            duration: Some(Duration::from_millis(50)),
+        });
+        metrics.on_event(&CheckEvent::Retry {
+            uri: &broken.body().uri,
+            attempt: 1,
+            status: broken.status(),
+        });
+        // Checks without a duration, e.g. of excluded links, aren't timed
+        metrics.on_event(&CheckEvent::CheckCompleted {
+            response: &broken,
+            duration: None,
+        });
+        metrics.on_event(&CheckEvent::CheckCompleted {
+            response: &timeout,
+            duration: Some(Duration::from_secs(90)),
+        });
+
+        let rendered = metrics.render();
+        for line in [
+            "# TYPE lychee_checks_total counter",
+            "lychee_checks_total{class=\"2xx\"} 1",
+            "lychee_checks_total{class=\"error\"} 1",
+            "lychee_checks_total{class=\"timeout\"} 1",
+            "lychee_host_checks_total{host=\"example.com\"} 2",
+            "lychee_host_errors_total{host=\"example.com\"} 1",
+            "lychee_host_errors_total{host=\"example.org\"} 1",
+            "lychee_retries_total 1",
+            "lychee_check_duration_seconds_bucket{le=\"0.1\"} 1",
+            "lychee_check_duration_seconds_bucket{le=\"60\"} 1",
+            "lychee_check_duration_seconds_bucket{le=\"+Inf\"} 2",
+            "lychee_check_duration_seconds_count 2",
+        ] {
+            assert!(
+                rendered.lines().any(|rendered| rendered == line),
+                "Missing `{line}` in:\n{rendered}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape(r#"a"b\c"#), r#"a\"b\\c"#);
+    }
+}
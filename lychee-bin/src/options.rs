@@ -1,33 +1,48 @@
-use crate::archive::Archive;
+use crate::archive::{Archive, ArchiveDate};
+use crate::hooks::Hooks;
 use crate::parse::parse_base;
 use crate::verbosity::Verbosity;
-use anyhow::{anyhow, Context, Error, Result};
+use anyhow::{anyhow, bail, Context, Error, Result};
 use clap::builder::PossibleValuesParser;
-use clap::{arg, builder::TypedValueParser, Parser};
+use clap::parser::ValueSource;
+use clap::{arg, builder::TypedValueParser, ArgMatches, Args, Parser, Subcommand};
 use const_format::{concatcp, formatcp};
 use lychee_lib::{
-    Base, BasicAuthSelector, Input, StatusCodeExcluder, StatusCodeSelector, DEFAULT_MAX_REDIRECTS,
-    DEFAULT_MAX_RETRIES, DEFAULT_RETRY_WAIT_TIME_SECS, DEFAULT_TIMEOUT_SECS, DEFAULT_USER_AGENT,
+    ratelimit::HostConfig, Base, BasicAuthSelector, FileType, Input, Platform, PlatformConfig,
+    StatusCodeExcluder, StatusCodeSelector, DEFAULT_MAIL_DNS_MAX_RETRIES,
+    DEFAULT_MAIL_DNS_TIMEOUT_SECS, DEFAULT_MAX_REDIRECTS, DEFAULT_MAX_RETRIES,
+    DEFAULT_RETRY_WAIT_TIME_SECS, DEFAULT_TIMEOUT_SECS, DEFAULT_USER_AGENT,
 };
 use secrecy::{ExposeSecret, SecretString};
 use serde::Deserialize;
+use std::fmt;
 use std::path::Path;
-use std::{fs, path::PathBuf, str::FromStr, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    net::SocketAddr,
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
 use strum::{Display, EnumIter, EnumString, VariantNames};
 
-pub(crate) const LYCHEE_IGNORE_FILE: &str = ".lycheeignore";
+pub(crate) use lychee_lib::filter::LYCHEE_IGNORE_FILE;
 pub(crate) const LYCHEE_CACHE_FILE: &str = ".lycheecache";
+pub(crate) const LYCHEE_ANCHOR_CACHE_FILE: &str = ".lycheeanchors";
 pub(crate) const LYCHEE_CONFIG_FILE: &str = "lychee.toml";
 
 const DEFAULT_METHOD: &str = "get";
 const DEFAULT_MAX_CACHE_AGE: &str = "1d";
 const DEFAULT_MAX_CONCURRENCY: usize = 128;
+const DEFAULT_MAX_ANCHOR_CACHE_ENTRIES: usize = 10_000;
 
 // this exists because clap requires `&str` type values for defaults
 // whereas serde expects owned `String` types
 // (we can't use e.g. `TIMEOUT` or `timeout()` which gets created for serde)
 const MAX_CONCURRENCY_STR: &str = concatcp!(DEFAULT_MAX_CONCURRENCY);
 const MAX_CACHE_AGE_STR: &str = concatcp!(DEFAULT_MAX_CACHE_AGE);
+const MAX_ANCHOR_CACHE_ENTRIES_STR: &str = concatcp!(DEFAULT_MAX_ANCHOR_CACHE_ENTRIES);
 const MAX_REDIRECTS_STR: &str = concatcp!(DEFAULT_MAX_REDIRECTS);
 const MAX_RETRIES_STR: &str = concatcp!(DEFAULT_MAX_RETRIES);
 const HELP_MSG_CACHE: &str = formatcp!(
@@ -39,11 +54,13 @@ const HELP_MSG_CACHE: &str = formatcp!(
 // provided a custom value. If they didn't, we won't throw an error if
 // the file doesn't exist.
 const HELP_MSG_CONFIG_FILE: &str = formatcp!(
-    "Configuration file to use\n\n[default: {}]",
+    "Configuration file to use. Can be given multiple times; options in later files override those in earlier ones\n\n[default: {}]",
     LYCHEE_CONFIG_FILE,
 );
 const TIMEOUT_STR: &str = concatcp!(DEFAULT_TIMEOUT_SECS);
 const RETRY_WAIT_TIME_STR: &str = concatcp!(DEFAULT_RETRY_WAIT_TIME_SECS);
+const MAIL_DNS_TIMEOUT_STR: &str = concatcp!(DEFAULT_MAIL_DNS_TIMEOUT_SECS);
+const MAIL_DNS_MAX_RETRIES_STR: &str = concatcp!(DEFAULT_MAIL_DNS_MAX_RETRIES);
 
 /// The format to use for the final status report
 #[derive(Debug, Deserialize, Default, Clone, Display, EnumIter, VariantNames, PartialEq)]
@@ -74,6 +91,318 @@ impl FromStr for StatsFormat {
     }
 }
 
+/// Subsets of links which can be selected with `--dump --only`
+#[derive(
+    Debug, Deserialize, Clone, Copy, Display, EnumIter, EnumString, VariantNames, PartialEq,
+)]
+#[non_exhaustive]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum DumpFilter {
+    /// Links which were not checked in the previous run (not in the cache)
+    New,
+    /// Links which failed in the previous run (according to the cache)
+    Broken,
+    /// Links to websites other than the page they were found in
+    External,
+    /// Links to local files or to the same website as the page they were
+    /// found in
+    Internal,
+}
+
+impl DumpFilter {
+    /// Returns `true` if the filter needs the results of the previous run
+    pub(crate) const fn uses_cache(self) -> bool {
+        matches!(self, DumpFilter::New | DumpFilter::Broken)
+    }
+}
+
+/// Renderers whose rules can be selected with `--fragment-style`
+#[derive(
+    Debug, Deserialize, Default, Clone, Copy, Display, EnumIter, EnumString, VariantNames, PartialEq,
+)]
+#[non_exhaustive]
+#[strum(serialize_all = "kebab-case", ascii_case_insensitive)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum FragmentStyle {
+    #[default]
+    Github,
+    Gitlab,
+    Kramdown,
+    /// Use the regex given with `--fragment-regex`
+    CustomRegex,
+}
+
+/// How thoroughly mail addresses are checked, see `--mail-check-level`
+#[derive(
+    Debug, Deserialize, Default, Clone, Copy, Display, EnumIter, EnumString, VariantNames, PartialEq,
+)]
+#[non_exhaustive]
+#[strum(serialize_all = "kebab-case", ascii_case_insensitive)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum MailCheckLevel {
+    Syntax,
+    #[default]
+    Mx,
+    Smtp,
+}
+
+impl From<MailCheckLevel> for lychee_lib::MailCheckLevel {
+    fn from(level: MailCheckLevel) -> Self {
+        match level {
+            MailCheckLevel::Syntax => Self::Syntax,
+            MailCheckLevel::Mx => Self::Mx,
+            MailCheckLevel::Smtp => Self::Smtp,
+        }
+    }
+}
+
+/// Minimum TLS version of HTTPS connections, see `--min-tls`
+#[derive(
+    Debug, Deserialize, Clone, Copy, Display, EnumIter, EnumString, VariantNames, PartialEq,
+)]
+#[non_exhaustive]
+pub(crate) enum TlsVersion {
+    #[strum(serialize = "1.0")]
+    #[serde(rename = "1.0")]
+    Tls1_0,
+    #[strum(serialize = "1.1")]
+    #[serde(rename = "1.1")]
+    Tls1_1,
+    #[strum(serialize = "1.2")]
+    #[serde(rename = "1.2")]
+    Tls1_2,
+    #[strum(serialize = "1.3")]
+    #[serde(rename = "1.3")]
+    Tls1_3,
+}
+
+impl From<TlsVersion> for lychee_lib::TlsVersion {
+    fn from(version: TlsVersion) -> Self {
+        match version {
+            TlsVersion::Tls1_0 => Self::Tls1_0,
+            TlsVersion::Tls1_1 => Self::Tls1_1,
+            TlsVersion::Tls1_2 => Self::Tls1_2,
+            TlsVersion::Tls1_3 => Self::Tls1_3,
+        }
+    }
+}
+
+/// How the progress of a run is shown, see `--progress`
+#[derive(
+    Debug, Deserialize, Default, Clone, Copy, Display, EnumIter, EnumString, VariantNames, PartialEq,
+)]
+#[non_exhaustive]
+#[strum(serialize_all = "kebab-case", ascii_case_insensitive)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ProgressMode {
+    /// A single bar for all links
+    #[default]
+    Bar,
+    /// An additional line per active host
+    PerHost,
+}
+
+/// Format of the `tracing` output, see `--trace-format`
+#[derive(Debug, Clone, Copy, Display, EnumIter, EnumString, VariantNames, PartialEq, Eq)]
+#[non_exhaustive]
+#[strum(serialize_all = "kebab-case", ascii_case_insensitive)]
+pub(crate) enum TraceFormat {
+    /// Human-readable lines
+    Text,
+    /// A JSON object per line, e.g. for log collectors
+    Json,
+}
+
+/// Sections of the final report which can be selected with `--report-sections`
+#[derive(
+    Debug, Deserialize, Clone, Copy, Display, EnumIter, EnumString, VariantNames, PartialEq, Eq,
+)]
+#[non_exhaustive]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ReportSection {
+    /// Failed links per input
+    Errors,
+    /// Replacements for links, e.g. from `--suggest`
+    Suggestions,
+    /// Excluded links per input (only with `--verbose`)
+    Excluded,
+    /// Number of links and errors per host
+    Hosts,
+    /// Error rates of the input directories with the most failing links
+    Directories,
+}
+
+impl ReportSection {
+    /// Sections which are shown unless `--report-sections` is given
+    pub(crate) const DEFAULT: [ReportSection; 3] = [
+        ReportSection::Errors,
+        ReportSection::Suggestions,
+        ReportSection::Excluded,
+    ];
+}
+
+/// What to do with permanently redirected links, see `--hint-permanent-redirects`
+#[derive(
+    Debug, Deserialize, Clone, Copy, Display, EnumIter, EnumString, VariantNames, PartialEq,
+)]
+#[non_exhaustive]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum PermanentRedirects {
+    /// Suggest the target of the redirect as a replacement
+    Suggest,
+    /// Also treat the link as an error
+    Fail,
+}
+
+/// What to suggest for links into the `master` branch of GitHub repositories
+/// with another default branch, see `--suggest-default-branch`
+#[derive(
+    Debug, Deserialize, Clone, Copy, Display, EnumIter, EnumString, VariantNames, PartialEq,
+)]
+#[non_exhaustive]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum DefaultBranchSuggestion {
+    /// Link into the default branch instead
+    Branch,
+    /// Link into the latest commit of the default branch, which doesn't change
+    Permalink,
+}
+
+/// File types which can be selected with `--extensionless-file-type`
+#[derive(
+    Debug, Deserialize, Default, Clone, Copy, Display, EnumIter, EnumString, VariantNames, PartialEq,
+)]
+#[non_exhaustive]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ExtensionlessFileType {
+    /// Guess the file type from the content of the file
+    #[default]
+    Auto,
+    Markdown,
+    Html,
+    Plaintext,
+}
+
+impl ExtensionlessFileType {
+    /// The file type to use for all files without an extension, or `None` to
+    /// guess it
+    pub(crate) const fn file_type(self) -> Option<FileType> {
+        match self {
+            ExtensionlessFileType::Auto => None,
+            ExtensionlessFileType::Markdown => Some(FileType::Markdown),
+            ExtensionlessFileType::Html => Some(FileType::Html),
+            ExtensionlessFileType::Plaintext => Some(FileType::Plaintext),
+        }
+    }
+}
+
+/// Bundles of config defaults which can be selected with `--preset`
+#[derive(
+    Debug, Deserialize, Clone, Copy, Display, EnumIter, EnumString, VariantNames, PartialEq,
+)]
+#[non_exhaustive]
+#[strum(serialize_all = "kebab-case", ascii_case_insensitive)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Preset {
+    /// Documentation written in Markdown
+    Docs,
+    /// Repositories with many packages
+    Monorepo,
+    /// Output directory of a static site generator
+    StaticSite,
+}
+
+impl Preset {
+    /// The config of the preset, which is merged like a config file
+    pub(crate) fn config(self) -> Config {
+        let toml = match self {
+            Preset::Docs => include_str!("presets/docs.toml"),
+            Preset::Monorepo => include_str!("presets/monorepo.toml"),
+            Preset::StaticSite => include_str!("presets/static-site.toml"),
+        };
+        toml::from_str(toml).expect("presets are valid configs")
+    }
+}
+
+/// Where an option was set, to point users to the origin of conflicting
+/// options
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum OptionSource {
+    CommandLine,
+    Environment,
+    ConfigFile(PathBuf),
+    Preset(Preset),
+}
+
+impl fmt::Display for OptionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionSource::CommandLine => write!(f, "command line"),
+            OptionSource::Environment => write!(f, "environment variable"),
+            OptionSource::ConfigFile(path) => write!(f, "config file `{}`", path.display()),
+            OptionSource::Preset(preset) => write!(f, "preset `{preset}`"),
+        }
+    }
+}
+
+/// Rate limits for a single host, set in the `[hosts]` table of the config file
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct HostLimits {
+    /// Maximum number of concurrent requests to the host
+    pub(crate) max_concurrency: Option<usize>,
+    /// Minimum time in milliseconds between two requests to the host
+    pub(crate) min_interval_ms: Option<u64>,
+}
+
+impl From<HostLimits> for HostConfig {
+    fn from(limits: HostLimits) -> Self {
+        HostConfig {
+            max_concurrency: limits.max_concurrency,
+            min_interval: limits.min_interval_ms.map(Duration::from_millis),
+        }
+    }
+}
+
+/// Code hosting platform of a single host, set in the `[platforms]` table of
+/// the config file
+#[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PlatformHost {
+    /// `gitlab`, `bitbucket`, `gitea` or `forgejo`
+    #[serde(rename = "type")]
+    pub(crate) platform: Platform,
+    /// API token for links into private repositories
+    pub(crate) token: Option<SecretString>,
+}
+
+impl From<PlatformHost> for PlatformConfig {
+    fn from(host: PlatformHost) -> Self {
+        PlatformConfig {
+            platform: host.platform,
+            token: host.token,
+        }
+    }
+}
+
+/// Requirement on the bodies of the responses of matching URLs, set in an
+/// `[[assertions]]` entry of the config file
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Assertion {
+    /// Regex of the URLs whose responses are checked
+    pub(crate) url: String,
+    /// Regex which the body has to match
+    pub(crate) contains: Option<String>,
+    /// Regex which the body must not match, e.g. `(?i)page not found`
+    pub(crate) not_contains: Option<String>,
+}
+
 /// The different formatter modes
 ///
 /// This decides over whether to use color,
@@ -141,21 +470,29 @@ default_function! {
     max_retries: u64 = DEFAULT_MAX_RETRIES;
     max_concurrency: usize = DEFAULT_MAX_CONCURRENCY;
     max_cache_age: Duration = humantime::parse_duration(DEFAULT_MAX_CACHE_AGE).unwrap();
+    max_anchor_cache_entries: usize = DEFAULT_MAX_ANCHOR_CACHE_ENTRIES;
     user_agent: String = DEFAULT_USER_AGENT.to_string();
     timeout: usize = DEFAULT_TIMEOUT_SECS;
     retry_wait_time: usize = DEFAULT_RETRY_WAIT_TIME_SECS;
+    mail_dns_timeout: usize = DEFAULT_MAIL_DNS_TIMEOUT_SECS;
+    mail_dns_max_retries: u64 = DEFAULT_MAIL_DNS_MAX_RETRIES;
     method: String = DEFAULT_METHOD.to_string();
     verbosity: Verbosity = Verbosity::default();
     cache_exclude_selector: StatusCodeExcluder = StatusCodeExcluder::new();
     accept_selector: StatusCodeSelector = StatusCodeSelector::default();
 }
 
-// Macro for merging configuration values
+// Macro for merging configuration values. Values given on the command line
+// or with environment variables are kept, even if they equal the default.
 macro_rules! fold_in {
-    ( $cli:ident , $toml:ident ; $( $key:ident : $default:expr; )* ) => {
+    ( $cli:ident , $toml:ident , $source:ident ; $( $key:ident : $default:expr; )* ) => {
         $(
-            if $cli.$key == $default && $toml.$key != $default {
+            if !$cli.is_set_explicitly(stringify!($key))
+                && $cli.$key == $default
+                && $toml.$key != $default
+            {
                 $cli.$key = $toml.$key;
+                $cli.sources.insert(stringify!($key).to_string(), $source.clone());
             }
         )*
     };
@@ -166,22 +503,85 @@ macro_rules! fold_in {
 /// Finds broken URLs and mail addresses inside Markdown, HTML,
 /// `reStructuredText`, websites and more!
 #[derive(Parser, Debug)]
-#[command(version, about)]
+#[command(
+    version,
+    about,
+    subcommand_negates_reqs = true,
+    args_conflicts_with_subcommands = true,
+    disable_help_subcommand = true
+)]
 pub(crate) struct LycheeOptions {
     /// The inputs (where to get links to check from).
     /// These can be: files (e.g. `README.md`), file globs (e.g. `"~/git/*/README.md"`),
-    /// remote URLs (e.g. `https://example.com/README.md`) or standard input (`-`).
+    /// remote URLs (e.g. `https://example.com/README.md`), sitemaps (e.g. `https://example.com/sitemap.xml`),
+    /// archives (e.g. `site.zip` or `site.tar.gz`), container images (e.g. `docker://alpine:3.20`
+    /// or `oci:path/to/layout`), storage buckets (e.g. `s3://bucket/docs/`) or standard input (`-`).
     /// NOTE: Use `--` to separate inputs from options that allow multiple arguments.
-    #[arg(name = "inputs", required = true)]
+    #[arg(name = "inputs", required_unless_present_any = ["input_tag", "input_base"])]
     raw_inputs: Vec<String>,
 
     /// Configuration file to use
     #[arg(short, long = "config")]
     #[arg(help = HELP_MSG_CONFIG_FILE)]
-    pub(crate) config_file: Option<PathBuf>,
+    pub(crate) config_file: Vec<PathBuf>,
+
+    /// Check an input with a tag, e.g. `--input-tag docs ./docs`. The links of
+    /// each tag are summarized separately and can be allowed a number of
+    /// errors with `--tag-max-errors`. Can be given multiple times
+    #[arg(long, num_args = 2, value_names = ["TAG", "INPUT"])]
+    input_tag: Vec<String>,
+
+    /// Check an input with its own base URL or directory instead of `--base`,
+    /// e.g. `--input-base https://a.example.com docs/site-a`. This helps with
+    /// monorepos which contain multiple sites. Can be given multiple times
+    #[arg(long, num_args = 2, value_names = ["BASE", "INPUT"])]
+    input_base: Vec<String>,
 
     #[clap(flatten)]
     pub(crate) config: Config,
+
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+}
+
+/// Commands which don't take inputs.
+///
+/// Subcommands take precedence over inputs, so check a file or directory with
+/// the name of a subcommand with a path, e.g. `lychee ./history`.
+#[derive(Subcommand, Debug)]
+pub(crate) enum Command {
+    /// Query the outcomes of previous runs recorded with `--history`
+    History(HistoryArgs),
+    /// Check links over HTTP with `POST /check` until Ctrl+C or SIGTERM,
+    /// keeping the client and cache between requests
+    Serve(ServeArgs),
+}
+
+/// Arguments of `lychee history`
+#[derive(Args, Debug)]
+pub(crate) struct HistoryArgs {
+    /// Show the outcome of this URL in each recorded run
+    #[arg(required_unless_present = "flaky")]
+    pub(crate) url: Option<String>,
+
+    /// List the links which both succeeded and failed in the recorded runs,
+    /// most failures first
+    #[arg(long, conflicts_with = "url")]
+    pub(crate) flaky: bool,
+}
+
+/// Arguments of `lychee serve`
+#[derive(Args, Debug)]
+pub(crate) struct ServeArgs {
+    /// Address to listen at
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+    pub(crate) listen: SocketAddr,
+
+    /// Also accept requests with this `Host` header, e.g. the name of the
+    /// machine when listening at `0.0.0.0`. Requests for `localhost` and IP
+    /// addresses are always accepted. Can be given multiple times
+    #[arg(long, value_name = "HOST")]
+    pub(crate) allowed_host: Vec<String>,
 }
 
 impl LycheeOptions {
@@ -195,10 +595,31 @@ impl LycheeOptions {
         } else {
             Some(self.config.exclude_path.clone())
         };
+        let tagged = self
+            .input_tag
+            .chunks_exact(2)
+            .map(|pair| (&pair[1], Some(pair[0].clone()), None));
+        let based = self
+            .input_base
+            .chunks_exact(2)
+            .map(|pair| (&pair[1], None, Some(&pair[0])));
         self.raw_inputs
             .iter()
-            .map(|s| Input::new(s, None, self.config.glob_ignore_case, excluded.clone()))
-            .collect::<Result<_, _>>()
+            .map(|s| (s, None, None))
+            .chain(tagged)
+            .chain(based)
+            .map(|(s, tag, base)| {
+                let base = base.map(|base| parse_base(base)).transpose()?;
+                let input = Input::new(s, None, self.config.glob_ignore_case, excluded.clone())?
+                    .with_tag(tag)
+                    .with_base(base);
+                Ok(if self.config.image_path.is_empty() {
+                    input
+                } else {
+                    input.image_paths(&self.config.image_path)
+                })
+            })
+            .collect::<Result<_, lychee_lib::ErrorKind>>()
             .context("Cannot parse inputs from arguments")
     }
 }
@@ -212,17 +633,56 @@ pub(crate) struct Config {
     #[serde(default = "verbosity")]
     pub(crate) verbose: Verbosity,
 
+    /// Apply a bundle of defaults for a common kind of project: `docs` for
+    /// documentation written in Markdown, `monorepo` for repositories with many
+    /// packages and `static-site` for the output directory of a static site
+    /// generator.
+    ///
+    /// Presets can be combined. Options given on the command line or in the config
+    /// file take precedence over presets, and earlier presets take precedence over
+    /// later ones.
+    #[arg(long, value_parser = PossibleValuesParser::new(Preset::VARIANTS).map(|s| s.parse::<Preset>().unwrap()))]
+    #[serde(default)]
+    pub(crate) preset: Vec<Preset>,
+
     /// Do not show progress bar.
     /// This is recommended for non-interactive shells (e.g. for continuous integration)
     #[arg(short, long, verbatim_doc_comment)]
     #[serde(default)]
     pub(crate) no_progress: bool,
 
+    /// How to show the progress: `bar` shows a single progress bar, `per-host`
+    /// additionally shows the queued and active requests, the throughput and
+    /// the estimated remaining time of each host. This helps to find out why a
+    /// run is slow, which is usually a single rate-limited host.
+    #[arg(long, default_value = "bar", value_parser = PossibleValuesParser::new(ProgressMode::VARIANTS).map(|s| s.parse::<ProgressMode>().unwrap()))]
+    #[serde(default)]
+    pub(crate) progress: ProgressMode,
+
+    /// Send the progress as JSON events to the clients of a Unix socket at
+    /// this path, e.g. for wrappers and GUIs. Each line is an event
+    /// (`collected`, `checked` or `finished`) with the number of collected,
+    /// checked and failed links so far and the current URL. Only the current
+    /// user can connect to the socket
+    #[arg(long, value_name = "PATH")]
+    #[serde(default)]
+    pub(crate) progress_socket: Option<PathBuf>,
+
     #[arg(help = HELP_MSG_CACHE)]
     #[arg(long)]
     #[serde(default)]
     pub(crate) cache: bool,
 
+    /// Where to keep the cache: a file path or an HTTP(S) URL of a cache file which
+    /// is shared between runners, e.g. of different CI jobs. A shared cache is
+    /// downloaded with `GET` before and uploaded with `PUT` after checking; entries
+    /// of other runners are merged in. Credentials can be given in the URL.
+    ///
+    /// [default: .lycheecache]
+    #[arg(long, value_name = "PATH_OR_URL")]
+    #[serde(default)]
+    pub(crate) cache_backend: Option<String>,
+
     /// Discard all cached requests older than this duration
     #[arg(
         long,
@@ -233,6 +693,42 @@ pub(crate) struct Config {
     #[serde(with = "humantime_serde")]
     pub(crate) max_cache_age: Duration,
 
+    /// Discard cached requests which failed because the host name could not
+    /// be resolved, if they are older than this duration.
+    /// Defaults to `--max-cache-age`
+    #[arg(long, value_parser = humantime::parse_duration, verbatim_doc_comment)]
+    #[serde(default, with = "humantime_serde")]
+    pub(crate) cache_max_age_dns: Option<Duration>,
+
+    /// Discard cached requests which timed out, if they are older than this
+    /// duration.
+    /// Defaults to `--max-cache-age`
+    #[arg(long, value_parser = humantime::parse_duration, verbatim_doc_comment)]
+    #[serde(default, with = "humantime_serde")]
+    pub(crate) cache_max_age_timeout: Option<Duration>,
+
+    /// Discard cached requests which failed with a client error (4xx), if
+    /// they are older than this duration.
+    /// Defaults to `--max-cache-age`
+    #[arg(long, value_parser = humantime::parse_duration, verbatim_doc_comment)]
+    #[serde(default, with = "humantime_serde")]
+    pub(crate) cache_max_age_4xx: Option<Duration>,
+
+    /// Discard cached requests which failed with a server error (5xx), if
+    /// they are older than this duration.
+    /// Defaults to `--max-cache-age`
+    #[arg(long, value_parser = humantime::parse_duration, verbatim_doc_comment)]
+    #[serde(default, with = "humantime_serde")]
+    pub(crate) cache_max_age_5xx: Option<Duration>,
+
+    /// Maximum number of files whose fragments are kept in the anchor cache.
+    /// With `--cache` and `--include-fragments`, the fragments of local files are
+    /// stored next to the cache file in `.lycheeanchors`, so unchanged files are
+    /// not parsed again. The least recently used files are evicted first.
+    #[arg(long, value_name = "N", default_value = &MAX_ANCHOR_CACHE_ENTRIES_STR)]
+    #[serde(default = "max_anchor_cache_entries")]
+    pub(crate) max_anchor_cache_entries: usize,
+
     /// A list of status codes that will be excluded from the cache
     #[arg(
         long,
@@ -254,34 +750,100 @@ list of excluded status codes. This example will not cache results with a status
     pub(crate) cache_exclude_status: StatusCodeExcluder,
 
     /// Don't perform any link checking.
-    /// Instead, dump all the links extracted from inputs that would be checked
+    /// Instead, dump all the links extracted from inputs that would be checked.
+    /// With `--format json`, dump one JSON object per line with the source, position,
+    /// element, attribute and kind of each link and whether it is excluded
     #[arg(long)]
     #[serde(default)]
     pub(crate) dump: bool,
 
     /// Don't perform any link extraction and checking.
-    /// Instead, dump all input sources from which links would be collected
+    /// Instead, dump all input sources from which links would be collected.
+    /// With `--format json`, also dump the file type, size and number of links of each input
     #[arg(long)]
     #[serde(default)]
     pub(crate) dump_inputs: bool,
 
+    /// Only dump a subset of links when using `--dump`.
+    /// `new` and `broken` compare against the previous run stored in the cache
+    #[arg(long, value_parser = PossibleValuesParser::new(DumpFilter::VARIANTS).map(|s| s.parse::<DumpFilter>().unwrap()))]
+    #[serde(default)]
+    pub(crate) only: Option<DumpFilter>,
+
     /// Specify the use of a specific web archive.
     /// Can be used in combination with `--suggest`
     #[arg(long, value_parser = PossibleValuesParser::new(Archive::VARIANTS).map(|s| s.parse::<Archive>().unwrap()))]
     #[serde(default)]
     pub(crate) archive: Option<Archive>,
 
+    /// Look up broken links in the web archive at a date in the past
+    /// (`YYYY-MM-DD`) and report whether they were alive then or not archived
+    /// within a year of it, e.g. to tell recent link rot from links which were
+    /// always broken
+    #[arg(long, value_name = "DATE")]
+    #[serde(default)]
+    pub(crate) as_of: Option<ArchiveDate>,
+
     /// Suggest link replacements for broken links, using a web archive.
     /// The web archive can be specified with `--archive`
     #[arg(long)]
     #[serde(default)]
     pub(crate) suggest: bool,
 
+    /// Replace broken links in the input files with their suggested replacements,
+    /// i.e. archived versions (implies `--suggest`), the targets of permanent
+    /// redirects with `--hint-permanent-redirects`, default branches with
+    /// `--suggest-default-branch` and commits with `--lint-permalinks`.
+    /// Only local files are changed, remote inputs and archives are left as is
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) fix: bool,
+
+    /// Only show the changes of `--fix` without writing them to the files
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) dry_run: bool,
+
     /// Maximum number of allowed redirects
     #[arg(short, long, default_value = &MAX_REDIRECTS_STR)]
     #[serde(default = "max_redirects")]
     pub(crate) max_redirects: usize,
 
+    /// Show the redirect chain of links which are redirected, e.g.
+    /// `301 http://a -> 302 https://a -> 200 https://b`
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) verbose_redirects: bool,
+
+    /// Treat links which are redirected as errors, e.g. to find outdated links.
+    /// The redirect chain is shown in the error output.
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) fail_on_redirects: bool,
+
+    /// Use a distinct exit code if all failed links failed for the same reason:
+    /// 4 for timeouts, 5 for DNS failures, 6 for client errors (4xx) and 7 for
+    /// server errors (5xx). Mixed failures still exit with 2
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) granular_exit_codes: bool,
+
+    /// Suggest the final location of links which are permanently redirected
+    /// (301 or 308) as a replacement.
+    /// With `--hint-permanent-redirects=fail`, these links are also treated as errors.
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "suggest", value_parser = PossibleValuesParser::new(PermanentRedirects::VARIANTS).map(|s| s.parse::<PermanentRedirects>().unwrap()))]
+    #[serde(default)]
+    pub(crate) hint_permanent_redirects: Option<PermanentRedirects>,
+
+    /// Suggest replacements for links into the `master` branch of GitHub
+    /// repositories whose default branch is another one (e.g. `main`), which
+    /// is looked up via the GitHub API.
+    /// With `--suggest-default-branch=permalink`, the latest commit of the default
+    /// branch is suggested instead. The suggestions are applied with `--fix`
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "branch", value_parser = PossibleValuesParser::new(DefaultBranchSuggestion::VARIANTS).map(|s| s.parse::<DefaultBranchSuggestion>().unwrap()))]
+    #[serde(default)]
+    pub(crate) suggest_default_branch: Option<DefaultBranchSuggestion>,
+
     /// Maximum number of retries per request
     #[arg(long, default_value = &MAX_RETRIES_STR)]
     #[serde(default = "max_retries")]
@@ -292,6 +854,52 @@ list of excluded status codes. This example will not cache results with a status
     #[serde(default = "max_concurrency")]
     pub(crate) max_concurrency: usize,
 
+    /// Open up to this many connections to each host with several links
+    /// among the first links before checking them, so that the handshakes
+    /// (e.g. of TLS) don't slow down the first burst of requests. The
+    /// connections are opened with HEAD requests to the first link of the
+    /// host. Only the links of these hosts wait for them. The time of the
+    /// warm-up is shown in the `hosts` report section next to the mean
+    /// request time of each host, which doesn't include it
+    #[arg(long, value_name = "CONNECTIONS")]
+    #[serde(default)]
+    pub(crate) warm_up: Option<usize>,
+
+    /// Check at most this many distinct URLs per host. For hosts with more
+    /// URLs, a deterministic random sample is checked and the other links are
+    /// reported as skipped, e.g. for quick health probes of large generated
+    /// sites. All links are collected before the first one is checked
+    #[arg(long, value_name = "N")]
+    #[serde(default)]
+    pub(crate) sample_per_host: Option<usize>,
+
+    /// Host of the checked site, e.g. `example.com`. Can be given multiple
+    /// times. Links to these hosts, to the host of a remote `--base` and to
+    /// local files are internal links, all others are external links.
+    /// Internal and external links are checked with separate concurrency
+    /// limits, so slow external hosts don't hold up the internal links.
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) internal_host: Vec<String>,
+
+    /// Maximum number of concurrent requests to internal links (see
+    /// `--internal-host`). Defaults to `--max-concurrency`
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) internal_max_concurrency: Option<usize>,
+
+    /// Timeout in seconds of requests to internal links (see
+    /// `--internal-host`). Defaults to `--timeout`
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) internal_timeout: Option<usize>,
+
+    /// Maximum number of retries of internal links (see `--internal-host`).
+    /// Defaults to `--max-retries`
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) internal_max_retries: Option<u64>,
+
     /// Number of threads to utilize.
     /// Defaults to number of cores available to the system
     #[arg(short = 'T', long)]
@@ -308,18 +916,59 @@ list of excluded status codes. This example will not cache results with a status
     #[serde(default)]
     pub(crate) insecure: bool,
 
+    /// Minimum TLS version of HTTPS connections. Connections to servers
+    /// which only support older versions fail
+    #[arg(long, value_name = "VERSION", value_parser = PossibleValuesParser::new(TlsVersion::VARIANTS).map(|s| s.parse::<TlsVersion>().unwrap()))]
+    #[serde(default)]
+    pub(crate) min_tls: Option<TlsVersion>,
+
+    /// Trust the root certificates of this PEM file in addition to the
+    /// system ones, e.g. of a corporate certificate authority which signs
+    /// internal HTTPS endpoints. Can be given multiple times
+    #[arg(long, value_name = "PATH")]
+    #[serde(default)]
+    pub(crate) ca_cert: Vec<PathBuf>,
+
+    /// Use HTTP/2 without negotiating it with the server first, also for
+    /// plain HTTP connections. Servers without HTTP/2 support will fail
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) http2_prior_knowledge: bool,
+
+    /// URL or path of a proxy auto-config (PAC) file, which selects the proxy
+    /// for each link. Replaces the proxies of the environment (e.g.
+    /// `HTTPS_PROXY`). Only the common subset of JavaScript in PAC files is
+    /// supported, e.g. `if` statements and PAC functions like `shExpMatch`
+    #[arg(long, value_name = "URL_OR_PATH")]
+    #[serde(default)]
+    pub(crate) proxy_pac: Option<String>,
+
     /// Only test links with the given schemes (e.g. https).
     /// Omit to check links with any other scheme.
     /// At the moment, we support http, https, file, and mailto.
-    #[arg(short, long)]
-    #[serde(default)]
+    #[arg(short, long, visible_alias = "allow-scheme")]
+    #[serde(default, alias = "allow_scheme")]
     pub(crate) scheme: Vec<String>,
 
+    /// Never test links with the given schemes (e.g. ftp). Like `data:` and
+    /// `javascript:` links, which are never tested, they are reported as
+    /// excluded with the reason
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) deny_scheme: Vec<String>,
+
     /// Only check local files and block network requests.
     #[arg(long)]
     #[serde(default)]
     pub(crate) offline: bool,
 
+    /// Test the network connection before checking links: establish a HTTPS connection to a
+    /// known host with the proxy, TLS and `--resolve` options of the run. If this fails, exit
+    /// with code 8 instead of reporting every remote link as broken
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) preflight: bool,
+
     /// URLs to check (supports regex). Has preference over all excludes.
     #[arg(long)]
     #[serde(default)]
@@ -361,6 +1010,20 @@ list of excluded status codes. This example will not cache results with a status
     #[serde(default)]
     pub(crate) exclude_loopback: bool,
 
+    /// Only check the canonical version of versioned documentation (e.g.
+    /// Sphinx or Docusaurus sites), skipping links into other versions like
+    /// `/v1.2/`, `/2.0/` or `/en/stable/`. Links without a version are checked
+    /// as usual
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) skip_versioned_docs: bool,
+
+    /// The canonical version of versioned documentation, used with
+    /// `--skip-versioned-docs`, e.g. `v2.0` [default: `latest`]
+    #[arg(long, value_name = "VERSION")]
+    #[serde(default)]
+    pub(crate) docs_version: Option<String>,
+
     /// Exclude all mail addresses from checking
     /// (deprecated; excluded by default)
     #[arg(long)]
@@ -372,6 +1035,60 @@ list of excluded status codes. This example will not cache results with a status
     #[serde(default)]
     pub(crate) include_mail: bool,
 
+    /// How thoroughly email addresses are checked.
+    ///
+    /// `syntax` only checks the syntax of addresses, `mx` also checks that their domain
+    /// accepts mail. `smtp` also asks the mail server whether the mailbox exists, which is
+    /// slow and often blocked by mail servers.
+    #[arg(long, default_value = "mx", value_parser = PossibleValuesParser::new(MailCheckLevel::VARIANTS).map(|s| s.parse::<MailCheckLevel>().unwrap()))]
+    #[serde(default)]
+    pub(crate) mail_check_level: MailCheckLevel,
+
+    /// Also check phone numbers of `tel:` links.
+    ///
+    /// Numbers have to be in E.164 format, e.g. `tel:+1-555-0100`, and match the patterns of
+    /// their country calling code in the `[tel_patterns]` table of the config file (if any).
+    /// Malformed numbers are reported as errors.
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) include_tel: bool,
+
+    /// Timeout in seconds for a single MX lookup when checking email addresses
+    #[arg(long, default_value = &MAIL_DNS_TIMEOUT_STR)]
+    #[serde(default = "mail_dns_timeout")]
+    pub(crate) mail_dns_timeout: usize,
+
+    /// Maximum number of retries of timed out MX lookups
+    #[arg(long, default_value = &MAIL_DNS_MAX_RETRIES_STR)]
+    #[serde(default = "mail_dns_max_retries")]
+    pub(crate) mail_dns_max_retries: u64,
+
+    /// Warn about mail addresses on disposable domains and role accounts
+    /// (e.g. `noreply@`). Warnings don't affect the exit code
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) lint_mail: bool,
+
+    /// Disposable mail domains to warn about when using `--lint-mail`.
+    /// Defaults to a built-in list of well-known providers
+    #[arg(long, value_delimiter = ',', verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) lint_mail_disposable_domains: Vec<String>,
+
+    /// Role accounts (the part before the `@`) to warn about when using `--lint-mail`.
+    /// Defaults to a built-in list, e.g. `noreply`, `admin`, or `postmaster`
+    #[arg(long, value_delimiter = ',', verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) lint_mail_role_accounts: Vec<String>,
+
+    /// Warn about links to lines of files on GitHub or GitLab (e.g. `#L10`)
+    /// which point into a branch instead of a commit, as the lines drift when
+    /// the branch changes. With `--fix`, the links are pinned to the current
+    /// commit of the branch, which is looked up via the API
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) lint_permalinks: bool,
+
     /// Remap URI matching pattern to different URI
     #[serde(default)]
     #[arg(long)]
@@ -390,11 +1107,67 @@ Example: --fallback-extensions html,htm,php,asp,aspx,jsp,cgi"
     )]
     pub(crate) fallback_extensions: Vec<String>,
 
+    /// Check links to the pages of a static site generator against the source
+    /// files of the pages, e.g. `--path-mapping 'docs/(.*)\.md -> /$1/'` checks
+    /// links to `/guide/install/` against `docs/guide/install.md`. Patterns
+    /// match the paths of files relative to `--root-dir` (or a local `--base`
+    /// or the current directory). Can be given multiple times; the first
+    /// matching mapping of a file wins
+    #[arg(long, value_name = "PATTERN -> PATH")]
+    #[serde(default)]
+    pub(crate) path_mapping: Vec<String>,
+
     /// Custom request header
     #[arg(long)]
     #[serde(default)]
     pub(crate) header: Vec<String>,
 
+    /// Custom request header which is only sent to a single host, e.g.
+    /// `--header-host "api.example.com: Authorization=Bearer X"`.
+    /// Replaces a global header with the same name and isn't sent along
+    /// redirects to other hosts. Can be given multiple times
+    #[arg(long, value_name = "HOST: KEY=VALUE")]
+    #[serde(default)]
+    pub(crate) header_host: Vec<String>,
+
+    /// Value of the `Accept-Language` header, e.g. `--accept-language de`.
+    /// If given multiple times, websites are checked once per language and
+    /// links which only fail in some languages (e.g. missing translations)
+    /// are reported with the failing languages
+    #[arg(long, value_name = "LANGUAGE")]
+    #[serde(default)]
+    pub(crate) accept_language: Vec<String>,
+
+    /// Resolve a host to a fixed address instead of using DNS, like curl's
+    /// `--resolve`. Applies to all ports of the host. Can be given multiple
+    /// times, e.g. `--resolve staging.example.com:443:127.0.0.1`
+    #[arg(long, value_name = "HOST:PORT:ADDR")]
+    #[serde(default)]
+    pub(crate) resolve: Vec<String>,
+
+    /// Send the requests to a host with a different `Host` header, e.g.
+    /// `--host-header origin.example.com=www.example.com` to check the
+    /// origin server of a CDN with the virtual host of the site. Together
+    /// with `--resolve`, a specific CDN edge can be checked. Can be given
+    /// multiple times
+    #[arg(long, value_name = "HOST=VALUE")]
+    #[serde(default)]
+    pub(crate) host_header: Vec<String>,
+
+    /// Ask caches between lychee and the servers for a fresh response by
+    /// sending `Cache-Control: no-cache` and `Pragma: no-cache`. Detects
+    /// failing origin servers which are masked by stale CDN caches
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) bypass_caches: bool,
+
+    /// Add a query parameter with a unique value to each request, e.g.
+    /// `--cache-bust-param lychee`. Bypasses caches which ignore
+    /// `Cache-Control` request headers
+    #[arg(long, value_name = "NAME")]
+    #[serde(default)]
+    pub(crate) cache_bust_param: Option<String>,
+
     /// A List of accepted status codes for valid links
     #[arg(
         short,
@@ -419,10 +1192,51 @@ separated list of accepted status codes. This example will accept 200, 201,
     pub(crate) accept: StatusCodeSelector,
 
     /// Enable the checking of fragments in links.
+    ///
+    /// For remote links, the HTML body of the response is downloaded and searched
+    /// for a matching `id` or `name` attribute.
     #[arg(long)]
     #[serde(default)]
     pub(crate) include_fragments: bool,
 
+    /// Report HTML pages which are retrieved from a different URL (after redirects)
+    /// than their canonical URL, given with `<link rel="canonical">`.
+    /// This downloads the body of all HTML pages.
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) check_canonical: bool,
+
+    /// Render the pages of this host in a headless Chrome or Chromium before checking
+    /// their fragments, canonical URL and assertions, e.g. for single-page applications
+    /// which create their content with JavaScript. The browser sends the same headers
+    /// and cookies as other requests. Can be given multiple times. Requires lychee to be
+    /// built with the `render-js` feature
+    #[arg(long, value_name = "HOST")]
+    #[serde(default)]
+    pub(crate) render_js: Vec<String>,
+
+    /// Describe failed HTML pages by their title and first heading, e.g.
+    /// `Not Found – Page not found – Acme Docs`, to tell blocked requests from genuine errors.
+    /// At most 64 KiB of each error page are downloaded.
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) error_page_snippet: bool,
+
+    /// How the fragments of Markdown headings are generated when checking
+    /// fragments of local files.
+    ///
+    /// Use the style of the renderer the documents are written for. With `custom-regex`,
+    /// all matches of `--fragment-regex` are removed from the lowercased heading and
+    /// whitespace is replaced with `-`.
+    #[arg(long, default_value = "github", value_parser = PossibleValuesParser::new(FragmentStyle::VARIANTS).map(|s| s.parse::<FragmentStyle>().unwrap()))]
+    #[serde(default)]
+    pub(crate) fragment_style: FragmentStyle,
+
+    /// Regex for `--fragment-style custom-regex`, e.g. `[^a-z0-9 -]`
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) fragment_regex: Option<String>,
+
     /// Website timeout in seconds from connect to response finished
     #[arg(short, long, default_value = &TIMEOUT_STR)]
     #[serde(default = "timeout")]
@@ -433,6 +1247,15 @@ separated list of accepted status codes. This example will accept 200, 201,
     #[serde(default = "retry_wait_time")]
     pub(crate) retry_wait_time: usize,
 
+    /// Maximum wall-clock time of the whole run, e.g. `10m`.
+    /// Afterwards, no new requests are sent and failed requests are not
+    /// retried anymore. Requests which were already sent are completed and
+    /// all remaining links are reported as skipped.
+    /// Runs which skipped links exit with code 9, unless other links failed.
+    #[arg(long, value_parser = humantime::parse_duration, verbatim_doc_comment)]
+    #[serde(default, with = "humantime_serde")]
+    pub(crate) max_runtime: Option<Duration>,
+
     /// Request method
     // Using `-X` as a short param similar to curl
     #[arg(short = 'X', long, default_value = DEFAULT_METHOD)]
@@ -456,11 +1279,59 @@ separated list of accepted status codes. This example will accept 200, 201,
     #[serde(default)]
     pub(crate) basic_auth: Option<Vec<BasicAuthSelector>>,
 
+    /// Don't use the credentials of the netrc file (`NETRC` or `~/.netrc`)
+    /// for hosts which don't match `--basic-auth`. Otherwise, the credentials
+    /// of its `machine` entries are sent in https requests to their hosts.
+    /// The `default` entry is ignored
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) no_netrc: bool,
+
     /// GitHub API token to use when checking github.com links, to avoid rate limiting
     #[arg(long, env = "GITHUB_TOKEN", hide_env_values = true)]
     #[serde(default)]
     pub(crate) github_token: Option<SecretString>,
 
+    /// GitLab API token (with the `read_api` scope) to check links into private
+    /// or rate-limited projects on gitlab.com and the hosts of `--gitlab-host`
+    #[arg(
+        long,
+        env = "GITLAB_TOKEN",
+        hide_env_values = true,
+        verbatim_doc_comment
+    )]
+    #[serde(default)]
+    pub(crate) gitlab_token: Option<SecretString>,
+
+    /// Hosts of self-hosted GitLab instances whose links are checked via the
+    /// GitLab API when using `--gitlab-token`, separated by commas
+    #[arg(long, value_delimiter = ',', verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) gitlab_host: Vec<String>,
+
+    /// Token endpoint of an OAuth2 authorization server (https only). An
+    /// access token is fetched with the client credentials flow and sent in
+    /// https requests to the hosts of `--oauth2-host`
+    #[arg(long, value_name = "URL")]
+    #[serde(default)]
+    pub(crate) oauth2_token_url: Option<String>,
+
+    /// Client ID for `--oauth2-token-url`
+    #[arg(long, value_name = "ID")]
+    #[serde(default)]
+    pub(crate) oauth2_client_id: Option<String>,
+
+    /// Client secret for `--oauth2-token-url`
+    #[arg(long, env = "OAUTH2_CLIENT_SECRET", hide_env_values = true)]
+    #[serde(default)]
+    pub(crate) oauth2_client_secret: Option<SecretString>,
+
+    /// Hosts which receive the OAuth2 access token, separated by commas.
+    /// Defaults to the host of `--oauth2-token-url`
+    #[arg(long, value_delimiter = ',')]
+    #[serde(default)]
+    pub(crate) oauth2_host: Vec<String>,
+
     /// Skip missing input files (default is to error if they don't exist)
     #[arg(long)]
     #[serde(default)]
@@ -477,11 +1348,145 @@ separated list of accepted status codes. This example will accept 200, 201,
     #[serde(default)]
     pub(crate) hidden: bool,
 
+    /// Follow symlinks in directories and glob matches. Paths given directly as
+    /// inputs are always followed. Symlink loops are skipped and dangling
+    /// symlinks are reported as errors
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) follow_symlinks: bool,
+
+    /// After checking all inputs, watch the local files, directories and
+    /// globs among them and check the links of changed files again until
+    /// Ctrl+C is pressed
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) watch: bool,
+
+    /// Recursively check all pages of the same website as remote inputs
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) recursive: bool,
+
+    /// Maximum recursion depth (in links away from the input) when using `--recursive`
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) depth: Option<usize>,
+
+    /// Record the progress of a crawl with `--recursive` in this file. An
+    /// interrupted crawl continues where it stopped when lychee runs again
+    /// with the same inputs; the links of pages which were already collected
+    /// are reported again. The file is discarded if the inputs changed and
+    /// removed once the crawl is complete
+    #[arg(long, value_name = "PATH")]
+    #[serde(default)]
+    pub(crate) crawl_state: Option<PathBuf>,
+
+    /// Abort if more than this many input files are found, e.g. because a glob
+    /// accidentally matches a `node_modules` or build directory
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) max_files: Option<usize>,
+
+    /// Abort if more than this many links are found
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) max_links: Option<usize>,
+
+    /// Encoding of input files and stdin without a byte order mark, e.g.
+    /// `latin1` or `shift_jis`. By default, UTF-8 is assumed and Windows-1252
+    /// (Latin-1) is used if a file is not valid UTF-8
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) input_encoding: Option<String>,
+
+    /// Also extract links from inputs which look like binary files (e.g.
+    /// images), which are skipped and listed in the output by default
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) include_binary: bool,
+
+    /// Report undefined references and unused definitions of reference-style
+    /// links and footnotes in Markdown files as errors
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) check_references: bool,
+
+    /// Stream all input files instead of reading them into memory, which
+    /// keeps memory usage low for huge inputs. Files larger than 64 MiB are
+    /// always streamed. Streamed Markdown files are scanned like plain text
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) stream_large_files: bool,
+
+    /// Glob pattern of files to check in container image inputs
+    /// (`oci:<layout>` or `docker://<image>`). Can be given multiple times.
+    /// Defaults to `/usr/share/doc/**`
+    #[arg(long, value_name = "GLOB")]
+    #[serde(default)]
+    pub(crate) image_path: Vec<String>,
+
     /// Find links in verbatim sections like `pre`- and `code` blocks
     #[arg(long)]
     #[serde(default)]
     pub(crate) include_verbatim: bool,
 
+    /// Also find links in the outputs of code cells in Jupyter notebooks
+    /// (`.ipynb`), which are skipped by default
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) include_notebook_outputs: bool,
+
+    /// Also find the images of lazy-loading scripts in HTML, i.e. the
+    /// `data-src` and `data-srcset` attributes, which are skipped by default
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) include_lazy_images: bool,
+
+    /// Only find links in the comments of source code files (Rust,
+    /// JavaScript/TypeScript, Python and shell scripts) instead of the whole
+    /// file, so that links in string literals are not checked. Source files
+    /// are detected by their extension, e.g. with an input like `'src/**/*.rs'`
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) extract_comments: bool,
+
+    /// Report failed links in generated regions (e.g. between
+    /// `<!-- AUTO-GENERATED -->` and `<!-- END -->`) separately. They can't be
+    /// fixed by hand, so they don't fail the run
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) generated_regions: bool,
+
+    /// Regex of the line which starts a generated region, used with
+    /// `--generated-regions` [default: `<!-- AUTO-GENERATED -->`]
+    #[arg(long, value_name = "REGEX")]
+    #[serde(default)]
+    pub(crate) generated_start: Option<String>,
+
+    /// Regex of the line which ends a generated region, used with
+    /// `--generated-regions` [default: `<!-- END -->`]
+    #[arg(long, value_name = "REGEX")]
+    #[serde(default)]
+    pub(crate) generated_end: Option<String>,
+
+    /// Number of failed links which are allowed for the inputs with a tag
+    /// (see `--input-tag`) before they fail the run, e.g.
+    /// `--tag-max-errors blog=5`. Links of other inputs fail the run as usual.
+    /// Can be given multiple times
+    #[arg(long, value_name = "TAG=N")]
+    #[serde(default)]
+    pub(crate) tag_max_errors: Vec<String>,
+
+    /// File type of input files without an extension, like `README` or
+    /// `LICENSE`.
+    ///
+    /// With `auto`, the file type is guessed from the start of the file: HTML if it
+    /// starts with a doctype or `<html>` tag, Markdown if it contains headings, code
+    /// fences or links, and plaintext otherwise.
+    #[arg(long, default_value = "auto", value_parser = PossibleValuesParser::new(ExtensionlessFileType::VARIANTS).map(|s| s.parse::<ExtensionlessFileType>().unwrap()))]
+    #[serde(default)]
+    pub(crate) extensionless_file_type: ExtensionlessFileType,
+
     /// Ignore case when expanding filesystem path glob inputs
     #[arg(long)]
     #[serde(default)]
@@ -492,16 +1497,94 @@ separated list of accepted status codes. This example will accept 200, 201,
     #[serde(default)]
     pub(crate) output: Option<PathBuf>,
 
+    /// Write a JSON summary of the run (exit code, counts per status class,
+    /// errors, cache usage and duration) to this file, even if the run fails
+    #[arg(long, value_parser)]
+    #[serde(default)]
+    pub(crate) report_file: Option<PathBuf>,
+
+    /// Append the outcome of each checked link to this SQLite database, e.g.
+    /// `.lycheehistory.sqlite` (which `lychee history` reads by default).
+    /// Query it with `lychee history <URL>` or `lychee history --flaky`.
+    /// Requires lychee to be built with the `history` feature
+    #[arg(long, value_parser)]
+    #[serde(default)]
+    pub(crate) history: Option<PathBuf>,
+
+    /// Write Prometheus metrics of the run to this file, e.g. for the
+    /// textfile collector of the node exporter: checks by status class,
+    /// checks and errors by host, retries and check durations
+    #[arg(long, value_name = "PATH")]
+    #[serde(default)]
+    pub(crate) metrics_file: Option<PathBuf>,
+
+    /// Serve the Prometheus metrics of the run over HTTP at this address
+    /// while links are checked, e.g. `127.0.0.1:9464`
+    #[arg(long, value_name = "ADDR")]
+    #[serde(default)]
+    pub(crate) metrics_listen: Option<SocketAddr>,
+
+    /// Don't redact secrets in the output, logs and cache. By default, credentials
+    /// in URLs (`user:pass@`), the values of token query parameters and
+    /// `Authorization` headers are replaced with `REDACTED`
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) no_redact: bool,
+
+    /// Names of query parameters whose values are redacted, separated by commas.
+    /// Defaults to a built-in list, e.g. `token`, `api_key`, or `signature`
+    #[arg(long, value_delimiter = ',', verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) redact_query_params: Vec<String>,
+
     /// Set the output display mode. Determines how results are presented in the terminal
     #[arg(long, default_value = "color", value_parser = PossibleValuesParser::new(OutputMode::VARIANTS).map(|s| s.parse::<OutputMode>().unwrap()))]
     #[serde(default)]
     pub(crate) mode: OutputMode,
 
+    /// Write log messages and the spans of the collection, extraction, checks
+    /// and retries of links with their durations to stderr in this format,
+    /// instead of plain log messages. Helps to find out why a run is slow,
+    /// e.g. in CI. The verbosity and `RUST_LOG` still apply. Only available
+    /// on the command line, as logging starts before config files are read
+    #[arg(long, value_parser = PossibleValuesParser::new(TraceFormat::VARIANTS).map(|s| s.parse::<TraceFormat>().unwrap()))]
+    #[serde(skip)]
+    pub(crate) trace_format: Option<TraceFormat>,
+
     /// Output format of final status report
     #[arg(short, long, default_value = "compact", value_parser = PossibleValuesParser::new(StatsFormat::VARIANTS).map(|s| s.parse::<StatsFormat>().unwrap()))]
     #[serde(default)]
     pub(crate) format: StatsFormat,
 
+    /// Sections of the detailed, Markdown and JSON reports, separated by commas,
+    /// e.g. `errors,hosts`
+    ///
+    /// [default: errors,suggestions,excluded]
+    #[arg(long, value_delimiter = ',', value_parser = PossibleValuesParser::new(ReportSection::VARIANTS).map(|s| s.parse::<ReportSection>().unwrap()))]
+    #[serde(default)]
+    pub(crate) report_sections: Vec<ReportSection>,
+
+    /// Replace URLs and input sources in the final report with hashes, keeping
+    /// statuses and counts, e.g. to share the report outside of an organization.
+    /// Links to the same host get the same host hash
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) anonymize: bool,
+
+    /// Secret key of the hashes of `--anonymize`, so that the same link gets the
+    /// same hash in the reports of different runs. Without it, a random key is
+    /// used for each run
+    #[arg(long, env = "LYCHEE_ANONYMIZE_SALT", hide_env_values = true)]
+    #[serde(default)]
+    pub(crate) anonymize_salt: Option<SecretString>,
+
+    /// Compare the broken links with a previous JSON report (`--format json`)
+    /// and report newly broken, fixed and still broken links instead of the
+    /// status report. Only newly broken links fail the run
+    #[arg(long, value_name = "PREVIOUS_REPORT")]
+    #[serde(default)]
+    pub(crate) diff: Option<PathBuf>,
+
     /// When HTTPS is available, treat HTTP links as errors
     #[arg(long)]
     #[serde(default)]
@@ -513,6 +1596,76 @@ separated list of accepted status codes. This example will accept 200, 201,
     #[arg(long)]
     #[serde(default)]
     pub(crate) cookie_jar: Option<PathBuf>,
+
+    /// Rate limits per host, e.g.
+    /// `"github.com" = { max_concurrency = 2, min_interval_ms = 500 }`.
+    /// This can only be set in the config file.
+    #[arg(skip)]
+    #[serde(default)]
+    pub(crate) hosts: HashMap<String, HostLimits>,
+
+    /// Code hosting platforms and API tokens per host, e.g.
+    /// `"git.example.com" = { type = "gitea", token = "..." }`.
+    /// Links to these hosts which fail are checked via the API of the
+    /// platform. This can only be set in the config file.
+    #[arg(skip)]
+    #[serde(default)]
+    pub(crate) platforms: HashMap<String, PlatformHost>,
+
+    /// File types of custom file extensions, e.g. `mdoc = "markdown"`.
+    /// Files with these extensions are also checked in directories.
+    /// This can only be set in the config file.
+    #[arg(skip)]
+    #[serde(default)]
+    pub(crate) filetypes: HashMap<String, FileType>,
+
+    /// Custom labels of statuses, which are reported in place of the status
+    /// text, e.g. `"403" = "requires VPN"`. Keys are status codes, the short
+    /// form of statuses (e.g. `TIMEOUT`) or status texts (e.g. `Cannot find
+    /// fragment`). This can only be set in the config file.
+    #[arg(skip)]
+    #[serde(default)]
+    pub(crate) labels: HashMap<String, String>,
+
+    /// Regexes of valid phone numbers by country calling code for
+    /// `--include-tel`, e.g. `"49" = '49[1-9]\d{5,13}'`. The digits of the
+    /// numbers, including the calling code, have to match the whole regex.
+    /// This can only be set in the config file.
+    #[arg(skip)]
+    #[serde(default)]
+    pub(crate) tel_patterns: HashMap<String, String>,
+
+    /// Commands which are run before and after checking links, e.g.
+    /// `after = "notify-send lychee done"`. Keys are `before`, `after` and
+    /// `on_failure`. This can only be set in a config file which is passed
+    /// with `--config`; hooks of the `lychee.toml` which is loaded from the
+    /// current directory by default are ignored.
+    #[arg(skip)]
+    #[serde(default)]
+    pub(crate) hooks: Hooks,
+
+    /// Requirements on the bodies of the responses of matching URLs, e.g.
+    /// `[[assertions]]` with `url = '^https://docs\.example\.com/'` and
+    /// `not_contains = '(?i)page not found'`. Responses which fail an
+    /// assertion are reported as errors. This can only be set in the config
+    /// file.
+    #[arg(skip)]
+    #[serde(default)]
+    pub(crate) assertions: Vec<Assertion>,
+
+    /// A base config file whose options are used unless they are set in this
+    /// config file, e.g. `extends = "../lychee-base.toml"`.
+    /// Relative paths are resolved against the directory of this config file.
+    /// This can only be set in the config file.
+    #[arg(skip)]
+    #[serde(default)]
+    pub(crate) extends: Option<PathBuf>,
+
+    /// Where options were set, by their name (e.g. `max_retries`).
+    /// Options which are not in here have their default value.
+    #[arg(skip)]
+    #[serde(skip)]
+    pub(crate) sources: HashMap<String, OptionSource>,
 }
 
 impl Config {
@@ -523,25 +1676,109 @@ impl Config {
         toml::from_str(&contents).with_context(|| "Failed to parse configuration file")
     }
 
+    /// Load the config file at `path` followed by the config files it
+    /// (transitively) extends, so that earlier configs take precedence
+    pub(crate) fn load_with_bases(path: &Path) -> Result<Vec<(PathBuf, Config)>> {
+        let mut configs = Vec::new();
+        let mut visited = HashSet::new();
+        let mut next = Some(path.to_path_buf());
+        while let Some(path) = next {
+            let canonical = fs::canonicalize(&path)
+                .with_context(|| format!("Cannot find config file `{}`", path.display()))?;
+            if !visited.insert(canonical) {
+                bail!(
+                    "Config file `{}` extends itself (possibly through other config files)",
+                    path.display()
+                );
+            }
+            let config = Self::load_from_file(&path)
+                .with_context(|| format!("Cannot load config file `{}`", path.display()))?;
+            next = config.extends.as_ref().map(|base| {
+                path.parent()
+                    .map_or_else(|| base.clone(), |parent| parent.join(base))
+            });
+            configs.push((path, config));
+        }
+        Ok(configs)
+    }
+
+    /// Remember which options were set on the command line or with
+    /// environment variables
+    pub(crate) fn record_sources(&mut self, matches: &ArgMatches) {
+        for id in matches.ids() {
+            let source = match matches.value_source(id.as_str()) {
+                Some(ValueSource::CommandLine) => OptionSource::CommandLine,
+                Some(ValueSource::EnvVariable) => OptionSource::Environment,
+                _ => continue,
+            };
+            self.sources.insert(id.to_string(), source);
+        }
+    }
+
+    /// Whether the option with the given name was set on the command line or
+    /// with an environment variable (see [`Config::record_sources`])
+    fn is_set_explicitly(&self, name: &str) -> bool {
+        matches!(
+            self.sources.get(name),
+            Some(OptionSource::CommandLine | OptionSource::Environment)
+        )
+    }
+
+    /// Merge the configs of the selected presets, which only changes options
+    /// that were neither set on the command line nor in the config file
+    pub(crate) fn merge_presets(&mut self) {
+        for preset in self.preset.clone() {
+            self.merge(preset.config(), &OptionSource::Preset(preset));
+        }
+    }
+
     /// Merge the configuration from TOML into the CLI configuration
-    pub(crate) fn merge(&mut self, toml: Config) {
+    pub(crate) fn merge(&mut self, toml: Config, source: &OptionSource) {
         fold_in! {
             // Destination and source configs
-            self, toml;
+            self, toml, source;
 
             // Keys with defaults to assign
             verbose: Verbosity::default();
+            preset: Vec::<Preset>::new();
             cache: false;
             no_progress: false;
+            progress: ProgressMode::default();
+            progress_socket: None;
             max_redirects: DEFAULT_MAX_REDIRECTS;
+            verbose_redirects: false;
+            fail_on_redirects: false;
+            granular_exit_codes: false;
+            hint_permanent_redirects: None;
+            suggest_default_branch: None;
+            fix: false;
+            dry_run: false;
             max_retries: DEFAULT_MAX_RETRIES;
             max_concurrency: DEFAULT_MAX_CONCURRENCY;
+            internal_host: Vec::<String>::new();
+            internal_max_concurrency: None;
+            warm_up: None;
+            sample_per_host: None;
+            internal_timeout: None;
+            internal_max_retries: None;
+            cache_backend: None;
             max_cache_age: humantime::parse_duration(DEFAULT_MAX_CACHE_AGE).unwrap();
+            cache_max_age_dns: None;
+            cache_max_age_timeout: None;
+            cache_max_age_4xx: None;
+            cache_max_age_5xx: None;
+            max_anchor_cache_entries: DEFAULT_MAX_ANCHOR_CACHE_ENTRIES;
             cache_exclude_status: StatusCodeExcluder::default();
             threads: None;
             user_agent: DEFAULT_USER_AGENT;
             insecure: false;
+            min_tls: None;
+            ca_cert: Vec::<PathBuf>::new();
+            http2_prior_knowledge: false;
+            proxy_pac: None;
             scheme: Vec::<String>::new();
+            deny_scheme: Vec::<String>::new();
+            preflight: false;
             include: Vec::<String>::new();
             exclude: Vec::<String>::new();
             exclude_file: Vec::<String>::new(); // deprecated
@@ -551,24 +1788,88 @@ impl Config {
             exclude_link_local: false;
             exclude_loopback: false;
             exclude_mail: false;
+            skip_versioned_docs: false;
+            docs_version: None;
             format: StatsFormat::default();
+            report_sections: Vec::<ReportSection>::new();
+            anonymize: false;
+            diff: None;
+            as_of: None;
             remap: Vec::<String>::new();
             fallback_extensions: Vec::<String>::new();
+            path_mapping: Vec::<String>::new();
             header: Vec::<String>::new();
+            header_host: Vec::<String>::new();
+            accept_language: Vec::<String>::new();
+            resolve: Vec::<String>::new();
+            host_header: Vec::<String>::new();
+            bypass_caches: false;
+            cache_bust_param: None;
             timeout: DEFAULT_TIMEOUT_SECS;
             retry_wait_time: DEFAULT_RETRY_WAIT_TIME_SECS;
+            max_runtime: None;
             method: DEFAULT_METHOD;
             base: None;
             basic_auth: None;
+            no_netrc: false;
             skip_missing: false;
+            follow_symlinks: false;
+            watch: false;
+            recursive: false;
+            depth: None;
+            crawl_state: None;
+            max_files: None;
+            max_links: None;
+            input_encoding: None;
+            include_binary: false;
+            check_references: false;
+            stream_large_files: false;
+            image_path: Vec::<String>::new();
             include_verbatim: false;
+            include_notebook_outputs: false;
+            include_lazy_images: false;
+            extract_comments: false;
+            generated_regions: false;
+            generated_start: None;
+            generated_end: None;
+            tag_max_errors: Vec::<String>::new();
+            extensionless_file_type: ExtensionlessFileType::default();
             include_mail: false;
+            mail_check_level: MailCheckLevel::default();
+            include_tel: false;
+            mail_dns_timeout: DEFAULT_MAIL_DNS_TIMEOUT_SECS;
+            mail_dns_max_retries: DEFAULT_MAIL_DNS_MAX_RETRIES;
+            lint_mail: false;
+            lint_mail_disposable_domains: Vec::<String>::new();
+            lint_mail_role_accounts: Vec::<String>::new();
+            lint_permalinks: false;
             glob_ignore_case: false;
             output: None;
+            report_file: None;
+            history: None;
+            metrics_file: None;
+            metrics_listen: None;
+            no_redact: false;
+            redact_query_params: Vec::<String>::new();
             require_https: false;
             cookie_jar: None;
             include_fragments: false;
+            check_canonical: false;
+            render_js: Vec::<String>::new();
+            error_page_snippet: false;
+            fragment_style: FragmentStyle::default();
+            fragment_regex: None;
             accept: StatusCodeSelector::default();
+            hosts: HashMap::<String, HostLimits>::new();
+            filetypes: HashMap::<String, FileType>::new();
+            labels: HashMap::<String, String>::new();
+            tel_patterns: HashMap::<String, String>::new();
+            hooks: Hooks::default();
+            assertions: Vec::<Assertion>::new();
+            gitlab_host: Vec::<String>::new();
+            oauth2_token_url: None;
+            oauth2_client_id: None;
+            oauth2_host: Vec::<String>::new();
         }
 
         if self
@@ -583,12 +1884,70 @@ impl Config {
                 .is_some()
         {
             self.github_token = toml.github_token;
+            self.sources
+                .insert("github_token".to_string(), source.clone());
+        }
+
+        if self
+            .gitlab_token
+            .as_ref()
+            .map(ExposeSecret::expose_secret)
+            .is_none()
+            && toml
+                .gitlab_token
+                .as_ref()
+                .map(ExposeSecret::expose_secret)
+                .is_some()
+        {
+            self.gitlab_token = toml.gitlab_token;
+            self.sources
+                .insert("gitlab_token".to_string(), source.clone());
+        }
+
+        if self
+            .anonymize_salt
+            .as_ref()
+            .map(ExposeSecret::expose_secret)
+            .is_none()
+            && toml
+                .anonymize_salt
+                .as_ref()
+                .map(ExposeSecret::expose_secret)
+                .is_some()
+        {
+            self.anonymize_salt = toml.anonymize_salt;
+            self.sources
+                .insert("anonymize_salt".to_string(), source.clone());
+        }
+
+        if self
+            .oauth2_client_secret
+            .as_ref()
+            .map(ExposeSecret::expose_secret)
+            .is_none()
+            && toml
+                .oauth2_client_secret
+                .as_ref()
+                .map(ExposeSecret::expose_secret)
+                .is_some()
+        {
+            self.oauth2_client_secret = toml.oauth2_client_secret;
+            self.sources
+                .insert("oauth2_client_secret".to_string(), source.clone());
+        }
+
+        // Tokens can't be compared with the default in `fold_in!`
+        if self.platforms.is_empty() && !toml.platforms.is_empty() {
+            self.platforms = toml.platforms;
+            self.sources.insert("platforms".to_string(), source.clone());
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use strum::IntoEnumIterator;
+
     use super::*;
 
     #[test]
@@ -599,7 +1958,10 @@ mod tests {
         };
 
         let mut cli = Config::default();
-        cli.merge(toml);
+        cli.merge(
+            toml,
+            &OptionSource::ConfigFile(PathBuf::from(LYCHEE_CONFIG_FILE)),
+        );
 
         assert!(cli.accept.contains(429));
         assert!(cli.accept.contains(200));
@@ -608,6 +1970,244 @@ mod tests {
         assert!(!cli.accept.contains(205));
     }
 
+    #[test]
+    fn test_presets() {
+        for preset in Preset::iter() {
+            preset.config();
+        }
+
+        let mut cli = Config {
+            preset: vec![Preset::StaticSite, Preset::Monorepo],
+            max_concurrency: 8,
+            ..Default::default()
+        };
+        cli.merge_presets();
+
+        assert!(cli.include_fragments);
+        assert_eq!(cli.fallback_extensions, ["html"]);
+        assert_eq!(cli.max_concurrency, 8);
+        assert_eq!(cli.hosts["github.com"].max_concurrency, Some(4));
+
+        let mut cli = Config {
+            preset: vec![Preset::StaticSite, Preset::Monorepo],
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            ..Default::default()
+        };
+        cli.merge_presets();
+        assert_eq!(cli.max_concurrency, 64);
+
+        // An explicit value is kept, even if it equals the default
+        let mut cli = Config {
+            preset: vec![Preset::StaticSite],
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            sources: HashMap::from([("max_concurrency".to_string(), OptionSource::CommandLine)]),
+            ..Default::default()
+        };
+        cli.merge_presets();
+        assert_eq!(cli.max_concurrency, DEFAULT_MAX_CONCURRENCY);
+    }
+
+    #[test]
+    fn test_load_with_bases() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("repo")).unwrap();
+        fs::write(
+            dir.path().join("base.toml"),
+            "max_retries = 5\ncache = true",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("repo").join("lychee.toml"),
+            "extends = \"../base.toml\"\nmax_retries = 1",
+        )
+        .unwrap();
+
+        let configs =
+            Config::load_with_bases(&dir.path().join("repo").join("lychee.toml")).unwrap();
+        let mut cli = Config::default();
+        for (path, config) in configs {
+            cli.merge(config, &OptionSource::ConfigFile(path));
+        }
+        assert_eq!(cli.max_retries, 1);
+        assert!(cli.cache);
+        assert_eq!(
+            cli.sources["cache"],
+            OptionSource::ConfigFile(dir.path().join("repo").join("../base.toml"))
+        );
+
+        fs::write(
+            dir.path().join("base.toml"),
+            "extends = \"repo/lychee.toml\"",
+        )
+        .unwrap();
+        let error = Config::load_with_bases(&dir.path().join("base.toml")).unwrap_err();
+        assert!(error.to_string().contains("extends itself"));
+    }
+
+    #[test]
+    fn test_hosts() {
+        let toml: Config = toml::from_str(
+            r#"
+            [hosts]
+            "github.com" = { max_concurrency = 2, min_interval_ms = 500 }
+            "example.com" = { max_concurrency = 8 }
+            "#,
+        )
+        .unwrap();
+
+        let mut cli = Config::default();
+        cli.merge(
+            toml,
+            &OptionSource::ConfigFile(PathBuf::from(LYCHEE_CONFIG_FILE)),
+        );
+
+        assert_eq!(
+            HostConfig::from(cli.hosts["github.com"]),
+            HostConfig::new()
+                .with_max_concurrency(2)
+                .with_min_interval(Duration::from_millis(500))
+        );
+        assert_eq!(
+            HostConfig::from(cli.hosts["example.com"]),
+            HostConfig::new().with_max_concurrency(8)
+        );
+    }
+
+    #[test]
+    fn test_platforms() {
+        let toml: Config = toml::from_str(
+            r#"
+            [platforms]
+            "bitbucket.org" = { type = "bitbucket", token = "secret" }
+            "git.example.com" = { type = "forgejo" }
+            "#,
+        )
+        .unwrap();
+
+        let mut cli = Config::default();
+        cli.merge(
+            toml,
+            &OptionSource::ConfigFile(PathBuf::from(LYCHEE_CONFIG_FILE)),
+        );
+
+        let bitbucket = &cli.platforms["bitbucket.org"];
+        assert_eq!(bitbucket.platform, Platform::Bitbucket);
+        assert_eq!(
+            bitbucket.token.as_ref().map(ExposeSecret::expose_secret),
+            Some("secret")
+        );
+        let forgejo = &cli.platforms["git.example.com"];
+        assert_eq!(forgejo.platform, Platform::Gitea);
+        assert!(forgejo.token.is_none());
+
+        assert!(toml::from_str::<Config>(
+            r#"
+            [platforms]
+            "git.example.com" = { type = "sourceforge" }
+            "#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_assertions() {
+        let toml: Config = toml::from_str(
+            r#"
+            [[assertions]]
+            url = '^https://docs\.example\.com/'
+            not_contains = '(?i)page not found'
+
+            [[assertions]]
+            url = '^https://example\.com/'
+            contains = '<main'
+            "#,
+        )
+        .unwrap();
+
+        let mut cli = Config::default();
+        cli.merge(
+            toml,
+            &OptionSource::ConfigFile(PathBuf::from(LYCHEE_CONFIG_FILE)),
+        );
+
+        assert_eq!(cli.assertions.len(), 2);
+        assert_eq!(
+            cli.assertions[0].not_contains.as_deref(),
+            Some("(?i)page not found")
+        );
+        assert_eq!(cli.assertions[1].contains.as_deref(), Some("<main"));
+
+        assert!(toml::from_str::<Config>(
+            r#"
+            [[assertions]]
+            url = "example.com"
+            matches = "Example"
+            "#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_filetypes() {
+        let toml: Config = toml::from_str(
+            r#"
+            [filetypes]
+            "mdoc" = "markdown"
+            "htm" = "html"
+            "txt.j2" = "plaintext"
+            "#,
+        )
+        .unwrap();
+
+        let mut cli = Config::default();
+        cli.merge(
+            toml,
+            &OptionSource::ConfigFile(PathBuf::from(LYCHEE_CONFIG_FILE)),
+        );
+
+        assert_eq!(cli.filetypes["mdoc"], FileType::Markdown);
+        assert_eq!(cli.filetypes["htm"], FileType::Html);
+        assert_eq!(cli.filetypes["txt.j2"], FileType::Plaintext);
+        assert!(toml::from_str::<Config>("[filetypes]\nrst = \"restructuredtext\"").is_err());
+    }
+
+    #[test]
+    fn test_mail_check_level() {
+        let toml: Config = toml::from_str("mail_check_level = \"smtp\"").unwrap();
+        let mut cli = Config::default();
+        assert_eq!(cli.mail_check_level, MailCheckLevel::Mx);
+        cli.merge(
+            toml,
+            &OptionSource::ConfigFile(PathBuf::from(LYCHEE_CONFIG_FILE)),
+        );
+        assert_eq!(cli.mail_check_level, MailCheckLevel::Smtp);
+        assert!(toml::from_str::<Config>("mail_check_level = \"dns\"").is_err());
+    }
+
+    #[test]
+    fn test_min_tls() {
+        let toml: Config = toml::from_str("min_tls = \"1.2\"").unwrap();
+        let mut cli = Config::default();
+        assert_eq!(cli.min_tls, None);
+        cli.merge(
+            toml,
+            &OptionSource::ConfigFile(PathBuf::from(LYCHEE_CONFIG_FILE)),
+        );
+        assert_eq!(cli.min_tls, Some(TlsVersion::Tls1_2));
+        assert!(toml::from_str::<Config>("min_tls = \"1.4\"").is_err());
+    }
+
+    #[test]
+    fn test_unknown_host_limit() {
+        let toml = toml::from_str::<Config>(
+            r#"
+            [hosts]
+            "github.com" = { max_requests = 2 }
+            "#,
+        );
+        assert!(toml.is_err());
+    }
+
     #[test]
     fn test_default() {
         let cli = Config::default();
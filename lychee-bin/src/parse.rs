@@ -1,6 +1,8 @@
 use anyhow::{anyhow, Context, Result};
-use headers::{HeaderMap, HeaderName};
+use headers::{HeaderMap, HeaderName, HeaderValue};
 use lychee_lib::{remap::Remaps, Base};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
 use std::time::Duration;
 
 /// Split a single HTTP header into a (key, value) tuple
@@ -30,12 +32,99 @@ pub(crate) fn parse_headers<T: AsRef<str>>(headers: &[T]) -> Result<HeaderMap> {
     Ok(out)
 }
 
+/// Parse headers for specific hosts of the form `host: key=value`
+///
+/// Headers of the same host are collected into a single `HeaderMap`.
+pub(crate) fn parse_host_headers(headers: &[String]) -> Result<HashMap<String, HeaderMap>> {
+    let mut out: HashMap<String, HeaderMap> = HashMap::new();
+    for value in headers {
+        let Some((host, header)) = value.split_once(':') else {
+            return Err(anyhow!(
+                "Host header must be of the form 'host: key=value', got {value}"
+            ));
+        };
+        let (key, val) = read_header(header.trim())?;
+        out.entry(host.trim().to_lowercase())
+            .or_default()
+            .append(HeaderName::from_bytes(key.as_bytes())?, val.parse()?);
+    }
+    Ok(out)
+}
+
+/// Parse `Host` headers of the form `host=value`, which are sent with the
+/// requests to `host` instead of the host of the URL
+pub(crate) fn parse_host_header_overrides(
+    overrides: &[String],
+) -> Result<Vec<(String, HeaderValue)>> {
+    overrides
+        .iter()
+        .map(|value| {
+            let Some((host, header)) = value.split_once('=') else {
+                return Err(anyhow!(
+                    "Host header override must be of the form host=value, got {value}"
+                ));
+            };
+            let header = header
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid host header in {value}"))?;
+            Ok((host.trim().to_lowercase(), header))
+        })
+        .collect()
+}
+
 /// Parse URI remaps
 pub(crate) fn parse_remaps(remaps: &[String]) -> Result<Remaps> {
     Remaps::try_from(remaps)
         .context("Remaps must be of the form '<pattern> <uri>' (separated by whitespace)")
 }
 
+/// Parse DNS overrides of the form `host:port:addr` (like curl's `--resolve`)
+///
+/// IPv6 addresses can be wrapped in brackets, e.g. `example.com:443:[::1]`.
+pub(crate) fn parse_dns_overrides(overrides: &[String]) -> Result<HashMap<String, SocketAddr>> {
+    overrides
+        .iter()
+        .map(|value| {
+            let mut parts = value.splitn(3, ':');
+            let (Some(host), Some(port), Some(addr)) = (parts.next(), parts.next(), parts.next())
+            else {
+                return Err(anyhow!(
+                    "DNS override must be of the form host:port:addr, got {value}"
+                ));
+            };
+            let port = port
+                .parse()
+                .with_context(|| format!("Invalid port in DNS override {value}"))?;
+            let addr: IpAddr = addr
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse()
+                .with_context(|| format!("Invalid address in DNS override {value}"))?;
+            Ok((host.to_string(), SocketAddr::new(addr, port)))
+        })
+        .collect()
+}
+
+/// Parse the number of allowed errors per tag of the form `tag=n`
+pub(crate) fn parse_tag_max_errors(limits: &[String]) -> Result<HashMap<String, usize>> {
+    limits
+        .iter()
+        .map(|value| {
+            let Some((tag, max)) = value.split_once('=') else {
+                return Err(anyhow!(
+                    "Tag error limit must be of the form tag=n, got {value}"
+                ));
+            };
+            let max = max
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid number of errors in {value}"))?;
+            Ok((tag.trim().to_string(), max))
+        })
+        .collect()
+}
+
 pub(crate) fn parse_base(src: &str) -> Result<Base, lychee_lib::ErrorKind> {
     Base::try_from(src)
 }
@@ -56,6 +145,82 @@ mod tests {
         assert_eq!(parse_headers(&["accept=text/html"]).unwrap(), custom);
     }
 
+    #[test]
+    fn test_parse_host_headers() {
+        let headers = parse_host_headers(&[
+            "API.example.com: Authorization=Bearer X".to_string(),
+            "api.example.com:X-Api-Key=key".to_string(),
+            "example.org: accept=text/html".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(headers.len(), 2);
+        let api = &headers["api.example.com"];
+        assert_eq!(api[header::AUTHORIZATION], "Bearer X");
+        assert_eq!(api["x-api-key"], "key");
+        assert_eq!(headers["example.org"][header::ACCEPT], "text/html");
+
+        assert!(parse_host_headers(&["Authorization=Bearer X".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_parse_host_header_overrides() {
+        let overrides = parse_host_header_overrides(&[
+            "Origin.example.com=www.example.com".to_string(),
+            "staging.example.com = example.com".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(
+            overrides,
+            [
+                (
+                    "origin.example.com".to_string(),
+                    HeaderValue::from_static("www.example.com")
+                ),
+                (
+                    "staging.example.com".to_string(),
+                    HeaderValue::from_static("example.com")
+                ),
+            ]
+        );
+
+        assert!(parse_host_header_overrides(&["www.example.com".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_parse_dns_overrides() {
+        let overrides = parse_dns_overrides(&[
+            "example.com:443:127.0.0.1".to_string(),
+            "staging.example.com:80:[::1]".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(
+            overrides,
+            HashMap::from([
+                ("example.com".to_string(), "127.0.0.1:443".parse().unwrap()),
+                (
+                    "staging.example.com".to_string(),
+                    "[::1]:80".parse().unwrap()
+                ),
+            ])
+        );
+
+        assert!(parse_dns_overrides(&["example.com:127.0.0.1".to_string()]).is_err());
+        assert!(parse_dns_overrides(&["example.com:https:127.0.0.1".to_string()]).is_err());
+        assert!(parse_dns_overrides(&["example.com:443:localhost".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_parse_tag_max_errors() {
+        let limits = parse_tag_max_errors(&["blog=5".to_string(), "docs = 0".to_string()]).unwrap();
+        assert_eq!(
+            limits,
+            HashMap::from([("blog".to_string(), 5), ("docs".to_string(), 0)])
+        );
+
+        assert!(parse_tag_max_errors(&["blog".to_string()]).is_err());
+        assert!(parse_tag_max_errors(&["blog=-1".to_string()]).is_err());
+    }
+
     #[test]
     fn test_parse_remap() {
         let remaps =
@@ -0,0 +1,171 @@
+//! Pin links to lines of code to commits (`--lint-permalinks` with `--fix`).
+//!
+//! The links flagged by the [`PermalinkLint`] point into a branch, so the
+//! lines they point to drift as the branch changes. The commit which the
+//! branch currently points to is looked up via the GitHub or GitLab API and
+//! suggested as a replacement for the branch.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use lychee_lib::{lint::CodeLink, PermalinkLint};
+use reqwest::{Client, Url};
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+
+use crate::archive::Suggestion;
+use crate::default_branch::GithubApi;
+use crate::stats::{LintReason, ResponseStats};
+
+#[derive(Debug, Deserialize)]
+struct GitlabCommit {
+    id: String,
+}
+
+/// Suggest links into the current commit of the branch as replacements for
+/// the links of `stats` which were flagged by `lint`
+pub(crate) async fn suggest_permalinks(
+    lint: &PermalinkLint,
+    github_token: Option<&SecretString>,
+    gitlab_token: Option<&SecretString>,
+    user_agent: &str,
+    stats: &mut ResponseStats,
+    timeout: Duration,
+) {
+    let client = match Client::builder()
+        .timeout(timeout)
+        .user_agent(user_agent)
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            log::warn!("Cannot look up commits of permalinks: {e}");
+            return;
+        }
+    };
+    let github = GithubApi::new(client.clone(), github_token);
+
+    let mut links = Vec::new();
+    for (source, warnings) in &stats.warning_map {
+        for warning in warnings {
+            if !matches!(warning.reason, LintReason::Permalink(_)) {
+                continue;
+            }
+            let Ok(original) = Url::parse(warning.uri.as_str()) else {
+                continue;
+            };
+            if let Some(link) = lint.code_link(&warning.uri) {
+                links.push((source.clone(), original, link));
+            }
+        }
+    }
+
+    let mut commits: HashMap<(String, String, String), Option<String>> = HashMap::new();
+    for (source, original, link) in links {
+        let key = (link.host.clone(), link.repo.clone(), link.reference.clone());
+        let sha = match commits.get(&key) {
+            Some(sha) => sha.clone(),
+            None => {
+                let sha = if link.host == "github.com" {
+                    github.commit_sha(&link.repo, &link.reference).await
+                } else {
+                    gitlab_commit_sha(&client, &link, gitlab_token).await
+                };
+                let sha = sha
+                    .inspect_err(|e| {
+                        log::warn!(
+                            "Cannot look up commit of `{}` in {}/{}: {e}",
+                            link.reference,
+                            link.host,
+                            link.repo
+                        );
+                    })
+                    .ok();
+                commits.insert(key, sha.clone());
+                sha
+            }
+        };
+        let Some(suggestion) = sha.and_then(|sha| pin(&original, &sha)) else {
+            continue;
+        };
+        stats
+            .suggestion_map
+            .entry(source)
+            .or_default()
+            .insert(Suggestion {
+                original,
+                suggestion,
+            });
+    }
+}
+
+/// The SHA of the commit which the reference of `link` currently points to,
+/// looked up via the GitLab API of its host
+async fn gitlab_commit_sha(
+    client: &Client,
+    link: &CodeLink,
+    token: Option<&SecretString>,
+) -> Result<String, reqwest::Error> {
+    let mut url =
+        Url::parse(&format!("https://{}/api/v4/projects", link.host)).expect("Host of a valid URL");
+    url.path_segments_mut()
+        .expect("HTTPS URLs have path segments")
+        .push(&link.repo)
+        .extend(["repository", "commits"])
+        .push(&link.reference);
+    let request = client.get(url);
+    let request = match token {
+        Some(token) => request.header("PRIVATE-TOKEN", token.expose_secret()),
+        None => request,
+    };
+    let GitlabCommit { id } = request.send().await?.error_for_status()?.json().await?;
+    Ok(id)
+}
+
+/// Replace the reference after `blob` in the path of `url` with the commit
+/// `sha`
+fn pin(url: &Url, sha: &str) -> Option<Url> {
+    let mut segments: Vec<_> = url.path_segments()?.collect();
+    let blob = segments
+        .iter()
+        .skip(2)
+        .position(|segment| *segment == "blob")?
+        + 2;
+    *segments.get_mut(blob + 1)? = sha;
+    let mut pinned = url.clone();
+    pinned.set_path(&format!("/{}", segments.join("/")));
+    Some(pinned)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn test_pin() {
+        assert_eq!(
+            pin(
+                &url("https://github.com/lycheeverse/lychee/blob/master/lychee-lib/src/lib.rs#L10"),
+                "3f2a9c1"
+            ),
+            Some(url(
+                "https://github.com/lycheeverse/lychee/blob/3f2a9c1/lychee-lib/src/lib.rs#L10"
+            ))
+        );
+        assert_eq!(
+            pin(
+                &url("https://gitlab.com/group/subgroup/repo/-/blob/main/src/lib.rs#L10-20"),
+                "3f2a9c1"
+            ),
+            Some(url(
+                "https://gitlab.com/group/subgroup/repo/-/blob/3f2a9c1/src/lib.rs#L10-20"
+            ))
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Connectivity self-test before checking links (`--preflight`)
+//!
+//! Without network access, every remote link fails with a timeout or DNS
+//! error, which buries the actual cause in thousands of errors. The self-test
+//! detects this up front, so the run can be aborted with a clear message.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use lychee_lib::Client;
+use reqwest::Url;
+use tokio::net::{lookup_host, TcpStream};
+use tokio::time::timeout;
+
+/// Host which is resolved and connected to by the self-test
+const PREFLIGHT_URL: &str = "https://example.com";
+
+/// Environment variables of the proxies which are used for HTTPS requests,
+/// in the order of precedence of `reqwest`
+const PROXY_VARIABLES: [&str; 4] = ["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"];
+
+/// Checks that the network can be used to check links: a HTTPS connection
+/// to [`PREFLIGHT_URL`] is established with `client`, so that its proxies,
+/// TLS options and DNS overrides apply.
+///
+/// # Errors
+///
+/// Returns an error which describes why the connection failed. If the
+/// configured proxy (if any) is not reachable or the host can't be resolved,
+/// that is reported instead of the error of the request.
+pub(crate) async fn run(client: &Client, max_duration: Duration) -> Result<()> {
+    let url = Url::parse(PREFLIGHT_URL)?;
+    let Err(e) = probe(client, url.clone(), max_duration).await else {
+        return Ok(());
+    };
+
+    // Find the first step which failed. DNS resolution is done by the proxy
+    // if there is one.
+    let host = url.host_str().unwrap_or_default();
+    match proxy()? {
+        Some(proxy) => check_proxy(&proxy, max_duration).await?,
+        None => check_dns(host, max_duration).await?,
+    }
+    Err(e)
+}
+
+/// Sends a request to `url` with `client`. Any response means that the
+/// connection (e.g. the TLS handshake) succeeded.
+async fn probe(client: &Client, url: Url, max_duration: Duration) -> Result<()> {
+    timeout(max_duration, client.probe(url.clone()))
+        .await
+        .map_err(|_| anyhow!("Timed out connecting to {url}"))?
+        .with_context(|| format!("Cannot connect to {url}"))
+}
+
+/// The proxy for HTTPS requests, if one is configured
+fn proxy() -> Result<Option<Url>> {
+    let Some(value) = PROXY_VARIABLES
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+    else {
+        return Ok(None);
+    };
+    let proxy = Url::parse(&value)
+        // Like `reqwest`, assume HTTP for proxies without a scheme
+        .or_else(|_| Url::parse(&format!("http://{value}")))
+        .with_context(|| format!("Invalid proxy URL `{value}`"))?;
+    Ok(Some(proxy))
+}
+
+/// Checks that `host` can be resolved
+async fn check_dns(host: &str, max_duration: Duration) -> Result<()> {
+    let addresses = timeout(max_duration, lookup_host((host, 443)))
+        .await
+        .map_err(|_| anyhow!("Timed out resolving `{host}`"))?
+        .with_context(|| format!("Cannot resolve `{host}`"))?;
+    if addresses.count() == 0 {
+        return Err(anyhow!("Cannot resolve `{host}`: no addresses found"));
+    }
+    Ok(())
+}
+
+/// Checks that a TCP connection to `proxy` can be established
+async fn check_proxy(proxy: &Url, max_duration: Duration) -> Result<()> {
+    let host = proxy.host_str().unwrap_or_default();
+    let port = proxy.port_or_known_default().unwrap_or(80);
+    timeout(max_duration, TcpStream::connect((host, port)))
+        .await
+        .map_err(|_| anyhow!("Timed out connecting to the proxy `{host}:{port}`"))?
+        .with_context(|| format!("Cannot connect to the proxy `{host}:{port}`"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use lychee_lib::{proxy_pac::ProxyPac, ClientBuilder};
+    use reqwest::Url;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::{check_proxy, probe};
+
+    #[tokio::test]
+    async fn test_check_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        assert!(check_proxy(&proxy, Duration::from_secs(1)).await.is_ok());
+
+        drop(listener);
+        assert!(check_proxy(&proxy, Duration::from_secs(1)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_probe_with_proxy_pac() {
+        // A proxy which answers every request
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request).await;
+                let _ = stream
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                    .await;
+            }
+        });
+        let script = format!(r#"function FindProxyForURL(url, host) {{ return "PROXY {addr}"; }}"#);
+        let pac = ProxyPac::parse(&script).unwrap();
+        // The host can only be reached through the proxy of the PAC file
+        let url = Url::parse("http://preflight.invalid/").unwrap();
+
+        let client = ClientBuilder::builder()
+            .proxy_pac(Some(pac))
+            .build()
+            .client()
+            .unwrap();
+        assert!(probe(&client, url.clone(), Duration::from_secs(5))
+            .await
+            .is_ok());
+
+        let client = ClientBuilder::builder().build().client().unwrap();
+        assert!(probe(&client, url, Duration::from_secs(5)).await.is_err());
+    }
+}
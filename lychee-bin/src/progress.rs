@@ -0,0 +1,133 @@
+//! Progress of the requests per host (`--progress per-host`).
+//!
+//! A run is usually slow because of a single host whose rate limits hold back
+//! its requests. Below the main progress bar, a line per active host shows its
+//! queued and active requests, its throughput and the estimated time until
+//! its queue is done.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use lychee_lib::ratelimit::HostActivity;
+use lychee_lib::Client;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, Instant};
+
+/// How often the lines of the hosts are updated
+const UPDATE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Lines of the active hosts below the main progress bar
+#[derive(Debug)]
+pub(crate) struct HostProgress {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl HostProgress {
+    /// Show the hosts of `client` below the bars of `multi` until
+    /// [`HostProgress::stop`] is called
+    pub(crate) fn spawn(multi: MultiProgress, client: Client) -> Self {
+        let (stop, mut stopped) = oneshot::channel();
+        let task = tokio::spawn(async move {
+            let start = Instant::now();
+            let mut bars: HashMap<String, ProgressBar> = HashMap::new();
+            let mut ticks = interval(UPDATE_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = &mut stopped => break,
+                    _ = ticks.tick() => {
+                        update(&multi, &mut bars, &client.host_activity(), start.elapsed());
+                    }
+                }
+            }
+            for bar in bars.values() {
+                bar.finish_and_clear();
+            }
+        });
+        Self { stop, task }
+    }
+
+    /// Remove the lines of the hosts
+    pub(crate) async fn stop(self) {
+        // The task only ends after being stopped
+        let _ = self.stop.send(());
+        let _ = self.task.await;
+    }
+}
+
+/// Show a line for each host with outstanding requests and remove the lines of
+/// the other hosts
+fn update(
+    multi: &MultiProgress,
+    bars: &mut HashMap<String, ProgressBar>,
+    activity: &[HostActivity],
+    elapsed: Duration,
+) {
+    for host in activity {
+        if host.queued == 0 && host.active == 0 {
+            if let Some(bar) = bars.remove(&host.host) {
+                bar.finish_and_clear();
+            }
+            continue;
+        }
+        let bar = bars.entry(host.host.clone()).or_insert_with(|| {
+            let bar = multi.add(
+                ProgressBar::new_spinner().with_style(
+                    ProgressStyle::with_template("  {prefix:.238} {wide_msg}")
+                        .expect("Valid progress bar"),
+                ),
+            );
+            bar.set_prefix(host.host.clone());
+            bar
+        });
+        bar.set_message(host_message(host, elapsed));
+    }
+}
+
+/// Queued and active requests, throughput and estimated remaining time of
+/// a host
+#[allow(clippy::cast_precision_loss)]
+fn host_message(host: &HostActivity, elapsed: Duration) -> String {
+    let throughput = host.completed as f64 / elapsed.as_secs_f64().max(1.0);
+    let message = format!(
+        "{} queued, {} active, {throughput:.1}/s",
+        host.queued, host.active
+    );
+    if host.completed == 0 {
+        return message;
+    }
+    // The throughput of a rate-limited host is bound by its limits, so the
+    // outstanding requests are done at roughly the same rate
+    let eta = Duration::from_secs_f64(((host.queued + host.active) as f64 / throughput).ceil());
+    format!("{message}, ETA {}", humantime::format_duration(eta))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activity(queued: usize, active: usize, completed: u64) -> HostActivity {
+        HostActivity {
+            host: "github.com".to_string(),
+            queued,
+            active,
+            completed,
+            warm_up: None,
+            mean_request_time: None,
+        }
+    }
+
+    #[test]
+    fn test_host_message() {
+        assert_eq!(
+            host_message(&activity(18, 2, 10), Duration::from_secs(5)),
+            "18 queued, 2 active, 2.0/s, ETA 10s"
+        );
+        assert_eq!(
+            host_message(&activity(3, 1, 0), Duration::from_secs(5)),
+            "3 queued, 1 active, 0.0/s"
+        );
+    }
+}
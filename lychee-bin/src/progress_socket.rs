@@ -0,0 +1,292 @@
+//! Progress events for other programs (`--progress-socket`).
+//!
+//! Wrappers and GUIs connect to a Unix socket and receive a JSON object per
+//! line whenever a link is collected or checked, instead of scraping the
+//! progress bar. Each event contains the counts so far, so clients which
+//! connect late or miss events still show the current progress:
+//!
+//! ```json
+//! {"event":"checked","collected":120,"checked":42,"failed":3,"url":"https://example.com/"}
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::Serialize;
+use tokio::sync::{broadcast, oneshot};
+use tokio::task::JoinHandle;
+
+use crate::redact;
+
+/// Number of events which are buffered for slow clients
+const CAPACITY: usize = 1024;
+
+/// How long clients get to receive the remaining events at the end of a run
+const FINISH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Kind of a [`ProgressEvent`]
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum EventKind {
+    /// Sent to each client when it connects
+    Connected,
+    /// A link was collected from the inputs
+    Collected,
+    /// A link was checked
+    Checked,
+    /// All links were checked
+    Finished,
+}
+
+/// A line on the socket
+#[derive(Debug, Serialize, PartialEq, Eq)]
+struct ProgressEvent<'a> {
+    event: EventKind,
+    collected: usize,
+    checked: usize,
+    failed: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<&'a str>,
+}
+
+/// Counts of the run so far
+#[derive(Debug, Default)]
+struct Counts {
+    collected: AtomicUsize,
+    checked: AtomicUsize,
+    failed: AtomicUsize,
+}
+
+impl Counts {
+    fn event(&self, event: EventKind, url: Option<&str>) -> String {
+        let mut line = serde_json::to_string(&ProgressEvent {
+            event,
+            collected: self.collected.load(Ordering::Relaxed),
+            checked: self.checked.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            url,
+        })
+        .expect("Progress events can be serialized");
+        line.push('\n');
+        line
+    }
+}
+
+/// Sends progress events to the clients of a Unix socket
+#[derive(Debug)]
+pub(crate) struct ProgressSocket {
+    path: PathBuf,
+    counts: Arc<Counts>,
+    events: broadcast::Sender<String>,
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl ProgressSocket {
+    /// Listen for clients on a Unix socket at `path`, replacing a socket
+    /// which was left over by a previous run.
+    ///
+    /// Only the current user can connect to the socket.
+    #[cfg(unix)]
+    pub(crate) fn bind(path: &Path) -> Result<Self> {
+        use std::fs::{DirBuilder, Permissions};
+        use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+
+        use anyhow::{bail, Context};
+        use tokio::net::UnixListener;
+
+        if let Ok(metadata) = std::fs::symlink_metadata(path) {
+            if !metadata.file_type().is_socket() {
+                bail!(
+                    "Cannot create progress socket `{}`, the file exists",
+                    path.display()
+                );
+            }
+            std::fs::remove_file(path)?;
+        }
+        // The socket is created with the umask, so it is bound in a private
+        // directory and only moved into place once its permissions are
+        // restricted. Otherwise, other users could connect in between.
+        let parent = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let private = parent.join(format!(".lychee-progress-{}", std::process::id()));
+        DirBuilder::new()
+            .mode(0o700)
+            .create(&private)
+            .with_context(|| format!("Cannot create progress socket `{}`", path.display()))?;
+        let staged = private.join("socket");
+        let listener = UnixListener::bind(&staged).and_then(|listener| {
+            std::fs::set_permissions(&staged, Permissions::from_mode(0o600))?;
+            std::fs::rename(&staged, path)?;
+            Ok(listener)
+        });
+        let _ = std::fs::remove_dir_all(&private);
+        let listener = listener
+            .with_context(|| format!("Cannot create progress socket `{}`", path.display()))?;
+
+        let counts = Arc::new(Counts::default());
+        let (events, receiver) = broadcast::channel(CAPACITY);
+        let (stop, mut stopped) = oneshot::channel();
+        let task = tokio::spawn({
+            let counts = Arc::clone(&counts);
+            async move {
+                let mut clients = tokio::task::JoinSet::new();
+                loop {
+                    tokio::select! {
+                        _ = &mut stopped => break,
+                        client = listener.accept() => {
+                            let Ok((stream, _)) = client else {
+                                continue;
+                            };
+                            let hello = counts.event(EventKind::Connected, None);
+                            clients.spawn(serve(stream, hello, receiver.resubscribe()));
+                        }
+                    }
+                }
+                // Let the clients receive the remaining events
+                while clients.join_next().await.is_some() {}
+            }
+        });
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            counts,
+            events,
+            stop,
+            task,
+        })
+    }
+
+    /// Progress sockets need Unix sockets
+    #[cfg(not(unix))]
+    pub(crate) fn bind(_path: &Path) -> Result<Self> {
+        anyhow::bail!("`--progress-socket` is only supported on Unix")
+    }
+
+    /// A link was collected from the inputs
+    pub(crate) fn collected(&self, url: &str) {
+        self.counts.collected.fetch_add(1, Ordering::Relaxed);
+        self.send(EventKind::Collected, Some(url));
+    }
+
+    /// A link was checked
+    pub(crate) fn checked(&self, url: &str, failed: bool) {
+        self.counts.checked.fetch_add(1, Ordering::Relaxed);
+        if failed {
+            self.counts.failed.fetch_add(1, Ordering::Relaxed);
+        }
+        self.send(EventKind::Checked, Some(url));
+    }
+
+    /// Send the final counts to the clients, wait until they received all
+    /// events and remove the socket
+    pub(crate) async fn finish(self) {
+        self.send(EventKind::Finished, None);
+        let Self {
+            path,
+            events,
+            stop,
+            task,
+            ..
+        } = self;
+        // Without senders, the clients stop after the remaining events
+        drop(events);
+        let _ = stop.send(());
+        let _ = tokio::time::timeout(FINISH_TIMEOUT, task).await;
+        let _ = std::fs::remove_file(path);
+    }
+
+    fn send(&self, event: EventKind, url: Option<&str>) {
+        let url = url.map(redact::redact);
+        // There are no receivers without clients
+        let _ = self.events.send(self.counts.event(event, url.as_deref()));
+    }
+}
+
+/// Write the events to a client until the run is finished or the client
+/// disconnects
+#[cfg(unix)]
+async fn serve(
+    mut stream: tokio::net::UnixStream,
+    hello: String,
+    mut events: broadcast::Receiver<String>,
+) {
+    use tokio::io::AsyncWriteExt;
+    use tokio::sync::broadcast::error::RecvError;
+
+    if stream.write_all(hello.as_bytes()).await.is_err() {
+        return;
+    }
+    loop {
+        let line = match events.recv().await {
+            Ok(line) => line,
+            // The counts of the next event are up to date again
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        };
+        if stream.write_all(line.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio::net::UnixStream;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_progress_socket() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("progress.sock");
+        let socket = ProgressSocket::bind(&path).unwrap();
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+        // The private directory of the socket is removed
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let mut lines = BufReader::new(UnixStream::connect(&path).await.unwrap()).lines();
+        // Events are only sent to connected clients
+        assert_eq!(
+            lines.next_line().await.unwrap().unwrap(),
+            r#"{"event":"connected","collected":0,"checked":0,"failed":0}"#
+        );
+
+        socket.collected("https://example.com/");
+        socket.collected("https://example.com/broken");
+        socket.checked("https://example.com/broken", true);
+        socket.finish().await;
+
+        let mut events = Vec::new();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            events.push(line);
+        }
+        assert_eq!(
+            events,
+            [
+                r#"{"event":"collected","collected":1,"checked":0,"failed":0,"url":"https://example.com/"}"#,
+                r#"{"event":"collected","collected":2,"checked":0,"failed":0,"url":"https://example.com/broken"}"#,
+                r#"{"event":"checked","collected":2,"checked":1,"failed":1,"url":"https://example.com/broken"}"#,
+                r#"{"event":"finished","collected":2,"checked":1,"failed":1}"#,
+            ]
+        );
+        assert!(!path.exists());
+
+        // Stale sockets are replaced
+        std::os::unix::net::UnixListener::bind(&path).unwrap();
+        ProgressSocket::bind(&path).unwrap().finish().await;
+    }
+}
@@ -0,0 +1,119 @@
+//! Machine-readable summary of a run (`--report-file`)
+//!
+//! The exit code alone doesn't tell CI wrappers *why* lychee failed.
+//! The report is written in every code path, including config errors,
+//! so that wrappers can always rely on it being present.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::stats::ResponseStats;
+
+/// Number of links per status class
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub(crate) struct Counts {
+    pub(crate) total: usize,
+    pub(crate) successful: usize,
+    pub(crate) errors: usize,
+    pub(crate) timeouts: usize,
+    pub(crate) redirects: usize,
+    pub(crate) excludes: usize,
+    pub(crate) skipped: usize,
+    pub(crate) unsupported: usize,
+    pub(crate) unknown: usize,
+    pub(crate) cached: usize,
+    pub(crate) warnings: usize,
+    pub(crate) generated_errors: usize,
+}
+
+impl From<&ResponseStats> for Counts {
+    fn from(stats: &ResponseStats) -> Self {
+        Self {
+            total: stats.total,
+            successful: stats.successful,
+            errors: stats.errors,
+            timeouts: stats.timeouts,
+            redirects: stats.redirects,
+            excludes: stats.excludes,
+            skipped: stats.skipped,
+            unsupported: stats.unsupported,
+            unknown: stats.unknown,
+            cached: stats.cached,
+            warnings: stats.warnings,
+            generated_errors: stats.generated_errors,
+        }
+    }
+}
+
+/// Summary of a run, written to the file given with `--report-file`
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct Report {
+    /// Exit code of the process
+    pub(crate) exit_code: i32,
+    /// Links per status class; `None` if no links were checked
+    /// (e.g. because of an error or when using `--dump`)
+    pub(crate) counts: Option<Counts>,
+    /// Error while loading the configuration
+    pub(crate) config_error: Option<String>,
+    /// Any other error which aborted the run
+    pub(crate) error: Option<String>,
+    /// Whether results of a previous run were loaded from the cache
+    pub(crate) cache_used: bool,
+    /// Wall time of the run in seconds
+    pub(crate) duration_secs: f64,
+}
+
+impl Report {
+    /// Write the report as JSON to `path`
+    pub(crate) fn write(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+            .with_context(|| format!("Cannot write report to `{}`", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_from_stats() {
+        let stats = ResponseStats {
+            total: 5,
+            successful: 2,
+            errors: 1,
+            timeouts: 1,
+            excludes: 1,
+            ..ResponseStats::default()
+        };
+        let counts = Counts::from(&stats);
+        assert_eq!(counts.total, 5);
+        assert_eq!(counts.successful, 2);
+        assert_eq!(counts.errors, 1);
+        assert_eq!(counts.timeouts, 1);
+        assert_eq!(counts.excludes, 1);
+        assert_eq!(counts.redirects, 0);
+    }
+
+    #[test]
+    fn test_write_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.json");
+        let report = Report {
+            exit_code: 3,
+            config_error: Some("invalid config".to_string()),
+            ..Report::default()
+        };
+        report.write(&path).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["exit_code"], 3);
+        assert_eq!(json["config_error"], "invalid config");
+        assert_eq!(json["counts"], serde_json::Value::Null);
+        assert_eq!(json["cache_used"], false);
+    }
+}
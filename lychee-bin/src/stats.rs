@@ -2,11 +2,134 @@
 #![allow(clippy::mutable_key_type)]
 
 use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use crate::archive::Suggestion;
-use lychee_lib::{CacheStatus, InputSource, Response, ResponseBody, Status};
+use crate::archive::{HistoricalStatus, Suggestion};
+use crate::default_branch::master_branch_repo;
+use crate::history::Outcome;
+use crate::options::{DefaultBranchSuggestion, PermanentRedirects, ReportSection};
+use lychee_lib::{
+    ratelimit::HostActivity, CacheStatus, InputSource, MailLintKind, PermalinkLintKind, RawUriSpan,
+    ReferenceIssue, Response, ResponseBody, SkipReason, Status, Uri,
+};
+use reqwest::Url;
 use serde::Serialize;
 
+/// A lint warning for a link
+///
+/// Warnings don't count as errors and don't affect the exit code.
+#[derive(Debug, Serialize, Hash, PartialEq, Eq)]
+pub(crate) struct Warning {
+    /// The URI which was flagged
+    pub(crate) uri: Uri,
+    /// The reason why the URI was flagged
+    pub(crate) reason: LintReason,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} | {}", self.uri, self.reason)
+    }
+}
+
+/// The reason of a lint warning, depending on the lint which flagged the link
+#[derive(Debug, Serialize, Hash, PartialEq, Eq)]
+#[serde(untagged)]
+pub(crate) enum LintReason {
+    /// Flagged by `--lint-mail`
+    Mail(MailLintKind),
+    /// Flagged by `--lint-permalinks`
+    Permalink(PermalinkLintKind),
+    /// Flagged while checking the fragments of local files
+    Fragment(FragmentLintKind),
+}
+
+impl Display for LintReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Mail(reason) => reason.fmt(f),
+            Self::Permalink(reason) => reason.fmt(f),
+            Self::Fragment(reason) => reason.fmt(f),
+        }
+    }
+}
+
+/// The reason why a fragment of a local file was flagged
+#[derive(Debug, Serialize, Hash, PartialEq, Eq)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub(crate) enum FragmentLintKind {
+    /// The fragment occurs more than once in the file, e.g. as the `id` of
+    /// two HTML elements. The span is the position of the repetition.
+    Duplicate(RawUriSpan),
+}
+
+impl Display for FragmentLintKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Duplicate(span) => {
+                write!(f, "Duplicate fragment at {span}, links to it are ambiguous")
+            }
+        }
+    }
+}
+
+impl From<MailLintKind> for LintReason {
+    fn from(reason: MailLintKind) -> Self {
+        Self::Mail(reason)
+    }
+}
+
+impl From<PermalinkLintKind> for LintReason {
+    fn from(reason: PermalinkLintKind) -> Self {
+        Self::Permalink(reason)
+    }
+}
+
+/// Number of directories in the `directories` report section
+pub(crate) const TOP_DIRECTORIES: usize = 10;
+
+/// Number of links and errors of a single host or source directory
+#[derive(Debug, Default, Serialize, PartialEq, Eq)]
+pub(crate) struct LinkCounts {
+    /// Total number of links
+    pub(crate) total: usize,
+    /// Number of links with an error status
+    pub(crate) errors: usize,
+}
+
+impl LinkCounts {
+    /// Percentage of links with an error status, rounded down
+    pub(crate) const fn error_percentage(&self) -> usize {
+        if self.total == 0 {
+            return 0;
+        }
+        self.errors * 100 / self.total
+    }
+
+    /// Count a link with or without an error status
+    const fn add(&mut self, is_error: bool) {
+        self.total += 1;
+        if is_error {
+            self.errors += 1;
+        }
+    }
+}
+
+/// Warm-up and request times of a single host
+#[derive(Debug, Default, Serialize, PartialEq, Eq)]
+pub(crate) struct HostTimings {
+    /// Time in milliseconds to open the connections to the host before its
+    /// links were checked (if `--warm-up` is set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) warm_up_ms: Option<u64>,
+    /// Mean time in milliseconds from sending a request to the host until
+    /// the response headers were received
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) mean_request_ms: Option<u64>,
+}
+
 /// Response statistics
 ///
 /// This struct contains various counters for the responses received during a
@@ -34,20 +157,93 @@ pub(crate) struct ResponseStats {
     pub(crate) excludes: usize,
     /// Number of responses with an error status
     pub(crate) errors: usize,
+    /// Number of links which were not checked, e.g. because they weren't
+    /// sampled (`--sample-per-host`)
+    pub(crate) skipped: usize,
+    /// Number of skipped links which were not checked because the maximum
+    /// runtime (`--max-runtime`) was reached. Such runs are incomplete.
+    pub(crate) deadline_skipped: usize,
     /// Number of responses that were cached from a previous run
     pub(crate) cached: usize,
+    /// Number of lint warnings (if `--lint-mail` is enabled)
+    pub(crate) warnings: usize,
+    /// Number of failed links inside generated regions (if
+    /// `--generated-regions` is enabled), which don't count as errors
+    pub(crate) generated_errors: usize,
     /// Map to store successful responses (if `detailed_stats` is enabled)
     pub(crate) success_map: HashMap<InputSource, HashSet<ResponseBody>>,
     /// Map to store failed responses (if `detailed_stats` is enabled)
     pub(crate) error_map: HashMap<InputSource, HashSet<ResponseBody>>,
     /// Replacement suggestions for failed responses (if `--suggest` is enabled)
     pub(crate) suggestion_map: HashMap<InputSource, HashSet<Suggestion>>,
+    /// Whether failed links worked at the date given with `--as-of`
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub(crate) history_map: HashMap<InputSource, HashSet<HistoricalStatus>>,
     /// Map to store excluded responses (if `detailed_stats` is enabled)
     pub(crate) excluded_map: HashMap<InputSource, HashSet<ResponseBody>>,
+    /// Map to store lint warnings (if `--lint-mail` is enabled)
+    pub(crate) warning_map: HashMap<InputSource, HashSet<Warning>>,
+    /// Map to store failed responses inside generated regions
+    pub(crate) generated_error_map: HashMap<InputSource, HashSet<ResponseBody>>,
+    /// Link counts per host (if the `hosts` report section is selected)
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub(crate) host_map: HashMap<String, LinkCounts>,
+    /// Warm-up and request times per host (if the `hosts` report section is
+    /// selected)
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub(crate) host_timings: HashMap<String, HostTimings>,
+    /// Link counts per directory of the inputs (if the `directories` report
+    /// section is selected)
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub(crate) directory_map: HashMap<String, LinkCounts>,
+    /// Link counts per type of input, e.g. `stdin`, `file` or `remote`
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub(crate) source_type_map: HashMap<&'static str, LinkCounts>,
+    /// Link counts per tag of the inputs (see `--input-tag`)
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub(crate) tag_map: HashMap<String, LinkCounts>,
+    /// Inputs which were skipped because they look like binary files
+    /// (unless `--include-binary` is enabled)
+    pub(crate) skipped_binaries: HashSet<InputSource>,
+    /// Undefined references and unused definitions in Markdown files (if
+    /// `--check-references` is enabled)
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub(crate) reference_issues: HashMap<InputSource, Vec<ReferenceIssue>>,
     /// Used to store the duration of the run in seconds.
     pub(crate) duration_secs: u64,
     /// Also track successful and excluded responses
     pub(crate) detailed_stats: bool,
+    /// Count redirected responses as errors (if `--fail-on-redirects` is enabled)
+    #[serde(skip)]
+    pub(crate) fail_on_redirects: bool,
+    /// Suggest the targets of permanent redirects as replacements (if
+    /// `--hint-permanent-redirects` is enabled)
+    #[serde(skip)]
+    pub(crate) permanent_redirects: Option<PermanentRedirects>,
+    /// Suggest the default branches of GitHub repositories as replacements
+    /// for links into `master` (if `--suggest-default-branch` is enabled)
+    #[serde(skip)]
+    pub(crate) default_branch: Option<DefaultBranchSuggestion>,
+    /// Links into the `master` branch of GitHub repositories (if
+    /// `--suggest-default-branch` is enabled)
+    #[serde(skip)]
+    pub(crate) master_branch_links: HashMap<InputSource, HashSet<Url>>,
+    /// Also count the links per host (if the `hosts` report section is selected)
+    #[serde(skip)]
+    pub(crate) host_stats: bool,
+    /// Also count the links per source directory (if the `directories`
+    /// report section is selected)
+    #[serde(skip)]
+    pub(crate) directory_stats: bool,
+    /// Outcomes of the checked links (if `record_outcomes` is enabled)
+    #[serde(skip)]
+    pub(crate) outcomes: Vec<Outcome>,
+    /// Also record the outcome of each checked link (if `--history` is set)
+    #[serde(skip)]
+    pub(crate) record_outcomes: bool,
+    /// Number of errors which are allowed per tag (see `--tag-max-errors`)
+    #[serde(skip)]
+    pub(crate) tag_max_errors: HashMap<String, usize>,
 }
 
 impl ResponseStats {
@@ -68,17 +264,41 @@ impl ResponseStats {
     pub(crate) fn increment_status_counters(&mut self, status: &Status) {
         match status {
             Status::Ok(_) => self.successful += 1,
-            Status::Error(_) => self.errors += 1,
+            Status::Error(_) | Status::CanonicalMismatch { .. } => self.errors += 1,
             Status::UnknownStatusCode(_) => self.unknown += 1,
             Status::Timeout(_) => self.timeouts += 1,
-            Status::Redirected(_) => self.redirects += 1,
-            Status::Excluded => self.excludes += 1,
+            Status::Redirected(..) => {
+                self.redirects += 1;
+                // Redirects which were followed to a successful response
+                if status.is_success() {
+                    if self.is_failed_redirect(status) {
+                        self.errors += 1;
+                    } else {
+                        self.successful += 1;
+                    }
+                }
+            }
+            Status::Excluded | Status::ExcludedScheme(_) => self.excludes += 1,
             Status::Unsupported(_) => self.unsupported += 1,
-            Status::Cached(cache_status) => {
+            Status::Skipped(reason) => {
+                self.skipped += 1;
+                if *reason == SkipReason::Deadline {
+                    self.deadline_skipped += 1;
+                }
+            }
+            Status::Cached(cache_status, _) => {
                 self.cached += 1;
                 match cache_status {
                     CacheStatus::Ok(_) => self.successful += 1,
                     CacheStatus::Error(_) => self.errors += 1,
+                    CacheStatus::Redirected { .. } => {
+                        self.redirects += 1;
+                        if self.is_failed_redirect(status) {
+                            self.errors += 1;
+                        } else {
+                            self.successful += 1;
+                        }
+                    }
                     CacheStatus::Excluded => self.excludes += 1,
                     CacheStatus::Unsupported => self.unsupported += 1,
                 }
@@ -91,25 +311,292 @@ impl ResponseStats {
         let status = response.status();
         let source = response.source().clone();
         let status_map_entry = match status {
-            _ if status.is_error() => self.error_map.entry(source).or_default(),
+            _ if self.is_failure(status) => self.error_map.entry(source).or_default(),
             Status::Ok(_) if self.detailed_stats => self.success_map.entry(source).or_default(),
-            Status::Excluded if self.detailed_stats => self.excluded_map.entry(source).or_default(),
+            Status::Excluded | Status::ExcludedScheme(_) if self.detailed_stats => {
+                self.excluded_map.entry(source).or_default()
+            }
             _ => return,
         };
-        status_map_entry.insert(response.1);
+        status_map_entry.insert(response.body);
     }
 
     /// Update the stats with a new response
     pub(crate) fn add(&mut self, response: Response) {
         self.total += 1;
+        if self.record_outcomes {
+            let success = !self.is_failure(response.status());
+            self.outcomes.extend(Outcome::new(&response, success));
+        }
+        if response.is_generated() && self.is_failure(response.status()) {
+            // Links in generated regions can't be fixed by hand, so they are
+            // reported separately and don't fail the run
+            self.generated_errors += 1;
+            self.generated_error_map
+                .entry(response.source().clone())
+                .or_default()
+                .insert(response.body);
+            return;
+        }
         self.increment_status_counters(response.status());
+        self.add_host_stats(&response);
+        self.add_directory_stats(&response);
+        self.add_source_type_stats(&response);
+        self.add_tag_stats(&response);
+        self.add_redirect_suggestion(&response);
+        self.add_master_branch_link(&response);
         self.add_response_status(response);
     }
 
+    /// Add a lint warning for a link found in the given input
+    pub(crate) fn add_warning(&mut self, source: InputSource, warning: Warning) {
+        if self.warning_map.entry(source).or_default().insert(warning) {
+            self.warnings += 1;
+        }
+    }
+
+    /// Add a warning for each fragment which occurs more than once in a
+    /// checked local file, with the file as the source
+    pub(crate) fn add_duplicate_fragments(
+        &mut self,
+        duplicates: HashMap<PathBuf, Vec<(String, RawUriSpan)>>,
+    ) {
+        for (path, fragments) in duplicates {
+            let Some(mut url) = file_url(&path) else {
+                continue;
+            };
+            for (fragment, span) in fragments {
+                url.set_fragment(Some(&fragment));
+                let warning = Warning {
+                    uri: Uri::from(url.clone()),
+                    reason: LintReason::Fragment(FragmentLintKind::Duplicate(span)),
+                };
+                self.add_warning(InputSource::FsPath(path.clone()), warning);
+            }
+        }
+    }
+
+    /// Count the link of `response` for its host
+    fn add_host_stats(&mut self, response: &Response) {
+        if !self.host_stats {
+            return;
+        }
+        let uri = &response.body().uri;
+        let Some(host) = uri
+            .domain()
+            .map(ToString::to_string)
+            .or_else(|| uri.host_ip().map(|ip| ip.to_string()))
+        else {
+            return;
+        };
+        let is_error = self.is_failure(response.status());
+        self.host_map.entry(host).or_default().add(is_error);
+    }
+
+    /// Add the warm-up and request times of the hosts in the `hosts` report
+    /// section
+    pub(crate) fn add_host_timings(&mut self, activity: &[HostActivity]) {
+        if !self.host_stats {
+            return;
+        }
+        let millis = |duration: Duration| u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+        for host in activity {
+            if !self.host_map.contains_key(&host.host) {
+                continue;
+            }
+            let timings = HostTimings {
+                warm_up_ms: host.warm_up.map(millis),
+                mean_request_ms: host.mean_request_time.map(millis),
+            };
+            if timings != HostTimings::default() {
+                self.host_timings.insert(host.host.clone(), timings);
+            }
+        }
+    }
+
+    /// Count the link of `response` for the directory of its input
+    fn add_directory_stats(&mut self, response: &Response) {
+        if !self.directory_stats {
+            return;
+        }
+        let Some(directory) = source_directory(response.source()) else {
+            return;
+        };
+        let is_error = self.is_failure(response.status());
+        self.directory_map
+            .entry(directory)
+            .or_default()
+            .add(is_error);
+    }
+
+    /// Count the link of `response` for the type of its input
+    fn add_source_type_stats(&mut self, response: &Response) {
+        let is_error = self.is_failure(response.status());
+        self.source_type_map
+            .entry(source_type(response.source()))
+            .or_default()
+            .add(is_error);
+    }
+
+    /// Count the link of `response` for the tag of its input (if any)
+    fn add_tag_stats(&mut self, response: &Response) {
+        let Some(tag) = response.tag() else {
+            return;
+        };
+        let is_error = self.is_failure(response.status());
+        self.tag_map
+            .entry(tag.to_string())
+            .or_default()
+            .add(is_error);
+    }
+
+    /// Link counts per tag, sorted by tag, with the number of allowed errors
+    /// of each tag (if any)
+    pub(crate) fn tags(&self) -> Vec<(&str, &LinkCounts, Option<usize>)> {
+        let mut tags: Vec<_> = self
+            .tag_map
+            .iter()
+            .map(|(tag, counts)| (tag.as_str(), counts, self.tag_max_errors.get(tag).copied()))
+            .collect();
+        tags.sort_by_key(|(tag, ..)| *tag);
+        tags
+    }
+
+    /// Number of errors of tags which stay within their allowed number of
+    /// errors, so they don't fail the run
+    fn allowed_tag_errors(&self) -> usize {
+        self.tags()
+            .into_iter()
+            .filter(|(_, counts, max_errors)| max_errors.is_some_and(|max| counts.errors <= max))
+            .map(|(_, counts, _)| counts.errors)
+            .sum()
+    }
+
+    /// Link counts per type of input, sorted by type, if links were found in
+    /// more than one type of input (e.g. both stdin and files)
+    pub(crate) fn mixed_source_types(&self) -> Vec<(&str, &LinkCounts)> {
+        if self.source_type_map.len() < 2 {
+            return Vec::new();
+        }
+        let mut source_types: Vec<_> = self
+            .source_type_map
+            .iter()
+            .map(|(source_type, counts)| (*source_type, counts))
+            .collect();
+        source_types.sort_by_key(|(source_type, _)| *source_type);
+        source_types
+    }
+
+    /// The directories with the highest error rates (at most
+    /// [`TOP_DIRECTORIES`]), leaving out directories without errors
+    pub(crate) fn worst_directories(&self) -> Vec<(&str, &LinkCounts)> {
+        let mut directories: Vec<_> = self
+            .directory_map
+            .iter()
+            .filter(|(_, counts)| counts.errors > 0)
+            .map(|(directory, counts)| (directory.as_str(), counts))
+            .collect();
+        // Compare the error rates without rounding
+        directories.sort_by(|(a, a_counts), (b, b_counts)| {
+            (b_counts.errors * a_counts.total)
+                .cmp(&(a_counts.errors * b_counts.total))
+                .then(b_counts.errors.cmp(&a_counts.errors))
+                .then(a.cmp(b))
+        });
+        directories.truncate(TOP_DIRECTORIES);
+        directories
+    }
+
+    /// Remove the sections which are not in `sections` from the report
+    pub(crate) fn retain_sections(&mut self, sections: &[ReportSection]) {
+        if !sections.contains(&ReportSection::Errors) {
+            self.error_map.clear();
+            self.generated_error_map.clear();
+            self.history_map.clear();
+        }
+        if !sections.contains(&ReportSection::Suggestions) {
+            self.suggestion_map.clear();
+        }
+        if !sections.contains(&ReportSection::Excluded) {
+            self.excluded_map.clear();
+        }
+        if !sections.contains(&ReportSection::Hosts) {
+            self.host_map.clear();
+            self.host_timings.clear();
+        }
+        if !sections.contains(&ReportSection::Directories) {
+            self.directory_map.clear();
+        }
+    }
+
+    /// Suggest the target of a permanent redirect as a replacement for the
+    /// link of `response` (if `--hint-permanent-redirects` is enabled)
+    fn add_redirect_suggestion(&mut self, response: &Response) {
+        let Status::Redirected(_, redirects) = response.status() else {
+            return;
+        };
+        if self.permanent_redirects.is_none() {
+            return;
+        }
+        let (Some(target), Ok(original)) = (
+            redirects.permanent_target(),
+            Url::parse(response.body().uri.as_str()),
+        ) else {
+            return;
+        };
+        self.suggestion_map
+            .entry(response.source().clone())
+            .or_default()
+            .insert(Suggestion {
+                original,
+                suggestion: target.clone(),
+            });
+    }
+
+    /// Remember the link of `response` if it points into the `master` branch
+    /// of a GitHub repository (if `--suggest-default-branch` is enabled)
+    fn add_master_branch_link(&mut self, response: &Response) {
+        if self.default_branch.is_none() {
+            return;
+        }
+        let Ok(url) = Url::parse(response.body().uri.as_str()) else {
+            return;
+        };
+        if master_branch_repo(&url).is_some() {
+            self.master_branch_links
+                .entry(response.source().clone())
+                .or_default()
+                .insert(url);
+        }
+    }
+
+    /// Check if `status` counts as an error
+    fn is_failure(&self, status: &Status) -> bool {
+        status.is_error() || self.is_failed_redirect(status)
+    }
+
+    /// Check if `status` is a redirect which counts as an error
+    fn is_failed_redirect(&self, status: &Status) -> bool {
+        let permanent = match status {
+            Status::Redirected(_, redirects) => redirects.permanent_target().is_some(),
+            Status::Cached(CacheStatus::Redirected { permanent, .. }, _) => *permanent,
+            _ => return false,
+        };
+        let fail_on_permanent =
+            self.permanent_redirects == Some(PermanentRedirects::Fail) && permanent;
+        status.is_success() && (self.fail_on_redirects || fail_on_permanent)
+    }
+
     #[inline]
     /// Check if the entire run was successful
-    pub(crate) const fn is_success(&self) -> bool {
-        self.total == self.successful + self.excludes + self.unsupported
+    pub(crate) fn is_success(&self) -> bool {
+        self.total
+            == self.successful
+                + self.excludes
+                + self.unsupported
+                + self.skipped
+                + self.generated_errors
+                + self.allowed_tag_errors()
     }
 
     #[inline]
@@ -119,16 +606,58 @@ impl ResponseStats {
     }
 }
 
+/// The directory of a file or remote page, e.g. `docs/guide` for
+/// `docs/guide/intro.md`, or `None` for other inputs like stdin
+fn source_directory(source: &InputSource) -> Option<String> {
+    match source {
+        InputSource::FsPath(path) => Some(
+            path.parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .map_or_else(|| ".".to_string(), |parent| parent.display().to_string()),
+        ),
+        InputSource::RemoteUrl(url) => url.join(".").ok().map(String::from),
+        _ => None,
+    }
+}
+
+/// The `file://` URL of a local file, which may be relative to the current
+/// directory
+fn file_url(path: &Path) -> Option<Url> {
+    let path = std::path::absolute(path).ok()?;
+    Url::from_file_path(path).ok()
+}
+
+/// The type of an input, e.g. `file` for `docs/intro.md`
+fn source_type(source: &InputSource) -> &'static str {
+    match source {
+        InputSource::RemoteUrl(_) | InputSource::Sitemap(_) => "remote",
+        InputSource::FsGlob { .. } | InputSource::FsPath(_) => "file",
+        InputSource::Archive(_) | InputSource::ArchiveEntry { .. } => "archive",
+        InputSource::ContainerImage { .. } => "container image",
+        InputSource::Bucket(_) => "bucket",
+        InputSource::Stdin => "stdin",
+        _ => "string",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
     use std::collections::{HashMap, HashSet};
+    use std::num::NonZeroUsize;
+    use std::path::PathBuf;
+    use std::time::Duration;
 
     use http::StatusCode;
-    use lychee_lib::{ErrorKind, InputSource, Response, ResponseBody, Status, Uri};
+    use lychee_lib::{
+        ratelimit::HostActivity, CacheStatus, ErrorKind, InputSource, MailLintKind, RawUriSpan,
+        Redirect, Redirects, Response, ResponseBody, SkipReason, Status, Uri,
+    };
     use reqwest::Url;
 
-    use super::ResponseStats;
+    use super::{HostTimings, LinkCounts, ResponseStats, Warning};
+    use crate::archive::Suggestion;
+    use crate::options::{DefaultBranchSuggestion, PermanentRedirects, ReportSection};
 
     fn website(url: &str) -> Uri {
         Uri::from(Url::parse(url).expect("Expected valid Website URI"))
@@ -175,7 +704,10 @@ mod tests {
 
         let response = dummy_error();
         let expected_error_map: HashMap<InputSource, HashSet<ResponseBody>> =
-            HashMap::from_iter([(response.source().clone(), HashSet::from_iter([response.1]))]);
+            HashMap::from_iter([(
+                response.source().clone(),
+                HashSet::from_iter([response.body]),
+            )]);
         assert_eq!(stats.error_map, expected_error_map);
 
         assert!(stats.success_map.is_empty());
@@ -197,7 +729,7 @@ mod tests {
         let entry = expected_error_map
             .entry(response.source().clone())
             .or_default();
-        entry.insert(response.1);
+        entry.insert(response.body);
         assert_eq!(stats.error_map, expected_error_map);
 
         let mut expected_success_map: HashMap<InputSource, HashSet<ResponseBody>> = HashMap::new();
@@ -205,7 +737,7 @@ mod tests {
         let entry = expected_success_map
             .entry(response.source().clone())
             .or_default();
-        entry.insert(response.1);
+        entry.insert(response.body);
         assert_eq!(stats.success_map, expected_success_map);
 
         let mut expected_excluded_map: HashMap<InputSource, HashSet<ResponseBody>> = HashMap::new();
@@ -213,7 +745,395 @@ mod tests {
         let entry = expected_excluded_map
             .entry(response.source().clone())
             .or_default();
-        entry.insert(response.1);
+        entry.insert(response.body);
         assert_eq!(stats.excluded_map, expected_excluded_map);
     }
+
+    #[test]
+    fn test_generated_errors() {
+        let mut stats = ResponseStats::default();
+        stats.add(dummy_error().with_generated(true));
+        stats.add(dummy_ok().with_generated(true));
+
+        assert_eq!(stats.errors, 0);
+        assert_eq!(stats.generated_errors, 1);
+        assert!(stats.error_map.is_empty());
+        assert_eq!(stats.generated_error_map[&InputSource::Stdin].len(), 1);
+        assert!(stats.is_success());
+
+        stats.add(dummy_error());
+        assert!(!stats.is_success());
+    }
+
+    #[test]
+    fn test_skipped() {
+        let mut stats = ResponseStats::default();
+        stats.add(dummy_ok());
+        stats.add(mock_response(Status::Skipped(SkipReason::Deadline)));
+
+        assert_eq!(stats.skipped, 1);
+        assert_eq!(stats.deadline_skipped, 1);
+        assert_eq!(stats.errors, 0);
+        assert!(stats.error_map.is_empty());
+        assert!(stats.is_success());
+
+        stats.add(mock_response(Status::Skipped(SkipReason::Sampled)));
+        assert_eq!(stats.skipped, 2);
+        assert_eq!(stats.deadline_skipped, 1);
+    }
+
+    #[test]
+    fn test_record_outcomes() {
+        let mut stats = ResponseStats {
+            record_outcomes: true,
+            ..ResponseStats::default()
+        };
+        stats.add(dummy_ok());
+        stats.add(dummy_error());
+        stats.add(dummy_excluded());
+
+        assert_eq!(
+            stats
+                .outcomes
+                .iter()
+                .map(|outcome| (outcome.status.as_str(), outcome.success))
+                .collect::<Vec<_>>(),
+            [("200", true), ("ERROR", false)]
+        );
+    }
+
+    #[test]
+    fn test_redirects() {
+        let redirected = || {
+            mock_response(Status::Redirected(
+                StatusCode::OK,
+                Redirects::new(vec![
+                    Redirect {
+                        code: StatusCode::MOVED_PERMANENTLY,
+                        url: Url::parse("https://some-url.com/ok").unwrap(),
+                    },
+                    Redirect {
+                        code: StatusCode::OK,
+                        url: Url::parse("https://some-url.com/new").unwrap(),
+                    },
+                ]),
+            ))
+        };
+
+        let mut stats = ResponseStats::default();
+        stats.add(redirected());
+        assert_eq!(stats.redirects, 1);
+        assert!(stats.error_map.is_empty());
+        assert!(stats.is_success());
+
+        let mut stats = ResponseStats {
+            fail_on_redirects: true,
+            ..Default::default()
+        };
+        stats.add(redirected());
+        assert_eq!(stats.errors, 1);
+        assert_eq!(stats.error_map[&InputSource::Stdin].len(), 1);
+        assert!(!stats.is_success());
+
+        // Redirects from the cache fail as well
+        let mut stats = ResponseStats {
+            fail_on_redirects: true,
+            ..Default::default()
+        };
+        stats.add(mock_response(Status::Cached(
+            CacheStatus::Redirected {
+                code: 200,
+                permanent: true,
+            },
+            None,
+        )));
+        assert_eq!(stats.redirects, 1);
+        assert_eq!(stats.errors, 1);
+        assert!(!stats.is_success());
+    }
+
+    #[test]
+    fn test_permanent_redirects() {
+        let redirected = |code| {
+            mock_response(Status::Redirected(
+                StatusCode::OK,
+                Redirects::new(vec![
+                    Redirect {
+                        code,
+                        url: Url::parse("https://some-url.com/ok").unwrap(),
+                    },
+                    Redirect {
+                        code: StatusCode::OK,
+                        url: Url::parse("https://some-url.com/new").unwrap(),
+                    },
+                ]),
+            ))
+        };
+
+        let mut stats = ResponseStats {
+            permanent_redirects: Some(PermanentRedirects::Suggest),
+            ..Default::default()
+        };
+        stats.add(redirected(StatusCode::MOVED_PERMANENTLY));
+        stats.add(redirected(StatusCode::FOUND));
+        let suggestions = &stats.suggestion_map[&InputSource::Stdin];
+        assert_eq!(suggestions.len(), 1);
+        assert!(suggestions.contains(&Suggestion {
+            original: Url::parse("https://some-url.com/ok").unwrap(),
+            suggestion: Url::parse("https://some-url.com/new").unwrap(),
+        }));
+        assert!(stats.is_success());
+
+        let mut stats = ResponseStats {
+            permanent_redirects: Some(PermanentRedirects::Fail),
+            ..Default::default()
+        };
+        stats.add(redirected(StatusCode::PERMANENT_REDIRECT));
+        stats.add(redirected(StatusCode::TEMPORARY_REDIRECT));
+        assert_eq!(stats.errors, 1);
+        assert_eq!(stats.successful, 1);
+        assert!(!stats.is_success());
+    }
+
+    #[test]
+    fn test_retain_sections() {
+        let mut stats = ResponseStats {
+            host_stats: true,
+            ..ResponseStats::extended()
+        };
+        stats.add(dummy_error());
+        stats.add(dummy_excluded());
+        stats.add(dummy_ok());
+        assert_eq!(
+            stats.host_map["some-url.com"],
+            LinkCounts {
+                total: 3,
+                errors: 1
+            }
+        );
+
+        stats.retain_sections(&[ReportSection::Errors, ReportSection::Hosts]);
+        assert_eq!(stats.error_map.len(), 1);
+        assert_eq!(stats.host_map.len(), 1);
+        assert!(stats.excluded_map.is_empty());
+        let json = serde_json::to_value(&stats).unwrap();
+        assert!(json.get("host_map").is_some());
+
+        stats.retain_sections(&ReportSection::DEFAULT);
+        assert!(stats.host_map.is_empty());
+        let json = serde_json::to_value(&stats).unwrap();
+        assert!(json.get("host_map").is_none());
+    }
+
+    #[test]
+    fn test_host_timings() {
+        let mut stats = ResponseStats {
+            host_stats: true,
+            ..ResponseStats::default()
+        };
+        stats.add(dummy_ok());
+        let activity = |host: &str| HostActivity {
+            host: host.to_string(),
+            queued: 0,
+            active: 0,
+            completed: 2,
+            warm_up: Some(Duration::from_millis(120)),
+            mean_request_time: Some(Duration::from_micros(45_500)),
+        };
+        stats.add_host_timings(&[activity("some-url.com"), activity("other.com")]);
+        assert_eq!(
+            stats.host_timings,
+            HashMap::from([(
+                "some-url.com".to_string(),
+                HostTimings {
+                    warm_up_ms: Some(120),
+                    mean_request_ms: Some(45),
+                }
+            )])
+        );
+    }
+
+    #[test]
+    fn test_worst_directories() {
+        let response = |file: &str, status| {
+            Response::new(
+                website("https://some-url.com/ok"),
+                status,
+                InputSource::FsPath(PathBuf::from(file)),
+            )
+        };
+        let error = || Status::Error(ErrorKind::InvalidStatusCode(1000));
+        let ok = || Status::Ok(StatusCode::OK);
+
+        let mut stats = ResponseStats {
+            directory_stats: true,
+            ..ResponseStats::default()
+        };
+        stats.add(response("docs/guide/intro.md", error()));
+        stats.add(response("docs/guide/setup.md", ok()));
+        stats.add(response("docs/api/index.md", error()));
+        stats.add(response("docs/api/client.md", error()));
+        stats.add(response("docs/api/server.md", ok()));
+        stats.add(response("docs/blog/post.md", ok()));
+        stats.add(response("README.md", error()));
+
+        assert_eq!(
+            stats.worst_directories(),
+            [
+                (
+                    ".",
+                    &LinkCounts {
+                        total: 1,
+                        errors: 1
+                    }
+                ),
+                (
+                    "docs/api",
+                    &LinkCounts {
+                        total: 3,
+                        errors: 2
+                    }
+                ),
+                (
+                    "docs/guide",
+                    &LinkCounts {
+                        total: 2,
+                        errors: 1
+                    }
+                ),
+            ]
+        );
+        assert_eq!(stats.directory_map["docs/blog"].error_percentage(), 0);
+        assert_eq!(stats.directory_map["docs/api"].error_percentage(), 66);
+    }
+
+    #[test]
+    fn test_mixed_source_types() {
+        let response =
+            |source, status| Response::new(website("https://some-url.com/ok"), status, source);
+        let error = || Status::Error(ErrorKind::InvalidStatusCode(1000));
+        let ok = || Status::Ok(StatusCode::OK);
+
+        let mut stats = ResponseStats::default();
+        stats.add(response(
+            InputSource::FsPath(PathBuf::from("README.md")),
+            ok(),
+        ));
+        // Links of a single type of input are not broken down
+        assert_eq!(stats.mixed_source_types(), []);
+
+        stats.add(response(InputSource::Stdin, error()));
+        stats.add(response(InputSource::Stdin, ok()));
+        assert_eq!(
+            stats.mixed_source_types(),
+            [
+                (
+                    "file",
+                    &LinkCounts {
+                        total: 1,
+                        errors: 0
+                    }
+                ),
+                (
+                    "stdin",
+                    &LinkCounts {
+                        total: 2,
+                        errors: 1
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tag_max_errors() {
+        let tagged = |tag: &str, response: Response| response.with_tag(Some(tag.to_string()));
+
+        let mut stats = ResponseStats {
+            tag_max_errors: HashMap::from([("blog".to_string(), 1)]),
+            ..Default::default()
+        };
+        stats.add(tagged("docs", dummy_ok()));
+        stats.add(tagged("blog", dummy_error()));
+        stats.add(tagged("blog", dummy_ok()));
+        assert_eq!(
+            stats.tags(),
+            [
+                (
+                    "blog",
+                    &LinkCounts {
+                        total: 2,
+                        errors: 1
+                    },
+                    Some(1)
+                ),
+                (
+                    "docs",
+                    &LinkCounts {
+                        total: 1,
+                        errors: 0
+                    },
+                    None
+                ),
+            ]
+        );
+        // The error is within the limit of its tag
+        assert_eq!(stats.errors, 1);
+        assert!(stats.is_success());
+
+        stats.add(tagged("blog", dummy_error()));
+        assert!(!stats.is_success());
+
+        // Tags without a limit fail on any error
+        let mut stats = ResponseStats::default();
+        stats.add(tagged("docs", dummy_error()));
+        assert!(!stats.is_success());
+    }
+
+    #[test]
+    fn test_warnings_do_not_affect_success() {
+        let mut stats = ResponseStats::default();
+        stats.add(dummy_ok());
+
+        let warning = || Warning {
+            uri: Uri::try_from("mailto:noreply@lychee.cli.rs").unwrap(),
+            reason: MailLintKind::RoleAccount("noreply".to_string()).into(),
+        };
+        stats.add_warning(InputSource::Stdin, warning());
+        // Duplicate warnings for the same input are only counted once
+        stats.add_warning(InputSource::Stdin, warning());
+
+        assert_eq!(stats.warnings, 1);
+        assert_eq!(stats.warning_map[&InputSource::Stdin].len(), 1);
+        assert!(stats.is_success());
+    }
+
+    #[test]
+    fn test_duplicate_fragments() {
+        let mut stats = ResponseStats::default();
+        let path = std::env::current_dir().unwrap().join("guide.md");
+        let span = RawUriSpan {
+            line: NonZeroUsize::new(4).unwrap(),
+            column: NonZeroUsize::new(1),
+        };
+        stats.add_duplicate_fragments(HashMap::from([(
+            path.clone(),
+            vec![("install".to_string(), span)],
+        )]));
+
+        assert_eq!(stats.warnings, 1);
+        let warning = stats.warning_map[&InputSource::FsPath(path)]
+            .iter()
+            .next()
+            .unwrap();
+        assert!(warning.uri.as_str().ends_with("/guide.md#install"));
+        assert_eq!(
+            warning.to_string(),
+            format!(
+                "{} | Duplicate fragment at 4:1, links to it are ambiguous",
+                warning.uri
+            )
+        );
+        assert!(stats.is_success());
+    }
 }
@@ -0,0 +1,166 @@
+//! Validation of the regexes in the config, e.g. `--exclude` patterns.
+//!
+//! The regexes are compiled while loading the config, so that all invalid
+//! ones are reported at once, together with where they were set, instead of
+//! failing on the first one while creating the client.
+
+use std::fmt;
+
+use anyhow::{bail, Result};
+use regex::Regex;
+
+use crate::options::{Config, OptionSource};
+
+/// A regex which doesn't compile
+#[derive(Debug)]
+pub(crate) struct InvalidRegex {
+    /// The option (or file) which contains the regex
+    pub(crate) origin: String,
+    /// Where the option was set
+    pub(crate) source: Option<OptionSource>,
+    /// The regex itself
+    pub(crate) pattern: String,
+    /// Why the regex doesn't compile
+    pub(crate) error: regex::Error,
+}
+
+impl fmt::Display for InvalidRegex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.origin)?;
+        if let Some(source) = &self.source {
+            write!(f, " ({source})")?;
+        }
+        write!(f, ": `{}`", self.pattern)?;
+        for line in self.error.to_string().lines() {
+            write!(f, "\n    {line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Find all invalid regexes in the options of `cfg`
+pub(crate) fn regexes(cfg: &Config) -> Vec<InvalidRegex> {
+    let mut patterns: Vec<(&str, &str)> = Vec::new();
+    patterns.extend(cfg.include.iter().map(|p| ("include", p.as_str())));
+    patterns.extend(cfg.exclude.iter().map(|p| ("exclude", p.as_str())));
+    // Malformed remaps are reported by `parse::parse_remaps`
+    patterns.extend(cfg.remap.iter().filter_map(|remap| {
+        match remap.split_whitespace().collect::<Vec<_>>()[..] {
+            [pattern, _] => Some(("remap", pattern)),
+            _ => None,
+        }
+    }));
+    patterns.extend(
+        cfg.basic_auth
+            .iter()
+            .flatten()
+            .map(|selector| ("basic_auth", selector.raw_uri_regex.as_str())),
+    );
+    patterns.extend(
+        cfg.fragment_regex
+            .iter()
+            .map(|p| ("fragment_regex", p.as_str())),
+    );
+
+    patterns
+        .into_iter()
+        .filter_map(|(name, pattern)| {
+            let error = Regex::new(pattern).err()?;
+            Some(InvalidRegex {
+                origin: format!("`--{}`", name.replace('_', "-")),
+                source: cfg.sources.get(name).cloned(),
+                pattern: pattern.to_string(),
+                error,
+            })
+        })
+        .collect()
+}
+
+/// Find all invalid regexes in `patterns`, which were read from `file`
+pub(crate) fn patterns(file: &str, patterns: &[String]) -> Vec<InvalidRegex> {
+    patterns
+        .iter()
+        .filter_map(|pattern| {
+            let error = Regex::new(pattern).err()?;
+            Some(InvalidRegex {
+                origin: format!("`{file}`"),
+                source: None,
+                pattern: pattern.clone(),
+                error,
+            })
+        })
+        .collect()
+}
+
+/// Fail with a list of all `invalid` regexes, if there are any
+pub(crate) fn check(invalid: &[InvalidRegex]) -> Result<()> {
+    match invalid {
+        [] => Ok(()),
+        [invalid] => bail!("Invalid regex in {invalid}"),
+        _ => {
+            let list = invalid
+                .iter()
+                .map(|invalid| format!("\n  {invalid}"))
+                .collect::<String>();
+            bail!("{} invalid regexes:{list}", invalid.len())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_regexes() {
+        let config_file = OptionSource::ConfigFile(PathBuf::from("lychee.toml"));
+        let cfg = Config {
+            include: vec!["^https://example\\.com".to_string()],
+            exclude: vec!["(unclosed".to_string(), "fine".to_string()],
+            remap: vec![
+                "[a-z https://example.com".to_string(),
+                "not-a-remap".to_string(),
+            ],
+            basic_auth: Some(vec!["*.example.com user:pwd".parse().unwrap()]),
+            fragment_regex: Some("[^a-z]".to_string()),
+            sources: HashMap::from([
+                ("exclude".to_string(), config_file.clone()),
+                ("remap".to_string(), OptionSource::CommandLine),
+            ]),
+            ..Default::default()
+        };
+
+        let invalid = regexes(&cfg);
+        let origins: Vec<_> = invalid
+            .iter()
+            .map(|i| (i.origin.as_str(), i.source.clone(), i.pattern.as_str()))
+            .collect();
+        assert_eq!(
+            origins,
+            [
+                ("`--exclude`", Some(config_file), "(unclosed"),
+                ("`--remap`", Some(OptionSource::CommandLine), "[a-z"),
+                ("`--basic-auth`", None, "*.example.com"),
+            ]
+        );
+
+        let message = check(&invalid).unwrap_err().to_string();
+        assert!(message.starts_with(
+            "3 invalid regexes:\n  `--exclude` (config file `lychee.toml`): `(unclosed`\n    regex parse error:"
+        ));
+    }
+
+    #[test]
+    fn test_patterns() {
+        let invalid = patterns(".lycheeignore", &["ok".to_string(), "a{2".to_string()]);
+        assert_eq!(invalid.len(), 1);
+        assert!(check(&invalid)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Invalid regex in `.lycheeignore`: `a{2`"));
+        assert!(check(&[]).is_ok());
+    }
+}
@@ -0,0 +1,438 @@
+//! Check links again while documentation is edited (`--watch`).
+//!
+//! After all inputs were checked once, lychee watches the local files,
+//! directories and globs among the inputs. When files change, only the links
+//! in the changed files and in the files which link to them are checked
+//! again, followed by a summary of the errors in all files. Remote inputs are
+//! only checked once.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use log::warn;
+use lychee_lib::{
+    Client, Collector, FileExtensions, FileType, Input, InputSource, Request, ResponseBody, Uri,
+};
+use notify::{EventKind, RecursiveMode, Watcher};
+use reqwest::Url;
+use tokio::sync::mpsc;
+
+use crate::cache::Cache;
+use crate::commands::check::handle;
+use crate::formatters::get_response_formatter;
+use crate::options::Config;
+use crate::ExitCode;
+
+/// How long to wait for more changes after a change, as editors and build
+/// tools often write several files (or a file in several steps) at once
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Links and failed links of an input
+#[derive(Debug, Default)]
+struct InputResult {
+    links: usize,
+    errors: Vec<ResponseBody>,
+    /// Local files and directories the input links to
+    targets: BTreeSet<PathBuf>,
+}
+
+/// The local inputs, which decide which changed files are checked again
+#[derive(Debug)]
+struct Watched {
+    cwd: PathBuf,
+    files: BTreeSet<PathBuf>,
+    directories: Vec<PathBuf>,
+    globs: Vec<glob::Pattern>,
+    excluded: Vec<PathBuf>,
+    file_extensions: FileExtensions,
+    skip_hidden: bool,
+}
+
+impl Watched {
+    fn new(inputs: &[Input], cfg: &Config) -> Result<Self> {
+        let cwd = std::env::current_dir()?.canonicalize()?;
+        let mut watched = Watched {
+            files: BTreeSet::new(),
+            directories: Vec::new(),
+            globs: Vec::new(),
+            excluded: cfg
+                .exclude_path
+                .iter()
+                .filter_map(|path| path.canonicalize().ok())
+                .collect(),
+            file_extensions: FileExtensions::new(cfg.filetypes.clone()),
+            skip_hidden: !cfg.hidden,
+            cwd,
+        };
+        for input in inputs {
+            match &input.source {
+                InputSource::FsPath(path) if path.is_dir() => {
+                    watched.directories.push(path.canonicalize()?);
+                }
+                InputSource::FsPath(path) => {
+                    watched.files.insert(path.canonicalize()?);
+                }
+                InputSource::FsGlob { pattern, .. } => {
+                    let absolute = watched.cwd.join(pattern);
+                    let pattern = glob::Pattern::new(&absolute.to_string_lossy())
+                        .with_context(|| format!("Cannot watch glob `{pattern}`"))?;
+                    watched.globs.push(pattern);
+                }
+                _ => {}
+            }
+        }
+        Ok(watched)
+    }
+
+    /// Directories to watch, which contain all watched files
+    fn roots(&self) -> BTreeMap<PathBuf, RecursiveMode> {
+        // Editors often replace files instead of writing them, so the
+        // directory of a file is watched rather than the file
+        let mut roots: BTreeMap<_, _> = self
+            .files
+            .iter()
+            .filter_map(|file| file.parent())
+            .map(|parent| (parent.to_path_buf(), RecursiveMode::NonRecursive))
+            .collect();
+        let globs = self.globs.iter().map(|glob| glob_root(glob.as_str()));
+        for directory in self.directories.iter().cloned().chain(globs) {
+            roots.insert(directory, RecursiveMode::Recursive);
+        }
+        roots
+    }
+
+    /// Whether the file at the canonical `path` is one of the inputs
+    fn contains(&self, path: &Path) -> bool {
+        if self
+            .excluded
+            .iter()
+            .any(|excluded| path.starts_with(excluded))
+        {
+            return false;
+        }
+        if self.files.contains(path) {
+            return true;
+        }
+        let in_directory = self.directories.iter().any(|directory| {
+            path.strip_prefix(directory).is_ok_and(|relative| {
+                !(self.skip_hidden && relative.iter().any(|part| is_hidden(part)))
+            }) && self.has_valid_extension(path)
+        });
+        in_directory || self.globs.iter().any(|glob| glob.matches_path(path))
+    }
+
+    /// Files in directories are only checked if lychee supports their type.
+    /// Like in [`lychee_lib::Input`], SVG images need a custom extension.
+    fn has_valid_extension(&self, path: &Path) -> bool {
+        self.file_extensions.get(path).is_some()
+            || matches!(
+                FileType::from(path),
+                FileType::Markdown | FileType::Html | FileType::Ipynb
+            )
+    }
+
+    /// `path` relative to the working directory, as inputs are usually given
+    fn relative(&self, path: &Path) -> PathBuf {
+        path.strip_prefix(&self.cwd)
+            .map_or_else(|_| path.to_path_buf(), Path::to_path_buf)
+    }
+
+    /// The key of `source` in the results, which is the same for a file
+    /// found in a directory and the same file after a change
+    fn key(&self, source: &InputSource) -> String {
+        match source {
+            InputSource::FsPath(path) => path.canonicalize().map_or_else(
+                |_| path.display().to_string(),
+                |path| self.relative(&path).display().to_string(),
+            ),
+            source => source.to_string(),
+        }
+    }
+}
+
+/// Check the links of `inputs`, then check the links of changed files again
+/// until Ctrl+C is pressed.
+///
+/// Returns the exit code for the errors of the last checks.
+pub(crate) async fn watch(
+    collector: Collector,
+    inputs: Vec<Input>,
+    client: Client,
+    cache: Arc<Cache>,
+    cfg: &Config,
+) -> Result<ExitCode> {
+    let watched = Watched::new(&inputs, cfg)?;
+    let roots = watched.roots();
+    if roots.is_empty() {
+        warn!("None of the inputs are local files, so there is nothing to watch");
+    }
+
+    let (sender, mut events) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        // Nobody is listening anymore once lychee exits
+        let _ = sender.send(event);
+    })
+    .context("Cannot watch inputs")?;
+    for (root, mode) in &roots {
+        watcher
+            .watch(root, *mode)
+            .with_context(|| format!("Cannot watch `{}`", root.display()))?;
+    }
+
+    let checker = Checker {
+        client,
+        cache,
+        cfg,
+        watched: &watched,
+    };
+    let mut results = checker.check(collector.clone().collect_links(inputs)).await;
+    print_errors(&results, cfg)?;
+    print_totals(&results)?;
+
+    loop {
+        let event = tokio::select! {
+            event = events.recv() => event,
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        let Some(event) = event else {
+            break;
+        };
+        let mut changed = BTreeSet::new();
+        add_changed(&mut changed, event);
+        tokio::time::sleep(DEBOUNCE).await;
+        while let Ok(event) = events.try_recv() {
+            add_changed(&mut changed, event);
+        }
+
+        let mut rechecked = BTreeSet::new();
+        let mut removed = false;
+        for path in &changed {
+            // Anchors may have been added, renamed or removed
+            checker.client.forget_fragments(path).await;
+            if path.is_file() {
+                if watched.contains(path) {
+                    rechecked.insert(watched.relative(path));
+                }
+            } else if results
+                .remove(&watched.relative(path).display().to_string())
+                .is_some()
+            {
+                removed = true;
+            }
+        }
+        // Links to the changed files may be broken or fixed now
+        for (key, result) in &results {
+            let links_to_changed = result
+                .targets
+                .iter()
+                .any(|target| changed.iter().any(|path| links_to(target, path)));
+            if links_to_changed {
+                rechecked.insert(PathBuf::from(key));
+            }
+        }
+        if rechecked.is_empty() && !removed {
+            continue;
+        }
+
+        let inputs: Vec<_> = rechecked
+            .into_iter()
+            .map(|path| Input::from_source(InputSource::FsPath(path)))
+            .collect();
+        let mut checked: BTreeMap<String, InputResult> = inputs
+            .iter()
+            .map(|input| (watched.key(&input.source), InputResult::default()))
+            .collect();
+        checked.extend(checker.check(collector.clone().collect_links(inputs)).await);
+        print_errors(&checked, cfg)?;
+        results.extend(checked);
+        print_totals(&results)?;
+    }
+
+    let failed = results.values().any(|result| !result.errors.is_empty());
+    Ok(if failed {
+        ExitCode::LinkCheckFailure
+    } else {
+        ExitCode::Success
+    })
+}
+
+/// Checks the links of inputs with the shared client and cache
+struct Checker<'a> {
+    client: Client,
+    cache: Arc<Cache>,
+    cfg: &'a Config,
+    watched: &'a Watched,
+}
+
+impl Checker<'_> {
+    /// The links and errors of each input in `requests`
+    async fn check(
+        &self,
+        requests: impl futures::Stream<Item = lychee_lib::Result<Request>>,
+    ) -> BTreeMap<String, InputResult> {
+        let cache_exclude_status = self.cfg.cache_exclude_status.clone().into_set();
+        let accept = self.cfg.accept.clone().into_set();
+        let responses: Vec<_> = requests
+            .filter_map(|request| async move {
+                // Files may be removed while they are read
+                request
+                    .inspect_err(|e| warn!("Cannot collect links: {e}"))
+                    .ok()
+            })
+            .map(|request| {
+                handle(
+                    &self.client,
+                    Arc::clone(&self.cache),
+                    cache_exclude_status.clone(),
+                    request,
+                    accept.clone(),
+                )
+            })
+            .buffer_unordered(self.cfg.max_concurrency)
+            .collect()
+            .await;
+
+        let mut results: BTreeMap<String, InputResult> = BTreeMap::new();
+        for response in responses {
+            let result = results
+                .entry(self.watched.key(response.source()))
+                .or_default();
+            result.links += 1;
+            if let Some(target) = local_target(&response.body.uri) {
+                result.targets.insert(target);
+            }
+            if response.status().is_error() {
+                result.errors.push(response.body);
+            }
+        }
+        results
+    }
+}
+
+/// The local file or directory `uri` points to, if any
+fn local_target(uri: &Uri) -> Option<PathBuf> {
+    if !uri.is_file() {
+        return None;
+    }
+    let path = Url::parse(uri.as_str()).ok()?.to_file_path().ok()?;
+    Some(path.canonicalize().unwrap_or(path))
+}
+
+/// Whether a change of the file at `path` can change the result of a link to
+/// `target`, which is the file itself or the directory of an index file
+fn links_to(target: &Path, path: &Path) -> bool {
+    path == target || (path.parent() == Some(target) && target.is_dir())
+}
+
+/// Remember the files of a file system event, except for reads
+fn add_changed(changed: &mut BTreeSet<PathBuf>, event: notify::Result<notify::Event>) {
+    match event {
+        Ok(event) if matches!(event.kind, EventKind::Access(_)) => {}
+        Ok(event) => changed.extend(event.paths),
+        Err(e) => warn!("Cannot watch inputs: {e}"),
+    }
+}
+
+/// Print the failed links of the checked inputs
+fn print_errors(checked: &BTreeMap<String, InputResult>, cfg: &Config) -> Result<()> {
+    let formatter = get_response_formatter(&cfg.mode);
+    let mut stdout = io::stdout().lock();
+    for (input, result) in checked {
+        for error in &result.errors {
+            write!(stdout, "{}", formatter.format_response(error))?;
+            match error.span {
+                Some(span) => writeln!(stdout, " ({input}:{span})")?,
+                None => writeln!(stdout, " ({input})")?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Print the number of links and errors of all inputs
+fn print_totals(results: &BTreeMap<String, InputResult>) -> Result<()> {
+    let links: usize = results.values().map(|result| result.links).sum();
+    let errors: usize = results.values().map(|result| result.errors.len()).sum();
+    let failed_inputs = results
+        .values()
+        .filter(|result| !result.errors.is_empty())
+        .count();
+    writeln!(
+        io::stdout(),
+        "\u{1f50d} {links} links in {} inputs, \u{1f6ab} {errors} errors in {failed_inputs} inputs. Watching for changes, press Ctrl+C to exit.", // 🔍 🚫
+        results.len()
+    )?;
+    Ok(())
+}
+
+/// Whether a file or directory is hidden, e.g. `.git`
+fn is_hidden(name: &std::ffi::OsStr) -> bool {
+    name.to_str()
+        .is_some_and(|name| name.starts_with('.') && name != "." && name != "..")
+}
+
+/// The directory which contains all matches of the absolute glob `pattern`
+fn glob_root(pattern: &str) -> PathBuf {
+    Path::new(pattern)
+        .ancestors()
+        .find(|path| !path.to_string_lossy().contains(['*', '?', '[']))
+        .map_or_else(|| PathBuf::from("/"), Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_glob_root() {
+        assert_eq!(glob_root("/docs/**/*.md"), PathBuf::from("/docs"));
+        assert_eq!(glob_root("/docs/guide.md"), PathBuf::from("/docs/guide.md"));
+    }
+
+    #[test]
+    fn test_links_to() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(root.join("docs")).unwrap();
+
+        assert!(links_to(&root.join("guide.md"), &root.join("guide.md")));
+        assert!(links_to(&root.join("docs"), &root.join("docs/index.md")));
+        assert!(!links_to(
+            &root.join("guide.md"),
+            &root.join("docs/index.md")
+        ));
+        assert!(!links_to(&root, &root.join("docs/index.md")));
+    }
+
+    #[test]
+    fn test_watched_contains() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("docs/.drafts")).unwrap();
+        std::fs::write(root.join("README.md"), "").unwrap();
+
+        let inputs = [root.join("docs"), root.join("README.md")]
+            .map(|path| Input::from_source(InputSource::FsPath(path)));
+        let watched = Watched::new(&inputs, &Config::default()).unwrap();
+
+        assert!(watched.contains(&root.join("README.md")));
+        assert!(watched.contains(&root.join("docs/guide.md")));
+        assert!(!watched.contains(&root.join("docs/image.png")));
+        assert!(!watched.contains(&root.join("docs/.drafts/guide.md")));
+        assert!(!watched.contains(&root.join("CHANGELOG.md")));
+        assert_eq!(
+            watched.roots(),
+            BTreeMap::from([
+                (root.clone(), RecursiveMode::NonRecursive),
+                (root.join("docs"), RecursiveMode::Recursive),
+            ])
+        );
+    }
+}
@@ -0,0 +1,9 @@
+//! Authentication of requests to protected endpoints
+//!
+//! Unlike basic auth credentials, which are static, the access tokens of
+//! these schemes are fetched (and refreshed) while links are checked.
+
+mod oauth2;
+
+pub use oauth2::OAuth2Config;
+pub(crate) use oauth2::OAuth2Handler;
@@ -0,0 +1,270 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use http::header::{HeaderValue, AUTHORIZATION};
+use reqwest::{Request, Response, Url};
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+use tokio::time::Instant;
+
+use crate::{
+    chain::{ChainResult, Handler},
+    ErrorKind, Status,
+};
+
+/// Tokens are refreshed this long before they expire, so that they don't
+/// expire while a request is in flight
+const EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
+/// Configuration of the OAuth2 client credentials flow
+/// ([RFC 6749, section 4.4](https://datatracker.ietf.org/doc/html/rfc6749#section-4.4))
+///
+/// The access token is fetched from the token endpoint before the first
+/// request to one of the `hosts` and refreshed once it expires. Both the
+/// token endpoint and the `hosts` are only requested with credentials over
+/// https.
+#[derive(Debug, Clone)]
+pub struct OAuth2Config {
+    /// URL of the token endpoint of the authorization server
+    pub token_url: Url,
+    /// Client identifier
+    pub client_id: String,
+    /// Client secret
+    pub client_secret: SecretString,
+    /// Hosts whose requests get the access token.
+    /// If this is empty, the host of the `token_url` is used.
+    pub hosts: Vec<String>,
+}
+
+impl OAuth2Config {
+    /// Create a new configuration, which sends the access token to the host
+    /// of the `token_url`
+    #[must_use]
+    pub fn new(token_url: Url, client_id: impl Into<String>, client_secret: SecretString) -> Self {
+        Self {
+            token_url,
+            client_id: client_id.into(),
+            client_secret,
+            hosts: Vec::new(),
+        }
+    }
+
+    /// Send the access token to `hosts` instead of the host of the token URL
+    #[must_use]
+    pub fn with_hosts(mut self, hosts: Vec<String>) -> Self {
+        self.hosts = hosts;
+        self
+    }
+
+    /// The (lowercase) hosts whose requests get the access token
+    pub(crate) fn hosts(&self) -> Vec<String> {
+        if self.hosts.is_empty() {
+            self.token_url
+                .host_str()
+                .map(str::to_string)
+                .into_iter()
+                .collect()
+        } else {
+            self.hosts.iter().map(|host| host.to_lowercase()).collect()
+        }
+    }
+}
+
+/// Successful response of the token endpoint
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    token_type: String,
+    /// Lifetime of the token in seconds
+    expires_in: Option<u64>,
+}
+
+/// Access token which was fetched from the token endpoint
+#[derive(Debug)]
+struct CachedToken {
+    authorization: HeaderValue,
+    /// `None` if the token doesn't expire
+    refresh_at: Option<Instant>,
+}
+
+impl CachedToken {
+    fn is_fresh(&self) -> bool {
+        self.refresh_at
+            .map_or(true, |refresh_at| Instant::now() < refresh_at)
+    }
+}
+
+/// Adds an OAuth2 access token to requests, fetching a new token whenever
+/// the previous one expires
+#[derive(Debug)]
+pub(crate) struct OAuth2Handler {
+    config: OAuth2Config,
+    client: reqwest::Client,
+    token: Option<CachedToken>,
+}
+
+impl OAuth2Handler {
+    /// Fetch tokens for `config` with `client`
+    pub(crate) const fn new(config: OAuth2Config, client: reqwest::Client) -> Self {
+        Self {
+            config,
+            client,
+            token: None,
+        }
+    }
+
+    /// The `Authorization` header with a fresh access token
+    async fn authorization(&mut self) -> Result<HeaderValue, ErrorKind> {
+        match &self.token {
+            Some(token) if token.is_fresh() => Ok(token.authorization.clone()),
+            _ => {
+                let token = self.fetch_token().await?;
+                let authorization = token.authorization.clone();
+                self.token = Some(token);
+                Ok(authorization)
+            }
+        }
+    }
+
+    async fn fetch_token(&self) -> Result<CachedToken, ErrorKind> {
+        let response = self
+            .client
+            .post(self.config.token_url.clone())
+            .basic_auth(
+                &self.config.client_id,
+                Some(self.config.client_secret.expose_secret()),
+            )
+            .form(&[("grant_type", "client_credentials")])
+            .send()
+            .await
+            .and_then(Response::error_for_status)
+            .map_err(ErrorKind::OAuth2Request)?;
+        let body = response.text().await.map_err(ErrorKind::OAuth2Request)?;
+
+        let TokenResponse {
+            access_token,
+            token_type,
+            expires_in,
+        } = serde_json::from_str(&body)
+            .map_err(|e| ErrorKind::InvalidOAuth2Response(e.to_string()))?;
+        if !token_type.eq_ignore_ascii_case("bearer") {
+            return Err(ErrorKind::InvalidOAuth2Response(format!(
+                "Unsupported token type `{token_type}`"
+            )));
+        }
+        let mut authorization =
+            HeaderValue::try_from(format!("Bearer {access_token}")).map_err(|_| {
+                ErrorKind::InvalidOAuth2Response("Access token contains invalid characters".into())
+            })?;
+        authorization.set_sensitive(true);
+
+        Ok(CachedToken {
+            authorization,
+            refresh_at: expires_in.map(|secs| {
+                Instant::now() + Duration::from_secs(secs).saturating_sub(EXPIRY_MARGIN)
+            }),
+        })
+    }
+}
+
+#[async_trait]
+impl Handler<Request, Status> for OAuth2Handler {
+    async fn handle(&mut self, mut request: Request) -> ChainResult<Request, Status> {
+        match self.authorization().await {
+            Ok(authorization) => {
+                request.headers_mut().insert(AUTHORIZATION, authorization);
+                ChainResult::Next(request)
+            }
+            Err(e) => ChainResult::Done(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+    use reqwest::Method;
+    use wiremock::{
+        matchers::{body_string, header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+
+    async fn token_server(body: &str, expected_requests: u64) -> MockServer {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            // `id:secret`
+            .and(header("Authorization", "Basic aWQ6c2VjcmV0"))
+            .and(body_string("grant_type=client_credentials"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_string(body))
+            .expect(expected_requests)
+            .mount(&mock_server)
+            .await;
+        mock_server
+    }
+
+    fn handler(mock_server: &MockServer) -> OAuth2Handler {
+        let token_url = Url::parse(&format!("{}/token", mock_server.uri())).unwrap();
+        OAuth2Handler::new(
+            OAuth2Config::new(token_url, "id", SecretString::from("secret")),
+            reqwest::Client::new(),
+        )
+    }
+
+    async fn authorization(handler: &mut OAuth2Handler) -> Option<HeaderValue> {
+        let request = Request::new(Method::GET, Url::parse("https://example.com").unwrap());
+        match handler.handle(request).await {
+            ChainResult::Next(request) => request.headers().get(AUTHORIZATION).cloned(),
+            ChainResult::Done(_) => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_token_is_cached() {
+        let mock_server = token_server(
+            r#"{"access_token": "abc", "token_type": "Bearer", "expires_in": 3600}"#,
+            1,
+        )
+        .await;
+        let mut handler = handler(&mock_server);
+
+        for _ in 0..3 {
+            assert_eq!(authorization(&mut handler).await.unwrap(), "Bearer abc");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_is_refreshed() {
+        // Expires within the margin, so the token is refreshed right away
+        let mock_server = token_server(
+            r#"{"access_token": "abc", "token_type": "bearer", "expires_in": 10}"#,
+            2,
+        )
+        .await;
+        let mut handler = handler(&mock_server);
+
+        assert_eq!(authorization(&mut handler).await.unwrap(), "Bearer abc");
+        assert_eq!(authorization(&mut handler).await.unwrap(), "Bearer abc");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_token_response() {
+        let mock_server = token_server(r#"{"access_token": "abc", "token_type": "mac"}"#, 1).await;
+        let mut handler = handler(&mock_server);
+        assert!(authorization(&mut handler).await.is_none());
+    }
+
+    #[test]
+    fn test_hosts() {
+        let config = OAuth2Config::new(
+            Url::parse("https://auth.example.com/token").unwrap(),
+            "id",
+            SecretString::from("secret"),
+        );
+        assert_eq!(config.hosts(), ["auth.example.com"]);
+        let config = config.with_hosts(vec!["API.example.com".to_string()]);
+        assert_eq!(config.hosts(), ["api.example.com"]);
+    }
+}
@@ -1,8 +1,12 @@
+mod netrc;
+
 use regex::RegexSet;
 use thiserror::Error;
 
 use crate::{BasicAuthCredentials, BasicAuthSelector, Uri};
 
+pub use netrc::{Netrc, NetrcError};
+
 #[derive(Debug, Error)]
 pub enum BasicAuthExtractorError {
     #[error("RegexSet error")]
@@ -11,11 +15,13 @@ pub enum BasicAuthExtractorError {
 
 /// Extracts basic auth credentials from a given URI.
 /// Credentials are extracted if the URI matches one of the provided
-/// [`BasicAuthSelector`] instances.
+/// [`BasicAuthSelector`] instances, or else from the entry of its host in a
+/// [`Netrc`] file if the URI uses https.
 #[derive(Debug, Clone)]
 pub struct BasicAuthExtractor {
     credentials: Vec<BasicAuthCredentials>,
     regex_set: RegexSet,
+    netrc: Option<Netrc>,
 }
 
 impl BasicAuthExtractor {
@@ -55,18 +61,37 @@ impl BasicAuthExtractor {
         Ok(Self {
             credentials,
             regex_set,
+            netrc: None,
         })
     }
 
+    /// Use the credentials of `netrc` for https URIs which don't match any
+    /// of the selectors
+    #[must_use]
+    pub fn with_netrc(mut self, netrc: Netrc) -> Self {
+        self.netrc = Some(netrc);
+        self
+    }
+
     /// Matches the provided URI against the [`RegexSet`] and returns
     /// [`BasicAuthCredentials`] if the a match was found. It should be noted
     /// that only the first match will be used to return the appropriate
-    /// credentials.
+    /// credentials. https URIs without a match get the credentials of their
+    /// host in the netrc file, if any, so that these are never sent in plain
+    /// text.
     pub(crate) fn matches(&self, uri: &Uri) -> Option<BasicAuthCredentials> {
         let matches: Vec<_> = self.regex_set.matches(uri.as_str()).into_iter().collect();
 
         if matches.is_empty() {
-            return None;
+            if uri.scheme() != "https" {
+                return None;
+            }
+            return self
+                .netrc
+                .as_ref()
+                .zip(uri.host_str())
+                .and_then(|(netrc, host)| netrc.credentials(host))
+                .cloned();
         }
 
         Some(self.credentials[matches[0]].clone())
@@ -159,4 +184,29 @@ mod tests {
 
         assert!(credentials.is_none());
     }
+
+    #[test]
+    fn test_basic_auth_extractor_netrc() {
+        let selector = BasicAuthSelector::from_str("http://example.com foo:bar").unwrap();
+        let netrc: Netrc = "machine example.com login netrc password secret\n\
+            machine example.org login netrc password secret"
+            .parse()
+            .unwrap();
+        let extractor = BasicAuthExtractor::new([selector])
+            .unwrap()
+            .with_netrc(netrc);
+
+        let uri = Uri::try_from("http://example.com").unwrap();
+        assert_eq!(extractor.matches(&uri).unwrap().username, "foo");
+
+        let uri = Uri::try_from("https://example.org/path").unwrap();
+        assert_eq!(extractor.matches(&uri).unwrap().username, "netrc");
+
+        let uri = Uri::try_from("https://example.net").unwrap();
+        assert!(extractor.matches(&uri).is_none());
+
+        // The credentials of the netrc file are never sent in plain text
+        let uri = Uri::try_from("http://example.org/path").unwrap();
+        assert!(extractor.matches(&uri).is_none());
+    }
 }
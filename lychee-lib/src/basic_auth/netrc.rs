@@ -0,0 +1,217 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use shellexpand::tilde;
+use thiserror::Error;
+
+use crate::BasicAuthCredentials;
+
+/// Environment variable with the path of the netrc file, like in curl
+const NETRC_ENV: &str = "NETRC";
+
+/// Default location of the netrc file
+const DEFAULT_NETRC: &str = "~/.netrc";
+
+/// Errors of reading and parsing [`Netrc`] files
+#[derive(Debug, Error)]
+pub enum NetrcError {
+    /// The netrc file could not be read
+    #[error("Failed to read netrc file {0}")]
+    Io(PathBuf, #[source] std::io::Error),
+
+    /// A keyword of the netrc file is not followed by its value
+    #[error("Invalid netrc file: missing value after `{0}`")]
+    MissingValue(String),
+}
+
+/// Credentials per host from a [netrc file], as used by curl and wget.
+///
+/// The `default` entry is ignored, so that credentials are only sent to
+/// the hosts they were set up for.
+///
+/// [netrc file]: https://everything.curl.dev/usingcurl/netrc.html
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Netrc {
+    machines: HashMap<String, BasicAuthCredentials>,
+}
+
+impl Netrc {
+    /// Read the netrc file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or parsed.
+    pub fn from_path(path: &Path) -> Result<Self, NetrcError> {
+        std::fs::read_to_string(path)
+            .map_err(|e| NetrcError::Io(path.to_path_buf(), e))?
+            .parse()
+    }
+
+    /// Read the netrc file at the path of the `NETRC` environment variable
+    /// or at `~/.netrc`.
+    ///
+    /// Returns `None` if the file doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists, but can't be read or parsed.
+    pub fn discover() -> Result<Option<Self>, NetrcError> {
+        let path = match std::env::var_os(NETRC_ENV) {
+            Some(path) => PathBuf::from(path),
+            None => PathBuf::from(tilde(DEFAULT_NETRC).as_ref()),
+        };
+        if !path.is_file() {
+            return Ok(None);
+        }
+        Self::from_path(&path).map(Some)
+    }
+
+    /// The credentials of the `machine` entry of `host`
+    #[must_use]
+    pub fn credentials(&self, host: &str) -> Option<&BasicAuthCredentials> {
+        self.machines.get(&host.to_lowercase())
+    }
+}
+
+/// Credentials of a `machine` or `default` entry while it is parsed
+#[derive(Default)]
+struct Entry {
+    login: Option<String>,
+    password: Option<String>,
+}
+
+impl Entry {
+    /// Entries without a password are ignored
+    fn into_credentials(self) -> Option<BasicAuthCredentials> {
+        Some(BasicAuthCredentials {
+            username: self.login.unwrap_or_default(),
+            password: self.password?,
+        })
+    }
+}
+
+impl FromStr for Netrc {
+    type Err = NetrcError;
+
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        let mut netrc = Netrc::default();
+        // Host of the current entry, or `None` for the `default` entry
+        let mut current: Option<(Option<String>, Entry)> = None;
+
+        let mut finish = |current: Option<(Option<String>, Entry)>| {
+            if let Some((Some(host), entry)) = current {
+                if let Some(credentials) = entry.into_credentials() {
+                    netrc.machines.entry(host).or_insert(credentials);
+                }
+            }
+        };
+
+        let mut lines = content.lines();
+        while let Some(line) = lines.next() {
+            if line.trim_start().starts_with('#') {
+                continue;
+            }
+            let mut tokens = line.split_whitespace();
+            while let Some(token) = tokens.next() {
+                let mut value = || {
+                    tokens
+                        .next()
+                        .map(str::to_string)
+                        .ok_or_else(|| NetrcError::MissingValue(token.to_string()))
+                };
+                match token {
+                    "machine" => {
+                        let host = value()?.to_lowercase();
+                        finish(current.replace((Some(host), Entry::default())));
+                    }
+                    "default" => finish(current.replace((None, Entry::default()))),
+                    "login" => {
+                        let login = value()?;
+                        if let Some((_, entry)) = &mut current {
+                            entry.login = Some(login);
+                        }
+                    }
+                    "password" => {
+                        let password = value()?;
+                        if let Some((_, entry)) = &mut current {
+                            entry.password = Some(password);
+                        }
+                    }
+                    "account" => {
+                        value()?;
+                    }
+                    "macdef" => {
+                        // Macros end with an empty line
+                        value()?;
+                        for line in lines.by_ref() {
+                            if line.trim().is_empty() {
+                                break;
+                            }
+                        }
+                        break;
+                    }
+                    // Ignore unknown tokens
+                    _ => {}
+                }
+            }
+        }
+        finish(current);
+
+        Ok(netrc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credentials(username: &str, password: &str) -> BasicAuthCredentials {
+        BasicAuthCredentials {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_netrc() {
+        let netrc: Netrc = r"
+            # Comment
+            machine example.com login user password secret
+            machine API.example.com
+                login api
+                password token
+            macdef init
+            cd /pub
+            machine ignored.example.com password ignored
+
+            machine only-login.example.com login user
+            default login anonymous password guest
+        "
+        .parse()
+        .unwrap();
+
+        assert_eq!(
+            netrc.credentials("example.com"),
+            Some(&credentials("user", "secret"))
+        );
+        assert_eq!(
+            netrc.credentials("api.example.com"),
+            Some(&credentials("api", "token"))
+        );
+        // The `default` entry is never used
+        assert_eq!(netrc.credentials("ignored.example.com"), None);
+        assert_eq!(netrc.credentials("only-login.example.com"), None);
+        assert_eq!(netrc.credentials("other.example.com"), None);
+    }
+
+    #[test]
+    fn test_parse_netrc_missing_value() {
+        assert!(matches!(
+            "machine example.com login".parse::<Netrc>(),
+            Err(NetrcError::MissingValue(token)) if token == "login"
+        ));
+    }
+}
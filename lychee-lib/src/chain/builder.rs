@@ -0,0 +1,192 @@
+use std::fmt::{self, Debug};
+
+use async_trait::async_trait;
+
+use super::{traverse_handlers, Chain, ChainResult, Handler, InnerChain};
+
+/// Predicate which decides whether a conditional handler is applied
+type Predicate<T> = Box<dyn Fn(&T) -> bool + Send + Sync>;
+
+/// Fluent builder for a [`Chain`]
+///
+/// Handlers are traversed in the order in which they were added.
+///
+/// # Example
+///
+/// ```
+/// use lychee_lib::chain::{ChainBuilder, InjectHeaders, OverrideStatus, RequestChain};
+/// use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+/// use regex::Regex;
+/// use http::StatusCode;
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer token"));
+///
+/// let chain: RequestChain = ChainBuilder::new()
+///     // Links which block automated requests
+///     .then(OverrideStatus::new(
+///         Regex::new(r"^https://www\.linkedin\.com/").unwrap(),
+///         StatusCode::OK,
+///     ))
+///     // Only send the token to our own servers
+///     .when(
+///         |request: &reqwest::Request| request.url().host_str() == Some("example.com"),
+///         InjectHeaders::new(headers),
+///     )
+///     .build();
+/// ```
+pub struct ChainBuilder<T, R> {
+    handlers: InnerChain<T, R>,
+}
+
+impl<T, R> Default for ChainBuilder<T, R> {
+    fn default() -> Self {
+        Self {
+            handlers: InnerChain::default(),
+        }
+    }
+}
+
+impl<T, R> Debug for ChainBuilder<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChainBuilder")
+            .field("handlers", &self.handlers)
+            .finish()
+    }
+}
+
+impl<T: Send + 'static, R: Send + 'static> ChainBuilder<T, R> {
+    /// Create a builder without any handlers
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a `handler` to the end of the chain
+    #[must_use]
+    pub fn then(mut self, handler: impl Handler<T, R> + Send + 'static) -> Self {
+        self.handlers.push(Box::new(handler));
+        self
+    }
+
+    /// Add a `handler` which is only applied to inputs matching the
+    /// `predicate`. Other inputs are passed on to the next handler.
+    #[must_use]
+    pub fn when(
+        self,
+        predicate: impl Fn(&T) -> bool + Send + Sync + 'static,
+        handler: impl Handler<T, R> + Send + 'static,
+    ) -> Self {
+        self.branch(
+            predicate,
+            ChainBuilder::new().then(handler),
+            ChainBuilder::new(),
+        )
+    }
+
+    /// Pass inputs matching the `predicate` through the handlers of `then`
+    /// and all other inputs through the handlers of `otherwise`.
+    ///
+    /// If the handlers of the branch don't return [`ChainResult::Done`], the
+    /// input is passed on to the next handler after the branch.
+    #[must_use]
+    pub fn branch(
+        mut self,
+        predicate: impl Fn(&T) -> bool + Send + Sync + 'static,
+        then: ChainBuilder<T, R>,
+        otherwise: ChainBuilder<T, R>,
+    ) -> Self {
+        self.handlers.push(Box::new(Branch {
+            predicate: Box::new(predicate),
+            then: then.handlers,
+            otherwise: otherwise.handlers,
+        }));
+        self
+    }
+
+    /// Build the chain
+    #[must_use]
+    pub fn build(self) -> Chain<T, R> {
+        Chain::new(self.handlers)
+    }
+}
+
+/// Handler which traverses one of two sub-chains, depending on the input
+struct Branch<T, R> {
+    predicate: Predicate<T>,
+    then: InnerChain<T, R>,
+    otherwise: InnerChain<T, R>,
+}
+
+impl<T, R> Debug for Branch<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Branch")
+            .field("then", &self.then)
+            .field("otherwise", &self.otherwise)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<T: Send + 'static, R: Send + 'static> Handler<T, R> for Branch<T, R> {
+    async fn handle(&mut self, input: T) -> ChainResult<T, R> {
+        let handlers = if (self.predicate)(&input) {
+            &mut self.then
+        } else {
+            &mut self.otherwise
+        };
+        traverse_handlers(handlers, input, None).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::ChainResult::{Done, Next};
+
+    #[derive(Debug)]
+    struct Add(usize);
+
+    #[async_trait]
+    impl Handler<usize, usize> for Add {
+        async fn handle(&mut self, input: usize) -> ChainResult<usize, usize> {
+            Next(input + self.0)
+        }
+    }
+
+    #[derive(Debug)]
+    struct Stop;
+
+    #[async_trait]
+    impl Handler<usize, usize> for Stop {
+        async fn handle(&mut self, input: usize) -> ChainResult<usize, usize> {
+            Done(input)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_then_and_when() {
+        let chain = ChainBuilder::new()
+            .then(Add(1))
+            .when(|input: &usize| *input > 5, Add(100))
+            .then(Add(10))
+            .build();
+        assert_eq!(chain.traverse(0).await, Next(11));
+        assert_eq!(chain.traverse(5).await, Next(116));
+    }
+
+    #[tokio::test]
+    async fn test_branch() {
+        let chain = ChainBuilder::new()
+            .branch(
+                |input: &usize| input % 2 == 0,
+                ChainBuilder::new().then(Add(1)).then(Stop),
+                ChainBuilder::new().then(Add(2)),
+            )
+            .then(Add(10))
+            .build();
+        // Even inputs stop inside the branch
+        assert_eq!(chain.traverse(2).await, Done(3));
+        assert_eq!(chain.traverse(1).await, Next(13));
+    }
+}
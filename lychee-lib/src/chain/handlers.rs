@@ -0,0 +1,203 @@
+//! Reusable handlers for request chains
+
+use async_trait::async_trait;
+use http::StatusCode;
+use regex::Regex;
+use reqwest::{header::HeaderMap, Request, Url};
+
+use super::{ChainResult, Handler};
+use crate::{ErrorKind, Redirects, Status};
+
+/// Adds headers to requests, replacing existing headers with the same names
+#[derive(Debug, Clone)]
+pub struct InjectHeaders {
+    headers: HeaderMap,
+}
+
+impl InjectHeaders {
+    /// Add `headers` to all requests
+    #[must_use]
+    pub const fn new(headers: HeaderMap) -> Self {
+        Self { headers }
+    }
+}
+
+#[async_trait]
+impl Handler<Request, Status> for InjectHeaders {
+    async fn handle(&mut self, mut request: Request) -> ChainResult<Request, Status> {
+        let headers = request.headers_mut();
+        for name in self.headers.keys() {
+            headers.remove(name);
+        }
+        for (name, value) in &self.headers {
+            headers.append(name, value.clone());
+        }
+        ChainResult::Next(request)
+    }
+}
+
+/// Rewrites the URLs of requests matching a pattern
+///
+/// The replacement can refer to capture groups of the pattern, e.g. `$1`.
+/// Unlike remaps, the rewritten URL is only used for the request and the
+/// original URL is still reported.
+#[derive(Debug, Clone)]
+pub struct RewriteUrl {
+    pattern: Regex,
+    replacement: String,
+}
+
+impl RewriteUrl {
+    /// Replace the matches of `pattern` in request URLs with `replacement`
+    #[must_use]
+    pub fn new(pattern: Regex, replacement: impl Into<String>) -> Self {
+        Self {
+            pattern,
+            replacement: replacement.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Handler<Request, Status> for RewriteUrl {
+    async fn handle(&mut self, mut request: Request) -> ChainResult<Request, Status> {
+        let url = request.url().as_str();
+        if !self.pattern.is_match(url) {
+            return ChainResult::Next(request);
+        }
+        let rewritten = self
+            .pattern
+            .replace_all(url, self.replacement.as_str())
+            .into_owned();
+        match Url::parse(&rewritten) {
+            Ok(url) => {
+                *request.url_mut() = url;
+                ChainResult::Next(request)
+            }
+            Err(e) => ChainResult::Done(ErrorKind::ParseUrl(e, rewritten).into()),
+        }
+    }
+}
+
+/// Answers requests to URLs matching a pattern with a fixed status code,
+/// without sending them
+///
+/// This is useful for links which are known to work, but block automated
+/// requests, or for links which should always be reported as broken.
+#[derive(Debug, Clone)]
+pub struct OverrideStatus {
+    pattern: Regex,
+    code: StatusCode,
+}
+
+impl OverrideStatus {
+    /// Answer requests to URLs matching `pattern` with the status `code`
+    #[must_use]
+    pub const fn new(pattern: Regex, code: StatusCode) -> Self {
+        Self { pattern, code }
+    }
+
+    fn status(&self) -> Status {
+        if self.code.is_success() {
+            Status::Ok(self.code)
+        } else if self.code.is_redirection() {
+            Status::Redirected(self.code, Redirects::default())
+        } else {
+            ErrorKind::OverriddenStatusCode(self.code).into()
+        }
+    }
+}
+
+#[async_trait]
+impl Handler<Request, Status> for OverrideStatus {
+    async fn handle(&mut self, request: Request) -> ChainResult<Request, Status> {
+        if self.pattern.is_match(request.url().as_str()) {
+            ChainResult::Done(self.status())
+        } else {
+            ChainResult::Next(request)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::{header::HeaderValue, Method};
+
+    use super::*;
+
+    fn new_request(url: &str) -> Request {
+        Request::new(Method::GET, Url::parse(url).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_inject_headers() {
+        let mut headers = HeaderMap::new();
+        headers.append("X-Custom", HeaderValue::from_static("a"));
+        headers.append("X-Custom", HeaderValue::from_static("b"));
+        let mut handler = InjectHeaders::new(headers);
+
+        let mut request = new_request("https://example.com");
+        request
+            .headers_mut()
+            .insert("X-Custom", HeaderValue::from_static("old"));
+        let ChainResult::Next(request) = handler.handle(request).await else {
+            panic!("Expected the request to be passed on");
+        };
+        let values: Vec<_> = request.headers().get_all("X-Custom").iter().collect();
+        assert_eq!(values, ["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_rewrite_url() {
+        let mut handler = RewriteUrl::new(
+            Regex::new(r"^https://old\.example\.com/(.*)$").unwrap(),
+            "https://new.example.com/$1",
+        );
+
+        let ChainResult::Next(request) = handler
+            .handle(new_request("https://old.example.com/a"))
+            .await
+        else {
+            panic!("Expected the request to be passed on");
+        };
+        assert_eq!(request.url().as_str(), "https://new.example.com/a");
+
+        let ChainResult::Next(request) = handler.handle(new_request("https://example.com/a")).await
+        else {
+            panic!("Expected the request to be passed on");
+        };
+        assert_eq!(request.url().as_str(), "https://example.com/a");
+
+        let mut handler = RewriteUrl::new(Regex::new(".*").unwrap(), "not a url");
+        assert!(matches!(
+            handler.handle(new_request("https://example.com")).await,
+            ChainResult::Done(Status::Error(ErrorKind::ParseUrl(..)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_override_status() {
+        let mut handler = OverrideStatus::new(
+            Regex::new(r"^https://blocked\.example\.com/").unwrap(),
+            StatusCode::OK,
+        );
+        assert!(matches!(
+            handler
+                .handle(new_request("https://blocked.example.com/a"))
+                .await,
+            ChainResult::Done(Status::Ok(StatusCode::OK))
+        ));
+        assert!(matches!(
+            handler.handle(new_request("https://example.com/a")).await,
+            ChainResult::Next(_)
+        ));
+
+        let mut handler = OverrideStatus::new(Regex::new(".*").unwrap(), StatusCode::GONE);
+        let ChainResult::Done(status) = handler.handle(new_request("https://example.com")).await
+        else {
+            panic!("Expected a status");
+        };
+        assert!(status.is_error());
+        assert_eq!(status.code(), Some(StatusCode::GONE));
+    }
+}
@@ -8,14 +8,31 @@
 //! [`Handler::chain`] on each handler in the chain consecutively.
 //!
 //! To add external handlers, you can implement the [`Handler`] trait and add
-//! the handler to the chain.
+//! the handler to the chain. A [`ChainBuilder`] assembles chains from
+//! handlers, which can also be applied conditionally, e.g. to some hosts only.
+//! Common handlers like [`InjectHeaders`], [`RewriteUrl`] and
+//! [`OverrideStatus`] are provided by this module.
+//!
+//! Each handler runs in a `handler` [tracing] span with its
+//! [name](Handler::name), and its execution time and outcome are logged at the
+//! debug level. [`Chain::stats`] sums them up over all traversals of a chain,
+//! e.g. to find out which handler slows down or short-circuits requests.
 //!
 //! [pattern]: https://github.com/lpxxn/rust-design-pattern/blob/master/behavioral/chain_of_responsibility.rs
+mod builder;
+mod handlers;
+mod stats;
+
+pub use builder::ChainBuilder;
+pub use handlers::{InjectHeaders, OverrideStatus, RewriteUrl};
+pub use stats::HandlerStats;
+
 use crate::Status;
 use async_trait::async_trait;
 use core::fmt::Debug;
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 use tokio::sync::Mutex;
+use tracing::Instrument;
 
 /// Result of a handler.
 ///
@@ -97,16 +114,33 @@ pub type RequestChain = Chain<reqwest::Request, Status>;
 /// threads.
 pub(crate) type InnerChain<T, R> = Vec<Box<dyn Handler<T, R> + Send>>;
 
+/// Handlers of a [`Chain`] and their accumulated [`HandlerStats`]
+#[derive(Debug)]
+struct Handlers<T, R> {
+    handlers: InnerChain<T, R>,
+    stats: Vec<HandlerStats>,
+}
+
+impl<T, R> From<InnerChain<T, R>> for Handlers<T, R> {
+    fn from(handlers: InnerChain<T, R>) -> Self {
+        let stats = handlers
+            .iter()
+            .map(|handler| HandlerStats::new(handler.name()))
+            .collect();
+        Self { handlers, stats }
+    }
+}
+
 /// The outer chain type.
 ///
 /// This is a wrapper around the inner chain type and allows for
 /// concurrent access to the chain.
 #[derive(Debug)]
-pub struct Chain<T, R>(Arc<Mutex<InnerChain<T, R>>>);
+pub struct Chain<T, R>(Arc<Mutex<Handlers<T, R>>>);
 
 impl<T, R> Default for Chain<T, R> {
     fn default() -> Self {
-        Self(Arc::new(Mutex::new(InnerChain::default())))
+        Self::new(InnerChain::default())
     }
 }
 
@@ -122,7 +156,7 @@ impl<T, R> Chain<T, R> {
     /// Create a new chain from a vector of chainable handlers
     #[must_use]
     pub fn new(values: InnerChain<T, R>) -> Self {
-        Self(Arc::new(Mutex::new(values)))
+        Self(Arc::new(Mutex::new(values.into())))
     }
 
     /// Traverse the chain with the given input.
@@ -133,19 +167,54 @@ impl<T, R> Chain<T, R> {
     ///
     /// If no handler returns `ChainResult::Done`, the chain will return
     /// `ChainResult::Next` with the input.
-    pub(crate) async fn traverse(&self, mut input: T) -> ChainResult<T, R> {
-        use ChainResult::{Done, Next};
-        for e in self.0.lock().await.iter_mut() {
-            match e.handle(input).await {
-                Next(r) => input = r,
-                Done(r) => {
-                    return Done(r);
-                }
+    pub(crate) async fn traverse(&self, input: T) -> ChainResult<T, R> {
+        let mut chain = self.0.lock().await;
+        let Handlers { handlers, stats } = &mut *chain;
+        traverse_handlers(handlers, input, Some(stats)).await
+    }
+
+    /// Execution times and outcomes of the handlers, summed up over all
+    /// traversals of this chain (and its clones), in the order of the
+    /// handlers.
+    ///
+    /// The handlers of a [branch](ChainBuilder::branch) are counted as a
+    /// single handler.
+    pub async fn stats(&self) -> Vec<HandlerStats> {
+        self.0.lock().await.stats.clone()
+    }
+}
+
+/// Call [`Handler::handle`] on each of the `handlers` until one of them returns
+/// [`ChainResult::Done`].
+///
+/// If `stats` are given, the call of each handler is recorded in the entry at
+/// the same index.
+async fn traverse_handlers<T, R>(
+    handlers: &mut InnerChain<T, R>,
+    mut input: T,
+    mut stats: Option<&mut Vec<HandlerStats>>,
+) -> ChainResult<T, R> {
+    use ChainResult::{Done, Next};
+    for (index, e) in handlers.iter_mut().enumerate() {
+        let name = e.name();
+        let span = tracing::debug_span!("handler", handler = name, index);
+        let start = Instant::now();
+        let result = e.handle(input).instrument(span.clone()).await;
+        let elapsed = start.elapsed();
+        let done = matches!(result, Done(_));
+        tracing::debug!(parent: &span, ?elapsed, done, "{name} finished");
+        if let Some(stats) = stats.as_mut().and_then(|stats| stats.get_mut(index)) {
+            stats.record(elapsed, done);
+        }
+        match result {
+            Next(r) => input = r,
+            Done(r) => {
+                return Done(r);
             }
         }
-
-        Next(input)
     }
+
+    Next(input)
 }
 
 /// Handler trait for implementing request handlers
@@ -190,6 +259,13 @@ pub trait Handler<T, R>: Debug {
     /// }
     /// ```
     async fn handle(&mut self, input: T) -> ChainResult<T, R>;
+
+    /// Name of the handler in traces and [`HandlerStats`].
+    ///
+    /// Defaults to the name of the type.
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
 }
 
 /// Client request chains
@@ -271,4 +347,18 @@ mod test {
         let result = chain.traverse(Result(0)).await;
         assert_eq!(result, Done(Result(80)));
     }
+
+    #[tokio::test]
+    async fn chain_stats() {
+        use super::Chain;
+        let chain: Chain<Result, Result> = Chain::new(vec![Box::new(Add(60)), Box::new(Add(50))]);
+        chain.traverse(Result(0)).await;
+        chain.traverse(Result(50)).await;
+
+        let stats = chain.stats().await;
+        assert_eq!(stats.len(), 2);
+        assert!(stats[0].name.ends_with("::Add"));
+        assert_eq!((stats[0].calls, stats[0].done), (2, 1));
+        assert_eq!((stats[1].calls, stats[1].done), (1, 1));
+    }
 }
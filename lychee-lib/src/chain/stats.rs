@@ -0,0 +1,58 @@
+use std::{fmt, time::Duration};
+
+/// Execution time and outcome of a [`Handler`](super::Handler), summed up over
+/// all traversals of a [`Chain`](super::Chain)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandlerStats {
+    /// Name of the handler, see [`Handler::name`](super::Handler::name)
+    pub name: &'static str,
+    /// Number of inputs which were passed to the handler
+    pub calls: u64,
+    /// Number of inputs for which the handler returned
+    /// [`ChainResult::Done`](super::ChainResult::Done), skipping all
+    /// subsequent handlers
+    pub done: u64,
+    /// Total execution time of the handler
+    pub total_time: Duration,
+}
+
+impl HandlerStats {
+    pub(crate) const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            calls: 0,
+            done: 0,
+            total_time: Duration::ZERO,
+        }
+    }
+
+    pub(crate) fn record(&mut self, elapsed: Duration, done: bool) {
+        self.calls += 1;
+        self.done += u64::from(done);
+        self.total_time += elapsed;
+    }
+
+    /// Average execution time per call, or zero if the handler was never
+    /// called
+    #[must_use]
+    pub fn average_time(&self) -> Duration {
+        u32::try_from(self.calls)
+            .ok()
+            .and_then(|calls| self.total_time.checked_div(calls))
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for HandlerStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} calls ({} done), {:?} total, {:?} average",
+            self.name,
+            self.calls,
+            self.done,
+            self.total_time,
+            self.average_time()
+        )
+    }
+}
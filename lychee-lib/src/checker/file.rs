@@ -1,8 +1,16 @@
+use async_trait::async_trait;
 use http::StatusCode;
 use log::warn;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
 
-use crate::{utils::fragment_checker::FragmentChecker, Base, ErrorKind, Status, Uri};
+use super::Checker;
+use crate::{
+    path_mapping::PathMappings, utils::fragment_checker::FragmentChecker, AnchorCache, Base,
+    BasicAuthCredentials, ErrorKind, FragmentStyle, Result, Status, Uri,
+};
 
 /// A utility for checking the existence and validity of file-based URIs.
 ///
@@ -15,6 +23,12 @@ pub(crate) struct FileChecker {
     base: Option<Base>,
     /// List of file extensions to try if the original path doesn't exist.
     fallback_extensions: Vec<String>,
+    /// Mappings of source files to the URL paths of their pages, which are
+    /// used if the original path doesn't exist.
+    path_mappings: Option<PathMappings>,
+    /// The source files by the URL paths of their pages, which is built on
+    /// first use.
+    path_index: Arc<OnceCell<HashMap<String, PathBuf>>>,
     /// Whether to check for the existence of fragments (e.g., `#section-id`) in HTML files.
     include_fragments: bool,
     /// Utility for performing fragment checks in HTML files.
@@ -28,17 +42,25 @@ impl FileChecker {
     ///
     /// * `base` - Optional base path or URL for resolving relative paths.
     /// * `fallback_extensions` - List of extensions to try if the original file is not found.
+    /// * `path_mappings` - Mappings of source files to the URL paths of their pages.
     /// * `include_fragments` - Whether to check for fragment existence in HTML files.
+    /// * `fragment_style` - How the fragments of Markdown headings are generated.
+    /// * `anchor_cache` - Fragments of files from previous runs, if any.
     pub(crate) fn new(
         base: Option<Base>,
         fallback_extensions: Vec<String>,
+        path_mappings: Option<PathMappings>,
         include_fragments: bool,
+        fragment_style: FragmentStyle,
+        anchor_cache: Option<AnchorCache>,
     ) -> Self {
         Self {
             base,
             fallback_extensions,
+            path_mappings,
+            path_index: Arc::default(),
             include_fragments,
-            fragment_checker: FragmentChecker::new(),
+            fragment_checker: FragmentChecker::new(fragment_style, anchor_cache),
         }
     }
 
@@ -106,9 +128,41 @@ impl FileChecker {
             return self.check_existing_path(path, uri).await;
         }
 
+        if let Some(source) = self.mapped_source(path).await {
+            return self.check_existing_path(&source, uri).await;
+        }
+
         self.check_with_fallback_extensions(path, uri).await
     }
 
+    /// Finds the source file of the page at `path` with the path mappings,
+    /// e.g. `docs/guide/install.md` for `/guide/install/`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The resolved path of the page.
+    ///
+    /// # Returns
+    ///
+    /// Returns the path of the source file, if any.
+    async fn mapped_source(&self, path: &Path) -> Option<PathBuf> {
+        let mappings = self.path_mappings.as_ref()?;
+        let url_path = mappings.url_path_of(path)?;
+        let index = self
+            .path_index
+            .get_or_init(|| {
+                let mappings = mappings.clone();
+                async move {
+                    // Walking the source tree blocks
+                    tokio::task::spawn_blocking(move || mappings.index())
+                        .await
+                        .unwrap_or_default()
+                }
+            })
+            .await;
+        index.get(&url_path).cloned()
+    }
+
     /// Checks an existing path, optionally verifying fragments for HTML files.
     ///
     /// # Arguments
@@ -156,6 +210,12 @@ impl FileChecker {
         ErrorKind::InvalidFilePath(uri.clone()).into()
     }
 
+    /// Forgets the fragments of the local file at `path`, which are parsed
+    /// again when they are needed the next time.
+    pub(crate) async fn forget_fragments(&self, path: &Path) {
+        self.fragment_checker.forget(path).await;
+    }
+
     /// Checks for the existence of a fragment in an HTML file.
     ///
     /// # Arguments
@@ -177,3 +237,10 @@ impl FileChecker {
         }
     }
 }
+
+#[async_trait]
+impl Checker for FileChecker {
+    async fn check(&self, uri: &Uri, _credentials: Option<BasicAuthCredentials>) -> Result<Status> {
+        Ok(FileChecker::check(self, uri).await)
+    }
+}
@@ -1,29 +1,106 @@
 #[cfg(all(feature = "email-check", feature = "native-tls"))]
-use http::StatusCode;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+#[cfg(all(feature = "email-check", feature = "native-tls"))]
+use log::debug;
+
+#[cfg(all(feature = "email-check", feature = "native-tls"))]
+use tokio::{sync::OnceCell, time::timeout};
 
 #[cfg(all(feature = "email-check", feature = "native-tls"))]
-use crate::ErrorKind;
+use crate::ratelimit::{HostConfig, HostPool};
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use email_address::EmailAddress;
+use http::StatusCode;
 
-use crate::{Status, Uri};
+use super::Checker;
+use crate::{BasicAuthCredentials, ErrorKind, MailCheckLevel, Status, Uri};
 
 #[cfg(all(feature = "email-check", feature = "native-tls"))]
-use check_if_email_exists::{check_email, CheckEmailInput, Reachable};
+use check_if_email_exists::{
+    misc::check_misc,
+    mx::{check_mx, MxDetails, MxError},
+    smtp::check_smtp,
+    syntax::{check_syntax, SyntaxDetails},
+    CheckEmailInput, CheckEmailOutput, Reachable,
+};
 
 #[cfg(all(feature = "email-check", feature = "native-tls"))]
 use crate::types::mail;
 
+/// Limits for SMTP probes per MX host.
+///
+/// Mail servers tend to greylist clients which open many connections in a
+/// short amount of time, so probes to the same MX host are sent one at a time.
+#[cfg(all(feature = "email-check", feature = "native-tls"))]
+const SMTP_HOST_CONFIG: HostConfig = HostConfig::new()
+    .with_max_concurrency(1)
+    .with_min_interval(Duration::from_secs(1));
+
+/// Result of a MX lookup, or `None` if all attempts timed out
+#[cfg(all(feature = "email-check", feature = "native-tls"))]
+type MxResult = Arc<Option<Result<MxDetails, MxError>>>;
+
+/// MX lookup shared between all addresses of a domain
+#[cfg(all(feature = "email-check", feature = "native-tls"))]
+type MxLookup = Arc<OnceCell<MxResult>>;
+
 /// A utility for checking the validity of email addresses.
 ///
 /// `EmailChecker` is responsible for validating email addresses,
 /// optionally performing reachability checks when the appropriate
 /// features are enabled.
+///
+/// MX lookups are cached per domain for the lifetime of the checker and
+/// SMTP probes are rate-limited per MX host, so documents with many
+/// addresses of the same domain don't trigger greylisting.
+/// Each MX lookup is bounded by a timeout, so slow DNS resolvers can't stall
+/// the whole pipeline.
 #[derive(Debug, Clone)]
-pub(crate) struct MailChecker {}
+pub(crate) struct MailChecker {
+    /// MX lookups per (lowercase) domain
+    #[cfg(all(feature = "email-check", feature = "native-tls"))]
+    mx_cache: Arc<Mutex<HashMap<String, MxLookup>>>,
+    /// Rate limits for SMTP probes per MX host
+    #[cfg(all(feature = "email-check", feature = "native-tls"))]
+    smtp_hosts: Arc<HostPool>,
+    /// Timeout of a single MX lookup attempt
+    #[cfg(all(feature = "email-check", feature = "native-tls"))]
+    dns_timeout: Duration,
+    /// Number of retries of timed out MX lookups
+    #[cfg(all(feature = "email-check", feature = "native-tls"))]
+    dns_max_retries: u64,
+    /// How thoroughly addresses are checked
+    level: MailCheckLevel,
+}
 
 impl MailChecker {
     /// Creates a new `EmailChecker`.
-    pub(crate) const fn new() -> Self {
-        Self {}
+    #[cfg(all(feature = "email-check", feature = "native-tls"))]
+    pub(crate) fn new(dns_timeout: Duration, dns_max_retries: u64, level: MailCheckLevel) -> Self {
+        Self {
+            mx_cache: Arc::default(),
+            smtp_hosts: Arc::new(HostPool::new(SMTP_HOST_CONFIG)),
+            dns_timeout,
+            dns_max_retries,
+            level,
+        }
+    }
+
+    /// Creates a new `EmailChecker`.
+    #[cfg(not(all(feature = "email-check", feature = "native-tls")))]
+    pub(crate) const fn new(
+        _dns_timeout: Duration,
+        _dns_max_retries: u64,
+        level: MailCheckLevel,
+    ) -> Self {
+        Self { level }
     }
 
     /// Check a mail address, or equivalently a `mailto` URI.
@@ -31,27 +108,202 @@ impl MailChecker {
     /// URIs may contain query parameters (e.g. `contact@example.com?subject="Hello"`),
     /// which are ignored by this check. They are not part of the mail address
     /// and instead passed to a mail client.
-    #[cfg(all(feature = "email-check", feature = "native-tls"))]
+    ///
+    /// The syntax is checked with any features. The MX and SMTP checks
+    /// require the `email-check` and `native-tls` features.
     pub(crate) async fn check_mail(&self, uri: &Uri) -> Status {
+        if !EmailAddress::is_valid(uri.url.path()) {
+            return ErrorKind::UnreachableEmailAddress(
+                uri.clone(),
+                "Invalid: The address is syntactically incorrect".to_string(),
+            )
+            .into();
+        }
+        if self.level == MailCheckLevel::Syntax {
+            return Status::Ok(StatusCode::OK);
+        }
         self.perform_email_check(uri).await
     }
 
-    /// Ignore the mail check if the `email-check` and `native-tls` features are not enabled.
+    /// Ignore the MX and SMTP checks if the `email-check` and `native-tls`
+    /// features are not enabled.
     #[cfg(not(all(feature = "email-check", feature = "native-tls")))]
-    pub(crate) async fn check_mail(&self, _uri: &Uri) -> Status {
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    async fn perform_email_check(&self, _uri: &Uri) -> Status {
         Status::Excluded
     }
 
+    /// Look up the MX records of the address' domain.
+    ///
+    /// Concurrent lookups of the same domain wait for the first one to finish.
+    /// Timed out lookups are cached as well, so that a slow domain is only
+    /// waited for once.
+    #[cfg(all(feature = "email-check", feature = "native-tls"))]
+    async fn mx_lookup(&self, syntax: &SyntaxDetails) -> MxResult {
+        let cell = {
+            let mut cache = self
+                .mx_cache
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            Arc::clone(cache.entry(syntax.domain.to_lowercase()).or_default())
+        };
+        Arc::clone(
+            cell.get_or_init(|| async { Arc::new(self.mx_lookup_with_timeout(syntax).await) })
+                .await,
+        )
+    }
+
+    /// Look up the MX records, retrying if the DNS resolver is too slow.
+    ///
+    /// Returns `None` if all attempts timed out.
+    #[cfg(all(feature = "email-check", feature = "native-tls"))]
+    async fn mx_lookup_with_timeout(
+        &self,
+        syntax: &SyntaxDetails,
+    ) -> Option<Result<MxDetails, MxError>> {
+        for attempt in 0..=self.dns_max_retries {
+            if let Ok(result) = timeout(self.dns_timeout, check_mx(syntax)).await {
+                return Some(result);
+            }
+            debug!(
+                "MX lookup for {} timed out (attempt {} of {})",
+                syntax.domain,
+                attempt + 1,
+                self.dns_max_retries + 1
+            );
+        }
+        None
+    }
+
+    /// Check the address the same way as `check_if_email_exists::check_email`,
+    /// but with cached MX lookups and rate-limited SMTP probes.
+    ///
+    /// The checks after the [`MailCheckLevel`] of the checker are skipped.
     #[cfg(all(feature = "email-check", feature = "native-tls"))]
     async fn perform_email_check(&self, uri: &Uri) -> Status {
         let address = uri.url.path().to_string();
-        let input = CheckEmailInput::new(address);
-        let result = &(check_email(&input).await);
+        let input = CheckEmailInput::new(address.clone());
+
+        let syntax = check_syntax(&address);
+        let Some(email_address) = syntax.address.clone().filter(|_| syntax.is_valid_syntax) else {
+            return Self::invalid(
+                uri,
+                &CheckEmailOutput {
+                    input: address,
+                    is_reachable: Reachable::Invalid,
+                    syntax,
+                    ..Default::default()
+                },
+            );
+        };
+
+        let mx = self.mx_lookup(&syntax).await;
+        let lookup = match mx.as_ref() {
+            Some(Ok(MxDetails { lookup: Ok(lookup) })) => lookup,
+            // Domain doesn't accept mail
+            Some(Ok(MxDetails { lookup: Err(_) })) => {
+                return Self::invalid(
+                    uri,
+                    &CheckEmailOutput {
+                        input: address,
+                        is_reachable: Reachable::Invalid,
+                        syntax,
+                        ..Default::default()
+                    },
+                );
+            }
+            // Internal error during the lookup; the address might be fine
+            Some(Err(_)) => return Status::Ok(StatusCode::OK),
+            // DNS resolver didn't answer in time
+            None => return Status::Timeout(None),
+        };
+        if self.level == MailCheckLevel::Mx {
+            return Status::Ok(StatusCode::OK);
+        }
+
+        // Like `check_email`, skip the first and last records, because some
+        // domains use them as dummies. Pick the middle one instead of a
+        // random one, so that probes for the same domain hit the same host.
+        let mut records = lookup.iter().collect::<Vec<_>>();
+        records.sort_by_key(|record| record.preference());
+        let Some(host) = records.get(records.len() / 2) else {
+            return Status::Ok(StatusCode::OK);
+        };
 
-        if let Reachable::Invalid = result.is_reachable {
-            ErrorKind::UnreachableEmailAddress(uri.clone(), mail::error_from_output(result)).into()
+        let misc = check_misc(
+            &syntax,
+            input.check_gravatar,
+            input.haveibeenpwned_api_key.clone(),
+        )
+        .await;
+
+        let smtp = {
+            let _permit = self.smtp_hosts.acquire(&host.exchange().to_string()).await;
+            check_smtp(
+                &email_address,
+                host.exchange(),
+                input.smtp_port,
+                syntax.domain.as_ref(),
+                &input,
+            )
+            .await
+        };
+
+        let is_invalid = smtp.as_ref().is_ok_and(|smtp| {
+            let is_risky = misc.is_disposable
+                || misc.is_role_account
+                || smtp.is_catch_all
+                || smtp.has_full_inbox;
+            !is_risky && (!smtp.is_deliverable || !smtp.can_connect_smtp || smtp.is_disabled)
+        });
+
+        if is_invalid {
+            Self::invalid(
+                uri,
+                &CheckEmailOutput {
+                    input: address,
+                    is_reachable: Reachable::Invalid,
+                    misc: Ok(misc),
+                    smtp,
+                    syntax,
+                    ..Default::default()
+                },
+            )
         } else {
             Status::Ok(StatusCode::OK)
         }
     }
+
+    #[cfg(all(feature = "email-check", feature = "native-tls"))]
+    fn invalid(uri: &Uri, output: &CheckEmailOutput) -> Status {
+        ErrorKind::UnreachableEmailAddress(uri.clone(), mail::error_from_output(output)).into()
+    }
+}
+
+#[async_trait]
+impl Checker for MailChecker {
+    async fn check(
+        &self,
+        uri: &Uri,
+        _credentials: Option<BasicAuthCredentials>,
+    ) -> crate::Result<Status> {
+        Ok(self.check_mail(uri).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::MailChecker;
+    use crate::{MailCheckLevel, Uri};
+
+    #[tokio::test]
+    async fn test_syntax_level() {
+        let checker = MailChecker::new(Duration::from_secs(1), 0, MailCheckLevel::Syntax);
+        let valid = Uri::try_from("mailto:user@example.com").unwrap();
+        assert!(checker.check_mail(&valid).await.is_success());
+        let invalid = Uri::try_from("mailto:foo@@bar").unwrap();
+        assert!(checker.check_mail(&invalid).await.is_error());
+    }
 }
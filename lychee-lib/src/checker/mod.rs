@@ -4,4 +4,57 @@
 
 pub(crate) mod file;
 pub(crate) mod mail;
+pub(crate) mod platform;
+pub(crate) mod render;
+pub(crate) mod tel;
 pub(crate) mod website;
+
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+
+use crate::{BasicAuthCredentials, Result, Status, Uri};
+
+/// Checks the links of a URI scheme
+///
+/// The [`Client`](crate::Client) dispatches each link to the checker which is
+/// registered for its scheme. lychee registers checkers for `file`, `mailto`,
+/// `tel`, `http` and `https` links. Checkers for other schemes (or replacements
+/// of the built-in checkers) are registered with
+/// [`Client::register_checker`](crate::Client::register_checker).
+///
+/// # Example
+///
+/// ```
+/// use async_trait::async_trait;
+/// use http::StatusCode;
+/// use lychee_lib::{BasicAuthCredentials, Checker, ClientBuilder, Result, Status, Uri};
+///
+/// #[derive(Debug)]
+/// struct IpfsChecker;
+///
+/// #[async_trait]
+/// impl Checker for IpfsChecker {
+///     async fn check(&self, _uri: &Uri, _: Option<BasicAuthCredentials>) -> Result<Status> {
+///         // Look up the content identifier here
+///         Ok(Status::Ok(StatusCode::OK))
+///     }
+/// }
+///
+/// # fn main() -> Result<()> {
+/// let mut client = ClientBuilder::default().client()?;
+/// client.register_checker("ipfs", IpfsChecker);
+/// # Ok(())
+/// # }
+/// ```
+#[async_trait]
+pub trait Checker: Debug + Send + Sync {
+    /// Check `uri`, using the basic auth `credentials` of the request (if any).
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if `uri` can't be checked at all. Links which were
+    /// checked and found to be broken are reported with an error [`Status`]
+    /// instead.
+    async fn check(&self, uri: &Uri, credentials: Option<BasicAuthCredentials>) -> Result<Status>;
+}
@@ -0,0 +1,231 @@
+use async_trait::async_trait;
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+
+use super::{fetch, https_origin, repo_status, PlatformChecker, RepoUri};
+use crate::{Platform, Status, Uri};
+
+/// Host of Bitbucket Cloud. Links to other hosts are checked via the API of
+/// Bitbucket Data Center (formerly Bitbucket Server).
+const CLOUD_HOST: &str = "bitbucket.org";
+
+/// Base URL of the Bitbucket Cloud REST API
+const DEFAULT_BITBUCKET_API: &str = "https://api.bitbucket.org/2.0";
+
+/// Top-level paths of `bitbucket.org` which aren't workspaces
+const EXCLUDED_ENDPOINTS: &[&str] = &[
+    "account",
+    "dashboard",
+    "product",
+    "repo",
+    "site",
+    "snippets",
+    "socialauth",
+    "workspace",
+];
+
+/// The part of a repository of the Bitbucket Cloud API which is needed to
+/// check links
+#[derive(Debug, Deserialize)]
+struct BitbucketRepository {
+    is_private: Option<bool>,
+}
+
+/// The part of a repository of the Bitbucket Data Center API which is
+/// needed to check links
+#[derive(Debug, Deserialize)]
+struct BitbucketServerRepository {
+    public: Option<bool>,
+}
+
+/// Checks links to Bitbucket Cloud or a self-hosted Bitbucket Data Center
+/// instance via the Bitbucket REST API
+#[derive(Debug)]
+pub(crate) struct BitbucketChecker {
+    /// Base URL of the Bitbucket Cloud API
+    api: String,
+    /// Repository, project or workspace access token
+    token: Option<SecretString>,
+}
+
+impl BitbucketChecker {
+    pub(crate) fn new(token: Option<SecretString>) -> Self {
+        Self {
+            api: DEFAULT_BITBUCKET_API.to_string(),
+            token,
+        }
+    }
+
+    async fn check_cloud(&self, client: &reqwest::Client, uri: &Uri) -> Option<Status> {
+        let RepoUri {
+            owner,
+            repo,
+            endpoint,
+        } = RepoUri::parse(uri, EXCLUDED_ENDPOINTS)?;
+        let mut request = client.get(format!("{}/repositories/{owner}/{repo}", self.api));
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token.expose_secret());
+        }
+        let repository = fetch::<BitbucketRepository>(Platform::Bitbucket, request).await?;
+        let private = repository.is_private.unwrap_or_default();
+        Some(repo_status(
+            Platform::Bitbucket,
+            private,
+            &format!("{owner}/{repo}"),
+            endpoint,
+        ))
+    }
+
+    async fn check_server(&self, client: &reqwest::Client, uri: &Uri) -> Option<Status> {
+        let (repo, endpoint) = parse_server_path(uri)?;
+        let mut request = client.get(format!("{}/rest/api/1.0/{repo}", https_origin(uri)?));
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token.expose_secret());
+        }
+        let repository = fetch::<BitbucketServerRepository>(Platform::Bitbucket, request).await?;
+        let private = repository.public == Some(false);
+        Some(repo_status(Platform::Bitbucket, private, &repo, endpoint))
+    }
+}
+
+/// Path of the repository of a Bitbucket Data Center link in the REST API
+/// and the rest of the link, e.g. `projects/KEY/repos/repo` and
+/// `browse/README.md` for `/projects/KEY/repos/repo/browse/README.md`
+fn parse_server_path(uri: &Uri) -> Option<(String, Option<String>)> {
+    let parts: Vec<_> = uri
+        .path_segments()?
+        .filter(|part| !part.is_empty())
+        .collect();
+    match parts.as_slice() {
+        [kind @ ("projects" | "users"), owner, "repos", repo, endpoint @ ..] => Some((
+            format!("{kind}/{owner}/repos/{repo}"),
+            (!endpoint.is_empty()).then(|| endpoint.join("/")),
+        )),
+        // Clone URLs, where repositories of users are prefixed with `~`
+        ["scm", owner, repo] => {
+            let repo = repo.strip_suffix(".git").unwrap_or(*repo);
+            let repo = match owner.strip_prefix('~') {
+                Some(user) => format!("users/{user}/repos/{repo}"),
+                None => format!("projects/{owner}/repos/{repo}"),
+            };
+            Some((repo, None))
+        }
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl PlatformChecker for BitbucketChecker {
+    async fn check(&self, client: &reqwest::Client, uri: &Uri) -> Option<Status> {
+        if uri.host_str() == Some(CLOUD_HOST) {
+            self.check_cloud(client, uri).await
+        } else {
+            self.check_server(client, uri).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+    use wiremock::{
+        matchers::{header, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::test_utils::{https_client, https_server, website};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bitbucket() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path("/repositories/workspace/private"))
+            .and(header("Authorization", "Bearer secret"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_string(r#"{"is_private": true}"#),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/repositories/workspace/public"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_string(r#"{"is_private": false}"#),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let checker = BitbucketChecker {
+            api: mock_server.uri(),
+            token: Some(SecretString::from("secret")),
+        };
+        let client = reqwest::Client::new();
+
+        for (url, success) in [
+            (
+                "https://bitbucket.org/workspace/private/src/main/README.md",
+                true,
+            ),
+            ("https://bitbucket.org/workspace/public", true),
+            // Links into public repositories should succeed without the API
+            ("https://bitbucket.org/workspace/public/issues/1", false),
+            ("https://bitbucket.org/workspace/missing", false),
+        ] {
+            let status = checker.check(&client, &website(url)).await;
+            assert_eq!(
+                status.is_some_and(|status| status.is_success()),
+                success,
+                "{url}"
+            );
+        }
+        assert!(checker
+            .check(&client, &website("https://bitbucket.org/account/settings"))
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_bitbucket_server() {
+        let mock_server = MockServer::start().await;
+        for repo in [
+            "/rest/api/1.0/projects/KEY/repos/private",
+            "/rest/api/1.0/users/jdoe/repos/private",
+        ] {
+            Mock::given(path(repo))
+                .and(header("Authorization", "Bearer secret"))
+                .respond_with(
+                    ResponseTemplate::new(StatusCode::OK).set_body_string(r#"{"public": false}"#),
+                )
+                .mount(&mock_server)
+                .await;
+        }
+
+        let checker = BitbucketChecker::new(Some(SecretString::from("secret")));
+        let client = https_client();
+        let origin = https_server(&mock_server).await;
+
+        for (url, success) in [
+            (
+                format!("{origin}/projects/KEY/repos/private/browse/README.md"),
+                true,
+            ),
+            (format!("{origin}/scm/~jdoe/private.git"), true),
+            (format!("{origin}/projects/KEY/repos/missing"), false),
+            // The API is only requested over https
+            (
+                format!("{}/projects/KEY/repos/private", mock_server.uri()),
+                false,
+            ),
+        ] {
+            let status = checker.check(&client, &website(&url)).await;
+            assert_eq!(
+                status.is_some_and(|status| status.is_success()),
+                success,
+                "{url}"
+            );
+        }
+        assert!(checker
+            .check(&client, &website(&format!("{origin}/dashboard")))
+            .await
+            .is_none());
+    }
+}
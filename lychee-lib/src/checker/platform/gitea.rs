@@ -0,0 +1,121 @@
+use async_trait::async_trait;
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+
+use super::{fetch, https_origin, repo_status, PlatformChecker, RepoUri};
+use crate::{Platform, Status, Uri};
+
+/// Top-level paths of Gitea and Forgejo instances which aren't users or
+/// organizations
+const EXCLUDED_ENDPOINTS: &[&str] = &[
+    "-",
+    "admin",
+    "api",
+    "assets",
+    "explore",
+    "notifications",
+    "org",
+    "repo",
+    "user",
+];
+
+/// The part of a repository of the Gitea API which is needed to check links
+#[derive(Debug, Deserialize)]
+struct GiteaRepository {
+    private: Option<bool>,
+    /// Repositories which are only visible to signed-in users
+    internal: Option<bool>,
+}
+
+/// Checks links to a Gitea or Forgejo instance via its REST API
+#[derive(Debug)]
+pub(crate) struct GiteaChecker {
+    /// Access token with the `read:repository` scope
+    token: Option<SecretString>,
+}
+
+impl GiteaChecker {
+    pub(crate) const fn new(token: Option<SecretString>) -> Self {
+        Self { token }
+    }
+}
+
+#[async_trait]
+impl PlatformChecker for GiteaChecker {
+    async fn check(&self, client: &reqwest::Client, uri: &Uri) -> Option<Status> {
+        let RepoUri {
+            owner,
+            repo,
+            endpoint,
+        } = RepoUri::parse(uri, EXCLUDED_ENDPOINTS)?;
+        let url = format!("{}/api/v1/repos/{owner}/{repo}", https_origin(uri)?);
+        let mut request = client.get(url);
+        if let Some(token) = &self.token {
+            request = request.header(
+                http::header::AUTHORIZATION,
+                format!("token {}", token.expose_secret()),
+            );
+        }
+        let repository = fetch::<GiteaRepository>(Platform::Gitea, request).await?;
+        let private =
+            repository.private.unwrap_or_default() || repository.internal.unwrap_or_default();
+        Some(repo_status(
+            Platform::Gitea,
+            private,
+            &format!("{owner}/{repo}"),
+            endpoint,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+    use wiremock::{
+        matchers::{header, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::test_utils::{https_client, https_server, website};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_gitea() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path("/api/v1/repos/owner/private"))
+            .and(header("Authorization", "token secret"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_string(r#"{"private": true}"#),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let client = https_client();
+        let origin = https_server(&mock_server).await;
+        let uri = website(&format!("{origin}/owner/private/src/branch/main/README.md"));
+
+        let checker = GiteaChecker::new(Some(SecretString::from("secret")));
+        assert!(checker
+            .check(&client, &uri)
+            .await
+            .is_some_and(|status| status.is_success()));
+
+        // The API is only requested over https, and without the credentials
+        // of the link
+        let http = website(&format!(
+            "{}/owner/private",
+            mock_server.uri().replace("://", "://user:password@")
+        ));
+        assert!(checker.check(&client, &http).await.is_none());
+
+        // Private repositories aren't found without a token, so the status
+        // of the normal request is kept
+        let checker = GiteaChecker::new(None);
+        assert!(checker.check(&client, &uri).await.is_none());
+        assert!(checker
+            .check(&client, &website("https://codeberg.org/explore/repos"))
+            .await
+            .is_none());
+    }
+}
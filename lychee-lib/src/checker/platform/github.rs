@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use http::StatusCode;
+use octocrab::Octocrab;
+
+use super::PlatformChecker;
+use crate::{types::uri::github::GithubUri, ErrorKind, Status, Uri};
+
+/// Checks links to GitHub via the GitHub API
+#[derive(Debug)]
+pub(crate) struct GithubChecker {
+    client: Octocrab,
+}
+
+impl GithubChecker {
+    /// Hosts of links which are parsed as [`GithubUri`]s
+    pub(crate) const HOSTS: [&'static str; 3] =
+        ["github.com", "www.github.com", "raw.githubusercontent.com"];
+
+    pub(crate) const fn new(client: Octocrab) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl PlatformChecker for GithubChecker {
+    /// Check a `uri` hosted on `GitHub` via the GitHub API.
+    ///
+    /// # Caveats
+    ///
+    /// Files inside private repositories won't get checked and instead would
+    /// be reported as valid if the repository itself is reachable through the
+    /// API.
+    ///
+    /// A better approach would be to download the file through the API or
+    /// clone the repo, but we chose the pragmatic approach.
+    async fn check(&self, _client: &reqwest::Client, uri: &Uri) -> Option<Status> {
+        let uri = GithubUri::try_from(uri).ok()?;
+        let repo = match self.client.repos(&uri.owner, &uri.repo).get().await {
+            Ok(repo) => repo,
+            Err(e) => return Some(ErrorKind::GithubRequest(Box::new(e)).into()),
+        };
+        if let Some(true) = repo.private {
+            return Some(Status::Ok(StatusCode::OK));
+        } else if let Some(endpoint) = uri.endpoint {
+            return Some(
+                ErrorKind::InvalidGithubUrl(format!("{}/{}/{endpoint}", uri.owner, uri.repo))
+                    .into(),
+            );
+        }
+        Some(Status::Ok(StatusCode::OK))
+    }
+}
@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+
+use super::{fetch, repo_status, PlatformChecker};
+use crate::{types::uri::gitlab::GitlabUri, Platform, Status, Uri};
+
+/// The part of a project of the GitLab API which is needed to check links
+#[derive(Debug, Deserialize)]
+struct GitlabProject {
+    /// `public`, `internal` or `private`
+    visibility: Option<String>,
+}
+
+/// Checks links to a GitLab instance via the GitLab REST API
+#[derive(Debug)]
+pub(crate) struct GitlabChecker {
+    /// Personal, group or project access token with the `read_api` scope
+    token: Option<SecretString>,
+}
+
+impl GitlabChecker {
+    pub(crate) const fn new(token: Option<SecretString>) -> Self {
+        Self { token }
+    }
+}
+
+#[async_trait]
+impl PlatformChecker for GitlabChecker {
+    async fn check(&self, client: &reqwest::Client, uri: &Uri) -> Option<Status> {
+        let uri = GitlabUri::parse(uri).ok()?;
+        let mut request = client.get(uri.api_url());
+        if let Some(token) = &self.token {
+            request = request.header("PRIVATE-TOKEN", token.expose_secret());
+        }
+        let project = fetch::<GitlabProject>(Platform::Gitlab, request).await?;
+        let private = project
+            .visibility
+            .is_some_and(|visibility| visibility != "public");
+        Some(repo_status(
+            Platform::Gitlab,
+            private,
+            &format!("{}/-", uri.project),
+            uri.endpoint,
+        ))
+    }
+}
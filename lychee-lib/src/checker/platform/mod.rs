@@ -0,0 +1,252 @@
+//! Fallback checks of links to code hosting platforms via their APIs
+//!
+//! Links to private repositories (or platforms which rate limit or block
+//! automated requests) fail with a normal request. For these links, the
+//! [`PlatformChecker`] of the host checks that the repository exists via the
+//! API of the platform instead.
+
+mod bitbucket;
+mod gitea;
+mod github;
+mod gitlab;
+
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
+
+use async_trait::async_trait;
+use http::StatusCode;
+use log::debug;
+use reqwest::{RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+
+use crate::{types::redirect, ErrorKind, Platform, PlatformConfig, Status, Uri};
+
+pub(crate) use bitbucket::BitbucketChecker;
+pub(crate) use gitea::GiteaChecker;
+pub(crate) use github::GithubChecker;
+pub(crate) use gitlab::GitlabChecker;
+
+/// Checks links to repositories on a code hosting platform via its API
+///
+/// lychee comes with checkers for GitHub, GitLab, Bitbucket and Gitea.
+/// Checkers for other platforms are registered by host with
+/// [`ClientBuilder::platform_checkers`](crate::ClientBuilder::platform_checkers).
+/// They are only used for links which fail with a normal request, and only
+/// a successful status replaces the status of the normal request.
+///
+/// # Example
+///
+/// ```
+/// use std::{collections::HashMap, sync::Arc};
+///
+/// use async_trait::async_trait;
+/// use http::StatusCode;
+/// use lychee_lib::{ClientBuilder, PlatformChecker, Result, Status, Uri};
+///
+/// #[derive(Debug)]
+/// struct SourcehutChecker;
+///
+/// #[async_trait]
+/// impl PlatformChecker for SourcehutChecker {
+///     async fn check(&self, _client: &reqwest::Client, _uri: &Uri) -> Option<Status> {
+///         // Look up the repository via the API here
+///         Some(Status::Ok(StatusCode::OK))
+///     }
+/// }
+///
+/// # fn main() -> Result<()> {
+/// let checker: Arc<dyn PlatformChecker> = Arc::new(SourcehutChecker);
+/// let client = ClientBuilder::builder()
+///     .platform_checkers(HashMap::from([("git.sr.ht".to_string(), checker)]))
+///     .build()
+///     .client()?;
+/// # Ok(())
+/// # }
+/// ```
+#[async_trait]
+pub trait PlatformChecker: Debug + Send + Sync {
+    /// Check `uri` via the API of the platform.
+    ///
+    /// Returns `None` if `uri` is not a link to a repository, e.g. a link
+    /// to the settings of a user, or if the API request fails. The status
+    /// of the normal request of the link is kept in that case.
+    async fn check(&self, client: &reqwest::Client, uri: &Uri) -> Option<Status>;
+}
+
+/// The [`PlatformChecker`]s of all hosts whose links are checked via an API
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PlatformCheckers {
+    checkers: HashMap<String, Arc<dyn PlatformChecker>>,
+}
+
+impl PlatformCheckers {
+    /// Hosts which are known to run one of the platforms
+    pub(crate) const DEFAULT_HOSTS: [(&'static str, Platform); 2] = [
+        ("bitbucket.org", Platform::Bitbucket),
+        ("codeberg.org", Platform::Gitea),
+    ];
+
+    /// Check links to `host` with `checker`
+    pub(crate) fn insert(&mut self, host: impl Into<String>, checker: Arc<dyn PlatformChecker>) {
+        self.checkers.insert(host.into(), checker);
+    }
+
+    /// Check links to `host` with the checker for the platform of `config`
+    pub(crate) fn insert_config(&mut self, host: impl Into<String>, config: PlatformConfig) {
+        let PlatformConfig { platform, token } = config;
+        let checker: Arc<dyn PlatformChecker> = match platform {
+            Platform::Gitlab => Arc::new(GitlabChecker::new(token)),
+            Platform::Bitbucket => Arc::new(BitbucketChecker::new(token)),
+            Platform::Gitea => Arc::new(GiteaChecker::new(token)),
+        };
+        self.insert(host, checker);
+    }
+
+    /// The checker for links to the host of `uri`, if any
+    pub(crate) fn get(&self, uri: &Uri) -> Option<&dyn PlatformChecker> {
+        let host = uri.host_str()?;
+        self.checkers.get(host).map(AsRef::as_ref)
+    }
+}
+
+/// Owner and name of a repository, extracted from a link like
+/// `https://codeberg.org/owner/repo/src/branch/main/README.md`
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct RepoUri {
+    pub(crate) owner: String,
+    pub(crate) repo: String,
+    /// e.g. `issues/1` in `/owner/repo/issues/1`
+    pub(crate) endpoint: Option<String>,
+}
+
+impl RepoUri {
+    /// Parse the path of `uri` as `/owner/repo/endpoint`, unless the first
+    /// segment is one of the `excluded` top-level pages of the platform.
+    pub(crate) fn parse(uri: &Uri, excluded: &[&str]) -> Option<Self> {
+        let parts: Vec<_> = uri
+            .path_segments()?
+            .filter(|part| !part.is_empty())
+            .collect();
+        let [owner, repo, endpoint @ ..] = parts.as_slice() else {
+            return None;
+        };
+        if excluded.contains(owner) {
+            return None;
+        }
+        Some(RepoUri {
+            owner: (*owner).to_string(),
+            // If the URL ends with `.git`, assume this is a clone URL
+            repo: repo.strip_suffix(".git").unwrap_or(*repo).to_string(),
+            endpoint: (!endpoint.is_empty()).then(|| endpoint.join("/")),
+        })
+    }
+}
+
+/// Scheme, host and port of the API of the instance which hosts `uri`.
+///
+/// The API is always requested over https and without the credentials of
+/// `uri`, so that the token of a request is never sent in plain text.
+fn https_origin(uri: &Uri) -> Option<String> {
+    let host = uri.host_str()?;
+    Some(match uri.url.port() {
+        Some(port) => format!("https://{host}:{port}"),
+        None => format!("https://{host}"),
+    })
+}
+
+/// Send an API `request` and parse the JSON body of the response.
+///
+/// Returns `None` if the request fails, is redirected or the body doesn't
+/// have the expected fields, in which case the status of the normal
+/// request of the link is kept. Redirects aren't followed, so that the
+/// token of the request never reaches another URL.
+async fn fetch<T: DeserializeOwned>(platform: Platform, request: RequestBuilder) -> Option<T> {
+    let response = redirect::without_following(request.send())
+        .await
+        .and_then(Response::error_for_status);
+    let body = match response {
+        Ok(response) if response.status().is_redirection() => {
+            debug!(
+                "Not following the redirect of the {platform} API request to {}",
+                response.url()
+            );
+            return None;
+        }
+        Ok(response) => response.text().await,
+        Err(e) => Err(e),
+    };
+    match body {
+        Ok(body) => serde_json::from_str(&body).ok(),
+        Err(e) => {
+            debug!("{}", ErrorKind::PlatformRequest(platform, e));
+            None
+        }
+    }
+}
+
+/// Status of a link into a repository which was found via the API.
+///
+/// # Caveats
+///
+/// Only the repository itself is checked. Links to files or issues inside
+/// private repositories are reported as valid if the repository is
+/// reachable through the API. The normal request of a link into a public
+/// repository should have succeeded, so these links are reported as
+/// invalid.
+fn repo_status(platform: Platform, private: bool, path: &str, endpoint: Option<String>) -> Status {
+    match endpoint {
+        Some(endpoint) if !private => {
+            ErrorKind::InvalidPlatformUrl(platform, format!("{path}/{endpoint}")).into()
+        }
+        _ => Status::Ok(StatusCode::OK),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::website;
+
+    use super::*;
+
+    #[test]
+    fn test_repo_uri() {
+        assert_eq!(
+            RepoUri::parse(
+                &website("https://codeberg.org/forgejo/forgejo.git/"),
+                &["explore"]
+            ),
+            Some(RepoUri {
+                owner: "forgejo".to_string(),
+                repo: "forgejo".to_string(),
+                endpoint: None,
+            })
+        );
+        assert_eq!(
+            RepoUri::parse(
+                &website("https://bitbucket.org/workspace/repo/src/main/README.md"),
+                &[]
+            )
+            .and_then(|uri| uri.endpoint),
+            Some("src/main/README.md".to_string())
+        );
+        assert_eq!(
+            RepoUri::parse(&website("https://codeberg.org/explore/repos"), &["explore"]),
+            None
+        );
+        assert_eq!(
+            RepoUri::parse(&website("https://codeberg.org/forgejo"), &[]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_platform_checkers() {
+        let mut checkers = PlatformCheckers::default();
+        checkers.insert_config("git.example.com", PlatformConfig::new(Platform::Gitea));
+        assert!(checkers
+            .get(&website("https://git.example.com/owner/repo"))
+            .is_some());
+        assert!(checkers
+            .get(&website("https://example.com/owner/repo"))
+            .is_none());
+    }
+}
@@ -0,0 +1,536 @@
+//! Rendering of JavaScript pages in a headless browser (`render-js` feature)
+//!
+//! Single-page applications often respond with an empty shell (or a bot
+//! detection page) and create their content, including the elements which
+//! fragments refer to, with JavaScript. Pages of the hosts given to
+//! [`ClientBuilder::render_js_hosts`](crate::ClientBuilder) are loaded in a
+//! headless Chrome or Chromium instead, which is started on first use. Their
+//! fragments, canonical URL and content assertions are checked in the
+//! rendered document.
+//!
+//! The browser sends the headers of the client (e.g. the user agent, custom
+//! headers and basic auth credentials) and its cookies, uses the proxy of the
+//! proxy auto-config file (if any) and accepts invalid certificates if the
+//! client does. Browsers can't be given custom root certificates, so with
+//! root certificates, the certificate of a page is verified with the client
+//! before it is rendered.
+//!
+//! If the browser can't be started or the page can't be rendered, the link is
+//! checked with a normal request.
+
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use http::{
+    header::{COOKIE, TRANSFER_ENCODING},
+    HeaderMap, StatusCode,
+};
+use log::warn;
+use reqwest::{cookie::CookieStore, Request, Url};
+use reqwest_cookie_store::CookieStoreMutex;
+
+use super::website::canonical_mismatch;
+use crate::{
+    chain::{ChainResult, Handler},
+    proxy_pac::ProxyPac,
+    utils::fragment_checker::FragmentChecker,
+    ContentAssertion, ErrorKind, Redirects, Status, Uri,
+};
+
+/// Maximum time to load and render a page if the client has no timeout
+const DEFAULT_RENDER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How the browser connects to the rendered hosts, like the client
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BrowserOptions {
+    /// Headers of all requests, e.g. the user agent and custom headers
+    pub(crate) headers: HeaderMap,
+    /// Cookies which are sent with the requests
+    pub(crate) cookie_jar: Option<Arc<CookieStoreMutex>>,
+    /// Selects the proxy of each page. Without it, the browser uses the
+    /// proxy of the environment like the client.
+    pub(crate) proxy_pac: Option<ProxyPac>,
+    /// Accept invalid certificates
+    pub(crate) allow_insecure: bool,
+    /// Client which trusts custom root certificates, to verify the
+    /// certificate of a page before it is rendered
+    pub(crate) verify_with: Option<reqwest::Client>,
+}
+
+impl BrowserOptions {
+    /// Headers of the browser's requests for `request`: the headers of the
+    /// client, of the request and the cookies of its URL
+    fn headers(&self, request: &Request) -> HeaderMap {
+        let mut headers = self.headers.clone();
+        headers.remove(TRANSFER_ENCODING);
+        headers.extend(request.headers().clone());
+        if let Some(cookies) = self
+            .cookie_jar
+            .as_ref()
+            .and_then(|jar| jar.cookies(request.url()))
+        {
+            headers.insert(COOKIE, cookies);
+        }
+        headers
+    }
+
+    /// Verify the certificate of `url` with the client which trusts the
+    /// custom root certificates (if any), as the browser doesn't. Invalid
+    /// certificates are accepted with `allow_insecure`.
+    async fn verify_certificate(&self, url: &Url) -> Result<(), String> {
+        match &self.verify_with {
+            Some(client) if url.scheme() == "https" && !self.allow_insecure => client
+                .head(url.clone())
+                .send()
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("Cannot verify the certificate: {e}")),
+            _ => Ok(()),
+        }
+    }
+
+    /// The proxy of `url`, if it is selected by the proxy auto-config file
+    fn proxy(&self, url: &Url) -> Option<Url> {
+        self.proxy_pac.as_ref().and_then(|pac| pac.find_proxy(url))
+    }
+}
+
+/// Request handler which renders the pages of some hosts in a headless
+/// browser and checks their status, content assertions, fragments and
+/// canonical URL
+#[derive(Debug, Clone)]
+pub(crate) struct RenderJs {
+    /// Hosts whose pages are rendered (lowercase)
+    hosts: Arc<HashSet<String>>,
+    /// Status codes which are accepted in addition to `2xx`
+    accepted: Option<HashSet<StatusCode>>,
+    /// Verify that fragments exist in the rendered document
+    include_fragments: bool,
+    /// Verify that pages are retrieved from their canonical URL
+    check_canonical: bool,
+    /// Caches the fragments of rendered documents
+    fragment_checker: FragmentChecker,
+    /// Maximum time to load and render a page
+    timeout: Duration,
+    /// Requirements on the rendered documents of matching URLs
+    content_assertions: Arc<Vec<ContentAssertion>>,
+    /// How the browser connects to the rendered hosts
+    options: BrowserOptions,
+    #[cfg(feature = "render-js")]
+    browser: Arc<browser::Browser>,
+}
+
+impl RenderJs {
+    /// Render the pages of `hosts` and check them like the responses of
+    /// normal requests
+    ///
+    /// # Errors
+    ///
+    /// Fails if lychee was built without the `render-js` feature.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        hosts: &[String],
+        accepted: Option<HashSet<StatusCode>>,
+        include_fragments: bool,
+        check_canonical: bool,
+        fragment_checker: FragmentChecker,
+        timeout: Option<Duration>,
+        content_assertions: Vec<ContentAssertion>,
+        options: BrowserOptions,
+    ) -> crate::Result<Self> {
+        if cfg!(not(feature = "render-js")) {
+            return Err(ErrorKind::RenderJs(
+                "lychee was built without the `render-js` feature".to_string(),
+            ));
+        }
+        Ok(Self {
+            hosts: Arc::new(hosts.iter().map(|host| host.to_lowercase()).collect()),
+            accepted,
+            include_fragments,
+            check_canonical,
+            fragment_checker,
+            timeout: timeout.unwrap_or(DEFAULT_RENDER_TIMEOUT),
+            #[cfg(feature = "render-js")]
+            browser: Arc::new(browser::Browser::new(
+                options.allow_insecure || options.verify_with.is_some(),
+            )),
+            content_assertions: Arc::new(content_assertions),
+            options,
+        })
+    }
+
+    /// Whether the page at `url` is rendered
+    fn matches(&self, url: &Url) -> bool {
+        url.host_str()
+            .is_some_and(|host| self.hosts.contains(&host.to_lowercase()))
+    }
+
+    /// Status of a page which was rendered after a response with `code`
+    fn status(&self, code: StatusCode) -> Status {
+        if code.is_success() || self.accepted.as_ref().is_some_and(|a| a.contains(&code)) {
+            Status::Ok(code)
+        } else if code.is_redirection() {
+            Status::Redirected(code, Redirects::default())
+        } else {
+            ErrorKind::RenderedStatusCode(code).into()
+        }
+    }
+
+    /// Check the content assertions, fragment and canonical URL of `url` in
+    /// its rendered `content`, if requested
+    async fn check_content(&self, url: &Url, content: &str, status: Status) -> Status {
+        if !status.is_success() {
+            return status;
+        }
+        if let Some(reason) = self
+            .content_assertions
+            .iter()
+            .filter(|assertion| assertion.applies_to(url))
+            .find_map(|assertion| assertion.check(content))
+        {
+            return match status.code() {
+                Some(code) => ErrorKind::ContentAssertion(code, reason).into(),
+                None => status,
+            };
+        }
+        let has_fragment = url.fragment().is_some_and(|f| !f.is_empty());
+        if self.include_fragments && has_fragment {
+            match self.fragment_checker.check_html(url, content).await {
+                Ok(true) => {}
+                Ok(false) => return ErrorKind::InvalidFragment(Uri::from(url.clone())).into(),
+                Err(err) => {
+                    warn!("Skipping fragment check due to the following error: {err}");
+                }
+            }
+        }
+        if self.check_canonical {
+            if let Some(status) = canonical_mismatch(url, content, &status) {
+                return status;
+            }
+        }
+        status
+    }
+
+    /// Load `url` in the browser with the `headers` of the request through
+    /// the `proxy` (if any) and return the status code of the response and
+    /// the rendered document
+    #[cfg(feature = "render-js")]
+    async fn render(
+        &self,
+        url: &Url,
+        headers: &HeaderMap,
+        proxy: Option<&Url>,
+    ) -> Result<(StatusCode, String), String> {
+        self.browser.render(url, headers, proxy, self.timeout).await
+    }
+
+    #[cfg(not(feature = "render-js"))]
+    #[allow(clippy::unused_async)]
+    async fn render(
+        &self,
+        _url: &Url,
+        _headers: &HeaderMap,
+        _proxy: Option<&Url>,
+    ) -> Result<(StatusCode, String), String> {
+        Err("lychee was built without the `render-js` feature".to_string())
+    }
+}
+
+#[async_trait]
+impl Handler<Request, Status> for Option<RenderJs> {
+    async fn handle(&mut self, request: Request) -> ChainResult<Request, Status> {
+        let Some(render_js) = self else {
+            return ChainResult::Next(request);
+        };
+        if request.method() != reqwest::Method::GET || !render_js.matches(request.url()) {
+            return ChainResult::Next(request);
+        }
+        let url = request.url().clone();
+        let options = &render_js.options;
+        let headers = options.headers(&request);
+        let rendered = match options.verify_certificate(&url).await {
+            Ok(()) => {
+                let proxy = options.proxy(&url);
+                render_js.render(&url, &headers, proxy.as_ref()).await
+            }
+            Err(e) => Err(e),
+        };
+        match rendered {
+            Ok((code, content)) => {
+                let status = render_js.status(code);
+                ChainResult::Done(render_js.check_content(&url, &content, status).await)
+            }
+            Err(e) => {
+                warn!("Cannot render {url}, checking it without JavaScript: {e}");
+                ChainResult::Next(request)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "render-js")]
+mod browser {
+    use std::time::Duration;
+
+    use chromiumoxide::{
+        cdp::browser_protocol::{
+            network::{Headers, SetExtraHttpHeadersParams},
+            target::{
+                BrowserContextId, CreateBrowserContextParams, CreateTargetParams,
+                DisposeBrowserContextParams,
+            },
+        },
+        BrowserConfig, Page,
+    };
+    use futures::StreamExt;
+    use http::{HeaderMap, StatusCode};
+    use log::warn;
+    use reqwest::Url;
+    use tokio::sync::{OnceCell, Semaphore};
+
+    /// Maximum number of pages which are rendered at the same time
+    const MAX_PAGES: usize = 8;
+
+    /// A headless browser, which is launched on first use
+    #[derive(Debug)]
+    pub(super) struct Browser {
+        inner: OnceCell<chromiumoxide::Browser>,
+        /// Limits the number of open pages
+        pages: Semaphore,
+        /// Accept invalid certificates
+        ignore_certificate_errors: bool,
+    }
+
+    impl Browser {
+        /// A browser which is launched on first use
+        pub(super) fn new(ignore_certificate_errors: bool) -> Self {
+            Self {
+                inner: OnceCell::new(),
+                pages: Semaphore::new(MAX_PAGES),
+                ignore_certificate_errors,
+            }
+        }
+
+        /// Launch the browser and poll its events in the background
+        async fn launch(&self) -> Result<chromiumoxide::Browser, String> {
+            let mut config = BrowserConfig::builder();
+            if self.ignore_certificate_errors {
+                config = config.arg("--ignore-certificate-errors");
+            }
+            let config = config.build()?;
+            let (browser, mut handler) = chromiumoxide::Browser::launch(config)
+                .await
+                .map_err(|e| format!("Cannot launch browser: {e}"))?;
+            tokio::spawn(async move { while handler.next().await.is_some() {} });
+            Ok(browser)
+        }
+
+        /// Load `url` in a new page, which sends `headers` and connects
+        /// through `proxy` (if any), and return the status code of the
+        /// response and the rendered document
+        pub(super) async fn render(
+            &self,
+            url: &Url,
+            headers: &HeaderMap,
+            proxy: Option<&Url>,
+            timeout: Duration,
+        ) -> Result<(StatusCode, String), String> {
+            let browser = self.inner.get_or_try_init(|| self.launch()).await?;
+            let _permit = self.pages.acquire().await.map_err(|e| e.to_string())?;
+            // Proxies can only be set for a browser context, so pages with a
+            // proxy get their own context
+            let context = match proxy {
+                Some(proxy) => {
+                    let params = CreateBrowserContextParams::builder()
+                        .proxy_server(&proxy[..url::Position::AfterPort])
+                        .build();
+                    let context = browser.execute(params).await.map_err(|e| e.to_string())?;
+                    Some(context.result.browser_context_id)
+                }
+                None => None,
+            };
+            let result = Self::render_in(browser, context.clone(), url, headers, timeout).await;
+            if let Some(context) = context {
+                if let Err(e) = browser
+                    .execute(DisposeBrowserContextParams::new(context))
+                    .await
+                {
+                    warn!("Cannot close browser context of {url}: {e}");
+                }
+            }
+            result
+        }
+
+        /// Load `url` in a new page of the browser `context` (or the default
+        /// context)
+        async fn render_in(
+            browser: &chromiumoxide::Browser,
+            context: Option<BrowserContextId>,
+            url: &Url,
+            headers: &HeaderMap,
+            timeout: Duration,
+        ) -> Result<(StatusCode, String), String> {
+            let mut params = CreateTargetParams::builder().url("about:blank");
+            if let Some(context) = context {
+                params = params.browser_context_id(context);
+            }
+            let page = browser
+                .new_page(params.build()?)
+                .await
+                .map_err(|e| e.to_string())?;
+            let result = tokio::time::timeout(timeout, load(&page, url, headers))
+                .await
+                .unwrap_or_else(|_| Err(format!("Timed out after {}s", timeout.as_secs())));
+            if let Err(e) = page.close().await {
+                warn!("Cannot close page of {url}: {e}");
+            }
+            result
+        }
+    }
+
+    /// Navigate `page` to `url` with the extra `headers` and wait until it
+    /// is loaded
+    async fn load(
+        page: &Page,
+        url: &Url,
+        headers: &HeaderMap,
+    ) -> Result<(StatusCode, String), String> {
+        let headers = headers
+            .iter()
+            .filter_map(|(name, value)| {
+                let value = value.to_str().ok()?;
+                Some((name.to_string(), serde_json::Value::from(value)))
+            })
+            .collect::<serde_json::Map<_, _>>();
+        page.execute(SetExtraHttpHeadersParams::new(Headers::new(
+            serde_json::Value::Object(headers),
+        )))
+        .await
+        .map_err(|e| e.to_string())?;
+        page.goto(url.as_str()).await.map_err(|e| e.to_string())?;
+        let request = page
+            .wait_for_navigation_response()
+            .await
+            .map_err(|e| e.to_string())?;
+        let code = request
+            .as_ref()
+            .and_then(|request| request.response.as_ref())
+            .and_then(|response| u16::try_from(response.status).ok())
+            .and_then(|code| StatusCode::from_u16(code).ok())
+            .ok_or_else(|| "No response received".to_string())?;
+        let content = page.content().await.map_err(|e| e.to_string())?;
+        Ok((code, content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, sync::Arc};
+
+    use http::{
+        header::{AUTHORIZATION, COOKIE, TRANSFER_ENCODING, USER_AGENT},
+        HeaderMap, HeaderValue, StatusCode,
+    };
+    use reqwest::{cookie::CookieStore, Request, Url};
+    use reqwest_cookie_store::CookieStoreMutex;
+
+    use super::{BrowserOptions, RenderJs};
+    use crate::{utils::fragment_checker::FragmentChecker, ErrorKind, Status};
+
+    #[cfg(not(feature = "render-js"))]
+    #[test]
+    fn test_requires_feature() {
+        let result = RenderJs::new(
+            &["app.example.com".to_string()],
+            None,
+            true,
+            false,
+            FragmentChecker::default(),
+            None,
+            Vec::new(),
+            BrowserOptions::default(),
+        );
+        assert!(matches!(result, Err(ErrorKind::RenderJs(_))));
+    }
+
+    #[cfg(feature = "render-js")]
+    #[tokio::test]
+    async fn test_check_content() {
+        let render_js = RenderJs::new(
+            &["App.example.com".to_string()],
+            Some(HashSet::from([StatusCode::FORBIDDEN])),
+            true,
+            false,
+            FragmentChecker::default(),
+            None,
+            vec![crate::ContentAssertion {
+                url: regex::Regex::new("/docs/").unwrap(),
+                contains: None,
+                not_contains: Some(regex::Regex::new("(?i)page not found").unwrap()),
+            }],
+            BrowserOptions::default(),
+        )
+        .unwrap();
+        let url = Url::parse("https://app.example.com/#settings").unwrap();
+        assert!(render_js.matches(&url));
+        assert!(!render_js.matches(&Url::parse("https://example.com/").unwrap()));
+
+        assert!(matches!(
+            render_js.status(StatusCode::FORBIDDEN),
+            Status::Ok(StatusCode::FORBIDDEN)
+        ));
+        assert!(matches!(
+            render_js.status(StatusCode::NOT_FOUND),
+            Status::Error(ErrorKind::RenderedStatusCode(StatusCode::NOT_FOUND))
+        ));
+
+        let rendered = r#"<div id="app"><h2 id="settings">Settings</h2></div>"#;
+        let status = Status::Ok(StatusCode::OK);
+        assert!(render_js
+            .check_content(&url, rendered, status.clone())
+            .await
+            .is_success());
+        let missing = Url::parse("https://app.example.com/other#missing").unwrap();
+        assert!(matches!(
+            render_js
+                .check_content(&missing, rendered, status.clone())
+                .await,
+            Status::Error(ErrorKind::InvalidFragment(_))
+        ));
+        let docs = Url::parse("https://app.example.com/docs/a").unwrap();
+        assert!(matches!(
+            render_js
+                .check_content(&docs, "<h1>Page not found</h1>", status)
+                .await,
+            Status::Error(ErrorKind::ContentAssertion(StatusCode::OK, _))
+        ));
+    }
+
+    #[test]
+    fn test_headers() {
+        let jar = Arc::new(CookieStoreMutex::default());
+        let url = Url::parse("https://app.example.com/").unwrap();
+        jar.set_cookies(
+            &mut [HeaderValue::from_static("session=secret")].iter(),
+            &url,
+        );
+        let options = BrowserOptions {
+            headers: HeaderMap::from_iter([
+                (USER_AGENT, HeaderValue::from_static("lychee")),
+                (TRANSFER_ENCODING, HeaderValue::from_static("chunked")),
+            ]),
+            cookie_jar: Some(jar),
+            ..BrowserOptions::default()
+        };
+        let mut request = Request::new(reqwest::Method::GET, url);
+        request
+            .headers_mut()
+            .insert(AUTHORIZATION, HeaderValue::from_static("Basic dXNlcg=="));
+
+        let headers = options.headers(&request);
+        assert_eq!(headers[USER_AGENT], "lychee");
+        assert_eq!(headers[AUTHORIZATION], "Basic dXNlcg==");
+        assert_eq!(headers[COOKIE], "session=secret");
+        assert!(!headers.contains_key(TRANSFER_ENCODING));
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use http::StatusCode;
+use regex::Regex;
+
+use super::Checker;
+use crate::{BasicAuthCredentials, ErrorKind, Status, Uri};
+
+/// Maximum number of digits of a phone number in E.164 format, including the
+/// country calling code
+const MAX_DIGITS: usize = 15;
+
+/// Characters which may separate the digits of a phone number in a `tel:` URI
+/// (see [RFC 3966](https://www.rfc-editor.org/rfc/rfc3966#section-3)), and
+/// spaces, which are common in practice
+const VISUAL_SEPARATORS: &[char] = &['-', '.', '(', ')', ' '];
+
+/// Checks the syntax of phone numbers in `tel:` URIs.
+///
+/// Numbers have to be global numbers in E.164 format, i.e. a `+` followed by
+/// the country calling code and up to 15 digits in total. Visual separators
+/// and parameters like `;ext=123` are allowed. Numbers of the country calling
+/// codes with a pattern have to match the pattern as well.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TelChecker {
+    /// Patterns of the numbers (without `+`) by country calling code
+    patterns: HashMap<String, Regex>,
+}
+
+impl TelChecker {
+    /// Creates a checker with the `patterns` of numbers by country calling
+    /// code (e.g. `49`), with or without leading `+`
+    pub(crate) fn new(patterns: HashMap<String, Regex>) -> Self {
+        let patterns = patterns
+            .into_iter()
+            .map(|(code, pattern)| (code.trim_start_matches('+').to_string(), pattern))
+            .collect();
+        Self { patterns }
+    }
+
+    /// Returns why `number` (without scheme) is malformed, if it is
+    fn validate(&self, number: &str) -> Option<String> {
+        // Parameters like `;ext=` are not part of the number
+        let number = number.split(';').next().unwrap_or_default();
+        let Some(digits) = number.strip_prefix('+') else {
+            return Some("Not a global number starting with `+`".to_string());
+        };
+        let digits: String = digits
+            .chars()
+            .filter(|c| !VISUAL_SEPARATORS.contains(c))
+            .collect();
+
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Some("Contains characters other than digits".to_string());
+        }
+        if digits.starts_with('0') {
+            return Some("Country calling codes don't start with 0".to_string());
+        }
+        if digits.len() > MAX_DIGITS {
+            return Some(format!("More than {MAX_DIGITS} digits"));
+        }
+
+        let (code, pattern) = self
+            .patterns
+            .iter()
+            .filter(|(code, _)| digits.starts_with(code.as_str()))
+            .max_by_key(|(code, _)| code.len())?;
+        let matches = pattern
+            .find(&digits)
+            .is_some_and(|m| m.start() == 0 && m.end() == digits.len());
+        (!matches).then(|| format!("Doesn't match the pattern of +{code}: `{pattern}`"))
+    }
+}
+
+#[async_trait]
+impl Checker for TelChecker {
+    async fn check(&self, uri: &Uri, _: Option<BasicAuthCredentials>) -> crate::Result<Status> {
+        let number = percent_encoding::percent_decode_str(uri.url.path()).decode_utf8_lossy();
+        Ok(match self.validate(&number) {
+            Some(reason) => ErrorKind::InvalidPhoneNumber(uri.clone(), reason).into(),
+            None => Status::Ok(StatusCode::OK),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use regex::Regex;
+
+    use super::TelChecker;
+
+    #[test]
+    fn test_validate() {
+        let checker = TelChecker::default();
+        assert_eq!(checker.validate("+49-30-1234567"), None);
+        assert_eq!(checker.validate("+1(555)0100;ext=12"), None);
+        assert!(checker.validate("030-1234567").is_some());
+        assert!(checker.validate("+0301234567").is_some());
+        assert_eq!(checker.validate("+49 30 1234567"), None);
+        assert!(checker.validate("+49 30 abc").is_some());
+        assert!(checker.validate("+4930123456789012").is_some());
+    }
+
+    #[test]
+    fn test_validate_with_pattern() {
+        let checker = TelChecker::new(HashMap::from([(
+            "+49".to_string(),
+            Regex::new(r"49[1-9]\d{5,12}").unwrap(),
+        )]));
+        assert_eq!(checker.validate("+49-30-1234567"), None);
+        assert!(checker.validate("+49-0-1234567").is_some());
+        // Other countries are only checked for E.164
+        assert_eq!(checker.validate("+1-555-0100"), None);
+    }
+}
@@ -1,15 +1,66 @@
 use crate::{
     chain::{Chain, ChainResult, ClientRequestChains, Handler, RequestChain},
+    checker::{platform::PlatformCheckers, render::RenderJs, Checker},
+    extract::html::html5gum::{extract_html_canonical, extract_html_title_and_heading},
+    observer::{CheckEvent, CheckObserver},
     quirks::Quirks,
+    ratelimit::{HostActivity, HostPool},
     retry::RetryExt,
-    types::uri::github::GithubUri,
-    BasicAuthCredentials, ErrorKind, Status, Uri,
+    types::redirect,
+    utils::fragment_checker::FragmentChecker,
+    BasicAuthCredentials, ContentAssertion, ErrorKind, Redirect, Redirects, SkipReason, Status,
+    Uri,
 };
 use async_trait::async_trait;
-use http::StatusCode;
-use octocrab::Octocrab;
-use reqwest::Request;
-use std::{collections::HashSet, time::Duration};
+use http::{
+    header::{HeaderMap, ACCEPT_LANGUAGE, CONTENT_TYPE, LOCATION},
+    Method, StatusCode,
+};
+use log::warn;
+use reqwest::{Request, Response};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tracing::Instrument;
+
+/// Links into the checked site, which can get another timeout and number of
+/// retries than external links
+#[derive(Debug, Clone, Default)]
+pub(crate) struct InternalLinks {
+    /// Hosts of the site (lowercase)
+    pub(crate) hosts: HashSet<String>,
+    /// Timeout of requests to internal links, instead of the one of the
+    /// client
+    pub(crate) timeout: Option<Duration>,
+    /// Maximum number of retries of internal links, instead of `max_retries`
+    pub(crate) max_retries: Option<u64>,
+}
+
+impl InternalLinks {
+    /// Whether `url` is a local file or points to one of the hosts of the site
+    pub(crate) fn contains(&self, url: &reqwest::Url) -> bool {
+        url.scheme() == "file"
+            || url
+                .host_str()
+                .is_some_and(|host| self.hosts.contains(&host.to_lowercase()))
+    }
+}
+
+/// Maximum number of bytes of an HTML error page which are downloaded to
+/// describe it
+const ERROR_PAGE_LIMIT: usize = 64 * 1024;
+
+/// Maximum number of bytes of a page which are downloaded to check its
+/// content, fragments and canonical URL
+const BODY_LIMIT: usize = 16 * 1024 * 1024;
+
+/// Maximum number of characters of the description of an HTML error page
+const ERROR_PAGE_SNIPPET_LENGTH: usize = 100;
 
 #[derive(Debug, Clone)]
 pub(crate) struct WebsiteChecker {
@@ -19,15 +70,27 @@ pub(crate) struct WebsiteChecker {
     /// The HTTP client used for requests.
     reqwest_client: reqwest::Client,
 
-    /// GitHub client used for requests.
-    github_client: Option<Octocrab>,
+    /// Checkers of code hosting platforms (like GitHub) by host, used for
+    /// links which fail with a normal request.
+    platform_checkers: PlatformCheckers,
 
     /// The chain of plugins to be executed on each request.
     plugin_request_chain: RequestChain,
 
+    /// Adds the headers and OAuth2 access tokens of specific hosts to their
+    /// requests.
+    host_chain: RequestChain,
+
+    /// Headers of specific hosts, which are removed from the requests once
+    /// a redirect leads to another host.
+    host_headers: HashMap<String, HeaderMap>,
+
     /// Maximum number of retries per request before returning an error.
     max_retries: u64,
 
+    /// Links into the checked site, with their own timeout and retries.
+    internal: InternalLinks,
+
     /// Initial wait time between retries of failed requests. This doubles after
     /// each failure.
     retry_wait_time: Duration,
@@ -41,6 +104,43 @@ pub(crate) struct WebsiteChecker {
     ///
     /// This would treat unencrypted links as errors when HTTPS is available.
     require_https: bool,
+
+    /// Rate limits for requests per host.
+    host_pool: Arc<HostPool>,
+
+    /// Verify that fragments exist in the HTML body of the response.
+    include_fragments: bool,
+
+    /// Caches the fragments of downloaded HTML documents.
+    fragment_checker: FragmentChecker,
+
+    /// Verify that HTML pages are retrieved from their canonical URL.
+    check_canonical: bool,
+
+    /// Describe failed responses with HTML bodies by their title and first
+    /// heading.
+    error_page_snippet: bool,
+
+    /// Report the redirects followed by the requests.
+    track_redirects: bool,
+
+    /// Name of a query parameter with a unique value added to each request,
+    /// so that caches between lychee and the origin server are bypassed.
+    cache_bust_param: Option<String>,
+
+    /// Notified when requests are sent and retried.
+    observer: Option<Arc<dyn CheckObserver>>,
+
+    /// Values of the `Accept-Language` header. Each link is checked once per
+    /// language.
+    accept_languages: Vec<String>,
+
+    /// Renders the pages of some hosts in a headless browser instead of
+    /// sending a request.
+    render_js: Option<RenderJs>,
+
+    /// Requirements on the bodies of the responses of matching URLs.
+    content_assertions: Arc<Vec<ContentAssertion>>,
 }
 
 impl WebsiteChecker {
@@ -49,50 +149,343 @@ impl WebsiteChecker {
         method: reqwest::Method,
         retry_wait_time: Duration,
         max_retries: u64,
+        internal: InternalLinks,
         reqwest_client: reqwest::Client,
         accepted: Option<HashSet<StatusCode>>,
-        github_client: Option<Octocrab>,
+        platform_checkers: PlatformCheckers,
         require_https: bool,
         plugin_request_chain: RequestChain,
+        host_chain: RequestChain,
+        host_headers: HashMap<String, HeaderMap>,
+        host_pool: Arc<HostPool>,
+        include_fragments: bool,
+        fragment_checker: FragmentChecker,
+        check_canonical: bool,
+        error_page_snippet: bool,
+        track_redirects: bool,
+        cache_bust_param: Option<String>,
+        observer: Option<Arc<dyn CheckObserver>>,
+        accept_languages: Vec<String>,
+        render_js: Option<RenderJs>,
+        content_assertions: Arc<Vec<ContentAssertion>>,
     ) -> Self {
         Self {
             method,
             reqwest_client,
-            github_client,
+            platform_checkers,
             plugin_request_chain,
+            host_chain,
+            host_headers,
             max_retries,
+            internal,
             retry_wait_time,
             accepted,
             require_https,
+            host_pool,
+            include_fragments,
+            fragment_checker,
+            check_canonical,
+            error_page_snippet,
+            track_redirects,
+            cache_bust_param,
+            observer,
+            accept_languages,
+            render_js,
+            content_assertions,
         }
     }
 
-    /// Retry requests up to `max_retries` times
-    /// with an exponential backoff.
+    /// Whether the deadline of the run (if any) has passed
+    pub(crate) fn is_past_deadline(&self) -> bool {
+        self.host_pool.is_expired()
+    }
+
+    /// Whether `url` is an internal link, see [`InternalLinks`]
+    pub(crate) fn is_internal(&self, url: &reqwest::Url) -> bool {
+        self.internal.contains(url)
+    }
+
+    /// The current requests per host
+    pub(crate) fn host_activity(&self) -> Vec<HostActivity> {
+        self.host_pool.activity()
+    }
+
+    /// Send a `HEAD` request to `url` without any checks, rate limits or
+    /// retries, see [`Client::probe`](crate::Client::probe)
+    pub(crate) async fn probe(&self, url: reqwest::Url) -> Result<(), ErrorKind> {
+        self.reqwest_client
+            .head(url)
+            .send()
+            .await
+            .map_err(ErrorKind::NetworkRequest)?;
+        Ok(())
+    }
+
+    /// Start opening up to `connections` connections to the host of `url`
+    /// with `HEAD` requests to `url`, which is a link of the host that is
+    /// checked anyway. The connections are kept alive for the following
+    /// requests, which wait until they are open.
+    ///
+    /// The time it took is recorded separately from the time of the
+    /// requests. Returns `false` if the host is already warming up.
+    pub(crate) fn warm_up(&self, url: &reqwest::Url, connections: usize) -> bool {
+        let Some(warm_up) = url.host_str().and_then(|host| self.host_pool.warm_up(host)) else {
+            return false;
+        };
+        let client = self.reqwest_client.clone();
+        let url = url.clone();
+        tokio::spawn(async move {
+            let requests = (0..connections).map(|_| async {
+                let _permit = warm_up.acquire().await;
+                client.head(url.clone()).send().await.ok()
+            });
+            let opened = futures::future::join_all(requests)
+                .await
+                .into_iter()
+                .flatten()
+                .count();
+            if opened > 0 {
+                warm_up.finish();
+            }
+        });
+        true
+    }
+
+    /// Retry requests up to `max_retries` times (or the maximum number of
+    /// retries of internal links) with an exponential backoff.
     pub(crate) async fn retry_request(&self, request: Request) -> Status {
+        let max_retries = match self.internal.max_retries {
+            Some(max_retries) if self.is_internal(request.url()) => max_retries,
+            _ => self.max_retries,
+        };
         let mut retries: u64 = 0;
         let mut wait_time = self.retry_wait_time;
-        let mut status = self.check_default(clone_unwrap(&request)).await;
-        while retries < self.max_retries {
+        let mut status = self
+            .check_default(clone_unwrap(&request))
+            .instrument(tracing::debug_span!("request", attempt = 0))
+            .await;
+        while retries < max_retries {
             if status.is_success() || !status.should_retry() {
                 return status;
             }
             retries += 1;
+            tracing::debug!(attempt = retries, ?wait_time, %status, "Retrying request");
+            if let Some(observer) = &self.observer {
+                observer.on_event(&CheckEvent::Retry {
+                    uri: &Uri::from(request.url().clone()),
+                    attempt: retries,
+                    status: &status,
+                });
+            }
             tokio::time::sleep(wait_time).await;
+            // Report the last failure instead of skipping the link, if the
+            // deadline has passed in the meantime
+            if self.is_past_deadline() {
+                return status;
+            }
             wait_time = wait_time.saturating_mul(2);
-            status = self.check_default(clone_unwrap(&request)).await;
+            status = self
+                .check_default(clone_unwrap(&request))
+                .instrument(tracing::debug_span!("request", attempt = retries))
+                .await;
         }
         status
     }
 
+    /// Send `request`. If its host has headers of its own, these are removed
+    /// before a redirect to another host is followed.
+    async fn send(&self, request: Request) -> reqwest::Result<Response> {
+        let Some((host, headers)) = request
+            .url()
+            .host_str()
+            .and_then(|host| self.host_headers.get_key_value(host))
+        else {
+            return self.reqwest_client.execute(request).await;
+        };
+        let Some(mut next) = request.try_clone() else {
+            return self.reqwest_client.execute(request).await;
+        };
+        let response =
+            redirect::within_host(host.clone(), self.reqwest_client.execute(request)).await?;
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| response.url().join(location).ok());
+        let Some(location) = location.filter(|_| response.status().is_redirection()) else {
+            return Ok(response);
+        };
+        redirect::push(Redirect {
+            code: response.status(),
+            url: response.url().clone(),
+        });
+        for name in headers.keys() {
+            next.headers_mut().remove(name);
+        }
+        if response.status() == StatusCode::SEE_OTHER && next.method() != Method::HEAD {
+            *next.method_mut() = Method::GET;
+        }
+        *next.url_mut() = location;
+        self.reqwest_client.execute(next).await
+    }
+
     /// Check a URI using [reqwest](https://github.com/seanmonstar/reqwest).
-    async fn check_default(&self, request: Request) -> Status {
-        match self.reqwest_client.execute(request).await {
-            Ok(ref response) => Status::new(response, self.accepted.clone()),
+    ///
+    /// Waits for the rate limits of the host (if any) before sending the request.
+    async fn check_default(&self, mut request: Request) -> Status {
+        let permit = match request.url().host_str() {
+            Some(host) => match self.host_pool.acquire(host).await {
+                Some(permit) => Some(permit),
+                None => return Status::Skipped(SkipReason::Deadline),
+            },
+            None => None,
+        };
+        let is_get = request.method() == reqwest::Method::GET;
+        let check_fragment = self.include_fragments
+            && is_get
+            && request.url().fragment().is_some_and(|f| !f.is_empty());
+        let check_canonical = self.check_canonical && is_get;
+        let assertions: Vec<&ContentAssertion> = if is_get {
+            self.content_assertions
+                .iter()
+                .filter(|assertion| assertion.applies_to(request.url()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        if let Some(timeout) = self.internal.timeout {
+            if self.is_internal(request.url()) {
+                *request.timeout_mut() = Some(timeout);
+            }
+        }
+        let url = request.url().clone();
+        if let Some(observer) = &self.observer {
+            observer.on_event(&CheckEvent::CheckStarted(&Uri::from(url.clone())));
+        }
+        if let Some(name) = &self.cache_bust_param {
+            request
+                .url_mut()
+                .query_pairs_mut()
+                .append_pair(name, &cache_bust_value());
+        }
+        let sent = Instant::now();
+        let (result, mut redirects) = redirect::track(self.send(request)).await;
+        if let Some(permit) = &permit {
+            permit.record_request(sent.elapsed());
+        }
+        for redirect in &mut redirects {
+            redirect.url = self.without_cache_bust(redirect.url.clone());
+        }
+        let redirects = self.track_redirects.then_some(redirects);
+        match result {
+            Ok(response) => {
+                let mut status = Status::new(&response, self.accepted.clone());
+                if let (&Status::Ok(code), Some(mut redirects)) = (&status, redirects) {
+                    if code.is_success() && !redirects.is_empty() {
+                        redirects.push(Redirect {
+                            code,
+                            url: self.without_cache_bust(response.url().clone()),
+                        });
+                        status = Status::Redirected(code, Redirects::new(redirects));
+                    }
+                }
+                let check_html = (check_fragment || check_canonical) && is_html(&response);
+                if (check_html || !assertions.is_empty()) && status.is_success() {
+                    return self
+                        .check_body(
+                            &url,
+                            response,
+                            status,
+                            check_html && check_fragment,
+                            check_html && check_canonical,
+                            &assertions,
+                        )
+                        .await;
+                }
+                if self.error_page_snippet && status.is_error() && is_html(&response) {
+                    return describe_error_page(status, response).await;
+                }
+                status
+            }
             Err(e) => e.into(),
         }
     }
 
+    /// Remove the cache-busting query parameter (if any) from `url`, e.g. from
+    /// the URL of a redirect which kept the query of the request.
+    ///
+    /// The other parameters are kept as they are, so that their encoding
+    /// doesn't change.
+    fn without_cache_bust(&self, mut url: reqwest::Url) -> reqwest::Url {
+        let (Some(name), Some(query)) = (&self.cache_bust_param, url.query()) else {
+            return url;
+        };
+        let query = query
+            .split('&')
+            .filter(|pair| {
+                !url::form_urlencoded::parse(pair.as_bytes())
+                    .next()
+                    .is_some_and(|(key, _)| key == name.as_str())
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+        url.set_query((!query.is_empty()).then_some(query.as_str()));
+        url
+    }
+
+    /// Download the body of `response` and check that it passes the content
+    /// `assertions`, that it contains the fragment of `url` and that it was
+    /// retrieved from its canonical URL, if requested.
+    ///
+    /// Returns `status` if the checks pass, if the fragments can't be
+    /// extracted or if the body is larger than [`BODY_LIMIT`].
+    async fn check_body(
+        &self,
+        url: &reqwest::Url,
+        response: Response,
+        status: Status,
+        check_fragment: bool,
+        check_canonical: bool,
+        assertions: &[&ContentAssertion],
+    ) -> Status {
+        let final_url = self.without_cache_bust(response.url().clone());
+        let content = match read_body(response, BODY_LIMIT).await {
+            Ok(Some(content)) => content,
+            Ok(None) => {
+                warn!(
+                    "Skipping the checks of the content of {url}, which is larger than {} MiB",
+                    BODY_LIMIT / 1024 / 1024
+                );
+                return status;
+            }
+            Err(e) => return e.into(),
+        };
+        if let Some(reason) = assertions
+            .iter()
+            .find_map(|assertion| assertion.check(&content))
+        {
+            return match status.code() {
+                Some(code) => ErrorKind::ContentAssertion(code, reason).into(),
+                None => status,
+            };
+        }
+        if check_fragment {
+            match self.fragment_checker.check_html(url, &content).await {
+                Ok(true) => {}
+                Ok(false) => return ErrorKind::InvalidFragment(Uri::from(url.clone())).into(),
+                Err(err) => {
+                    warn!("Skipping fragment check due to the following error: {err}");
+                }
+            }
+        }
+        if check_canonical {
+            if let Some(status) = canonical_mismatch(&final_url, &content, &status) {
+                return status;
+            }
+        }
+        status
+    }
+
     /// Checks the given URI of a website.
     ///
     /// # Errors
@@ -110,11 +503,14 @@ impl WebsiteChecker {
         let default_chain: RequestChain = Chain::new(vec![
             Box::<Quirks>::default(),
             Box::new(credentials),
+            Box::new(self.render_js.clone()),
             Box::new(self.clone()),
         ]);
 
         match self.check_website_inner(uri, &default_chain).await {
-            Status::Ok(code) if self.require_https && uri.scheme() == "http" => {
+            status @ (Status::Ok(_) | Status::Redirected(..))
+                if status.is_success() && self.require_https && uri.scheme() == "http" =>
+            {
                 if self
                     .check_website_inner(&uri.to_https()?, &default_chain)
                     .await
@@ -122,7 +518,7 @@ impl WebsiteChecker {
                 {
                     Ok(Status::Error(ErrorKind::InsecureURL(uri.to_https()?)))
                 } else {
-                    Ok(Status::Ok(code))
+                    Ok(status)
                 }
             }
             s => Ok(s),
@@ -142,67 +538,201 @@ impl WebsiteChecker {
     /// - The request failed.
     /// - The response status code is not accepted.
     async fn check_website_inner(&self, uri: &Uri, default_chain: &RequestChain) -> Status {
-        let request = self
+        let [first, others @ ..] = self.accept_languages.as_slice() else {
+            return self.check_website_language(uri, default_chain, None).await;
+        };
+        let status = self
+            .check_website_language(uri, default_chain, Some(first))
+            .await;
+        if others.is_empty() {
+            return status;
+        }
+
+        let mut failures = Vec::new();
+        if status.is_error() {
+            failures.push((first.clone(), status.code_as_string()));
+        }
+        for language in others {
+            let other = self
+                .check_website_language(uri, default_chain, Some(language))
+                .await;
+            if other.is_error() {
+                failures.push((language.clone(), other.code_as_string()));
+            }
+        }
+        // Links which are broken in every language are reported as usual
+        if failures.is_empty() || failures.len() == self.accept_languages.len() {
+            status
+        } else {
+            ErrorKind::LanguageFailures(failures).into()
+        }
+    }
+
+    /// Checks the given URI of a website with the `Accept-Language` header
+    /// set to `language` (if any).
+    async fn check_website_language(
+        &self,
+        uri: &Uri,
+        default_chain: &RequestChain,
+        language: Option<&str>,
+    ) -> Status {
+        let mut request = self
             .reqwest_client
-            .request(self.method.clone(), uri.as_str())
-            .build();
+            .request(self.method.clone(), uri.as_str());
+        if let Some(language) = language {
+            request = request.header(ACCEPT_LANGUAGE, language);
+        }
+        let request = request.build();
 
         let request = match request {
             Ok(r) => r,
             Err(e) => return e.into(),
         };
 
-        let status = ClientRequestChains::new(vec![&self.plugin_request_chain, default_chain])
-            .traverse(request)
-            .await;
+        let status = ClientRequestChains::new(vec![
+            &self.plugin_request_chain,
+            &self.host_chain,
+            default_chain,
+        ])
+        .traverse(request)
+        .await;
 
-        self.handle_github(status, uri).await
+        self.handle_platform(status, uri).await
     }
 
     // Pull out the heavy machinery in case of a failed normal request.
-    // This could be a GitHub URL and we ran into the rate limiter.
-    // TODO: We should try to parse the URI as GitHub URI first (Lucius, Jan 2023)
-    async fn handle_github(&self, status: Status, uri: &Uri) -> Status {
+    // This could be a link into a private repository on GitHub or another
+    // code hosting platform, or we ran into the rate limiter.
+    async fn handle_platform(&self, status: Status, uri: &Uri) -> Status {
         if status.is_success() {
             return status;
         }
 
-        if let Ok(github_uri) = GithubUri::try_from(uri) {
-            let status = self.check_github(github_uri).await;
-            if status.is_success() {
-                return status;
+        if let Some(checker) = self.platform_checkers.get(uri) {
+            if let Some(platform_status) = checker.check(&self.reqwest_client, uri).await {
+                if platform_status.is_success() {
+                    return platform_status;
+                }
             }
         }
 
         status
     }
+}
 
-    /// Check a `uri` hosted on `GitHub` via the GitHub API.
-    ///
-    /// # Caveats
-    ///
-    /// Files inside private repositories won't get checked and instead would
-    /// be reported as valid if the repository itself is reachable through the
-    /// API.
-    ///
-    /// A better approach would be to download the file through the API or
-    /// clone the repo, but we chose the pragmatic approach.
-    async fn check_github(&self, uri: GithubUri) -> Status {
-        let Some(client) = &self.github_client else {
-            return ErrorKind::MissingGitHubToken.into();
-        };
-        let repo = match client.repos(&uri.owner, &uri.repo).get().await {
-            Ok(repo) => repo,
-            Err(e) => return ErrorKind::GithubRequest(Box::new(e)).into(),
-        };
-        if let Some(true) = repo.private {
-            return Status::Ok(StatusCode::OK);
-        } else if let Some(endpoint) = uri.endpoint {
-            return ErrorKind::InvalidGithubUrl(format!("{}/{}/{endpoint}", uri.owner, uri.repo))
-                .into();
+/// Returns [`Status::CanonicalMismatch`] if the HTML `content` retrieved from
+/// `url` declares a different canonical URL. Fragments are ignored.
+pub(super) fn canonical_mismatch(
+    url: &reqwest::Url,
+    content: &str,
+    status: &Status,
+) -> Option<Status> {
+    let canonical = extract_html_canonical(content)?;
+    let Ok(mut canonical) = url.join(canonical.trim()) else {
+        warn!("Skipping canonical check of {url} due to the invalid canonical URL `{canonical}`");
+        return None;
+    };
+    canonical.set_fragment(None);
+    let mut url = url.clone();
+    url.set_fragment(None);
+    if url == canonical {
+        return None;
+    }
+    Some(Status::CanonicalMismatch {
+        code: status.code()?,
+        url: Box::new(url),
+        canonical: Box::new(canonical),
+    })
+}
+
+/// Read the body of `response` as text, unless it is larger than `limit`
+/// bytes. Then `None` is returned and the rest of the body isn't downloaded.
+async fn read_body(mut response: Response, limit: usize) -> reqwest::Result<Option<String>> {
+    if response
+        .content_length()
+        .is_some_and(|length| length > limit as u64)
+    {
+        return Ok(None);
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > limit {
+            return Ok(None);
         }
-        Status::Ok(StatusCode::OK)
+        body.extend_from_slice(&chunk);
     }
+    Ok(Some(String::from_utf8_lossy(&body).into_owned()))
+}
+
+/// Add the title and first heading of the HTML error page in `response` to
+/// the details of the failed `status`, e.g. to tell a page blocked by a web
+/// application firewall from a genuine 404.
+///
+/// At most [`ERROR_PAGE_LIMIT`] bytes of the page are downloaded.
+async fn describe_error_page(status: Status, mut response: Response) -> Status {
+    let Status::Error(ErrorKind::NetworkRequest(error)) = status else {
+        return status;
+    };
+    let mut body = Vec::new();
+    while body.len() < ERROR_PAGE_LIMIT {
+        match response.chunk().await {
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+            Ok(None) | Err(_) => break,
+        }
+    }
+    body.truncate(ERROR_PAGE_LIMIT);
+    match error_page_snippet(&String::from_utf8_lossy(&body)) {
+        Some(snippet) => ErrorKind::ErrorPage(error, snippet).into(),
+        None => ErrorKind::NetworkRequest(error).into(),
+    }
+}
+
+/// The title and first heading of an HTML error page, joined by ` – ` and
+/// shortened to [`ERROR_PAGE_SNIPPET_LENGTH`] characters. The heading is
+/// left out if it is part of the title.
+fn error_page_snippet(content: &str) -> Option<String> {
+    let (title, heading) = extract_html_title_and_heading(content);
+    let snippet = match (title, heading) {
+        (Some(title), Some(heading)) if !title.contains(&heading) => {
+            format!("{heading} – {title}")
+        }
+        (Some(title), _) => title,
+        (None, Some(heading)) => heading,
+        (None, None) => return None,
+    };
+    if snippet.chars().count() <= ERROR_PAGE_SNIPPET_LENGTH {
+        return Some(snippet);
+    }
+    let mut shortened: String = snippet
+        .chars()
+        .take(ERROR_PAGE_SNIPPET_LENGTH - 1)
+        .collect();
+    shortened.push('…');
+    Some(shortened)
+}
+
+/// A value which is unique for each request, so that no cache has a response
+/// for the URL.
+fn cache_bust_value() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    format!("{nanos:x}{:x}", COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Returns `true` if the response declares an HTML content type.
+fn is_html(response: &Response) -> bool {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            let mime = value.split(';').next().unwrap_or_default().trim();
+            mime.eq_ignore_ascii_case("text/html")
+                || mime.eq_ignore_ascii_case("application/xhtml+xml")
+        })
 }
 
 /// Clones a `reqwest::Request`.
@@ -224,3 +754,35 @@ impl Handler<Request, Status> for WebsiteChecker {
         ChainResult::Done(self.retry_request(input).await)
     }
 }
+
+#[async_trait]
+impl Checker for WebsiteChecker {
+    async fn check(
+        &self,
+        uri: &Uri,
+        credentials: Option<BasicAuthCredentials>,
+    ) -> Result<Status, ErrorKind> {
+        self.check_website(uri, credentials).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::read_body;
+
+    #[tokio::test]
+    async fn test_read_body_limit() {
+        let mock_server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::any())
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string("0123456789"))
+            .mount(&mock_server)
+            .await;
+
+        let get = || async { reqwest::get(mock_server.uri()).await.unwrap() };
+        assert_eq!(
+            read_body(get().await, 10).await.unwrap(),
+            Some("0123456789".to_string())
+        );
+        assert_eq!(read_body(get().await, 9).await.unwrap(), None);
+    }
+}
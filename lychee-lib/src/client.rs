@@ -13,7 +13,13 @@
     clippy::default_trait_access,
     clippy::used_underscore_binding
 )]
-use std::{collections::HashSet, path::Path, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use http::{
     header::{HeaderMap, HeaderValue},
@@ -21,20 +27,36 @@ use http::{
 };
 use log::{debug, warn};
 use octocrab::Octocrab;
-use regex::RegexSet;
+use regex::{Regex, RegexSet};
 use reqwest::{header, redirect};
 use reqwest_cookie_store::CookieStoreMutex;
 use secrecy::{ExposeSecret, SecretString};
+use tracing::Instrument;
 use typed_builder::TypedBuilder;
 
 use crate::{
-    chain::RequestChain,
+    auth::{OAuth2Config, OAuth2Handler},
+    chain::{ChainBuilder, InjectHeaders, RequestChain},
     checker::file::FileChecker,
-    checker::{mail::MailChecker, website::WebsiteChecker},
-    filter::{Excludes, Filter, Includes},
+    checker::{
+        mail::MailChecker,
+        platform::{GithubChecker, GitlabChecker, PlatformChecker, PlatformCheckers},
+        render::{BrowserOptions, RenderJs},
+        tel::TelChecker,
+        website::{InternalLinks, WebsiteChecker},
+        Checker,
+    },
+    filter::{Excludes, Filter, Includes, LycheeIgnore, VersionedDocs},
+    observer::{CheckEvent, CheckObserver},
+    path_mapping::PathMappings,
+    proxy_pac::ProxyPac,
+    ratelimit::{HostActivity, HostConfig, HostPool},
     remap::Remaps,
+    types::{self, uri::gitlab::DEFAULT_GITLAB_HOST},
     utils::fragment_checker::FragmentChecker,
-    Base, BasicAuthCredentials, ErrorKind, Request, Response, Result, Status, Uri,
+    AnchorCache, Base, BasicAuthCredentials, ContentAssertion, ErrorKind, FragmentStyle,
+    HttpVersion, InputSource, MailCheckLevel, PlatformConfig, Request, Response, Result, Status,
+    TlsVersion, Uri, UriKind,
 };
 
 /// Default number of redirects before a request is deemed as failed, 5.
@@ -45,6 +67,10 @@ pub const DEFAULT_MAX_RETRIES: u64 = 3;
 pub const DEFAULT_RETRY_WAIT_TIME_SECS: usize = 1;
 /// Default timeout in seconds before a request is deemed as failed, 20.
 pub const DEFAULT_TIMEOUT_SECS: usize = 20;
+/// Default timeout in seconds for a single MX lookup of a mail domain, 5.
+pub const DEFAULT_MAIL_DNS_TIMEOUT_SECS: usize = 5;
+/// Default number of retries of timed out MX lookups, 1.
+pub const DEFAULT_MAIL_DNS_MAX_RETRIES: u64 = 1;
 /// Default user agent, `lychee-<PKG_VERSION>`.
 pub const DEFAULT_USER_AGENT: &str = concat!("lychee/", env!("CARGO_PKG_VERSION"));
 
@@ -73,6 +99,42 @@ pub struct ClientBuilder {
     /// 5000 per hour with token.
     github_token: Option<SecretString>,
 
+    /// Optional GitLab token used for links to GitLab instances.
+    ///
+    /// Links which fail with a normal request (e.g. into private projects or
+    /// because of rate limiting) are checked via the GitLab REST API instead.
+    /// The token needs the `read_api` scope.
+    gitlab_token: Option<SecretString>,
+
+    /// Hosts of self-hosted GitLab instances, e.g. `gitlab.example.com`.
+    ///
+    /// Links to `gitlab.com` are always checked via the GitLab API if a
+    /// [`gitlab_token`](ClientBuilder::gitlab_token) is given.
+    gitlab_hosts: Vec<String>,
+
+    /// Fetch an access token with the OAuth2 client credentials flow and
+    /// send it in requests to the hosts of the configuration.
+    ///
+    /// The token is refreshed once it expires. It is only sent in https
+    /// requests.
+    oauth2: Option<OAuth2Config>,
+
+    /// Code hosting platforms of specific hosts and their API tokens, e.g.
+    /// for self-hosted Gitea instances.
+    ///
+    /// Links to these hosts which fail with a normal request are checked via
+    /// the API of the platform. `bitbucket.org` and `codeberg.org` are
+    /// always checked via their APIs, but need an entry with a token for
+    /// links into private repositories.
+    platforms: HashMap<String, PlatformConfig>,
+
+    /// Custom checkers of code hosting platforms by host, e.g. for
+    /// platforms which lychee doesn't support.
+    ///
+    /// They replace the checkers of [`platforms`](ClientBuilder::platforms)
+    /// and of the built-in hosts.
+    platform_checkers: HashMap<String, Arc<dyn PlatformChecker>>,
+
     /// Remap URIs matching a pattern to a different URI.
     ///
     /// This makes it possible to remap any HTTP/HTTPS endpoint to a different
@@ -92,6 +154,17 @@ pub struct ClientBuilder {
     /// Automatically append file extensions to `file://` URIs as needed
     fallback_extensions: Vec<String>,
 
+    /// Check links to the pages of a static site generator (e.g.
+    /// `/guide/install/`) against the source files of the pages (e.g.
+    /// `docs/guide/install.md`), before the site is built.
+    path_mappings: Option<PathMappings>,
+
+    /// Proxy auto-config (PAC) file, which selects the proxy for each request.
+    ///
+    /// This replaces the proxies of the environment (e.g. `HTTPS_PROXY`).
+    /// See [`proxy_pac`](crate::proxy_pac) for the supported PAC files.
+    proxy_pac: Option<ProxyPac>,
+
     /// Links matching this set of regular expressions are **always** checked.
     ///
     /// This has higher precedence over [`ClientBuilder::excludes`], **but**
@@ -180,18 +253,160 @@ pub struct ClientBuilder {
     /// When `true`, check mail addresses.
     include_mail: bool,
 
+    /// How thoroughly mail addresses are checked.
+    ///
+    /// Defaults to [`MailCheckLevel::Mx`], which checks that the domain of
+    /// the address accepts mail. [`MailCheckLevel::Smtp`] also asks the mail
+    /// server whether the mailbox exists, which is slow and blocked by many
+    /// mail servers.
+    mail_check_level: MailCheckLevel,
+
+    /// When `true`, check the syntax of phone numbers in `tel:` links.
+    ///
+    /// Numbers have to be in E.164 format, e.g. `tel:+1-555-0100`. Malformed
+    /// numbers are reported as errors. Otherwise, `tel:` links are excluded.
+    include_tel: bool,
+
+    /// Patterns of valid phone numbers by country calling code, e.g. `49`
+    /// (see [`ClientBuilder::include_tel`]).
+    ///
+    /// The digits of numbers with the calling code, including the calling
+    /// code but without `+` and separators, have to match the whole pattern.
+    tel_patterns: HashMap<String, Regex>,
+
+    /// Timeout for a single MX lookup when checking mail addresses.
+    ///
+    /// Defaults to [`DEFAULT_MAIL_DNS_TIMEOUT_SECS`].
+    ///
+    /// # Notes
+    ///
+    /// This is independent of [`ClientBuilder::timeout`], because slow DNS
+    /// resolvers would otherwise stall the checks of all mail addresses.
+    #[builder(default_code = "Duration::from_secs(DEFAULT_MAIL_DNS_TIMEOUT_SECS as u64)")]
+    mail_dns_timeout: Duration,
+
+    /// Maximum number of retries of timed out MX lookups.
+    ///
+    /// Defaults to [`DEFAULT_MAIL_DNS_MAX_RETRIES`].
+    #[builder(default = DEFAULT_MAIL_DNS_MAX_RETRIES)]
+    mail_dns_max_retries: u64,
+
+    /// Rate limits for requests to specific hosts, e.g. `github.com`.
+    ///
+    /// Requests to other hosts are not limited.
+    host_configs: HashMap<String, HostConfig>,
+
+    /// Additional headers which are only sent in requests to specific hosts,
+    /// e.g. credentials for an API.
+    ///
+    /// They replace the [`ClientBuilder::custom_headers`] with the same
+    /// names. They are dropped once a redirect leads to another host.
+    host_headers: HashMap<String, HeaderMap>,
+
+    /// Patterns of `.lycheeignore` files in subdirectories, which only
+    /// exclude links found in inputs inside of these directories.
+    ///
+    /// See [`LycheeIgnore::discover`].
+    lycheeignore: LycheeIgnore,
+
+    /// Skip links into versions of versioned documentation other than the
+    /// canonical one, e.g. `/en/stable/` when `latest` is checked.
+    versioned_docs: Option<VersionedDocs>,
+
+    /// Resolve these hosts to fixed addresses instead of asking DNS.
+    ///
+    /// This allows checking links to staging servers whose domains are not
+    /// live yet, or to split-horizon DNS names, without editing `/etc/hosts`.
+    ///
+    /// # Notes
+    ///
+    /// Unlike the `--resolve` option of curl, an override applies to all
+    /// ports of the host. The port of the address is ignored and the port of
+    /// the URL is used instead.
+    dns_overrides: HashMap<String, SocketAddr>,
+
+    /// HTTP version used for requests.
+    ///
+    /// Defaults to [`HttpVersion::Auto`], which negotiates the version with
+    /// the server. Some endpoints (e.g. gRPC gateways or CDNs) behave
+    /// differently over HTTP/1.1 and HTTP/2.
+    http_version: HttpVersion,
+
     /// Maximum number of redirects per request before returning an error.
     ///
     /// Defaults to [`DEFAULT_MAX_REDIRECTS`].
     #[builder(default = DEFAULT_MAX_REDIRECTS)]
     max_redirects: usize,
 
+    /// Record the redirects followed by each request.
+    ///
+    /// Requests which are redirected to a successful response are reported
+    /// as [`Status::Redirected`] with the redirect chain, e.g.
+    /// `301 a -> 302 b -> 200 c`, instead of [`Status::Ok`].
+    track_redirects: bool,
+
+    /// Name of a query parameter which is added to each request with a
+    /// unique value, e.g. `?lychee-cache-bust=18c6e3f4a2d1`.
+    ///
+    /// A stale copy in a CDN or proxy cache can hide that the origin server
+    /// fails. Most caches key responses by the full URL, so the request
+    /// with a unique query bypasses them. The parameter is not part of the
+    /// reported redirects and canonical URLs.
+    cache_bust_param: Option<String>,
+
+    /// Values of the `Accept-Language` header, e.g. `["en", "de"]`.
+    ///
+    /// With more than one language, websites are checked once per language.
+    /// Links which only fail with some of them (e.g. a missing translation)
+    /// are reported as [`ErrorKind::LanguageFailures`].
+    accept_languages: Vec<String>,
+
     /// Maximum number of retries per request before returning an error.
     ///
     /// Defaults to [`DEFAULT_MAX_RETRIES`].
     #[builder(default = DEFAULT_MAX_RETRIES)]
     max_retries: u64,
 
+    /// Hosts of the checked site, e.g. `example.com`.
+    ///
+    /// Links to these hosts and to local files are internal. Hosts are
+    /// compared case-insensitively, subdomains are not included.
+    /// Requests to internal and external hosts are scheduled in separate
+    /// lanes of the [`HostPool`] (see
+    /// [`ClientBuilder::internal_max_concurrency`]), so that internal links
+    /// are not starved by slow external hosts. [`Client::is_internal`] tells
+    /// internal and external links apart.
+    internal_hosts: Vec<String>,
+
+    /// Maximum number of concurrent requests to all internal hosts (see
+    /// [`ClientBuilder::internal_hosts`]) together.
+    ///
+    /// Unlimited by default.
+    internal_max_concurrency: Option<usize>,
+
+    /// Maximum number of concurrent requests to all external hosts, i.e.
+    /// hosts other than the [`ClientBuilder::internal_hosts`], together.
+    ///
+    /// Unlimited by default.
+    external_max_concurrency: Option<usize>,
+
+    /// Timeout of requests to internal links (see
+    /// [`ClientBuilder::internal_hosts`]) instead of [`ClientBuilder::timeout`].
+    internal_timeout: Option<Duration>,
+
+    /// Maximum number of retries of internal links (see
+    /// [`ClientBuilder::internal_hosts`]) instead of
+    /// [`ClientBuilder::max_retries`].
+    internal_max_retries: Option<u64>,
+
+    /// Point in time after which no more requests are sent.
+    ///
+    /// Links which are checked afterwards, or which are still waiting for
+    /// the rate limits of their host, are reported as
+    /// [`Status::Skipped`] and failed requests are not retried anymore.
+    /// Requests which were already sent are completed.
+    deadline: Option<Instant>,
+
     /// User-agent used for checking links.
     ///
     /// Defaults to [`DEFAULT_USER_AGENT`].
@@ -215,12 +430,38 @@ pub struct ClientBuilder {
     // TODO: We should add a warning message in CLI. (Lucius, Jan 2023)
     allow_insecure: bool,
 
+    /// Minimum TLS version of HTTPS connections.
+    ///
+    /// Connections to servers which only support older versions fail.
+    /// Defaults to the minimum version of the TLS backend.
+    min_tls_version: Option<TlsVersion>,
+
+    /// Paths of additional root certificates (PEM files, which may contain
+    /// several certificates) to trust besides the system ones.
+    ///
+    /// This allows checking internal HTTPS endpoints signed by a private
+    /// certificate authority without [`allow_insecure`](Self::allow_insecure).
+    #[builder(via_mutators, mutators(
+        /// Trust the root certificates of the PEM file at `path` besides the
+        /// system ones. Can be called several times.
+        pub fn add_root_certificate(&mut self, path: impl Into<PathBuf>) {
+            self.root_certificates.push(path.into());
+        }
+    ))]
+    root_certificates: Vec<PathBuf>,
+
     /// Set of accepted URL schemes.
     ///
     /// Only links with matched URI schemes are checked. This has no effect when
     /// it's empty.
     schemes: HashSet<String>,
 
+    /// Set of denied URL schemes.
+    ///
+    /// Links with these schemes are never checked, like `data:` and
+    /// `javascript:` links. They are reported as excluded with the reason.
+    denied_schemes: HashSet<String>,
+
     /// Default [headers] for every request.
     ///
     /// This allows working around validation issues on some websites. See also
@@ -276,13 +517,67 @@ pub struct ClientBuilder {
     cookie_jar: Option<Arc<CookieStoreMutex>>,
 
     /// Enable the checking of fragments in links.
+    ///
+    /// For websites, the HTML body of the response is downloaded and searched
+    /// for an element with a matching `id` (or `name` for `<a>` elements).
     include_fragments: bool,
 
+    /// Enable the checking of canonical URLs of websites.
+    ///
+    /// The HTML body of the response is downloaded and the URL the page was
+    /// retrieved from (after redirects) is compared to its canonical URL
+    /// (`<link rel="canonical">`). Mismatches are reported as
+    /// [`Status::CanonicalMismatch`].
+    check_canonical: bool,
+
+    /// Hosts whose pages are rendered in a headless browser before their
+    /// fragments and canonical URL are checked, e.g. single-page applications
+    /// which create their content with JavaScript.
+    ///
+    /// Requires the `render-js` feature and Chrome or Chromium. Pages which
+    /// can't be rendered are checked with a normal request.
+    render_js_hosts: Vec<String>,
+
+    /// Requirements on the bodies of the responses of matching URLs, e.g. to
+    /// detect "Page not found" pages which are served with a successful
+    /// status code.
+    ///
+    /// The body of each successful `GET` response of a matching URL is
+    /// downloaded. Failed assertions are reported as
+    /// [`ErrorKind::ContentAssertion`].
+    content_assertions: Vec<ContentAssertion>,
+
+    /// Describe failed responses with HTML bodies by the title and the first
+    /// heading of the page, e.g. `Not Found – Page not found – Acme Docs`.
+    ///
+    /// This helps to tell error pages of web application firewalls from
+    /// genuine errors. At most 64 KiB of each error page are downloaded. The
+    /// description is part of the details of [`ErrorKind::ErrorPage`].
+    error_page_snippet: bool,
+
+    /// How the fragments of Markdown headings are generated when checking
+    /// fragments of local files.
+    ///
+    /// Defaults to [`FragmentStyle::Github`].
+    fragment_style: FragmentStyle,
+
+    /// Cache of the fragments of local files, which is shared across runs.
+    ///
+    /// Unchanged files are not parsed again when checking fragments. See
+    /// [`AnchorCache`] for loading and storing the cache.
+    anchor_cache: Option<AnchorCache>,
+
     /// Requests run through this chain where each item in the chain
     /// can modify the request. A chained item can also decide to exit
     /// early and return a status, so that subsequent chain items are
     /// skipped and the lychee-internal request chain is not activated.
     plugin_request_chain: RequestChain,
+
+    /// Notified of the progress of each check, e.g. to report it while
+    /// the client is used.
+    ///
+    /// See [`CheckObserver`].
+    observer: Option<Arc<dyn CheckObserver>>,
 }
 
 impl Default for ClientBuilder {
@@ -302,10 +597,14 @@ impl ClientBuilder {
     /// - The user-agent contains characters other than ASCII 32-127.
     /// - The reqwest client cannot be instantiated. This occurs if a TLS
     ///   backend cannot be initialized or the resolver fails to load the system
-    ///   configuration. See [here].
+    ///   configuration. See [here]. This also occurs if the TLS backend does
+    ///   not support the minimum TLS version.
+    /// - A root certificate cannot be read or contains no certificates.
     /// - The GitHub client cannot be created. Since the implementation also
     ///   uses reqwest under the hood, this errors in the same circumstances as
     ///   the last one.
+    /// - The token URL of the [`ClientBuilder::oauth2`] configuration doesn't
+    ///   use https.
     ///
     /// [here]: https://docs.rs/reqwest/latest/reqwest/struct.ClientBuilder.html#errors
     pub fn client(self) -> Result<Client> {
@@ -329,17 +628,32 @@ impl ClientBuilder {
             HeaderValue::from_static("chunked"),
         );
 
-        // Custom redirect policy to enable logging of redirects.
+        // Custom redirect policy to enable logging (and tracking) of redirects.
         let max_redirects = self.max_redirects;
+        let track_redirects = self.track_redirects;
         let redirect_policy = redirect::Policy::custom(move |attempt| {
-            if attempt.previous().len() > max_redirects {
+            if types::redirect::is_stopped(&attempt) {
+                attempt.stop()
+            } else if attempt.previous().len() > max_redirects {
                 attempt.error("too many redirects")
             } else {
                 debug!("Redirecting to {}", attempt.url());
+                if track_redirects {
+                    types::redirect::record(&attempt);
+                }
                 attempt.follow()
             }
         });
 
+        // The browser of `render_js_hosts` connects like the client
+        let mut browser_options = BrowserOptions {
+            headers: headers.clone(),
+            cookie_jar: self.cookie_jar.clone(),
+            proxy_pac: self.proxy_pac.clone(),
+            allow_insecure: self.allow_insecure,
+            verify_with: None,
+        };
+
         let mut builder = reqwest::ClientBuilder::new()
             .gzip(true)
             .default_headers(headers)
@@ -348,10 +662,30 @@ impl ClientBuilder {
             .tcp_keepalive(Duration::from_secs(TCP_KEEPALIVE))
             .redirect(redirect_policy);
 
+        builder = self.http_version.apply(builder);
+
+        if let Some(version) = self.min_tls_version {
+            builder = builder.min_tls_version(version.to_reqwest());
+        }
+
+        for path in &self.root_certificates {
+            for certificate in read_root_certificates(path)? {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        for (host, addr) in &self.dns_overrides {
+            builder = builder.resolve(host, *addr);
+        }
+
         if let Some(cookie_jar) = self.cookie_jar {
             builder = builder.cookie_provider(cookie_jar);
         }
 
+        if let Some(pac) = self.proxy_pac {
+            builder = builder.proxy(reqwest::Proxy::custom(move |url| pac.find_proxy(url)));
+        }
+
         let reqwest_client = match self.timeout {
             Some(t) => builder.timeout(t),
             None => builder,
@@ -359,56 +693,222 @@ impl ClientBuilder {
         .build()
         .map_err(ErrorKind::NetworkRequest)?;
 
-        let github_client = match self.github_token.as_ref().map(ExposeSecret::expose_secret) {
-            Some(token) if !token.is_empty() => Some(
-                Octocrab::builder()
+        if !self.root_certificates.is_empty() {
+            browser_options.verify_with = Some(reqwest_client.clone());
+        }
+
+        let mut platform_checkers = PlatformCheckers::default();
+        for (host, platform) in PlatformCheckers::DEFAULT_HOSTS {
+            platform_checkers.insert_config(host, PlatformConfig::new(platform));
+        }
+
+        if let Some(token) = self.github_token.as_ref().map(ExposeSecret::expose_secret) {
+            if !token.is_empty() {
+                let client = Octocrab::builder()
                     .personal_token(token.to_string())
                     .build()
                     // this is essentially the same `reqwest::ClientBuilder::build` error
                     // see https://docs.rs/octocrab/0.18.1/src/octocrab/lib.rs.html#360-364
-                    .map_err(|e: octocrab::Error| ErrorKind::BuildGithubClient(Box::new(e)))?,
-            ),
-            _ => None,
-        };
+                    .map_err(|e: octocrab::Error| ErrorKind::BuildGithubClient(Box::new(e)))?;
+                let checker: Arc<dyn PlatformChecker> = Arc::new(GithubChecker::new(client));
+                for host in GithubChecker::HOSTS {
+                    platform_checkers.insert(host, checker.clone());
+                }
+            }
+        }
+
+        if let Some(token) = self.gitlab_token {
+            if !token.expose_secret().is_empty() {
+                let checker: Arc<dyn PlatformChecker> = Arc::new(GitlabChecker::new(Some(token)));
+                for host in
+                    std::iter::once(DEFAULT_GITLAB_HOST.to_string()).chain(self.gitlab_hosts)
+                {
+                    platform_checkers.insert(host, checker.clone());
+                }
+            }
+        }
+
+        for (host, config) in self.platforms {
+            platform_checkers.insert_config(host, config);
+        }
+        for (host, checker) in self.platform_checkers {
+            platform_checkers.insert(host, checker);
+        }
 
         let filter = Filter {
             includes: self.includes.map(|regex| Includes { regex }),
             excludes: self.excludes.map(|regex| Excludes { regex }),
             schemes: self.schemes,
+            denied_schemes: self.denied_schemes,
             // exclude_all_private option turns on all "private" excludes,
             // including private IPs, link-local IPs and loopback IPs
             exclude_private_ips: self.exclude_all_private || self.exclude_private_ips,
             exclude_link_local_ips: self.exclude_all_private || self.exclude_link_local_ips,
             exclude_loopback_ips: self.exclude_all_private || self.exclude_loopback_ips,
             include_mail: self.include_mail,
+            include_tel: self.include_tel,
+            lycheeignore: self.lycheeignore,
+            versioned_docs: self.versioned_docs,
         };
 
+        let host_headers: HashMap<String, HeaderMap> = self
+            .host_headers
+            .into_iter()
+            .filter(|(_, headers)| !headers.is_empty())
+            .map(|(host, headers)| (host.to_lowercase(), headers))
+            .collect();
+        let mut host_chain =
+            host_headers
+                .iter()
+                .fold(ChainBuilder::new(), |chain, (host, headers)| {
+                    let host = host.clone();
+                    chain.when(
+                        move |request: &reqwest::Request| {
+                            request.url().host_str() == Some(host.as_str())
+                        },
+                        InjectHeaders::new(headers.clone()),
+                    )
+                });
+        if let Some(config) = self.oauth2 {
+            if config.token_url.scheme() != "https" {
+                return Err(ErrorKind::InsecureOAuth2TokenUrl(config.token_url));
+            }
+            let hosts = config.hosts();
+            // The token is never sent in plain text
+            host_chain = host_chain.when(
+                move |request: &reqwest::Request| {
+                    request.url().scheme() == "https"
+                        && request
+                            .url()
+                            .host_str()
+                            .is_some_and(|host| hosts.iter().any(|h| h == host))
+                },
+                OAuth2Handler::new(config, reqwest_client.clone()),
+            );
+        }
+
+        let fragment_checker =
+            FragmentChecker::new(self.fragment_style.clone(), self.anchor_cache.clone());
+        let render_js = if self.render_js_hosts.is_empty() {
+            None
+        } else {
+            Some(RenderJs::new(
+                &self.render_js_hosts,
+                self.accepted.clone(),
+                self.include_fragments,
+                self.check_canonical,
+                fragment_checker.clone(),
+                self.timeout,
+                self.content_assertions.clone(),
+                browser_options,
+            )?)
+        };
+        let mut pool =
+            host_pool(&self.host_configs, self.deadline).with_internal_hosts(&self.internal_hosts);
+        if let Some(max_concurrency) = self.internal_max_concurrency {
+            pool = pool.with_internal_max_concurrency(max_concurrency);
+        }
+        if let Some(max_concurrency) = self.external_max_concurrency {
+            pool = pool.with_external_max_concurrency(max_concurrency);
+        }
+        let internal = InternalLinks {
+            hosts: self
+                .internal_hosts
+                .iter()
+                .map(|host| host.to_lowercase())
+                .collect(),
+            timeout: self.internal_timeout,
+            max_retries: self.internal_max_retries,
+        };
         let website_checker = WebsiteChecker::new(
             self.method,
             self.retry_wait_time,
             self.max_retries,
+            internal,
             reqwest_client,
             self.accepted,
-            github_client,
+            platform_checkers,
             self.require_https,
             self.plugin_request_chain,
+            host_chain.build(),
+            host_headers,
+            Arc::new(pool),
+            self.include_fragments,
+            fragment_checker.clone(),
+            self.check_canonical,
+            self.error_page_snippet,
+            self.track_redirects,
+            self.cache_bust_param,
+            self.observer.clone(),
+            self.accept_languages,
+            render_js,
+            Arc::new(self.content_assertions),
         );
 
+        let website_checker = Arc::new(website_checker);
+        let file_checker = Arc::new(FileChecker::new(
+            self.base,
+            self.fallback_extensions,
+            self.path_mappings,
+            self.include_fragments,
+            self.fragment_style,
+            self.anchor_cache,
+        ));
+        let email_checker = Arc::new(MailChecker::new(
+            self.mail_dns_timeout,
+            self.mail_dns_max_retries,
+            self.mail_check_level,
+        ));
+        let checkers = HashMap::from([
+            ("file".to_string(), file_checker.clone() as Arc<dyn Checker>),
+            (
+                "mailto".to_string(),
+                email_checker.clone() as Arc<dyn Checker>,
+            ),
+            (
+                "tel".to_string(),
+                Arc::new(TelChecker::new(self.tel_patterns)) as Arc<dyn Checker>,
+            ),
+        ]);
+
         Ok(Client {
             remaps: self.remaps,
             filter,
-            email_checker: MailChecker::new(),
+            checkers,
+            email_checker,
             website_checker,
-            file_checker: FileChecker::new(
-                self.base,
-                self.fallback_extensions,
-                self.include_fragments,
-            ),
-            fragment_checker: FragmentChecker::new(),
+            file_checker,
+            fragment_checker,
+            observer: self.observer,
         })
     }
 }
 
+/// The certificates of the PEM file at `path`
+fn read_root_certificates(path: &Path) -> Result<Vec<reqwest::Certificate>> {
+    let invalid = |e: String| ErrorKind::InvalidRootCertificate(path.to_path_buf(), e);
+    let pem = std::fs::read(path).map_err(|e| invalid(e.to_string()))?;
+    let certificates =
+        reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| invalid(e.to_string()))?;
+    if certificates.is_empty() {
+        return Err(invalid("No PEM certificates found".to_string()));
+    }
+    Ok(certificates)
+}
+
+/// The rate limits of all hosts, which stop at the `deadline` (if any)
+fn host_pool(host_configs: &HashMap<String, HostConfig>, deadline: Option<Instant>) -> HostPool {
+    let pool = host_configs
+        .iter()
+        .fold(HostPool::default(), |pool, (host, config)| {
+            pool.with_host_config(host, *config)
+        });
+    match deadline {
+        Some(deadline) => pool.with_deadline(deadline.into()),
+        None => pool,
+    }
+}
+
 /// Handles incoming requests and returns responses.
 ///
 /// See [`ClientBuilder`] which contains sane defaults for all configuration
@@ -421,17 +921,27 @@ pub struct Client {
     /// Rules to decided whether each link should be checked or ignored.
     filter: Filter,
 
+    /// Checkers by URI scheme, replacing the `website_checker` for this
+    /// scheme.
+    ///
+    /// Websites and links with other schemes are checked by the
+    /// `website_checker`.
+    checkers: HashMap<String, Arc<dyn Checker>>,
+
     /// A checker for website URLs.
-    website_checker: WebsiteChecker,
+    website_checker: Arc<WebsiteChecker>,
 
     /// A checker for file URLs.
-    file_checker: FileChecker,
+    file_checker: Arc<FileChecker>,
 
     /// A checker for email URLs.
-    email_checker: MailChecker,
+    email_checker: Arc<MailChecker>,
 
     /// Caches Fragments
     fragment_checker: FragmentChecker,
+
+    /// Notified of the progress of each check.
+    observer: Option<Arc<dyn CheckObserver>>,
 }
 
 impl Client {
@@ -444,20 +954,18 @@ impl Client {
     ///
     /// Returns an `Err` if:
     /// - `request` does not represent a valid URI.
-    /// - Encrypted connection for a HTTP URL is available but unused. (Only
-    ///   checked when `Client::require_https` is `true`.)
+    /// - The remapped URI is not a valid URI.
+    ///
+    /// Errors of the checker are reported as the [`Status::Error`] of the
+    /// response.
     #[allow(clippy::missing_panics_doc)]
     pub async fn check<T, E>(&self, request: T) -> Result<Response>
     where
         Request: TryFrom<T, Error = E>,
         ErrorKind: From<E>,
     {
-        let Request {
-            ref mut uri,
-            credentials,
-            source,
-            ..
-        } = request.try_into()?;
+        let mut request: Request = request.try_into()?;
+        let credentials = request.credentials.take();
 
         // Allow filtering based on element and attribute
         // if !self.filter.is_allowed(uri) {
@@ -468,21 +976,68 @@ impl Client {
         //     ));
         // }
 
-        self.remap(uri)?;
-
-        if self.is_excluded(uri) {
-            return Ok(Response::new(uri.clone(), Status::Excluded, source));
+        self.remap(&mut request.uri)?;
+        let uri = &request.uri;
+
+        if self.is_excluded_from(uri, &request.source) {
+            let status = self
+                .filter
+                .scheme_policy(uri)
+                .map_or(Status::Excluded, Status::ExcludedScheme);
+            let response = Response::from_request(request, status);
+            self.notify(&CheckEvent::CheckCompleted {
+                response: &response,
+                duration: None,
+            });
+            return Ok(response);
         }
 
-        let status = match uri.scheme() {
-            // We don't check tel: URIs
-            _ if uri.is_tel() => Status::Excluded,
-            _ if uri.is_file() => self.check_file(uri).await,
-            _ if uri.is_mail() => self.check_mail(uri).await,
-            _ => self.check_website(uri, credentials).await?,
+        self.notify(&CheckEvent::RequestQueued(uri));
+        let queued = Instant::now();
+        let checker: &dyn Checker = match self.checkers.get(uri.scheme()) {
+            Some(checker) => {
+                self.notify(&CheckEvent::CheckStarted(uri));
+                &**checker
+            }
+            // Websites are checked by the website checker, which reports the
+            // start of each request itself, after waiting for the rate
+            // limits. Links with other schemes without a checker are
+            // checked like websites as well.
+            None => &*self.website_checker,
         };
+        let status = checker
+            .check(uri, credentials)
+            .instrument(tracing::info_span!("check", uri = %uri))
+            .await
+            .unwrap_or_else(Status::from);
+
+        let response = Response::from_request(request, status);
+        self.notify(&CheckEvent::CheckCompleted {
+            response: &response,
+            duration: Some(queued.elapsed()),
+        });
+        Ok(response)
+    }
 
-        Ok(Response::new(uri.clone(), status, source))
+    /// Send `event` to the observer (if any)
+    fn notify(&self, event: &CheckEvent<'_>) {
+        if let Some(observer) = &self.observer {
+            observer.on_event(event);
+        }
+    }
+
+    /// Check links with the `scheme` (e.g. `ipfs`) with `checker`, replacing
+    /// the checker which was registered for this scheme before (if any).
+    ///
+    /// Links with schemes without a checker are checked like websites.
+    pub fn register_checker(
+        &mut self,
+        scheme: impl Into<String>,
+        checker: impl Checker + 'static,
+    ) -> &mut Self {
+        self.checkers
+            .insert(scheme.into().to_lowercase(), Arc::new(checker));
+        self
     }
 
     /// Check a single file using the file checker.
@@ -490,6 +1045,14 @@ impl Client {
         self.file_checker.check(uri).await
     }
 
+    /// Forget the fragments of the local file at `path`, so that links to
+    /// its fragments are checked against its new content after it changed.
+    ///
+    /// The fragments of a file are parsed once per client otherwise.
+    pub async fn forget_fragments(&self, path: &Path) {
+        self.file_checker.forget_fragments(path).await;
+    }
+
     /// Remap `uri` using the client-defined remapping rules.
     ///
     /// # Errors
@@ -497,7 +1060,8 @@ impl Client {
     /// Returns an `Err` if the final, remapped `uri` is not a valid URI.
     pub fn remap(&self, uri: &mut Uri) -> Result<()> {
         if let Some(ref remaps) = self.remaps {
-            uri.url = remaps.remap(&uri.url)?;
+            let url = remaps.remap(&uri.url)?;
+            uri.set_url(url);
         }
         Ok(())
     }
@@ -508,6 +1072,23 @@ impl Client {
         self.filter.is_excluded(uri)
     }
 
+    /// Returns whether the given `uri` found in `source` should be ignored
+    /// from checking.
+    ///
+    /// Unlike [`Client::is_excluded`], this also applies the patterns of
+    /// `.lycheeignore` files which are scoped to the directory of `source`.
+    #[must_use]
+    pub fn is_excluded_from(&self, uri: &Uri, source: &InputSource) -> bool {
+        self.filter.is_excluded_from(uri, source)
+    }
+
+    /// Returns whether `uri` is a link into the checked site, i.e. a local
+    /// file or a link to one of the [`ClientBuilder::internal_hosts`].
+    #[must_use]
+    pub fn is_internal(&self, uri: &Uri) -> bool {
+        self.website_checker.is_internal(&uri.url)
+    }
+
     /// Checks the given URI of a website.
     ///
     /// # Errors
@@ -525,6 +1106,64 @@ impl Client {
         self.website_checker.check_website(uri, credentials).await
     }
 
+    /// Returns whether the [`ClientBuilder::deadline`] (if any) has passed,
+    /// after which no more requests are sent.
+    #[must_use]
+    pub fn is_past_deadline(&self) -> bool {
+        self.website_checker.is_past_deadline()
+    }
+
+    /// The current requests per website host, e.g. to show which hosts are
+    /// slowed down by their rate limits.
+    #[must_use]
+    pub fn host_activity(&self) -> Vec<HostActivity> {
+        self.website_checker.host_activity()
+    }
+
+    /// The fragments which occur more than once in the local files whose
+    /// fragments were checked, by path, with the positions of the
+    /// repetitions. Links to them are ambiguous.
+    ///
+    /// Repeated Markdown headings are not included, because they get a
+    /// unique suffix (e.g. `intro-1`). The handle stays valid when the client
+    /// is moved, so it can be taken before the links are checked.
+    #[must_use]
+    pub fn duplicate_fragments(
+        &self,
+    ) -> Arc<std::sync::Mutex<HashMap<PathBuf, Vec<(String, types::uri::raw::RawUriSpan)>>>> {
+        self.fragment_checker.duplicates()
+    }
+
+    /// Send a `HEAD` request to `url` with the connection settings of the
+    /// client (proxies, TLS options and DNS overrides), e.g. to test the
+    /// network connection before checking links. Any response counts as
+    /// success, no matter its status.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no response was received.
+    pub async fn probe(&self, url: reqwest::Url) -> Result<()> {
+        self.website_checker.probe(url).await
+    }
+
+    /// Start opening up to `connections` connections to the host of the
+    /// website `uri` in the background, so that the handshakes (e.g. of TLS)
+    /// don't slow down the checks of its links. `uri` should be one of these
+    /// links, as it is requested to open the connections. The connections are
+    /// kept alive and reused by the following requests to the host, which
+    /// wait until they are open. The links of other hosts don't wait.
+    ///
+    /// The rate limits of the host apply. The time it took is reported as
+    /// [`HostActivity::warm_up`], separately from the time of the requests.
+    /// Returns `false` if `uri` is not a website or its host is already
+    /// warming up.
+    pub fn warm_up(&self, uri: &Uri, connections: usize) -> bool {
+        if !matches!(uri.kind(), UriKind::Web) || self.is_past_deadline() {
+            return false;
+        }
+        self.website_checker.warm_up(&uri.url, connections)
+    }
+
     /// Checks a `mailto` URI.
     pub async fn check_mail(&self, uri: &Uri) -> Status {
         self.email_checker.check_mail(uri).await
@@ -567,22 +1206,30 @@ where
 #[cfg(test)]
 mod tests {
     use std::{
+        collections::{HashMap, HashSet},
         fs::File,
+        sync::{Arc, Mutex},
         time::{Duration, Instant},
     };
 
     use async_trait::async_trait;
     use http::{header::HeaderMap, StatusCode};
-    use reqwest::header;
+    use regex::Regex;
+    use reqwest::{header, Url};
+    use secrecy::SecretString;
     use tempfile::tempdir;
-    use wiremock::matchers::path;
+    use wiremock::{matchers::path, Mock, MockServer, ResponseTemplate};
 
     use super::ClientBuilder;
     use crate::{
         chain::{ChainResult, Handler, RequestChain},
         mock_server,
-        test_utils::get_mock_client_response,
-        ErrorKind, Request, Status, Uri,
+        path_mapping::PathMappings,
+        ratelimit::HostConfig,
+        test_utils::{fixture_path, get_mock_client_response, https_server},
+        AnchorCache, BasicAuthCredentials, CheckEvent, CheckObserver, Checker, ContentAssertion,
+        ErrorKind, HttpVersion, OAuth2Config, PlatformChecker, Request, SchemePolicy, Status,
+        TlsVersion, Uri,
     };
 
     #[tokio::test]
@@ -685,6 +1332,53 @@ mod tests {
         assert!(res.status().is_success());
     }
 
+    #[tokio::test]
+    async fn test_anchor_cache() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("index.html");
+        std::fs::write(&file, r#"<h1 id="intro">Intro</h1>"#).unwrap();
+        let anchor_cache = AnchorCache::default();
+        let client = ClientBuilder::builder()
+            .include_fragments(true)
+            .anchor_cache(anchor_cache.clone())
+            .build()
+            .client()
+            .unwrap();
+
+        let res = client
+            .check(format!("file://{}#intro", file.display()))
+            .await
+            .unwrap();
+        assert!(res.status().is_success());
+        assert_eq!(anchor_cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_path_mappings() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("docs/guide")).unwrap();
+        File::create(dir.path().join("docs/guide/install.md")).unwrap();
+        let mappings = PathMappings::parse(
+            dir.path().to_path_buf(),
+            &[r"docs/(.*)\.md -> /$1/".to_string()],
+        )
+        .unwrap();
+        let client = ClientBuilder::builder()
+            .path_mappings(mappings)
+            .build()
+            .client()
+            .unwrap();
+
+        let page = |path: &str| {
+            let url = Url::from_directory_path(dir.path().join(path)).unwrap();
+            Uri::from(url)
+        };
+        let res = client.check(page("guide/install")).await.unwrap();
+        assert!(res.status().is_success());
+        let res = client.check(page("guide/missing")).await.unwrap();
+        assert!(res.status().is_error());
+    }
+
     #[tokio::test]
     async fn test_custom_headers() {
         // See https://github.com/rust-lang/crates.io/issues/788
@@ -708,9 +1402,7 @@ mod tests {
             .build()
             .client()
             .unwrap();
-        assert!(client.is_excluded(&Uri {
-            url: "mailto://mail@example.com".try_into().unwrap()
-        }));
+        assert!(client.is_excluded(&Uri::try_from("mailto://mail@example.com").unwrap()));
     }
 
     #[tokio::test]
@@ -721,9 +1413,7 @@ mod tests {
             .build()
             .client()
             .unwrap();
-        assert!(client.is_excluded(&Uri {
-            url: "mailto://mail@example.com".try_into().unwrap()
-        }));
+        assert!(client.is_excluded(&Uri::try_from("mailto://mail@example.com").unwrap()));
 
         let client = ClientBuilder::builder()
             .include_mail(true)
@@ -731,17 +1421,43 @@ mod tests {
             .build()
             .client()
             .unwrap();
-        assert!(!client.is_excluded(&Uri {
-            url: "mailto://mail@example.com".try_into().unwrap()
-        }));
+        assert!(!client.is_excluded(&Uri::try_from("mailto://mail@example.com").unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_scheme_policy() {
+        let client = ClientBuilder::builder()
+            .denied_schemes(HashSet::from(["ftp".to_string()]))
+            .build()
+            .client()
+            .unwrap();
+        let res = client.check("javascript:void(0)").await.unwrap();
+        assert_eq!(
+            res.status(),
+            &Status::ExcludedScheme(SchemePolicy::Inline("javascript".to_string()))
+        );
+        let res = client.check("ftp://example.org/file").await.unwrap();
+        assert_eq!(
+            res.status(),
+            &Status::ExcludedScheme(SchemePolicy::Denied("ftp".to_string()))
+        );
+        assert!(res.status().is_excluded());
     }
 
     #[tokio::test]
     async fn test_include_tel() {
         let client = ClientBuilder::builder().build().client().unwrap();
-        assert!(client.is_excluded(&Uri {
-            url: "tel:1234567890".try_into().unwrap()
-        }));
+        assert!(client.is_excluded(&Uri::try_from("tel:1234567890").unwrap()));
+
+        let client = ClientBuilder::builder()
+            .include_tel(true)
+            .build()
+            .client()
+            .unwrap();
+        let res = client.check("tel:+1-555-0100").await.unwrap();
+        assert!(res.status().is_success());
+        let res = client.check("tel:555-0100").await.unwrap();
+        assert!(res.status().is_error());
     }
 
     #[tokio::test]
@@ -761,58 +1477,349 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_timeout() {
-        // Note: this checks response timeout, not connect timeout.
-        // To check connect timeout, we'd have to do something more involved,
-        // see: https://github.com/LukeMathWalker/wiremock-rs/issues/19
-        let mock_delay = Duration::from_millis(20);
-        let checker_timeout = Duration::from_millis(10);
-        assert!(mock_delay > checker_timeout);
+    async fn test_http_version() {
+        let mock_server = mock_server!(StatusCode::OK);
 
-        let mock_server = mock_server!(StatusCode::OK, set_delay(mock_delay));
+        for http_version in [
+            HttpVersion::Auto,
+            HttpVersion::Http1Only,
+            HttpVersion::Http2PriorKnowledge,
+        ] {
+            let client = ClientBuilder::builder()
+                .http_version(http_version)
+                .build()
+                .client()
+                .unwrap();
+            let res = client.check(mock_server.uri()).await.unwrap();
+            assert!(res.status().is_success(), "{http_version:?}");
+        }
+    }
 
+    #[tokio::test]
+    async fn test_min_tls_version() {
+        let mock_server = mock_server!(StatusCode::OK);
+        let origin = https_server(&mock_server).await;
         let client = ClientBuilder::builder()
-            .timeout(checker_timeout)
+            .min_tls_version(TlsVersion::Tls1_2)
+            .add_root_certificate(fixture_path("tls/ca.pem"))
             .build()
             .client()
             .unwrap();
-
+        let res = client.check(origin).await.unwrap();
+        assert!(res.status().is_success());
+        // Plain HTTP connections are not affected
         let res = client.check(mock_server.uri()).await.unwrap();
-        assert!(res.status().is_timeout());
+        assert!(res.status().is_success());
     }
 
     #[tokio::test]
-    async fn test_exponential_backoff() {
-        let mock_delay = Duration::from_millis(20);
-        let checker_timeout = Duration::from_millis(10);
-        assert!(mock_delay > checker_timeout);
-
-        let mock_server = mock_server!(StatusCode::OK, set_delay(mock_delay));
+    async fn test_add_root_certificate() {
+        let mock_server = mock_server!(StatusCode::OK);
+        let origin = https_server(&mock_server).await;
 
-        // Perform a warm-up request to ensure the lazy regexes
-        // in lychee-lib/src/quirks/mod.rs are compiled.
-        // On some platforms, this can take some time(approx. 110ms),
-        // which should not be counted in the test.
-        let warm_up_client = ClientBuilder::builder()
+        // The test CA is not trusted by default
+        let client = ClientBuilder::builder()
             .max_retries(0_u64)
             .build()
             .client()
             .unwrap();
-        let _res = warm_up_client.check(mock_server.uri()).await.unwrap();
+        let res = client.check(origin.as_str()).await.unwrap();
+        assert!(res.status().is_error());
 
         let client = ClientBuilder::builder()
-            .timeout(checker_timeout)
-            .max_retries(3_u64)
-            .retry_wait_time(Duration::from_millis(50))
+            .add_root_certificate(fixture_path("tls/ca.pem"))
             .build()
             .client()
             .unwrap();
+        let res = client.check(origin.as_str()).await.unwrap();
+        assert!(res.status().is_success());
+    }
 
-        // Summary:
-        // 1. First request fails with timeout (after 10ms)
-        // 2. Retry after 50ms (total 60ms)
-        // 3. Second request fails with timeout (after 10ms)
-        // 4. Retry after 100ms (total 160ms)
+    #[test]
+    fn test_root_certificates() {
+        let dir = tempdir().unwrap();
+        let missing = dir.path().join("missing.pem");
+        let result = ClientBuilder::builder()
+            .add_root_certificate(missing.clone())
+            .build()
+            .client();
+        assert!(matches!(
+            result,
+            Err(ErrorKind::InvalidRootCertificate(path, _)) if path == missing
+        ));
+
+        let empty = dir.path().join("empty.pem");
+        std::fs::write(&empty, "no certificate").unwrap();
+        let result = ClientBuilder::builder()
+            .add_root_certificate(empty.clone())
+            .build()
+            .client();
+        assert!(matches!(
+            result,
+            Err(ErrorKind::InvalidRootCertificate(path, _)) if path == empty
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_remote_fragments() {
+        let body = r#"<h1 id="intro">Intro</h1><a name="legacy"></a>"#;
+        let mock_server = mock_server!(StatusCode::OK, set_body_raw(body, "text/html"));
+        let client = ClientBuilder::builder()
+            .include_fragments(true)
+            .build()
+            .client()
+            .unwrap();
+
+        for fragment in ["intro", "legacy", "top", ""] {
+            let res = client
+                .check(format!("{}/#{fragment}", mock_server.uri()))
+                .await
+                .unwrap();
+            assert!(res.status().is_success(), "#{fragment}");
+        }
+
+        let res = client
+            .check(format!("{}/#missing", mock_server.uri()))
+            .await
+            .unwrap();
+        assert!(matches!(
+            res.status(),
+            Status::Error(ErrorKind::InvalidFragment(_))
+        ));
+
+        // Fragments are not checked unless enabled
+        let res = get_mock_client_response(format!("{}/#missing", mock_server.uri())).await;
+        assert!(res.status().is_success());
+    }
+
+    #[tokio::test]
+    async fn test_error_page_snippet() {
+        let mock_server = MockServer::start().await;
+        let page = "<title>Acme Docs</title><h1>Page not found</h1>";
+        Mock::given(path("/missing"))
+            .respond_with(ResponseTemplate::new(404).set_body_raw(page, "text/html"))
+            .mount(&mock_server)
+            .await;
+        let url = format!("{}/missing", mock_server.uri());
+        let client = ClientBuilder::builder()
+            .error_page_snippet(true)
+            .build()
+            .client()
+            .unwrap();
+
+        let res = client.check(url.clone()).await.unwrap();
+        assert_eq!(res.status().code(), Some(StatusCode::NOT_FOUND));
+        assert_eq!(
+            res.status().details(),
+            Some("Not Found – Page not found – Acme Docs".to_string())
+        );
+
+        // Error pages are not described unless enabled
+        let res = get_mock_client_response(url).await;
+        assert_eq!(res.status().details(), Some("Not Found".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_check_canonical() {
+        let mock_server = MockServer::start().await;
+        let page = r#"<link rel="canonical" href="/new/#top">"#;
+        for route in ["/new/", "/copy/"] {
+            Mock::given(path(route))
+                .respond_with(ResponseTemplate::new(200).set_body_raw(page, "text/html"))
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(path("/old/"))
+            .respond_with(ResponseTemplate::new(301).insert_header("Location", "/new/"))
+            .mount(&mock_server)
+            .await;
+        let client = ClientBuilder::builder()
+            .check_canonical(true)
+            .build()
+            .client()
+            .unwrap();
+
+        // Redirects to the canonical URL are fine
+        for route in ["/new/", "/old/"] {
+            let res = client
+                .check(format!("{}{route}", mock_server.uri()))
+                .await
+                .unwrap();
+            assert!(res.status().is_success(), "{route}");
+        }
+
+        let res = client
+            .check(format!("{}/copy/", mock_server.uri()))
+            .await
+            .unwrap();
+        assert!(matches!(
+            res.status(),
+            Status::CanonicalMismatch { url, canonical, .. }
+                if url.path() == "/copy/" && canonical.path() == "/new/"
+        ));
+
+        // Canonical URLs are not checked unless enabled
+        let res = get_mock_client_response(format!("{}/copy/", mock_server.uri())).await;
+        assert!(res.status().is_success());
+    }
+
+    #[tokio::test]
+    async fn test_content_assertions() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path("/docs/"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<main>Docs</main>"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/docs/missing"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<h1>Page not found</h1>"))
+            .mount(&mock_server)
+            .await;
+        let client = ClientBuilder::builder()
+            .content_assertions(vec![ContentAssertion {
+                url: Regex::new("/docs/").unwrap(),
+                contains: None,
+                not_contains: Some(Regex::new("(?i)page not found").unwrap()),
+            }])
+            .build()
+            .client()
+            .unwrap();
+
+        let res = client
+            .check(format!("{}/docs/", mock_server.uri()))
+            .await
+            .unwrap();
+        assert!(res.status().is_success());
+
+        let res = client
+            .check(format!("{}/docs/missing", mock_server.uri()))
+            .await
+            .unwrap();
+        assert_eq!(
+            res.status(),
+            &Status::Error(ErrorKind::ContentAssertion(
+                StatusCode::OK,
+                "Body matches `(?i)page not found`".to_string()
+            ))
+        );
+        assert_eq!(res.status().code(), Some(StatusCode::OK));
+    }
+
+    #[tokio::test]
+    async fn test_warm_up() {
+        let mock_server = mock_server!(StatusCode::OK);
+        let client = ClientBuilder::builder().build().client().unwrap();
+        let uri = Uri::try_from(mock_server.uri().as_str()).unwrap();
+
+        assert!(client.warm_up(&uri, 2));
+        assert!(!client.warm_up(&uri, 2));
+        // The check waits for the warm-up
+        client.check(mock_server.uri()).await.unwrap();
+        let activity = &client.host_activity()[0];
+        assert!(activity.warm_up.is_some());
+        assert!(activity.mean_request_time.is_some());
+        assert_eq!(activity.completed, 3);
+
+        let mail = Uri::try_from("mailto:hi@example.com").unwrap();
+        assert!(!client.warm_up(&mail, 2));
+    }
+
+    #[tokio::test]
+    async fn test_dns_overrides() {
+        let mock_server = mock_server!(StatusCode::OK);
+        let addr = *mock_server.address();
+
+        let client = ClientBuilder::builder()
+            .dns_overrides(HashMap::from([(
+                "staging.lychee.invalid".to_string(),
+                addr,
+            )]))
+            .build()
+            .client()
+            .unwrap();
+        let res = client
+            .check(format!("http://staging.lychee.invalid:{}/", addr.port()))
+            .await
+            .unwrap();
+        assert!(res.status().is_success());
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        // Note: this checks response timeout, not connect timeout.
+        // To check connect timeout, we'd have to do something more involved,
+        // see: https://github.com/LukeMathWalker/wiremock-rs/issues/19
+        let mock_delay = Duration::from_millis(20);
+        let checker_timeout = Duration::from_millis(10);
+        assert!(mock_delay > checker_timeout);
+
+        let mock_server = mock_server!(StatusCode::OK, set_delay(mock_delay));
+
+        let client = ClientBuilder::builder()
+            .timeout(checker_timeout)
+            .build()
+            .client()
+            .unwrap();
+
+        let res = client.check(mock_server.uri()).await.unwrap();
+        assert!(res.status().is_timeout());
+    }
+
+    #[tokio::test]
+    async fn test_internal_timeout() {
+        let mock_delay = Duration::from_millis(20);
+        let internal_timeout = Duration::from_millis(10);
+        assert!(mock_delay > internal_timeout);
+
+        let mock_server = mock_server!(StatusCode::OK, set_delay(mock_delay));
+        let uri = Uri::try_from(mock_server.uri()).unwrap();
+
+        let client = ClientBuilder::builder()
+            .internal_hosts(vec![uri.url.host_str().unwrap().to_string()])
+            .internal_timeout(internal_timeout)
+            .max_retries(0u64)
+            .build()
+            .client()
+            .unwrap();
+
+        assert!(client.is_internal(&uri));
+        assert!(!client.is_internal(&Uri::try_from("https://example.com").unwrap()));
+
+        let res = client.check(uri).await.unwrap();
+        assert!(res.status().is_timeout());
+    }
+
+    #[tokio::test]
+    async fn test_exponential_backoff() {
+        let mock_delay = Duration::from_millis(20);
+        let checker_timeout = Duration::from_millis(10);
+        assert!(mock_delay > checker_timeout);
+
+        let mock_server = mock_server!(StatusCode::OK, set_delay(mock_delay));
+
+        // Perform a warm-up request to ensure the lazy regexes
+        // in lychee-lib/src/quirks/mod.rs are compiled.
+        // On some platforms, this can take some time(approx. 110ms),
+        // which should not be counted in the test.
+        let warm_up_client = ClientBuilder::builder()
+            .max_retries(0_u64)
+            .build()
+            .client()
+            .unwrap();
+        let _res = warm_up_client.check(mock_server.uri()).await.unwrap();
+
+        let client = ClientBuilder::builder()
+            .timeout(checker_timeout)
+            .max_retries(3_u64)
+            .retry_wait_time(Duration::from_millis(50))
+            .build()
+            .client()
+            .unwrap();
+
+        // Summary:
+        // 1. First request fails with timeout (after 10ms)
+        // 2. Retry after 50ms (total 60ms)
+        // 3. Second request fails with timeout (after 10ms)
+        // 4. Retry after 100ms (total 160ms)
         // 5. Third request fails with timeout (after 10ms)
         // 6. Retry after 200ms (total 360ms)
         // Total: 360ms
@@ -828,6 +1835,163 @@ mod tests {
         assert!((350..=550).contains(&end.as_millis()));
     }
 
+    #[tokio::test]
+    async fn test_host_config() {
+        let mock_server = mock_server!(StatusCode::OK);
+        let interval = Duration::from_millis(100);
+
+        let client = ClientBuilder::builder()
+            .host_configs(HashMap::from([(
+                "127.0.0.1".to_string(),
+                HostConfig::new().with_min_interval(interval),
+            )]))
+            .build()
+            .client()
+            .unwrap();
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            let res = client.check(mock_server.uri()).await.unwrap();
+            assert!(res.status().is_success());
+        }
+        assert!(start.elapsed() >= 2 * interval);
+    }
+
+    #[tokio::test]
+    async fn test_oauth2() {
+        let mock_server = MockServer::start().await;
+        Mock::given(wiremock::matchers::method("POST"))
+            .and(path("/token"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK)
+                    .set_body_string(r#"{"access_token": "abc", "token_type": "Bearer"}"#),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/resource"))
+            .and(wiremock::matchers::header("Authorization", "Bearer abc"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .mount(&mock_server)
+            .await;
+        let origin = https_server(&mock_server).await;
+
+        let config = |token_url: String| {
+            OAuth2Config::new(
+                Url::parse(&token_url).unwrap(),
+                "id",
+                SecretString::from("secret"),
+            )
+        };
+        let client = ClientBuilder::builder()
+            .oauth2(Some(config(format!("{origin}/token"))))
+            .add_root_certificate(fixture_path("tls/ca.pem"))
+            .build()
+            .client()
+            .unwrap();
+        let res = client.check(format!("{origin}/resource")).await.unwrap();
+        assert!(res.status().is_success());
+
+        // The token is never sent in plain text
+        let res = client
+            .check(format!("{}/resource", mock_server.uri()))
+            .await
+            .unwrap();
+        assert!(res.status().is_error());
+        let result = ClientBuilder::builder()
+            .oauth2(Some(config(format!("{}/token", mock_server.uri()))))
+            .build()
+            .client();
+        assert!(matches!(result, Err(ErrorKind::InsecureOAuth2TokenUrl(_))));
+    }
+
+    #[tokio::test]
+    async fn test_host_headers() {
+        let mock_server = MockServer::start().await;
+        let port = mock_server.address().port();
+        Mock::given(path("/moved"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::FOUND)
+                    .insert_header("Location", format!("http://localhost:{port}/target")),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/target"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .mount(&mock_server)
+            .await;
+        Mock::given(wiremock::matchers::header("X-Api-Key", "secret"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .mount(&mock_server)
+            .await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Api-Key", "secret".parse().unwrap());
+        let client = ClientBuilder::builder()
+            .host_headers(HashMap::from([("127.0.0.1".to_string(), headers)]))
+            .build()
+            .client()
+            .unwrap();
+
+        let res = client
+            .check(format!("http://127.0.0.1:{port}"))
+            .await
+            .unwrap();
+        assert!(res.status().is_success());
+
+        // Same server, but a different host name
+        let res = client
+            .check(format!("http://localhost:{port}"))
+            .await
+            .unwrap();
+        assert!(res.status().is_error());
+
+        // The headers don't follow redirects to other hosts
+        let res = client
+            .check(format!("http://127.0.0.1:{port}/moved"))
+            .await
+            .unwrap();
+        assert!(res.status().is_success());
+        let requests = mock_server.received_requests().await.unwrap();
+        let target = requests
+            .iter()
+            .find(|request| request.url.path() == "/target")
+            .unwrap();
+        assert_eq!(target.url.host_str(), Some("localhost"));
+        assert!(!target.headers.contains_key("X-Api-Key"));
+    }
+
+    #[tokio::test]
+    async fn test_register_checker() {
+        #[derive(Debug)]
+        struct IpfsChecker;
+
+        #[async_trait]
+        impl Checker for IpfsChecker {
+            async fn check(
+                &self,
+                uri: &Uri,
+                _credentials: Option<BasicAuthCredentials>,
+            ) -> crate::Result<Status> {
+                Ok(if uri.host_str() == Some("bafybeigdyrzt") {
+                    Status::Ok(StatusCode::OK)
+                } else {
+                    Status::Error(ErrorKind::InvalidURI(uri.clone()))
+                })
+            }
+        }
+
+        let mut client = ClientBuilder::builder().build().client().unwrap();
+        // Checked like a website by default
+        let res = client.check("ipfs://bafybeigdyrzt").await.unwrap();
+        assert!(!res.status().is_success());
+
+        client.register_checker("IPFS", IpfsChecker);
+        let res = client.check("ipfs://bafybeigdyrzt").await.unwrap();
+        assert!(res.status().is_success());
+        let res = client.check("ipfs://missing").await.unwrap();
+        assert!(res.status().is_error());
+    }
+
     #[tokio::test]
     async fn test_avoid_reqwest_panic() {
         let client = ClientBuilder::builder().build().client().unwrap();
@@ -883,6 +2047,337 @@ mod tests {
         assert!(res.status().is_success());
     }
 
+    #[tokio::test]
+    async fn test_track_redirects() {
+        let mock_server = MockServer::start().await;
+        for (from, to) in [("/a", "/b"), ("/b", "/c")] {
+            Mock::given(path(from))
+                .respond_with(
+                    ResponseTemplate::new(StatusCode::MOVED_PERMANENTLY)
+                        .insert_header("Location", format!("{}{to}", mock_server.uri())),
+                )
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(path("/c"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::builder()
+            .track_redirects(true)
+            .build()
+            .client()
+            .unwrap();
+
+        let res = client
+            .check(format!("{}/a", mock_server.uri()))
+            .await
+            .unwrap();
+        assert!(res.status().is_success());
+        let Status::Redirected(code, redirects) = res.status() else {
+            panic!("Expected a redirect, got {:?}", res.status());
+        };
+        assert_eq!(*code, StatusCode::OK);
+        assert_eq!(
+            redirects.to_string(),
+            format!("301 {0}/a -> 301 {0}/b -> 200 {0}/c", mock_server.uri())
+        );
+
+        // Requests without redirects are reported as usual
+        let res = client
+            .check(format!("{}/c", mock_server.uri()))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), &Status::Ok(StatusCode::OK));
+
+        // Concurrent checks of the same URL get their own redirects
+        let uri = format!("{}/a", mock_server.uri());
+        let (a, b) = tokio::join!(client.check(uri.clone()), client.check(uri));
+        for res in [a.unwrap(), b.unwrap()] {
+            let Status::Redirected(_, redirects) = res.status() else {
+                panic!("Expected a redirect, got {:?}", res.status());
+            };
+            assert_eq!(
+                redirects.to_string(),
+                format!("301 {0}/a -> 301 {0}/b -> 200 {0}/c", mock_server.uri())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_observer() {
+        #[derive(Debug, Default)]
+        struct Recorder(Mutex<Vec<String>>);
+
+        impl CheckObserver for Recorder {
+            fn on_event(&self, event: &CheckEvent<'_>) {
+                let event = match event {
+                    CheckEvent::RequestQueued(_) => "queued".to_string(),
+                    CheckEvent::CheckStarted(_) => "started".to_string(),
+                    CheckEvent::Retry { attempt, .. } => format!("retry {attempt}"),
+                    CheckEvent::CheckCompleted { response, .. } => {
+                        format!("completed {}", response.status().code_as_string())
+                    }
+                };
+                self.0.lock().unwrap().push(event);
+            }
+        }
+
+        let mock_server = mock_server!(StatusCode::INTERNAL_SERVER_ERROR);
+        let recorder = Arc::new(Recorder::default());
+        let client = ClientBuilder::builder()
+            .max_retries(1_u64)
+            .retry_wait_time(Duration::from_millis(10))
+            .observer(Some(recorder.clone() as Arc<dyn CheckObserver>))
+            .build()
+            .client()
+            .unwrap();
+
+        client.check(mock_server.uri()).await.unwrap();
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            ["queued", "started", "retry 1", "started", "completed 500"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_observer_checker_error() {
+        #[derive(Debug)]
+        struct FailingChecker;
+
+        #[async_trait]
+        impl Checker for FailingChecker {
+            async fn check(
+                &self,
+                uri: &Uri,
+                _credentials: Option<BasicAuthCredentials>,
+            ) -> crate::Result<Status> {
+                Err(ErrorKind::InvalidURI(uri.clone()))
+            }
+        }
+
+        #[derive(Debug, Default)]
+        struct Recorder(Mutex<Vec<bool>>);
+
+        impl CheckObserver for Recorder {
+            fn on_event(&self, event: &CheckEvent<'_>) {
+                if let CheckEvent::CheckCompleted { response, .. } = event {
+                    self.0.lock().unwrap().push(response.status().is_error());
+                }
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let mut client = ClientBuilder::builder()
+            .observer(Some(recorder.clone() as Arc<dyn CheckObserver>))
+            .build()
+            .client()
+            .unwrap();
+        client.register_checker("ipfs", FailingChecker);
+
+        // Errors of the checker are reported as the status of the response
+        let res = client.check("ipfs://bafybeigdyrzt").await.unwrap();
+        assert!(res.status().is_error());
+        assert_eq!(*recorder.0.lock().unwrap(), [true]);
+    }
+
+    #[tokio::test]
+    async fn test_accept_languages() {
+        let mock_server = MockServer::start().await;
+        Mock::given(wiremock::matchers::header("Accept-Language", "fr"))
+            .respond_with(ResponseTemplate::new(StatusCode::NOT_FOUND))
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .mount(&mock_server)
+            .await;
+
+        let client = |languages: &[&str]| {
+            ClientBuilder::builder()
+                .accept_languages(
+                    languages
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>(),
+                )
+                .max_retries(0_u64)
+                .build()
+                .client()
+                .unwrap()
+        };
+
+        let res = client(&["en", "de"])
+            .check(mock_server.uri())
+            .await
+            .unwrap();
+        assert!(res.status().is_success());
+
+        let res = client(&["en", "fr"])
+            .check(mock_server.uri())
+            .await
+            .unwrap();
+        assert_eq!(
+            res.status(),
+            &Status::Error(ErrorKind::LanguageFailures(vec![(
+                "fr".to_string(),
+                "404".to_string()
+            )]))
+        );
+
+        // Links which fail in every language are reported as usual
+        let res = client(&["fr"]).check(mock_server.uri()).await.unwrap();
+        assert_eq!(res.status().code_as_string(), "404");
+    }
+
+    #[tokio::test]
+    async fn test_cache_bust_param() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path("/a"))
+            .and(|request: &wiremock::Request| {
+                request.url.query_pairs().any(|(key, _)| key == "cb")
+            })
+            .respond_with(
+                ResponseTemplate::new(StatusCode::MOVED_PERMANENTLY)
+                    .insert_header("Location", format!("{}/b", mock_server.uri())),
+            )
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/b"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::builder()
+            .cache_bust_param(Some("cb".to_string()))
+            .track_redirects(true)
+            .build()
+            .client()
+            .unwrap();
+
+        let uri = format!("{}/a", mock_server.uri());
+        let res = client.check(uri.clone()).await.unwrap();
+        let Status::Redirected(_, redirects) = res.status() else {
+            panic!("Expected a redirect, got {:?}", res.status());
+        };
+        // The parameter is not reported
+        assert_eq!(
+            redirects.to_string(),
+            format!("301 {0}/a -> 200 {0}/b", mock_server.uri())
+        );
+        assert!(client.check(uri).await.unwrap().status().is_success());
+
+        // The encoding of the other parameters is kept
+        let res = client
+            .check(format!("{}/a?q=a%20b", mock_server.uri()))
+            .await
+            .unwrap();
+        let Status::Redirected(_, redirects) = res.status() else {
+            panic!("Expected a redirect, got {:?}", res.status());
+        };
+        assert_eq!(
+            redirects.to_string(),
+            format!("301 {0}/a?q=a%20b -> 200 {0}/b", mock_server.uri())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_gitlab_api() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path("/group/project/-/issues/1"))
+            .respond_with(ResponseTemplate::new(StatusCode::FORBIDDEN))
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/api/v4/projects/group%2Fproject"))
+            .and(wiremock::matchers::header("PRIVATE-TOKEN", "secret"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK)
+                    .set_body_string(r#"{"id": 1, "visibility": "private"}"#),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/api/v4/projects/group%2Fmoved"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::MOVED_PERMANENTLY)
+                    .insert_header("Location", "/api/v4/projects/group%2Fproject"),
+            )
+            .mount(&mock_server)
+            .await;
+        // The API is only requested over https
+        let origin = https_server(&mock_server).await;
+        let uri = format!("{origin}/group/project/-/issues/1");
+
+        // Without a token, the normal request fails
+        let client = ClientBuilder::builder()
+            .gitlab_hosts(vec!["127.0.0.1".to_string()])
+            .add_root_certificate(fixture_path("tls/ca.pem"))
+            .build()
+            .client()
+            .unwrap();
+        assert!(client
+            .check(uri.as_str())
+            .await
+            .unwrap()
+            .status()
+            .is_error());
+
+        let client = ClientBuilder::builder()
+            .gitlab_token(Some(SecretString::from("secret")))
+            .gitlab_hosts(vec!["127.0.0.1".to_string()])
+            .add_root_certificate(fixture_path("tls/ca.pem"))
+            .build()
+            .client()
+            .unwrap();
+        assert!(client
+            .check(uri.as_str())
+            .await
+            .unwrap()
+            .status()
+            .is_success());
+
+        // The token is neither sent over http nor along redirects, and the
+        // status of the normal request is kept
+        for uri in [
+            format!("{}/group/project/-/issues/1", mock_server.uri()),
+            format!("{origin}/group/moved/-/issues/1"),
+        ] {
+            assert!(
+                client
+                    .check(uri.as_str())
+                    .await
+                    .unwrap()
+                    .status()
+                    .is_error(),
+                "{uri}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_platform_checker() {
+        #[derive(Debug)]
+        struct AlwaysPrivate;
+
+        #[async_trait]
+        impl PlatformChecker for AlwaysPrivate {
+            async fn check(&self, _client: &reqwest::Client, _uri: &Uri) -> Option<Status> {
+                Some(Status::Ok(StatusCode::OK))
+            }
+        }
+
+        let mock_server = mock_server!(StatusCode::NOT_FOUND);
+        let checker: Arc<dyn PlatformChecker> = Arc::new(AlwaysPrivate);
+        let client = ClientBuilder::builder()
+            .platform_checkers(HashMap::from([("127.0.0.1".to_string(), checker)]))
+            .build()
+            .client()
+            .unwrap();
+        let res = client.check(mock_server.uri()).await.unwrap();
+        assert!(res.status().is_success());
+    }
+
     #[tokio::test]
     async fn test_limit_max_redirects() {
         let mock_server = wiremock::MockServer::start().await;
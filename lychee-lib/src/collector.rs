@@ -1,16 +1,37 @@
 use crate::ErrorKind;
 use crate::InputSource;
 use crate::{
-    basic_auth::BasicAuthExtractor, extract::Extractor, types::uri::raw::RawUri, utils::request,
-    Base, Input, Request, Result,
+    basic_auth::BasicAuthExtractor,
+    extract::{markdown::extract_markdown_reference_issues, Extractor},
+    types::uri::raw::RawUri,
+    utils::{
+        crawl_log::{CrawlLog, Resumed},
+        encoding, request,
+    },
+    Base, ContentOptions, FileExtensions, FileType, GeneratedMarkers, Input, InputContent,
+    ReferenceIssue, Request, Result, SameDomainPolicy,
 };
+use async_stream::try_stream;
+use encoding_rs::Encoding;
 use futures::TryStreamExt;
 use futures::{
     stream::{self, Stream},
     StreamExt,
 };
+use log::{debug, warn};
 use par_stream::ParStreamExt;
-use std::path::PathBuf;
+use reqwest::Url;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::Instrument;
+
+/// Files larger than this are streamed instead of read into memory (64 MiB)
+pub const DEFAULT_STREAM_THRESHOLD: u64 = 64 * 1024 * 1024;
 
 /// Collector keeps the state of link collection
 /// It drives the link extraction from inputs
@@ -21,10 +42,27 @@ pub struct Collector {
     skip_missing_inputs: bool,
     skip_ignored: bool,
     skip_hidden: bool,
+    follow_symlinks: bool,
+    input_encoding: Option<&'static Encoding>,
+    stream_threshold: u64,
+    include_binary: bool,
+    skipped_binaries: Arc<Mutex<HashSet<InputSource>>>,
+    check_references: bool,
+    reference_issues: Arc<Mutex<HashMap<InputSource, Vec<ReferenceIssue>>>>,
     include_verbatim: bool,
+    include_notebook_outputs: bool,
+    include_lazy_images: bool,
+    extract_comments: bool,
+    file_extensions: FileExtensions,
     use_html5ever: bool,
     root_dir: Option<PathBuf>,
     base: Option<Base>,
+    recursion_depth: usize,
+    same_domain_policy: SameDomainPolicy,
+    crawl_state: Option<PathBuf>,
+    max_files: Option<usize>,
+    max_links: Option<usize>,
+    generated_markers: Option<GeneratedMarkers>,
 }
 
 impl Default for Collector {
@@ -33,11 +71,28 @@ impl Default for Collector {
             basic_auth_extractor: None,
             skip_missing_inputs: false,
             include_verbatim: false,
+            include_notebook_outputs: false,
+            include_lazy_images: false,
+            extract_comments: false,
+            file_extensions: FileExtensions::default(),
             use_html5ever: false,
             skip_hidden: true,
             skip_ignored: true,
+            follow_symlinks: false,
+            input_encoding: None,
+            stream_threshold: DEFAULT_STREAM_THRESHOLD,
+            include_binary: false,
+            skipped_binaries: Arc::default(),
+            check_references: false,
+            reference_issues: Arc::default(),
             root_dir: None,
             base: None,
+            recursion_depth: 0,
+            same_domain_policy: SameDomainPolicy::default(),
+            crawl_state: None,
+            max_files: None,
+            max_links: None,
+            generated_markers: None,
         }
     }
 }
@@ -58,11 +113,28 @@ impl Collector {
             basic_auth_extractor: None,
             skip_missing_inputs: false,
             include_verbatim: false,
+            include_notebook_outputs: false,
+            include_lazy_images: false,
+            extract_comments: false,
+            file_extensions: FileExtensions::default(),
             use_html5ever: false,
             skip_hidden: true,
             skip_ignored: true,
+            follow_symlinks: false,
+            input_encoding: None,
+            stream_threshold: DEFAULT_STREAM_THRESHOLD,
+            include_binary: false,
+            skipped_binaries: Arc::default(),
+            check_references: false,
+            reference_issues: Arc::default(),
             root_dir,
             base,
+            recursion_depth: 0,
+            same_domain_policy: SameDomainPolicy::default(),
+            crawl_state: None,
+            max_files: None,
+            max_links: None,
+            generated_markers: None,
         })
     }
 
@@ -87,6 +159,69 @@ impl Collector {
         self
     }
 
+    /// Follow symlinks when walking directories and expanding glob patterns
+    /// (default is to skip them)
+    #[must_use]
+    pub const fn follow_symlinks(mut self, yes: bool) -> Self {
+        self.follow_symlinks = yes;
+        self
+    }
+
+    /// Decode input files and stdin with this encoding instead of UTF-8,
+    /// unless they start with a byte order mark
+    #[must_use]
+    pub const fn input_encoding(mut self, encoding: Option<&'static Encoding>) -> Self {
+        self.input_encoding = encoding;
+        self
+    }
+
+    /// Stream files larger than `bytes` during link extraction instead of
+    /// reading them into memory (default: [`DEFAULT_STREAM_THRESHOLD`]).
+    ///
+    /// This keeps memory usage bounded for huge inputs like log exports, but
+    /// Markdown files are only scanned like plaintext when streamed
+    /// (see [`Extractor::extract_stream`]).
+    #[must_use]
+    pub const fn stream_threshold(mut self, bytes: u64) -> Self {
+        self.stream_threshold = bytes;
+        self
+    }
+
+    /// Extract links from inputs which look like binary files (default is to
+    /// skip them)
+    #[must_use]
+    pub const fn include_binary(mut self, yes: bool) -> Self {
+        self.include_binary = yes;
+        self
+    }
+
+    /// Inputs which were skipped because they look like binary files.
+    ///
+    /// The set is filled while links are collected, so it is only complete
+    /// once the stream returned by [`Collector::collect_links`] is exhausted.
+    #[must_use]
+    pub fn skipped_binaries(&self) -> Arc<Mutex<HashSet<InputSource>>> {
+        Arc::clone(&self.skipped_binaries)
+    }
+
+    /// Find undefined references and unused definitions of reference-style
+    /// links and footnotes in Markdown inputs (see [`ReferenceIssue`])
+    #[must_use]
+    pub const fn check_references(mut self, yes: bool) -> Self {
+        self.check_references = yes;
+        self
+    }
+
+    /// Reference issues of Markdown inputs, if
+    /// [`check_references`](Collector::check_references) is enabled.
+    ///
+    /// The map is filled while links are collected, so it is only complete
+    /// once the stream returned by [`Collector::collect_links`] is exhausted.
+    #[must_use]
+    pub fn reference_issues(&self) -> Arc<Mutex<HashMap<InputSource, Vec<ReferenceIssue>>>> {
+        Arc::clone(&self.reference_issues)
+    }
+
     /// Use `html5ever` to parse HTML instead of `html5gum`.
     #[must_use]
     pub const fn use_html5ever(mut self, yes: bool) -> Self {
@@ -101,6 +236,97 @@ impl Collector {
         self
     }
 
+    /// Also extract links from the outputs of code cells in Jupyter notebooks
+    #[must_use]
+    pub const fn include_notebook_outputs(mut self, yes: bool) -> Self {
+        self.include_notebook_outputs = yes;
+        self
+    }
+
+    /// Also extract the images of lazy-loading scripts (`data-src` and
+    /// `data-srcset`) from HTML
+    #[must_use]
+    pub const fn include_lazy_images(mut self, yes: bool) -> Self {
+        self.include_lazy_images = yes;
+        self
+    }
+
+    /// Only extract links from the comments of source code files
+    #[must_use]
+    pub const fn extract_comments(mut self, yes: bool) -> Self {
+        self.extract_comments = yes;
+        self
+    }
+
+    /// Detect the file type of files with custom extensions, e.g. to check
+    /// `.mdoc` files as Markdown. Directories also include these files.
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn file_extensions(mut self, file_extensions: FileExtensions) -> Self {
+        self.file_extensions = file_extensions;
+        self
+    }
+
+    /// Recursively collect links from pages of the same website as the
+    /// remote inputs, up to `depth` links away from the input.
+    ///
+    /// Which pages belong to the same website is decided by
+    /// `same_domain_policy`. Each page is only collected once, even if
+    /// pages link to each other. A `depth` of 0 disables recursion (default).
+    #[must_use]
+    pub const fn recurse(mut self, depth: usize, same_domain_policy: SameDomainPolicy) -> Self {
+        self.recursion_depth = depth;
+        self.same_domain_policy = same_domain_policy;
+        self
+    }
+
+    /// Record the progress of a recursive crawl in the file at `path`, so that
+    /// an interrupted crawl continues where it stopped when collecting from
+    /// the same inputs again.
+    ///
+    /// Pages are logged as soon as they are discovered, so large crawls
+    /// survive restarts, and the pages of the next depth are read back from
+    /// the file instead of being kept in memory. When resuming, the pages of
+    /// completed depths are collected again to report their links, but
+    /// their links aren't followed again. A file from a crawl of other
+    /// inputs is discarded. The file is removed once the crawl is complete.
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn crawl_state(mut self, path: Option<PathBuf>) -> Self {
+        self.crawl_state = path;
+        self
+    }
+
+    /// Abort link collection with an error if more than `max_files` inputs
+    /// (e.g. files matched by a glob or found in a directory) are read.
+    ///
+    /// This guards against unexpectedly large runs, for example when a glob
+    /// also matches `node_modules` or build output.
+    #[must_use]
+    pub const fn max_files(mut self, max_files: Option<usize>) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    /// Abort link collection with an error if more than `max_links` links
+    /// are found.
+    #[must_use]
+    pub const fn max_links(mut self, max_links: Option<usize>) -> Self {
+        self.max_links = max_links;
+        self
+    }
+
+    /// Mark links inside regions which are generated by other tools (see
+    /// [`Request::generated`]), e.g. to report their failures separately.
+    ///
+    /// Regions are only detected in inputs which are not streamed.
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn generated_markers(mut self, markers: Option<GeneratedMarkers>) -> Self {
+        self.generated_markers = markers;
+        self
+    }
+
     /// Pass a [`BasicAuthExtractor`] which is capable to match found
     /// URIs to basic auth credentials. These credentials get passed to the
     /// request in question.
@@ -119,56 +345,478 @@ impl Collector {
             .flatten()
     }
 
+    /// The extractor which is used to find links in the contents of inputs
+    #[must_use]
+    pub const fn extractor(&self) -> Extractor {
+        Extractor::new(self.use_html5ever, self.include_verbatim)
+            .include_notebook_outputs(self.include_notebook_outputs)
+            .include_lazy_images(self.include_lazy_images)
+            .extract_comments(self.extract_comments)
+    }
+
+    /// Collect the contents of all inputs, without extracting any links.
+    ///
+    /// Together with [`Collector::extractor`], this allows inspecting what
+    /// would be checked. Files are always read into memory here, regardless
+    /// of [`Collector::stream_threshold`].
+    pub fn collect_contents(self, inputs: Vec<Input>) -> impl Stream<Item = Result<InputContent>> {
+        let skip_missing_inputs = self.skip_missing_inputs;
+        let skip_hidden = self.skip_hidden;
+        let skip_ignored = self.skip_ignored;
+        let follow_symlinks = self.follow_symlinks;
+        let input_encoding = self.input_encoding;
+        let file_extensions = self.file_extensions;
+        stream::iter(inputs)
+            .par_then_unordered(None, move |input| {
+                let file_extensions = file_extensions.clone();
+                async move {
+                    input.get_contents(ContentOptions {
+                        skip_missing: skip_missing_inputs,
+                        skip_hidden,
+                        skip_gitignored: skip_ignored,
+                        follow_symlinks,
+                        encoding: input_encoding,
+                        stream_threshold: u64::MAX,
+                        file_extensions,
+                    })
+                }
+            })
+            .flatten()
+    }
+
     /// Fetch all unique links from inputs
     /// All relative URLs get prefixed with `base` (if given).
     /// (This can be a directory or a base URL)
+    /// The base of an input ([`Input::base`]) takes precedence.
     ///
     /// # Errors
     ///
     /// Will return `Err` if links cannot be extracted from an input
+    /// or if the limits set with [`Collector::max_files`] or
+    /// [`Collector::max_links`] are exceeded.
     pub fn collect_links(self, inputs: Vec<Input>) -> impl Stream<Item = Result<Request>> {
-        let skip_missing_inputs = self.skip_missing_inputs;
+        try_stream! {
+            let mut crawl = Crawl::new(self.same_domain_policy, &inputs);
+            let mut inputs = inputs;
+            let mut skip_missing_inputs = self.skip_missing_inputs;
+            let mut depth = 0;
+            let files = Arc::new(AtomicUsize::new(0));
+            let mut links = 0;
+            // Inputs which are collected again when a crawl is resumed
+            let mut replayed = None;
+
+            if let Some(path) = self.crawl_state.as_deref().filter(|_| self.recursion_depth > 0) {
+                if let Some((resumed_depth, pages)) = crawl.resume(path)? {
+                    debug!("Resuming crawl at depth {resumed_depth} with {} pages", pages.len());
+                    depth = resumed_depth;
+                    // The links of the inputs and of the pages of completed
+                    // depths are reported again. Their links to other pages
+                    // were already followed, so only the current depth
+                    // discovers new pages.
+                    replayed = Some(std::mem::replace(&mut inputs, pages));
+                    skip_missing_inputs = true;
+                }
+            }
+
+            while !inputs.is_empty() {
+                let span = tracing::info_span!("collect", depth, inputs = inputs.len());
+                let requests = self.clone().collect_inputs(
+                    inputs,
+                    skip_missing_inputs,
+                    Arc::clone(&files),
+                    span.clone(),
+                );
+                let requests = match replayed.take() {
+                    Some(replayed) => self
+                        .clone()
+                        .collect_inputs(
+                            replayed,
+                            self.skip_missing_inputs,
+                            Arc::clone(&files),
+                            span,
+                        )
+                        .chain(requests)
+                        .left_stream(),
+                    None => requests.right_stream(),
+                };
+                for await request in requests {
+                    let request = request?;
+                    links += 1;
+                    if let Some(max_links) = self.max_links.filter(|max| links > *max) {
+                        Err(ErrorKind::TooManyLinks(max_links))?;
+                    }
+                    if depth < self.recursion_depth {
+                        crawl.follow(&request, depth + 1)?;
+                    }
+                    yield request;
+                }
+                crawl.done(depth)?;
+                inputs = crawl.next_pages(depth + 1)?;
+                // Broken links to discovered pages are reported by the checker
+                skip_missing_inputs = true;
+                depth += 1;
+            }
+            crawl.finish()?;
+        }
+    }
+
+    /// Fetch all links from the given inputs, without recursion
+    ///
+    /// `files` counts the inputs read so far, including previous calls. The
+    /// extraction of each input is traced in a span below `span`.
+    fn collect_inputs(
+        self,
+        inputs: Vec<Input>,
+        skip_missing_inputs: bool,
+        files: Arc<AtomicUsize>,
+        span: tracing::Span,
+    ) -> impl Stream<Item = Result<Request>> {
+        let max_files = self.max_files;
+        let include_binary = self.include_binary;
+        let check_references = self.check_references;
         let skip_hidden = self.skip_hidden;
         let skip_ignored = self.skip_ignored;
+        let follow_symlinks = self.follow_symlinks;
+        let input_encoding = self.input_encoding;
+        let stream_threshold = self.stream_threshold;
+        let extractor = self.extractor();
         let global_base = self.base;
+        let file_extensions = self.file_extensions.clone();
         stream::iter(inputs)
             .par_then_unordered(None, move |input| {
-                let default_base = global_base.clone();
+                let base = input.base.clone().or_else(|| global_base.clone());
+                let file_extensions = file_extensions.clone();
+                let tag = input.tag.clone();
                 async move {
-                    let base = match &input.source {
-                        InputSource::RemoteUrl(url) => Base::try_from(url.as_str()).ok(),
-                        _ => default_base,
-                    };
                     input
-                        .get_contents(skip_missing_inputs, skip_hidden, skip_ignored)
-                        .map(move |content| (content, base.clone()))
+                        .get_contents(ContentOptions {
+                            skip_missing: skip_missing_inputs,
+                            skip_hidden,
+                            skip_gitignored: skip_ignored,
+                            follow_symlinks,
+                            encoding: input_encoding,
+                            stream_threshold,
+                            file_extensions,
+                        })
+                        .map(move |content| (content, base.clone(), tag.clone()))
                 }
             })
             .flatten()
-            .par_then_unordered(None, move |(content, base)| {
+            .par_then_unordered(None, move |(content, base, tag)| {
                 let root_dir = self.root_dir.clone();
                 let basic_auth_extractor = self.basic_auth_extractor.clone();
+                let generated_markers = self.generated_markers.clone();
+                let files = Arc::clone(&files);
+                let skipped_binaries = Arc::clone(&self.skipped_binaries);
+                let reference_issues = Arc::clone(&self.reference_issues);
+                let extract_span = tracing::debug_span!(
+                    parent: &span,
+                    "extract",
+                    source = tracing::field::Empty,
+                    file_type = tracing::field::Empty,
+                    links = tracing::field::Empty,
+                );
                 async move {
                     let content = content?;
-                    let extractor = Extractor::new(self.use_html5ever, self.include_verbatim);
-                    let uris: Vec<RawUri> = extractor.extract(&content);
-                    let requests = request::create(
+                    let current = tracing::Span::current();
+                    current.record("source", tracing::field::display(&content.source));
+                    current.record("file_type", tracing::field::debug(content.file_type));
+                    if let Some(max_files) = max_files {
+                        if files.fetch_add(1, Ordering::Relaxed) >= max_files {
+                            return Err(ErrorKind::TooManyFiles(max_files));
+                        }
+                    }
+                    let uris = match &content.stream {
+                        Some(path) => {
+                            extract_stream(
+                                extractor,
+                                &content,
+                                path.clone(),
+                                input_encoding,
+                                include_binary,
+                            )
+                            .await?
+                        }
+                        None if include_binary
+                            || !encoding::is_binary(content.content.as_bytes()) =>
+                        {
+                            Some(extractor.extract(&content))
+                        }
+                        None => None,
+                    };
+                    let Some(uris) = uris else {
+                        debug!("Skipping binary input {}", content.source);
+                        skipped_binaries.lock().unwrap().insert(content.source);
+                        return Ok(stream::iter(HashSet::new().into_iter().map(Ok)));
+                    };
+                    // Streamed files are only scanned like plaintext
+                    if check_references
+                        && content.file_type == FileType::Markdown
+                        && content.stream.is_none()
+                    {
+                        let issues = extract_markdown_reference_issues(&content.content);
+                        if !issues.is_empty() {
+                            reference_issues
+                                .lock()
+                                .unwrap()
+                                .insert(content.source.clone(), issues);
+                        }
+                    }
+                    // Remote pages are resolved relative to their own URL,
+                    // as a single input (e.g. a sitemap) can expand to many pages
+                    let base = match &content.source {
+                        InputSource::RemoteUrl(url) => Base::try_from(url.as_str()).ok(),
+                        _ => base,
+                    };
+                    let generated = generated_markers
+                        .map(|markers| markers.regions(&content.content))
+                        .unwrap_or_default();
+                    let mut requests = request::create(
                         uris,
                         &content.source,
                         root_dir.as_ref(),
                         base.as_ref(),
                         basic_auth_extractor.as_ref(),
+                        &generated,
                     );
+                    if tag.is_some() {
+                        requests = requests
+                            .into_iter()
+                            .map(|request| request.with_tag(tag.clone()))
+                            .collect();
+                    }
+                    current.record("links", requests.len());
                     Result::Ok(stream::iter(requests.into_iter().map(Ok)))
                 }
+                .instrument(extract_span)
             })
             .try_flatten()
     }
 }
 
+/// Extract the links of a streamed input from the file at `path`, or return
+/// `None` if it looks like a binary file and `include_binary` is `false`.
+///
+/// Reading and parsing large files takes a while, so this runs on a thread
+/// where blocking is allowed.
+async fn extract_stream(
+    extractor: Extractor,
+    content: &InputContent,
+    path: PathBuf,
+    encoding: Option<&'static Encoding>,
+    include_binary: bool,
+) -> Result<Option<Vec<RawUri>>> {
+    let file_type = content.file_type;
+    let source = content.source.to_string();
+    let file = path.clone();
+    tokio::task::spawn_blocking(move || {
+        let reader = encoding::DecodingReader::new(File::open(file)?, encoding, &source)?;
+        if !include_binary && reader.is_binary() {
+            return Ok(None);
+        }
+        extractor.extract_stream(reader, file_type).map(Some)
+    })
+    .await
+    .unwrap_or_else(|e| Err(io::Error::from(e)))
+    .map_err(|e| ErrorKind::ReadFileInput(e, path))
+}
+
+/// State of a recursive crawl, which decides the pages to collect next
+#[derive(Debug)]
+struct Crawl {
+    policy: SameDomainPolicy,
+    /// The remote inputs from which the crawl started
+    roots: Vec<Url>,
+    /// The tags of the roots, which are inherited by the discovered pages
+    tags: Vec<Option<String>>,
+    /// Fingerprints of the pages which were already collected (for cycle
+    /// detection), with the index of the root from which each page was
+    /// discovered. Only fingerprints are kept, so that large crawls need
+    /// little memory.
+    pages: HashMap<u64, usize>,
+    /// Pages to collect at the next depth, unless they are read back from
+    /// the log
+    next: Vec<Url>,
+    log: Option<CrawlLog>,
+}
+
+impl Crawl {
+    fn new(policy: SameDomainPolicy, inputs: &[Input]) -> Self {
+        let mut crawl = Crawl {
+            policy,
+            roots: Vec::new(),
+            tags: Vec::new(),
+            pages: HashMap::new(),
+            next: Vec::new(),
+            log: None,
+        };
+        for input in inputs {
+            if let InputSource::RemoteUrl(url) = &input.source {
+                crawl.root(without_fragment(url), input.tag.clone());
+            }
+        }
+        crawl
+    }
+
+    /// The index of `url` in the roots, which is added as a root with `tag`
+    /// if necessary
+    fn root(&mut self, url: Url, tag: Option<String>) -> usize {
+        if let Some(index) = self.roots.iter().position(|root| *root == url) {
+            return index;
+        }
+        self.pages.insert(fingerprint(&url), self.roots.len());
+        self.roots.push(url);
+        self.tags.push(tag);
+        self.roots.len() - 1
+    }
+
+    /// Continue the crawl recorded in the log at `path` and record the
+    /// progress from now on. Logs of crawls from other inputs are discarded.
+    ///
+    /// Returns the depth to continue at and the pages up to that depth, or
+    /// `None` if no depth was completed yet and the crawl starts with the
+    /// inputs.
+    fn resume(&mut self, path: &Path) -> Result<Option<(usize, Vec<Input>)>> {
+        let map_err = |e: io::Error| ErrorKind::CrawlState(e, path.to_path_buf());
+        let (mut log, mut resumed) = CrawlLog::open(path).map_err(map_err)?;
+
+        let mut logged_roots = resumed.roots.clone();
+        logged_roots.sort();
+        let mut roots = self.roots.clone();
+        roots.sort();
+        if logged_roots != roots {
+            if resumed != Resumed::default() {
+                warn!(
+                    "Ignoring the crawl state in `{}`, which belongs to other inputs",
+                    path.display()
+                );
+            }
+            log.clear().map_err(map_err)?;
+            log.roots(&self.roots).map_err(map_err)?;
+            resumed = Resumed::default();
+        }
+        self.log = Some(log);
+
+        let depth = resumed.done.map_or(0, |done| done + 1);
+        let mut pages = Vec::new();
+        for page in resumed.pages {
+            let Some(root) = self.roots.iter().position(|root| *root == page.root) else {
+                continue;
+            };
+            if self.pages.insert(fingerprint(&page.url), root).is_some() {
+                continue;
+            }
+            // Pages of later depths are read back from the log
+            if page.depth <= depth {
+                pages.push(page.url);
+            }
+        }
+        Ok((depth > 0).then(|| {
+            let pages = pages.into_iter().map(|url| self.page_input(url)).collect();
+            (depth, pages)
+        }))
+    }
+
+    /// Queue the linked page for `depth`, if it belongs to the same website
+    /// as the page containing the link and wasn't collected yet
+    fn follow(&mut self, request: &Request, depth: usize) -> Result<()> {
+        let InputSource::RemoteUrl(source) = &request.source else {
+            return Ok(());
+        };
+        let Some(&root) = self.pages.get(&fingerprint(&without_fragment(source))) else {
+            return Ok(());
+        };
+        let url = without_fragment(&request.uri.url);
+
+        if !self.policy.matches(&self.roots[root], &url)
+            || FileType::from(url.as_str()) != FileType::Html
+            || self.pages.contains_key(&fingerprint(&url))
+        {
+            return Ok(());
+        }
+        self.pages.insert(fingerprint(&url), root);
+        match &mut self.log {
+            Some(log) => log
+                .page(depth, &self.roots[root], &url)
+                .map_err(|e| ErrorKind::CrawlState(e, log.path().to_path_buf()))?,
+            None => self.next.push(url),
+        }
+        Ok(())
+    }
+
+    /// Record that all pages up to `depth` were collected
+    fn done(&mut self, depth: usize) -> Result<()> {
+        match &mut self.log {
+            Some(log) => log
+                .done(depth)
+                .map_err(|e| ErrorKind::CrawlState(e, log.path().to_path_buf())),
+            None => Ok(()),
+        }
+    }
+
+    /// The pages to collect at `depth`, the next depth
+    fn next_pages(&mut self, depth: usize) -> Result<Vec<Input>> {
+        let next = match &self.log {
+            Some(log) => log
+                .pages_at(depth)
+                .map_err(|e| ErrorKind::CrawlState(e, log.path().to_path_buf()))?
+                .into_iter()
+                .map(|page| page.url)
+                .collect(),
+            None => std::mem::take(&mut self.next),
+        };
+        Ok(next.into_iter().map(|url| self.page_input(url)).collect())
+    }
+
+    /// A discovered page as an input, tagged like its root
+    fn page_input(&self, url: Url) -> Input {
+        let tag = self
+            .pages
+            .get(&fingerprint(&url))
+            .and_then(|root| self.tags[*root].clone());
+        Input {
+            source: InputSource::RemoteUrl(Box::new(url)),
+            file_type_hint: None,
+            excluded_paths: None,
+            tag,
+            base: None,
+        }
+    }
+
+    /// Remove the log of the complete crawl
+    fn finish(&mut self) -> Result<()> {
+        match self.log.take() {
+            Some(log) => {
+                let path = log.path().to_path_buf();
+                log.remove().map_err(|e| ErrorKind::CrawlState(e, path))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// A fingerprint of `url`, which identifies a page of a crawl
+fn fingerprint(url: &Url) -> u64 {
+    let mut hasher = std::hash::DefaultHasher::new();
+    url.as_str().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Links to different fragments of a page point to the same page
+fn without_fragment(url: &Url) -> Url {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url
+}
+
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashSet, convert::TryFrom, fs::File, io::Write};
+    use std::{
+        collections::HashSet,
+        convert::TryFrom,
+        fs::{self, File},
+        io::Write,
+    };
 
     use http::StatusCode;
     use reqwest::Url;
@@ -177,8 +825,8 @@ mod tests {
     use crate::{
         mock_server,
         test_utils::{load_fixture, mail, path, website},
-        types::{FileType, Input, InputSource},
-        Result, Uri,
+        types::{FileExtensions, FileType, Input, InputSource},
+        ReferenceIssueKind, Result, Uri,
     };
 
     // Helper function to run the collector on the given inputs
@@ -217,7 +865,10 @@ mod tests {
         let _file = File::create(&file_path).unwrap();
         let input = Input::new(&file_path.as_path().display().to_string(), None, true, None)?;
         let contents: Vec<_> = input
-            .get_contents(true, true, true)
+            .get_contents(ContentOptions {
+                skip_missing: true,
+                ..ContentOptions::default()
+            })
             .collect::<Vec<_>>()
             .await;
 
@@ -230,7 +881,10 @@ mod tests {
     async fn test_url_without_extension_is_html() -> Result<()> {
         let input = Input::new("https://example.com/", None, true, None)?;
         let contents: Vec<_> = input
-            .get_contents(true, true, true)
+            .get_contents(ContentOptions {
+                skip_missing: true,
+                ..ContentOptions::default()
+            })
             .collect::<Vec<_>>()
             .await;
 
@@ -263,6 +917,8 @@ mod tests {
                 source: InputSource::String(TEST_STRING.to_owned()),
                 file_type_hint: None,
                 excluded_paths: None,
+                tag: None,
+                base: None,
             },
             Input {
                 source: InputSource::RemoteUrl(Box::new(
@@ -272,11 +928,15 @@ mod tests {
                 )),
                 file_type_hint: None,
                 excluded_paths: None,
+                tag: None,
+                base: None,
             },
             Input {
                 source: InputSource::FsPath(file_path),
                 file_type_hint: None,
                 excluded_paths: None,
+                tag: None,
+                base: None,
             },
             Input {
                 source: InputSource::FsGlob {
@@ -285,6 +945,8 @@ mod tests {
                 },
                 file_type_hint: None,
                 excluded_paths: None,
+                tag: None,
+                base: None,
             },
         ];
 
@@ -310,6 +972,8 @@ mod tests {
             source: InputSource::String("This is [a test](https://endler.dev). This is a relative link test [Relative Link Test](relative_link)".to_string()),
             file_type_hint: Some(FileType::Markdown),
                 excluded_paths: None,
+            tag: None,
+            base: None,
         };
         let links = collect(vec![input], None, Some(base)).await.ok().unwrap();
 
@@ -336,6 +1000,8 @@ mod tests {
             ),
             file_type_hint: Some(FileType::Html),
             excluded_paths: None,
+            tag: None,
+            base: None,
         };
         let links = collect(vec![input], None, Some(base)).await.ok().unwrap();
 
@@ -365,6 +1031,8 @@ mod tests {
             ),
             file_type_hint: Some(FileType::Html),
             excluded_paths: None,
+            tag: None,
+            base: None,
         };
         let links = collect(vec![input], None, Some(base)).await.ok().unwrap();
 
@@ -391,6 +1059,8 @@ mod tests {
             ),
             file_type_hint: Some(FileType::Markdown),
             excluded_paths: None,
+            tag: None,
+            base: None,
         };
 
         let links = collect(vec![input], None, Some(base)).await.ok().unwrap();
@@ -414,6 +1084,8 @@ mod tests {
             source: InputSource::String(input),
             file_type_hint: Some(FileType::Html),
             excluded_paths: None,
+            tag: None,
+            base: None,
         };
         let links = collect(vec![input], None, Some(base)).await.ok().unwrap();
 
@@ -446,6 +1118,8 @@ mod tests {
             source: InputSource::RemoteUrl(Box::new(server_uri.clone())),
             file_type_hint: None,
             excluded_paths: None,
+            tag: None,
+            base: None,
         };
 
         let links = collect(vec![input], None, None).await.ok().unwrap();
@@ -458,6 +1132,402 @@ mod tests {
         assert_eq!(links, expected_urls);
     }
 
+    #[tokio::test]
+    async fn test_sitemap_input() {
+        let mock_server = wiremock::MockServer::start().await;
+        let server_uri = Url::parse(&mock_server.uri()).unwrap();
+
+        wiremock::Mock::given(wiremock::matchers::path("/sitemap.xml"))
+            .respond_with(
+                wiremock::ResponseTemplate::new(StatusCode::OK).set_body_string(format!(
+                    "<urlset><url><loc>{server_uri}page</loc></url></urlset>"
+                )),
+            )
+            .mount(&mock_server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::path("/page"))
+            .respond_with(
+                wiremock::ResponseTemplate::new(StatusCode::OK)
+                    .set_body_string(r#"<a href="/about">About</a>"#),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let input = Input {
+            source: InputSource::Sitemap(Box::new(server_uri.join("sitemap.xml").unwrap())),
+            file_type_hint: None,
+            excluded_paths: None,
+            tag: None,
+            base: None,
+        };
+
+        let links = collect(vec![input], None, None).await.ok().unwrap();
+
+        let expected_urls = HashSet::from_iter([website(&format!("{server_uri}about"))]);
+
+        assert_eq!(links, expected_urls);
+    }
+
+    #[tokio::test]
+    async fn test_recursion() {
+        let mock_server = wiremock::MockServer::start().await;
+        let server_uri = Url::parse(&mock_server.uri()).unwrap();
+
+        for (page, body) in [
+            (
+                "/",
+                r#"<a href="/a">A</a><a href="https://github.com/">GitHub</a>"#,
+            ),
+            ("/a", r#"<a href="/#top">Home</a><a href="/b">B</a>"#),
+            ("/b", r#"<a href="/c">C</a>"#),
+        ] {
+            wiremock::Mock::given(wiremock::matchers::path(page))
+                .respond_with(wiremock::ResponseTemplate::new(StatusCode::OK).set_body_string(body))
+                .mount(&mock_server)
+                .await;
+        }
+
+        let collect_recursive = |depth| {
+            let input = Input {
+                source: InputSource::RemoteUrl(Box::new(server_uri.clone())),
+                file_type_hint: None,
+                excluded_paths: None,
+                tag: None,
+                base: None,
+            };
+            Collector::default()
+                .recurse(depth, SameDomainPolicy::SameHost)
+                .collect_links(vec![input])
+                .map(|r| r.unwrap().uri)
+                .collect::<HashSet<_>>()
+        };
+
+        let links = collect_recursive(1).await;
+        let expected_urls = HashSet::from_iter([
+            website(&format!("{server_uri}a")),
+            website("https://github.com/"),
+            website(&format!("{server_uri}#top")),
+            website(&format!("{server_uri}b")),
+        ]);
+        assert_eq!(links, expected_urls);
+
+        // `/#top` points back to the input, which isn't collected twice
+        let links = collect_recursive(5).await;
+        assert_eq!(links.len(), expected_urls.len() + 1);
+        assert!(links.contains(&website(&format!("{server_uri}c"))));
+    }
+
+    #[tokio::test]
+    async fn test_resume_crawl() {
+        let mock_server = wiremock::MockServer::start().await;
+        let server_uri = Url::parse(&mock_server.uri()).unwrap();
+
+        for (page, body) in [
+            ("/", r#"<a href="/a">A</a>"#),
+            ("/a", r#"<a href="/#top">Home</a><a href="/b">B</a>"#),
+            ("/b", r#"<a href="/c">C</a>"#),
+        ] {
+            wiremock::Mock::given(wiremock::matchers::path(page))
+                .respond_with(wiremock::ResponseTemplate::new(StatusCode::OK).set_body_string(body))
+                .mount(&mock_server)
+                .await;
+        }
+
+        // The previous run collected the input and was stopped at `/a`
+        let dir = tempfile::tempdir().unwrap();
+        let state = dir.path().join("crawl.log");
+        let log = format!("root\t{server_uri}\npage\t1\t{server_uri}\t{server_uri}a\ndone\t0\n");
+        fs::write(&state, &log).unwrap();
+
+        let collect = || async {
+            let input = Input {
+                source: InputSource::RemoteUrl(Box::new(server_uri.clone())),
+                file_type_hint: None,
+                excluded_paths: None,
+                tag: None,
+                base: None,
+            };
+            Collector::default()
+                .recurse(5, SameDomainPolicy::SameHost)
+                .crawl_state(Some(state.clone()))
+                .collect_links(vec![input])
+                .map(|r| r.unwrap().uri)
+                .collect::<HashSet<_>>()
+                .await
+        };
+
+        // The links of the completed depth are reported again
+        let expected_urls = HashSet::from_iter([
+            website(&format!("{server_uri}a")),
+            website(&format!("{server_uri}#top")),
+            website(&format!("{server_uri}b")),
+            website(&format!("{server_uri}c")),
+        ]);
+        assert_eq!(collect().await, expected_urls);
+        // The complete crawl starts over next time
+        assert!(!state.exists());
+
+        // Logs of crawls from other inputs are discarded
+        fs::write(
+            &state,
+            "root\thttps://example.com/\npage\t1\thttps://example.com/\thttps://example.com/a\ndone\t1\n",
+        )
+        .unwrap();
+        assert_eq!(collect().await, expected_urls);
+        assert!(!state.exists());
+    }
+
+    #[tokio::test]
+    async fn test_reference_issues() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("README.md");
+        fs::write(
+            &path,
+            "[Docs][docs] and [more][missing]\n\n[docs]: https://example.com/docs\n",
+        )
+        .unwrap();
+        let input = Input {
+            source: InputSource::FsPath(path.clone()),
+            file_type_hint: None,
+            excluded_paths: None,
+            tag: None,
+            base: None,
+        };
+
+        let collector = Collector::new(None, None).unwrap().check_references(true);
+        let reference_issues = collector.reference_issues();
+        let links = collector
+            .collect_links(vec![input])
+            .map(|request| request.unwrap().uri)
+            .collect::<HashSet<_>>()
+            .await;
+        assert_eq!(links, HashSet::from([website("https://example.com/docs")]));
+
+        let issues = reference_issues.lock().unwrap();
+        let kinds: Vec<_> = issues[&InputSource::FsPath(path)]
+            .iter()
+            .map(|issue| &issue.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![&ReferenceIssueKind::UndefinedReference(
+                "missing".to_string()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_skip_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("image.png");
+        fs::write(&binary, b"\x89PNG\0\0https://example.com/binary").unwrap();
+        let text = dir.path().join("README.md");
+        fs::write(&text, "https://example.com/text").unwrap();
+
+        let inputs = || {
+            [&binary, &text]
+                .map(|path| Input {
+                    source: InputSource::FsPath(path.clone()),
+                    file_type_hint: None,
+                    excluded_paths: None,
+                    tag: None,
+                    base: None,
+                })
+                .to_vec()
+        };
+
+        let collector = Collector::new(None, None).unwrap();
+        let skipped_binaries = collector.skipped_binaries();
+        let links = collector
+            .collect_links(inputs())
+            .map(|request| request.unwrap().uri)
+            .collect::<HashSet<_>>()
+            .await;
+        assert_eq!(links, HashSet::from([website("https://example.com/text")]));
+        assert_eq!(
+            *skipped_binaries.lock().unwrap(),
+            HashSet::from([InputSource::FsPath(binary.clone())])
+        );
+
+        let links = Collector::new(None, None)
+            .unwrap()
+            .include_binary(true)
+            .collect_links(inputs())
+            .map(|request| request.unwrap().uri)
+            .collect::<HashSet<_>>()
+            .await;
+        assert_eq!(links.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_file_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        // Without a custom extension, this would be skipped in directories
+        fs::write(
+            dir.path().join("guide.mdoc"),
+            "[guide](https://example.com/guide)",
+        )
+        .unwrap();
+        // Plaintext files are only checked in directories if they have a
+        // custom extension
+        fs::write(
+            dir.path().join("notes.txt.j2"),
+            "See https://example.com/template",
+        )
+        .unwrap();
+        fs::write(dir.path().join("notes.txt"), "https://example.com/txt").unwrap();
+
+        let input = Input {
+            source: InputSource::FsPath(dir.path().to_path_buf()),
+            file_type_hint: None,
+            excluded_paths: None,
+            tag: None,
+            base: None,
+        };
+        let file_extensions = FileExtensions::new([
+            ("mdoc".to_string(), FileType::Markdown),
+            ("txt.j2".to_string(), FileType::Plaintext),
+        ]);
+        let links = Collector::new(None, None)
+            .unwrap()
+            .file_extensions(file_extensions)
+            .collect_links(vec![input])
+            .map(|request| request.unwrap().uri)
+            .collect::<HashSet<_>>()
+            .await;
+        assert_eq!(
+            links,
+            HashSet::from([
+                website("https://example.com/guide"),
+                website("https://example.com/template"),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extensionless_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let readme = dir.path().join("README");
+        fs::write(
+            &readme,
+            "# Project\n\n[docs](https://example.com/docs)\n\n```\nhttps://example.com/code\n```\n",
+        )
+        .unwrap();
+
+        let links = |file_extensions: FileExtensions| {
+            let input = Input {
+                source: InputSource::FsPath(readme.clone()),
+                file_type_hint: None,
+                excluded_paths: None,
+                tag: None,
+                base: None,
+            };
+            Collector::new(None, None)
+                .unwrap()
+                .file_extensions(file_extensions)
+                .collect_links(vec![input])
+                .map(|request| request.unwrap().uri)
+                .collect::<HashSet<_>>()
+        };
+
+        // Sniffed as Markdown, which skips code blocks
+        assert_eq!(
+            links(FileExtensions::default()).await,
+            HashSet::from([website("https://example.com/docs")])
+        );
+        assert_eq!(
+            links(FileExtensions::default().extensionless(Some(FileType::Plaintext))).await,
+            HashSet::from([
+                website("https://example.com/docs"),
+                website("https://example.com/code"),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_large_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let html = dir.path().join("index.html");
+        fs::write(
+            &html,
+            r#"<a href="https://example.com/html">x</a><pre>https://example.com/pre</pre>"#,
+        )
+        .unwrap();
+        let markdown = dir.path().join("README.md");
+        fs::write(&markdown, "[docs](https://example.com/markdown)").unwrap();
+        let binary = dir.path().join("image.png");
+        fs::write(&binary, b"\x89PNG\0\0https://example.com/binary").unwrap();
+
+        let inputs = [&html, &markdown, &binary]
+            .map(|path| Input {
+                source: InputSource::FsPath(path.clone()),
+                file_type_hint: None,
+                excluded_paths: None,
+                tag: None,
+                base: None,
+            })
+            .to_vec();
+
+        let collector = Collector::new(None, None).unwrap().stream_threshold(0);
+        let skipped_binaries = collector.skipped_binaries();
+        let links = collector
+            .collect_links(inputs)
+            .map(|request| request.unwrap().uri)
+            .collect::<HashSet<_>>()
+            .await;
+        assert_eq!(
+            links,
+            HashSet::from([
+                website("https://example.com/html"),
+                website("https://example.com/markdown"),
+            ])
+        );
+        assert_eq!(
+            *skipped_binaries.lock().unwrap(),
+            HashSet::from([InputSource::FsPath(binary)])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_max_files_and_links() {
+        let inputs = || {
+            ["https://a.com", "https://b.com", "https://c.com"]
+                .map(|s| Input {
+                    source: InputSource::String(s.to_string()),
+                    file_type_hint: None,
+                    excluded_paths: None,
+                    tag: None,
+                    base: None,
+                })
+                .to_vec()
+        };
+        let collect_limited = |max_files, max_links| async move {
+            Collector::new(None, None)
+                .unwrap()
+                .max_files(max_files)
+                .max_links(max_links)
+                .collect_links(inputs())
+                .collect::<Vec<_>>()
+                .await
+        };
+
+        let results = collect_limited(Some(3), Some(3)).await;
+        assert!(results.iter().all(Result::is_ok));
+
+        let results = collect_limited(Some(2), None).await;
+        assert!(matches!(
+            results.last(),
+            Some(Err(ErrorKind::TooManyFiles(2)))
+        ));
+
+        let results = collect_limited(None, Some(2)).await;
+        assert_eq!(results.len(), 3);
+        assert!(matches!(
+            results.last(),
+            Some(Err(ErrorKind::TooManyLinks(2)))
+        ));
+    }
+
     #[tokio::test]
     async fn test_email_with_query_params() {
         let input = Input {
@@ -466,6 +1536,8 @@ mod tests {
             ),
             file_type_hint: None,
             excluded_paths: None,
+            tag: None,
+            base: None,
         };
         let links = collect(vec![input], None, None).await.ok().unwrap();
 
@@ -474,6 +1546,112 @@ mod tests {
         assert_eq!(links, expected_links);
     }
 
+    #[tokio::test]
+    async fn test_generated_markers() {
+        let input = Input {
+            source: InputSource::String(
+                "https://example.com/a\nhttps://example.com/e\n<!-- AUTO-GENERATED -->\nhttps://example.com/b\nhttps://example.com/c\nhttps://example.com/d\nhttps://example.com/e\n<!-- END -->\nhttps://example.com/c\nhttps://example.com/a"
+                    .to_string(),
+            ),
+            file_type_hint: Some(FileType::Plaintext),
+            excluded_paths: None,
+            tag: None,
+            base: None,
+        };
+        let generated: HashSet<(String, bool)> = Collector::default()
+            .generated_markers(Some(GeneratedMarkers::default()))
+            .collect_links(vec![input])
+            .map(|request| {
+                let request = request.unwrap();
+                (request.uri.to_string(), request.generated)
+            })
+            .collect()
+            .await;
+
+        assert_eq!(
+            generated,
+            HashSet::from_iter([
+                ("https://example.com/a".to_string(), false),
+                ("https://example.com/b".to_string(), true),
+                // Links which also occur outside of the generated region
+                // aren't generated, no matter which occurrence comes first
+                ("https://example.com/c".to_string(), false),
+                ("https://example.com/d".to_string(), true),
+                ("https://example.com/e".to_string(), false),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_input_tags() {
+        let inputs = [
+            ("https://example.com/docs", Some("docs")),
+            ("https://example.com/blog", None),
+        ]
+        .map(|(content, tag)| {
+            Input {
+                source: InputSource::String(content.to_string()),
+                file_type_hint: Some(FileType::Plaintext),
+                excluded_paths: None,
+                tag: None,
+                base: None,
+            }
+            .with_tag(tag.map(ToString::to_string))
+        });
+        let tags: HashSet<(String, Option<String>)> = Collector::default()
+            .collect_links(inputs.to_vec())
+            .map(|request| {
+                let request = request.unwrap();
+                (request.uri.to_string(), request.tag)
+            })
+            .collect()
+            .await;
+
+        assert_eq!(
+            tags,
+            HashSet::from_iter([
+                (
+                    "https://example.com/docs".to_string(),
+                    Some("docs".to_string())
+                ),
+                ("https://example.com/blog".to_string(), None),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_input_base() {
+        let inputs = ["https://a.example.com/", "https://b.example.com/"].map(|base| {
+            Input {
+                source: InputSource::String(r#"<a href="guide.html">Guide</a>"#.to_string()),
+                file_type_hint: Some(FileType::Html),
+                excluded_paths: None,
+                tag: None,
+                base: None,
+            }
+            .with_base(Some(Base::try_from(base).unwrap()))
+        });
+        let mut inputs = inputs.to_vec();
+        // Inputs without a base fall back to the base of the collector
+        inputs.push(Input {
+            source: InputSource::String(r#"<a href="index.html">Index</a>"#.to_string()),
+            file_type_hint: Some(FileType::Html),
+            excluded_paths: None,
+            tag: None,
+            base: None,
+        });
+        let base = Base::try_from("https://example.com/").unwrap();
+
+        let links = collect(inputs, None, Some(base)).await.ok().unwrap();
+
+        let expected_links = HashSet::from_iter([
+            website("https://a.example.com/guide.html"),
+            website("https://b.example.com/guide.html"),
+            website("https://example.com/index.html"),
+        ]);
+        assert_eq!(links, expected_links);
+    }
+
     #[tokio::test]
     async fn test_multiple_remote_urls() {
         let mock_server_1 = mock_server!(
@@ -496,6 +1674,8 @@ mod tests {
                 )),
                 file_type_hint: Some(FileType::Html),
                 excluded_paths: None,
+                tag: None,
+                base: None,
             },
             Input {
                 source: InputSource::RemoteUrl(Box::new(
@@ -507,6 +1687,8 @@ mod tests {
                 )),
                 file_type_hint: Some(FileType::Html),
                 excluded_paths: None,
+                tag: None,
+                base: None,
             },
         ];
 
@@ -542,6 +1724,8 @@ mod tests {
             ),
             file_type_hint: Some(FileType::Html),
             excluded_paths: None,
+            tag: None,
+            base: None,
         };
 
         let links = collect(vec![input], None, Some(base)).await.ok().unwrap();
@@ -0,0 +1,206 @@
+//! Comments of languages with C-like syntax (`//` and `/* */`)
+
+use super::{char_len, skip_string, Comment, Comments};
+
+/// Differences between the supported languages with C-like comments
+#[derive(Debug)]
+pub(super) struct Syntax {
+    /// Quotes of string literals which may contain comment markers
+    quotes: &'static [&'static str],
+    /// Block comments can be nested, as in `/* a /* b */ c */`
+    nested_blocks: bool,
+    /// Support for Rust raw strings (`r#"..."#`) and char literals, which
+    /// have to be told apart from lifetimes (`'a`)
+    rust_literals: bool,
+}
+
+pub(super) const RUST: Syntax = Syntax {
+    quotes: &["\""],
+    nested_blocks: true,
+    rust_literals: true,
+};
+
+pub(super) const JAVASCRIPT: Syntax = Syntax {
+    quotes: &["\"", "'", "`"],
+    nested_blocks: false,
+    rust_literals: false,
+};
+
+/// Collect the comments of `input`
+pub(super) fn comments<'a>(input: &'a str, syntax: &Syntax) -> Vec<Comment<'a>> {
+    let mut comments = Comments::default();
+    let mut pos = 0;
+    while pos < input.len() {
+        let rest = &input[pos..];
+        if rest.starts_with("//") {
+            let marker = if rest.starts_with("///") && !rest.starts_with("////") {
+                "///"
+            } else if rest.starts_with("//!") {
+                "//!"
+            } else {
+                "//"
+            };
+            let end = rest.find('\n').map_or(input.len(), |i| pos + i);
+            comments.line_comment(input, marker, pos, end);
+            pos = end;
+        } else if rest.starts_with("/*") {
+            let (end, text_end) = block_end(input, pos, syntax.nested_blocks);
+            // Skip the `*` of `/**` and the `!` of `/*!`, unless the comment
+            // is empty (`/**/`)
+            let mut start = pos + 2;
+            if start < text_end && matches!(input.as_bytes()[start], b'*' | b'!') {
+                start += 1;
+            }
+            comments.block_comment(input, start, text_end.max(start), true);
+            pos = end;
+        } else if let Some(quote) = syntax.quotes.iter().find(|quote| rest.starts_with(**quote)) {
+            pos = skip_string(input, pos, quote);
+        } else if syntax.rust_literals && rest.starts_with('\'') {
+            pos = skip_char_literal(input, pos);
+        } else if let Some(end) = syntax
+            .rust_literals
+            .then(|| skip_raw_string(input, pos))
+            .flatten()
+        {
+            pos = end;
+        } else {
+            pos += char_len(input, pos);
+        }
+    }
+    comments.comments
+}
+
+/// Find the end of the block comment starting at `pos`.
+///
+/// Returns the position after the comment and the position of its closing
+/// `*/`, which are the end of the input for unterminated comments.
+fn block_end(input: &str, pos: usize, nested: bool) -> (usize, usize) {
+    let mut depth = 0;
+    let mut pos = pos;
+    while pos < input.len() {
+        let rest = &input[pos..];
+        if rest.starts_with("/*") && (depth == 0 || nested) {
+            depth += 1;
+            pos += 2;
+        } else if rest.starts_with("*/") {
+            depth -= 1;
+            if depth == 0 {
+                return (pos + 2, pos);
+            }
+            pos += 2;
+        } else {
+            pos += char_len(input, pos);
+        }
+    }
+    (input.len(), input.len())
+}
+
+/// Skip a Rust char literal like `'x'` or `'\n'` at `pos`.
+/// Lifetimes like `'a` are skipped by a single character.
+fn skip_char_literal(input: &str, pos: usize) -> usize {
+    let rest = &input[pos + 1..];
+    if rest.starts_with('\\') {
+        // The escaped character might be a quote itself
+        return rest
+            .get(2..)
+            .and_then(|escape| escape.find('\''))
+            .map_or(input.len(), |i| pos + 3 + i + 1);
+    }
+    let Some(c) = rest.chars().next() else {
+        return input.len();
+    };
+    if rest[c.len_utf8()..].starts_with('\'') {
+        pos + 1 + c.len_utf8() + 1
+    } else {
+        pos + 1
+    }
+}
+
+/// Skip a Rust raw string like `r#"..."#` or `br"..."` at `pos`, if there
+/// is one
+fn skip_raw_string(input: &str, pos: usize) -> Option<usize> {
+    let follows_identifier = input[..pos]
+        .chars()
+        .next_back()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_');
+    if follows_identifier {
+        return None;
+    }
+    let rest = &input[pos..];
+    let rest = rest
+        .strip_prefix("br")
+        .or_else(|| rest.strip_prefix("cr"))
+        .or_else(|| rest.strip_prefix('r'))?;
+    let hashes = rest.len() - rest.trim_start_matches('#').len();
+    let rest = rest[hashes..].strip_prefix('"')?;
+    let closing = format!("\"{}", "#".repeat(hashes));
+    let start = input.len() - rest.len();
+    Some(
+        rest.find(&closing)
+            .map_or(input.len(), |i| start + i + closing.len()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(input: &str, syntax: &Syntax) -> Vec<String> {
+        comments(input, syntax).iter().map(Comment::text).collect()
+    }
+
+    #[test]
+    fn test_rust() {
+        let input = r##"
+//! Crate docs
+use std::fmt;
+
+/* Block /* nested */ comment */
+fn main() {
+    let s = "// not a comment";
+    let raw = r#"/* not a "comment" */"#;
+    let c = '"';
+    let escaped = '\'';
+    let slash = '/';
+    println!("{s}"); // trailing
+}
+
+struct Ref<'a>(&'a str); // after lifetime
+"##;
+        assert_eq!(
+            texts(input, &RUST),
+            [
+                "Crate docs",
+                "Block /* nested */ comment",
+                "trailing",
+                "after lifetime"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_javascript() {
+        let input = r#"
+/**
+ * Fetch the user.
+ * @see {@link https://example.com/api}
+ */
+const url = `https://example.com/${id}`; // template
+const quote = '/* not a comment */';
+"#;
+        assert_eq!(
+            texts(input, &JAVASCRIPT),
+            [
+                "\nFetch the user.\n@see {@link https://example.com/api}\n",
+                "template"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unterminated() {
+        assert_eq!(texts("/* open", &RUST), ["open"]);
+        assert_eq!(texts("\"open // string", &RUST), Vec::<String>::new());
+        assert_eq!(texts("'", &RUST), Vec::<String>::new());
+    }
+}
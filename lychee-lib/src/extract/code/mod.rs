@@ -0,0 +1,334 @@
+//! Extract links from the comments of source code files
+//!
+//! By default, source files are treated as plaintext, so links in string
+//! literals get checked as well (e.g. URL templates or test data) and
+//! Markdown links in doc comments which span multiple lines are missed.
+//! Instead, the comments of each supported language are collected and the
+//! comment markers (like `///` or ` * `) are removed. Doc comments of Rust
+//! and JavaScript are Markdown, the comments of other languages plaintext.
+//!
+//! The scanners for each language only know about comments and string
+//! literals; they don't parse the code.
+use std::path::Path;
+
+use super::{markdown::extract_markdown, plaintext::extract_raw_uri_from_plaintext};
+use crate::types::{
+    uri::raw::{RawUri, RawUriSpan, SourceSpanProvider, SpanProvider},
+    InputSource,
+};
+
+mod c_like;
+mod python;
+mod shell;
+
+/// Programming languages whose comments can be extracted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Language {
+    /// `//` and `/* */` comments, including doc comments (`///`, `//!`)
+    Rust,
+    /// `//` and `/* */` comments, including JSDoc (`/** */`).
+    /// Also used for TypeScript.
+    JavaScript,
+    /// `#` comments and docstrings
+    Python,
+    /// `#` comments of POSIX shells, Bash and Zsh
+    Shell,
+}
+
+impl Language {
+    /// Detect the language of a file from its extension
+    pub(crate) fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "rs" => Some(Self::Rust),
+            "js" | "mjs" | "cjs" | "jsx" | "ts" | "mts" | "cts" | "tsx" => Some(Self::JavaScript),
+            "py" | "pyi" => Some(Self::Python),
+            "sh" | "bash" | "zsh" | "ksh" => Some(Self::Shell),
+            _ => None,
+        }
+    }
+
+    /// Detect the language of an input from its path or URL
+    pub(crate) fn from_source(source: &InputSource) -> Option<Self> {
+        source.file_path().and_then(Self::from_path)
+    }
+
+    /// Whether the comments of the language are written in Markdown
+    const fn is_markdown(self) -> bool {
+        matches!(self, Self::Rust | Self::JavaScript)
+    }
+}
+
+/// The text of a comment without comment markers
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Comment<'a> {
+    /// Each line of the text and its byte offset in the source
+    lines: Vec<(usize, &'a str)>,
+}
+
+impl<'a> Comment<'a> {
+    fn push(&mut self, offset: usize, line: &'a str) {
+        self.lines.push((offset, line.trim_end()));
+    }
+
+    fn text(&self) -> String {
+        let lines: Vec<_> = self.lines.iter().map(|(_, line)| *line).collect();
+        lines.join("\n")
+    }
+
+    /// Map the span of a link in [`Comment::text`] to its span in the source
+    fn source_span(&self, span: RawUriSpan, source: &SourceSpanProvider<'_>) -> Option<RawUriSpan> {
+        let (offset, _) = self.lines.get(span.line.get() - 1)?;
+        let start = source.span(*offset);
+        Some(RawUriSpan {
+            line: start.line,
+            column: start
+                .column
+                .zip(span.column)
+                .map(|(start, column)| start.saturating_add(column.get() - 1)),
+        })
+    }
+}
+
+/// The comments of a source file, where consecutive line comments with the
+/// same marker are merged into one comment
+#[derive(Debug, Default)]
+struct Comments<'a> {
+    comments: Vec<Comment<'a>>,
+    /// Marker and end of the last comment, if it was a line comment on a
+    /// line of its own
+    last_line_comment: Option<(&'static str, usize)>,
+}
+
+impl<'a> Comments<'a> {
+    /// Add the line comment starting with `marker` at `start` and ending
+    /// at `end` (the end of the line)
+    fn line_comment(&mut self, input: &'a str, marker: &'static str, start: usize, end: usize) {
+        let (offset, text) = strip_space(start + marker.len(), &input[start + marker.len()..end]);
+        let line_start = input[..start].rfind('\n').map_or(0, |i| i + 1);
+        let own_line = input[line_start..start].trim().is_empty();
+
+        let continues = own_line
+            && self
+                .last_line_comment
+                .is_some_and(|(last_marker, last_end)| {
+                    let between = &input[last_end..start];
+                    last_marker == marker
+                        && between.trim().is_empty()
+                        && between.matches('\n').count() == 1
+                });
+        match self.comments.last_mut() {
+            Some(comment) if continues => comment.push(offset, text),
+            _ => {
+                let mut comment = Comment::default();
+                comment.push(offset, text);
+                self.comments.push(comment);
+            }
+        }
+        self.last_line_comment = own_line.then_some((marker, end));
+    }
+
+    /// Add the block comment whose text is between `start` and `end`.
+    ///
+    /// With `strip_stars`, the indentation and a leading `*` are removed
+    /// from all but the first line, as in
+    ///
+    /// ```text
+    /// /**
+    ///  * Text
+    ///  */
+    /// ```
+    fn block_comment(&mut self, input: &'a str, start: usize, end: usize, strip_stars: bool) {
+        let mut comment = Comment::default();
+        let mut line_offset = start;
+        for (i, line) in input[start..end].split('\n').enumerate() {
+            let (mut offset, mut text) = (line_offset, line);
+            if i == 0 {
+                (offset, text) = strip_space(offset, text);
+            } else if strip_stars {
+                let trimmed = text.trim_start();
+                offset += text.len() - trimmed.len();
+                text = trimmed;
+                if let Some(rest) = text.strip_prefix('*') {
+                    (offset, text) = strip_space(offset + 1, rest);
+                }
+            }
+            comment.push(offset, text);
+            line_offset += line.len() + 1;
+        }
+        self.comments.push(comment);
+        self.last_line_comment = None;
+    }
+}
+
+/// Remove a single space after a comment marker
+fn strip_space(offset: usize, text: &str) -> (usize, &str) {
+    match text.strip_prefix(' ') {
+        Some(text) => (offset + 1, text),
+        None => (offset, text),
+    }
+}
+
+/// Byte length of the character at `pos`
+fn char_len(input: &str, pos: usize) -> usize {
+    input[pos..].chars().next().map_or(1, char::len_utf8)
+}
+
+/// Skip a string literal opened by `quote` at `pos`, where a backslash
+/// escapes the next character. Returns the position after the literal.
+fn skip_string(input: &str, pos: usize, quote: &str) -> usize {
+    let mut pos = pos + quote.len();
+    while pos < input.len() {
+        let rest = &input[pos..];
+        if rest.starts_with('\\') {
+            pos += 1 + char_len(input, pos + 1).min(rest.len() - 1);
+        } else if rest.starts_with(quote) {
+            return pos + quote.len();
+        } else {
+            pos += char_len(input, pos);
+        }
+    }
+    input.len()
+}
+
+/// Extract unparsed URL strings from the comments of a source file
+pub(crate) fn extract_comments(
+    input: &str,
+    language: Language,
+    include_verbatim: bool,
+) -> Vec<RawUri> {
+    let comments = match language {
+        Language::Rust => c_like::comments(input, &c_like::RUST),
+        Language::JavaScript => c_like::comments(input, &c_like::JAVASCRIPT),
+        Language::Python => python::comments(input),
+        Language::Shell => shell::comments(input),
+    };
+
+    let span_provider = SourceSpanProvider::from_input(input);
+    let mut uris = Vec::new();
+    for comment in comments {
+        let text = comment.text();
+        let comment_uris = if language.is_markdown() {
+            extract_markdown(&text, include_verbatim)
+        } else {
+            extract_raw_uri_from_plaintext(&text, &SourceSpanProvider::from_input(&text))
+        };
+        uris.extend(comment_uris.into_iter().map(|uri| {
+            RawUri {
+                span: uri
+                    .span
+                    .and_then(|span| comment.source_span(span, &span_provider)),
+                ..uri
+            }
+        }));
+    }
+    uris
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use reqwest::Url;
+
+    use super::*;
+    use crate::test_utils::span;
+
+    fn texts(uris: Vec<RawUri>) -> Vec<String> {
+        uris.into_iter().map(|uri| uri.text).collect()
+    }
+
+    #[test]
+    fn test_language() {
+        assert_eq!(Language::from_path("src/lib.rs"), Some(Language::Rust));
+        assert_eq!(Language::from_path("app.TSX"), Some(Language::JavaScript));
+        assert_eq!(Language::from_path("setup.py"), Some(Language::Python));
+        assert_eq!(Language::from_path("install.sh"), Some(Language::Shell));
+        assert_eq!(Language::from_path("README.md"), None);
+        assert_eq!(Language::from_path("Makefile"), None);
+
+        assert_eq!(
+            Language::from_source(&InputSource::FsPath(PathBuf::from("main.rs"))),
+            Some(Language::Rust)
+        );
+        assert_eq!(
+            Language::from_source(&InputSource::RemoteUrl(Box::new(
+                Url::parse("https://example.com/script.js?v=1").unwrap()
+            ))),
+            Some(Language::JavaScript)
+        );
+        assert_eq!(
+            Language::from_source(&InputSource::String("fn main() {}".into())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_skip_string_literals() {
+        let input = r#"
+            /// See the [docs](https://example.com/docs).
+            const URL: &str = "https://example.com/{id}";
+        "#;
+        assert_eq!(
+            texts(extract_comments(input, Language::Rust, false)),
+            ["https://example.com/docs"]
+        );
+    }
+
+    #[test]
+    fn test_link_across_lines() {
+        let input = "
+/// Parses the input as described in [the
+/// specification](https://example.com/spec).
+fn parse() {}";
+        assert_eq!(
+            extract_comments(input, Language::Rust, false),
+            [RawUri {
+                text: "https://example.com/spec".into(),
+                element: Some("a".into()),
+                attribute: Some("href".into()),
+                span: Some(span(2, 38)),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_spans() {
+        let input = "x = 1  # https://example.com/a\n\"\"\"\n    https://example.com/b\n\"\"\"";
+        let uris = extract_comments(input, Language::Python, false);
+        let spans: Vec<_> = uris.into_iter().map(|uri| uri.span).collect();
+        assert_eq!(spans, [Some(span(1, 10)), Some(span(3, 5))]);
+    }
+
+    #[test]
+    fn test_code_blocks_in_doc_comments() {
+        let input = "
+/// Example:
+///
+/// ```
+/// let client = Client::new(\"https://example.com/code\");
+/// ```
+fn example() {}";
+        assert!(extract_comments(input, Language::Rust, false).is_empty());
+        assert_eq!(
+            texts(extract_comments(input, Language::Rust, true)),
+            ["https://example.com/code"]
+        );
+    }
+
+    #[test]
+    fn test_merge_line_comments() {
+        let input = "// a\n  // b\nfoo(); // c\n// d\n\n// e\n/// f";
+        let comments = c_like::comments(input, &c_like::RUST);
+        let texts: Vec<_> = comments.iter().map(Comment::text).collect();
+        assert_eq!(texts, ["a\nb", "c", "d", "e", "f"]);
+    }
+
+    #[test]
+    fn test_block_comment() {
+        let input = "/**\n * Text\n *   indented\n */";
+        let comments = c_like::comments(input, &c_like::JAVASCRIPT);
+        let texts: Vec<_> = comments.iter().map(Comment::text).collect();
+        assert_eq!(texts, ["\nText\n  indented\n"]);
+    }
+}
@@ -0,0 +1,99 @@
+//! Comments and docstrings of Python
+
+use super::{char_len, skip_string, Comment, Comments};
+
+/// Collect the `#` comments and docstrings of `input`.
+///
+/// Triple-quoted strings which stand on their own as a statement are
+/// treated as docstrings; other string literals are skipped.
+pub(super) fn comments(input: &str) -> Vec<Comment<'_>> {
+    let mut comments = Comments::default();
+    let mut pos = 0;
+    while pos < input.len() {
+        let rest = &input[pos..];
+        if rest.starts_with('#') {
+            let end = rest.find('\n').map_or(input.len(), |i| pos + i);
+            comments.line_comment(input, "#", pos, end);
+            pos = end;
+        } else if let Some((quote_start, quote)) = string_start(input, pos) {
+            let end = skip_string(input, quote_start, quote);
+            if quote.len() == 3 && starts_statement(input, pos) {
+                let text_end = end.saturating_sub(3).max(quote_start + 3);
+                comments.block_comment(input, quote_start + 3, text_end, false);
+            }
+            pos = end;
+        } else {
+            pos += char_len(input, pos);
+        }
+    }
+    comments.comments
+}
+
+/// If a string literal starts at `pos`, return the position and kind of
+/// its opening quote. String literals can have prefixes like `r` or `f`.
+fn string_start(input: &str, pos: usize) -> Option<(usize, &'static str)> {
+    let follows_identifier = input[..pos]
+        .chars()
+        .next_back()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_');
+    if follows_identifier {
+        return None;
+    }
+    let rest = &input[pos..];
+    let prefix = rest
+        .bytes()
+        .take_while(|b| matches!(b.to_ascii_lowercase(), b'r' | b'b' | b'u' | b'f'))
+        .count();
+    if prefix > 2 {
+        return None;
+    }
+    let quote = ["\"\"\"", "'''", "\"", "'"]
+        .into_iter()
+        .find(|quote| rest[prefix..].starts_with(quote))?;
+    Some((pos + prefix, quote))
+}
+
+/// Whether only indentation precedes `pos` on its line
+fn starts_statement(input: &str, pos: usize) -> bool {
+    let line_start = input[..pos].rfind('\n').map_or(0, |i| i + 1);
+    input[line_start..pos].trim().is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(input: &str) -> Vec<String> {
+        comments(input).iter().map(Comment::text).collect()
+    }
+
+    #[test]
+    fn test_python() {
+        let input = r#"#!/usr/bin/env python
+"""Module docs."""
+
+# Line comment
+# continued
+URL = "https://example.com/#{id}"
+TEMPLATE = """
+# not a comment
+"""
+
+def f():
+    r'''
+    Function docs
+    '''
+    return 'a # b'  # trailing
+"#;
+        assert_eq!(
+            texts(input),
+            [
+                "!/usr/bin/env python",
+                "Module docs.",
+                "Line comment\ncontinued",
+                "\n    Function docs\n",
+                "trailing"
+            ]
+        );
+    }
+}
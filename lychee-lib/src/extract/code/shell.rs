@@ -0,0 +1,60 @@
+//! Comments of shell scripts
+
+use super::{char_len, skip_string, Comment, Comments};
+
+/// Collect the `#` comments of `input`.
+///
+/// A `#` only starts a comment at the beginning of a word, so parameter
+/// expansions like `$#` or `${#array[@]}` are no comments.
+pub(super) fn comments(input: &str) -> Vec<Comment<'_>> {
+    let mut comments = Comments::default();
+    let mut pos = 0;
+    while pos < input.len() {
+        let rest = &input[pos..];
+        if rest.starts_with('#') && starts_word(input, pos) {
+            let end = rest.find('\n').map_or(input.len(), |i| pos + i);
+            comments.line_comment(input, "#", pos, end);
+            pos = end;
+        } else if rest.starts_with('\'') {
+            // No escapes in single quotes
+            pos = rest[1..]
+                .find('\'')
+                .map_or(input.len(), |i| pos + 1 + i + 1);
+        } else if rest.starts_with('"') {
+            pos = skip_string(input, pos, "\"");
+        } else if rest.starts_with('\\') {
+            pos += 1 + char_len(input, pos + 1).min(rest.len() - 1);
+        } else {
+            pos += char_len(input, pos);
+        }
+    }
+    comments.comments
+}
+
+fn starts_word(input: &str, pos: usize) -> bool {
+    input[..pos].chars().next_back().map_or(true, |c| {
+        c.is_whitespace() || matches!(c, ';' | '&' | '|' | '(' | ')')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell() {
+        let input = r##"#!/bin/sh
+# Install script, see https://example.com/install
+echo "# not a comment" 'it''s # not' \# escaped
+echo $# ${#args[@]} a#b # trailing
+"##;
+        let texts: Vec<_> = comments(input).iter().map(Comment::text).collect();
+        assert_eq!(
+            texts,
+            [
+                "!/bin/sh\nInstall script, see https://example.com/install",
+                "trailing"
+            ]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::cell::RefCell;
+use std::{cell::RefCell, num::NonZeroUsize};
 
 use html5ever::{
     buffer_queue::BufferQueue,
@@ -9,7 +9,27 @@ use html5ever::{
 use super::{
     super::plaintext::extract_raw_uri_from_plaintext, is_email_link, is_verbatim_elem, srcset,
 };
-use crate::types::uri::raw::RawUri;
+use crate::types::uri::raw::{RawUri, RawUriSpan, SpanProvider};
+
+/// html5ever only reports the line of a token, so all links extracted from a
+/// token share the same span without a column.
+struct LineSpanProvider(RawUriSpan);
+
+impl LineSpanProvider {
+    fn new(line_number: u64) -> Self {
+        let line = usize::try_from(line_number)
+            .ok()
+            .and_then(NonZeroUsize::new)
+            .unwrap_or(NonZeroUsize::MIN);
+        Self(RawUriSpan { line, column: None })
+    }
+}
+
+impl SpanProvider for LineSpanProvider {
+    fn span(&self, _offset: usize) -> RawUriSpan {
+        self.0
+    }
+}
 
 #[derive(Clone, Default)]
 struct LinkExtractor {
@@ -22,7 +42,8 @@ impl TokenSink for LinkExtractor {
     type Handle = ();
 
     #[allow(clippy::match_same_arms)]
-    fn process_token(&self, token: Token, _line_number: u64) -> TokenSinkResult<()> {
+    fn process_token(&self, token: Token, line_number: u64) -> TokenSinkResult<()> {
+        let span_provider = LineSpanProvider::new(line_number);
         match token {
             Token::CharacterTokens(raw) => {
                 if self.current_verbatim_element_name.borrow().is_some() {
@@ -31,7 +52,7 @@ impl TokenSink for LinkExtractor {
                 if self.include_verbatim {
                     self.links
                         .borrow_mut()
-                        .extend(extract_raw_uri_from_plaintext(&raw));
+                        .extend(extract_raw_uri_from_plaintext(&raw, &span_provider));
                 }
             }
             Token::TagToken(tag) => {
@@ -100,7 +121,7 @@ impl TokenSink for LinkExtractor {
                     );
 
                     let new_urls = match urls {
-                        None => extract_raw_uri_from_plaintext(&attr.value),
+                        None => extract_raw_uri_from_plaintext(&attr.value, &span_provider),
                         Some(urls) => urls
                             .into_iter()
                             .filter(|url| {
@@ -136,6 +157,7 @@ impl TokenSink for LinkExtractor {
                                 text: url.to_string(),
                                 element: Some(name.to_string()),
                                 attribute: Some(attr.name.local.to_string()),
+                                span: Some(span_provider.0),
                             })
                             .collect::<Vec<_>>(),
                     };
@@ -193,10 +215,16 @@ impl LinkExtractor {
             | ("object", "classid")
             | ("object", "codebase")
             | ("object", "data")
-            | ("video", "poster") => {
+            | ("video", "poster")
+            // Links of SVG 1.1, e.g. `<use xlink:href="#icon">`
+            | (_, "xlink:href")
+            // Images of lazy-loading scripts
+            | (_, "data-src") => {
                 Some(vec![attr_value].into_iter())
             }
-            (_, "srcset") => {
+            // Responsive images, including those of `<link rel="preload">`
+            // and lazy-loading scripts
+            (_, "srcset" | "data-srcset") | ("link", "imagesrcset") => {
                 Some(srcset::parse(attr_value).into_iter())
             }
             _ => None,
@@ -223,6 +251,13 @@ pub(crate) fn extract_html(buf: &str, include_verbatim: bool) -> Vec<RawUri> {
 mod tests {
     use super::*;
 
+    fn line_span(line: usize) -> RawUriSpan {
+        RawUriSpan {
+            line: NonZeroUsize::new(line).unwrap(),
+            column: None,
+        }
+    }
+
     const HTML_INPUT: &str = r#"
 <html>
     <body>
@@ -243,6 +278,7 @@ mod tests {
             text: "https://example.org".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            span: Some(line_span(4)),
         }];
 
         let uris = extract_html(HTML_INPUT, false);
@@ -256,26 +292,31 @@ mod tests {
                 text: "https://example.com".to_string(),
                 element: None,
                 attribute: None,
+                span: Some(line_span(4)),
             },
             RawUri {
                 text: "https://example.org".to_string(),
                 element: Some("a".to_string()),
                 attribute: Some("href".to_string()),
+                span: Some(line_span(4)),
             },
             RawUri {
                 text: "https://foo.com".to_string(),
                 element: None,
                 attribute: None,
+                span: Some(line_span(7)),
             },
             RawUri {
                 text: "http://bar.com/some/path".to_string(),
                 element: None,
                 attribute: None,
+                span: Some(line_span(7)),
             },
             RawUri {
                 text: "https://baz.org".to_string(),
                 element: Some("a".to_string()),
                 attribute: Some("href".to_string()),
+                span: Some(line_span(9)),
             },
         ];
 
@@ -298,6 +339,7 @@ mod tests {
             text: "https://example.com/".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            span: Some(line_span(2)),
         }];
 
         let uris = extract_html(HTML_INPUT, false);
@@ -315,6 +357,7 @@ mod tests {
             text: "https://example.org".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            span: Some(line_span(4)),
         }];
         let uris = extract_html(input, false);
         assert_eq!(uris, expected);
@@ -332,6 +375,7 @@ mod tests {
             text: "https://example.org".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            span: Some(line_span(5)),
         }];
         let uris = extract_html(input, false);
         assert_eq!(uris, expected);
@@ -354,6 +398,7 @@ mod tests {
             text: "mailto:foo@bar.com".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            span: Some(line_span(8)),
         }];
         let uris = extract_html(input, false);
         assert_eq!(uris, expected);
@@ -376,6 +421,7 @@ mod tests {
             text: "tel:1234567890".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            span: Some(line_span(8)),
         }];
         let uris = extract_html(input, false);
         assert_eq!(uris, expected);
@@ -456,6 +502,7 @@ mod tests {
             text: "https://example.com".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            span: Some(line_span(2)),
         }];
 
         let uris = extract_html(input, false);
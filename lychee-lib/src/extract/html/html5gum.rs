@@ -1,8 +1,194 @@
-use html5gum::{Emitter, Error, State, Tokenizer};
-use std::collections::{HashMap, HashSet};
+use html5gum::{Emitter, Error, Reader, State, Token, Tokenizer};
+use std::{
+    cell::Cell,
+    collections::{HashMap, HashSet},
+    convert::Infallible,
+    io::{self, Read},
+    rc::Rc,
+};
 
 use super::{is_email_link, is_verbatim_elem, srcset};
-use crate::{extract::plaintext::extract_raw_uri_from_plaintext, types::uri::raw::RawUri};
+use crate::{
+    extract::plaintext::extract_raw_uri_from_plaintext,
+    types::uri::raw::{
+        OffsetSpanProvider, RawUri, RawUriSpan, SourceSpanProvider, SpanProvider,
+        StreamSpanProvider,
+    },
+};
+
+/// Number of bytes read at once when streaming a document
+const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Plain text longer than this is scanned for links before the end of the
+/// text is reached, so that huge text nodes aren't kept in memory
+const MAX_RAW_STRING_LEN: usize = 1024 * 1024;
+
+/// A reader for an in-memory HTML document, which keeps track of how many
+/// bytes were consumed by the tokenizer.
+///
+/// html5gum doesn't report the position of tokens, so this is what
+/// the `LinkExtractor` uses to compute the span of a link.
+#[derive(Debug)]
+struct PositionReader<'a> {
+    input: &'a [u8],
+    position: Rc<Cell<usize>>,
+}
+
+impl PositionReader<'_> {
+    fn consume(&mut self, len: usize) -> &[u8] {
+        let (consumed, rest) = self.input.split_at(len);
+        self.input = rest;
+        self.position.set(self.position.get() + len);
+        consumed
+    }
+}
+
+impl Reader for PositionReader<'_> {
+    type Error = Infallible;
+
+    fn read_byte(&mut self) -> Result<Option<u8>, Self::Error> {
+        Ok(self.input.first().copied().inspect(|_| {
+            self.consume(1);
+        }))
+    }
+
+    fn read_until<'b>(
+        &'b mut self,
+        needle: &[u8],
+        _: &'b mut [u8; 4],
+    ) -> Result<Option<&'b [u8]>, Self::Error> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+        let len = match self.input.iter().position(|b| needle.contains(b)) {
+            Some(0) => 1,
+            Some(needle_pos) => needle_pos,
+            None => self.input.len(),
+        };
+        Ok(Some(self.consume(len)))
+    }
+
+    fn try_read_string(&mut self, s1: &[u8], case_sensitive: bool) -> Result<bool, Self::Error> {
+        if let Some(s2) = self.input.get(..s1.len()) {
+            if s1 == s2 || (!case_sensitive && s1.eq_ignore_ascii_case(s2)) {
+                self.consume(s1.len());
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// A reader for an HTML document which is streamed from `inner` (e.g. a large
+/// file) instead of being held in memory.
+///
+/// Like [`PositionReader`], it keeps track of how many bytes were consumed by
+/// the tokenizer. The consumed bytes are reported to a [`StreamSpanProvider`]
+/// to look up the line of a link.
+struct StreamReader<'a, R> {
+    inner: R,
+    /// Bytes read from `inner`; the bytes before `start` were consumed
+    buf: Vec<u8>,
+    start: usize,
+    /// The bytes returned by the last call to `read_until`
+    chunk: Vec<u8>,
+    position: Rc<Cell<usize>>,
+    lines: &'a StreamSpanProvider,
+}
+
+impl<'a, R: Read> StreamReader<'a, R> {
+    fn new(inner: R, position: Rc<Cell<usize>>, lines: &'a StreamSpanProvider) -> Self {
+        Self {
+            inner,
+            buf: Vec::with_capacity(STREAM_CHUNK_SIZE),
+            start: 0,
+            chunk: Vec::new(),
+            position,
+            lines,
+        }
+    }
+
+    /// Buffer at least `len` bytes, unless the end of the input is reached,
+    /// and return the unconsumed bytes
+    fn fill(&mut self, len: usize) -> io::Result<&[u8]> {
+        while self.buf.len() - self.start < len {
+            self.buf.drain(..self.start);
+            self.start = 0;
+
+            let end = self.buf.len();
+            self.buf.resize(end + STREAM_CHUNK_SIZE, 0);
+            let read = loop {
+                match self.inner.read(&mut self.buf[end..]) {
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    read => break read,
+                }
+            };
+            let read = match read {
+                Ok(read) => read,
+                Err(e) => {
+                    self.buf.truncate(end);
+                    return Err(e);
+                }
+            };
+            self.buf.truncate(end + read);
+            if read == 0 {
+                break;
+            }
+        }
+        Ok(&self.buf[self.start..])
+    }
+
+    fn consume(&mut self, len: usize) {
+        let offset = self.position.get();
+        self.lines
+            .advance(offset, &self.buf[self.start..self.start + len]);
+        self.position.set(offset + len);
+        self.start += len;
+    }
+}
+
+impl<R: Read> Reader for StreamReader<'_, R> {
+    type Error = io::Error;
+
+    fn read_byte(&mut self) -> Result<Option<u8>, Self::Error> {
+        let byte = self.fill(1)?.first().copied();
+        if byte.is_some() {
+            self.consume(1);
+        }
+        Ok(byte)
+    }
+
+    fn read_until<'b>(
+        &'b mut self,
+        needle: &[u8],
+        _: &'b mut [u8; 4],
+    ) -> Result<Option<&'b [u8]>, Self::Error> {
+        let available = self.fill(1)?;
+        if available.is_empty() {
+            return Ok(None);
+        }
+        let len = match available.iter().position(|b| needle.contains(b)) {
+            Some(0) => 1,
+            Some(needle_pos) => needle_pos,
+            None => available.len(),
+        };
+        self.chunk.clear();
+        self.chunk
+            .extend_from_slice(&self.buf[self.start..self.start + len]);
+        self.consume(len);
+        Ok(Some(&self.chunk))
+    }
+
+    fn try_read_string(&mut self, s1: &[u8], case_sensitive: bool) -> Result<bool, Self::Error> {
+        if let Some(s2) = self.fill(s1.len())?.get(..s1.len()) {
+            if s1 == s2 || (!case_sensitive && s1.eq_ignore_ascii_case(s2)) {
+                self.consume(s1.len());
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
 
 #[derive(Clone, Default, Debug)]
 struct Element {
@@ -24,13 +210,27 @@ struct Element {
 /// currently being processed.
 ///
 /// The `links` vector contains all links extracted from the HTML document and
-/// the `fragments` set contains all fragments extracted from the HTML document.
-#[derive(Clone, Default, Debug)]
-struct LinkExtractor {
+/// the `fragments` vector contains all fragments extracted from the HTML document.
+#[derive(Debug)]
+struct LinkExtractor<'a, S: SpanProvider> {
+    /// The HTML document being processed.
+    /// Empty if the document is streamed.
+    input: &'a [u8],
+    /// Number of bytes of the input consumed by the tokenizer so far.
+    position: Rc<Cell<usize>>,
+    /// Used to look up the span of a byte offset in the input.
+    span_provider: &'a S,
+    /// Byte offset of the current element in the input.
+    current_element_offset: usize,
+    /// Byte offset of the current plain characters in the input.
+    current_raw_string_offset: usize,
     /// Links extracted from the HTML document.
     links: Vec<RawUri>,
-    /// Fragments extracted from the HTML document.
-    fragments: HashSet<String>,
+    /// Fragments extracted from the HTML document with the position of their
+    /// element, in order of appearance.
+    fragments: Vec<(String, RawUriSpan)>,
+    /// URL of the first `<link rel="canonical">` element, if any.
+    canonical: Option<String>,
     /// Whether to include verbatim elements in the output.
     include_verbatim: bool,
     /// Current element being processed.
@@ -48,18 +248,54 @@ struct LinkExtractor {
     verbatim_stack: Vec<String>,
 }
 
-impl LinkExtractor {
+impl<'a, S: SpanProvider> LinkExtractor<'a, S> {
     /// Create a new `LinkExtractor`.
     ///
     /// Set `include_verbatim` to `true` if you want to include verbatim
     /// elements in the output.
-    fn new(include_verbatim: bool) -> Self {
+    fn new(
+        input: &'a str,
+        position: Rc<Cell<usize>>,
+        span_provider: &'a S,
+        include_verbatim: bool,
+    ) -> Self {
         Self {
+            input: input.as_bytes(),
+            position,
+            span_provider,
+            current_element_offset: 0,
+            current_raw_string_offset: 0,
+            links: Vec::new(),
+            fragments: Vec::new(),
+            canonical: None,
             include_verbatim,
-            ..Default::default()
+            current_element: Element::default(),
+            current_attributes: HashMap::new(),
+            current_attribute_name: String::new(),
+            current_raw_string: String::new(),
+            verbatim_stack: Vec::new(),
         }
     }
 
+    /// Remember the offset of the tag which is currently being opened.
+    ///
+    /// The tokenizer has already consumed the `<` and the first character of
+    /// the tag name when it starts a new tag, so search backwards for the
+    /// opening bracket.
+    fn init_element_offset(&mut self) {
+        let position = self.position.get();
+        self.current_element_offset = self
+            .input
+            .get(..position)
+            .and_then(|consumed| consumed.iter().rposition(|&b| b == b'<'))
+            .unwrap_or(position);
+    }
+
+    /// Span of the current element
+    fn current_element_span(&self) -> RawUriSpan {
+        self.span_provider.span(self.current_element_offset)
+    }
+
     /// Extract all semantically known links from a given HTML attribute.
     // For a comprehensive list of elements that might contain URLs/URIs
     // see https://www.w3.org/TR/REC-html40/index/attributes.html
@@ -67,12 +303,20 @@ impl LinkExtractor {
     fn extract_urls_from_elem_attr(&self) -> Vec<RawUri> {
         let mut urls = Vec::new();
 
-        // Process 'srcset' attribute first
-        if let Some(srcset) = self.current_attributes.get("srcset") {
+        // Process 'srcset' attributes first, including the responsive
+        // images of `<link rel="preload">` and lazy-loading scripts
+        for attr_name in ["srcset", "imagesrcset", "data-srcset"] {
+            if attr_name == "imagesrcset" && self.current_element.name != "link" {
+                continue;
+            }
+            let Some(srcset) = self.current_attributes.get(attr_name) else {
+                continue;
+            };
             urls.extend(srcset::parse(srcset).into_iter().map(|url| RawUri {
                 text: url.to_string(),
                 element: Some(self.current_element.name.clone()),
-                attribute: Some("srcset".to_string()),
+                attribute: Some(attr_name.to_string()),
+                span: Some(self.current_element_span()),
             }));
         }
 
@@ -95,11 +339,16 @@ impl LinkExtractor {
                 ("img", "longdesc") |
                 ("input", "formaction") |
                 ("object", "classid" | "codebase" | "data") |
-                ("video", "poster") => {
+                ("video", "poster") |
+                // Links of SVG 1.1, e.g. `<use xlink:href="#icon">`
+                (_, "xlink:href") |
+                // Images of lazy-loading scripts
+                (_, "data-src") => {
                     urls.push(RawUri {
                         text: attr_value.to_string(),
                         element: Some(self.current_element.name.clone()),
                         attribute: Some(attr_name.to_string()),
+                        span: Some(self.current_element_span()),
                     });
                 }
                 _ => {}
@@ -121,11 +370,40 @@ impl LinkExtractor {
             return;
         }
 
-        self.links
-            .extend(extract_raw_uri_from_plaintext(&self.current_raw_string));
+        let span_provider = OffsetSpanProvider {
+            offset: self.current_raw_string_offset,
+            inner: self.span_provider,
+        };
+        self.links.extend(extract_raw_uri_from_plaintext(
+            &self.current_raw_string,
+            &span_provider,
+        ));
         self.current_raw_string.clear();
     }
 
+    /// Extract links from the current string up to its last whitespace and
+    /// keep the rest, which might be the beginning of a link.
+    fn flush_complete_words(&mut self) {
+        let Some(end) = self.current_raw_string.rfind(char::is_whitespace) else {
+            return;
+        };
+        let rest = self.current_raw_string.split_off(end);
+        let in_verbatim =
+            is_verbatim_elem(&self.current_element.name) || !self.verbatim_stack.is_empty();
+        if self.include_verbatim || !in_verbatim {
+            let span_provider = OffsetSpanProvider {
+                offset: self.current_raw_string_offset,
+                inner: self.span_provider,
+            };
+            self.links.extend(extract_raw_uri_from_plaintext(
+                &self.current_raw_string,
+                &span_provider,
+            ));
+        }
+        self.current_raw_string = rest;
+        self.current_raw_string_offset += end;
+    }
+
     /// Update the current verbatim element name.
     ///
     /// Keeps track of the last verbatim element name, so that we can
@@ -155,13 +433,23 @@ impl LinkExtractor {
     ///   attribute, the current attribute value is ignored.
     /// - If the current attribute value is not a URL, it is treated as plain text and
     ///   added to the links vector.
-    /// - If the current attribute name is `id`, the current attribute value is added
-    ///   to the fragments set.
+    /// - If the current attribute name is `id` (or `name` on an `a` element), the
+    ///   current attribute value is added to the fragments.
     ///
     /// The current attribute name and value are cleared after processing.
     fn flush_links(&mut self) {
         self.update_verbatim_element();
 
+        if self.canonical.is_none() && self.current_element.name == "link" {
+            let is_canonical = self.current_attributes.get("rel").is_some_and(|rel| {
+                rel.split_ascii_whitespace()
+                    .any(|r| r.eq_ignore_ascii_case("canonical"))
+            });
+            if is_canonical {
+                self.canonical = self.current_attributes.get("href").cloned();
+            }
+        }
+
         if !self.include_verbatim
             && (!self.verbatim_stack.is_empty() || is_verbatim_elem(&self.current_element.name))
         {
@@ -223,15 +511,27 @@ impl LinkExtractor {
 
         self.links.extend(new_urls);
 
-        if let Some(id) = self.current_attributes.get("id") {
-            self.fragments.insert(id.to_string());
+        let id = self.current_attributes.get("id");
+        if let Some(id) = id {
+            let span = self.span_provider.span(self.current_element_offset);
+            self.fragments.push((id.to_string(), span));
+        }
+        // Legacy anchors like `<a name="section">` are valid fragment targets, too
+        if self.current_element.name == "a" {
+            if let Some(name) = self.current_attributes.get("name") {
+                // `<a id="x" name="x">` is a single target
+                if id != Some(name) {
+                    let span = self.span_provider.span(self.current_element_offset);
+                    self.fragments.push((name.to_string(), span));
+                }
+            }
         }
 
         self.current_attributes.clear();
     }
 }
 
-impl Emitter for &mut LinkExtractor {
+impl<S: SpanProvider> Emitter for &mut LinkExtractor<'_, S> {
     type Token = ();
 
     fn set_last_start_tag(&mut self, last_start_tag: Option<&[u8]>) {
@@ -255,13 +555,20 @@ impl Emitter for &mut LinkExtractor {
 
     /// Emit a bunch of plain characters as character tokens.
     fn emit_string(&mut self, c: &[u8]) {
+        if self.current_raw_string.is_empty() {
+            self.current_raw_string_offset = self.position.get().saturating_sub(c.len());
+        }
         self.current_raw_string
             .push_str(&String::from_utf8_lossy(c));
+        if self.current_raw_string.len() > MAX_RAW_STRING_LEN {
+            self.flush_complete_words();
+        }
     }
 
     fn init_start_tag(&mut self) {
         self.flush_current_characters();
         self.current_element = Element::default();
+        self.init_element_offset();
     }
 
     fn init_end_tag(&mut self) {
@@ -270,6 +577,7 @@ impl Emitter for &mut LinkExtractor {
             name: String::new(),
             is_closing: true,
         };
+        self.init_element_offset();
     }
 
     fn init_comment(&mut self) {
@@ -344,8 +652,26 @@ impl Emitter for &mut LinkExtractor {
 
 /// Extract unparsed URL strings from an HTML string.
 pub(crate) fn extract_html(buf: &str, include_verbatim: bool) -> Vec<RawUri> {
-    let mut extractor = LinkExtractor::new(include_verbatim);
-    let mut tokenizer = Tokenizer::new_with_emitter(buf, &mut extractor);
+    extract_html_with_span(buf, include_verbatim, &SourceSpanProvider::from_input(buf))
+}
+
+/// Extract unparsed URL strings from an HTML string, looking up the span of
+/// each URL with the given `span_provider`.
+///
+/// This is useful when the HTML is only a fragment of a larger document,
+/// e.g. inline HTML in a Markdown file.
+pub(crate) fn extract_html_with_span(
+    buf: &str,
+    include_verbatim: bool,
+    span_provider: &impl SpanProvider,
+) -> Vec<RawUri> {
+    let position = Rc::new(Cell::new(0));
+    let reader = PositionReader {
+        input: buf.as_bytes(),
+        position: Rc::clone(&position),
+    };
+    let mut extractor = LinkExtractor::new(buf, position, span_provider, include_verbatim);
+    let mut tokenizer = Tokenizer::new_with_emitter(reader, &mut extractor);
     assert!(tokenizer.next().is_none());
     extractor
         .links
@@ -354,17 +680,112 @@ pub(crate) fn extract_html(buf: &str, include_verbatim: bool) -> Vec<RawUri> {
         .collect()
 }
 
+/// Extract unparsed URL strings from an HTML document which is streamed from
+/// `reader`, so that the document doesn't have to fit into memory.
+///
+/// Spans only contain the line of each URL, not the column.
+pub(crate) fn extract_html_stream(
+    reader: impl Read,
+    include_verbatim: bool,
+) -> io::Result<Vec<RawUri>> {
+    let position = Rc::new(Cell::new(0));
+    let span_provider = StreamSpanProvider::default();
+    let reader = StreamReader::new(reader, Rc::clone(&position), &span_provider);
+    let mut extractor = LinkExtractor::new("", position, &span_provider, include_verbatim);
+    let mut tokenizer = Tokenizer::new_with_emitter(reader, &mut extractor);
+    if let Some(Err(e)) = tokenizer.next() {
+        return Err(e);
+    }
+    Ok(extractor
+        .links
+        .into_iter()
+        .filter(|link| link.attribute.is_some() || include_verbatim)
+        .collect())
+}
+
 /// Extract fragments from id attributes within a HTML string.
 pub(crate) fn extract_html_fragments(buf: &str) -> HashSet<String> {
-    let mut extractor = LinkExtractor::new(true);
+    let span_provider = SourceSpanProvider::from_input(buf);
+    extract_html_fragment_spans(buf, &span_provider)
+        .into_iter()
+        .map(|(fragment, _)| fragment)
+        .collect()
+}
+
+/// Extract fragments from id attributes within a HTML string, with the
+/// position of their element and in order of appearance. Fragments which
+/// occur more than once are repeated.
+pub(crate) fn extract_html_fragment_spans<S: SpanProvider>(
+    buf: &str,
+    span_provider: &S,
+) -> Vec<(String, RawUriSpan)> {
+    let mut extractor = LinkExtractor::new(buf, Rc::new(Cell::new(0)), span_provider, true);
     let mut tokenizer = Tokenizer::new_with_emitter(buf, &mut extractor);
     assert!(tokenizer.next().is_none());
     extractor.fragments
 }
 
+/// Extract the URL of the canonical version of an HTML document, given with
+/// `<link rel="canonical" href="...">`. The URL may be relative.
+pub(crate) fn extract_html_canonical(buf: &str) -> Option<String> {
+    let span_provider = SourceSpanProvider::from_input(buf);
+    let mut extractor = LinkExtractor::new(buf, Rc::new(Cell::new(0)), &span_provider, true);
+    let mut tokenizer = Tokenizer::new_with_emitter(buf, &mut extractor);
+    assert!(tokenizer.next().is_none());
+    extractor.canonical
+}
+
+/// Extract the text of the title (`<title>`) and of the first heading
+/// (`<h1>`) of an HTML document, with whitespace collapsed.
+pub(crate) fn extract_html_title_and_heading(buf: &str) -> (Option<String>, Option<String>) {
+    let mut title = None;
+    let mut heading = None;
+    // Name and text of the element whose text is collected
+    let mut open: Option<(Vec<u8>, String)> = None;
+    // Reading a string can't fail, and parse errors are skipped like other
+    // tokens
+    for token in Tokenizer::new(buf).flatten() {
+        match token {
+            Token::StartTag(tag) if open.is_none() => {
+                let name = tag.name.to_vec();
+                if (name == b"title" && title.is_none()) || (name == b"h1" && heading.is_none()) {
+                    open = Some((name, String::new()));
+                }
+            }
+            Token::String(text) => {
+                if let Some((_, content)) = &mut open {
+                    content.push_str(&String::from_utf8_lossy(&text));
+                }
+            }
+            Token::EndTag(tag)
+                if open
+                    .as_ref()
+                    .is_some_and(|(name, _)| tag.name.as_slice() == name.as_slice()) =>
+            {
+                let Some((name, content)) = open.take() else {
+                    continue;
+                };
+                let content = content.split_whitespace().collect::<Vec<_>>().join(" ");
+                let content = (!content.is_empty()).then_some(content);
+                if name == b"title" {
+                    title = content;
+                } else {
+                    heading = content;
+                }
+                if title.is_some() && heading.is_some() {
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    (title, heading)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::span;
 
     const HTML_INPUT: &str = r#"
 <html>
@@ -391,12 +812,78 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_extract_fragment_spans() {
+        let input =
+            "<h2 id=\"intro\">Intro</h2>\n<a id=\"top\" name=\"top\"></a>\n<p id=\"intro\">";
+        let span_provider = SourceSpanProvider::from_input(input);
+        let fragments: Vec<_> = extract_html_fragment_spans(input, &span_provider)
+            .into_iter()
+            .map(|(fragment, span)| (fragment, span.to_string()))
+            .collect();
+        assert_eq!(
+            fragments,
+            [
+                ("intro".to_string(), "1:1".to_string()),
+                ("top".to_string(), "2:1".to_string()),
+                ("intro".to_string(), "3:1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_canonical() {
+        let input = r#"<head>
+            <link rel="alternate" href="https://example.com/de/">
+            <link rel="Canonical" href="/docs/">
+            <link rel="canonical" href="/other/">
+        </head>"#;
+        assert_eq!(extract_html_canonical(input), Some("/docs/".to_string()));
+        assert_eq!(extract_html_canonical(HTML_INPUT), None);
+    }
+
+    #[test]
+    fn test_extract_title_and_heading() {
+        let input = r#"<html>
+            <head><title>
+                Page not found &ndash; Acme Docs
+            </title></head>
+            <body><h1>Not <em>found</em></h1><h1>Other</h1></body>
+        </html>"#;
+        assert_eq!(
+            extract_html_title_and_heading(input),
+            (
+                Some("Page not found – Acme Docs".to_string()),
+                Some("Not found".to_string())
+            )
+        );
+        assert_eq!(extract_html_title_and_heading(HTML_INPUT), (None, None));
+    }
+
+    #[test]
+    fn test_extract_title_and_heading_with_parse_errors() {
+        // Duplicate attributes and an unclosed title are parse errors
+        let input = r#"<h1 class="a" class="b">Missing <b>page</h1><title>Unclosed"#;
+        assert_eq!(
+            extract_html_title_and_heading(input),
+            (None, Some("Missing page".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_extract_fragments_from_anchor_names() {
+        let input = r#"<a name="legacy"></a><div name="ignored"></div><h2 id="modern">Title</h2>"#;
+        let expected = HashSet::from(["legacy".to_string(), "modern".to_string()]);
+        assert_eq!(extract_html_fragments(input), expected);
+    }
+
     #[test]
     fn test_skip_verbatim() {
         let expected = vec![RawUri {
             text: "https://example.org".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            span: Some(span(4, 112)),
         }];
 
         let uris = extract_html(HTML_INPUT, false);
@@ -410,26 +897,31 @@ mod tests {
                 text: "https://example.com".to_string(),
                 element: None,
                 attribute: None,
+                span: Some(span(4, 72)),
             },
             RawUri {
                 text: "https://example.org".to_string(),
                 element: Some("a".to_string()),
                 attribute: Some("href".to_string()),
+                span: Some(span(4, 112)),
             },
             RawUri {
                 text: "https://foo.com".to_string(),
                 element: None,
                 attribute: None,
+                span: Some(span(7, 9)),
             },
             RawUri {
                 text: "http://bar.com/some/path".to_string(),
                 element: None,
                 attribute: None,
+                span: Some(span(7, 29)),
             },
             RawUri {
                 text: "https://baz.org".to_string(),
                 element: Some("a".to_string()),
                 attribute: Some("href".to_string()),
+                span: Some(span(9, 9)),
             },
         ];
 
@@ -452,6 +944,7 @@ mod tests {
             text: "https://example.com/".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            span: Some(span(2, 9)),
         }];
 
         let uris = extract_html(HTML_INPUT, false);
@@ -488,6 +981,7 @@ mod tests {
             text: "https://example.org".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            span: Some(span(4, 9)),
         }];
         let uris = extract_html(input, false);
         assert_eq!(uris, expected);
@@ -514,6 +1008,7 @@ mod tests {
             text: "https://example.org".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            span: Some(span(5, 9)),
         }];
         let uris = extract_html(input, false);
         assert_eq!(uris, expected);
@@ -536,6 +1031,7 @@ mod tests {
             text: "tel:1234567890".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            span: Some(span(8, 13)),
         }];
         let uris = extract_html(input, false);
         assert_eq!(uris, expected);
@@ -558,6 +1054,7 @@ mod tests {
             text: "mailto:foo@bar.com".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            span: Some(span(8, 13)),
         }];
         let uris = extract_html(input, false);
         assert_eq!(uris, expected);
@@ -597,16 +1094,19 @@ mod tests {
             text: "/cdn-cgi/image/format=webp,width=640/https://img.youtube.com/vi/hVBl8_pgQf0/maxresdefault.jpg".to_string(),
             element: Some("img".to_string()),
             attribute: Some("srcset".to_string()),
+            span: Some(span(2, 13)),
         },
         RawUri {
             text: "/cdn-cgi/image/format=webp,width=750/https://img.youtube.com/vi/hVBl8_pgQf0/maxresdefault.jpg".to_string(),
             element: Some("img".to_string()),
             attribute: Some("srcset".to_string()),
+            span: Some(span(2, 13)),
         },
         RawUri {
             text: "/cdn-cgi/image/format=webp,width=3840/https://img.youtube.com/vi/hVBl8_pgQf0/maxresdefault.jpg".to_string(),
             element: Some("img".to_string()),
             attribute: Some("src".to_string()),
+            span: Some(span(2, 13)),
         }
 
         ];
@@ -653,6 +1153,7 @@ mod tests {
             text: "https://example.com".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            span: Some(span(2, 13)),
         }];
 
         let uris = extract_html(input, false);
@@ -688,4 +1189,53 @@ mod tests {
         let uris = extract_html(input, false);
         assert!(uris.is_empty());
     }
+
+    /// Returns the input one byte at a time, so that every token crosses
+    /// the boundary of a read
+    struct ByteReader<'a>(&'a [u8]);
+
+    impl Read for ByteReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Some((first, rest)) = self.0.split_first() else {
+                return Ok(0);
+            };
+            buf[0] = *first;
+            self.0 = rest;
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn test_extract_html_stream() {
+        let lines = |uris: Vec<RawUri>| {
+            let mut lines: Vec<_> = uris
+                .into_iter()
+                .map(|uri| (uri.text, uri.span.unwrap().line))
+                .collect();
+            lines.sort();
+            lines
+        };
+        for include_verbatim in [false, true] {
+            let expected = extract_html(HTML_INPUT, include_verbatim);
+            let uris =
+                extract_html_stream(ByteReader(HTML_INPUT.as_bytes()), include_verbatim).unwrap();
+            assert_eq!(lines(uris), lines(expected));
+        }
+    }
+
+    #[test]
+    fn test_extract_links_from_long_text() {
+        let input = format!(
+            "<p>https://example.com/start {} https://example.com/end</p>",
+            "word ".repeat(MAX_RAW_STRING_LEN / 4)
+        );
+        let uris: Vec<_> = extract_html(&input, true)
+            .into_iter()
+            .map(|uri| uri.text)
+            .collect();
+        assert_eq!(
+            uris,
+            ["https://example.com/start", "https://example.com/end"]
+        );
+    }
 }
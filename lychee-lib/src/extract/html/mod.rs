@@ -25,6 +25,15 @@ pub(crate) fn is_email_link(input: &str) -> bool {
     input.strip_prefix("mailto:").unwrap_or(input) == email
 }
 
+/// Check if the given attribute holds the image of a lazy-loading script,
+/// e.g. `<img data-src="photo.jpg">`.
+///
+/// Links in these attributes are only checked if enabled with
+/// [`Extractor::include_lazy_images`](crate::extract::Extractor::include_lazy_images).
+pub(crate) fn is_lazy_loading_attr(name: &str) -> bool {
+    matches!(name, "data-src" | "data-srcset")
+}
+
 /// Check if the given element is in the list of preformatted ("verbatim") tags.
 ///
 /// These will be excluded from link checking by default.
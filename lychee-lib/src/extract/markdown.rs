@@ -1,16 +1,48 @@
 //! Extract links and fragments from markdown documents
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
 
-use pulldown_cmark::{CowStr, Event, LinkType, Options, Parser, Tag, TagEnd, TextMergeStream};
+use pulldown_cmark::{
+    BrokenLink, CowStr, Event, LinkType, Options, Parser, Tag, TagEnd, TextMergeWithOffset,
+};
 
-use crate::{extract::plaintext::extract_raw_uri_from_plaintext, types::uri::raw::RawUri};
+use crate::{
+    extract::plaintext::extract_raw_uri_from_plaintext,
+    types::uri::raw::{OffsetSpanProvider, RawUri, RawUriSpan, SourceSpanProvider, SpanProvider},
+    FragmentStyle, ReferenceIssue, ReferenceIssueKind,
+};
 
-use super::html::html5gum::{extract_html, extract_html_fragments};
+use super::html::html5gum::{extract_html_fragment_spans, extract_html_with_span};
 
 /// Returns the default markdown extensions used by lychee.
 /// Sadly, `|` is not const for `Options` so we can't use a const global.
 fn md_extensions() -> Options {
-    Options::ENABLE_HEADING_ATTRIBUTES | Options::ENABLE_MATH
+    Options::ENABLE_HEADING_ATTRIBUTES | Options::ENABLE_MATH | Options::ENABLE_FOOTNOTES
+}
+
+/// Create a span provider for `text`, which is part of the source of an event
+/// at `range` in `input`.
+///
+/// The event source may contain Markdown syntax in front of the text (e.g. the
+/// backticks of inline code), so we search for the text and fall back to the
+/// start of the event if it can't be found verbatim (e.g. due to escaping).
+fn text_span_provider<'a>(
+    input: &str,
+    range: Range<usize>,
+    text: &str,
+    span_provider: &'a SourceSpanProvider<'a>,
+) -> OffsetSpanProvider<'a> {
+    let start = range.start;
+    let offset = input
+        .get(range)
+        .and_then(|source| source.find(text))
+        .unwrap_or(0);
+    OffsetSpanProvider {
+        offset: start + offset,
+        inner: span_provider,
+    }
 }
 
 /// Extract unparsed URL strings from a Markdown string.
@@ -19,9 +51,11 @@ pub(crate) fn extract_markdown(input: &str, include_verbatim: bool) -> Vec<RawUr
     // which is why we keep track of entries and exits while traversing the input.
     let mut inside_code_block = false;
 
-    let parser = TextMergeStream::new(Parser::new_ext(input, md_extensions()));
+    let span_provider = SourceSpanProvider::from_input(input);
+    let parser =
+        TextMergeWithOffset::new(Parser::new_ext(input, md_extensions()).into_offset_iter());
     parser
-        .filter_map(|event| match event {
+        .filter_map(|(event, span)| match event {
             // A link.
             Event::Start(Tag::Link {
                 link_type,
@@ -41,11 +75,13 @@ pub(crate) fn extract_markdown(input: &str, include_verbatim: bool) -> Vec<RawUr
                             // `LinkType` for better granularity in the future
                             element: Some("a".to_string()),
                             attribute: Some("href".to_string()),
+                            span: Some(span_provider.span(span.start)),
                         }])
                     }
-                    // Reference without destination in the document, but resolved by the `broken_link_callback`
+                    // Reference link like `[foo][bar]`, resolved to the destination of
+                    // its definition `[bar]: https://example.com`
                     LinkType::Reference |
-                    // Collapsed link like `[foo][]`
+                    // Reference without destination in the document, but resolved by the `broken_link_callback`
                     LinkType::ReferenceUnknown |
                     // Collapsed link like `[foo][]`
                     LinkType::Collapsed|
@@ -54,12 +90,25 @@ pub(crate) fn extract_markdown(input: &str, include_verbatim: bool) -> Vec<RawUr
                     // Shortcut link like `[foo]`
                     LinkType::Shortcut |
                     // Shortcut without destination in the document, but resolved by the `broken_link_callback`
-                    LinkType::ShortcutUnknown |
+                    LinkType::ShortcutUnknown => {
+                        // The destination is usually not part of the link, so the
+                        // span falls back to the start of the link
+                        let span_provider = text_span_provider(input, span, &dest_url, &span_provider);
+                        Some(vec![RawUri {
+                            text: dest_url.to_string(),
+                            element: Some("a".to_string()),
+                            attribute: Some("href".to_string()),
+                            span: Some(span_provider.span(0)),
+                        }])
+                    }
                     // Autolink like `<http://foo.bar/baz>`
                     LinkType::Autolink |
                     // Email address in autolink like `<john@example.org>`
                     LinkType::Email =>
-                     Some(extract_raw_uri_from_plaintext(&dest_url)),
+                     Some(extract_raw_uri_from_plaintext(
+                        &dest_url,
+                        &text_span_provider(input, span, &dest_url, &span_provider),
+                     )),
                 }
             }
 
@@ -73,6 +122,7 @@ pub(crate) fn extract_markdown(input: &str, include_verbatim: bool) -> Vec<RawUr
                     // `LinkType` for better granularity in the future
                     element: Some("img".to_string()),
                     attribute: Some("src".to_string()),
+                    span: Some(span_provider.span(span.start)),
                 }])
             }
 
@@ -91,7 +141,8 @@ pub(crate) fn extract_markdown(input: &str, include_verbatim: bool) -> Vec<RawUr
                 if inside_code_block && !include_verbatim {
                     None
                 } else {
-                    Some(extract_raw_uri_from_plaintext(&txt))
+                    let span_provider = text_span_provider(input, span, &txt, &span_provider);
+                    Some(extract_raw_uri_from_plaintext(&txt, &span_provider))
                 }
             }
 
@@ -99,13 +150,22 @@ pub(crate) fn extract_markdown(input: &str, include_verbatim: bool) -> Vec<RawUr
             Event::Html(html) | Event::InlineHtml(html) => {
                 // This won't exclude verbatim links right now, because HTML gets passed in chunks
                 // by pulldown_cmark. So excluding `<pre>` and `<code>` is not handled right now.
-                Some(extract_html(&html, include_verbatim))
+                let span_provider = OffsetSpanProvider {
+                    offset: span.start,
+                    inner: &span_provider,
+                };
+                Some(extract_html_with_span(
+                    &html,
+                    include_verbatim,
+                    &span_provider,
+                ))
             }
 
             // An inline code node.
             Event::Code(code) => {
                 if include_verbatim {
-                    Some(extract_raw_uri_from_plaintext(&code))
+                    let span_provider = text_span_provider(input, span, &code, &span_provider);
+                    Some(extract_raw_uri_from_plaintext(&code, &span_provider))
                 } else {
                     None
                 }
@@ -118,49 +178,188 @@ pub(crate) fn extract_markdown(input: &str, include_verbatim: bool) -> Vec<RawUr
         .collect()
 }
 
+/// Extract the text of links and the alt text of images from a Markdown
+/// string, keyed by the span of the link as returned by [`extract_markdown`].
+pub(crate) fn extract_markdown_link_texts(input: &str) -> HashMap<RawUriSpan, String> {
+    let span_provider = SourceSpanProvider::from_input(input);
+    // Links and images can be nested, e.g. `[![badge](img.svg)](link)`
+    let mut open: Vec<(RawUriSpan, String)> = Vec::new();
+    let mut texts = HashMap::new();
+
+    let parser =
+        TextMergeWithOffset::new(Parser::new_ext(input, md_extensions()).into_offset_iter());
+    for (event, span) in parser {
+        match event {
+            Event::Start(
+                Tag::Link {
+                    link_type: LinkType::Inline,
+                    ..
+                }
+                | Tag::Image { .. },
+            ) => open.push((span_provider.span(span.start), String::new())),
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                let span = text_span_provider(input, span, &dest_url, &span_provider).span(0);
+                open.push((span, String::new()));
+            }
+            Event::End(TagEnd::Link | TagEnd::Image) => {
+                if let Some((span, text)) = open.pop() {
+                    if let Some((_, outer)) = open.last_mut() {
+                        outer.push_str(&text);
+                    }
+                    texts.insert(span, text);
+                }
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, current)) = open.last_mut() {
+                    current.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+    texts
+}
+
+/// Find reference-style links and footnotes without a definition and
+/// definitions which are never referenced in a Markdown string.
+///
+/// Shortcut references like `[foo]` without a definition are not reported,
+/// as brackets are common in prose (e.g. `[WIP]`), unless they look like a
+/// footnote (`[^foo]`). Labels are matched case-insensitively, like in
+/// CommonMark.
+pub(crate) fn extract_markdown_reference_issues(input: &str) -> Vec<ReferenceIssue> {
+    let span_provider = SourceSpanProvider::from_input(input);
+    let mut broken = Vec::new();
+    let callback = |link: BrokenLink<'_>| {
+        broken.push((link.link_type, link.reference.to_string(), link.span.start));
+        None::<(CowStr<'_>, CowStr<'_>)>
+    };
+
+    let mut used_references = HashSet::new();
+    let mut used_footnotes = HashSet::new();
+    let mut footnotes = Vec::new();
+    let mut parser = Parser::new_with_broken_link_callback(input, md_extensions(), Some(callback))
+        .into_offset_iter();
+    for (event, span) in parser.by_ref() {
+        match event {
+            Event::Start(Tag::Link { link_type, id, .. } | Tag::Image { link_type, id, .. })
+                if matches!(
+                    link_type,
+                    LinkType::Reference | LinkType::Collapsed | LinkType::Shortcut
+                ) =>
+            {
+                used_references.insert(normalize_label(&id));
+            }
+            Event::FootnoteReference(label) => {
+                used_footnotes.insert(normalize_label(&label));
+            }
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                footnotes.push((label.to_string(), span.start));
+            }
+            _ => {}
+        }
+    }
+    let definitions: Vec<(String, usize)> = parser
+        .reference_definitions()
+        .iter()
+        .map(|(label, definition)| (label.to_string(), definition.span.start))
+        .collect();
+    drop(parser);
+
+    let mut issues = Vec::new();
+    for (link_type, reference, offset) in broken {
+        let kind = match reference.strip_prefix('^') {
+            Some(label) => ReferenceIssueKind::UndefinedFootnote(label.to_string()),
+            None if link_type == LinkType::Shortcut => continue,
+            None => ReferenceIssueKind::UndefinedReference(reference),
+        };
+        issues.push((kind, offset));
+    }
+    for (label, offset) in definitions {
+        if !used_references.contains(&normalize_label(&label)) {
+            issues.push((ReferenceIssueKind::UnusedDefinition(label), offset));
+        }
+    }
+    for (label, offset) in footnotes {
+        if !used_footnotes.contains(&normalize_label(&label)) {
+            issues.push((ReferenceIssueKind::UnusedFootnote(label), offset));
+        }
+    }
+    issues.sort_by_key(|(_, offset)| *offset);
+    issues
+        .into_iter()
+        .map(|(kind, offset)| ReferenceIssue {
+            kind,
+            span: span_provider.span(offset),
+        })
+        .collect()
+}
+
+/// Normalize the label of a reference: labels are case-insensitive and runs
+/// of whitespace are equivalent to a single space
+fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
 /// Extract fragments/anchors/fragments from a Markdown string.
 ///
-/// Fragments are generated from headings using the same unique kebab case method as GitHub.
+/// Fragments are generated from headings according to `style` (e.g. the same
+/// unique kebab case method as GitHub).
 /// If a [heading attribute](https://github.com/raphlinus/pulldown-cmark/blob/master/specs/heading_attrs.txt)
 /// is present,
-/// this will be added to the fragment set **alongside** the other generated fragment.
+/// this will be added to the fragments **alongside** the other generated fragment.
 /// It means a single heading such as `## Frag 1 {#frag-2}` would generate two fragments.
-pub(crate) fn extract_markdown_fragments(input: &str) -> HashSet<String> {
-    let mut in_heading = false;
+///
+/// Fragments are returned with the position of their heading or HTML element
+/// and in order of appearance. Fragments which occur more than once are
+/// repeated, except for the fragments of repeated headings, which get a
+/// unique suffix (e.g. `intro-1`).
+pub(crate) fn extract_markdown_fragment_spans(
+    input: &str,
+    style: &FragmentStyle,
+) -> Vec<(String, RawUriSpan)> {
+    let mut heading: Option<(RawUriSpan, Option<CowStr<'_>>)> = None;
     let mut heading_text = String::new();
-    let mut heading_id: Option<CowStr<'_>> = None;
-    let mut id_generator = HeadingIdGenerator::default();
+    let mut id_generator = HeadingIdGenerator::new(style);
 
-    let mut out = HashSet::new();
+    let span_provider = SourceSpanProvider::from_input(input);
+    let mut out = Vec::new();
 
-    for event in Parser::new_ext(input, md_extensions()) {
+    for (event, range) in Parser::new_ext(input, md_extensions()).into_offset_iter() {
         match event {
             Event::Start(Tag::Heading { id, .. }) => {
-                heading_id = id;
-                in_heading = true;
+                heading = Some((span_provider.span(range.start), id));
             }
             Event::End(TagEnd::Heading(_)) => {
-                if let Some(frag) = heading_id.take() {
-                    out.insert(frag.to_string());
+                let Some((span, id)) = heading.take() else {
+                    continue;
+                };
+                if let Some(frag) = id {
+                    out.push((frag.to_string(), span));
                 }
 
                 if !heading_text.is_empty() {
-                    let id = id_generator.generate(&heading_text);
-                    out.insert(id);
+                    out.push((id_generator.generate(&heading_text), span));
                     heading_text.clear();
                 }
-
-                in_heading = false;
             }
             Event::Text(text) | Event::Code(text) => {
-                if in_heading {
+                if heading.is_some() {
                     heading_text.push_str(&text);
                 };
             }
 
             // An HTML node
             Event::Html(html) | Event::InlineHtml(html) => {
-                out.extend(extract_html_fragments(&html));
+                let span_provider = OffsetSpanProvider {
+                    offset: range.start,
+                    inner: &span_provider,
+                };
+                out.extend(extract_html_fragment_spans(&html, &span_provider));
             }
 
             // Silently skip over other events
@@ -170,44 +369,39 @@ pub(crate) fn extract_markdown_fragments(input: &str) -> HashSet<String> {
     out
 }
 
-#[derive(Default)]
-struct HeadingIdGenerator {
+struct HeadingIdGenerator<'a> {
+    style: &'a FragmentStyle,
     counter: HashMap<String, usize>,
 }
 
-impl HeadingIdGenerator {
-    fn generate(&mut self, heading: &str) -> String {
-        let mut id = Self::into_kebab_case(heading);
-        let count = self.counter.entry(id.clone()).or_insert(0);
-        if *count != 0 {
-            id = format!("{}-{}", id, *count);
+impl<'a> HeadingIdGenerator<'a> {
+    fn new(style: &'a FragmentStyle) -> Self {
+        Self {
+            style,
+            counter: HashMap::new(),
         }
+    }
+
+    /// Returns the fragment of `heading`, with a suffix if the heading is
+    /// repeated
+    fn generate(&mut self, heading: &str) -> String {
+        let slug = self.style.slugify(heading);
+        let count = self.counter.entry(slug.clone()).or_insert(0);
+        let id = if *count == 0 {
+            slug
+        } else {
+            format!("{slug}-{count}")
+        };
         *count += 1;
 
         id
     }
-
-    /// Converts text into kebab case
-    #[must_use]
-    fn into_kebab_case(text: &str) -> String {
-        text.to_lowercase()
-            .chars()
-            .filter_map(|ch| {
-                if ch.is_alphanumeric() || ch == '_' || ch == '-' {
-                    Some(ch)
-                } else if ch.is_whitespace() {
-                    Some('-')
-                } else {
-                    None
-                }
-            })
-            .collect::<String>()
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::span;
 
     const MD_INPUT: &str = r#"
 # A Test
@@ -240,10 +434,31 @@ or inline like `https://bar.org` for instance.
             "some-code-in-a-heading".to_string(),
             "the-end".to_string(),
         ]);
-        let actual = extract_markdown_fragments(MD_INPUT);
+        let actual: HashSet<_> = extract_markdown_fragment_spans(MD_INPUT, &FragmentStyle::Github)
+            .into_iter()
+            .map(|(fragment, _)| fragment)
+            .collect();
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_extract_fragment_spans() {
+        let fragments: Vec<_> = extract_markdown_fragment_spans(MD_INPUT, &FragmentStyle::Github)
+            .into_iter()
+            .map(|(fragment, span)| (fragment, span.to_string()))
+            .collect();
+        assert_eq!(
+            fragments,
+            [
+                ("a-test".to_string(), "2:1".to_string()),
+                ("well-still-the-same-test".to_string(), "6:1".to_string()),
+                ("a-test-1".to_string(), "6:1".to_string()),
+                ("some-code-in-a-heading".to_string(), "16:1".to_string()),
+                ("the-end".to_string(), "20:1".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn test_skip_verbatim() {
         let expected = vec![
@@ -251,11 +466,13 @@ or inline like `https://bar.org` for instance.
                 text: "https://foo.com".to_string(),
                 element: Some("a".to_string()),
                 attribute: Some("href".to_string()),
+                span: Some(span(4, 19)),
             },
             RawUri {
                 text: "http://example.com".to_string(),
                 element: Some("a".to_string()),
                 attribute: Some("href".to_string()),
+                span: Some(span(18, 1)),
             },
         ];
 
@@ -270,21 +487,25 @@ or inline like `https://bar.org` for instance.
                 text: "https://foo.com".to_string(),
                 element: Some("a".to_string()),
                 attribute: Some("href".to_string()),
+                span: Some(span(4, 19)),
             },
             RawUri {
                 text: "https://bar.com/123".to_string(),
                 element: None,
                 attribute: None,
+                span: Some(span(11, 1)),
             },
             RawUri {
                 text: "https://bar.org".to_string(),
                 element: None,
                 attribute: None,
+                span: Some(span(14, 17)),
             },
             RawUri {
                 text: "http://example.com".to_string(),
                 element: Some("a".to_string()),
                 attribute: Some("href".to_string()),
+                span: Some(span(18, 1)),
             },
         ];
 
@@ -312,7 +533,7 @@ Some pre-formatted http://pre.com
     #[test]
     fn test_kebab_case() {
         let check = |input, expected| {
-            let actual = HeadingIdGenerator::into_kebab_case(input);
+            let actual = FragmentStyle::Github.slugify(input);
             assert_eq!(actual, expected);
         };
         check("A Heading", "a-heading");
@@ -350,6 +571,72 @@ $$
         assert_eq!(uris, expected);
     }
 
+    #[test]
+    fn test_link_spans() {
+        let input = "# Links\n\nSee <https://foo.com> and `https://bar.org`.\n\n  <a href=\"https://baz.net\">baz</a>";
+        let spans: Vec<_> = extract_markdown(input, true)
+            .into_iter()
+            .map(|uri| (uri.text, uri.span))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                // Autolinks are extracted from both the link and its text
+                ("https://foo.com".to_string(), Some(span(3, 6))),
+                ("https://foo.com".to_string(), Some(span(3, 6))),
+                ("https://bar.org".to_string(), Some(span(3, 28))),
+                ("https://baz.net".to_string(), Some(span(5, 3))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reference_links() {
+        let input = "See [the guide][guide], [Guide][] and [guide].\n\n[guide]: ./docs/guide.md\n";
+        let uris = extract_markdown(input, false);
+        assert_eq!(uris.len(), 3);
+        assert!(uris.iter().all(|uri| uri.text == "./docs/guide.md"));
+        assert_eq!(uris[0].span, Some(span(1, 5)));
+    }
+
+    #[test]
+    fn test_reference_issues() {
+        let input = "\
+Read [the docs][docs] and [the FAQ][faq], [WIP], a note[^1] and another[^2].
+
+[Docs]: https://example.com/docs
+[unused]: https://example.com/unused
+
+[^1]: Used
+[^3]: Unused
+";
+        let issues: Vec<_> = extract_markdown_reference_issues(input)
+            .into_iter()
+            .map(|issue| (issue.kind, issue.span))
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                (
+                    ReferenceIssueKind::UndefinedReference("faq".to_string()),
+                    span(1, 27)
+                ),
+                (
+                    ReferenceIssueKind::UndefinedFootnote("2".to_string()),
+                    span(1, 72)
+                ),
+                (
+                    ReferenceIssueKind::UnusedDefinition("unused".to_string()),
+                    span(4, 1)
+                ),
+                (
+                    ReferenceIssueKind::UnusedFootnote("3".to_string()),
+                    span(7, 1)
+                ),
+            ]
+        );
+    }
+
     #[test]
     fn test_underscore_in_urls_middle() {
         let markdown = r"https://example.com/_/foo";
@@ -357,6 +644,7 @@ $$
             text: "https://example.com/_/foo".to_string(),
             element: None,
             attribute: None,
+            span: Some(span(1, 1)),
         }];
         let uris = extract_markdown(markdown, true);
         assert_eq!(uris, expected);
@@ -369,6 +657,7 @@ $$
             text: "https://example.com/_".to_string(),
             element: None,
             attribute: None,
+            span: Some(span(1, 1)),
         }];
         let uris = extract_markdown(markdown, true);
         assert_eq!(uris, expected);
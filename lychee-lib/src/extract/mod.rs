@@ -1,19 +1,37 @@
-use crate::types::{uri::raw::RawUri, FileType, InputContent};
+use std::{
+    collections::HashMap,
+    io::{self, Read},
+};
 
+use serde::Serialize;
+
+use crate::types::{
+    uri::raw::{RawUri, RawUriSpan, SourceSpanProvider},
+    FileType, InputContent,
+};
+
+mod code;
 pub mod html;
 pub mod markdown;
+mod notebook;
 mod plaintext;
 
-use markdown::extract_markdown;
-use plaintext::extract_raw_uri_from_plaintext;
+use code::{extract_comments, Language};
+use markdown::{extract_markdown, extract_markdown_link_texts};
+use notebook::{extract_notebook, extract_notebook_cells};
+use plaintext::{extract_raw_uri_from_plaintext, extract_raw_uri_from_plaintext_stream};
 
 /// A handler for extracting links from various input formats like Markdown and
 /// HTML. Allocations should be avoided if possible as this is a
 /// performance-critical section of the library.
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Default, Debug, Clone, Copy)]
 pub struct Extractor {
     use_html5ever: bool,
     include_verbatim: bool,
+    include_notebook_outputs: bool,
+    extract_comments: bool,
+    include_lazy_images: bool,
 }
 
 impl Extractor {
@@ -34,25 +52,191 @@ impl Extractor {
         Self {
             use_html5ever,
             include_verbatim,
+            include_notebook_outputs: false,
+            extract_comments: false,
+            include_lazy_images: false,
         }
     }
 
+    /// Also extract links from the outputs of code cells in Jupyter notebooks,
+    /// which are skipped by default
+    #[must_use]
+    pub const fn include_notebook_outputs(mut self, yes: bool) -> Self {
+        self.include_notebook_outputs = yes;
+        self
+    }
+
+    /// Only extract links from the comments of source code files (Rust,
+    /// JavaScript/TypeScript, Python and shell scripts), detected by their
+    /// extension. Otherwise, source code is treated as plaintext.
+    ///
+    /// Streamed inputs (see [`InputContent::stream`]) are always treated as
+    /// plaintext.
+    #[must_use]
+    pub const fn extract_comments(mut self, yes: bool) -> Self {
+        self.extract_comments = yes;
+        self
+    }
+
+    /// Also extract the images of lazy-loading scripts from HTML, i.e. the
+    /// `data-src` and `data-srcset` attributes, which are skipped by default
+    #[must_use]
+    pub const fn include_lazy_images(mut self, yes: bool) -> Self {
+        self.include_lazy_images = yes;
+        self
+    }
+
     /// Main entrypoint for extracting links from various sources
-    /// (Markdown, HTML, and plaintext)
+    /// (Markdown, HTML, Jupyter notebooks, SVG images, source code and
+    /// plaintext)
+    ///
+    /// Links of streamed inputs (see [`InputContent::stream`]) are not
+    /// extracted, use [`Extractor::extract_stream`] for them.
     #[must_use]
     pub fn extract(&self, input_content: &InputContent) -> Vec<RawUri> {
-        match input_content.file_type {
+        let uris = match input_content.file_type {
             FileType::Markdown => extract_markdown(&input_content.content, self.include_verbatim),
-            FileType::Html => {
+            // SVG is XML, but its links are attributes like in HTML
+            FileType::Html | FileType::Svg => {
                 if self.use_html5ever {
                     html::html5ever::extract_html(&input_content.content, self.include_verbatim)
                 } else {
                     html::html5gum::extract_html(&input_content.content, self.include_verbatim)
                 }
             }
-            FileType::Plaintext => extract_raw_uri_from_plaintext(&input_content.content),
+            FileType::Ipynb => extract_notebook(
+                &input_content.content,
+                self.include_verbatim,
+                self.include_notebook_outputs,
+            ),
+            FileType::Plaintext => match self.source_language(input_content) {
+                Some(language) => {
+                    extract_comments(&input_content.content, language, self.include_verbatim)
+                }
+                None => extract_raw_uri_from_plaintext(
+                    &input_content.content,
+                    &SourceSpanProvider::from_input(&input_content.content),
+                ),
+            },
+        };
+        self.filter_lazy_images(uris)
+    }
+
+    /// Remove the images of lazy-loading scripts, unless they are included
+    fn filter_lazy_images(&self, mut uris: Vec<RawUri>) -> Vec<RawUri> {
+        if !self.include_lazy_images {
+            uris.retain(|uri| {
+                !uri.attribute
+                    .as_deref()
+                    .is_some_and(html::is_lazy_loading_attr)
+            });
         }
+        uris
     }
+
+    /// The programming language of the input, if comments are extracted
+    fn source_language(&self, input_content: &InputContent) -> Option<Language> {
+        if self.extract_comments {
+            Language::from_source(&input_content.source)
+        } else {
+            None
+        }
+    }
+
+    /// Extract links from a large input which is streamed from `reader`
+    /// (decoded to UTF-8), so that it doesn't have to fit into memory.
+    ///
+    /// HTML is always parsed with `html5gum`. Markdown can't be parsed
+    /// incrementally, so it is scanned for links like plaintext; relative
+    /// links in Markdown are not found this way. Spans of links in HTML only
+    /// contain the line. Notebooks are parsed while reading, but all cells
+    /// are kept in memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `reader` fails or a notebook is not valid JSON
+    pub fn extract_stream(
+        &self,
+        reader: impl Read,
+        file_type: FileType,
+    ) -> io::Result<Vec<RawUri>> {
+        let uris = match file_type {
+            FileType::Html | FileType::Svg => {
+                html::html5gum::extract_html_stream(reader, self.include_verbatim)?
+            }
+            FileType::Markdown | FileType::Plaintext => {
+                extract_raw_uri_from_plaintext_stream(reader)?
+            }
+            FileType::Ipynb => {
+                let notebook = serde_json::from_reader(reader)?;
+                extract_notebook_cells(
+                    notebook,
+                    self.include_verbatim,
+                    self.include_notebook_outputs,
+                )
+            }
+        };
+        Ok(self.filter_lazy_images(uris))
+    }
+}
+
+/// A link found by [`extract_links`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ExtractedLink {
+    /// Unparsed link as it appears in the document (e.g. a relative path)
+    pub url: String,
+    /// Position of the link in the document, if known
+    pub span: Option<RawUriSpan>,
+    /// Element that contained the link (e.g. `a` or `img`), if any
+    pub element: Option<String>,
+    /// Attribute that contained the link (e.g. `href` or `src`), if any
+    pub attribute: Option<String>,
+    /// Text of the link or alt text of the image.
+    /// This is only extracted from Markdown links for now.
+    pub text: Option<String>,
+}
+
+/// Extract all links from `content` of the given `file_type` without
+/// collecting inputs or checking anything, e.g. for editor integrations.
+///
+/// `extractor` selects the parser and which links are extracted (see
+/// [`Extractor::new`]).
+///
+/// # Example
+///
+/// ```
+/// use lychee_lib::{extract::{extract_links, Extractor}, FileType};
+///
+/// let links = extract_links(
+///     "See [the docs](https://lychee.cli.rs/).",
+///     FileType::Markdown,
+///     &Extractor::default(),
+/// );
+/// assert_eq!(links[0].url, "https://lychee.cli.rs/");
+/// assert_eq!(links[0].text.as_deref(), Some("the docs"));
+/// assert_eq!(links[0].span.unwrap().line.get(), 1);
+/// ```
+#[must_use]
+pub fn extract_links(
+    content: &str,
+    file_type: FileType,
+    extractor: &Extractor,
+) -> Vec<ExtractedLink> {
+    let texts = match file_type {
+        FileType::Markdown => extract_markdown_link_texts(content),
+        _ => HashMap::new(),
+    };
+    extractor
+        .extract(&InputContent::from_string(content, file_type))
+        .into_iter()
+        .map(|raw_uri| ExtractedLink {
+            text: raw_uri.span.and_then(|span| texts.get(&span).cloned()),
+            url: raw_uri.text,
+            span: raw_uri.span,
+            element: raw_uri.element,
+            attribute: raw_uri.attribute,
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -63,7 +247,7 @@ mod tests {
 
     use super::*;
     use crate::{
-        test_utils::{load_fixture, mail, website},
+        test_utils::{load_fixture, mail, span, website},
         types::{FileType, InputContent, InputSource},
         utils::url::find_links,
         Uri,
@@ -213,6 +397,7 @@ mod tests {
             source,
             file_type: FileType::Html,
             content: contents.to_string(),
+            stream: None,
         };
 
         for use_html5ever in [true, false] {
@@ -294,6 +479,65 @@ mod tests {
         assert_eq!(links, expected_links);
     }
 
+    #[test]
+    fn test_extract_responsive_images() {
+        let input = r#"
+            <picture>
+                <source media="(min-width: 800px)" srcset="https://cdn.example.com/large.webp 1x, https://cdn.example.com/large@2x.webp 2x">
+                <img src="https://cdn.example.com/small.jpg" data-src="https://cdn.example.com/lazy.jpg" data-srcset="https://cdn.example.com/lazy@2x.jpg 2x">
+            </picture>
+            <link rel="preload" as="image" imagesrcset="https://cdn.example.com/hero.jpg 1x, https://cdn.example.com/hero@2x.jpg 2x">
+        "#;
+        let links = extract_uris(input, FileType::Html);
+
+        let expected_links = IntoIterator::into_iter([
+            website("https://cdn.example.com/large.webp"),
+            website("https://cdn.example.com/large@2x.webp"),
+            website("https://cdn.example.com/small.jpg"),
+            website("https://cdn.example.com/hero.jpg"),
+            website("https://cdn.example.com/hero@2x.jpg"),
+        ])
+        .collect::<HashSet<Uri>>();
+        assert_eq!(links, expected_links);
+
+        for use_html5ever in [false, true] {
+            let lazy_links: HashSet<Uri> = Extractor::new(use_html5ever, false)
+                .include_lazy_images(true)
+                .extract(&InputContent::from_string(input, FileType::Html))
+                .into_iter()
+                .filter_map(|raw_uri| Uri::try_from(raw_uri).ok())
+                .collect();
+            assert!(lazy_links.contains(&website("https://cdn.example.com/lazy.jpg")));
+            assert!(lazy_links.contains(&website("https://cdn.example.com/lazy@2x.jpg")));
+        }
+    }
+
+    #[test]
+    fn test_extract_svg() {
+        let input = r##"<svg viewBox="0 0 10 10">
+            <a href="https://example.com/about"><text>About</text></a>
+            <image xlink:href="https://cdn.example.com/logo.png" width="10" height="10"/>
+            <use xlink:href="https://cdn.example.com/sprite.svg#icon"/>
+            <use href="https://cdn.example.com/sprite2.svg#icon"/>
+        </svg>"##;
+        let expected_links = IntoIterator::into_iter([
+            website("https://example.com/about"),
+            website("https://cdn.example.com/logo.png"),
+            website("https://cdn.example.com/sprite.svg#icon"),
+            website("https://cdn.example.com/sprite2.svg#icon"),
+        ])
+        .collect::<HashSet<Uri>>();
+
+        for use_html5ever in [false, true] {
+            let links: HashSet<Uri> = Extractor::new(use_html5ever, false)
+                .extract(&InputContent::from_string(input, FileType::Svg))
+                .into_iter()
+                .filter_map(|raw_uri| Uri::try_from(raw_uri).ok())
+                .collect();
+            assert_eq!(links, expected_links);
+        }
+    }
+
     #[test]
     fn test_extract_urls_with_at_sign_properly() {
         // note that these used to parse as emails
@@ -320,4 +564,70 @@ mod tests {
 
         assert_eq!(links, expected_links);
     }
+
+    #[test]
+    fn test_extract_comments() {
+        let input_content = InputContent {
+            source: InputSource::FsPath("src/lib.rs".into()),
+            file_type: FileType::Plaintext,
+            content:
+                "/// <https://example.com/docs>\nconst API: &str = \"https://example.com/api\";"
+                    .to_string(),
+            stream: None,
+        };
+        let texts = |extractor: Extractor| -> Vec<String> {
+            extractor
+                .extract(&input_content)
+                .into_iter()
+                .map(|uri| uri.text)
+                .collect()
+        };
+
+        let extractor = Extractor::new(false, false);
+        assert_eq!(
+            texts(extractor),
+            ["https://example.com/docs", "https://example.com/api"]
+        );
+        assert_eq!(
+            texts(extractor.extract_comments(true)),
+            ["https://example.com/docs"]
+        );
+    }
+
+    #[test]
+    fn test_extract_links() {
+        let links = extract_links(
+            "# Title\n\n[![badge](https://img.shields.io/b.svg)](https://example.com)",
+            FileType::Markdown,
+            &Extractor::default(),
+        );
+        assert_eq!(
+            links,
+            [
+                ExtractedLink {
+                    url: "https://example.com".to_string(),
+                    span: Some(span(3, 1)),
+                    element: Some("a".to_string()),
+                    attribute: Some("href".to_string()),
+                    text: Some("badge".to_string()),
+                },
+                ExtractedLink {
+                    url: "https://img.shields.io/b.svg".to_string(),
+                    span: Some(span(3, 2)),
+                    element: Some("img".to_string()),
+                    attribute: Some("src".to_string()),
+                    text: Some("badge".to_string()),
+                },
+            ]
+        );
+
+        let links = extract_links(
+            r#"<a href="https://example.com">Example</a>"#,
+            FileType::Html,
+            &Extractor::default(),
+        );
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].element.as_deref(), Some("a"));
+        assert_eq!(links[0].text, None);
+    }
 }
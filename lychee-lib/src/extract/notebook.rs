@@ -0,0 +1,217 @@
+//! Extract links from Jupyter notebooks (`.ipynb`)
+//!
+//! Notebooks are JSON documents made up of cells. Links are extracted from
+//! the Markdown cells; code cells are treated like Markdown code blocks
+//! (only checked with `include_verbatim`). The outputs of code cells are
+//! generated content (e.g. tracebacks and logs), so they are skipped unless
+//! `include_outputs` is set.
+//!
+//! The cells are extracted from the parsed JSON, so links in notebooks have
+//! no span.
+use std::collections::HashMap;
+
+use log::warn;
+use serde::{de::IgnoredAny, Deserialize};
+
+use super::{html::html5gum::extract_html, markdown::extract_markdown};
+use crate::{
+    extract::plaintext::extract_raw_uri_from_plaintext,
+    types::uri::raw::{RawUri, SourceSpanProvider},
+};
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Notebook {
+    #[serde(default)]
+    cells: Vec<Cell>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Cell {
+    cell_type: String,
+    #[serde(default)]
+    source: Text,
+    #[serde(default)]
+    outputs: Vec<Output>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Output {
+    /// Text of `stream` outputs (stdout and stderr)
+    #[serde(default)]
+    text: Text,
+    /// Representations of other outputs by MIME type
+    #[serde(default)]
+    data: HashMap<String, Text>,
+}
+
+/// Multiline text is either stored as a single string or as a list of lines
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Text {
+    String(String),
+    Lines(Vec<String>),
+    /// Non-textual data, like `application/json` outputs
+    Other(IgnoredAny),
+}
+
+impl Default for Text {
+    fn default() -> Self {
+        Self::String(String::new())
+    }
+}
+
+impl Text {
+    fn into_string(self) -> String {
+        match self {
+            Self::String(s) => s,
+            Self::Lines(lines) => lines.concat(),
+            Self::Other(_) => String::new(),
+        }
+    }
+}
+
+/// Extract unparsed URL strings from a notebook.
+///
+/// Falls back to extracting links from the whole input as plaintext if it
+/// isn't a valid notebook.
+pub(crate) fn extract_notebook(
+    input: &str,
+    include_verbatim: bool,
+    include_outputs: bool,
+) -> Vec<RawUri> {
+    match serde_json::from_str(input) {
+        Ok(notebook) => extract_notebook_cells(notebook, include_verbatim, include_outputs),
+        Err(e) => {
+            warn!("Cannot parse notebook, extracting links as plaintext: {e}");
+            extract_plaintext(input)
+        }
+    }
+}
+
+/// Extract unparsed URL strings from the cells of a parsed notebook
+pub(crate) fn extract_notebook_cells(
+    notebook: Notebook,
+    include_verbatim: bool,
+    include_outputs: bool,
+) -> Vec<RawUri> {
+    let mut uris = Vec::new();
+    for cell in notebook.cells {
+        match cell.cell_type.as_str() {
+            "markdown" => uris.extend(extract_markdown(
+                &cell.source.into_string(),
+                include_verbatim,
+            )),
+            "code" if include_verbatim => {
+                uris.extend(extract_plaintext(&cell.source.into_string()));
+            }
+            "raw" => uris.extend(extract_plaintext(&cell.source.into_string())),
+            _ => {}
+        }
+
+        if !include_outputs {
+            continue;
+        }
+        for output in cell.outputs {
+            uris.extend(extract_plaintext(&output.text.into_string()));
+            for (mime_type, data) in output.data {
+                let data = data.into_string();
+                match mime_type.as_str() {
+                    "text/html" => uris.extend(extract_html(&data, include_verbatim)),
+                    "text/markdown" => uris.extend(extract_markdown(&data, include_verbatim)),
+                    "text/plain" => uris.extend(extract_plaintext(&data)),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    for uri in &mut uris {
+        uri.span = None;
+    }
+    uris
+}
+
+fn extract_plaintext(input: &str) -> Vec<RawUri> {
+    extract_raw_uri_from_plaintext(input, &SourceSpanProvider::from_input(input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTEBOOK: &str = r##"{
+        "cells": [
+            {
+                "cell_type": "markdown",
+                "metadata": {},
+                "source": ["# Analysis\n", "See [the docs](https://example.com/docs).\n"]
+            },
+            {
+                "cell_type": "code",
+                "execution_count": 1,
+                "metadata": {},
+                "source": "requests.get(\"https://example.com/api\")",
+                "outputs": [
+                    {
+                        "output_type": "stream",
+                        "name": "stdout",
+                        "text": ["Fetched https://example.com/stream\n"]
+                    },
+                    {
+                        "output_type": "display_data",
+                        "data": {
+                            "text/html": "<a href=\"https://example.com/html\">result</a>",
+                            "application/json": {"url": "https://example.com/json"}
+                        },
+                        "metadata": {}
+                    }
+                ]
+            }
+        ],
+        "metadata": {},
+        "nbformat": 4,
+        "nbformat_minor": 5
+    }"##;
+
+    fn texts(uris: Vec<RawUri>) -> Vec<String> {
+        let mut texts: Vec<_> = uris.into_iter().map(|uri| uri.text).collect();
+        texts.sort();
+        texts
+    }
+
+    #[test]
+    fn test_skip_outputs() {
+        assert_eq!(
+            texts(extract_notebook(NOTEBOOK, false, false)),
+            ["https://example.com/docs"]
+        );
+    }
+
+    #[test]
+    fn test_include_outputs() {
+        assert_eq!(
+            texts(extract_notebook(NOTEBOOK, false, true)),
+            [
+                "https://example.com/docs",
+                "https://example.com/html",
+                "https://example.com/stream",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_include_verbatim() {
+        assert_eq!(
+            texts(extract_notebook(NOTEBOOK, true, false)),
+            ["https://example.com/api", "https://example.com/docs"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_notebook() {
+        assert_eq!(
+            texts(extract_notebook("{ https://example.com", false, false)),
+            ["https://example.com"]
+        );
+    }
+}
@@ -1,25 +1,90 @@
-use crate::{types::uri::raw::RawUri, utils::url};
+use std::io::{self, BufRead, BufReader, Read};
+
+use crate::{
+    types::uri::raw::{RawUri, SourceSpanProvider, SpanProvider},
+    utils::url,
+};
+
+/// Lines longer than this are scanned for links in pieces when streaming,
+/// so that a single huge line (e.g. minified content) doesn't have to fit
+/// into memory. Links crossing the end of a piece are cut off.
+const MAX_LINE_LEN: u64 = 1024 * 1024;
 
 /// Extract unparsed URL strings from plaintext
-pub(crate) fn extract_raw_uri_from_plaintext(input: &str) -> Vec<RawUri> {
+///
+/// The span of each URI is looked up with the given `span_provider`,
+/// using the byte offset of the URI in `input`.
+pub(crate) fn extract_raw_uri_from_plaintext(
+    input: &str,
+    span_provider: &impl SpanProvider,
+) -> Vec<RawUri> {
     url::find_links(input)
-        .map(|uri| RawUri::from(uri.as_str()))
+        .map(|uri| RawUri {
+            span: Some(span_provider.span(uri.start())),
+            ..RawUri::from(uri.as_str())
+        })
         .collect()
 }
 
+/// Extract unparsed URL strings from plaintext which is streamed from
+/// `reader` line by line, so that the text doesn't have to fit into memory
+pub(crate) fn extract_raw_uri_from_plaintext_stream(reader: impl Read) -> io::Result<Vec<RawUri>> {
+    let mut reader = BufReader::new(reader);
+    let mut uris = Vec::new();
+    let mut line = Vec::new();
+    let mut line_number = 0;
+    loop {
+        line.clear();
+        if reader
+            .by_ref()
+            .take(MAX_LINE_LEN)
+            .read_until(b'\n', &mut line)?
+            == 0
+        {
+            return Ok(uris);
+        }
+        let text = String::from_utf8_lossy(&line);
+        let span_provider = SourceSpanProvider::from_input(&text);
+        uris.extend(
+            extract_raw_uri_from_plaintext(&text, &span_provider)
+                .into_iter()
+                .map(|mut uri| {
+                    if let Some(span) = &mut uri.span {
+                        span.line = span.line.saturating_add(line_number);
+                    }
+                    uri
+                }),
+        );
+        if line.ends_with(b"\n") {
+            line_number += 1;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{test_utils::span, types::uri::raw::SourceSpanProvider};
+
+    fn extract(input: &str) -> Vec<RawUri> {
+        extract_raw_uri_from_plaintext(input, &SourceSpanProvider::from_input(input))
+    }
 
     #[test]
     fn test_extract_local_links() {
         let input = "http://127.0.0.1/ and http://127.0.0.1:8888/ are local links.";
-        let links: Vec<RawUri> = extract_raw_uri_from_plaintext(input);
+        let links: Vec<RawUri> = extract(input);
         assert_eq!(
             links,
             [
-                RawUri::from("http://127.0.0.1/"),
-                RawUri::from("http://127.0.0.1:8888/")
+                RawUri {
+                    span: Some(span(1, 1)),
+                    ..RawUri::from("http://127.0.0.1/")
+                },
+                RawUri {
+                    span: Some(span(1, 23)),
+                    ..RawUri::from("http://127.0.0.1:8888/")
+                },
             ]
         );
     }
@@ -27,9 +92,27 @@ mod tests {
     #[test]
     fn test_extract_link_at_end_of_line() {
         let input = "https://www.apache.org/licenses/LICENSE-2.0\n";
-        let uri = RawUri::from(input.trim_end());
+        let uri = RawUri {
+            span: Some(span(1, 1)),
+            ..RawUri::from(input.trim_end())
+        };
 
-        let uris: Vec<RawUri> = extract_raw_uri_from_plaintext(input);
+        let uris: Vec<RawUri> = extract(input);
         assert_eq!(vec![uri], uris);
     }
+
+    #[test]
+    fn test_extract_link_span() {
+        let input = "Some text\n\nSee the docs at https://lychee.cli.rs for details.";
+        let uris: Vec<RawUri> = extract(input);
+        assert_eq!(uris[0].span, Some(span(3, 17)));
+    }
+
+    #[test]
+    fn test_extract_stream() {
+        let input =
+            "Some text\n\nSee the docs at https://lychee.cli.rs for details.\nhttps://example.com";
+        let uris = extract_raw_uri_from_plaintext_stream(input.as_bytes()).unwrap();
+        assert_eq!(uris, extract(input));
+    }
 }
@@ -0,0 +1,191 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use ignore::WalkBuilder;
+use regex::RegexSet;
+
+use super::Excludes;
+use crate::{ErrorKind, InputSource, Result, Uri};
+
+/// Name of the file which contains URL patterns to ignore
+pub const LYCHEE_IGNORE_FILE: &str = ".lycheeignore";
+
+/// Ignore lines starting with this marker in `.lycheeignore` files
+const COMMENT_MARKER: &str = "#";
+
+/// Parse the contents of a `.lycheeignore` file into a list of regex patterns.
+///
+/// Empty lines and comments (lines starting with `#`) are skipped.
+#[must_use]
+pub fn parse_patterns(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter(|line| !line.is_empty() && !line.trim_start().starts_with(COMMENT_MARKER))
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Normalize a path, so that paths of ignore files and inputs can be compared
+fn normalize(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// URL patterns of a single `.lycheeignore` file, which apply to all inputs
+/// inside of the directory that contains the file
+#[derive(Clone, Debug)]
+struct Scope {
+    dir: PathBuf,
+    excludes: Excludes,
+}
+
+/// `.lycheeignore` files found in subdirectories, similar to `.gitignore`.
+///
+/// The patterns of each file only apply to links found in files inside the
+/// directory of the ignore file (including its subdirectories). This allows
+/// monorepos to scope exclusions per project.
+#[derive(Clone, Debug, Default)]
+pub struct LycheeIgnore {
+    scopes: Vec<Scope>,
+}
+
+impl LycheeIgnore {
+    /// Find the `.lycheeignore` files which apply to the inputs in
+    /// `input_dirs`: the files inside of these directories and the files in
+    /// their parent directories below `root`.
+    ///
+    /// Directories ignored by `.gitignore` are skipped and other directories
+    /// of `root` are not searched.
+    /// The ignore file in `root` itself is not included, because it applies
+    /// to all inputs and is merged into the global excludes instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a directory can't be traversed, an ignore file
+    /// can't be read or contains an invalid regex.
+    pub fn discover(root: &Path, input_dirs: &[PathBuf]) -> Result<Self> {
+        let root = normalize(root);
+        let mut dirs = BTreeSet::new();
+        for input_dir in input_dirs {
+            let input_dir = normalize(input_dir);
+            // Ignore files in the parents of an input apply to it as well
+            for dir in input_dir
+                .ancestors()
+                .skip(1)
+                .take_while(|dir| dir.starts_with(&root) && *dir != root)
+            {
+                if dir.join(LYCHEE_IGNORE_FILE).is_file() {
+                    dirs.insert(dir.to_path_buf());
+                }
+            }
+            if !input_dir.is_dir() {
+                continue;
+            }
+            let walker = WalkBuilder::new(&input_dir)
+                .hidden(false)
+                .filter_entry(|entry| entry.file_name() != ".git")
+                .build();
+            for entry in walker {
+                let entry = entry?;
+                if entry.file_name() != LYCHEE_IGNORE_FILE {
+                    continue;
+                }
+                if let Some(dir) = entry.path().parent() {
+                    dirs.insert(dir.to_path_buf());
+                }
+            }
+        }
+        dirs.remove(&root);
+
+        let mut scopes = Vec::new();
+        for dir in dirs {
+            let path = dir.join(LYCHEE_IGNORE_FILE);
+            let content =
+                fs::read_to_string(&path).map_err(|e| ErrorKind::ReadFileInput(e, path.clone()))?;
+            scopes.push(Scope {
+                dir,
+                excludes: Excludes {
+                    regex: RegexSet::new(parse_patterns(&content))?,
+                },
+            });
+        }
+        Ok(Self { scopes })
+    }
+
+    /// Whether no scoped ignore files were found
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.scopes.is_empty()
+    }
+
+    /// Returns `true` if the `uri` found in `source` matches a pattern of an
+    /// ignore file in the same directory or one of its parents.
+    ///
+    /// Only local files can be matched; other sources are never ignored.
+    #[must_use]
+    pub fn is_match(&self, uri: &Uri, source: &InputSource) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        let InputSource::FsPath(path) = source else {
+            return false;
+        };
+        let path = normalize(path);
+        self.scopes
+            .iter()
+            .any(|scope| path.starts_with(&scope.dir) && scope.excludes.is_match(uri.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::test_utils::website;
+
+    #[test]
+    fn test_parse_patterns() {
+        let content = "https://foo.com\n\n# comment\n  # indented comment\nbar.org/.*\n";
+        assert_eq!(
+            parse_patterns(content),
+            vec!["https://foo.com", "bar.org/.*"]
+        );
+    }
+
+    #[test]
+    fn test_scoped_patterns() -> Result<()> {
+        let root = tempdir().unwrap();
+        let project = root.path().join("project");
+        let other = root.path().join("other");
+        fs::create_dir_all(project.join("docs").join("api")).unwrap();
+        fs::create_dir_all(&other).unwrap();
+        fs::write(root.path().join(LYCHEE_IGNORE_FILE), "root.com").unwrap();
+        fs::write(project.join(LYCHEE_IGNORE_FILE), "# comment\nproject.com").unwrap();
+        fs::write(
+            project.join("docs").join("api").join(LYCHEE_IGNORE_FILE),
+            "api.com",
+        )
+        .unwrap();
+        fs::write(other.join(LYCHEE_IGNORE_FILE), "other.com").unwrap();
+
+        let ignore = LycheeIgnore::discover(root.path(), &[project.join("docs")])?;
+        let in_project = InputSource::FsPath(project.join("docs").join("README.md"));
+        let in_api = InputSource::FsPath(project.join("docs").join("api").join("README.md"));
+        let outside = InputSource::FsPath(root.path().join("README.md"));
+
+        assert!(ignore.is_match(&website("https://project.com"), &in_project));
+        assert!(ignore.is_match(&website("https://api.com"), &in_api));
+        assert!(!ignore.is_match(&website("https://api.com"), &in_project));
+        assert!(!ignore.is_match(&website("https://project.com"), &outside));
+        assert!(!ignore.is_match(&website("https://project.com"), &InputSource::Stdin));
+        // The root ignore file is not scoped
+        assert!(!ignore.is_match(&website("https://root.com"), &in_project));
+        // Directories without inputs are not searched
+        let in_other = InputSource::FsPath(other.join("README.md"));
+        assert!(!ignore.is_match(&website("https://other.com"), &in_other));
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
 mod excludes;
 mod includes;
+mod lycheeignore;
+mod versioned_docs;
 
 use once_cell::sync::Lazy;
 use regex::RegexSet;
@@ -7,8 +9,10 @@ use std::collections::HashSet;
 
 pub use excludes::Excludes;
 pub use includes::Includes;
+pub use lycheeignore::{parse_patterns, LycheeIgnore, LYCHEE_IGNORE_FILE};
+pub use versioned_docs::{VersionedDocs, DEFAULT_DOCS_VERSION};
 
-use crate::Uri;
+use crate::{InputSource, SchemePolicy, Uri};
 
 #[cfg(all(not(test), not(feature = "check_example_domains")))]
 /// These domains are explicitly defined by RFC 2606, section 3 Reserved Example
@@ -38,6 +42,9 @@ static UNSUPPORTED_DOMAINS: Lazy<HashSet<&'static str>> = Lazy::new(|| {
     ])
 });
 
+/// Schemes of links which contain the resource (or code) itself
+const INLINE_SCHEMES: &[&str] = &["data", "javascript"];
+
 /// Pre-defined exclusions for known false-positives
 const FALSE_POSITIVE_PAT: &[&str] = &[
     r"^https?://schemas.openxmlformats.org",
@@ -119,6 +126,8 @@ pub struct Filter {
     // TODO: includes_scheme and excludes_scheme
     // TODO: excludes_mail should be an alias for exclude_scheme=mailto
     pub schemes: HashSet<String>,
+    /// Schemes which are never checked, e.g. `ftp`
+    pub denied_schemes: HashSet<String>,
     /// Example: 192.168.0.1
     pub exclude_private_ips: bool,
     /// Example: 169.254.0.0
@@ -128,6 +137,12 @@ pub struct Filter {
     pub exclude_loopback_ips: bool,
     /// Example: octocat@github.com
     pub include_mail: bool,
+    /// Example: tel:+1-555-0100
+    pub include_tel: bool,
+    /// URIs excluded from checking, but only for inputs in some directories
+    pub lycheeignore: LycheeIgnore,
+    /// Only check the canonical version of versioned documentation
+    pub versioned_docs: Option<VersionedDocs>,
 }
 
 impl Filter {
@@ -138,6 +153,13 @@ impl Filter {
         uri.is_mail() && !self.include_mail
     }
 
+    #[inline]
+    #[must_use]
+    /// Whether phone numbers aren't checked (which is the default)
+    pub fn is_tel_excluded(&self, uri: &Uri) -> bool {
+        uri.is_tel() && !self.include_tel
+    }
+
     #[must_use]
     /// Whether the IP address is excluded from checking
     pub fn is_ip_excluded(&self, uri: &Uri) -> bool {
@@ -158,14 +180,37 @@ impl Filter {
         self.exclude_loopback_ips && uri.domain() == Some("localhost")
     }
 
+    #[must_use]
+    /// Whether the URI points into a non-canonical version of versioned
+    /// documentation
+    pub fn is_version_excluded(&self, uri: &Uri) -> bool {
+        matches!(self.versioned_docs, Some(ref docs) if docs.is_other_version(uri))
+    }
+
     #[inline]
     #[must_use]
     /// Whether the scheme of the given URI is excluded
     pub fn is_scheme_excluded(&self, uri: &Uri) -> bool {
-        if self.schemes.is_empty() {
-            return false;
+        self.scheme_policy(uri).is_some()
+    }
+
+    #[must_use]
+    /// Why the scheme of the URI is excluded from checking (if it is)
+    ///
+    /// `data:` and `javascript:` links are always excluded. Other schemes are
+    /// excluded if they are denied or if allowed schemes are given, but the
+    /// scheme is not one of them.
+    pub fn scheme_policy(&self, uri: &Uri) -> Option<SchemePolicy> {
+        let scheme = uri.scheme();
+        if INLINE_SCHEMES.contains(&scheme) {
+            Some(SchemePolicy::Inline(scheme.to_string()))
+        } else if self.denied_schemes.contains(scheme) {
+            Some(SchemePolicy::Denied(scheme.to_string()))
+        } else if !self.schemes.is_empty() && !self.schemes.contains(scheme) {
+            Some(SchemePolicy::NotAllowed(scheme.to_string()))
+        } else {
+            None
         }
-        !self.schemes.contains(uri.scheme())
     }
 
     #[inline]
@@ -197,6 +242,7 @@ impl Filter {
     ///   - If the IP address belongs to a type that is configured to exclude.
     ///   - If the host belongs to a type that is configured to exclude.
     ///   - If the scheme of URI is not the allowed scheme.
+    ///   - If it points into a non-canonical version of versioned documentation.
     /// 2. Decide whether the URI is *presumably included* or *explicitly included*:
     ///    - When both excludes and includes rules are empty, it's *presumably included* unless
     ///      it's a known false positive.
@@ -214,7 +260,8 @@ impl Filter {
             || self.is_host_excluded(uri)
             || self.is_ip_excluded(uri)
             || self.is_mail_excluded(uri)
-            || uri.is_tel()
+            || self.is_version_excluded(uri)
+            || self.is_tel_excluded(uri)
             || is_example_domain(uri)
             || is_unsupported_domain(uri)
         {
@@ -248,10 +295,20 @@ impl Filter {
 
         false
     }
+
+    /// Like [`Filter::is_excluded`], but also takes `.lycheeignore` files
+    /// into account, which only apply to links found in some inputs.
+    #[must_use]
+    pub fn is_excluded_from(&self, uri: &Uri, source: &InputSource) -> bool {
+        self.is_excluded(uri)
+            || (!self.is_includes_match(uri.as_str()) && self.lycheeignore.is_match(uri, source))
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
     use regex::RegexSet;
     use reqwest::Url;
     use url::Host;
@@ -259,7 +316,7 @@ mod tests {
     use super::{Excludes, Filter, Includes};
     use crate::{
         test_utils::{mail, website},
-        Uri,
+        SchemePolicy, Uri,
     };
 
     // Note: the standard library, as of Rust stable 1.47.0, does not expose
@@ -400,6 +457,46 @@ mod tests {
         assert!(!filter.is_excluded(&website("http://bar.dev")));
     }
 
+    #[test]
+    fn test_scheme_policy() {
+        let filter = Filter {
+            schemes: HashSet::from(["https".to_string(), "ftp".to_string()]),
+            denied_schemes: HashSet::from(["ftp".to_string()]),
+            ..Filter::default()
+        };
+        let policy = |uri: &str| filter.scheme_policy(&Uri::try_from(uri).unwrap());
+
+        assert_eq!(policy("https://example.org"), None);
+        assert_eq!(
+            policy("javascript:void(0)"),
+            Some(SchemePolicy::Inline("javascript".to_string()))
+        );
+        assert_eq!(
+            policy("data:text/plain,hello"),
+            Some(SchemePolicy::Inline("data".to_string()))
+        );
+        assert_eq!(
+            policy("ftp://example.org"),
+            Some(SchemePolicy::Denied("ftp".to_string()))
+        );
+        assert_eq!(
+            policy("http://example.org"),
+            Some(SchemePolicy::NotAllowed("http".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_include_tel() {
+        let tel = Uri::try_from("tel:+1-555-0100").unwrap();
+        assert!(Filter::default().is_excluded(&tel));
+
+        let filter = Filter {
+            include_tel: true,
+            ..Filter::default()
+        };
+        assert!(!filter.is_excluded(&tel));
+    }
+
     #[test]
     fn test_exclude_regex() {
         let excludes = Excludes {
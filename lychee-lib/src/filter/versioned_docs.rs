@@ -0,0 +1,118 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::Uri;
+
+/// Default canonical version of versioned documentation
+pub const DEFAULT_DOCS_VERSION: &str = "latest";
+
+/// Path segments which are version numbers, e.g. `v1.2`, `2.0` or `3.x`
+static VERSION: Lazy<Regex> = Lazy::new(|| Regex::new(r"^v?\d+(\.(\d+|x))+$").unwrap());
+
+/// Path segments which are languages of the documentation, e.g. `en` or
+/// `pt-br`, which are followed by the version on Read the Docs
+static LANGUAGE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-z]{2}(-[a-z]{2,4})?$").unwrap());
+
+/// Names of versions which are not numbers, e.g. `/en/stable/` or
+/// `/docs/next/`. They are only versions after a language or `docs`.
+const NAMED_VERSIONS: &[&str] = &["latest", "stable", "dev", "next", "nightly"];
+
+/// Skip links into other versions of versioned documentation (e.g. Sphinx or
+/// Docusaurus sites), which mostly duplicate the links of the canonical
+/// version.
+///
+/// Versions are detected from the path of the links: version numbers like
+/// `/v1.2/` or `/2.0/` anywhere in the path, and names like `latest` or
+/// `stable` after a language (`/en/stable/`) or `docs` (`/docs/next/`). Links
+/// without a version are always checked.
+#[derive(Clone, Debug)]
+pub struct VersionedDocs {
+    /// The version which is checked, without a leading `v`
+    canonical: String,
+}
+
+impl VersionedDocs {
+    /// Only check the links into version `canonical` (e.g. `latest` or
+    /// `v2.0`) of versioned documentation
+    #[must_use]
+    pub fn new(canonical: &str) -> Self {
+        Self {
+            canonical: normalize(canonical),
+        }
+    }
+
+    /// Whether `uri` points into a version of the documentation other than
+    /// the canonical one
+    #[must_use]
+    pub fn is_other_version(&self, uri: &Uri) -> bool {
+        if !matches!(uri.scheme(), "http" | "https") {
+            return false;
+        }
+        version(uri).is_some_and(|version| normalize(version) != self.canonical)
+    }
+}
+
+impl Default for VersionedDocs {
+    fn default() -> Self {
+        Self::new(DEFAULT_DOCS_VERSION)
+    }
+}
+
+/// The first version in the path of `uri`, if any
+fn version(uri: &Uri) -> Option<&str> {
+    let segments: Vec<&str> = uri.url.path_segments()?.collect();
+    segments.iter().enumerate().find_map(|(index, segment)| {
+        let after_prefix =
+            index > 0 && (LANGUAGE.is_match(segments[index - 1]) || segments[index - 1] == "docs");
+        let is_version = VERSION.is_match(segment)
+            || (after_prefix && NAMED_VERSIONS.contains(&segment.to_lowercase().as_str()));
+        is_version.then_some(*segment)
+    })
+}
+
+/// Compare versions case-insensitively and without a leading `v`
+fn normalize(version: &str) -> String {
+    let version = version.to_lowercase();
+    match version.strip_prefix('v') {
+        Some(number) if number.starts_with(|c: char| c.is_ascii_digit()) => number.to_string(),
+        _ => version,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_other_version(docs: &VersionedDocs, url: &str) -> bool {
+        docs.is_other_version(&Uri::try_from(url).unwrap())
+    }
+
+    #[test]
+    fn test_named_versions() {
+        let docs = VersionedDocs::default();
+        assert!(!is_other_version(
+            &docs,
+            "https://lychee.readthedocs.io/en/latest/usage/"
+        ));
+        assert!(is_other_version(
+            &docs,
+            "https://lychee.readthedocs.io/en/stable/usage/"
+        ));
+        assert!(is_other_version(&docs, "https://lychee.rs/docs/next/intro"));
+        // Names are only versions after a language or `docs`
+        assert!(!is_other_version(&docs, "https://lychee.rs/blog/stable/"));
+        assert!(!is_other_version(&docs, "https://lychee.rs/intro"));
+    }
+
+    #[test]
+    fn test_version_numbers() {
+        let docs = VersionedDocs::new("v2.0");
+        assert!(!is_other_version(&docs, "https://lychee.rs/docs/2.0/intro"));
+        assert!(!is_other_version(&docs, "https://lychee.rs/v2.0/intro"));
+        assert!(is_other_version(&docs, "https://lychee.rs/v1.2/intro"));
+        assert!(is_other_version(&docs, "https://lychee.rs/en/1.x/intro"));
+        // API versions without a dot are not documentation versions
+        assert!(!is_other_version(&docs, "https://api.lychee.rs/v1/links"));
+        assert!(!is_other_version(&docs, "file:///docs/v1.2/intro"));
+    }
+}
@@ -0,0 +1,208 @@
+//! Output formats for the responses and the final statistics of a run.
+//!
+//! The lychee binary implements its output formats (like `compact` or
+//! `json`) with these traits. Tools which embed lychee can add their own
+//! formats without forking the binary: implement [`ResponseFormatter`] or
+//! [`StatsFormatter`] and register the implementation under a name in a
+//! [`FormatterRegistry`], e.g. the value of a command-line option.
+//!
+//! ```
+//! use lychee_lib::formatters::{FormatterRegistry, ResponseFormatter};
+//! use lychee_lib::ResponseBody;
+//!
+//! struct Urls;
+//!
+//! impl ResponseFormatter for Urls {
+//!     fn format_response(&self, body: &ResponseBody) -> String {
+//!         body.uri.to_string()
+//!     }
+//! }
+//!
+//! // The registry is generic over the statistics of the embedding tool
+//! let mut registry = FormatterRegistry::<()>::new();
+//! registry.register_response_formatter("urls", Urls);
+//! assert!(registry.response_formatter("urls").is_some());
+//! ```
+
+use std::{collections::BTreeMap, error::Error, fmt, sync::Arc};
+
+use crate::ResponseBody;
+
+/// Error of a [`StatsFormatter`]
+pub type FormatterError = Box<dyn Error + Send + Sync>;
+
+/// A trait for formatting a response body
+///
+/// This trait is used to convert response body into a human-readable string.
+/// It can be implemented for different formatting styles such as
+/// colorized output or plaintext.
+pub trait ResponseFormatter: Send + Sync {
+    /// Format the response body into a human-readable string
+    fn format_response(&self, body: &ResponseBody) -> String;
+
+    /// Detailed response formatter (defaults to the normal formatter)
+    ///
+    /// This can be used for output modes which want to provide more detailed
+    /// information. It is also used if the output is set to verbose mode
+    /// (i.e. `-v`, `-vv` and above).
+    fn format_detailed_response(&self, body: &ResponseBody) -> String {
+        self.format_response(body)
+    }
+}
+
+/// A trait for formatting the statistics `S` of all responses, e.g. for the
+/// final report of a run
+pub trait StatsFormatter<S>: Send + Sync {
+    /// Format the stats of all responses.
+    /// Returns `None` if there is nothing to print.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stats can't be formatted, e.g. serialized.
+    fn format(&self, stats: S) -> Result<Option<String>, FormatterError>;
+}
+
+/// Response and stats formatters by name
+///
+/// `S` is the type of statistics the stats formatters accept.
+pub struct FormatterRegistry<S> {
+    response_formatters: BTreeMap<String, Arc<dyn ResponseFormatter>>,
+    stats_formatters: BTreeMap<String, Arc<dyn StatsFormatter<S>>>,
+}
+
+impl<S> FormatterRegistry<S> {
+    /// Create a registry without any formatters
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            response_formatters: BTreeMap::new(),
+            stats_formatters: BTreeMap::new(),
+        }
+    }
+
+    /// Register a response `formatter` under `name`, replacing the formatter
+    /// which was registered under this name before (if any)
+    pub fn register_response_formatter(
+        &mut self,
+        name: impl Into<String>,
+        formatter: impl ResponseFormatter + 'static,
+    ) -> &mut Self {
+        self.response_formatters
+            .insert(name.into(), Arc::new(formatter));
+        self
+    }
+
+    /// Register a stats `formatter` under `name`, replacing the formatter
+    /// which was registered under this name before (if any)
+    pub fn register_stats_formatter(
+        &mut self,
+        name: impl Into<String>,
+        formatter: impl StatsFormatter<S> + 'static,
+    ) -> &mut Self {
+        self.stats_formatters
+            .insert(name.into(), Arc::new(formatter));
+        self
+    }
+
+    /// The response formatter registered under `name`
+    #[must_use]
+    pub fn response_formatter(&self, name: &str) -> Option<Arc<dyn ResponseFormatter>> {
+        self.response_formatters.get(name).cloned()
+    }
+
+    /// The stats formatter registered under `name`
+    #[must_use]
+    pub fn stats_formatter(&self, name: &str) -> Option<Arc<dyn StatsFormatter<S>>> {
+        self.stats_formatters.get(name).cloned()
+    }
+
+    /// Names of all response formatters in alphabetical order
+    pub fn response_formatter_names(&self) -> impl Iterator<Item = &str> {
+        self.response_formatters.keys().map(String::as_str)
+    }
+
+    /// Names of all stats formatters in alphabetical order
+    pub fn stats_formatter_names(&self) -> impl Iterator<Item = &str> {
+        self.stats_formatters.keys().map(String::as_str)
+    }
+}
+
+impl<S> Default for FormatterRegistry<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> fmt::Debug for FormatterRegistry<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FormatterRegistry")
+            .field(
+                "response_formatters",
+                &self.response_formatter_names().collect::<Vec<_>>(),
+            )
+            .field(
+                "stats_formatters",
+                &self.stats_formatter_names().collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use crate::{Status, Uri};
+
+    use super::*;
+
+    struct Code;
+
+    impl ResponseFormatter for Code {
+        fn format_response(&self, body: &ResponseBody) -> String {
+            body.status.code_as_string()
+        }
+    }
+
+    struct Count;
+
+    impl StatsFormatter<Vec<ResponseBody>> for Count {
+        fn format(&self, stats: Vec<ResponseBody>) -> Result<Option<String>, FormatterError> {
+            if stats.is_empty() {
+                return Err("no responses".into());
+            }
+            Ok(Some(format!("{} responses", stats.len())))
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = FormatterRegistry::new();
+        registry
+            .register_response_formatter("code", Code)
+            .register_stats_formatter("count", Count);
+
+        let body = ResponseBody {
+            uri: Uri::try_from("https://example.com").unwrap(),
+            status: Status::Ok(StatusCode::OK),
+            span: None,
+            label: None,
+        };
+        let formatter = registry.response_formatter("code").unwrap();
+        assert_eq!(formatter.format_response(&body), "200");
+        assert_eq!(formatter.format_detailed_response(&body), "200");
+        assert!(registry.response_formatter("count").is_none());
+
+        let formatter = registry.stats_formatter("count").unwrap();
+        assert_eq!(
+            formatter.format(vec![body]).unwrap().as_deref(),
+            Some("1 responses")
+        );
+        assert!(formatter.format(vec![]).is_err());
+
+        assert_eq!(
+            registry.stats_formatter_names().collect::<Vec<_>>(),
+            ["count"]
+        );
+    }
+}
@@ -50,13 +50,16 @@
 #[cfg(doctest)]
 doc_comment::doctest!("../../README.md");
 
+mod auth;
 mod basic_auth;
 pub mod chain;
 mod checker;
 mod client;
 /// A pool of clients, to handle concurrent checks
 pub mod collector;
+mod observer;
 mod quirks;
+pub mod ratelimit;
 mod retry;
 mod types;
 mod utils;
@@ -64,6 +67,10 @@ mod utils;
 /// Functionality to extract URIs from inputs
 pub mod extract;
 
+pub mod formatters;
+pub mod lint;
+pub mod path_mapping;
+pub mod proxy_pac;
 pub mod remap;
 
 /// Filters are a way to define behavior when encountering
@@ -85,20 +92,30 @@ use openssl_sys as _; // required for vendored-openssl feature
 
 #[doc(inline)]
 pub use crate::{
-    basic_auth::BasicAuthExtractor,
+    auth::OAuth2Config,
+    basic_auth::{BasicAuthExtractor, Netrc, NetrcError},
     // Expose the `Handler` trait to allow defining external handlers (plugins)
     chain::{ChainResult, Handler},
+    checker::{platform::PlatformChecker, Checker},
     // Constants get exposed so that the CLI can use the same defaults as the library
     client::{
-        check, Client, ClientBuilder, DEFAULT_MAX_REDIRECTS, DEFAULT_MAX_RETRIES,
-        DEFAULT_RETRY_WAIT_TIME_SECS, DEFAULT_TIMEOUT_SECS, DEFAULT_USER_AGENT,
+        check, Client, ClientBuilder, DEFAULT_MAIL_DNS_MAX_RETRIES, DEFAULT_MAIL_DNS_TIMEOUT_SECS,
+        DEFAULT_MAX_REDIRECTS, DEFAULT_MAX_RETRIES, DEFAULT_RETRY_WAIT_TIME_SECS,
+        DEFAULT_TIMEOUT_SECS, DEFAULT_USER_AGENT,
     },
     collector::Collector,
-    filter::{Excludes, Filter, Includes},
+    filter::{Excludes, Filter, Includes, LycheeIgnore, VersionedDocs, DEFAULT_DOCS_VERSION},
+    lint::{MailLint, MailLintKind, PermalinkLint, PermalinkLintKind},
+    observer::{CheckEvent, CheckObserver},
     types::{
-        uri::valid::Uri, AcceptRange, AcceptRangeError, Base, BasicAuthCredentials,
-        BasicAuthSelector, CacheStatus, CookieJar, ErrorKind, FileType, Input, InputContent,
-        InputSource, Request, Response, ResponseBody, Result, Status, StatusCodeExcluder,
-        StatusCodeSelector,
+        uri::{kind::UriKind, raw::RawUriSpan, valid::Uri},
+        AcceptRange, AcceptRangeError, AnchorCache, Base, BasicAuthCredentials, BasicAuthSelector,
+        CacheStatus, ContentAssertion, ContentOptions, CookieJar, ErrorKind, FileExtensions,
+        FileType, FragmentStyle, GeneratedMarkers, HttpVersion, Input, InputContent, InputSource,
+        MailCheckLevel, Platform, PlatformConfig, Redirect, Redirects, ReferenceIssue,
+        ReferenceIssueKind, Request, Response, ResponseBody, Result, SameDomainPolicy,
+        SchemePolicy, SkipReason, Status, StatusCodeExcluder, StatusCodeSelector, TlsVersion,
+        DEFAULT_GENERATED_END, DEFAULT_GENERATED_START,
     },
+    utils::archive::UnpackedDir,
 };
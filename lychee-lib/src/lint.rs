@@ -0,0 +1,357 @@
+//! Lints for links which can be reached, but are still worth pointing out.
+//!
+//! Lints never turn a link into an error. They are reported as warnings
+//! alongside the regular check results, so that the document author can
+//! decide whether to act on them.
+
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::{types::uri::gitlab::DEFAULT_GITLAB_HOST, Uri};
+
+/// Domains of well-known disposable (throwaway) mail providers, which are
+/// flagged by default.
+pub const DEFAULT_DISPOSABLE_MAIL_DOMAINS: &[&str] = &[
+    "10minutemail.com",
+    "dispostable.com",
+    "getnada.com",
+    "guerrillamail.com",
+    "mailinator.com",
+    "maildrop.cc",
+    "sharklasers.com",
+    "temp-mail.org",
+    "tempmail.com",
+    "throwawaymail.com",
+    "trashmail.com",
+    "yopmail.com",
+];
+
+/// Local parts of role accounts (e.g. `noreply@`), which are flagged by
+/// default.
+pub const DEFAULT_ROLE_MAIL_ACCOUNTS: &[&str] = &[
+    "admin",
+    "administrator",
+    "donotreply",
+    "do-not-reply",
+    "hostmaster",
+    "no-reply",
+    "noreply",
+    "postmaster",
+    "root",
+    "webmaster",
+];
+
+/// The reason why a mail address was flagged by the [`MailLint`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum MailLintKind {
+    /// The address belongs to a disposable mail provider
+    DisposableDomain(String),
+    /// The address is a role account rather than a maintained contact
+    RoleAccount(String),
+}
+
+impl Display for MailLintKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DisposableDomain(domain) => write!(f, "Disposable mail domain `{domain}`"),
+            Self::RoleAccount(account) => write!(f, "Role account `{account}@`"),
+        }
+    }
+}
+
+/// A lint which flags mail addresses that are unlikely to be maintained
+/// contact addresses, such as addresses on disposable domains or role accounts
+/// like `noreply@`.
+///
+/// Matching is case-insensitive. Subdomains of a disposable domain are
+/// flagged as well.
+#[derive(Debug, Clone)]
+pub struct MailLint {
+    disposable_domains: HashSet<String>,
+    role_accounts: HashSet<String>,
+}
+
+impl Default for MailLint {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_DISPOSABLE_MAIL_DOMAINS.iter().copied(),
+            DEFAULT_ROLE_MAIL_ACCOUNTS.iter().copied(),
+        )
+    }
+}
+
+impl MailLint {
+    /// Create a new mail lint from a list of disposable domains and a list of
+    /// role account names (the part before the `@`)
+    #[must_use]
+    pub fn new<D, R>(disposable_domains: D, role_accounts: R) -> Self
+    where
+        D: IntoIterator,
+        D::Item: AsRef<str>,
+        R: IntoIterator,
+        R::Item: AsRef<str>,
+    {
+        Self {
+            disposable_domains: disposable_domains
+                .into_iter()
+                .map(|d| d.as_ref().trim().to_lowercase())
+                .collect(),
+            role_accounts: role_accounts
+                .into_iter()
+                .map(|r| r.as_ref().trim().trim_end_matches('@').to_lowercase())
+                .collect(),
+        }
+    }
+
+    /// Check the given URI and return the reason why it was flagged, if any.
+    ///
+    /// URIs which are not mail addresses are never flagged.
+    #[must_use]
+    pub fn check(&self, uri: &Uri) -> Option<MailLintKind> {
+        if !uri.is_mail() {
+            return None;
+        }
+        let (local, domain) = uri.path().rsplit_once('@')?;
+        let local = local.to_lowercase();
+        let domain = domain.to_lowercase();
+
+        if self.role_accounts.contains(&local) {
+            return Some(MailLintKind::RoleAccount(local));
+        }
+
+        self.disposable_domains
+            .iter()
+            .find(|disposable| {
+                domain == **disposable || domain.ends_with(&format!(".{disposable}"))
+            })
+            .map(|disposable| MailLintKind::DisposableDomain(disposable.clone()))
+    }
+}
+
+/// The reason why a link to code was flagged by the [`PermalinkLint`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum PermalinkLintKind {
+    /// The link points to lines of a file in a branch (or tag) instead of a
+    /// commit, so the lines drift as the branch changes
+    BranchLineLink(String),
+}
+
+impl Display for PermalinkLintKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BranchLineLink(reference) => {
+                write!(
+                    f,
+                    "Line anchor into branch `{reference}` instead of a commit"
+                )
+            }
+        }
+    }
+}
+
+/// A link to lines of a file in a repository, e.g.
+/// `https://github.com/owner/repo/blob/main/src/lib.rs#L10`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeLink {
+    /// Host of the repository, e.g. `github.com`
+    pub host: String,
+    /// Path of the repository, e.g. `owner/repo` or `group/subgroup/repo` on
+    /// GitLab
+    pub repo: String,
+    /// The branch, tag or commit which the link points into
+    pub reference: String,
+}
+
+impl CodeLink {
+    /// Parse links to lines of files on GitHub (`/owner/repo/blob/REF/...`)
+    /// and GitLab (`/group/repo/-/blob/REF/...`) with a line anchor like
+    /// `#L10` or `#L10-L20`
+    #[must_use]
+    pub fn parse(uri: &Uri, gitlab_hosts: &HashSet<String>) -> Option<Self> {
+        let anchor = uri.url.fragment()?.strip_prefix('L')?;
+        if !anchor.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        let host = uri.host_str()?;
+        let segments: Vec<_> = uri.path_segments()?.collect();
+        let (repo, reference) = if host == "github.com" {
+            match segments.as_slice() {
+                [owner, repo, "blob", reference, _, ..] => (format!("{owner}/{repo}"), reference),
+                _ => return None,
+            }
+        } else if gitlab_hosts.contains(host) {
+            let separator = segments.iter().position(|segment| *segment == "-")?;
+            match &segments[separator..] {
+                ["-", "blob", reference, _, ..] if separator >= 2 => {
+                    (segments[..separator].join("/"), reference)
+                }
+                _ => return None,
+            }
+        } else {
+            return None;
+        };
+        Some(Self {
+            host: host.to_string(),
+            repo,
+            reference: (*reference).to_string(),
+        })
+    }
+
+    /// Whether the link points into a commit, i.e. the reference looks like
+    /// an (abbreviated) commit SHA
+    #[must_use]
+    pub fn is_pinned(&self) -> bool {
+        (7..=40).contains(&self.reference.len())
+            && self.reference.chars().all(|c| c.is_ascii_hexdigit())
+    }
+}
+
+/// A lint which flags links to lines of files on GitHub or GitLab that point
+/// into a branch instead of a commit. The lines of a branch change over time,
+/// so these links silently point to other code.
+#[derive(Debug, Clone)]
+pub struct PermalinkLint {
+    gitlab_hosts: HashSet<String>,
+}
+
+impl Default for PermalinkLint {
+    fn default() -> Self {
+        Self::new(std::iter::empty::<&str>())
+    }
+}
+
+impl PermalinkLint {
+    /// Create a new permalink lint for links to `github.com`, `gitlab.com`
+    /// and the self-hosted GitLab instances at `gitlab_hosts`
+    #[must_use]
+    pub fn new<H>(gitlab_hosts: H) -> Self
+    where
+        H: IntoIterator,
+        H::Item: AsRef<str>,
+    {
+        Self {
+            gitlab_hosts: gitlab_hosts
+                .into_iter()
+                .map(|host| host.as_ref().to_lowercase())
+                .chain(std::iter::once(DEFAULT_GITLAB_HOST.to_string()))
+                .collect(),
+        }
+    }
+
+    /// The link to lines of code of `uri`, if any
+    #[must_use]
+    pub fn code_link(&self, uri: &Uri) -> Option<CodeLink> {
+        CodeLink::parse(uri, &self.gitlab_hosts)
+    }
+
+    /// Check the given URI and return the reason why it was flagged, if any.
+    #[must_use]
+    pub fn check(&self, uri: &Uri) -> Option<PermalinkLintKind> {
+        let link = self.code_link(uri)?;
+        (!link.is_pinned()).then_some(PermalinkLintKind::BranchLineLink(link.reference))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{mail, website};
+
+    #[test]
+    fn test_role_account() {
+        let lint = MailLint::default();
+        assert_eq!(
+            lint.check(&mail("noreply@lychee.cli.rs")),
+            Some(MailLintKind::RoleAccount("noreply".to_string()))
+        );
+        assert_eq!(
+            lint.check(&mail("Admin@lychee.cli.rs")),
+            Some(MailLintKind::RoleAccount("admin".to_string()))
+        );
+        assert_eq!(lint.check(&mail("matthias@lychee.cli.rs")), None);
+    }
+
+    #[test]
+    fn test_disposable_domain() {
+        let lint = MailLint::default();
+        assert_eq!(
+            lint.check(&mail("someone@mailinator.com")),
+            Some(MailLintKind::DisposableDomain("mailinator.com".to_string()))
+        );
+        assert_eq!(
+            lint.check(&mail("someone@eu.Mailinator.com")),
+            Some(MailLintKind::DisposableDomain("mailinator.com".to_string()))
+        );
+        assert_eq!(lint.check(&mail("someone@notmailinator.com")), None);
+    }
+
+    #[test]
+    fn test_custom_lists() {
+        let lint = MailLint::new(["example.org"], ["support@"]);
+        assert_eq!(
+            lint.check(&mail("support@lychee.cli.rs")),
+            Some(MailLintKind::RoleAccount("support".to_string()))
+        );
+        assert_eq!(
+            lint.check(&mail("hello@example.org")),
+            Some(MailLintKind::DisposableDomain("example.org".to_string()))
+        );
+        assert_eq!(lint.check(&mail("noreply@lychee.cli.rs")), None);
+    }
+
+    #[test]
+    fn test_ignore_websites() {
+        let lint = MailLint::default();
+        assert_eq!(lint.check(&website("https://mailinator.com/admin")), None);
+    }
+
+    #[test]
+    fn test_permalinks() {
+        let lint = PermalinkLint::new(["git.example.com"]);
+        assert_eq!(
+            lint.check(&website(
+                "https://github.com/lycheeverse/lychee/blob/master/lychee-lib/src/lib.rs#L10"
+            )),
+            Some(PermalinkLintKind::BranchLineLink("master".to_string()))
+        );
+        assert_eq!(
+            lint.check(&website(
+                "https://gitlab.com/group/subgroup/repo/-/blob/main/src/lib.rs#L10-20"
+            )),
+            Some(PermalinkLintKind::BranchLineLink("main".to_string()))
+        );
+        assert_eq!(
+            lint.code_link(&website(
+                "https://git.example.com/group/repo/-/blob/v1.0/README.md#L1"
+            )),
+            Some(CodeLink {
+                host: "git.example.com".to_string(),
+                repo: "group/repo".to_string(),
+                reference: "v1.0".to_string(),
+            })
+        );
+        // Commits, links without line anchors and other hosts are fine
+        assert_eq!(
+            lint.check(&website(
+                "https://github.com/lycheeverse/lychee/blob/3f2a9c1/lychee-lib/src/lib.rs#L10"
+            )),
+            None
+        );
+        assert_eq!(
+            lint.check(&website(
+                "https://github.com/lycheeverse/lychee/blob/master/README.md#installation"
+            )),
+            None
+        );
+        assert_eq!(
+            lint.check(&website(
+                "https://codeberg.org/forgejo/forgejo/src/branch/main/README.md#L1"
+            )),
+            None
+        );
+    }
+}
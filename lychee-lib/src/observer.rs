@@ -0,0 +1,78 @@
+//! Progress callbacks of a [`Client`](crate::Client).
+//!
+//! Tools which embed lychee (e.g. documentation generators) can show the
+//! progress of a check without consuming the responses themselves. An
+//! observer is set with
+//! [`ClientBuilder::observer`](crate::ClientBuilder::observer) and notified
+//! of every [`CheckEvent`] of the client.
+
+use std::fmt::Debug;
+use std::time::Duration;
+
+use crate::{Response, Status, Uri};
+
+/// An event in the check of a single link
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CheckEvent<'a> {
+    /// The link passed the filters and waits to be checked, e.g. for the
+    /// rate limits of its host
+    RequestQueued(&'a Uri),
+    /// The link is checked. For websites, this is sent for every request,
+    /// including retries.
+    CheckStarted(&'a Uri),
+    /// A failed request of the link is retried after a pause
+    Retry {
+        /// The link
+        uri: &'a Uri,
+        /// Number of the retry, starting at 1
+        attempt: u64,
+        /// Status of the failed request
+        status: &'a Status,
+    },
+    /// The check of the link is done, including links which were excluded
+    CheckCompleted {
+        /// The result of the check
+        response: &'a Response,
+        /// Time since the link was queued, or `None` if it was excluded
+        duration: Option<Duration>,
+    },
+}
+
+/// Receives the [`CheckEvent`]s of a [`Client`](crate::Client)
+///
+/// The events of concurrent checks are sent from their tasks, so
+/// implementations must be cheap and must not block.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+///
+/// use lychee_lib::{CheckEvent, CheckObserver, ClientBuilder};
+///
+/// #[derive(Debug, Default)]
+/// struct Completed(AtomicUsize);
+///
+/// impl CheckObserver for Completed {
+///     fn on_event(&self, event: &CheckEvent<'_>) {
+///         if let CheckEvent::CheckCompleted { .. } = event {
+///             self.0.fetch_add(1, Ordering::Relaxed);
+///         }
+///     }
+/// }
+///
+/// # fn main() -> lychee_lib::Result<()> {
+/// let completed = Arc::new(Completed::default());
+/// let _client = ClientBuilder::builder()
+///     .observer(Some(completed.clone() as Arc<dyn CheckObserver>))
+///     .build()
+///     .client()?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait CheckObserver: Debug + Send + Sync {
+    /// Called for each event of the client
+    fn on_event(&self, event: &CheckEvent<'_>);
+}
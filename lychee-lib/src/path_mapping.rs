@@ -0,0 +1,194 @@
+//! Mappings from source files to the URL paths at which static site
+//! generators (like mdBook, Hugo or Docusaurus) publish them.
+//!
+//! A site generator might publish `docs/guide/install.md` as
+//! `/guide/install/`. Links to `/guide/install/` don't exist in the source
+//! tree, so they can only be checked once the site is built. With a mapping
+//! like `docs/(.*)\.md -> /$1/`, such links are checked against the source
+//! files instead.
+//!
+//! # Notes
+//! Rules are checked sequentially and the first matching rule of a file wins.
+//! Patterns must match the whole path of a file relative to the root
+//! directory, using `/` as separator.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use crate::{ErrorKind, Result};
+
+/// Separator of the pattern and the URL path of a rule
+const SEPARATOR: &str = "->";
+
+/// Rules which map source files to the URL paths of their pages
+#[derive(Debug, Clone)]
+pub struct PathMappings {
+    root: PathBuf,
+    rules: Vec<(Regex, String)>,
+}
+
+impl PathMappings {
+    /// Create mappings for the source files below `root`, which is also the
+    /// root of the site (e.g. the directory given with `--root-dir`)
+    #[must_use]
+    pub const fn new(root: PathBuf, rules: Vec<(Regex, String)>) -> Self {
+        Self { root, rules }
+    }
+
+    /// Parse rules of the form `PATTERN -> PATH`, e.g. `docs/(.*)\.md -> /$1/`.
+    /// `PATH` can refer to the groups of `PATTERN` like [`Regex::replace`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if a rule is not of the form `PATTERN -> PATH` or
+    /// `PATTERN` is not a valid regular expression.
+    pub fn parse(root: PathBuf, rules: &[String]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                let Some((pattern, path)) = rule.split_once(SEPARATOR) else {
+                    return Err(ErrorKind::InvalidPathMapping(format!(
+                        "Path mappings must be of the form `PATTERN -> PATH`, got {rule}"
+                    )));
+                };
+                let pattern = Regex::new(&format!("^(?:{})$", pattern.trim())).map_err(|e| {
+                    ErrorKind::InvalidPathMapping(format!("Invalid pattern in {rule}: {e}"))
+                })?;
+                Ok((pattern, path.trim().to_string()))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self::new(root, rules))
+    }
+
+    /// The root directory of the source files and the site
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The URL path of the source file at `path` (relative to the root), or
+    /// `None` if no rule matches
+    #[must_use]
+    pub fn url_path(&self, path: &str) -> Option<String> {
+        self.rules
+            .iter()
+            .find(|(pattern, _)| pattern.is_match(path))
+            .map(|(pattern, replacement)| normalize(&pattern.replace(path, replacement)))
+    }
+
+    /// The source files below the root by the URL paths of their pages.
+    ///
+    /// Hidden files and files ignored by `.gitignore` are left out, like
+    /// when collecting inputs from directories.
+    #[must_use]
+    pub fn index(&self) -> HashMap<String, PathBuf> {
+        let mut index = HashMap::new();
+        for entry in ignore::WalkBuilder::new(&self.root).build().flatten() {
+            if !entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file())
+            {
+                continue;
+            }
+            let Some(relative) = relative_path(&self.root, entry.path()) else {
+                continue;
+            };
+            if let Some(url_path) = self.url_path(&relative) {
+                index.entry(url_path).or_insert_with(|| entry.into_path());
+            }
+        }
+        index
+    }
+
+    /// The URL path which `path` (e.g. a resolved link to `/guide/install/`)
+    /// stands for, or `None` if it is outside of the root
+    #[must_use]
+    pub fn url_path_of(&self, path: &Path) -> Option<String> {
+        relative_path(&self.root, path).map(|relative| normalize(&relative))
+    }
+}
+
+/// The path of `path` relative to `root` with `/` as separator
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let components: Vec<_> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+    Some(components.join("/"))
+}
+
+/// Compare URL paths with a leading slash and without a trailing slash, so
+/// that `/guide/install/` and `/guide/install` are the same page
+fn normalize(path: &str) -> String {
+    format!("/{}", path.trim_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn mappings(root: &Path) -> PathMappings {
+        PathMappings::parse(
+            root.to_path_buf(),
+            &[
+                r"docs/index\.md -> /".to_string(),
+                r"docs/(.*)\.md -> /$1/".to_string(),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_url_path() {
+        let mappings = mappings(Path::new("/project"));
+        assert_eq!(mappings.url_path("docs/index.md"), Some("/".to_string()));
+        assert_eq!(
+            mappings.url_path("docs/guide/install.md"),
+            Some("/guide/install".to_string())
+        );
+        // Patterns match whole paths
+        assert_eq!(mappings.url_path("src/docs/install.md"), None);
+        assert_eq!(mappings.url_path("README.md"), None);
+
+        assert_eq!(
+            mappings.url_path_of(Path::new("/project/guide/install/")),
+            Some("/guide/install".to_string())
+        );
+        assert_eq!(mappings.url_path_of(Path::new("/elsewhere/guide")), None);
+    }
+
+    #[test]
+    fn test_index() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("docs/guide")).unwrap();
+        fs::write(dir.path().join("docs/index.md"), "").unwrap();
+        fs::write(dir.path().join("docs/guide/install.md"), "").unwrap();
+        fs::write(dir.path().join("README.md"), "").unwrap();
+
+        let index = mappings(dir.path()).index();
+        assert_eq!(
+            index,
+            HashMap::from([
+                ("/".to_string(), dir.path().join("docs/index.md")),
+                (
+                    "/guide/install".to_string(),
+                    dir.path().join("docs/guide/install.md")
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let root = PathBuf::from("/project");
+        assert!(PathMappings::parse(root.clone(), &["docs/(.*)\\.md /$1/".to_string()]).is_err());
+        assert!(PathMappings::parse(root, &["docs/(.*\\.md -> /$1/".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,1118 @@
+//! Proxy auto-config (PAC) files, which select the proxy for each URL.
+//!
+//! PAC files are JavaScript programs with a `FindProxyForURL(url, host)`
+//! function. Instead of embedding a JavaScript engine, lychee evaluates the
+//! subset of JavaScript which PAC files typically consist of:
+//!
+//! - `if`/`else` statements, `return` statements, `var` declarations,
+//!   assignments and calls
+//! - `!`, `&&`, `||`, comparisons (`==`, `!=`, `<`, ...), `+` and `-` of
+//!   strings and numbers
+//! - the string methods `toLowerCase`, `toUpperCase`, `substring` and
+//!   `indexOf` and the `length` property
+//! - the PAC functions `isPlainHostName`, `dnsDomainIs`,
+//!   `localHostOrDomainIs`, `shExpMatch`, `isInNet`, `isResolvable`,
+//!   `dnsResolve`, `myIpAddress`, `dnsDomainLevels`, `weekdayRange`,
+//!   `timeRange` and `alert`
+//!
+//! Files with other constructs (e.g. `dateRange` or loops) are rejected when
+//! they are parsed. If evaluating `FindProxyForURL` fails, lychee warns and
+//! connects directly.
+//!
+//! `isInNet`, `isResolvable` and `dnsResolve` resolve host names to their
+//! IPv4 address. The address of each host and of this machine is only looked
+//! up once, and on a multi-threaded runtime the lookup doesn't block the
+//! other requests. A host which can't be resolved is in no network, and
+//! `dnsResolve` returns an empty string for it instead of `null`.
+//!
+//! `weekdayRange` and `timeRange` always use UTC, as if `"GMT"` was passed.
+//!
+//! Of the proxies returned by `FindProxyForURL` (e.g. `PROXY a:8080; DIRECT`),
+//! the first `PROXY`, `HTTP`, `HTTPS` or `DIRECT` entry is used. SOCKS proxies
+//! are not supported and skipped.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    net::{IpAddr, Ipv4Addr, ToSocketAddrs, UdpSocket},
+    ops::RangeInclusive,
+    sync::{Arc, Mutex, OnceLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::{debug, warn};
+use tokio::runtime::{Handle, RuntimeFlavor};
+use url::Url;
+
+use crate::{ErrorKind, Result};
+
+/// A parsed proxy auto-config file.
+///
+/// See module level documentation for the supported subset of JavaScript.
+#[derive(Debug, Clone)]
+pub struct ProxyPac {
+    /// Names of the `url` and `host` parameters of `FindProxyForURL`
+    params: (String, String),
+    /// Body of `FindProxyForURL`
+    body: Vec<Stmt>,
+    /// Addresses of the hosts which were resolved so far
+    resolver: Resolver,
+}
+
+impl ProxyPac {
+    /// Parse the `script` of a PAC file
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the script is not a `FindProxyForURL` function or
+    /// uses unsupported JavaScript.
+    pub fn parse(script: &str) -> Result<Self> {
+        let tokens = tokenize(script).map_err(ErrorKind::InvalidProxyPac)?;
+        Parser { tokens, pos: 0 }
+            .pac()
+            .map_err(ErrorKind::InvalidProxyPac)
+    }
+
+    /// Returns the proxy for `url`, or `None` to connect directly
+    #[must_use]
+    pub fn find_proxy(&self, url: &Url) -> Option<Url> {
+        self.find_proxy_at(url, SystemTime::now())
+    }
+
+    /// Returns the proxy for `url` at the time `now`
+    fn find_proxy_at(&self, url: &Url, now: SystemTime) -> Option<Url> {
+        let host = url.host_str().unwrap_or_default();
+        let mut vars = HashMap::from([
+            (self.params.0.clone(), Value::Str(url.to_string())),
+            (self.params.1.clone(), Value::Str(host.to_string())),
+        ]);
+        let context = Context {
+            resolver: &self.resolver,
+            now: now.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+        };
+        match run(&self.body, &mut vars, &context) {
+            Ok(Some(Value::Str(proxies))) => parse_proxies(&proxies),
+            Ok(_) => None,
+            Err(e) => {
+                warn!("Cannot evaluate proxy auto-config for {url}, connecting directly: {e}");
+                None
+            }
+        }
+    }
+}
+
+/// Resolves host names to IPv4 addresses, caching the results.
+///
+/// Shared between clones of a [`ProxyPac`].
+#[derive(Debug, Clone, Default)]
+struct Resolver {
+    hosts: Arc<Mutex<HashMap<String, Option<Ipv4Addr>>>>,
+    my_ip_address: Arc<OnceLock<Ipv4Addr>>,
+}
+
+impl Resolver {
+    /// The IPv4 address of `host`, or `None` if it can't be resolved
+    fn resolve(&self, host: &str) -> Option<Ipv4Addr> {
+        if let Ok(ip) = host.parse() {
+            return Some(ip);
+        }
+        if let Some(ip) = self.hosts.lock().unwrap().get(host) {
+            return *ip;
+        }
+        let ip = blocking(|| {
+            (host, 0).to_socket_addrs().ok().and_then(|mut addrs| {
+                addrs.find_map(|addr| match addr.ip() {
+                    IpAddr::V4(ip) => Some(ip),
+                    IpAddr::V6(_) => None,
+                })
+            })
+        });
+        self.hosts.lock().unwrap().insert(host.to_string(), ip);
+        ip
+    }
+
+    /// The IPv4 address of this machine for `myIpAddress`
+    fn my_ip_address(&self) -> Ipv4Addr {
+        *self.my_ip_address.get_or_init(|| blocking(my_ip_address))
+    }
+}
+
+/// Runs the blocking function `f`.
+///
+/// The proxy is selected synchronously while reqwest connects, so lookups
+/// can't be awaited. On a multi-threaded runtime, the tasks of the current
+/// worker are moved to other workers in the meantime.
+fn blocking<T>(f: impl FnOnce() -> T) -> T {
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(f)
+        }
+        _ => f(),
+    }
+}
+
+/// The IPv4 address of this machine, or the loopback address if there is no
+/// network
+fn my_ip_address() -> Ipv4Addr {
+    // Connecting a UDP socket sends no packets, but selects the address of
+    // the interface which routes to the (documentation) address
+    UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+        .and_then(|socket| {
+            socket.connect((Ipv4Addr::new(192, 0, 2, 1), 80))?;
+            socket.local_addr()
+        })
+        .ok()
+        .and_then(|addr| match addr.ip() {
+            IpAddr::V4(ip) if !ip.is_unspecified() => Some(ip),
+            _ => None,
+        })
+        .unwrap_or(Ipv4Addr::LOCALHOST)
+}
+
+/// Returns the first supported proxy of a `FindProxyForURL` result like
+/// `PROXY a:8080; DIRECT`
+fn parse_proxies(proxies: &str) -> Option<Url> {
+    for entry in proxies.split(';') {
+        let mut parts = entry.split_whitespace();
+        let scheme = match parts.next().map(str::to_ascii_uppercase).as_deref() {
+            Some("DIRECT") => return None,
+            Some("PROXY" | "HTTP") => "http",
+            Some("HTTPS") => "https",
+            _ => continue,
+        };
+        if let Some(proxy) = parts
+            .next()
+            .and_then(|address| Url::parse(&format!("{scheme}://{address}")).ok())
+        {
+            return Some(proxy);
+        }
+    }
+    None
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Num(i64),
+    Punct(&'static str),
+}
+
+/// Punctuation of the supported JavaScript, longest first
+const PUNCTUATION: [&str; 21] = [
+    "===", "!==", "==", "!=", "<=", ">=", "&&", "||", "(", ")", "{", "}", ",", ";", "!", "<", ">",
+    "=", ".", "+", "-",
+];
+
+fn tokenize(script: &str) -> PacResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = script;
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+        } else if let Some(comment) = rest.strip_prefix("//") {
+            rest = comment.find('\n').map_or("", |end| &comment[end..]);
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            let end = comment.find("*/").ok_or("Unterminated comment")?;
+            rest = &comment[end + 2..];
+        } else if c == '"' || c == '\'' {
+            let mut value = String::new();
+            let mut chars = rest[1..].char_indices();
+            let end = loop {
+                match chars.next() {
+                    Some((i, q)) if q == c => break i + 2,
+                    Some((_, '\\')) => value.extend(chars.next().map(|(_, e)| e)),
+                    Some((_, other)) => value.push(other),
+                    None => return Err("Unterminated string".to_string()),
+                }
+            };
+            tokens.push(Token::Str(value));
+            rest = &rest[end..];
+        } else if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let number = rest[..end].parse().map_err(|_| "Number too large")?;
+            tokens.push(Token::Num(number));
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else {
+            let punct = PUNCTUATION
+                .into_iter()
+                .find(|p| rest.starts_with(p))
+                .ok_or_else(|| format!("Unsupported character `{c}`"))?;
+            tokens.push(Token::Punct(punct));
+            rest = &rest[punct.len()..];
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone)]
+enum Stmt {
+    If(Expr, Vec<Stmt>, Vec<Stmt>),
+    Return(Expr),
+    /// A `var` declaration or an assignment
+    Var(String, Expr),
+    /// An expression whose value is discarded, e.g. a call of `alert`
+    Expr(Expr),
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Value(Value),
+    Var(String),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Compare(&'static str, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+    Method(Box<Expr>, Method, Vec<Expr>),
+    Length(Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Function {
+    IsPlainHostName,
+    DnsDomainIs,
+    LocalHostOrDomainIs,
+    ShExpMatch,
+    IsInNet,
+    IsResolvable,
+    DnsResolve,
+    MyIpAddress,
+    DnsDomainLevels,
+    WeekdayRange,
+    TimeRange,
+    Alert,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<(Self, RangeInclusive<usize>)> {
+        Some(match name {
+            "isPlainHostName" => (Self::IsPlainHostName, 1..=1),
+            "dnsDomainIs" => (Self::DnsDomainIs, 2..=2),
+            "localHostOrDomainIs" => (Self::LocalHostOrDomainIs, 2..=2),
+            "shExpMatch" => (Self::ShExpMatch, 2..=2),
+            "isInNet" => (Self::IsInNet, 3..=3),
+            "isResolvable" => (Self::IsResolvable, 1..=1),
+            "dnsResolve" => (Self::DnsResolve, 1..=1),
+            "myIpAddress" => (Self::MyIpAddress, 0..=0),
+            "dnsDomainLevels" => (Self::DnsDomainLevels, 1..=1),
+            "weekdayRange" => (Self::WeekdayRange, 1..=3),
+            "timeRange" => (Self::TimeRange, 1..=7),
+            "alert" => (Self::Alert, 1..=1),
+            _ => return None,
+        })
+    }
+
+    fn call(self, args: &[Value], context: &Context) -> PacResult<Value> {
+        match self {
+            Self::WeekdayRange => return Ok(Value::Bool(weekday_range(args, context.now))),
+            Self::TimeRange => return time_range(args, context.now).map(Value::Bool),
+            _ => {}
+        }
+        let resolver = context.resolver;
+        let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+        Ok(match (self, args.as_slice()) {
+            (Self::IsPlainHostName, [host]) => Value::Bool(!host.contains('.')),
+            (Self::DnsDomainIs, [host, domain]) => Value::Bool(host.ends_with(domain.as_str())),
+            (Self::LocalHostOrDomainIs, [host, hostdom]) => Value::Bool(
+                host == hostdom
+                    || (!host.contains('.') && hostdom.starts_with(&format!("{host}."))),
+            ),
+            (Self::ShExpMatch, [text, pattern]) => Value::Bool(shell_match(text, pattern)),
+            (Self::IsInNet, [host, pattern, mask]) => {
+                let parse = |s: &String| s.parse::<Ipv4Addr>().ok().map(u32::from);
+                let host = resolver.resolve(host).map(u32::from);
+                Value::Bool(match (host, parse(pattern), parse(mask)) {
+                    (Some(host), Some(pattern), Some(mask)) => host & mask == pattern & mask,
+                    _ => false,
+                })
+            }
+            (Self::IsResolvable, [host]) => Value::Bool(resolver.resolve(host).is_some()),
+            (Self::DnsResolve, [host]) => Value::Str(
+                resolver
+                    .resolve(host)
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+            ),
+            (Self::MyIpAddress, []) => Value::Str(resolver.my_ip_address().to_string()),
+            (Self::DnsDomainLevels, [host]) => {
+                Value::Num(i64::try_from(host.matches('.').count()).unwrap_or(i64::MAX))
+            }
+            (Self::Alert, [message]) => {
+                debug!("Proxy auto-config: {message}");
+                Value::Bool(false)
+            }
+            // The number of arguments is checked by the parser
+            _ => Value::Bool(false),
+        })
+    }
+}
+
+/// Methods of strings
+#[derive(Debug, Clone, Copy)]
+enum Method {
+    ToLowerCase,
+    ToUpperCase,
+    Substring,
+    IndexOf,
+}
+
+impl Method {
+    fn from_name(name: &str) -> Option<(Self, RangeInclusive<usize>)> {
+        Some(match name {
+            "toLowerCase" => (Self::ToLowerCase, 0..=0),
+            "toUpperCase" => (Self::ToUpperCase, 0..=0),
+            "substring" => (Self::Substring, 1..=2),
+            "indexOf" => (Self::IndexOf, 1..=1),
+            _ => return None,
+        })
+    }
+
+    fn call(self, s: &str, args: &[Value]) -> PacResult<Value> {
+        Ok(match (self, args) {
+            (Self::ToLowerCase, []) => Value::Str(s.to_lowercase()),
+            (Self::ToUpperCase, []) => Value::Str(s.to_uppercase()),
+            (Self::Substring, [start, end @ ..]) => {
+                // Indices are clamped to the string and swapped if needed
+                let len = s.chars().count();
+                let index = |value: &Value| {
+                    value
+                        .number()
+                        .map(|n| usize::try_from(n).unwrap_or(0).min(len))
+                };
+                let start = index(start)?;
+                let end = match end {
+                    [end] => index(end)?,
+                    _ => len,
+                };
+                let (start, end) = (start.min(end), start.max(end));
+                Value::Str(s.chars().skip(start).take(end - start).collect())
+            }
+            (Self::IndexOf, [search]) => Value::Num(s.find(&search.to_string()).map_or(-1, |i| {
+                i64::try_from(s[..i].chars().count()).unwrap_or(i64::MAX)
+            })),
+            // The number of arguments is checked by the parser
+            _ => Value::Bool(false),
+        })
+    }
+}
+
+/// Days of the week for `weekdayRange`, in the order of the week days since
+/// the UNIX epoch (a Thursday)
+const WEEKDAYS: [&str; 7] = ["THU", "FRI", "SAT", "SUN", "MON", "TUE", "WED"];
+
+/// Whether the day of `now` (in seconds since the UNIX epoch) is within
+/// `weekdayRange(wd1, wd2, gmt)`
+fn weekday_range(args: &[Value], now: u64) -> bool {
+    let days: Vec<usize> = match without_gmt(args)
+        .iter()
+        .map(|day| WEEKDAYS.iter().position(|&d| d == day.to_string()))
+        .collect::<Option<_>>()
+    {
+        Some(days) => days,
+        None => return false,
+    };
+    let today = usize::try_from((now / 86_400) % 7).unwrap_or_default();
+    match days[..] {
+        [day] => today == day,
+        [first, last] if first <= last => (first..=last).contains(&today),
+        [first, last] => today >= first || today <= last,
+        _ => false,
+    }
+}
+
+/// Whether the time of `now` (in seconds since the UNIX epoch) is within
+/// `timeRange(hour1, min1, sec1, hour2, min2, sec2, gmt)`, where minutes and
+/// seconds are optional
+fn time_range(args: &[Value], now: u64) -> PacResult<bool> {
+    let args = without_gmt(args)
+        .iter()
+        .map(Value::number)
+        .collect::<PacResult<Vec<_>>>()?;
+    let time = i64::try_from(now % 86_400).unwrap_or_default();
+    let seconds = |hour: i64, min: i64, sec: i64| {
+        hour.saturating_mul(3600)
+            .saturating_add(min.saturating_mul(60))
+            .saturating_add(sec)
+    };
+    let (start, end) = match args[..] {
+        [hour] => (seconds(hour, 0, 0), seconds(hour.saturating_add(1), 0, 0)),
+        [hour1, hour2] => (seconds(hour1, 0, 0), seconds(hour2, 0, 0)),
+        [hour1, min1, hour2, min2] => (seconds(hour1, min1, 0), seconds(hour2, min2, 0)),
+        // The last second is part of the range
+        [hour1, min1, sec1, hour2, min2, sec2] => (
+            seconds(hour1, min1, sec1),
+            seconds(hour2, min2, sec2).saturating_add(1),
+        ),
+        _ => return Ok(false),
+    };
+    Ok(if start <= end {
+        start <= time && time < end
+    } else {
+        // The range wraps around midnight
+        time >= start || time < end
+    })
+}
+
+/// `args` without a trailing `"GMT"`, as all times are in UTC
+fn without_gmt(args: &[Value]) -> &[Value] {
+    match args {
+        [rest @ .., Value::Str(gmt)] if gmt == "GMT" => rest,
+        _ => args,
+    }
+}
+
+/// Whether `text` matches the shell expression `pattern` with `*` and `?`.
+///
+/// On a mismatch, only the last `*` matches one more character, so this
+/// takes at most `text.len() * pattern.len()` steps.
+fn shell_match(text: &str, pattern: &str) -> bool {
+    let text: Vec<char> = text.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    let (mut t, mut p) = (0, 0);
+    // Positions of the last `*` in the pattern and of the text after it
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                t += 1;
+                p += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Str(String),
+    Num(i64),
+    Bool(bool),
+}
+
+impl Value {
+    fn is_truthy(&self) -> bool {
+        match self {
+            Self::Str(s) => !s.is_empty(),
+            Self::Num(n) => *n != 0,
+            Self::Bool(b) => *b,
+        }
+    }
+
+    /// The value as a number, like JavaScript's `Number(value)`
+    fn number(&self) -> PacResult<i64> {
+        match self {
+            Self::Str(s) => s
+                .trim()
+                .parse()
+                .map_err(|_| format!("Expected a number, found {s:?}")),
+            Self::Num(n) => Ok(*n),
+            Self::Bool(b) => Ok(i64::from(*b)),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Str(s) => f.write_str(s),
+            Self::Num(n) => write!(f, "{n}"),
+            Self::Bool(b) => write!(f, "{b}"),
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+/// Result of parsing or evaluating a PAC file, with a description of the error
+type PacResult<T> = std::result::Result<T, String>;
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> PacResult<Token> {
+        let token = self.peek().cloned().ok_or("Unexpected end of file")?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, punct: &'static str) -> bool {
+        let found = self.peek() == Some(&Token::Punct(punct));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, punct: &'static str) -> PacResult<()> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(format!("Expected `{punct}`, found {:?}", self.peek()))
+        }
+    }
+
+    fn ident(&mut self) -> PacResult<String> {
+        match self.next()? {
+            Token::Ident(name) => Ok(name),
+            token => Err(format!("Expected a name, found {token:?}")),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Ident(name)) if name == keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn pac(mut self) -> PacResult<ProxyPac> {
+        if !self.keyword("function") || self.ident()? != "FindProxyForURL" {
+            return Err("Expected a `FindProxyForURL` function".to_string());
+        }
+        self.expect("(")?;
+        let url = self.ident()?;
+        self.expect(",")?;
+        let host = self.ident()?;
+        self.expect(")")?;
+        let body = self.block()?;
+        if let Some(token) = self.peek() {
+            return Err(format!("Unsupported {token:?} after `FindProxyForURL`"));
+        }
+        Ok(ProxyPac {
+            params: (url, host),
+            body,
+            resolver: Resolver::default(),
+        })
+    }
+
+    fn block(&mut self) -> PacResult<Vec<Stmt>> {
+        self.expect("{")?;
+        let mut stmts = Vec::new();
+        while !self.eat("}") {
+            stmts.push(self.stmt()?);
+        }
+        Ok(stmts)
+    }
+
+    /// A block or a single statement, e.g. after `if`
+    fn body(&mut self) -> PacResult<Vec<Stmt>> {
+        if self.peek() == Some(&Token::Punct("{")) {
+            self.block()
+        } else {
+            Ok(vec![self.stmt()?])
+        }
+    }
+
+    fn stmt(&mut self) -> PacResult<Stmt> {
+        let stmt = if self.keyword("if") {
+            self.expect("(")?;
+            let condition = self.expr()?;
+            self.expect(")")?;
+            let then = self.body()?;
+            let otherwise = if self.keyword("else") {
+                self.body()?
+            } else {
+                Vec::new()
+            };
+            return Ok(Stmt::If(condition, then, otherwise));
+        } else if self.keyword("return") {
+            Stmt::Return(self.expr()?)
+        } else if self.keyword("var") || self.keyword("let") || self.keyword("const") {
+            let name = self.ident()?;
+            self.expect("=")?;
+            Stmt::Var(name, self.expr()?)
+        } else if let Some([Token::Ident(name), Token::Punct("=")]) =
+            self.tokens.get(self.pos..self.pos + 2)
+        {
+            let name = name.clone();
+            self.pos += 2;
+            Stmt::Var(name, self.expr()?)
+        } else {
+            Stmt::Expr(self.expr()?)
+        };
+        self.eat(";");
+        Ok(stmt)
+    }
+
+    fn expr(&mut self) -> PacResult<Expr> {
+        let mut left = self.and()?;
+        while self.eat("||") {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> PacResult<Expr> {
+        let mut left = self.comparison()?;
+        while self.eat("&&") {
+            left = Expr::And(Box::new(left), Box::new(self.comparison()?));
+        }
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> PacResult<Expr> {
+        let left = self.additive()?;
+        for op in ["===", "!==", "==", "!=", "<=", ">=", "<", ">"] {
+            if self.eat(op) {
+                return Ok(Expr::Compare(
+                    op,
+                    Box::new(left),
+                    Box::new(self.additive()?),
+                ));
+            }
+        }
+        Ok(left)
+    }
+
+    fn additive(&mut self) -> PacResult<Expr> {
+        let mut left = self.unary()?;
+        loop {
+            left = if self.eat("+") {
+                Expr::Add(Box::new(left), Box::new(self.unary()?))
+            } else if self.eat("-") {
+                Expr::Sub(Box::new(left), Box::new(self.unary()?))
+            } else {
+                return Ok(left);
+            };
+        }
+    }
+
+    fn unary(&mut self) -> PacResult<Expr> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("-") {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        let mut expr = self.primary()?;
+        while self.eat(".") {
+            let name = self.ident()?;
+            expr = if self.eat("(") {
+                let (method, arity) = Method::from_name(&name)
+                    .ok_or_else(|| format!("Unsupported method `{name}`"))?;
+                Expr::Method(Box::new(expr), method, self.args(&name, arity)?)
+            } else if name == "length" {
+                Expr::Length(Box::new(expr))
+            } else {
+                return Err(format!("Unsupported property `{name}`"));
+            };
+        }
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> PacResult<Expr> {
+        match self.next()? {
+            Token::Punct("(") => {
+                let expr = self.expr()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Str(s) => Ok(Expr::Value(Value::Str(s))),
+            Token::Num(n) => Ok(Expr::Value(Value::Num(n))),
+            Token::Ident(name) if name == "true" || name == "false" => {
+                Ok(Expr::Value(Value::Bool(name == "true")))
+            }
+            Token::Ident(name) if self.eat("(") => {
+                let (function, arity) = Function::from_name(&name)
+                    .ok_or_else(|| format!("Unsupported function `{name}`"))?;
+                Ok(Expr::Call(function, self.args(&name, arity)?))
+            }
+            Token::Ident(name) => Ok(Expr::Var(name)),
+            token => Err(format!("Unexpected {token:?}")),
+        }
+    }
+
+    /// The arguments of a call to `name` after the opening parenthesis
+    fn args(&mut self, name: &str, arity: RangeInclusive<usize>) -> PacResult<Vec<Expr>> {
+        let mut args = Vec::new();
+        while !self.eat(")") {
+            if !args.is_empty() {
+                self.expect(",")?;
+            }
+            args.push(self.expr()?);
+        }
+        if !arity.contains(&args.len()) {
+            let (min, max) = arity.into_inner();
+            return Err(if min == max {
+                format!("`{name}` takes {min} arguments")
+            } else {
+                format!("`{name}` takes {min} to {max} arguments")
+            });
+        }
+        Ok(args)
+    }
+}
+
+/// What the functions of a PAC file can use besides their arguments
+struct Context<'a> {
+    resolver: &'a Resolver,
+    /// Seconds since the UNIX epoch, for `weekdayRange` and `timeRange`
+    now: u64,
+}
+
+/// Runs `stmts` and returns the returned value (if any)
+fn run(
+    stmts: &[Stmt],
+    vars: &mut HashMap<String, Value>,
+    context: &Context,
+) -> PacResult<Option<Value>> {
+    for stmt in stmts {
+        match stmt {
+            Stmt::If(condition, then, otherwise) => {
+                let branch = if eval(condition, vars, context)?.is_truthy() {
+                    then
+                } else {
+                    otherwise
+                };
+                if let Some(value) = run(branch, vars, context)? {
+                    return Ok(Some(value));
+                }
+            }
+            Stmt::Return(expr) => return eval(expr, vars, context).map(Some),
+            Stmt::Var(name, expr) => {
+                let value = eval(expr, vars, context)?;
+                vars.insert(name.clone(), value);
+            }
+            Stmt::Expr(expr) => {
+                eval(expr, vars, context)?;
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn eval(expr: &Expr, vars: &HashMap<String, Value>, context: &Context) -> PacResult<Value> {
+    Ok(match expr {
+        Expr::Value(value) => value.clone(),
+        Expr::Var(name) => vars
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Unknown variable `{name}`"))?,
+        Expr::Not(expr) => Value::Bool(!eval(expr, vars, context)?.is_truthy()),
+        Expr::Neg(expr) => Value::Num(eval(expr, vars, context)?.number()?.saturating_neg()),
+        Expr::And(left, right) => Value::Bool(
+            eval(left, vars, context)?.is_truthy() && eval(right, vars, context)?.is_truthy(),
+        ),
+        Expr::Or(left, right) => Value::Bool(
+            eval(left, vars, context)?.is_truthy() || eval(right, vars, context)?.is_truthy(),
+        ),
+        Expr::Add(left, right) => match (eval(left, vars, context)?, eval(right, vars, context)?) {
+            // Strings are concatenated
+            (left @ Value::Str(_), right) | (left, right @ Value::Str(_)) => {
+                Value::Str(format!("{left}{right}"))
+            }
+            (left, right) => Value::Num(left.number()?.saturating_add(right.number()?)),
+        },
+        Expr::Sub(left, right) => Value::Num(
+            eval(left, vars, context)?
+                .number()?
+                .saturating_sub(eval(right, vars, context)?.number()?),
+        ),
+        Expr::Compare(op, left, right) => {
+            let (left, right) = (eval(left, vars, context)?, eval(right, vars, context)?);
+            Value::Bool(match (*op, &left, &right) {
+                ("==" | "===", ..) => left == right,
+                ("!=" | "!==", ..) => left != right,
+                (op, Value::Num(l), Value::Num(r)) => match op {
+                    "<" => l < r,
+                    ">" => l > r,
+                    "<=" => l <= r,
+                    _ => l >= r,
+                },
+                (op, Value::Str(l), Value::Str(r)) => match op {
+                    "<" => l < r,
+                    ">" => l > r,
+                    "<=" => l <= r,
+                    _ => l >= r,
+                },
+                _ => return Err(format!("Cannot compare {left:?} and {right:?}")),
+            })
+        }
+        Expr::Call(function, args) => {
+            let args = args
+                .iter()
+                .map(|arg| eval(arg, vars, context))
+                .collect::<PacResult<Vec<_>>>()?;
+            function.call(&args, context)?
+        }
+        Expr::Method(target, method, args) => {
+            let Value::Str(target) = eval(target, vars, context)? else {
+                return Err(format!("`{method:?}` is only supported on strings"));
+            };
+            let args = args
+                .iter()
+                .map(|arg| eval(arg, vars, context))
+                .collect::<PacResult<Vec<_>>>()?;
+            method.call(&target, &args)?
+        }
+        Expr::Length(target) => match eval(target, vars, context)? {
+            Value::Str(s) => Value::Num(i64::try_from(s.chars().count()).unwrap_or(i64::MAX)),
+            value => return Err(format!("{value:?} has no length")),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use url::Url;
+
+    use super::{shell_match, ProxyPac};
+
+    /// Monday, January 1st 2024, midnight UTC
+    const MONDAY: u64 = 1_704_067_200;
+    const DAY: u64 = 86_400;
+    const HOUR: u64 = 3600;
+
+    const PAC: &str = r#"
+        // Proxy of the example corporation
+        function FindProxyForURL(url, host) {
+            /* Intranet */
+            if (isPlainHostName(host) || dnsDomainIs(host, ".corp.example.com"))
+                return "DIRECT";
+            if (isInNet(host, "10.0.0.0", "255.0.0.0")) {
+                return 'DIRECT';
+            } else if (shExpMatch(url, "https://*.example.org/*")) {
+                return "PROXY secure.example.com:3128; DIRECT";
+            }
+            var levels = dnsDomainLevels(host);
+            if (levels > 3) return "SOCKS socks.example.com:1080; PROXY deep.example.com:80";
+            return "PROXY proxy.example.com:8080";
+        }
+    "#;
+
+    fn find_proxy(pac: &ProxyPac, url: &str) -> Option<String> {
+        pac.find_proxy(&Url::parse(url).unwrap())
+            .map(|proxy| proxy.to_string())
+    }
+
+    fn find_proxy_at(pac: &ProxyPac, url: &str, now: u64) -> Option<String> {
+        pac.find_proxy_at(
+            &Url::parse(url).unwrap(),
+            UNIX_EPOCH + Duration::from_secs(now),
+        )
+        .map(|proxy| proxy.to_string())
+    }
+
+    #[test]
+    fn test_find_proxy() {
+        let pac = ProxyPac::parse(PAC).unwrap();
+        assert_eq!(find_proxy(&pac, "http://intranet/"), None);
+        assert_eq!(find_proxy(&pac, "https://wiki.corp.example.com/"), None);
+        assert_eq!(find_proxy(&pac, "http://10.1.2.3/"), None);
+        assert_eq!(
+            find_proxy(&pac, "https://docs.example.org/a"),
+            Some("http://secure.example.com:3128/".to_string())
+        );
+        assert_eq!(
+            find_proxy(&pac, "https://a.b.c.d.example.net/"),
+            Some("http://deep.example.com/".to_string())
+        );
+        assert_eq!(
+            find_proxy(&pac, "https://lychee.cli.rs/"),
+            Some("http://proxy.example.com:8080/".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_host() {
+        let pac = ProxyPac::parse(
+            r#"
+            function FindProxyForURL(url, host) {
+                if (isInNet(host, "127.0.0.0", "255.0.0.0")) return "DIRECT";
+                if (dnsResolve(host) == "" && !isResolvable(host)) return "PROXY unresolved:80";
+                if (myIpAddress() != "") return "PROXY proxy.example.com:8080";
+            }
+        "#,
+        )
+        .unwrap();
+        assert_eq!(find_proxy(&pac, "http://localhost:8080/"), None);
+        assert_eq!(find_proxy(&pac, "http://127.0.0.1/"), None);
+        assert_eq!(
+            find_proxy(&pac, "http://unresolved.invalid/"),
+            Some("http://unresolved/".to_string())
+        );
+        assert_eq!(
+            find_proxy(&pac, "http://192.0.2.1/"),
+            Some("http://proxy.example.com:8080/".to_string())
+        );
+    }
+
+    #[test]
+    fn test_shell_match() {
+        assert!(shell_match(
+            "https://docs.example.org/a",
+            "https://*.example.org/*"
+        ));
+        assert!(shell_match("abc", "a?c"));
+        assert!(shell_match("", "*"));
+        assert!(!shell_match("abc", "a?"));
+        assert!(!shell_match("https://example.org", "*.example.org"));
+        // Fails quickly instead of trying every split of the text
+        let text = "a".repeat(200);
+        let pattern = format!("{}b", "*a".repeat(50));
+        assert!(!shell_match(&text, &pattern));
+    }
+
+    #[test]
+    fn test_real_world_pac() {
+        // In the style of the examples of https://findproxyforurl.com
+        let pac = ProxyPac::parse(
+            r#"
+            function FindProxyForURL(url, host) {
+                host = host.toLowerCase();
+                var proxy = "PROXY " + "proxy.example.com" + ":" + (8000 + 80);
+                alert("Finding the proxy for " + url + " (" + host.length + " characters)");
+
+                // Plain host names and the intranet are reached directly
+                if (isPlainHostName(host) || dnsDomainIs(host, ".intranet.example.com"))
+                    return "DIRECT";
+
+                // FTP uses a separate proxy
+                if (url.substring(0, 4) == "ftp:")
+                    return "PROXY ftp-proxy.example.com:2121";
+
+                // Local subnets
+                if (isInNet(dnsResolve(host), "10.0.0.0", "255.0.0.0") ||
+                    isInNet(dnsResolve(host), "127.0.0.0", "255.0.0.0"))
+                    return "DIRECT";
+
+                if (host.indexOf("partner") != -1)
+                    return "PROXY partner-proxy.example.com:3128; DIRECT";
+
+                // Only use the proxy during office hours
+                if (weekdayRange("MON", "FRI") && timeRange(8, 18))
+                    return proxy + "; DIRECT";
+
+                return "DIRECT";
+            }
+        "#,
+        )
+        .unwrap();
+        let office_hours = MONDAY + 10 * HOUR;
+        assert_eq!(find_proxy_at(&pac, "http://intranet/", office_hours), None);
+        assert_eq!(
+            find_proxy_at(&pac, "https://wiki.intranet.example.com/", office_hours),
+            None
+        );
+        assert_eq!(
+            find_proxy_at(&pac, "ftp://files.example.com/a", office_hours),
+            Some("http://ftp-proxy.example.com:2121/".to_string())
+        );
+        assert_eq!(find_proxy_at(&pac, "http://127.0.0.1/", office_hours), None);
+        assert_eq!(
+            find_proxy_at(&pac, "https://partner.example.com/", office_hours),
+            Some("http://partner-proxy.example.com:3128/".to_string())
+        );
+        assert_eq!(
+            find_proxy_at(&pac, "https://lychee.cli.rs/", office_hours),
+            Some("http://proxy.example.com:8080/".to_string())
+        );
+        // Evening and Saturday
+        assert_eq!(
+            find_proxy_at(&pac, "https://lychee.cli.rs/", MONDAY + 20 * HOUR),
+            None
+        );
+        assert_eq!(
+            find_proxy_at(&pac, "https://lychee.cli.rs/", office_hours + 5 * DAY),
+            None
+        );
+    }
+
+    #[test]
+    fn test_date_and_time() {
+        let pac = ProxyPac::parse(
+            r#"
+            function FindProxyForURL(url, host) {
+                if (weekdayRange("SAT", "SUN", "GMT")) return "PROXY weekend:80";
+                if (timeRange(22, 6)) return "PROXY night:80";
+                if (timeRange(12, 0, 13, 30)) return "PROXY lunch:80";
+                if (timeRange(14, 0, 0, 14, 0, 30, "GMT")) return "PROXY meeting:80";
+                if (weekdayRange("FRI") && timeRange(17)) return "PROXY friday:80";
+                return "DIRECT";
+            }
+        "#,
+        )
+        .unwrap();
+        let url = "https://lychee.cli.rs/";
+        let at = |now| find_proxy_at(&pac, url, now);
+        assert_eq!(at(MONDAY + 5 * DAY), Some("http://weekend/".to_string()));
+        assert_eq!(at(MONDAY + 6 * DAY), Some("http://weekend/".to_string()));
+        assert_eq!(at(MONDAY + 23 * HOUR), Some("http://night/".to_string()));
+        assert_eq!(at(MONDAY + 3 * HOUR), Some("http://night/".to_string()));
+        assert_eq!(at(MONDAY + 6 * HOUR), None);
+        assert_eq!(
+            at(MONDAY + 13 * HOUR + 15 * 60),
+            Some("http://lunch/".to_string())
+        );
+        assert_eq!(at(MONDAY + 13 * HOUR + 30 * 60), None);
+        assert_eq!(
+            at(MONDAY + 14 * HOUR + 30),
+            Some("http://meeting/".to_string())
+        );
+        assert_eq!(at(MONDAY + 14 * HOUR + 31), None);
+        assert_eq!(
+            at(MONDAY + 4 * DAY + 17 * HOUR + 30 * 60),
+            Some("http://friday/".to_string())
+        );
+        assert_eq!(at(MONDAY + 17 * HOUR + 30 * 60), None);
+    }
+
+    #[test]
+    fn test_unsupported() {
+        assert!(ProxyPac::parse("function Other(url, host) { return 'DIRECT'; }").is_err());
+        assert!(ProxyPac::parse(
+            "function FindProxyForURL(url, host) { if (dateRange('JAN', 'MAR')) return 'DIRECT'; }"
+        )
+        .is_err());
+        assert!(
+            ProxyPac::parse("function FindProxyForURL(url, host) { return host.split('.'); }")
+                .is_err()
+        );
+        assert!(
+            ProxyPac::parse("function FindProxyForURL(url, host) { return weekdayRange(); }")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_evaluation_error_connects_directly() {
+        let pac = ProxyPac::parse(
+            "function FindProxyForURL(url, host) { return 'PROXY ' + proxy + ':80'; }",
+        )
+        .unwrap();
+        assert_eq!(find_proxy(&pac, "https://lychee.cli.rs/"), None);
+    }
+}
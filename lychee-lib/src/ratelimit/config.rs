@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+/// Rate limits for a single host
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HostConfig {
+    /// Maximum number of concurrent requests to the host.
+    /// `None` means that the number of requests is not limited.
+    pub max_concurrency: Option<usize>,
+    /// Minimum time between the start of two consecutive requests to the host.
+    /// `None` means that requests are sent as fast as possible.
+    pub min_interval: Option<Duration>,
+}
+
+impl HostConfig {
+    /// Create a new host configuration without any limits
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_concurrency: None,
+            min_interval: None,
+        }
+    }
+
+    /// Limit the number of concurrent requests to the host
+    #[must_use]
+    pub const fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency);
+        self
+    }
+
+    /// Set the minimum time between two consecutive requests to the host
+    #[must_use]
+    pub const fn with_min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = Some(min_interval);
+        self
+    }
+}
@@ -0,0 +1,18 @@
+//! Per-host rate limiting.
+//!
+//! Sending too many requests to the same host in a short amount of time is
+//! impolite at best, and gets lychee blocked or greylisted at worst.
+//! A [`HostPool`] keeps track of the hosts lychee talks to and makes sure that
+//! the limits of each host's [`HostConfig`] are respected.
+//!
+//! Before contacting a host, acquire a [`HostPermit`] with
+//! [`HostPool::acquire`]. The permit is released when it gets dropped.
+//! The hosts of the checked site and all other hosts are scheduled in
+//! separate lanes, which can have their own concurrency limits.
+//! Once the deadline of the pool (if any) has passed, no more permits are
+//! handed out.
+mod config;
+mod pool;
+
+pub use config::HostConfig;
+pub use pool::{HostActivity, HostPermit, HostPool, HostWarmUp};
@@ -0,0 +1,567 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use tokio::{
+    sync::{Mutex as AsyncMutex, OwnedRwLockWriteGuard, OwnedSemaphorePermit, RwLock, Semaphore},
+    time::{sleep_until, timeout_at, Instant},
+};
+
+use super::HostConfig;
+
+/// Book-keeping for a single host
+#[derive(Debug)]
+struct Host {
+    config: HostConfig,
+    /// Limits the number of concurrent requests, if configured
+    semaphore: Option<Arc<Semaphore>>,
+    /// Start of the most recent request to this host
+    last_request: AsyncMutex<Option<Instant>>,
+    /// Number of requests waiting for a permit
+    queued: AtomicUsize,
+    /// Number of permits which are currently held
+    active: AtomicUsize,
+    /// Number of permits which were released
+    completed: AtomicU64,
+    /// Number of requests whose duration was recorded
+    timed_requests: AtomicU64,
+    /// Total duration of the recorded requests in microseconds
+    request_micros: AtomicU64,
+    /// Time to open the connections of the warm-up, if any
+    warm_up: Mutex<Option<Duration>>,
+    /// Locked while the connections to the host are opened, see
+    /// [`HostPool::warm_up`]
+    warm_up_gate: Arc<RwLock<()>>,
+}
+
+impl Host {
+    fn new(config: HostConfig) -> Self {
+        let semaphore = config
+            .max_concurrency
+            .map(|permits| Arc::new(Semaphore::new(permits.max(1))));
+        Self {
+            config,
+            semaphore,
+            last_request: AsyncMutex::new(None),
+            queued: AtomicUsize::new(0),
+            active: AtomicUsize::new(0),
+            completed: AtomicU64::new(0),
+            timed_requests: AtomicU64::new(0),
+            request_micros: AtomicU64::new(0),
+            warm_up: Mutex::new(None),
+            warm_up_gate: Arc::default(),
+        }
+    }
+}
+
+/// Counts a request as queued while it waits for a permit, even if waiting
+/// is cancelled
+struct Queued<'a>(&'a Host);
+
+impl<'a> Queued<'a> {
+    fn new(host: &'a Host) -> Self {
+        host.queued.fetch_add(1, Ordering::Relaxed);
+        Self(host)
+    }
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.queued.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Requests to a single host at a point in time, see [`HostPool::activity`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostActivity {
+    /// Name of the host (lowercase)
+    pub host: String,
+    /// Number of requests which wait for the rate limits of the host
+    pub queued: usize,
+    /// Number of requests which are currently sent to the host
+    pub active: usize,
+    /// Number of requests which were completed
+    pub completed: u64,
+    /// Time to open the connections to the host before checking its links,
+    /// see [`HostWarmUp::finish`]
+    pub warm_up: Option<Duration>,
+    /// Mean duration of the requests to the host, see
+    /// [`HostPermit::record_request`]. Requests which wait for a warm-up
+    /// reuse its connections, so the time to open them isn't included.
+    pub mean_request_time: Option<Duration>,
+}
+
+/// Permission to send a request to a host, acquired from a [`HostPool`].
+///
+/// The concurrency slot of the host is released once the permit is dropped.
+#[derive(Debug)]
+pub struct HostPermit {
+    host: Arc<Host>,
+    _permit: Option<OwnedSemaphorePermit>,
+    /// Slot in the lane of the host, see [`HostPool::with_internal_hosts`]
+    _lane: Option<OwnedSemaphorePermit>,
+}
+
+impl HostPermit {
+    /// Record the duration of the request which was sent with this permit,
+    /// from sending it until the response headers were received
+    pub fn record_request(&self, duration: Duration) {
+        let micros = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        self.host
+            .request_micros
+            .fetch_add(micros, Ordering::Relaxed);
+        self.host.timed_requests.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Drop for HostPermit {
+    fn drop(&mut self) {
+        self.host.active.fetch_sub(1, Ordering::Relaxed);
+        self.host.completed.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Opening of the connections to a host before its links are checked,
+/// started with [`HostPool::warm_up`].
+///
+/// Requests to the host wait until the warm-up is finished or dropped.
+#[derive(Debug)]
+pub struct HostWarmUp {
+    host: Arc<Host>,
+    lane: Option<Arc<Semaphore>>,
+    started: Instant,
+    _gate: OwnedRwLockWriteGuard<()>,
+}
+
+impl HostWarmUp {
+    /// Wait until a request of the warm-up is allowed by the rate limits of
+    /// the host. Unlike [`HostPool::acquire`], this doesn't wait for the
+    /// warm-up itself.
+    pub async fn acquire(&self) -> HostPermit {
+        HostPool::wait(Arc::clone(&self.host), self.lane.as_ref(), false).await
+    }
+
+    /// Record the time since the warm-up was started as the time to open
+    /// the connections to the host and let its requests continue
+    pub fn finish(self) {
+        *self
+            .host
+            .warm_up
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(self.started.elapsed());
+    }
+}
+
+/// Keeps track of all hosts and enforces their [`HostConfig`].
+///
+/// Hosts are identified by name, e.g. `github.com` or `mx.example.com`.
+/// Names are compared case-insensitively.
+/// Hosts without a specific configuration use the default configuration of
+/// the pool.
+///
+/// Requests are scheduled in two lanes: the hosts of the checked site (see
+/// [`HostPool::with_internal_hosts`]) are internal, all others external.
+/// Each lane can limit the number of concurrent requests to all of its hosts,
+/// so that slow external hosts can't hold up the internal ones.
+///
+/// If the pool has a deadline, no permits are handed out after it passed, so
+/// that requests which are still waiting for their host give up.
+#[derive(Debug, Default)]
+pub struct HostPool {
+    default_config: HostConfig,
+    host_configs: HashMap<String, HostConfig>,
+    hosts: Mutex<HashMap<String, Arc<Host>>>,
+    deadline: Option<Instant>,
+    internal_hosts: HashSet<String>,
+    /// Limits the concurrent requests to internal hosts, if configured
+    internal_lane: Option<Arc<Semaphore>>,
+    /// Limits the concurrent requests to external hosts, if configured
+    external_lane: Option<Arc<Semaphore>>,
+}
+
+impl HostPool {
+    /// Create a new pool, which applies `default_config` to all hosts
+    #[must_use]
+    pub fn new(default_config: HostConfig) -> Self {
+        Self {
+            default_config,
+            ..Default::default()
+        }
+    }
+
+    /// Use a specific configuration for the given host
+    #[must_use]
+    pub fn with_host_config(mut self, host: &str, config: HostConfig) -> Self {
+        self.host_configs.insert(host.to_lowercase(), config);
+        self
+    }
+
+    /// Stop handing out permits once `deadline` has passed
+    #[must_use]
+    pub const fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Schedule the requests to `hosts` in the internal lane, e.g. the hosts
+    /// of the checked site
+    #[must_use]
+    pub fn with_internal_hosts<I, S>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.internal_hosts = hosts
+            .into_iter()
+            .map(|host| host.as_ref().to_lowercase())
+            .collect();
+        self
+    }
+
+    /// Send at most `max_concurrency` concurrent requests to all internal
+    /// hosts together
+    #[must_use]
+    pub fn with_internal_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.internal_lane = Some(Arc::new(Semaphore::new(max_concurrency.max(1))));
+        self
+    }
+
+    /// Send at most `max_concurrency` concurrent requests to all external
+    /// hosts together
+    #[must_use]
+    pub fn with_external_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.external_lane = Some(Arc::new(Semaphore::new(max_concurrency.max(1))));
+        self
+    }
+
+    /// Whether requests to `host` are scheduled in the internal lane
+    #[must_use]
+    pub fn is_internal(&self, host: &str) -> bool {
+        self.internal_hosts.contains(&host.to_lowercase())
+    }
+
+    /// Whether the deadline of the pool (if any) has passed
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// The configuration which applies to the given host
+    #[must_use]
+    pub fn config(&self, host: &str) -> HostConfig {
+        self.host_configs
+            .get(&host.to_lowercase())
+            .copied()
+            .unwrap_or(self.default_config)
+    }
+
+    fn host(&self, host: &str) -> Arc<Host> {
+        let name = host.to_lowercase();
+        // The lock is never held across an await point, so poisoning
+        // can only happen if an allocation fails.
+        let mut hosts = self
+            .hosts
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let config = self.config(&name);
+        Arc::clone(
+            hosts
+                .entry(name)
+                .or_insert_with(|| Arc::new(Host::new(config))),
+        )
+    }
+
+    /// Wait until a request to the given host is allowed.
+    ///
+    /// This waits for a free concurrency slot of the host first, then for
+    /// the minimum interval since the previous request to pass and then for
+    /// a free slot in the lane of the host.
+    ///
+    /// Returns `None` if the deadline of the pool passes before the request
+    /// is allowed.
+    pub async fn acquire(&self, host: &str) -> Option<HostPermit> {
+        if self.is_expired() {
+            return None;
+        }
+        let lane = self.lane(host);
+        let host = self.host(host);
+        match self.deadline {
+            Some(deadline) => timeout_at(deadline, Self::wait(host, lane, true))
+                .await
+                .ok(),
+            None => Some(Self::wait(host, lane, true).await),
+        }
+    }
+
+    /// Start opening the connections to the given host, so that its
+    /// requests, which wait for the warm-up, don't include the time to open
+    /// them (e.g. the TLS handshake).
+    ///
+    /// Returns `None` if the deadline of the pool has passed or the host is
+    /// already warming up.
+    #[must_use]
+    pub fn warm_up(&self, host: &str) -> Option<HostWarmUp> {
+        if self.is_expired() {
+            return None;
+        }
+        let lane = self.lane(host).cloned();
+        let host = self.host(host);
+        let gate = Arc::clone(&host.warm_up_gate).try_write_owned().ok()?;
+        Some(HostWarmUp {
+            host,
+            lane,
+            started: Instant::now(),
+            _gate: gate,
+        })
+    }
+
+    /// The lane of the given host, see [`HostPool::with_internal_hosts`]
+    fn lane(&self, host: &str) -> Option<&Arc<Semaphore>> {
+        if self.is_internal(host) {
+            self.internal_lane.as_ref()
+        } else {
+            self.external_lane.as_ref()
+        }
+    }
+
+    async fn wait(
+        host: Arc<Host>,
+        lane: Option<&Arc<Semaphore>>,
+        after_warm_up: bool,
+    ) -> HostPermit {
+        let queued = Queued::new(&host);
+        if after_warm_up {
+            drop(host.warm_up_gate.read().await);
+        }
+        let permit = acquire_owned(host.semaphore.as_ref()).await;
+
+        // The slot in the lane is only taken when the host is ready, so that
+        // a rate-limited host doesn't hold up the other hosts of its lane.
+        // The interval starts once the request can be sent.
+        let lane_permit = if let Some(min_interval) = host.config.min_interval {
+            let mut last_request = host.last_request.lock().await;
+            if let Some(last_request) = *last_request {
+                sleep_until(last_request + min_interval).await;
+            }
+            let lane_permit = acquire_owned(lane).await;
+            *last_request = Some(Instant::now());
+            lane_permit
+        } else {
+            acquire_owned(lane).await
+        };
+
+        drop(queued);
+        host.active.fetch_add(1, Ordering::Relaxed);
+        HostPermit {
+            host,
+            _permit: permit,
+            _lane: lane_permit,
+        }
+    }
+
+    /// The current requests of all hosts which were contacted so far,
+    /// ordered by host name
+    #[must_use]
+    pub fn activity(&self) -> Vec<HostActivity> {
+        let hosts = self
+            .hosts
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let mut activity: Vec<_> = hosts
+            .iter()
+            .map(|(name, host)| HostActivity {
+                host: name.clone(),
+                queued: host.queued.load(Ordering::Relaxed),
+                active: host.active.load(Ordering::Relaxed),
+                completed: host.completed.load(Ordering::Relaxed),
+                warm_up: *host
+                    .warm_up
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner),
+                mean_request_time: match host.timed_requests.load(Ordering::Relaxed) {
+                    0 => None,
+                    requests => Some(Duration::from_micros(
+                        host.request_micros.load(Ordering::Relaxed) / requests,
+                    )),
+                },
+            })
+            .collect();
+        activity.sort_by(|a, b| a.host.cmp(&b.host));
+        activity
+    }
+}
+
+/// A permit of `semaphore`, if any
+async fn acquire_owned(semaphore: Option<&Arc<Semaphore>>) -> Option<OwnedSemaphorePermit> {
+    // The semaphores are never closed, so acquiring can't fail
+    Arc::clone(semaphore?).acquire_owned().await.ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_host_config_lookup() {
+        let limited = HostConfig::new().with_max_concurrency(2);
+        let pool = HostPool::default().with_host_config("GitHub.com", limited);
+
+        assert_eq!(pool.config("github.com"), limited);
+        assert_eq!(pool.config("example.com"), HostConfig::default());
+    }
+
+    #[tokio::test]
+    async fn test_min_interval() {
+        let interval = Duration::from_millis(100);
+        let pool = HostPool::new(HostConfig::new().with_min_interval(interval));
+
+        let start = Instant::now();
+        drop(pool.acquire("mx.example.com").await);
+        drop(pool.acquire("mx.example.com").await);
+        drop(pool.acquire("mx.example.com").await);
+        assert!(start.elapsed() >= 2 * interval);
+
+        // Other hosts are not affected
+        let start = Instant::now();
+        drop(pool.acquire("mx.example.org").await);
+        assert!(start.elapsed() < interval);
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrency() {
+        let pool = Arc::new(HostPool::new(HostConfig::new().with_max_concurrency(1)));
+
+        let permit = pool.acquire("mx.example.com").await;
+        let waiting = tokio::spawn({
+            let pool = Arc::clone(&pool);
+            async move { pool.acquire("mx.example.com").await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        drop(permit);
+        assert!(waiting.await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_lanes() {
+        let pool = Arc::new(
+            HostPool::default()
+                .with_internal_hosts(["Example.com"])
+                .with_internal_max_concurrency(1)
+                .with_external_max_concurrency(1),
+        );
+        assert!(pool.is_internal("example.COM"));
+        assert!(!pool.is_internal("github.com"));
+
+        // A busy external lane doesn't hold up internal hosts
+        let external = pool.acquire("github.com").await;
+        let internal = pool.acquire("example.com").await;
+        assert!(internal.is_some());
+
+        // The lane is shared by all of its hosts
+        let waiting = tokio::spawn({
+            let pool = Arc::clone(&pool);
+            async move { pool.acquire("gitlab.com").await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        drop(external);
+        assert!(waiting.await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_activity() {
+        let pool = Arc::new(HostPool::new(HostConfig::new().with_max_concurrency(1)));
+
+        let permit = pool.acquire("mx.example.com").await;
+        let waiting = tokio::spawn({
+            let pool = Arc::clone(&pool);
+            async move { pool.acquire("mx.example.com").await }
+        });
+        tokio::task::yield_now().await;
+        assert_eq!(
+            pool.activity(),
+            [HostActivity {
+                host: "mx.example.com".to_string(),
+                queued: 1,
+                active: 1,
+                completed: 0,
+                warm_up: None,
+                mean_request_time: None,
+            }]
+        );
+
+        drop(permit);
+        drop(waiting.await.unwrap());
+        assert_eq!(
+            pool.activity(),
+            [HostActivity {
+                host: "mx.example.com".to_string(),
+                queued: 0,
+                active: 0,
+                completed: 2,
+                warm_up: None,
+                mean_request_time: None,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timings() {
+        let pool = HostPool::default();
+        for millis in [10, 30] {
+            let permit = pool.acquire("mx.example.com").await.unwrap();
+            permit.record_request(Duration::from_millis(millis));
+        }
+        let activity = &pool.activity()[0];
+        assert_eq!(activity.warm_up, None);
+        assert_eq!(activity.mean_request_time, Some(Duration::from_millis(20)));
+    }
+
+    #[tokio::test]
+    async fn test_warm_up() {
+        let pool = Arc::new(HostPool::default());
+        let warm_up = pool.warm_up("MX.example.com").unwrap();
+        assert!(pool.warm_up("mx.example.com").is_none());
+
+        // Requests of the warm-up are allowed, other requests wait for it
+        drop(warm_up.acquire().await);
+        let request = tokio::spawn({
+            let pool = Arc::clone(&pool);
+            async move { pool.acquire("mx.example.com").await.is_some() }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!request.is_finished());
+        assert_eq!(pool.activity()[0].queued, 1);
+
+        warm_up.finish();
+        assert!(request.await.unwrap());
+        assert!(pool.activity()[0].warm_up.is_some());
+        assert!(pool.acquire("mx.example.org").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_deadline() {
+        let interval = Duration::from_secs(60);
+        let pool = HostPool::new(HostConfig::new().with_min_interval(interval))
+            .with_deadline(Instant::now() + Duration::from_millis(100));
+
+        assert!(pool.acquire("mx.example.com").await.is_some());
+        // Waiting for the minimum interval would exceed the deadline
+        let start = Instant::now();
+        assert!(pool.acquire("mx.example.com").await.is_none());
+        assert!(start.elapsed() < interval);
+        assert!(pool.is_expired());
+        assert!(pool.acquire("mx.example.org").await.is_none());
+    }
+}
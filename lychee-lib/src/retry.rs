@@ -118,11 +118,14 @@ impl RetryExt for Status {
             Status::Ok(_) => false,
             Status::Error(err) => err.should_retry(),
             Status::Timeout(_) => true,
-            Status::Redirected(_) => false,
+            Status::Redirected(..) => false,
             Status::UnknownStatusCode(_) => false,
             Status::Excluded => false,
+            Status::ExcludedScheme(_) => false,
             Status::Unsupported(_) => false,
-            Status::Cached(_) => false,
+            Status::Cached(..) => false,
+            Status::CanonicalMismatch { .. } => false,
+            Status::Skipped(_) => false,
         }
     }
 }
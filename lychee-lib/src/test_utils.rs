@@ -1,8 +1,14 @@
-use std::{convert::TryFrom, fs, path::Path};
+use std::{
+    convert::TryFrom,
+    fs,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+};
 
 use reqwest::Url;
+use tokio::net::{TcpListener, TcpStream};
 
-use crate::{ClientBuilder, ErrorKind, Request, Uri};
+use crate::{types::uri::raw::RawUriSpan, ClientBuilder, ErrorKind, Request, Uri};
 
 #[macro_export]
 /// Creates a mock web server, which responds with a predefined status when
@@ -60,12 +66,64 @@ pub(crate) fn mail(address: &str) -> Uri {
     .into()
 }
 
-/// Loads a fixture from the `fixtures` directory
-pub(crate) fn load_fixture(filename: &str) -> String {
-    let fixture_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+/// Creates the span of a raw URI from a one-based line and column
+pub(crate) fn span(line: usize, column: usize) -> RawUriSpan {
+    RawUriSpan {
+        line: NonZeroUsize::new(line).expect("Expected non-zero line"),
+        column: Some(NonZeroUsize::new(column).expect("Expected non-zero column")),
+    }
+}
+
+/// Path of a fixture in the `fixtures` directory
+pub(crate) fn fixture_path(filename: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
         .parent()
         .unwrap()
         .join("fixtures")
-        .join(filename);
-    fs::read_to_string(fixture_path).unwrap()
+        .join(filename)
+}
+
+/// Loads a fixture from the `fixtures` directory
+pub(crate) fn load_fixture(filename: &str) -> String {
+    fs::read_to_string(fixture_path(filename)).unwrap()
+}
+
+/// Serves `mock_server` over https and returns the base URL of the https
+/// server, e.g. `https://127.0.0.1:1234`.
+///
+/// The certificate of the server is signed by the test CA in
+/// `fixtures/tls/ca.pem`, which clients need to trust.
+pub(crate) async fn https_server(mock_server: &wiremock::MockServer) -> String {
+    let cert = fs::read(fixture_path("tls/localhost.pem")).unwrap();
+    let key = fs::read(fixture_path("tls/localhost.key")).unwrap();
+    let identity = native_tls::Identity::from_pkcs8(&cert, &key).unwrap();
+    let acceptor =
+        tokio_native_tls::TlsAcceptor::from(native_tls::TlsAcceptor::new(identity).unwrap());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let upstream = *mock_server.address();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                let Ok(mut stream) = acceptor.accept(stream).await else {
+                    return;
+                };
+                let Ok(mut upstream) = TcpStream::connect(upstream).await else {
+                    return;
+                };
+                let _ = tokio::io::copy_bidirectional(&mut stream, &mut upstream).await;
+            });
+        }
+    });
+    format!("https://127.0.0.1:{port}")
+}
+
+/// A client which trusts the test CA of [`https_server`]
+pub(crate) fn https_client() -> reqwest::Client {
+    let ca = fs::read(fixture_path("tls/ca.pem")).unwrap();
+    reqwest::Client::builder()
+        .add_root_certificate(reqwest::Certificate::from_pem(&ca).unwrap())
+        .build()
+        .unwrap()
 }
@@ -0,0 +1,266 @@
+use crate::{ErrorKind, FragmentStyle, Result};
+use log::info;
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::io::ErrorKind as IoErrorKind;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{uri::raw::RawUriSpan, FileType};
+
+/// Prefix of the keys, so that the fragments of files which were parsed by
+/// other versions of lychee (with other extractors) are not used
+const KEY_VERSION: &str = concat!("v", env!("CARGO_PKG_VERSION"));
+
+/// The fragments of a parsed file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ParsedFile {
+    /// All fragments of the file
+    pub(crate) fragments: HashSet<String>,
+    /// Fragments which occur more than once, with the position of each
+    /// repetition
+    #[serde(default)]
+    pub(crate) duplicates: Vec<(String, RawUriSpan)>,
+}
+
+/// A parsed file in the cache
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AnchorCacheEntry {
+    #[serde(flatten)]
+    parsed: ParsedFile,
+    /// Seconds since the Unix epoch when the entry was last used
+    last_used: u64,
+}
+
+/// A cache of the fragments (anchors) of local files, which can be stored
+/// on disk and shared across runs
+///
+/// Entries are keyed by a hash of the file content, so unchanged files are
+/// not parsed again and changed files are parsed like new ones. Entries of
+/// other versions of lychee are discarded when the cache is loaded. When the
+/// cache is stored, the least recently used entries are evicted to keep it
+/// within the given number of entries.
+#[derive(Debug, Clone, Default)]
+pub struct AnchorCache {
+    entries: Arc<Mutex<HashMap<String, AnchorCacheEntry>>>,
+}
+
+impl AnchorCache {
+    /// Load the anchor cache from a JSON file
+    ///
+    /// Returns an empty cache if the file does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if
+    /// - the file cannot be read (except for `NotFound`) or
+    /// - the file is not a valid anchor cache
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == IoErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut entries: HashMap<String, AnchorCacheEntry> = serde_json::from_str(&content)
+            .map_err(|e| ErrorKind::AnchorCache(format!("{}: {e}", path.display())))?;
+        entries.retain(|key, _| {
+            key.strip_prefix(KEY_VERSION)
+                .is_some_and(|rest| rest.starts_with(':'))
+        });
+        info!(
+            "Loaded {} anchor cache entries from {}",
+            entries.len(),
+            path.display()
+        );
+        Ok(Self {
+            entries: Arc::new(Mutex::new(entries)),
+        })
+    }
+
+    /// Save the anchor cache to a JSON file, keeping at most `max_entries`
+    /// of the most recently used entries
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be written to
+    /// or the cache cannot be serialized.
+    pub fn store(&self, path: &Path, max_entries: usize) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() > max_entries {
+            let mut by_last_use: Vec<_> = entries.drain().collect();
+            by_last_use.sort_unstable_by(|(_, a), (_, b)| b.last_used.cmp(&a.last_used));
+            by_last_use.truncate(max_entries);
+            entries.extend(by_last_use);
+        }
+        info!(
+            "Saving {} anchor cache entries to {}",
+            entries.len(),
+            path.display()
+        );
+        let content = serde_json::to_string(&*entries)
+            .map_err(|e| ErrorKind::AnchorCache(format!("{}: {e}", path.display())))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// The number of entries in the cache
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.lock().map_or(0, |entries| entries.len())
+    }
+
+    /// Returns `true` if the cache has no entries
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The parsed file with `content`, parsed with `parse` unless it is
+    /// cached.
+    ///
+    /// The cache is not locked while parsing, so files are parsed in parallel.
+    /// If the same content is parsed twice at the same time, the result of
+    /// the last parse is kept.
+    pub(crate) fn get_or_parse(
+        &self,
+        file_type: FileType,
+        style: &FragmentStyle,
+        content: &str,
+        parse: impl FnOnce(&str) -> ParsedFile,
+    ) -> ParsedFile {
+        let key = Self::key(file_type, style, content);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        if let Some(entry) = self
+            .entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_mut(&key)
+        {
+            entry.last_used = now;
+            return entry.parsed.clone();
+        }
+
+        let parsed = parse(content);
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(
+                key,
+                AnchorCacheEntry {
+                    parsed: parsed.clone(),
+                    last_used: now,
+                },
+            );
+        parsed
+    }
+
+    /// The key of a file: the fragments depend on the version of lychee, the
+    /// content and, for Markdown files, on the fragment style
+    fn key(file_type: FileType, style: &FragmentStyle, content: &str) -> String {
+        let prefix = match file_type {
+            FileType::Markdown => format!("{KEY_VERSION}:md:{style:?}"),
+            _ => format!("{KEY_VERSION}:html"),
+        };
+        let hash = digest(&SHA256, content.as_bytes());
+        hash.as_ref()
+            .iter()
+            .fold(format!("{prefix}:"), |mut key, byte| {
+                let _ = write!(key, "{byte:02x}");
+                key
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use tempfile::tempdir;
+
+    use super::{AnchorCache, ParsedFile};
+    use crate::{FileType, FragmentStyle};
+
+    fn parse(content: &str) -> ParsedFile {
+        ParsedFile {
+            fragments: HashSet::from([content.to_string()]),
+            duplicates: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_anchor_cache() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("anchors");
+        let style = FragmentStyle::default();
+
+        let cache = AnchorCache::load(&path).unwrap();
+        assert!(cache.is_empty());
+        cache.get_or_parse(FileType::Html, &style, "a", parse);
+        cache.get_or_parse(FileType::Html, &style, "b", parse);
+        cache.store(&path, 10).unwrap();
+
+        let cache = AnchorCache::load(&path).unwrap();
+        assert_eq!(cache.len(), 2);
+        let fragments = cache.get_or_parse(FileType::Html, &style, "a", |_| {
+            panic!("cached content is parsed again")
+        });
+        assert_eq!(fragments, parse("a"));
+
+        cache.store(&path, 1).unwrap();
+        assert_eq!(AnchorCache::load(&path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_anchor_cache_discards_other_versions() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("anchors");
+        let style = FragmentStyle::default();
+
+        let cache = AnchorCache::default();
+        cache.get_or_parse(FileType::Html, &style, "a", parse);
+        let key = cache.entries.lock().unwrap().keys().next().unwrap().clone();
+        let (_, unversioned) = key.split_once(':').unwrap();
+        std::fs::write(
+            &path,
+            format!(
+                r#"{{"{unversioned}": {{"fragments": ["stale"], "last_used": 0}},
+                    "v0.1.0:{unversioned}": {{"fragments": ["stale"], "last_used": 0}}}}"#
+            ),
+        )
+        .unwrap();
+
+        let cache = AnchorCache::load(&path).unwrap();
+        assert!(cache.is_empty());
+        assert_eq!(
+            cache.get_or_parse(FileType::Html, &style, "a", parse),
+            parse("a")
+        );
+    }
+
+    #[test]
+    fn test_anchor_cache_evicts_least_recently_used() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("anchors");
+        let style = FragmentStyle::default();
+
+        let cache = AnchorCache::default();
+        cache.get_or_parse(FileType::Html, &style, "old", parse);
+        cache.get_or_parse(FileType::Html, &style, "new", parse);
+        for entry in cache.entries.lock().unwrap().values_mut() {
+            if entry.parsed.fragments.contains("old") {
+                entry.last_used = 0;
+            }
+        }
+        cache.store(&path, 1).unwrap();
+
+        let cache = AnchorCache::load(&path).unwrap();
+        cache.get_or_parse(FileType::Html, &style, "new", |_| {
+            panic!("recently used entry is evicted")
+        });
+    }
+}
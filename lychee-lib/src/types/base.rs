@@ -7,7 +7,7 @@ use crate::{ErrorKind, InputSource};
 /// When encountering links without a full domain in a document,
 /// the base determines where this resource can be found.
 /// Both, local and remote targets are supported.
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Clone)]
 #[allow(variant_size_differences)]
 #[serde(try_from = "String")]
 pub enum Base {
@@ -42,6 +42,9 @@ impl Base {
                 // We keep the username and password intact
                 Some(Base::Remote(*base_url))
             }
+            // Relative links in a bucket object point to other objects, so
+            // they are resolved against the URL of the object itself
+            InputSource::Bucket(url) => Some(Base::Remote(*url.clone())),
             // other inputs do not have a URL to extract a base
             _ => None,
         }
@@ -1,18 +1,25 @@
 use std::fmt::Display;
 
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::{ErrorKind, Status};
 
 /// Representation of the status of a cached request. This is kept simple on
 /// purpose because the type gets serialized to a cache file and might need to
 /// be parsed by other tools or edited by humans.
-#[derive(Debug, Serialize, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub enum CacheStatus {
     /// The cached request delivered a valid response
     Ok(u16),
     /// The cached request failed before
     Error(Option<u16>),
+    /// The cached request was redirected to a valid response
+    Redirected {
+        /// Status code of the final response
+        code: u16,
+        /// Whether the redirect chain starts with a permanent redirect
+        permanent: bool,
+    },
     /// The request was excluded (skipped)
     Excluded,
     /// The protocol is not yet supported
@@ -24,6 +31,32 @@ pub enum CacheStatus {
     Unsupported,
 }
 
+/// Suffixes of the status codes of redirected requests in cache files
+const REDIRECTED: &str = " (redirected)";
+const REDIRECTED_PERMANENTLY: &str = " (redirected permanently)";
+
+impl Serialize for CacheStatus {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Ok(code) | Self::Error(Some(code)) => serializer.serialize_u16(*code),
+            Self::Error(None) => serializer.serialize_none(),
+            Self::Redirected { code, permanent } => {
+                let suffix = if *permanent {
+                    REDIRECTED_PERMANENTLY
+                } else {
+                    REDIRECTED
+                };
+                serializer.serialize_str(&format!("{code}{suffix}"))
+            }
+            Self::Excluded => serializer.serialize_str("Excluded"),
+            Self::Unsupported => serializer.serialize_str("Unsupported"),
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for CacheStatus {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -36,6 +69,16 @@ impl<'de> Deserialize<'de> for CacheStatus {
             // no longer gets serialized. Can be removed at a later point in
             // time.
             "Unsupported" => Ok(CacheStatus::Unsupported),
+            other if other.ends_with(REDIRECTED) || other.ends_with(REDIRECTED_PERMANENTLY) => {
+                let permanent = other.ends_with(REDIRECTED_PERMANENTLY);
+                let code = other
+                    .trim_end_matches(REDIRECTED)
+                    .trim_end_matches(REDIRECTED_PERMANENTLY);
+                match code.parse::<u16>() {
+                    Ok(code) => Ok(CacheStatus::Redirected { code, permanent }),
+                    Err(_) => Ok(CacheStatus::Error(None)),
+                }
+            }
             other => match other.parse::<u16>() {
                 Ok(code) => match code {
                     // classify successful status codes as cache status success
@@ -57,6 +100,7 @@ impl Display for CacheStatus {
         match self {
             Self::Ok(_) => write!(f, "OK (cached)"),
             Self::Error(_) => write!(f, "Error (cached)"),
+            Self::Redirected { .. } => write!(f, "Redirect (cached)"),
             Self::Excluded => write!(f, "Excluded (cached)"),
             Self::Unsupported => write!(f, "Unsupported (cached)"),
         }
@@ -66,17 +110,26 @@ impl Display for CacheStatus {
 impl From<&Status> for CacheStatus {
     fn from(s: &Status) -> Self {
         match s {
-            Status::Cached(s) => *s,
+            Status::Cached(s, _) => *s,
             // Reqwest treats unknown status codes as Ok(StatusCode).
             // TODO: Use accepted status codes to decide whether this is a
             // success or failure
             Status::Ok(code) | Status::UnknownStatusCode(code) => Self::Ok(code.as_u16()),
-            Status::Excluded => Self::Excluded,
-            Status::Unsupported(_) => Self::Unsupported,
-            Status::Redirected(code) => Self::Error(Some(code.as_u16())),
+            Status::Excluded | Status::ExcludedScheme(_) => Self::Excluded,
+            // Skipped links are never cached, as they were not checked
+            Status::Unsupported(_) | Status::Skipped(_) => Self::Unsupported,
+            // Redirects which were followed to a successful response
+            Status::Redirected(code, redirects) if s.is_success() => Self::Redirected {
+                code: code.as_u16(),
+                permanent: redirects.permanent_target().is_some(),
+            },
+            Status::Redirected(code, _) => Self::Error(Some(code.as_u16())),
             Status::Timeout(code) => Self::Error(code.map(|code| code.as_u16())),
+            // The status code is a success, so don't let it get accepted
+            Status::CanonicalMismatch { .. } => Self::Error(None),
             Status::Error(e) => match e {
                 ErrorKind::NetworkRequest(e)
+                | ErrorKind::ErrorPage(e, _)
                 | ErrorKind::ReadResponseBody(e)
                 | ErrorKind::BuildRequestClient(e) => match e.status() {
                     Some(code) => Self::Error(Some(code.as_u16())),
@@ -90,10 +143,11 @@ impl From<&Status> for CacheStatus {
 
 #[cfg(test)]
 mod tests {
+    use http::StatusCode;
     use serde::de::value::{BorrowedStrDeserializer, Error as DeserializerError};
     use serde::Deserialize;
 
-    use crate::CacheStatus;
+    use crate::{CacheStatus, Redirect, Redirects, Status};
 
     fn deserialize_cache_status(s: &str) -> Result<CacheStatus, DeserializerError> {
         let deserializer: BorrowedStrDeserializer<DeserializerError> =
@@ -130,6 +184,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_deserialize_cache_status_redirected() {
+        assert_eq!(
+            deserialize_cache_status("200 (redirected)"),
+            Ok(CacheStatus::Redirected {
+                code: 200,
+                permanent: false
+            })
+        );
+        assert_eq!(
+            deserialize_cache_status("204 (redirected permanently)"),
+            Ok(CacheStatus::Redirected {
+                code: 204,
+                permanent: true
+            })
+        );
+    }
+
+    #[test]
+    fn test_cache_status_from_redirected() {
+        let redirects = Redirects::new(vec![
+            Redirect {
+                code: StatusCode::MOVED_PERMANENTLY,
+                url: "http://example.com/".parse().unwrap(),
+            },
+            Redirect {
+                code: StatusCode::OK,
+                url: "https://example.com/".parse().unwrap(),
+            },
+        ]);
+        assert_eq!(
+            CacheStatus::from(&Status::Redirected(StatusCode::OK, redirects)),
+            CacheStatus::Redirected {
+                code: 200,
+                permanent: true
+            }
+        );
+        // Redirects which weren't followed are errors
+        assert_eq!(
+            CacheStatus::from(&Status::Redirected(StatusCode::FOUND, Redirects::default())),
+            CacheStatus::Error(Some(302))
+        );
+    }
+
     #[test]
     fn test_deserialize_cache_status_blank() {
         assert_eq!(deserialize_cache_status(""), Ok(CacheStatus::Error(None)));
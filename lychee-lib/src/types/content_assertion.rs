@@ -0,0 +1,70 @@
+use regex::Regex;
+use reqwest::Url;
+
+/// A requirement on the body of the responses of matching URLs
+///
+/// Some servers respond to missing pages with a successful status code and a
+/// "Page not found" body (a soft 404). An assertion can catch these by
+/// requiring that the body of a page matches (or does not match) a regex.
+/// The body of successful `GET` responses is downloaded for URLs which
+/// match [`url`](ContentAssertion::url).
+#[derive(Debug, Clone)]
+pub struct ContentAssertion {
+    /// URLs whose responses are checked
+    pub url: Regex,
+    /// The body has to match this regex
+    pub contains: Option<Regex>,
+    /// The body must not match this regex
+    pub not_contains: Option<Regex>,
+}
+
+impl ContentAssertion {
+    /// Whether the assertion applies to the response of `url`
+    pub(crate) fn applies_to(&self, url: &Url) -> bool {
+        self.url.is_match(url.as_str())
+    }
+
+    /// The reason why `content` fails the assertion, if it does
+    pub(crate) fn check(&self, content: &str) -> Option<String> {
+        if let Some(contains) = &self.contains {
+            if !contains.is_match(content) {
+                return Some(format!("Body does not match `{contains}`"));
+            }
+        }
+        if let Some(not_contains) = &self.not_contains {
+            if not_contains.is_match(content) {
+                return Some(format!("Body matches `{not_contains}`"));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+    use reqwest::Url;
+
+    use super::ContentAssertion;
+
+    #[test]
+    fn test_content_assertion() {
+        let assertion = ContentAssertion {
+            url: Regex::new(r"^https://docs\.example\.com/").unwrap(),
+            contains: Some(Regex::new("<main").unwrap()),
+            not_contains: Some(Regex::new("(?i)page not found").unwrap()),
+        };
+        assert!(assertion.applies_to(&Url::parse("https://docs.example.com/guide").unwrap()));
+        assert!(!assertion.applies_to(&Url::parse("https://example.com/").unwrap()));
+
+        assert_eq!(assertion.check("<main>Guide</main>"), None);
+        assert_eq!(
+            assertion.check("<div>Guide</div>"),
+            Some("Body does not match `<main`".to_string())
+        );
+        assert_eq!(
+            assertion.check("<main>Page Not Found</main>"),
+            Some("Body matches `(?i)page not found`".to_string())
+        );
+    }
+}
@@ -6,7 +6,7 @@ use thiserror::Error;
 use tokio::task::JoinError;
 
 use super::InputContent;
-use crate::types::StatusCodeSelectorError;
+use crate::types::{Platform, StatusCodeSelectorError};
 use crate::{basic_auth::BasicAuthExtractorError, utils, Uri};
 
 /// Kinds of status errors
@@ -17,6 +17,10 @@ pub enum ErrorKind {
     /// Network error while handling request
     #[error("Network error")]
     NetworkRequest(#[source] reqwest::Error),
+    /// The request failed with an HTML error page, described by its title
+    /// and first heading
+    #[error("Network error")]
+    ErrorPage(#[source] reqwest::Error, String),
     /// Cannot read the body of the received response
     #[error("Error reading response body: {0}")]
     ReadResponseBody(#[source] reqwest::Error),
@@ -28,6 +32,15 @@ pub enum ErrorKind {
     #[error("Network error (GitHub client)")]
     GithubRequest(#[from] Box<octocrab::Error>),
 
+    /// Network error while using the API of a code hosting platform
+    #[error("Network error ({0} API)")]
+    PlatformRequest(Platform, #[source] reqwest::Error),
+
+    /// Network error while fetching an access token from an OAuth2 token
+    /// endpoint
+    #[error("Network error (OAuth2 token endpoint)")]
+    OAuth2Request(#[source] reqwest::Error),
+
     /// Error while executing a future on the Tokio runtime
     #[error("Task failed to execute to completion")]
     RuntimeJoin(#[from] JoinError),
@@ -52,6 +65,20 @@ pub enum ErrorKind {
     #[error("GitHub URL is invalid: {0}")]
     InvalidGithubUrl(String),
 
+    /// Invalid URL of a code hosting platform
+    #[error("{0} URL is invalid: {1}")]
+    InvalidPlatformUrl(Platform, String),
+
+    /// The response of an OAuth2 token endpoint doesn't contain a usable
+    /// access token
+    #[error("Invalid response of the OAuth2 token endpoint: {0}")]
+    InvalidOAuth2Response(String),
+
+    /// The OAuth2 token endpoint isn't requested over https, so the client
+    /// credentials would be sent in plain text
+    #[error("The OAuth2 token URL must use https: {0}")]
+    InsecureOAuth2TokenUrl(reqwest::Url),
+
     /// The input is empty and not accepted as a valid URL
     #[error("URL cannot be empty")]
     EmptyUrl,
@@ -76,6 +103,10 @@ pub enum ErrorKind {
     #[error("Unreachable mail address: {0}: {1}")]
     UnreachableEmailAddress(Uri, String),
 
+    /// The given phone number is malformed
+    #[error("Invalid phone number: {0}: {1}")]
+    InvalidPhoneNumber(Uri, String),
+
     /// The given header could not be parsed.
     /// A possible error when converting a `HeaderValue` from a string or byte
     /// slice.
@@ -106,14 +137,46 @@ pub enum ErrorKind {
     #[error("Error remapping URL: `{0}`")]
     InvalidUrlRemap(String),
 
+    /// The given input can not be parsed into a path mapping
+    #[error("Error mapping paths: `{0}`")]
+    InvalidPathMapping(String),
+
+    /// The proxy auto-config (PAC) file is invalid or unsupported
+    #[error("Invalid proxy auto-config: {0}")]
+    InvalidProxyPac(String),
+
     /// The given path does not resolve to a valid file
     #[error("Invalid file path: {0}")]
     InvalidFile(PathBuf),
 
+    /// A symlink which was followed points to a file that doesn't exist
+    #[error("Dangling symlink: `{0}` points to a file that doesn't exist")]
+    DanglingSymlink(PathBuf),
+
     /// Error while traversing an input directory
     #[error("Cannot traverse input directory: {0}")]
     DirTraversal(#[from] ignore::Error),
 
+    /// The sitemap can't be decoded or has an unexpected format
+    #[error("Cannot parse sitemap `{0}`: {1}")]
+    InvalidSitemap(String, String),
+
+    /// The archive can't be unpacked
+    #[error("Cannot unpack archive `{0}`: {1}")]
+    InvalidArchive(PathBuf, String),
+
+    /// The root certificate can't be read
+    #[error("Cannot read root certificate `{0}`: {1}")]
+    InvalidRootCertificate(PathBuf, String),
+
+    /// The container image can't be read
+    #[error("Cannot read container image `{0}`: {1}")]
+    InvalidContainerImage(String, String),
+
+    /// The objects of a storage bucket can't be read
+    #[error("Cannot read bucket `{0}`: {1}")]
+    InvalidBucket(String, String),
+
     /// The given glob pattern is not valid
     #[error("UNIX glob pattern is invalid")]
     InvalidGlobPattern(#[from] glob::PatternError),
@@ -142,6 +205,25 @@ pub enum ErrorKind {
     #[error("Invalid status code: {0}")]
     InvalidStatusCode(u16),
 
+    /// The status of the request was overridden with an error status code
+    /// by a handler of the request chain
+    #[error("Status code overridden: {0}")]
+    OverriddenStatusCode(http::StatusCode),
+
+    /// A page which was rendered in a headless browser responded with an
+    /// error status code
+    #[error("Rendered page responded with status code {0}")]
+    RenderedStatusCode(http::StatusCode),
+
+    /// The body of a successful response failed a content assertion, e.g.
+    /// because it is a "Page not found" page
+    #[error("Content assertion failed: {1}")]
+    ContentAssertion(http::StatusCode, String),
+
+    /// Pages can't be rendered in a headless browser
+    #[error("Cannot render pages: {0}")]
+    RenderJs(String),
+
     /// Regex error
     #[error("Error when using regex engine: {0}")]
     Regex(#[from] regex::Error),
@@ -158,6 +240,28 @@ pub enum ErrorKind {
     #[error("Cannot load cookies")]
     Cookies(String),
 
+    /// The anchor cache cannot be read or written
+    #[error("Invalid anchor cache: {0}")]
+    AnchorCache(String),
+
+    /// More input files than allowed were found
+    #[error("Found more than {0} input files; exclude unexpected directories (e.g. `node_modules`) or raise the limit")]
+    TooManyFiles(usize),
+
+    /// The state of a recursive crawl cannot be read or written
+    #[error("Cannot read or write crawl state `{1}`")]
+    CrawlState(#[source] std::io::Error, PathBuf),
+
+    /// More links than allowed were found
+    #[error("Found more than {0} links; exclude unexpected inputs or raise the limit")]
+    TooManyLinks(usize),
+
+    /// The link failed with some of the requested languages, given as
+    /// pairs of the `Accept-Language` value and the status code of the
+    /// failure
+    #[error("Failed for some languages: {}", format_language_failures(.0))]
+    LanguageFailures(Vec<(String, String)>),
+
     /// Status code selector parse error
     #[error("Status code range error")]
     StatusCodeSelectorError(#[from] StatusCodeSelectorError),
@@ -192,6 +296,13 @@ impl ErrorKind {
                     }
                 }
             }
+            ErrorKind::ErrorPage(e, snippet) => {
+                let reason = e
+                    .status()
+                    .and_then(|status| status.canonical_reason())
+                    .unwrap_or("Unknown status code");
+                Some(format!("{reason} – {snippet}"))
+            }
             ErrorKind::GithubRequest(e) => {
                 if let octocrab::Error::GitHub { source, .. } = &**e {
                     Some(source.message.clone())
@@ -233,6 +344,9 @@ impl PartialEq for ErrorKind {
             (Self::NetworkRequest(e1), Self::NetworkRequest(e2)) => {
                 e1.to_string() == e2.to_string()
             }
+            (Self::ErrorPage(e1, s1), Self::ErrorPage(e2, s2)) => {
+                e1.to_string() == e2.to_string() && s1 == s2
+            }
             (Self::ReadResponseBody(e1), Self::ReadResponseBody(e2)) => {
                 e1.to_string() == e2.to_string()
             }
@@ -244,12 +358,25 @@ impl PartialEq for ErrorKind {
                 e1.kind() == e2.kind() && s1 == s2
             }
             (Self::ReadStdinInput(e1), Self::ReadStdinInput(e2)) => e1.kind() == e2.kind(),
+            (Self::CrawlState(e1, p1), Self::CrawlState(e2, p2)) => {
+                e1.kind() == e2.kind() && p1 == p2
+            }
             (Self::GithubRequest(e1), Self::GithubRequest(e2)) => e1.to_string() == e2.to_string(),
             (Self::InvalidGithubUrl(s1), Self::InvalidGithubUrl(s2)) => s1 == s2,
+            (Self::PlatformRequest(p1, e1), Self::PlatformRequest(p2, e2)) => {
+                p1 == p2 && e1.to_string() == e2.to_string()
+            }
+            (Self::InvalidPlatformUrl(p1, s1), Self::InvalidPlatformUrl(p2, s2)) => {
+                p1 == p2 && s1 == s2
+            }
+            (Self::OAuth2Request(e1), Self::OAuth2Request(e2)) => e1.to_string() == e2.to_string(),
+            (Self::InvalidOAuth2Response(s1), Self::InvalidOAuth2Response(s2)) => s1 == s2,
+            (Self::InsecureOAuth2TokenUrl(u1), Self::InsecureOAuth2TokenUrl(u2)) => u1 == u2,
             (Self::ParseUrl(s1, e1), Self::ParseUrl(s2, e2)) => s1 == s2 && e1 == e2,
             (Self::UnreachableEmailAddress(u1, ..), Self::UnreachableEmailAddress(u2, ..)) => {
                 u1 == u2
             }
+            (Self::InvalidPhoneNumber(u1, ..), Self::InvalidPhoneNumber(u2, ..)) => u1 == u2,
             (Self::InsecureURL(u1), Self::InsecureURL(u2)) => u1 == u2,
             (Self::InvalidGlobPattern(e1), Self::InvalidGlobPattern(e2)) => {
                 e1.msg == e2.msg && e1.pos == e2.pos
@@ -257,10 +384,17 @@ impl PartialEq for ErrorKind {
             (Self::InvalidHeader(_), Self::InvalidHeader(_))
             | (Self::MissingGitHubToken, Self::MissingGitHubToken) => true,
             (Self::InvalidStatusCode(c1), Self::InvalidStatusCode(c2)) => c1 == c2,
+            (Self::OverriddenStatusCode(c1), Self::OverriddenStatusCode(c2))
+            | (Self::RenderedStatusCode(c1), Self::RenderedStatusCode(c2)) => c1 == c2,
+            (Self::RenderJs(e1), Self::RenderJs(e2)) => e1 == e2,
+            (Self::ContentAssertion(c1, r1), Self::ContentAssertion(c2, r2)) => {
+                c1 == c2 && r1 == r2
+            }
             (Self::InvalidUrlHost, Self::InvalidUrlHost) => true,
             (Self::InvalidURI(u1), Self::InvalidURI(u2)) => u1 == u2,
             (Self::Regex(e1), Self::Regex(e2)) => e1.to_string() == e2.to_string(),
             (Self::DirTraversal(e1), Self::DirTraversal(e2)) => e1.to_string() == e2.to_string(),
+            (Self::DanglingSymlink(p1), Self::DanglingSymlink(p2)) => p1 == p2,
             (Self::Channel(_), Self::Channel(_)) => true,
             (Self::TooManyRedirects(e1), Self::TooManyRedirects(e2)) => {
                 e1.to_string() == e2.to_string()
@@ -268,13 +402,28 @@ impl PartialEq for ErrorKind {
             (Self::BasicAuthExtractorError(e1), Self::BasicAuthExtractorError(e2)) => {
                 e1.to_string() == e2.to_string()
             }
-            (Self::Cookies(e1), Self::Cookies(e2)) => e1 == e2,
+            (Self::Cookies(e1), Self::Cookies(e2))
+            | (Self::AnchorCache(e1), Self::AnchorCache(e2)) => e1 == e2,
+            (Self::TooManyFiles(n1), Self::TooManyFiles(n2))
+            | (Self::TooManyLinks(n1), Self::TooManyLinks(n2)) => n1 == n2,
             (Self::InvalidFile(p1), Self::InvalidFile(p2)) => p1 == p2,
             (Self::InvalidFilePath(u1), Self::InvalidFilePath(u2)) => u1 == u2,
             (Self::InvalidFragment(u1), Self::InvalidFragment(u2)) => u1 == u2,
+            (Self::LanguageFailures(f1), Self::LanguageFailures(f2)) => f1 == f2,
             (Self::InvalidUrlFromPath(p1), Self::InvalidUrlFromPath(p2)) => p1 == p2,
             (Self::InvalidBase(b1, e1), Self::InvalidBase(b2, e2)) => b1 == b2 && e1 == e2,
             (Self::InvalidUrlRemap(r1), Self::InvalidUrlRemap(r2)) => r1 == r2,
+            (Self::InvalidPathMapping(m1), Self::InvalidPathMapping(m2)) => m1 == m2,
+            (Self::InvalidProxyPac(e1), Self::InvalidProxyPac(e2)) => e1 == e2,
+            (Self::InvalidSitemap(u1, e1), Self::InvalidSitemap(u2, e2)) => u1 == u2 && e1 == e2,
+            (Self::InvalidArchive(p1, e1), Self::InvalidArchive(p2, e2)) => p1 == p2 && e1 == e2,
+            (Self::InvalidRootCertificate(p1, e1), Self::InvalidRootCertificate(p2, e2)) => {
+                p1 == p2 && e1 == e2
+            }
+            (Self::InvalidContainerImage(r1, e1), Self::InvalidContainerImage(r2, e2)) => {
+                r1 == r2 && e1 == e2
+            }
+            (Self::InvalidBucket(u1, e1), Self::InvalidBucket(u2, e2)) => u1 == u2 && e1 == e2,
             (Self::EmptyUrl, Self::EmptyUrl) => true,
 
             _ => false,
@@ -294,13 +443,21 @@ impl Hash for ErrorKind {
             Self::RuntimeJoin(e) => e.to_string().hash(state),
             Self::ReadFileInput(e, s) => (e.kind(), s).hash(state),
             Self::ReadStdinInput(e) => e.kind().hash(state),
+            Self::CrawlState(e, p) => (e.kind(), p).hash(state),
             Self::NetworkRequest(e) => e.to_string().hash(state),
+            Self::ErrorPage(e, s) => (e.to_string(), s).hash(state),
             Self::ReadResponseBody(e) => e.to_string().hash(state),
             Self::BuildRequestClient(e) => e.to_string().hash(state),
             Self::BuildGithubClient(e) => e.to_string().hash(state),
             Self::GithubRequest(e) => e.to_string().hash(state),
             Self::InvalidGithubUrl(s) => s.hash(state),
+            Self::PlatformRequest(p, e) => (p, e.to_string()).hash(state),
+            Self::InvalidPlatformUrl(p, s) => (p, s).hash(state),
+            Self::OAuth2Request(e) => e.to_string().hash(state),
+            Self::InvalidOAuth2Response(s) => s.hash(state),
+            Self::InsecureOAuth2TokenUrl(u) => u.hash(state),
             Self::DirTraversal(e) => e.to_string().hash(state),
+            Self::DanglingSymlink(p) => p.hash(state),
             Self::InvalidFile(e) => e.to_string_lossy().hash(state),
             Self::EmptyUrl => "Empty URL".hash(state),
             Self::ParseUrl(e, s) => (e.to_string(), s).hash(state),
@@ -309,7 +466,9 @@ impl Hash for ErrorKind {
             Self::Utf8(e) => e.to_string().hash(state),
             Self::InvalidFilePath(u) => u.hash(state),
             Self::InvalidFragment(u) => u.hash(state),
+            Self::LanguageFailures(f) => f.hash(state),
             Self::UnreachableEmailAddress(u, ..) => u.hash(state),
+            Self::InvalidPhoneNumber(u, ..) => u.hash(state),
             Self::InsecureURL(u, ..) => u.hash(state),
             Self::InvalidBase(base, e) => (base, e).hash(state),
             Self::InvalidBaseJoin(s) => s.hash(state),
@@ -317,9 +476,19 @@ impl Hash for ErrorKind {
             Self::RootDirMustBeAbsolute(s) => s.hash(state),
             Self::UnsupportedUriType(s) => s.hash(state),
             Self::InvalidUrlRemap(remap) => (remap).hash(state),
+            Self::InvalidPathMapping(mapping) => mapping.hash(state),
+            Self::InvalidProxyPac(e) => e.hash(state),
             Self::InvalidHeader(e) => e.to_string().hash(state),
             Self::InvalidGlobPattern(e) => e.to_string().hash(state),
+            Self::InvalidSitemap(url, e) => (url, e).hash(state),
+            Self::InvalidArchive(path, e) => (path, e).hash(state),
+            Self::InvalidRootCertificate(path, e) => (path, e).hash(state),
+            Self::InvalidContainerImage(reference, e) => (reference, e).hash(state),
+            Self::InvalidBucket(url, e) => (url, e).hash(state),
             Self::InvalidStatusCode(c) => c.hash(state),
+            Self::OverriddenStatusCode(c) | Self::RenderedStatusCode(c) => c.hash(state),
+            Self::RenderJs(e) => e.hash(state),
+            Self::ContentAssertion(c, reason) => (c, reason).hash(state),
             Self::Channel(e) => e.to_string().hash(state),
             Self::MissingGitHubToken | Self::InvalidUrlHost => {
                 std::mem::discriminant(self).hash(state);
@@ -328,11 +497,24 @@ impl Hash for ErrorKind {
             Self::TooManyRedirects(e) => e.to_string().hash(state),
             Self::BasicAuthExtractorError(e) => e.to_string().hash(state),
             Self::Cookies(e) => e.to_string().hash(state),
+            Self::AnchorCache(e) => e.hash(state),
             Self::StatusCodeSelectorError(e) => e.to_string().hash(state),
+            Self::TooManyFiles(n) | Self::TooManyLinks(n) => {
+                (std::mem::discriminant(self), n).hash(state);
+            }
         }
     }
 }
 
+/// Format failures of languages as `de (404), fr (TIMEOUT)`
+fn format_language_failures(failures: &[(String, String)]) -> String {
+    failures
+        .iter()
+        .map(|(language, code)| format!("{language} ({code})"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 impl Serialize for ErrorKind {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
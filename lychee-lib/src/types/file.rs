@@ -1,13 +1,20 @@
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
 use url::Url;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 /// `FileType` defines which file types lychee can handle
 pub enum FileType {
     /// File in HTML format
     Html,
     /// File in Markdown format
     Markdown,
+    /// Jupyter notebook (JSON with Markdown and code cells)
+    Ipynb,
+    /// SVG image, whose links (e.g. `<a>`, `<image>` and `<use>` elements)
+    /// are extracted like those of HTML
+    Svg,
     /// Generic text file without syntax-specific parsing
     Plaintext,
 }
@@ -18,8 +25,65 @@ impl Default for FileType {
     }
 }
 
+/// Number of bytes at the start of a file which are used to guess its type
+const SNIFF_LEN: usize = 1024;
+
+impl FileType {
+    /// Guess the file type from the start of the `content` of a file without
+    /// an extension, like `README` or `LICENSE`.
+    ///
+    /// Content starting with a doctype or `<html>` tag is HTML, content
+    /// starting with an `<svg>` tag is an SVG image. Content with
+    /// Markdown headings, code fences or links is Markdown. Everything else
+    /// (including scripts with a shebang line) is plaintext.
+    #[must_use]
+    pub fn sniff(content: &str) -> FileType {
+        let mut end = content.len().min(SNIFF_LEN);
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        let head = content[..end].trim_start_matches('\u{feff}').trim_start();
+
+        let starts_with_tag = |tag: &str| {
+            head.get(..tag.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(tag))
+        };
+        if starts_with_tag("<!doctype html") || starts_with_tag("<html") {
+            return FileType::Html;
+        }
+        if starts_with_tag("<svg") {
+            return FileType::Svg;
+        }
+        if head.starts_with("#!") {
+            return FileType::Plaintext;
+        }
+
+        let mut previous = "";
+        for line in head.lines() {
+            let heading = line.trim_start_matches('#');
+            let is_heading = (1..=6).contains(&(line.len() - heading.len()))
+                && (heading.is_empty() || heading.starts_with(' '));
+            let is_underline = line.len() >= 3
+                && (line.bytes().all(|b| b == b'=') || line.bytes().all(|b| b == b'-'))
+                && !previous.trim().is_empty();
+            let is_link = line.find("](").is_some_and(|i| line[..i].contains('['));
+            if is_heading
+                || is_underline
+                || is_link
+                || line.starts_with("```")
+                || line.starts_with("~~~")
+            {
+                return FileType::Markdown;
+            }
+            previous = line;
+        }
+        FileType::Plaintext
+    }
+}
+
 impl<P: AsRef<Path>> From<P> for FileType {
-    /// Detect if the given path points to a Markdown, HTML, or plaintext file.
+    /// Detect if the given path points to a Markdown, HTML, Jupyter notebook,
+    /// SVG, or plaintext file.
     //
     // Assume HTML in case of no extension.
     //
@@ -47,12 +111,84 @@ impl<P: AsRef<Path>> From<P> for FileType {
                 FileType::Markdown
             }
             Some("htm" | "html") => FileType::Html,
+            Some("ipynb") => FileType::Ipynb,
+            Some("svg") => FileType::Svg,
             None if is_url(path) => FileType::Html,
             _ => FileType::default(),
         }
     }
 }
 
+/// Custom file extensions and the [`FileType`] of files with them, e.g.
+/// `mdoc` for Markdown or `txt.j2` for plaintext.
+///
+/// Custom extensions take precedence over the built-in extensions of
+/// [`FileType::from`]. Extensions can consist of multiple parts, in which
+/// case the longest matching extension is used.
+///
+/// The file type of local files without an extension is guessed from their
+/// content (see [`FileType::sniff`]), unless it is set with
+/// [`FileExtensions::extensionless`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FileExtensions {
+    extensions: BTreeMap<String, FileType>,
+    extensionless: Option<FileType>,
+}
+
+impl FileExtensions {
+    /// Create a mapping of extensions (without the leading dot) to file
+    /// types. Extensions are matched case-insensitively.
+    #[must_use]
+    pub fn new(extensions: impl IntoIterator<Item = (String, FileType)>) -> Self {
+        Self {
+            extensions: extensions
+                .into_iter()
+                .map(|(extension, file_type)| {
+                    (extension.trim_start_matches('.').to_lowercase(), file_type)
+                })
+                .collect(),
+            extensionless: None,
+        }
+    }
+
+    /// Use `file_type` for local files without an extension instead of
+    /// guessing it from their content
+    #[must_use]
+    pub const fn extensionless(mut self, file_type: Option<FileType>) -> Self {
+        self.extensionless = file_type;
+        self
+    }
+
+    /// The file type of `path` if it has one of the custom extensions
+    #[must_use]
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<FileType> {
+        let name = path.as_ref().file_name()?.to_str()?.to_lowercase();
+        self.extensions
+            .iter()
+            .filter(|(extension, _)| {
+                name.strip_suffix(extension.as_str())
+                    .and_then(|stem| stem.strip_suffix('.'))
+                    .is_some_and(|stem| !stem.is_empty())
+            })
+            .max_by_key(|(extension, _)| extension.len())
+            .map(|(_, file_type)| *file_type)
+    }
+
+    /// The file type of `path`, detected from the custom extensions or the
+    /// built-in ones
+    #[must_use]
+    pub fn file_type(&self, path: impl AsRef<Path>) -> FileType {
+        self.get(&path).unwrap_or_else(|| FileType::from(path))
+    }
+
+    /// The file type of a local file without an extension and with the
+    /// given `content`
+    pub(crate) fn extensionless_file_type(&self, content: &str) -> FileType {
+        self.extensionless
+            .unwrap_or_else(|| FileType::sniff(content))
+    }
+}
+
 /// Helper function to check if a path is likely a URL.
 fn is_url(path: &Path) -> bool {
     path.to_str()
@@ -82,12 +218,74 @@ mod tests {
 
         assert_eq!(FileType::from(Path::new("test.htm")), FileType::Html);
         assert_eq!(FileType::from(Path::new("index.html")), FileType::Html);
+        assert_eq!(FileType::from(Path::new("analysis.ipynb")), FileType::Ipynb);
+        assert_eq!(FileType::from(Path::new("icons.SVG")), FileType::Svg);
         assert_eq!(
             FileType::from(Path::new("http://foo.com/index.html")),
             FileType::Html
         );
     }
 
+    #[test]
+    fn test_file_extensions() {
+        let extensions = FileExtensions::new([
+            ("mdoc".to_string(), FileType::Markdown),
+            (".HTM".to_string(), FileType::Plaintext),
+            ("txt.j2".to_string(), FileType::Plaintext),
+            ("j2".to_string(), FileType::Html),
+        ]);
+
+        assert_eq!(extensions.get("guide.mdoc"), Some(FileType::Markdown));
+        assert_eq!(extensions.get("docs/INDEX.htm"), Some(FileType::Plaintext));
+        assert_eq!(extensions.get("notes.txt.j2"), Some(FileType::Plaintext));
+        assert_eq!(extensions.get("page.j2"), Some(FileType::Html));
+        assert_eq!(extensions.get("README.md"), None);
+        assert_eq!(extensions.get(".mdoc"), None);
+        assert_eq!(extensions.get("txt.j2"), Some(FileType::Html));
+
+        assert_eq!(extensions.file_type("README.md"), FileType::Markdown);
+        assert_eq!(extensions.file_type("index.htm"), FileType::Plaintext);
+    }
+
+    #[test]
+    fn test_sniff() {
+        assert_eq!(
+            FileType::sniff("\n<!DOCTYPE html>\n<title>Docs</title>"),
+            FileType::Html
+        );
+        assert_eq!(FileType::sniff("<html lang=\"en\">"), FileType::Html);
+        assert_eq!(
+            FileType::sniff("<svg xmlns=\"http://www.w3.org/2000/svg\">"),
+            FileType::Svg
+        );
+        assert_eq!(
+            FileType::sniff("# Project\n\nSee https://example.com"),
+            FileType::Markdown
+        );
+        assert_eq!(FileType::sniff("Project\n=======\n"), FileType::Markdown);
+        assert_eq!(
+            FileType::sniff("See [the docs](https://example.com)."),
+            FileType::Markdown
+        );
+        assert_eq!(
+            FileType::sniff("Usage:\n```\nmake\n```"),
+            FileType::Markdown
+        );
+        assert_eq!(
+            FileType::sniff("#!/bin/sh\n# Install\ncurl https://example.com"),
+            FileType::Plaintext
+        );
+        assert_eq!(
+            FileType::sniff("MIT License\n\nCopyright (c) 2024 #1"),
+            FileType::Plaintext
+        );
+        assert_eq!(
+            FileType::sniff("#hashtag\nhttps://example.com"),
+            FileType::Plaintext
+        );
+        assert_eq!(FileType::sniff(""), FileType::Plaintext);
+    }
+
     #[test]
     fn test_is_url() {
         // Valid URLs
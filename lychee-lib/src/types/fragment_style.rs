@@ -0,0 +1,122 @@
+use regex::Regex;
+
+/// Rules for generating the fragments (anchors) of Markdown headings
+///
+/// Markdown renderers derive the `id` of a heading from its text, but each
+/// renderer does so slightly differently. For example, the heading `## A - B`
+/// gets the fragment `#a---b` on GitHub, but `#a-b` on GitLab.
+/// Pick the style of the renderer the documents are written for.
+///
+/// Duplicate headings get a numeric suffix (`-1`, `-2`, ...) in all styles.
+#[derive(Debug, Clone, Default)]
+pub enum FragmentStyle {
+    /// GitHub: lowercase the text, remove all characters except letters,
+    /// numbers, `_` and `-` and replace whitespace with `-`.
+    #[default]
+    Github,
+    /// GitLab: like [`FragmentStyle::Github`], but runs of `-` are collapsed
+    /// into a single `-`.
+    Gitlab,
+    /// Kramdown (e.g. Jekyll): remove all characters except ASCII letters,
+    /// numbers, spaces and `-`, remove everything up to the first letter,
+    /// lowercase the text and replace spaces with `-`. Headings without
+    /// letters get the fragment `section`.
+    Kramdown,
+    /// Lowercase the text, remove all matches of the regex and replace
+    /// whitespace with `-`.
+    CustomRegex(Regex),
+}
+
+impl FragmentStyle {
+    /// Generate the fragment for a heading with the given text, without
+    /// the suffix for duplicate headings
+    #[must_use]
+    pub fn slugify(&self, heading: &str) -> String {
+        match self {
+            Self::Github => Self::github(heading),
+            Self::Gitlab => {
+                let mut slug = String::with_capacity(heading.len());
+                for ch in Self::github(heading).chars() {
+                    if ch != '-' || !slug.ends_with('-') {
+                        slug.push(ch);
+                    }
+                }
+                slug
+            }
+            Self::Kramdown => {
+                let slug: String = heading
+                    .chars()
+                    .filter(|ch| ch.is_ascii_alphanumeric() || *ch == ' ' || *ch == '-')
+                    .skip_while(|ch| !ch.is_ascii_alphabetic())
+                    .map(|ch| {
+                        if ch == ' ' {
+                            '-'
+                        } else {
+                            ch.to_ascii_lowercase()
+                        }
+                    })
+                    .collect();
+                if slug.is_empty() {
+                    "section".to_string()
+                } else {
+                    slug
+                }
+            }
+            Self::CustomRegex(regex) => regex
+                .replace_all(&heading.to_lowercase(), "")
+                .chars()
+                .map(|ch| if ch.is_whitespace() { '-' } else { ch })
+                .collect(),
+        }
+    }
+
+    fn github(heading: &str) -> String {
+        heading
+            .to_lowercase()
+            .chars()
+            .filter_map(|ch| {
+                if ch.is_alphanumeric() || ch == '_' || ch == '-' {
+                    Some(ch)
+                } else if ch.is_whitespace() {
+                    Some('-')
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_github() {
+        let style = FragmentStyle::Github;
+        assert_eq!(style.slugify("Step 1: Install"), "step-1-install");
+        assert_eq!(style.slugify("A - B"), "a---b");
+    }
+
+    #[test]
+    fn test_gitlab() {
+        let style = FragmentStyle::Gitlab;
+        assert_eq!(style.slugify("Step 1: Install"), "step-1-install");
+        assert_eq!(style.slugify("This - is - a - header"), "this-is-a-header");
+    }
+
+    #[test]
+    fn test_kramdown() {
+        let style = FragmentStyle::Kramdown;
+        assert_eq!(style.slugify("1. Getting Started"), "getting-started");
+        assert_eq!(style.slugify("snake_case & Co"), "snakecase--co");
+        assert_eq!(style.slugify("123"), "section");
+    }
+
+    #[test]
+    fn test_custom_regex() {
+        let style = FragmentStyle::CustomRegex(Regex::new(r"[^a-z0-9 ]").unwrap());
+        assert_eq!(style.slugify("Step 1: Install"), "step-1-install");
+        assert_eq!(style.slugify("Über uns"), "ber-uns");
+    }
+}
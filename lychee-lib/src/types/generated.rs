@@ -0,0 +1,94 @@
+use std::ops::RangeInclusive;
+
+use regex::Regex;
+
+use crate::Result;
+
+/// Default start marker of generated regions, e.g. `<!-- AUTO-GENERATED -->`
+/// or `<!-- BEGIN AUTO-GENERATED TABLE -->`
+pub const DEFAULT_GENERATED_START: &str = r"(?i)<!--\s*(BEGIN\s+)?AUTO-?GENERATED\b.*?-->";
+
+/// Default end marker of generated regions, e.g. `<!-- END -->` or
+/// `<!-- END AUTO-GENERATED -->`
+pub const DEFAULT_GENERATED_END: &str = r"(?i)<!--\s*END\b.*?-->";
+
+/// Markers of regions in inputs which are generated by other tools, e.g.
+/// tables of contents or API references.
+///
+/// Links in these regions can't be fixed by hand, so their failures are
+/// reported separately (see [`Request::generated`](crate::Request::generated)).
+#[derive(Debug, Clone)]
+pub struct GeneratedMarkers {
+    start: Regex,
+    end: Regex,
+}
+
+impl Default for GeneratedMarkers {
+    fn default() -> Self {
+        Self {
+            start: Regex::new(DEFAULT_GENERATED_START).unwrap(),
+            end: Regex::new(DEFAULT_GENERATED_END).unwrap(),
+        }
+    }
+}
+
+impl GeneratedMarkers {
+    /// Regions start at lines matching `start` and end at the next line
+    /// matching `end`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the patterns is not a valid regex
+    pub fn new(start: &str, end: &str) -> Result<Self> {
+        Ok(Self {
+            start: Regex::new(start)?,
+            end: Regex::new(end)?,
+        })
+    }
+
+    /// The (one-based) lines of all generated regions in `content`, including
+    /// the lines of the markers. A region without an end marker extends to
+    /// the end of the content.
+    pub(crate) fn regions(&self, content: &str) -> Vec<RangeInclusive<usize>> {
+        let mut regions = Vec::new();
+        let mut start = None;
+        let mut last = 0;
+        for (line, text) in (1..).zip(content.lines()) {
+            last = line;
+            match start {
+                None if self.start.is_match(text) => start = Some(line),
+                Some(first) if self.end.is_match(text) => {
+                    regions.push(first..=line);
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(first) = start {
+            regions.push(first..=last);
+        }
+        regions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regions() {
+        let content = "\
+# Docs
+<!-- AUTO-GENERATED -->
+| [a](https://example.com/a) |
+<!-- END -->
+text
+<!-- BEGIN AUTO-GENERATED TABLE -->
+| [b](https://example.com/b) |";
+        assert_eq!(GeneratedMarkers::default().regions(content), [2..=4, 6..=7]);
+
+        let markers = GeneratedMarkers::new("^// generated", "^// end").unwrap();
+        assert_eq!(markers.regions("a\n// generated\nb\n// end\nc"), [2..=4]);
+        assert!(markers.regions(content).is_empty());
+    }
+}
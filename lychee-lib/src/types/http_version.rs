@@ -0,0 +1,27 @@
+/// HTTP protocol versions used for requests
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum HttpVersion {
+    /// Negotiate the version with the server, like browsers do.
+    ///
+    /// HTTPS connections use HTTP/2 if the server offers it via ALPN,
+    /// plain HTTP connections use HTTP/1.1.
+    #[default]
+    Auto,
+    /// Only use HTTP/1.1
+    Http1Only,
+    /// Use HTTP/2 without negotiation, also for plain HTTP (`h2c`).
+    ///
+    /// Servers which don't support HTTP/2 can't be checked with this setting.
+    Http2PriorKnowledge,
+}
+
+impl HttpVersion {
+    /// Configure a [`reqwest::ClientBuilder`] to use this HTTP version
+    pub(crate) fn apply(self, builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        match self {
+            Self::Auto => builder,
+            Self::Http1Only => builder.http1_only(),
+            Self::Http2PriorKnowledge => builder.http2_prior_knowledge(),
+        }
+    }
+}
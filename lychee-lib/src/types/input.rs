@@ -1,23 +1,36 @@
-use crate::types::FileType;
-use crate::{utils, ErrorKind, Result};
+use crate::types::{FileExtensions, FileType};
+use crate::utils::archive::UnpackedDir;
+use crate::utils::bucket::{self, Bucket};
+use crate::utils::{archive, container, encoding, sitemap};
+use crate::{utils, Base, ErrorKind, Result};
 use async_stream::try_stream;
-use futures::stream::Stream;
+use encoding_rs::Encoding;
+use futures::stream::{Stream, TryStreamExt};
 use glob::glob_with;
 use ignore::WalkBuilder;
+use log::warn;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use shellexpand::tilde;
+use std::collections::HashSet;
 use std::fmt::Display;
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
 use tokio::io::{stdin, AsyncReadExt};
 
 const STDIN: &str = "-";
 
 // Check the extension of the given path against the list of known/accepted
-// file extensions
-fn valid_extension(p: &Path) -> bool {
-    matches!(FileType::from(p), FileType::Markdown | FileType::Html)
+// file extensions. Files with custom extensions are always accepted.
+// SVG images are only accepted with a custom extension (e.g. `svg = "svg"`),
+// as directories often contain many images without links.
+fn valid_extension(p: &Path, file_extensions: &FileExtensions) -> bool {
+    file_extensions.get(p).is_some()
+        || matches!(
+            FileType::from(p),
+            FileType::Markdown | FileType::Html | FileType::Ipynb
+        )
 }
 
 #[derive(Debug)]
@@ -27,8 +40,13 @@ pub struct InputContent {
     pub source: InputSource,
     /// File type of given input
     pub file_type: FileType,
-    /// Raw UTF-8 string content
+    /// Content decoded to UTF-8
     pub content: String,
+    /// File from which the content gets streamed during link extraction,
+    /// because it is too large to be read into memory.
+    ///
+    /// If this is set, `content` is empty. See [`Extractor::extract_stream`](crate::extract::Extractor::extract_stream).
+    pub stream: Option<PathBuf>,
 }
 
 impl InputContent {
@@ -40,6 +58,7 @@ impl InputContent {
             source: InputSource::String(s.to_owned()),
             file_type,
             content: s.to_owned(),
+            stream: None,
         }
     }
 }
@@ -48,13 +67,14 @@ impl TryFrom<&PathBuf> for InputContent {
     type Error = crate::ErrorKind;
 
     fn try_from(path: &PathBuf) -> std::result::Result<Self, Self::Error> {
-        let input =
-            fs::read_to_string(path).map_err(|e| ErrorKind::ReadFileInput(e, path.clone()))?;
+        let bytes = fs::read(path).map_err(|e| ErrorKind::ReadFileInput(e, path.clone()))?;
+        let input = encoding::decode(&bytes, None, &path.to_string_lossy());
 
         Ok(Self {
             source: InputSource::String(input.clone()),
             file_type: FileType::from(path),
             content: input,
+            stream: None,
         })
     }
 }
@@ -65,6 +85,11 @@ impl TryFrom<&PathBuf> for InputContent {
 pub enum InputSource {
     /// URL (of HTTP/HTTPS scheme).
     RemoteUrl(Box<Url>),
+    /// URL of a sitemap (of HTTP/HTTPS scheme).
+    ///
+    /// Links on all pages listed in the sitemap get checked.
+    /// Sitemap index files and gzipped sitemaps are supported.
+    Sitemap(Box<Url>),
     /// Unix shell-style glob pattern.
     FsGlob {
         /// The glob pattern matching all input files
@@ -74,6 +99,40 @@ pub enum InputSource {
     },
     /// File path.
     FsPath(PathBuf),
+    /// Archive file (zip or tar.gz).
+    ///
+    /// The archive gets unpacked and all Markdown and HTML files in it get
+    /// checked.
+    Archive(PathBuf),
+    /// Container image, either a local OCI image layout (`oci:<path>`) or
+    /// an image in a registry (`docker://<image>`).
+    ///
+    /// The files matching `paths` get unpacked from the image and checked.
+    ContainerImage {
+        /// Reference to the image as given by the user
+        reference: String,
+        /// Glob patterns of the files to check, e.g. `/usr/share/doc/**`
+        paths: Vec<String>,
+    },
+    /// File inside of an archive or container image, displayed as
+    /// `archive.zip!/docs/readme.md`.
+    ArchiveEntry {
+        /// Path of the archive or reference to the container image
+        archive: PathBuf,
+        /// Path of the file inside of the archive
+        path: PathBuf,
+        /// Path of the unpacked file
+        unpacked: PathBuf,
+        /// Directory of the unpacked files, which is removed once the
+        /// sources of all its files are dropped
+        #[serde(skip)]
+        dir: UnpackedDir,
+    },
+    /// Object in a cloud storage bucket (`s3://`, `gs://` or `az://`).
+    ///
+    /// As input, all Markdown and HTML objects whose key starts with the path
+    /// of the URL get checked.
+    Bucket(Box<Url>),
     /// Standard Input.
     Stdin,
     /// Raw string input.
@@ -92,15 +151,65 @@ impl Serialize for InputSource {
     }
 }
 
+impl InputSource {
+    /// The path of the file or URL, which determines the file type
+    pub(crate) fn file_path(&self) -> Option<&Path> {
+        match self {
+            Self::FsPath(path) | Self::ArchiveEntry { path, .. } => Some(path),
+            Self::RemoteUrl(url) | Self::Bucket(url) => Some(Path::new(url.path())),
+            _ => None,
+        }
+    }
+}
+
 impl Display for InputSource {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(match self {
-            Self::RemoteUrl(url) => url.as_str(),
+        let source = match self {
+            Self::RemoteUrl(url) | Self::Sitemap(url) | Self::Bucket(url) => url.as_str(),
             Self::FsGlob { pattern, .. } => pattern,
-            Self::FsPath(path) => path.to_str().unwrap_or_default(),
+            Self::FsPath(path) | Self::Archive(path) => path.to_str().unwrap_or_default(),
+            Self::ContainerImage { reference, .. } => reference,
+            Self::ArchiveEntry { archive, path, .. } => {
+                return write!(f, "{}!/{}", archive.display(), path.display());
+            }
             Self::Stdin => "stdin",
             Self::String(s) => s,
-        })
+        };
+        f.write_str(source)
+    }
+}
+
+/// Options for retrieving the contents of an [`Input`]
+/// (see [`Input::get_contents`])
+#[derive(Clone, Debug)]
+pub struct ContentOptions {
+    /// Skip inputs which can't be read instead of returning an error
+    pub skip_missing: bool,
+    /// Skip hidden files and directories
+    pub skip_hidden: bool,
+    /// Skip files ignored by `.gitignore` and similar files
+    pub skip_gitignored: bool,
+    /// Follow symlinks in directories and glob matches
+    pub follow_symlinks: bool,
+    /// Encoding of files and stdin without a byte order mark
+    pub encoding: Option<&'static Encoding>,
+    /// Size in bytes above which files are streamed instead of read into memory
+    pub stream_threshold: u64,
+    /// Custom file extensions and their file types
+    pub file_extensions: FileExtensions,
+}
+
+impl Default for ContentOptions {
+    fn default() -> Self {
+        Self {
+            skip_missing: false,
+            skip_hidden: true,
+            skip_gitignored: true,
+            follow_symlinks: false,
+            encoding: None,
+            stream_threshold: u64::MAX,
+            file_extensions: FileExtensions::default(),
+        }
     }
 }
 
@@ -113,6 +222,13 @@ pub struct Input {
     pub file_type_hint: Option<FileType>,
     /// Excluded paths that will be skipped when reading content
     pub excluded_paths: Option<Vec<PathBuf>>,
+    /// Tag of the input (e.g. `docs`), which is passed on to the requests of
+    /// its links (see [`Request::tag`](crate::Request::tag))
+    pub tag: Option<String>,
+    /// Base of the relative links in this input, which takes precedence over
+    /// the base of the [`Collector`](crate::Collector), see
+    /// [`Input::with_base`]
+    pub(crate) base: Option<Base>,
 }
 
 impl Input {
@@ -132,21 +248,31 @@ impl Input {
     ) -> Result<Self> {
         let source = if value == STDIN {
             InputSource::Stdin
+        } else if container::is_image_reference(value) {
+            InputSource::ContainerImage {
+                reference: value.to_owned(),
+                paths: container::DEFAULT_IMAGE_PATHS
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+            }
         } else {
             // We use [`reqwest::Url::parse`] because it catches some other edge cases that [`http::Request:builder`] does not
             // This could be improved with further refinement.
             match Url::parse(value) {
                 // Weed out non-http schemes, including Windows drive specifiers, which will be successfully parsed by the Url crate
                 Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {
-                    InputSource::RemoteUrl(Box::new(url))
+                    Self::remote_source(url)
                 }
+                Ok(url) if bucket::is_bucket_url(&url) => InputSource::Bucket(Box::new(url)),
                 Ok(_) => {
                     // URL parsed successfully, but it's not http or https
                     return Err(ErrorKind::InvalidFile(PathBuf::from(value)));
                 }
                 _ => {
-                    // this seems to be the only way to determine if this is a glob pattern
-                    let is_glob = glob::Pattern::escape(value) != value;
+                    // this seems to be the only way to determine if this is a glob pattern.
+                    // The `?` of Windows verbatim paths (e.g. `\\?\C:\docs`) is not a wildcard.
+                    let is_glob = !is_verbatim_path(value) && glob::Pattern::escape(value) != value;
 
                     if is_glob {
                         InputSource::FsGlob {
@@ -158,7 +284,9 @@ impl Input {
 
                         // On Windows, a filepath can never be mistaken for a url because Windows filepaths use \ and urls use /
                         #[cfg(windows)]
-                        if path.exists() {
+                        if path.is_file() && archive::is_archive(&path) {
+                            InputSource::Archive(path)
+                        } else if path.exists() {
                             // The file exists, so we return the path
                             InputSource::FsPath(path)
                         } else {
@@ -167,7 +295,9 @@ impl Input {
                         }
 
                         #[cfg(unix)]
-                        if path.exists() {
+                        if path.is_file() && archive::is_archive(&path) {
+                            InputSource::Archive(path)
+                        } else if path.exists() {
                             InputSource::FsPath(path)
                         } else if value.starts_with('~') || value.starts_with('.') {
                             // The path is not valid, but it might be a valid URL
@@ -184,7 +314,7 @@ impl Input {
                             let url = Url::parse(&format!("http://{value}")).map_err(|e| {
                                 ErrorKind::ParseUrl(e, "Input is not a valid URL".to_string())
                             })?;
-                            InputSource::RemoteUrl(Box::new(url))
+                            Self::remote_source(url)
                         }
                     }
                 }
@@ -194,23 +324,119 @@ impl Input {
             source,
             file_type_hint,
             excluded_paths,
+            tag: None,
+            base: None,
         })
     }
 
+    /// Construct an `Input` from `source` as it is, without a file type hint,
+    /// excluded paths, tag or base
+    #[must_use]
+    pub const fn from_source(source: InputSource) -> Self {
+        Self {
+            source,
+            file_type_hint: None,
+            excluded_paths: None,
+            tag: None,
+            base: None,
+        }
+    }
+
+    /// The base of the relative links in this input, if it has its own
+    #[must_use]
+    pub const fn base(&self) -> Option<&Base> {
+        self.base.as_ref()
+    }
+
+    /// Tag the requests of the links in this input with `tag`, e.g. to
+    /// summarize them separately from the links of other inputs
+    #[must_use]
+    pub fn with_tag(mut self, tag: Option<String>) -> Self {
+        self.tag = tag;
+        self
+    }
+
+    /// Resolve the relative links in this input against `base` instead of
+    /// the base of the collector, e.g. for one of many sites in a monorepo
+    #[must_use]
+    pub fn with_base(mut self, base: Option<Base>) -> Self {
+        self.base = base;
+        self
+    }
+
+    /// Check the files matching the glob `paths` if this input is a container
+    /// image (default: `/usr/share/doc/**`)
+    #[must_use]
+    pub fn image_paths(mut self, image_paths: &[String]) -> Self {
+        if let InputSource::ContainerImage { ref mut paths, .. } = self.source {
+            *paths = image_paths.to_vec();
+        }
+        self
+    }
+
+    /// Create the source for a remote URL; URLs which look like sitemaps
+    /// (e.g. `https://example.com/sitemap.xml`) are detected automatically
+    fn remote_source(url: Url) -> InputSource {
+        if sitemap::is_sitemap_url(&url) {
+            InputSource::Sitemap(Box::new(url))
+        } else {
+            InputSource::RemoteUrl(Box::new(url))
+        }
+    }
+
     /// Retrieve the contents from the input
     ///
+    /// Symlinks found in directories or matched by glob patterns are only
+    /// followed if [`ContentOptions::follow_symlinks`] is `true`; symlink
+    /// loops are skipped. Paths given directly as input are always followed.
+    ///
+    /// Files and stdin are decoded with [`ContentOptions::encoding`] if they
+    /// don't start with a byte order mark. Without an encoding, UTF-8 is
+    /// assumed and Windows-1252 (Latin-1) is used as fallback for invalid
+    /// UTF-8.
+    ///
+    /// Files larger than [`ContentOptions::stream_threshold`] bytes are not
+    /// read into memory; their content gets streamed during link extraction
+    /// instead (see [`InputContent::stream`]).
+    ///
+    /// The file type of files and URLs with one of the
+    /// [`ContentOptions::file_extensions`] is
+    /// taken from there; directories also include these files. The file type
+    /// of local files without an extension is guessed from their content,
+    /// unless [`FileExtensions::extensionless`] is set. Streamed files
+    /// without an extension are treated as plaintext.
+    ///
     /// # Errors
     ///
     /// Returns an error if the contents can not be retrieved
     /// because of an underlying I/O error (e.g. an error while making a
     /// network request or retrieving the contents from the file system)
-    pub fn get_contents(
-        self,
-        skip_missing: bool,
-        skip_hidden: bool,
-        skip_gitignored: bool,
-    ) -> impl Stream<Item = Result<InputContent>> {
+    /// or if a followed symlink is dangling.
+    pub fn get_contents(self, options: ContentOptions) -> impl Stream<Item = Result<InputContent>> {
+        let custom_file_types = options.file_extensions.clone();
+        self.read_contents(options).map_ok(move |mut content| {
+            let Some(path) = content.source.file_path() else {
+                return content;
+            };
+            if let Some(file_type) = custom_file_types.get(path) {
+                content.file_type = file_type;
+            } else if path.extension().is_none()
+                && matches!(
+                    content.source,
+                    InputSource::FsPath(_) | InputSource::ArchiveEntry { .. }
+                )
+            {
+                content.file_type = custom_file_types.extensionless_file_type(&content.content);
+            }
+            content
+        })
+    }
+
+    fn read_contents(self, options: ContentOptions) -> impl Stream<Item = Result<InputContent>> {
         try_stream! {
+            let skip_missing = options.skip_missing;
+            let encoding = options.encoding;
+            let stream_threshold = options.stream_threshold;
             match self.source {
                 InputSource::RemoteUrl(ref url) => {
                     let content = Self::url_contents(url).await;
@@ -220,41 +446,45 @@ impl Input {
                         Ok(content) => yield content,
                     }
                 },
+                InputSource::Sitemap(ref url) => {
+                    for await page in sitemap::page_urls(url) {
+                        let page = match page {
+                            Err(_) if skip_missing => break,
+                            page => page?,
+                        };
+                        match Self::url_contents(&page).await {
+                            Err(_) if skip_missing => (),
+                            Err(e) => Err(e)?,
+                            Ok(content) => yield content,
+                        }
+                    }
+                },
                 InputSource::FsGlob {
                     ref pattern,
                     ignore_case,
                 } => {
-                    for await content in self.glob_contents(pattern, ignore_case) {
+                    for await content in self.glob_contents(pattern, ignore_case, &options) {
                         let content = content?;
                         yield content;
                     }
                 }
                 InputSource::FsPath(ref path) => {
                     if path.is_dir() {
-                        for entry in WalkBuilder::new(path).standard_filters(skip_gitignored).hidden(skip_hidden).build() {
-                            let entry = entry?;
-
-                            if self.is_excluded_path(&entry.path().to_path_buf()) {
-                                continue;
-                            }
-
-                            match entry.file_type() {
-                                None => continue,
-                                Some(file_type) => {
-                                    if !file_type.is_file() || !valid_extension(entry.path()) {
-                                        continue;
-                                    }
-                                }
-                            };
-
-                            let content = Self::path_content(entry.path()).await?;
-                            yield content
+                        for await content in self.dir_contents(path, &options) {
+                            let content = content?;
+                            yield content;
                         }
                     } else {
                         if self.is_excluded_path(path) {
                             return;
                         }
-                        let content = Self::path_content(path).await;
+                        if is_dangling_symlink(path) {
+                            if !skip_missing {
+                                Err(ErrorKind::DanglingSymlink(path.clone()))?;
+                            }
+                            return;
+                        }
+                        let content = Self::path_content(path, encoding, stream_threshold).await;
                         match content {
                             Err(_) if skip_missing => (),
                             Err(e) => Err(e)?,
@@ -262,8 +492,73 @@ impl Input {
                         };
                     }
                 },
+                InputSource::Archive(ref archive) => {
+                    let unpacked = match archive::unpack(archive).await {
+                        Err(_) if skip_missing => return,
+                        unpacked => unpacked?,
+                    };
+                    let Some(root) = unpacked.path() else {
+                        return;
+                    };
+                    // All files of an archive are checked, and its symlinks
+                    // are never followed out of the unpacked directory
+                    let options = ContentOptions {
+                        skip_gitignored: false,
+                        follow_symlinks: false,
+                        ..options.clone()
+                    };
+                    for await content in self.dir_contents(root, &options) {
+                        yield archive_entry(content?, archive, &unpacked);
+                    }
+                },
+                InputSource::ContainerImage { ref reference, ref paths } => {
+                    let unpacked = match container::unpack(reference, paths).await {
+                        Err(_) if skip_missing => return,
+                        unpacked => unpacked?,
+                    };
+                    let Some(root) = unpacked.path() else {
+                        return;
+                    };
+                    // Documentation in images is often plain text (e.g. `README`),
+                    // so all unpacked files are checked
+                    for entry in WalkBuilder::new(root).standard_filters(false).build() {
+                        let entry = entry?;
+                        if !entry.file_type().is_some_and(|t| t.is_file()) {
+                            continue;
+                        }
+                        let content = Self::path_content(entry.path(), encoding, stream_threshold).await?;
+                        yield archive_entry(content, Path::new(reference), &unpacked);
+                    }
+                },
+                InputSource::Bucket(ref url) => {
+                    let bucket = Bucket::connect(url).await?;
+                    for await object in bucket.objects() {
+                        let object = match object {
+                            Err(_) if skip_missing => break,
+                            object => object?,
+                        };
+                        if !valid_extension(Path::new(object.path()), &options.file_extensions) {
+                            continue;
+                        }
+                        match bucket.get(&object).await {
+                            Err(_) if skip_missing => (),
+                            Err(e) => Err(e)?,
+                            Ok(bytes) => yield InputContent {
+                                file_type: FileType::from(object.path()),
+                                content: encoding::decode(&bytes, encoding, object.as_str()),
+                                source: InputSource::Bucket(Box::new(object)),
+                                stream: None,
+                            },
+                        }
+                    }
+                },
+                InputSource::ArchiveEntry { ref unpacked, .. } => {
+                    let mut content = Self::path_content(unpacked, encoding, stream_threshold).await?;
+                    content.source = self.source.clone();
+                    yield content;
+                },
                 InputSource::Stdin => {
-                    let content = Self::stdin_content(self.file_type_hint).await?;
+                    let content = Self::stdin_content(self.file_type_hint, encoding).await?;
                     yield content;
                 },
                 InputSource::String(ref s) => {
@@ -278,17 +573,27 @@ impl Input {
     /// input:
     ///
     /// - Remote URLs are returned as is, in their full form
+    /// - Sitemaps are expanded and the URL of each listed page is returned
     /// - Filepath Glob Patterns are expanded and each matched entry is returned
-    /// - Absolute or relative filepaths are returned as is
+    /// - Absolute or relative filepaths (including archives) are returned as is
+    /// - Container images are returned as given, without pulling them
+    /// - Buckets are listed and the URL of each Markdown and HTML object is
+    ///   returned
     /// - All other input types are not returned
     ///
     /// # Errors
     ///
-    /// Returns an error if the globbing fails with the expanded pattern.
+    /// Returns an error if the globbing fails with the expanded pattern
+    /// or a sitemap or bucket can't be retrieved.
     pub fn get_sources(self) -> impl Stream<Item = Result<String>> {
         try_stream! {
             match self.source {
                 InputSource::RemoteUrl(url) => yield url.to_string(),
+                InputSource::Sitemap(url) => {
+                    for await page in sitemap::page_urls(&url) {
+                        yield page?.to_string();
+                    }
+                },
                 InputSource::FsGlob { pattern, ignore_case } => {
                     let glob_expanded = tilde(&pattern).to_string();
                     let mut match_opts = glob::MatchOptions::new();
@@ -302,7 +607,18 @@ impl Input {
                         }
                     }
                 },
-                InputSource::FsPath(path) => yield path.to_string_lossy().to_string(),
+                InputSource::FsPath(path) | InputSource::Archive(path) => yield path.to_string_lossy().to_string(),
+                InputSource::ContainerImage { reference, .. } => yield reference,
+                InputSource::Bucket(url) => {
+                    let bucket = Bucket::connect(&url).await?;
+                    for await object in bucket.objects() {
+                        let object = object?;
+                        if valid_extension(Path::new(object.path()), &FileExtensions::default()) {
+                            yield object.to_string();
+                        }
+                    }
+                },
+                source @ InputSource::ArchiveEntry { .. } => yield source.to_string(),
                 InputSource::Stdin => yield "Stdin".into(),
                 InputSource::String(_) => yield "Raw String".into(),
             }
@@ -324,22 +640,68 @@ impl Input {
             source: InputSource::RemoteUrl(Box::new(url.clone())),
             file_type,
             content: res.text().await.map_err(ErrorKind::ReadResponseBody)?,
+            stream: None,
         };
 
         Ok(input_content)
     }
 
-    fn glob_contents(
-        &self,
+    /// Walk a directory and get the contents of all Markdown, HTML and notebook files
+    /// and files with custom extensions (see [`ContentOptions::file_extensions`])
+    fn dir_contents<'a>(
+        &'a self,
+        path: &'a Path,
+        options: &'a ContentOptions,
+    ) -> impl Stream<Item = Result<InputContent>> + 'a {
+        try_stream! {
+            for entry in WalkBuilder::new(path).standard_filters(options.skip_gitignored).hidden(options.skip_hidden).follow_links(options.follow_symlinks).build() {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) if is_symlink_loop(&e) => {
+                        warn!("Skipping symlink loop: {e}");
+                        continue;
+                    }
+                    Err(e) => match dangling_symlink(&e) {
+                        Some(_) if options.skip_missing => continue,
+                        Some(path) => Err(ErrorKind::DanglingSymlink(path))?,
+                        None => Err(e)?,
+                    },
+                };
+
+                if self.is_excluded_path(&entry.path().to_path_buf()) {
+                    continue;
+                }
+
+                match entry.file_type() {
+                    None => continue,
+                    Some(file_type) => {
+                        if !file_type.is_file() || !valid_extension(entry.path(), &options.file_extensions) {
+                            continue;
+                        }
+                    }
+                };
+
+                let content = Self::path_content(entry.path(), options.encoding, options.stream_threshold).await?;
+                yield content
+            }
+        }
+    }
+
+    fn glob_contents<'a>(
+        &'a self,
         pattern: &str,
         ignore_case: bool,
-    ) -> impl Stream<Item = Result<InputContent>> + '_ {
+        options: &'a ContentOptions,
+    ) -> impl Stream<Item = Result<InputContent>> + 'a {
         let glob_expanded = tilde(&pattern).to_string();
         let mut match_opts = glob::MatchOptions::new();
 
         match_opts.case_sensitive = !ignore_case;
 
         try_stream! {
+            // `**` descends into symlinked directories, so a symlink loop
+            // matches the same files over and over (until the OS gives up)
+            let mut seen = HashSet::new();
             for entry in glob_with(&glob_expanded, match_opts)? {
                 match entry {
                     Ok(path) => {
@@ -354,7 +716,21 @@ impl Input {
                         if self.is_excluded_path(&path) {
                             continue;
                         }
-                        let content: InputContent = Self::path_content(&path).await?;
+                        if path.is_symlink() {
+                            if !options.follow_symlinks {
+                                continue;
+                            }
+                            if is_dangling_symlink(&path) {
+                                if options.skip_missing {
+                                    continue;
+                                }
+                                Err(ErrorKind::DanglingSymlink(path.clone()))?;
+                            }
+                        }
+                        if !seen.insert(fs::canonicalize(&path).unwrap_or_else(|_| path.clone())) {
+                            continue;
+                        }
+                        let content: InputContent = Self::path_content(&path, options.encoding, options.stream_threshold).await?;
                         yield content;
                     }
                     Err(e) => eprintln!("{e:?}"),
@@ -371,35 +747,58 @@ impl Input {
         is_excluded_path(excluded_paths, path)
     }
 
-    /// Get the input content of a given path
+    /// Get the input content of a given path, decoded with `encoding`.
+    /// Files larger than `stream_threshold` bytes are streamed
+    /// (see [`Input::get_contents`])
+    ///
     /// # Errors
     ///
     /// Will return `Err` if file contents can't be read
     pub async fn path_content<P: Into<PathBuf> + AsRef<Path> + Clone>(
         path: P,
+        encoding: Option<&'static Encoding>,
+        stream_threshold: u64,
     ) -> Result<InputContent> {
         let path = path.into();
-        let content = tokio::fs::read_to_string(&path)
+        let metadata = tokio::fs::metadata(&path)
             .await
             .map_err(|e| ErrorKind::ReadFileInput(e, path.clone()))?;
+        if metadata.len() > stream_threshold {
+            return Ok(InputContent {
+                file_type: FileType::from(&path),
+                source: InputSource::FsPath(path.clone()),
+                content: String::new(),
+                stream: Some(path),
+            });
+        }
+        let bytes = tokio::fs::read(&path)
+            .await
+            .map_err(|e| ErrorKind::ReadFileInput(e, path.clone()))?;
+        let content = encoding::decode(&bytes, encoding, &path.to_string_lossy());
         let input_content = InputContent {
             file_type: FileType::from(&path),
             source: InputSource::FsPath(path),
             content,
+            stream: None,
         };
 
         Ok(input_content)
     }
 
-    async fn stdin_content(file_type_hint: Option<FileType>) -> Result<InputContent> {
-        let mut content = String::new();
+    async fn stdin_content(
+        file_type_hint: Option<FileType>,
+        encoding: Option<&'static Encoding>,
+    ) -> Result<InputContent> {
+        let mut bytes = Vec::new();
         let mut stdin = stdin();
-        stdin.read_to_string(&mut content).await?;
+        stdin.read_to_end(&mut bytes).await?;
+        let content = encoding::decode(&bytes, encoding, "stdin");
 
         let input_content = InputContent {
             source: InputSource::Stdin,
             file_type: file_type_hint.unwrap_or_default(),
             content,
+            stream: None,
         };
 
         Ok(input_content)
@@ -410,6 +809,62 @@ impl Input {
     }
 }
 
+/// Attribute `content` read from the `unpacked` directory of an archive or
+/// container image to the file inside of the `archive`
+fn archive_entry(
+    mut content: InputContent,
+    archive: &Path,
+    unpacked: &UnpackedDir,
+) -> InputContent {
+    if let (InputSource::FsPath(path), Some(root)) = (&content.source, unpacked.path()) {
+        content.source = InputSource::ArchiveEntry {
+            archive: archive.to_path_buf(),
+            path: path.strip_prefix(root).unwrap_or(path).to_path_buf(),
+            unpacked: path.clone(),
+            dir: unpacked.clone(),
+        };
+    }
+    content
+}
+
+/// Check if `value` is a Windows verbatim path, which is used for long paths
+fn is_verbatim_path(value: &str) -> bool {
+    cfg!(windows) && value.starts_with(r"\\?\")
+}
+
+/// Check if `path` is a symlink which points to a file that doesn't exist
+fn is_dangling_symlink(path: &Path) -> bool {
+    path.is_symlink() && !path.exists()
+}
+
+/// Check if the directory walker stopped at a symlink which points to one of
+/// its own parent directories
+fn is_symlink_loop(error: &ignore::Error) -> bool {
+    match error {
+        ignore::Error::Loop { .. } => true,
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithPath { err, .. } => {
+            is_symlink_loop(err)
+        }
+        _ => false,
+    }
+}
+
+/// Get the path of a dangling symlink the directory walker tried to follow
+fn dangling_symlink(error: &ignore::Error) -> Option<PathBuf> {
+    match error {
+        ignore::Error::WithDepth { err, .. } => dangling_symlink(err),
+        ignore::Error::WithPath { path, err }
+            if err
+                .io_error()
+                .is_some_and(|e| e.kind() == io::ErrorKind::NotFound)
+                && is_dangling_symlink(path) =>
+        {
+            Some(path.clone())
+        }
+        _ => None,
+    }
+}
+
 /// Function for path exclusion tests
 ///
 /// This is a standalone function to allow for easier testing
@@ -441,7 +896,9 @@ mod tests {
             Ok(Input {
                 source: InputSource::FsPath(PathBuf { .. }),
                 file_type_hint: None,
-                excluded_paths: None
+                excluded_paths: None,
+                tag: None,
+                base: None,
             })
         ));
     }
@@ -461,13 +918,19 @@ mod tests {
 
     #[test]
     fn test_valid_extension() {
-        assert!(valid_extension(Path::new("file.md")));
-        assert!(valid_extension(Path::new("file.markdown")));
-        assert!(valid_extension(Path::new("file.html")));
-        assert!(valid_extension(Path::new("file.htm")));
-        assert!(valid_extension(Path::new("file.HTM")));
-        assert!(!valid_extension(Path::new("file.txt")));
-        assert!(!valid_extension(Path::new("file")));
+        let defaults = FileExtensions::default();
+        assert!(valid_extension(Path::new("file.md"), &defaults));
+        assert!(valid_extension(Path::new("file.markdown"), &defaults));
+        assert!(valid_extension(Path::new("file.html"), &defaults));
+        assert!(valid_extension(Path::new("file.htm"), &defaults));
+        assert!(valid_extension(Path::new("file.HTM"), &defaults));
+        assert!(!valid_extension(Path::new("file.txt"), &defaults));
+        assert!(!valid_extension(Path::new("file"), &defaults));
+        assert!(!valid_extension(Path::new("icon.svg"), &defaults));
+
+        let custom = FileExtensions::new([("txt.j2".to_string(), FileType::Plaintext)]);
+        assert!(valid_extension(Path::new("notes.txt.j2"), &custom));
+        assert!(!valid_extension(Path::new("notes.txt"), &custom));
     }
 
     #[test]
@@ -536,6 +999,18 @@ mod tests {
         }
     }
 
+    // Ensure that a Windows verbatim path (as used for long paths) is not
+    // mistaken for a glob pattern because of the `?`
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_verbatim_path() {
+        let path = fs::canonicalize("../fixtures/windows_paths/index.md").unwrap();
+        assert!(path.to_str().unwrap().starts_with(r"\\?\"));
+
+        let input = Input::new(path.to_str().unwrap(), None, false, None).unwrap();
+        assert_eq!(input.source, InputSource::FsPath(path));
+    }
+
     #[test]
     fn test_url_scheme_check_succeeding() {
         // Valid http and https URLs
@@ -574,6 +1049,63 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_sitemap_detection() {
+        assert!(matches!(
+            Input::new("https://example.com/sitemap.xml", None, false, None),
+            Ok(Input {
+                source: InputSource::Sitemap(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            Input::new("example.com/sitemap_index.xml.gz", None, false, None),
+            Ok(Input {
+                source: InputSource::Sitemap(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            Input::new("https://example.com/feed.xml", None, false, None),
+            Ok(Input {
+                source: InputSource::RemoteUrl(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_container_image_detection() {
+        let input = Input::new("docker://alpine:3.20", None, false, None)
+            .unwrap()
+            .image_paths(&["/usr/share/licenses/**".to_string()]);
+        assert_eq!(
+            input.source,
+            InputSource::ContainerImage {
+                reference: "docker://alpine:3.20".to_string(),
+                paths: vec!["/usr/share/licenses/**".to_string()],
+            }
+        );
+        assert!(matches!(
+            Input::new("oci:./image", None, false, None),
+            Ok(Input {
+                source: InputSource::ContainerImage { .. },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_bucket_detection() {
+        for value in ["s3://docs/site/", "gs://docs", "az://docs/site"] {
+            let input = Input::new(value, None, false, None).unwrap();
+            assert_eq!(
+                input.source,
+                InputSource::Bucket(Box::new(Url::parse(value).unwrap()))
+            );
+        }
+    }
+
     #[test]
     fn test_url_scheme_check_failing() {
         // Invalid schemes
@@ -618,4 +1150,97 @@ mod tests {
             })
         ));
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlinks() {
+        use futures::StreamExt;
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.md"), "a").unwrap();
+        symlink(dir.path().join("a.md"), dir.path().join("link.md")).unwrap();
+        // Points back to the directory itself
+        symlink(dir.path(), dir.path().join("loop")).unwrap();
+
+        let sources = |follow_symlinks| {
+            let input = Input {
+                source: InputSource::FsPath(dir.path().to_path_buf()),
+                file_type_hint: None,
+                excluded_paths: None,
+                tag: None,
+                base: None,
+            };
+            input
+                .get_contents(ContentOptions {
+                    follow_symlinks,
+                    ..ContentOptions::default()
+                })
+                .map(|content| content.map(|content| content.source))
+                .collect::<Vec<_>>()
+        };
+
+        let sources_without_follow = sources(false).await;
+        assert_eq!(
+            sources_without_follow,
+            vec![Ok(InputSource::FsPath(dir.path().join("a.md")))]
+        );
+        let sources_with_follow: HashSet<_> = sources(true)
+            .await
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            sources_with_follow,
+            HashSet::from([
+                InputSource::FsPath(dir.path().join("a.md")),
+                InputSource::FsPath(dir.path().join("link.md")),
+            ])
+        );
+
+        let dangling = dir.path().join("dangling.md");
+        symlink(dir.path().join("missing.md"), &dangling).unwrap();
+        assert!(sources(true)
+            .await
+            .contains(&Err(ErrorKind::DanglingSymlink(dangling))));
+        assert!(sources(false).await.iter().all(Result::is_ok));
+    }
+
+    #[tokio::test]
+    async fn test_svg_in_directory() {
+        use futures::StreamExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.md"), "a").unwrap();
+        fs::write(dir.path().join("icon.svg"), "<svg></svg>").unwrap();
+
+        let sources = |file_extensions| {
+            let input = Input {
+                source: InputSource::FsPath(dir.path().to_path_buf()),
+                file_type_hint: None,
+                excluded_paths: None,
+                tag: None,
+                base: None,
+            };
+            input
+                .get_contents(ContentOptions {
+                    file_extensions,
+                    ..ContentOptions::default()
+                })
+                .map(|content| content.unwrap().source)
+                .collect::<HashSet<_>>()
+        };
+
+        assert_eq!(
+            sources(FileExtensions::default()).await,
+            HashSet::from([InputSource::FsPath(dir.path().join("a.md"))])
+        );
+        assert_eq!(
+            sources(FileExtensions::new([("svg".to_string(), FileType::Svg)])).await,
+            HashSet::from([
+                InputSource::FsPath(dir.path().join("a.md")),
+                InputSource::FsPath(dir.path().join("icon.svg")),
+            ])
+        );
+    }
 }
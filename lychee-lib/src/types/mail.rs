@@ -1,9 +1,25 @@
-#![cfg(all(feature = "email-check", feature = "native-tls"))]
-
+#[cfg(all(feature = "email-check", feature = "native-tls"))]
 use check_if_email_exists::{CheckEmailOutput, Reachable};
 
+/// How thoroughly mail addresses are checked. Each level includes the
+/// checks of the previous ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum MailCheckLevel {
+    /// Only check the syntax of the address
+    Syntax,
+    /// Also check that the domain of the address has MX records, i.e.
+    /// accepts mail
+    #[default]
+    Mx,
+    /// Also ask the mail server via SMTP whether the mailbox exists.
+    ///
+    /// This is slow and many mail servers block or greylist these probes.
+    Smtp,
+}
+
 /// A crude way to extract error details from the mail output.
 /// This was added because `CheckEmailOutput` doesn't impl `Display`.
+#[cfg(all(feature = "email-check", feature = "native-tls"))]
 pub(crate) fn error_from_output(o: &CheckEmailOutput) -> String {
     if let Err(_e) = o.misc.as_ref() {
         return "Error occurred connecting to this email server via SMTP".to_string();
@@ -1,32 +1,54 @@
 #![allow(unreachable_pub)]
 
 mod accept;
+mod anchor_cache;
 mod base;
 mod basic_auth;
 mod cache;
+mod content_assertion;
 mod cookies;
 mod error;
 mod file;
+mod fragment_style;
+mod generated;
+mod http_version;
 mod input;
 pub(crate) mod mail;
+mod platform;
+mod recursion;
+pub(crate) mod redirect;
+mod reference_issue;
 mod request;
 mod response;
 mod status;
 mod status_code;
+mod tls_version;
 pub(crate) mod uri;
 
 pub use accept::*;
+pub use anchor_cache::AnchorCache;
+pub(crate) use anchor_cache::ParsedFile;
 pub use base::Base;
 pub use basic_auth::{BasicAuthCredentials, BasicAuthSelector};
 pub use cache::CacheStatus;
+pub use content_assertion::ContentAssertion;
 pub use cookies::CookieJar;
 pub use error::ErrorKind;
-pub use file::FileType;
-pub use input::{Input, InputContent, InputSource};
+pub use file::{FileExtensions, FileType};
+pub use fragment_style::FragmentStyle;
+pub use generated::{GeneratedMarkers, DEFAULT_GENERATED_END, DEFAULT_GENERATED_START};
+pub use http_version::HttpVersion;
+pub use input::{ContentOptions, Input, InputContent, InputSource};
+pub use mail::MailCheckLevel;
+pub use platform::{Platform, PlatformConfig};
+pub use recursion::SameDomainPolicy;
+pub use redirect::{Redirect, Redirects};
+pub use reference_issue::{ReferenceIssue, ReferenceIssueKind};
 pub use request::Request;
 pub use response::{Response, ResponseBody};
-pub use status::Status;
+pub use status::{SchemePolicy, SkipReason, Status};
 pub use status_code::*;
+pub use tls_version::TlsVersion;
 
 /// The lychee `Result` type
 pub type Result<T> = std::result::Result<T, crate::ErrorKind>;
@@ -0,0 +1,59 @@
+use std::fmt::Display;
+
+use secrecy::SecretString;
+use serde::Deserialize;
+
+/// Code hosting platforms whose links can be checked via their API
+///
+/// Links to repositories on these platforms are checked via the API of the
+/// platform if the normal request fails, e.g. because the repository is
+/// private or the platform rate limits requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    /// GitLab, either `gitlab.com` or a self-hosted instance
+    Gitlab,
+    /// Bitbucket Cloud (`bitbucket.org`) or a self-hosted Bitbucket Data
+    /// Center instance
+    Bitbucket,
+    /// Gitea or Forgejo (e.g. `codeberg.org`)
+    #[serde(alias = "forgejo")]
+    Gitea,
+}
+
+impl Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Gitlab => write!(f, "GitLab"),
+            Self::Bitbucket => write!(f, "Bitbucket"),
+            Self::Gitea => write!(f, "Gitea"),
+        }
+    }
+}
+
+/// Platform of a host and the API token used for its links
+#[derive(Debug, Clone)]
+pub struct PlatformConfig {
+    /// The platform running on the host
+    pub platform: Platform,
+    /// API token, required for links to private repositories
+    pub token: Option<SecretString>,
+}
+
+impl PlatformConfig {
+    /// Create a config for `platform` without a token
+    #[must_use]
+    pub const fn new(platform: Platform) -> Self {
+        Self {
+            platform,
+            token: None,
+        }
+    }
+
+    /// Use `token` for requests to the API
+    #[must_use]
+    pub fn with_token(mut self, token: SecretString) -> Self {
+        self.token = Some(token);
+        self
+    }
+}
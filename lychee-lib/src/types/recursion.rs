@@ -0,0 +1,66 @@
+use reqwest::Url;
+
+/// Decides which discovered pages are followed when recursively crawling
+/// a website, relative to the URL of the input the crawl started from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SameDomainPolicy {
+    /// Only follow pages on exactly the same host as the input
+    #[default]
+    SameHost,
+    /// Also follow pages on subdomains of the input's host.
+    ///
+    /// A leading `www.` of the input's host is ignored, so crawling
+    /// `https://www.example.com` also follows `https://blog.example.com`.
+    IncludeSubdomains,
+}
+
+impl SameDomainPolicy {
+    /// Returns `true` if `url` belongs to the same site as `root`
+    #[must_use]
+    pub fn matches(self, root: &Url, url: &Url) -> bool {
+        let (Some(root_host), Some(host)) = (root.host_str(), url.host_str()) else {
+            return false;
+        };
+        match self {
+            Self::SameHost => root_host == host && root.port() == url.port(),
+            Self::IncludeSubdomains => {
+                let domain = root_host.strip_prefix("www.").unwrap_or(root_host);
+                host == domain
+                    || host
+                        .strip_suffix(domain)
+                        .is_some_and(|subdomain| subdomain.ends_with('.'))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> Url {
+        Url::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_same_host() {
+        let root = url("https://example.com/docs/");
+        let policy = SameDomainPolicy::SameHost;
+        assert!(policy.matches(&root, &url("https://example.com/about")));
+        assert!(policy.matches(&root, &url("http://example.com/")));
+        assert!(!policy.matches(&root, &url("https://example.com:8080/")));
+        assert!(!policy.matches(&root, &url("https://blog.example.com/")));
+        assert!(!policy.matches(&root, &url("mailto:user@example.com")));
+    }
+
+    #[test]
+    fn test_include_subdomains() {
+        let root = url("https://www.example.com/");
+        let policy = SameDomainPolicy::IncludeSubdomains;
+        assert!(policy.matches(&root, &url("https://example.com/")));
+        assert!(policy.matches(&root, &url("https://www.example.com/")));
+        assert!(policy.matches(&root, &url("https://blog.example.com/")));
+        assert!(!policy.matches(&root, &url("https://notexample.com/")));
+        assert!(!policy.matches(&root, &url("https://example.org/")));
+    }
+}
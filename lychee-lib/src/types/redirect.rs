@@ -0,0 +1,192 @@
+use std::{cell::RefCell, fmt::Display, future::Future};
+
+use http::StatusCode;
+use reqwest::{redirect::Attempt, Url};
+use serde::Serialize;
+
+/// A single response of a redirect chain
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize)]
+pub struct Redirect {
+    /// Status code of the response
+    #[serde(serialize_with = "serialize_code")]
+    pub code: StatusCode,
+    /// URL the response was retrieved from
+    pub url: Url,
+}
+
+impl Display for Redirect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.code.as_str(), self.url)
+    }
+}
+
+fn serialize_code<S>(code: &StatusCode, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_u16(code.as_u16())
+}
+
+/// The chain of responses of a request which was redirected, from the
+/// requested URL to the final response, e.g. `301 a -> 302 b -> 200 c`
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq, Serialize)]
+pub struct Redirects(Vec<Redirect>);
+
+impl Redirects {
+    /// Create a redirect chain from its responses
+    #[must_use]
+    pub const fn new(redirects: Vec<Redirect>) -> Self {
+        Self(redirects)
+    }
+
+    /// Returns `true` if the chain contains no responses
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The URL which permanently replaces the requested URL, i.e. the target
+    /// of the permanent redirects (301 and 308) at the start of the chain
+    #[must_use]
+    pub fn permanent_target(&self) -> Option<&Url> {
+        let permanent = self
+            .0
+            .iter()
+            .take_while(|redirect| {
+                matches!(
+                    redirect.code,
+                    StatusCode::MOVED_PERMANENTLY | StatusCode::PERMANENT_REDIRECT
+                )
+            })
+            .count();
+        if permanent == 0 {
+            return None;
+        }
+        self.0.get(permanent).map(|redirect| &redirect.url)
+    }
+}
+
+impl Display for Redirects {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, redirect) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " -> ")?;
+            }
+            write!(f, "{redirect}")?;
+        }
+        Ok(())
+    }
+}
+
+tokio::task_local! {
+    /// Redirects followed by the request which is tracked by the current
+    /// task (see [`track`])
+    static REDIRECTS: RefCell<Vec<Redirect>>;
+
+    /// Set while the current task sends a request whose redirects must not
+    /// be followed (see [`without_following`])
+    static STOPPED: ();
+
+    /// Host which the redirects of the request sent by the current task must
+    /// not leave (see [`within_host`])
+    static HOST: String;
+}
+
+/// Record the redirect response of `attempt` for the request which is
+/// tracked by the current task, if any.
+///
+/// The redirect policy of `reqwest` is shared by all requests of a client,
+/// but it is called while the response future is polled, i.e. by the task
+/// which sent the request.
+pub(crate) fn record(attempt: &Attempt<'_>) {
+    let Some(url) = attempt.previous().last() else {
+        return;
+    };
+    push(Redirect {
+        code: attempt.status(),
+        url: url.clone(),
+    });
+}
+
+/// Record a redirect for the request which is tracked by the current task,
+/// e.g. one which was followed manually
+pub(crate) fn push(redirect: Redirect) {
+    // Requests which aren't tracked are ignored
+    let _ = REDIRECTS.try_with(|redirects| redirects.borrow_mut().push(redirect));
+}
+
+/// Await the response of a request and return it together with the
+/// redirects which were followed to get it
+pub(crate) async fn track<F: Future>(response: F) -> (F::Output, Vec<Redirect>) {
+    REDIRECTS
+        .scope(RefCell::new(Vec::new()), async move {
+            let response = response.await;
+            (response, REDIRECTS.with(RefCell::take))
+        })
+        .await
+}
+
+/// Returns `true` if the redirect of `attempt` must not be followed for the
+/// request which is sent by the current task
+pub(crate) fn is_stopped(attempt: &Attempt<'_>) -> bool {
+    STOPPED.try_with(|()| ()).is_ok()
+        || HOST
+            .try_with(|host| attempt.url().host_str() != Some(host.as_str()))
+            .unwrap_or(false)
+}
+
+/// Await the response of a request without following its redirects, e.g.
+/// of a request with credentials which must not reach another URL. The
+/// redirect response itself is returned instead.
+pub(crate) async fn without_following<F: Future>(response: F) -> F::Output {
+    STOPPED.scope((), response).await
+}
+
+/// Await the response of a request, following only the redirects within
+/// `host`. The response of the first redirect to another host is returned
+/// instead, e.g. so that headers which are only meant for `host` can be
+/// removed before following it.
+pub(crate) async fn within_host<F: Future>(host: String, response: F) -> F::Output {
+    HOST.scope(host, response).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let redirects = Redirects::new(vec![
+            Redirect {
+                code: StatusCode::MOVED_PERMANENTLY,
+                url: Url::parse("http://example.com/a").unwrap(),
+            },
+            Redirect {
+                code: StatusCode::FOUND,
+                url: Url::parse("https://example.com/a").unwrap(),
+            },
+            Redirect {
+                code: StatusCode::OK,
+                url: Url::parse("https://example.com/b").unwrap(),
+            },
+        ]);
+        assert_eq!(
+            redirects.to_string(),
+            "301 http://example.com/a -> 302 https://example.com/a -> 200 https://example.com/b"
+        );
+        // Only the permanent redirect at the start of the chain counts
+        assert_eq!(
+            redirects.permanent_target().unwrap().as_str(),
+            "https://example.com/a"
+        );
+        assert_eq!(
+            serde_json::to_string(&Redirects::new(vec![Redirect {
+                code: StatusCode::OK,
+                url: Url::parse("https://example.com/").unwrap(),
+            }]))
+            .unwrap(),
+            r#"[{"code":200,"url":"https://example.com/"}]"#
+        );
+        assert_eq!(Redirects::default().permanent_target(), None);
+    }
+}
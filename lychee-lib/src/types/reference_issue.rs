@@ -0,0 +1,54 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use super::uri::raw::RawUriSpan;
+
+/// The problem with a reference-style link or footnote of a Markdown
+/// document (see [`ReferenceIssue`])
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(tag = "kind", content = "label", rename_all = "snake_case")]
+pub enum ReferenceIssueKind {
+    /// A link like `[text][label]` or `[label][]` refers to a label which
+    /// has no definition
+    UndefinedReference(String),
+    /// A link reference definition like `[label]: https://example.com` is
+    /// never referenced
+    UnusedDefinition(String),
+    /// A footnote reference like `[^label]` has no definition
+    UndefinedFootnote(String),
+    /// A footnote definition like `[^label]: Text` is never referenced
+    UnusedFootnote(String),
+}
+
+impl Display for ReferenceIssueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UndefinedReference(label) => write!(f, "Undefined reference `[{label}]`"),
+            Self::UnusedDefinition(label) => write!(f, "Unused link definition `[{label}]`"),
+            Self::UndefinedFootnote(label) => write!(f, "Undefined footnote `[^{label}]`"),
+            Self::UnusedFootnote(label) => write!(f, "Unused footnote `[^{label}]`"),
+        }
+    }
+}
+
+/// An undefined reference or an unused definition of a reference-style link
+/// or footnote in a Markdown document
+///
+/// These are errors of the document itself rather than of a link, so they
+/// are collected per input by the [`Collector`](crate::Collector) if
+/// [`check_references`](crate::Collector::check_references) is enabled.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct ReferenceIssue {
+    /// What is wrong with the reference or definition
+    #[serde(flatten)]
+    pub kind: ReferenceIssueKind,
+    /// Position of the reference or definition inside of the document
+    pub span: RawUriSpan,
+}
+
+impl Display for ReferenceIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} | {}", self.span, self.kind)
+    }
+}
@@ -1,11 +1,15 @@
-use std::{convert::TryFrom, fmt::Display};
+use std::{
+    convert::TryFrom,
+    fmt::Display,
+    hash::{Hash, Hasher},
+};
 
 use crate::{BasicAuthCredentials, ErrorKind, Uri};
 
-use super::InputSource;
+use super::{uri::raw::RawUriSpan, InputSource};
 
 /// A request type that can be handle by lychee
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, Clone)]
 pub struct Request {
     /// A valid Uniform Resource Identifier of a given endpoint, which can be
     /// checked with lychee
@@ -24,6 +28,22 @@ pub struct Request {
 
     /// Basic auth credentials
     pub credentials: Option<BasicAuthCredentials>,
+
+    /// Position of the URI inside of the source, if known.
+    /// This is not taken into account when comparing requests,
+    /// so that the same link is only checked once per source.
+    pub span: Option<RawUriSpan>,
+
+    /// Whether the URI is inside a region of its source which is generated
+    /// by another tool (see [`GeneratedMarkers`](crate::GeneratedMarkers)).
+    /// Like the span, this is not taken into account when comparing requests.
+    pub generated: bool,
+
+    /// Tag of the input which contained the URI (see
+    /// [`Input::tag`](crate::Input::tag)), e.g. to apply different policies
+    /// to the links of different inputs.
+    /// This is not taken into account when comparing requests either.
+    pub tag: Option<String>,
 }
 
 impl Request {
@@ -43,8 +63,49 @@ impl Request {
             element,
             attribute,
             credentials,
+            span: None,
+            generated: false,
+            tag: None,
         }
     }
+
+    /// Set the position of the URI inside of the source
+    #[inline]
+    #[must_use]
+    pub const fn with_span(mut self, span: Option<RawUriSpan>) -> Self {
+        self.span = span;
+        self
+    }
+
+    /// Set the tag of the input which contained the URI
+    #[inline]
+    #[must_use]
+    pub fn with_tag(mut self, tag: Option<String>) -> Self {
+        self.tag = tag;
+        self
+    }
+}
+
+impl PartialEq for Request {
+    fn eq(&self, other: &Self) -> bool {
+        self.uri == other.uri
+            && self.source == other.source
+            && self.element == other.element
+            && self.attribute == other.attribute
+            && self.credentials == other.credentials
+    }
+}
+
+impl Eq for Request {}
+
+impl Hash for Request {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.uri.hash(state);
+        self.source.hash(state);
+        self.element.hash(state);
+        self.attribute.hash(state);
+        self.credentials.hash(state);
+    }
 }
 
 impl Display for Request {
@@ -3,32 +3,117 @@ use std::fmt::Display;
 use http::StatusCode;
 use serde::Serialize;
 
-use crate::{InputSource, Status, Uri};
+use crate::{types::uri::raw::RawUriSpan, InputSource, Request, Status, Uri};
 
 /// Response type returned by lychee after checking a URI
-//
-// Body is public to allow inserting into stats maps (error_map, success_map,
-// etc.) without `Clone`, because the inner `ErrorKind` in `response.status` is
-// not `Clone`. Use `body()` to access the body in the rest of the code.
-//
-// `pub(crate)` is insufficient, because the `stats` module is in the `bin`
-// crate crate.
 #[derive(Debug)]
-pub struct Response(InputSource, pub ResponseBody);
+pub struct Response {
+    /// The resource which contained the checked URI
+    source: InputSource,
+    // The body is public to allow inserting into stats maps (error_map,
+    // success_map, etc.) without `Clone`, because the inner `ErrorKind` in
+    // `response.status` is not `Clone`. Use `body()` to access the body in the
+    // rest of the code.
+    //
+    // `pub(crate)` is insufficient, because the `stats` module is in the `bin`
+    // crate.
+    /// The checked URI and its status
+    pub body: ResponseBody,
+    /// Whether the URI is inside a generated region of its source, see
+    /// [`Request::generated`]
+    generated: bool,
+    /// The tag of the input which contained the URI, see [`Request::tag`]
+    tag: Option<String>,
+}
 
 impl Response {
     #[inline]
     #[must_use]
     /// Create new response
     pub const fn new(uri: Uri, status: Status, source: InputSource) -> Self {
-        Response(source, ResponseBody { uri, status })
+        Response {
+            source,
+            body: ResponseBody {
+                uri,
+                status,
+                span: None,
+                label: None,
+            },
+            generated: false,
+            tag: None,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Create the response to `request`, which keeps its source, span,
+    /// generated flag and tag
+    pub fn from_request(request: Request, status: Status) -> Self {
+        Response {
+            source: request.source,
+            body: ResponseBody {
+                uri: request.uri,
+                status,
+                span: request.span,
+                label: None,
+            },
+            generated: request.generated,
+            tag: request.tag,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set the position of the checked URI inside of its source
+    pub const fn with_span(mut self, span: Option<RawUriSpan>) -> Self {
+        self.body.span = span;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set a custom label of the status, which is reported in place of the
+    /// status text, e.g. `requires VPN` for `403`
+    pub fn with_label(mut self, label: Option<String>) -> Self {
+        self.body.label = label;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Mark the checked URI as being inside a generated region of its source
+    pub const fn with_generated(mut self, generated: bool) -> Self {
+        self.generated = generated;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Whether the checked URI is inside a generated region of its source
+    pub const fn is_generated(&self) -> bool {
+        self.generated
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set the tag of the input which contained the checked URI
+    pub fn with_tag(mut self, tag: Option<String>) -> Self {
+        self.tag = tag;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// The tag of the input which contained the checked URI, if any
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
     }
 
     #[inline]
     #[must_use]
     /// Retrieve the underlying status of the response
     pub const fn status(&self) -> &Status {
-        &self.1.status
+        &self.body.status
     }
 
     #[inline]
@@ -36,20 +121,20 @@ impl Response {
     /// Retrieve the underlying source of the response
     /// (e.g. the input file or the URL)
     pub const fn source(&self) -> &InputSource {
-        &self.0
+        &self.source
     }
 
     #[inline]
     #[must_use]
     /// Retrieve the underlying body of the response
     pub const fn body(&self) -> &ResponseBody {
-        &self.1
+        &self.body
     }
 }
 
 impl Display for Response {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        <ResponseBody as Display>::fmt(&self.1, f)
+        <ResponseBody as Display>::fmt(&self.body, f)
     }
 }
 
@@ -58,7 +143,7 @@ impl Serialize for Response {
     where
         S: serde::Serializer,
     {
-        <ResponseBody as Serialize>::serialize(&self.1, s)
+        <ResponseBody as Serialize>::serialize(&self.body, s)
     }
 }
 
@@ -71,6 +156,13 @@ pub struct ResponseBody {
     pub uri: Uri,
     /// The status of the check
     pub status: Status,
+    /// The position of the URI inside of its source (e.g. `42:17`), if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span: Option<RawUriSpan>,
+    /// A custom label of the status (e.g. `requires VPN`), which is reported
+    /// in place of the status text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
 }
 
 // Extract as much information from the underlying error conditions as possible
@@ -83,24 +175,23 @@ impl Display for ResponseBody {
         write!(f, "{}", self.uri)?;
 
         // Early return for OK status to avoid verbose output
-        if matches!(self.status, Status::Ok(StatusCode::OK)) {
+        if self.label.is_none() && matches!(self.status, Status::Ok(StatusCode::OK)) {
             return Ok(());
         }
 
-        // Format status and return early if empty
+        // Add details to the status if available
         let status_output = self.status.to_string();
-        if status_output.is_empty() {
-            return Ok(());
-        }
-
-        // Write status with separator
-        write!(f, " | {status_output}")?;
+        let status_output = match self.status.details() {
+            Some(details) if !status_output.is_empty() => format!("{status_output}: {details}"),
+            _ => status_output,
+        };
 
-        // Add details if available
-        if let Some(details) = self.status.details() {
-            write!(f, ": {details}")
-        } else {
-            Ok(())
+        // Write the label or the status with separator, if any
+        match &self.label {
+            Some(label) if status_output.is_empty() => write!(f, " | {label}"),
+            Some(label) => write!(f, " | {label} ({status_output})"),
+            None if status_output.is_empty() => Ok(()),
+            None => write!(f, " | {status_output}"),
         }
     }
 }
@@ -1,13 +1,13 @@
 use std::{collections::HashSet, fmt::Display};
 
 use http::StatusCode;
-use reqwest::Response;
+use reqwest::{Response, Url};
 use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
 
 use crate::ErrorKind;
 
-use super::CacheStatus;
+use super::{CacheStatus, Redirects};
 
 const ICON_OK: &str = "\u{2714}"; // ✔
 const ICON_REDIRECTED: &str = "\u{21c4}"; // ⇄
@@ -17,6 +17,50 @@ const ICON_UNKNOWN: &str = "\u{003f}"; // ?
 const ICON_ERROR: &str = "\u{2717}"; // ✗
 const ICON_TIMEOUT: &str = "\u{29d6}"; // ⧖
 const ICON_CACHED: &str = "\u{21bb}"; // ↻
+const ICON_SKIPPED: &str = "\u{23e9}"; // ⏩
+
+/// Reason why a link was not checked, see [`Status::Skipped`]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The deadline of the run (`--max-runtime`) was reached before the link
+    /// could be checked
+    Deadline,
+    /// The host has more distinct URLs than are sampled per host
+    /// (`--sample-per-host`) and the link is not in the sample
+    Sampled,
+}
+
+impl Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::Deadline => f.write_str("deadline reached"),
+            SkipReason::Sampled => f.write_str("sampled"),
+        }
+    }
+}
+
+/// Reason why a link was excluded because of its scheme, see
+/// [`Status::ExcludedScheme`]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum SchemePolicy {
+    /// `data:` and `javascript:` links contain the resource (or code) itself,
+    /// so there is nothing to check
+    Inline(String),
+    /// The scheme is denied, e.g. with `--deny-scheme`
+    Denied(String),
+    /// The scheme is not one of the allowed schemes, e.g. of `--allow-scheme`
+    NotAllowed(String),
+}
+
+impl Display for SchemePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemePolicy::Inline(scheme) => write!(f, "{scheme}: links are never checked"),
+            SchemePolicy::Denied(scheme) => write!(f, "{scheme}: links are denied"),
+            SchemePolicy::NotAllowed(scheme) => write!(f, "{scheme}: links are not allowed"),
+        }
+    }
+}
 
 /// Response status of the request.
 #[allow(variant_size_differences)]
@@ -29,31 +73,55 @@ pub enum Status {
     /// Request timed out
     Timeout(Option<StatusCode>),
     /// Got redirected to different resource
-    Redirected(StatusCode),
+    ///
+    /// The status code is the one of the final response. If the redirects
+    /// were followed and tracked (see `ClientBuilder::track_redirects`), the
+    /// redirect chain leads from the requested URL to the final response.
+    /// Otherwise, it is empty and the status code is the redirect itself.
+    Redirected(StatusCode, Redirects),
     /// The given status code is not known by lychee
     UnknownStatusCode(StatusCode),
     /// Resource was excluded from checking
     Excluded,
+    /// Resource was excluded from checking because of its scheme, e.g. for
+    /// `javascript:` links
+    ExcludedScheme(SchemePolicy),
     /// The request type is currently not supported,
     /// for example when the URL scheme is `slack://`.
     /// See <https://github.com/lycheeverse/lychee/issues/199>
     Unsupported(ErrorKind),
-    /// Cached request status from previous run
-    Cached(CacheStatus),
+    /// Cached request status from previous run (or from another input of the
+    /// same run), with the details of a failure, e.g. the error message
+    Cached(CacheStatus, Option<String>),
+    /// The page was retrieved successfully, but from a different URL than
+    /// its canonical URL (`<link rel="canonical">`), e.g. after a redirect
+    CanonicalMismatch {
+        /// Status code of the response
+        code: StatusCode,
+        /// URL the page was retrieved from, after following redirects
+        url: Box<Url>,
+        /// Canonical URL declared by the page
+        canonical: Box<Url>,
+    },
+    /// The link was not checked, e.g. because the run ran out of time
+    Skipped(SkipReason),
 }
 
 impl Display for Status {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Status::Ok(code) => write!(f, "{code}"),
-            Status::Redirected(code) => write!(f, "Redirect ({code})"),
+            Status::Redirected(code, _) => write!(f, "Redirect ({code})"),
             Status::UnknownStatusCode(code) => write!(f, "Unknown status ({code})"),
             Status::Timeout(Some(code)) => write!(f, "Timeout ({code})"),
             Status::Timeout(None) => f.write_str("Timeout"),
             Status::Unsupported(e) => write!(f, "Unsupported: {e}"),
             Status::Error(e) => write!(f, "{e}"),
-            Status::Cached(status) => write!(f, "{status}"),
+            Status::Cached(status, _) => write!(f, "{status}"),
+            Status::CanonicalMismatch { .. } => f.write_str("Canonical mismatch"),
+            Status::Skipped(reason) => write!(f, "Skipped ({reason})"),
             Status::Excluded => Ok(()),
+            Status::ExcludedScheme(policy) => write!(f, "Excluded ({policy})"),
         }
     }
 }
@@ -64,7 +132,14 @@ impl Serialize for Status {
         S: Serializer,
     {
         let mut s;
-        if let Some(code) = self.code() {
+        if let Status::Redirected(code, redirects) = self {
+            s = serializer.serialize_struct("Status", 3)?;
+            s.serialize_field("text", &self.to_string())?;
+            s.serialize_field("code", &code.as_u16())?;
+            if !redirects.is_empty() {
+                s.serialize_field("redirects", redirects)?;
+            }
+        } else if let Some(code) = self.code() {
             s = serializer.serialize_struct("Status", 2)?;
             s.serialize_field("text", &self.to_string())?;
             s.serialize_field("code", &code.as_u16())?;
@@ -91,7 +166,7 @@ impl Status {
         } else {
             match response.error_for_status_ref() {
                 Ok(_) if code.is_success() => Self::Ok(code),
-                Ok(_) if code.is_redirection() => Self::Redirected(code),
+                Ok(_) if code.is_redirection() => Self::Redirected(code, Redirects::default()),
                 Ok(_) => Self::UnknownStatusCode(code),
                 Err(e) => e.into(),
             }
@@ -112,19 +187,32 @@ impl Status {
         match s {
             CacheStatus::Ok(code) => {
                 if matches!(s, CacheStatus::Ok(_)) || accepted.contains(&code) {
-                    return Self::Cached(CacheStatus::Ok(code));
+                    return Self::Cached(CacheStatus::Ok(code), None);
                 };
-                Self::Cached(CacheStatus::Error(Some(code)))
+                Self::Cached(CacheStatus::Error(Some(code)), None)
             }
             CacheStatus::Error(code) => {
                 if let Some(code) = code {
                     if accepted.contains(&code) {
-                        return Self::Cached(CacheStatus::Ok(code));
+                        return Self::Cached(CacheStatus::Ok(code), None);
                     };
                 }
-                Self::Cached(CacheStatus::Error(code))
+                Self::Cached(CacheStatus::Error(code), None)
             }
-            _ => Self::Cached(s),
+            _ => Self::Cached(s, None),
+        }
+    }
+
+    /// Keep the `details` of a cached failure, e.g. the error message of the
+    /// request which was cached.
+    ///
+    /// Other statuses are returned unchanged; in particular, a failure which
+    /// is accepted in the current run has no details.
+    #[must_use]
+    pub fn with_cached_details(self, details: Option<String>) -> Self {
+        match self {
+            Self::Cached(status @ CacheStatus::Error(_), _) => Self::Cached(status, details),
+            status => status,
         }
     }
 
@@ -140,21 +228,33 @@ impl Status {
     pub fn details(&self) -> Option<String> {
         match &self {
             Status::Ok(code) => code.canonical_reason().map(String::from),
-            Status::Redirected(code) => code.canonical_reason().map(String::from),
+            Status::Redirected(code, redirects) if redirects.is_empty() => {
+                code.canonical_reason().map(String::from)
+            }
+            Status::Redirected(_, redirects) => Some(redirects.to_string()),
             Status::Error(e) => e.details(),
             Status::Timeout(_) => None,
             Status::UnknownStatusCode(_) => None,
             Status::Unsupported(_) => None,
-            Status::Cached(_) => None,
-            Status::Excluded => None,
+            Status::Cached(_, details) => details.clone(),
+            Status::CanonicalMismatch { url, canonical, .. } => Some(format!(
+                "Retrieved from {url}, but the canonical URL is {canonical}"
+            )),
+            Status::Skipped(_) => None,
+            Status::Excluded | Status::ExcludedScheme(_) => None,
         }
     }
 
     #[inline]
     #[must_use]
     /// Returns `true` if the check was successful
+    ///
+    /// Redirects are successful if they were followed to a successful response.
     pub const fn is_success(&self) -> bool {
-        matches!(self, Status::Ok(_) | Status::Cached(CacheStatus::Ok(_)))
+        matches!(
+            self,
+            Status::Ok(_) | Status::Cached(CacheStatus::Ok(_) | CacheStatus::Redirected { .. }, _)
+        ) || matches!(self, Status::Redirected(code, _) if code.as_u16() >= 200 && code.as_u16() < 300)
     }
 
     #[inline]
@@ -163,7 +263,10 @@ impl Status {
     pub const fn is_error(&self) -> bool {
         matches!(
             self,
-            Status::Error(_) | Status::Cached(CacheStatus::Error(_)) | Status::Timeout(_)
+            Status::Error(_)
+                | Status::Cached(CacheStatus::Error(_), _)
+                | Status::Timeout(_)
+                | Status::CanonicalMismatch { .. }
         )
     }
 
@@ -173,10 +276,17 @@ impl Status {
     pub const fn is_excluded(&self) -> bool {
         matches!(
             self,
-            Status::Excluded | Status::Cached(CacheStatus::Excluded)
+            Status::Excluded | Status::ExcludedScheme(_) | Status::Cached(CacheStatus::Excluded, _)
         )
     }
 
+    #[inline]
+    #[must_use]
+    /// Returns `true` if the link was not checked
+    pub const fn is_skipped(&self) -> bool {
+        matches!(self, Status::Skipped(_))
+    }
+
     #[inline]
     #[must_use]
     /// Returns `true` if a check took too long to complete
@@ -190,7 +300,7 @@ impl Status {
     pub const fn is_unsupported(&self) -> bool {
         matches!(
             self,
-            Status::Unsupported(_) | Status::Cached(CacheStatus::Unsupported)
+            Status::Unsupported(_) | Status::Cached(CacheStatus::Unsupported, _)
         )
     }
 
@@ -199,13 +309,14 @@ impl Status {
     pub const fn icon(&self) -> &str {
         match self {
             Status::Ok(_) => ICON_OK,
-            Status::Redirected(_) => ICON_REDIRECTED,
+            Status::Redirected(..) => ICON_REDIRECTED,
             Status::UnknownStatusCode(_) => ICON_UNKNOWN,
-            Status::Excluded => ICON_EXCLUDED,
-            Status::Error(_) => ICON_ERROR,
+            Status::Excluded | Status::ExcludedScheme(_) => ICON_EXCLUDED,
+            Status::Error(_) | Status::CanonicalMismatch { .. } => ICON_ERROR,
             Status::Timeout(_) => ICON_TIMEOUT,
             Status::Unsupported(_) => ICON_UNSUPPORTED,
-            Status::Cached(_) => ICON_CACHED,
+            Status::Cached(..) => ICON_CACHED,
+            Status::Skipped(_) => ICON_SKIPPED,
         }
     }
 
@@ -214,9 +325,15 @@ impl Status {
     pub fn code(&self) -> Option<StatusCode> {
         match self {
             Status::Ok(code)
-            | Status::Redirected(code)
+            | Status::Redirected(code, _)
             | Status::UnknownStatusCode(code)
-            | Status::Timeout(Some(code)) => Some(*code),
+            | Status::Timeout(Some(code))
+            | Status::CanonicalMismatch { code, .. }
+            | Status::Error(
+                ErrorKind::OverriddenStatusCode(code)
+                | ErrorKind::RenderedStatusCode(code)
+                | ErrorKind::ContentAssertion(code, _),
+            ) => Some(*code),
             Status::Error(kind) | Status::Unsupported(kind) => {
                 if let Some(error) = kind.reqwest_error() {
                     error.status()
@@ -224,12 +341,15 @@ impl Status {
                     None
                 }
             }
-            Status::Cached(CacheStatus::Ok(code) | CacheStatus::Error(Some(code))) => {
-                match StatusCode::from_u16(*code) {
-                    Ok(code) => Some(code),
-                    Err(_) => None,
-                }
-            }
+            Status::Cached(
+                CacheStatus::Ok(code)
+                | CacheStatus::Error(Some(code))
+                | CacheStatus::Redirected { code, .. },
+                _,
+            ) => match StatusCode::from_u16(*code) {
+                Ok(code) => Some(code),
+                Err(_) => None,
+            },
             _ => None,
         }
     }
@@ -238,12 +358,14 @@ impl Status {
     #[must_use]
     pub fn code_as_string(&self) -> String {
         match self {
-            Status::Ok(code) | Status::Redirected(code) | Status::UnknownStatusCode(code) => {
-                code.as_str().to_string()
-            }
-            Status::Excluded => "EXCLUDED".to_string(),
+            Status::Ok(code)
+            | Status::Redirected(code, _)
+            | Status::UnknownStatusCode(code)
+            | Status::CanonicalMismatch { code, .. } => code.as_str().to_string(),
+            Status::Excluded | Status::ExcludedScheme(_) => "EXCLUDED".to_string(),
             Status::Error(e) => match e {
                 ErrorKind::NetworkRequest(e)
+                | ErrorKind::ErrorPage(e, _)
                 | ErrorKind::ReadResponseBody(e)
                 | ErrorKind::BuildRequestClient(e) => match e.status() {
                     Some(code) => code.as_str().to_string(),
@@ -256,8 +378,9 @@ impl Status {
                 None => "TIMEOUT".to_string(),
             },
             Status::Unsupported(_) => "IGNORED".to_string(),
-            Status::Cached(cache_status) => match cache_status {
-                CacheStatus::Ok(code) => code.to_string(),
+            Status::Skipped(_) => "SKIPPED".to_string(),
+            Status::Cached(cache_status, _) => match cache_status {
+                CacheStatus::Ok(code) | CacheStatus::Redirected { code, .. } => code.to_string(),
                 CacheStatus::Error(code) => match code {
                     Some(code) => code.to_string(),
                     None => "ERROR".to_string(),
@@ -303,7 +426,7 @@ impl From<reqwest::Error> for Status {
 
 #[cfg(test)]
 mod tests {
-    use crate::{CacheStatus, ErrorKind, Status};
+    use crate::{CacheStatus, ErrorKind, Redirect, Redirects, Status};
     use http::StatusCode;
 
     #[test]
@@ -338,20 +461,23 @@ mod tests {
             999
         );
         assert_eq!(
-            Status::Redirected(StatusCode::from_u16(300).unwrap())
+            Status::Redirected(StatusCode::from_u16(300).unwrap(), Redirects::default())
                 .code()
                 .unwrap(),
             300
         );
-        assert_eq!(Status::Cached(CacheStatus::Ok(200)).code().unwrap(), 200);
         assert_eq!(
-            Status::Cached(CacheStatus::Error(Some(404)))
+            Status::Cached(CacheStatus::Ok(200), None).code().unwrap(),
+            200
+        );
+        assert_eq!(
+            Status::Cached(CacheStatus::Error(Some(404)), None)
                 .code()
                 .unwrap(),
             404
         );
         assert_eq!(Status::Timeout(None).code(), None);
-        assert_eq!(Status::Cached(CacheStatus::Error(None)).code(), None);
+        assert_eq!(Status::Cached(CacheStatus::Error(None), None).code(), None);
         assert_eq!(Status::Excluded.code(), None);
         assert_eq!(
             Status::Unsupported(ErrorKind::InvalidStatusCode(999)).code(),
@@ -359,6 +485,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_canonical_mismatch() {
+        let status = Status::CanonicalMismatch {
+            code: StatusCode::OK,
+            url: Box::new("https://example.com/old/".parse().unwrap()),
+            canonical: Box::new("https://example.com/new/".parse().unwrap()),
+        };
+        assert!(status.is_error());
+        assert_eq!(status.code(), Some(StatusCode::OK));
+        assert_eq!(
+            serde_json::to_string(&status).unwrap(),
+            r#"{"text":"Canonical mismatch","code":200}"#
+        );
+        assert_eq!(
+            status.details().unwrap(),
+            "Retrieved from https://example.com/old/, but the canonical URL is https://example.com/new/"
+        );
+    }
+
+    #[test]
+    fn test_redirected() {
+        let redirects = Redirects::new(vec![
+            Redirect {
+                code: StatusCode::MOVED_PERMANENTLY,
+                url: "http://example.com/".parse().unwrap(),
+            },
+            Redirect {
+                code: StatusCode::OK,
+                url: "https://example.com/".parse().unwrap(),
+            },
+        ]);
+        let status = Status::Redirected(StatusCode::OK, redirects);
+        assert!(status.is_success());
+        assert_eq!(
+            status.details().unwrap(),
+            "301 http://example.com/ -> 200 https://example.com/"
+        );
+        assert_eq!(
+            serde_json::to_string(&status).unwrap(),
+            r#"{"text":"Redirect (200 OK)","code":200,"redirects":[{"code":301,"url":"http://example.com/"},{"code":200,"url":"https://example.com/"}]}"#
+        );
+
+        let status = Status::Redirected(StatusCode::FOUND, Redirects::default());
+        assert!(!status.is_success());
+        assert_eq!(status.details().unwrap(), "Found");
+    }
+
     #[test]
     fn test_status_unknown() {
         assert!(Status::UnknownStatusCode(StatusCode::from_u16(999).unwrap()).is_unknown());
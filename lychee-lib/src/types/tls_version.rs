@@ -0,0 +1,27 @@
+/// Versions of TLS, used as the minimum version of HTTPS connections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TlsVersion {
+    /// TLS 1.0
+    Tls1_0,
+    /// TLS 1.1
+    Tls1_1,
+    /// TLS 1.2
+    Tls1_2,
+    /// TLS 1.3
+    ///
+    /// Not supported by all TLS backends, e.g. the `native-tls` backend on
+    /// some platforms.
+    Tls1_3,
+}
+
+impl TlsVersion {
+    /// The corresponding version of [`reqwest`]
+    pub(crate) const fn to_reqwest(self) -> reqwest::tls::Version {
+        match self {
+            Self::Tls1_0 => reqwest::tls::Version::TLS_1_0,
+            Self::Tls1_1 => reqwest::tls::Version::TLS_1_1,
+            Self::Tls1_2 => reqwest::tls::Version::TLS_1_2,
+            Self::Tls1_3 => reqwest::tls::Version::TLS_1_3,
+        }
+    }
+}
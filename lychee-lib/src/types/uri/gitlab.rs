@@ -0,0 +1,171 @@
+use std::collections::HashSet;
+
+use once_cell::sync::Lazy;
+
+use crate::{ErrorKind, Platform, Result, Uri};
+
+/// Host of the public GitLab instance, which is always checked via the API
+pub(crate) const DEFAULT_GITLAB_HOST: &str = "gitlab.com";
+
+/// Top-level paths of GitLab instances which aren't groups or users
+static GITLAB_API_EXCLUDED_ENDPOINTS: Lazy<HashSet<&'static str>> = Lazy::new(|| {
+    HashSet::from_iter([
+        "-",
+        "admin",
+        "api",
+        "dashboard",
+        "explore",
+        "groups",
+        "help",
+        "search",
+        "users",
+    ])
+});
+
+/// Uri parts extracted from a GitLab URL
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct GitlabUri {
+    /// Scheme, host and port of the GitLab instance, e.g. `https://gitlab.com`
+    pub origin: String,
+    /// Path of the project including all (sub)groups, e.g. `group/subgroup/project`
+    pub project: String,
+    /// e.g. `issues/1` in `/group/project/-/issues/1`
+    pub endpoint: Option<String>,
+}
+
+impl GitlabUri {
+    /// Parse `uri` as a link to a project on a GitLab instance.
+    ///
+    /// Everything in front of the `/-/` separator of GitLab URLs is the path of
+    /// the project. Legacy URLs without a separator (like `/group/project/issues`)
+    /// are taken as the path of a project in a subgroup.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `uri` is not a link to a project.
+    pub(crate) fn parse(uri: &Uri) -> Result<Self> {
+        debug_assert!(!uri.is_mail(), "Should only be called on a Website type!");
+
+        let invalid = || ErrorKind::InvalidPlatformUrl(Platform::Gitlab, uri.to_string());
+
+        let parts: Vec<_> = uri
+            .path_segments()
+            .ok_or_else(invalid)?
+            .filter(|part| !part.is_empty())
+            .collect();
+        let (project, endpoint) = match parts.iter().position(|part| *part == "-") {
+            Some(separator) => (&parts[..separator], &parts[separator + 1..]),
+            None => (&parts[..], &[][..]),
+        };
+
+        // Projects always belong to a group or user
+        if project.len() < 2 || GITLAB_API_EXCLUDED_ENDPOINTS.contains(project[0]) {
+            return Err(invalid());
+        }
+
+        let mut project = project.join("/");
+        // If the URL ends with `.git`, assume this is a clone URL
+        if let Some(stripped) = project.strip_suffix(".git") {
+            project = stripped.to_string();
+        }
+
+        let endpoint = if endpoint.is_empty() {
+            None
+        } else {
+            Some(endpoint.join("/"))
+        };
+
+        Ok(GitlabUri {
+            origin: uri.url.origin().ascii_serialization(),
+            project,
+            endpoint,
+        })
+    }
+
+    /// URL of the project in the GitLab REST API.
+    ///
+    /// The API is always requested over https, even for links to http
+    /// pages, so that the token of the request isn't sent in plain text.
+    pub(crate) fn api_url(&self) -> String {
+        let host = self
+            .origin
+            .split_once("://")
+            .map_or(self.origin.as_str(), |(_, host)| host);
+        format!(
+            "https://{host}/api/v4/projects/{}",
+            self.project.replace('/', "%2F")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::website;
+
+    use super::*;
+
+    fn gitlab(origin: &str, project: &str, endpoint: Option<&str>) -> GitlabUri {
+        GitlabUri {
+            origin: origin.to_string(),
+            project: project.to_string(),
+            endpoint: endpoint.map(ToString::to_string),
+        }
+    }
+
+    #[test]
+    fn test_gitlab() {
+        assert_eq!(
+            GitlabUri::parse(&website("https://gitlab.com/gitlab-org/gitlab")).unwrap(),
+            gitlab("https://gitlab.com", "gitlab-org/gitlab", None)
+        );
+
+        assert_eq!(
+            GitlabUri::parse(&website("https://gitlab.com/gitlab-org/gitlab-foss.git")).unwrap(),
+            gitlab("https://gitlab.com", "gitlab-org/gitlab-foss", None)
+        );
+    }
+
+    #[test]
+    fn test_gitlab_subgroups_and_endpoints() {
+        let uri = GitlabUri::parse(&website(
+            "https://gitlab.com/group/subgroup/project/-/blob/main/README.md",
+        ))
+        .unwrap();
+        assert_eq!(
+            uri,
+            gitlab(
+                "https://gitlab.com",
+                "group/subgroup/project",
+                Some("blob/main/README.md")
+            )
+        );
+        assert_eq!(
+            uri.api_url(),
+            "https://gitlab.com/api/v4/projects/group%2Fsubgroup%2Fproject"
+        );
+    }
+
+    #[test]
+    fn test_gitlab_self_hosted() {
+        let uri = GitlabUri::parse(&website(
+            "http://git.example.com:8080/team/project/-/issues/1",
+        ))
+        .unwrap();
+        assert_eq!(
+            uri.api_url(),
+            "https://git.example.com:8080/api/v4/projects/team%2Fproject"
+        );
+    }
+
+    #[test]
+    fn test_gitlab_false_positives() {
+        for url in [
+            "https://gitlab.com/gitlab-org",
+            "https://gitlab.com/explore/projects",
+            "https://gitlab.com/users/sign_in",
+            "https://gitlab.com/-/ide/project/group/project",
+        ] {
+            assert!(GitlabUri::parse(&website(url)).is_err(), "{url}");
+        }
+    }
+}
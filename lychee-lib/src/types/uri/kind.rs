@@ -0,0 +1,80 @@
+use std::fmt::Display;
+
+use url::Url;
+
+/// Classification of a [`Uri`](crate::Uri) by its scheme, which decides how
+/// the link is filtered and checked
+#[derive(Clone, Debug, PartialOrd, Ord, PartialEq, Eq, Hash)]
+pub enum UriKind {
+    /// Website (`http` or `https`)
+    Web,
+    /// Local file (`file`)
+    File,
+    /// Mail address (`mailto`)
+    Mail,
+    /// Phone number (`tel`), which is never checked
+    Tel,
+    /// Inline data (`data`)
+    Data,
+    /// Any other scheme, e.g. `ftp` or `ipfs`.
+    /// Links with these schemes are only checked by the
+    /// [`Checker`](crate::Checker) registered for the scheme (if any).
+    Unsupported(String),
+}
+
+impl UriKind {
+    /// Whether links of this kind are requested over the network
+    #[must_use]
+    pub const fn is_web(&self) -> bool {
+        matches!(self, Self::Web)
+    }
+}
+
+impl From<&Url> for UriKind {
+    fn from(url: &Url) -> Self {
+        match url.scheme() {
+            "http" | "https" => Self::Web,
+            "file" => Self::File,
+            "mailto" => Self::Mail,
+            "tel" => Self::Tel,
+            "data" => Self::Data,
+            scheme => Self::Unsupported(scheme.to_string()),
+        }
+    }
+}
+
+impl Display for UriKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Web => f.write_str("web"),
+            Self::File => f.write_str("file"),
+            Self::Mail => f.write_str("mail"),
+            Self::Tel => f.write_str("tel"),
+            Self::Data => f.write_str("data"),
+            Self::Unsupported(scheme) => write!(f, "unsupported ({scheme})"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uri_kind() {
+        for (url, kind) in [
+            ("https://example.com", UriKind::Web),
+            ("HTTP://example.com", UriKind::Web),
+            ("file:///etc/hosts", UriKind::File),
+            ("mailto:user@example.com", UriKind::Mail),
+            ("tel:+1234567890", UriKind::Tel),
+            ("data:text/plain,hello", UriKind::Data),
+            (
+                "ipfs://bafybeigdyrzt",
+                UriKind::Unsupported("ipfs".to_string()),
+            ),
+        ] {
+            assert_eq!(UriKind::from(&Url::parse(url).unwrap()), kind, "{url}");
+        }
+    }
+}
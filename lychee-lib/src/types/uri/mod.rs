@@ -1,3 +1,5 @@
 pub(crate) mod github;
+pub(crate) mod gitlab;
+pub(crate) mod kind;
 pub(crate) mod raw;
 pub(crate) mod valid;
@@ -1,4 +1,6 @@
-use std::fmt::Display;
+use std::{cell::RefCell, collections::VecDeque, fmt::Display, num::NonZeroUsize};
+
+use serde::{Deserialize, Serialize};
 
 /// A raw URI that got extracted from a document with a fuzzy parser.
 /// Note that this can still be invalid according to stricter URI standards
@@ -17,6 +19,8 @@ pub struct RawUri {
     /// that will be checked e.g. by trying to filter out links that were found
     /// in unwanted attributes like `srcset` or `manifest`.
     pub attribute: Option<String>,
+    /// Position of the URI in the document it was extracted from, if known
+    pub span: Option<RawUriSpan>,
 }
 
 impl Display for RawUri {
@@ -31,6 +35,205 @@ impl From<&str> for RawUri {
             text: text.to_string(),
             element: None,
             attribute: None,
+            span: None,
+        }
+    }
+}
+
+/// The position of a [`RawUri`] inside of the document it was extracted from.
+///
+/// Lines and columns are one-based, like in most text editors.
+/// Columns are counted in characters, not bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct RawUriSpan {
+    /// Line of the document on which the URI starts
+    pub line: NonZeroUsize,
+    /// Column of the line on which the URI starts. Some parsers only report
+    /// the line, in which case this is `None`.
+    pub column: Option<NonZeroUsize>,
+}
+
+impl Display for RawUriSpan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.column {
+            Some(column) => write!(f, "{}:{column}", self.line),
+            None => write!(f, "{}", self.line),
         }
     }
 }
+
+/// Computes the [`RawUriSpan`] for a byte offset into a document
+pub(crate) trait SpanProvider {
+    /// Return the span of the given byte offset
+    fn span(&self, offset: usize) -> RawUriSpan;
+}
+
+/// A [`SpanProvider`] for a whole document.
+///
+/// The start of each line is computed once, so that looking up a span
+/// only needs a binary search and counting the characters of a single line.
+#[derive(Debug)]
+pub(crate) struct SourceSpanProvider<'a> {
+    input: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> SourceSpanProvider<'a> {
+    /// Create a span provider for the given document
+    pub(crate) fn from_input(input: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(input.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { input, line_starts }
+    }
+}
+
+impl SpanProvider for SourceSpanProvider<'_> {
+    fn span(&self, offset: usize) -> RawUriSpan {
+        let offset = offset.min(self.input.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = self
+            .input
+            .get(line_start..offset)
+            .map_or(offset - line_start, |prefix| prefix.chars().count());
+
+        RawUriSpan {
+            line: NonZeroUsize::MIN.saturating_add(line - 1),
+            column: Some(NonZeroUsize::MIN.saturating_add(column)),
+        }
+    }
+}
+
+/// A [`SpanProvider`] for a fragment of a document, which starts at `offset`
+/// bytes into the document.
+///
+/// This is used when a document is passed to another extractor in chunks,
+/// e.g. inline HTML in a Markdown file.
+#[derive(Debug)]
+pub(crate) struct OffsetSpanProvider<'a, T: SpanProvider = SourceSpanProvider<'a>> {
+    pub(crate) offset: usize,
+    pub(crate) inner: &'a T,
+}
+
+impl<T: SpanProvider> SpanProvider for OffsetSpanProvider<'_, T> {
+    fn span(&self, offset: usize) -> RawUriSpan {
+        self.inner.span(self.offset + offset)
+    }
+}
+
+/// Number of line starts remembered by a [`StreamSpanProvider`]
+const MAX_RECENT_LINES: usize = 1024;
+
+/// A [`SpanProvider`] for a document which is read incrementally, e.g. a
+/// large file which is streamed instead of read into memory.
+///
+/// The reader reports the consumed bytes with [`StreamSpanProvider::advance`].
+/// Only the starts of the most recent lines are kept, so memory usage doesn't
+/// grow with the size of the document. Columns are not tracked.
+#[derive(Debug)]
+pub(crate) struct StreamSpanProvider {
+    lines: RefCell<RecentLines>,
+}
+
+#[derive(Debug)]
+struct RecentLines {
+    /// Line number of the first entry of `starts`
+    first_line: usize,
+    /// Byte offsets of the most recent line starts
+    starts: VecDeque<usize>,
+}
+
+impl Default for StreamSpanProvider {
+    fn default() -> Self {
+        Self {
+            lines: RefCell::new(RecentLines {
+                first_line: 1,
+                starts: VecDeque::from([0]),
+            }),
+        }
+    }
+}
+
+impl StreamSpanProvider {
+    /// Record that the bytes `consumed` were read, starting at byte `offset`
+    pub(crate) fn advance(&self, offset: usize, consumed: &[u8]) {
+        let mut lines = self.lines.borrow_mut();
+        let newlines = consumed.iter().enumerate().filter(|&(_, &b)| b == b'\n');
+        for (i, _) in newlines {
+            lines.starts.push_back(offset + i + 1);
+            if lines.starts.len() > MAX_RECENT_LINES {
+                lines.starts.pop_front();
+                lines.first_line += 1;
+            }
+        }
+    }
+}
+
+impl SpanProvider for StreamSpanProvider {
+    fn span(&self, offset: usize) -> RawUriSpan {
+        let lines = self.lines.borrow();
+        // Offsets before the oldest remembered line are attributed to it
+        let index = lines
+            .starts
+            .partition_point(|&start| start <= offset)
+            .saturating_sub(1);
+
+        RawUriSpan {
+            line: NonZeroUsize::MIN.saturating_add(lines.first_line + index - 1),
+            column: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::span;
+
+    #[test]
+    fn test_source_span_provider() {
+        let provider = SourceSpanProvider::from_input("foo\nbär baz\n\nqux");
+        assert_eq!(provider.span(0), span(1, 1));
+        assert_eq!(provider.span(2), span(1, 3));
+        assert_eq!(provider.span(4), span(2, 1));
+        // `ä` is two bytes long, but only a single column wide
+        assert_eq!(provider.span(9), span(2, 5));
+        assert_eq!(provider.span(13), span(3, 1));
+        assert_eq!(provider.span(14), span(4, 1));
+    }
+
+    #[test]
+    fn test_offset_span_provider() {
+        let inner = SourceSpanProvider::from_input("foo\nbar baz");
+        let provider = OffsetSpanProvider {
+            offset: 4,
+            inner: &inner,
+        };
+        assert_eq!(provider.span(4), span(2, 5));
+    }
+
+    #[test]
+    fn test_display_span() {
+        assert_eq!(span(42, 17).to_string(), "42:17");
+        assert_eq!(
+            RawUriSpan {
+                line: NonZeroUsize::new(42).unwrap(),
+                column: None,
+            }
+            .to_string(),
+            "42"
+        );
+    }
+
+    #[test]
+    fn test_stream_span_provider() {
+        let provider = StreamSpanProvider::default();
+        provider.advance(0, b"foo\nbar");
+        provider.advance(7, b" baz\n\nqux");
+        assert_eq!(provider.span(2).line.get(), 1);
+        assert_eq!(provider.span(4).line.get(), 2);
+        assert_eq!(provider.span(13).line.get(), 4);
+        assert_eq!(provider.span(13).column, None);
+    }
+}
@@ -7,17 +7,39 @@ use url::Url;
 
 use crate::{ErrorKind, Result};
 
-use super::raw::RawUri;
+use super::{kind::UriKind, raw::RawUri};
 
 /// Lychee's own representation of a URI, which encapsulates all supported
 /// formats.
 ///
-/// If the scheme is `mailto`, it's a mail address.
-/// Otherwise it's treated as a website URL.
+/// The [`UriKind`] of the URI is derived from its scheme once, when the URI
+/// is created.
 #[derive(Clone, Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "SerializedUri", into = "SerializedUri")]
 pub struct Uri {
     /// Website URL or mail address
     pub(crate) url: Url,
+    /// Classification of the URL by its scheme
+    kind: UriKind,
+}
+
+/// Serialized form of a [`Uri`], without the kind, which is derived from the
+/// URL again when deserializing
+#[derive(Serialize, Deserialize)]
+struct SerializedUri {
+    url: Url,
+}
+
+impl From<SerializedUri> for Uri {
+    fn from(uri: SerializedUri) -> Self {
+        uri.url.into()
+    }
+}
+
+impl From<Uri> for SerializedUri {
+    fn from(uri: Uri) -> Self {
+        Self { url: uri.url }
+    }
 }
 
 impl Uri {
@@ -35,10 +57,24 @@ impl Uri {
         self.url.scheme()
     }
 
+    #[inline]
+    #[must_use]
+    /// Returns the classification of the URI by its scheme
+    pub const fn kind(&self) -> &UriKind {
+        &self.kind
+    }
+
     #[inline]
     /// Changes this URL's scheme.
     pub(crate) fn set_scheme(&mut self, scheme: &str) -> std::result::Result<(), ()> {
-        self.url.set_scheme(scheme)
+        self.url.set_scheme(scheme)?;
+        self.kind = UriKind::from(&self.url);
+        Ok(())
+    }
+
+    /// Replaces the URL, e.g. after remapping it
+    pub(crate) fn set_url(&mut self, url: Url) {
+        *self = url.into();
     }
 
     #[inline]
@@ -48,6 +84,14 @@ impl Uri {
         self.url.domain()
     }
 
+    #[inline]
+    #[must_use]
+    /// Returns the host of the URI, which is either a domain or an IP address
+    /// (e.g. `example.com` or `[::1]`)
+    pub fn host_str(&self) -> Option<&str> {
+        self.url.host_str()
+    }
+
     #[inline]
     #[must_use]
     /// Returns the path of the URI (e.g. `/path/to/resource`)
@@ -89,29 +133,29 @@ impl Uri {
     #[inline]
     #[must_use]
     /// Check if the URI is a valid mail address
-    pub fn is_mail(&self) -> bool {
-        self.scheme() == "mailto"
+    pub const fn is_mail(&self) -> bool {
+        matches!(self.kind, UriKind::Mail)
     }
 
     #[inline]
     #[must_use]
     /// Check if the URI is a tel
-    pub fn is_tel(&self) -> bool {
-        self.scheme() == "tel"
+    pub const fn is_tel(&self) -> bool {
+        matches!(self.kind, UriKind::Tel)
     }
 
     #[inline]
     #[must_use]
     /// Check if the URI is a file
-    pub fn is_file(&self) -> bool {
-        self.scheme() == "file"
+    pub const fn is_file(&self) -> bool {
+        matches!(self.kind, UriKind::File)
     }
 
     #[inline]
     #[must_use]
     /// Check if the URI is a `data` URI
-    pub fn is_data(&self) -> bool {
-        self.scheme() == "data"
+    pub const fn is_data(&self) -> bool {
+        matches!(self.kind, UriKind::Data)
     }
 
     #[inline]
@@ -203,7 +247,8 @@ impl AsRef<str> for Uri {
 
 impl From<Url> for Uri {
     fn from(url: Url) -> Self {
-        Self { url }
+        let kind = UriKind::from(&url);
+        Self { url, kind }
     }
 }
 
@@ -383,4 +428,15 @@ mod tests {
     fn test_file_uri() {
         assert!(Uri::try_from("file:///path/to/file").unwrap().is_file());
     }
+
+    #[test]
+    fn test_kind_is_derived_after_deserializing() {
+        let uri = Uri::try_from("mail@example.com").unwrap();
+        assert_eq!(uri.kind(), &UriKind::Mail);
+
+        let json = serde_json::to_string(&uri).unwrap();
+        assert_eq!(json, r#"{"url":"mailto:mail@example.com"}"#);
+        let uri: Uri = serde_json::from_str(&json).unwrap();
+        assert_eq!(uri.kind(), &UriKind::Mail);
+    }
 }
@@ -0,0 +1,259 @@
+//! Support for archive inputs (zip and tar.gz)
+//!
+//! Archives are unpacked into a temporary directory, so that the contained
+//! documents can be read like any other directory. Relative links between
+//! documents of an archive point into that directory, so it has to outlive
+//! link collection. Each directory is removed once the last
+//! [`InputSource::ArchiveEntry`](crate::InputSource::ArchiveEntry) of its files, and with it the last request
+//! for its links, is dropped.
+
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use flate2::read::GzDecoder;
+use tempfile::TempDir;
+
+use crate::{ErrorKind, Result};
+
+/// Maximum total size of the files unpacked from a single archive (512 MiB)
+///
+/// This protects against decompression bombs.
+pub(crate) const MAX_UNPACKED_SIZE: u64 = 512 * 1024 * 1024;
+
+/// Temporary directory of an unpacked archive or container image, which is
+/// shared by the [`InputSource::ArchiveEntry`](crate::InputSource::ArchiveEntry) of each unpacked file.
+///
+/// The directory is removed when the last clone is dropped. All handles are
+/// equal, the unpacked files are told apart by their path.
+#[derive(Debug, Clone, Default)]
+pub struct UnpackedDir(Option<Arc<TempDir>>);
+
+impl UnpackedDir {
+    /// Keep `dir` until the last handle is dropped
+    pub(crate) fn new(dir: TempDir) -> Self {
+        Self(Some(Arc::new(dir)))
+    }
+
+    /// Path of the directory, `None` for the handles of deserialized sources
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        self.0.as_deref().map(TempDir::path)
+    }
+}
+
+impl PartialEq for UnpackedDir {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for UnpackedDir {}
+
+impl std::hash::Hash for UnpackedDir {
+    fn hash<H: std::hash::Hasher>(&self, _state: &mut H) {}
+}
+
+/// Returns `true` if the file name of `path` has an archive extension
+/// (`.zip`, `.tar.gz` or `.tgz`)
+pub(crate) fn is_archive(path: &Path) -> bool {
+    is_zip(path) || is_tar_gz(path)
+}
+
+fn is_zip(path: &Path) -> bool {
+    has_suffix(path, &[".zip"])
+}
+
+fn is_tar_gz(path: &Path) -> bool {
+    has_suffix(path, &[".tar.gz", ".tgz"])
+}
+
+fn has_suffix(path: &Path, suffixes: &[&str]) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    let name = name.to_ascii_lowercase();
+    suffixes.iter().any(|suffix| name.ends_with(suffix))
+}
+
+/// Unpack the regular files of `archive` into a new temporary directory.
+///
+/// Entries which would be unpacked outside of the directory (e.g.
+/// `../evil.md`), links and other special files are skipped. The archive is
+/// unpacked on a blocking thread.
+///
+/// # Errors
+///
+/// Returns an error if the archive can't be read or if the unpacked files
+/// exceed [`MAX_UNPACKED_SIZE`].
+pub(crate) async fn unpack(archive: &Path) -> Result<UnpackedDir> {
+    let archive = archive.to_path_buf();
+    tokio::task::spawn_blocking(move || unpack_blocking(&archive)).await?
+}
+
+fn unpack_blocking(archive: &Path) -> Result<UnpackedDir> {
+    let file =
+        File::open(archive).map_err(|e| ErrorKind::ReadFileInput(e, archive.to_path_buf()))?;
+    let invalid = |e: io::Error| ErrorKind::InvalidArchive(archive.to_path_buf(), e.to_string());
+    let dir = temp_dir().map_err(invalid)?;
+
+    let mut budget = MAX_UNPACKED_SIZE;
+    let unpacked = if is_zip(archive) {
+        unpack_zip(file, dir.path(), &mut budget)
+    } else {
+        unpack_tar_gz(file, dir.path(), &mut budget)
+    };
+    unpacked.map_err(invalid)?;
+
+    Ok(UnpackedDir::new(dir))
+}
+
+/// Create a temporary directory to unpack files into
+pub(crate) fn temp_dir() -> io::Result<TempDir> {
+    tempfile::Builder::new().prefix("lychee-").tempdir()
+}
+
+fn unpack_zip(file: File, dest: &Path, budget: &mut u64) -> io::Result<()> {
+    let mut zip = zip::ZipArchive::new(file)?;
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i)?;
+        if !entry.is_file() {
+            continue;
+        }
+        let Some(path) = entry.enclosed_name() else {
+            continue;
+        };
+        write_entry(&mut entry, &dest.join(path), budget)?;
+    }
+    Ok(())
+}
+
+fn unpack_tar_gz(file: File, dest: &Path, budget: &mut u64) -> io::Result<()> {
+    let mut tar = tar::Archive::new(GzDecoder::new(file));
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let Some(path) = enclosed_path(&entry.path()?) else {
+            continue;
+        };
+        write_entry(&mut entry, &dest.join(path), budget)?;
+    }
+    Ok(())
+}
+
+/// Returns `path` if it is relative and doesn't escape its parent directory
+pub(crate) fn enclosed_path(path: &Path) -> Option<PathBuf> {
+    path.components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        .then(|| path.to_path_buf())
+}
+
+/// Copy `entry` to `dest`, reducing `budget` by the number of bytes written.
+///
+/// The declared size of archive entries can't be trusted, so the limit is
+/// enforced while copying.
+pub(crate) fn write_entry(entry: &mut impl Read, dest: &Path, budget: &mut u64) -> io::Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut out = File::create(dest)?;
+    let written = io::copy(&mut entry.take(*budget + 1), &mut out)?;
+    if written > *budget {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("unpacked files exceed {MAX_UNPACKED_SIZE} bytes"),
+        ));
+    }
+    *budget -= written;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+
+    #[test]
+    fn test_is_archive() {
+        assert!(is_archive(Path::new("docs.zip")));
+        assert!(is_archive(Path::new("site/release.tar.gz")));
+        assert!(is_archive(Path::new("RELEASE.TGZ")));
+        assert!(!is_archive(Path::new("README.md")));
+        assert!(!is_archive(Path::new("archive.gz")));
+    }
+
+    #[test]
+    fn test_enclosed_path() {
+        assert_eq!(
+            enclosed_path(Path::new("./docs/readme.md")),
+            Some(PathBuf::from("./docs/readme.md"))
+        );
+        assert_eq!(enclosed_path(Path::new("../evil.md")), None);
+        assert_eq!(enclosed_path(Path::new("/etc/passwd")), None);
+    }
+
+    #[tokio::test]
+    async fn test_unpack_tar_gz() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("docs.tar.gz");
+
+        let mut tar = tar::Builder::new(GzEncoder::new(
+            File::create(&archive).unwrap(),
+            Compression::default(),
+        ));
+        let content = b"[home](https://example.com)";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_cksum();
+        tar.append_data(&mut header, "docs/readme.md", &content[..])
+            .unwrap();
+        tar.into_inner().unwrap().finish().unwrap();
+
+        let unpacked = unpack(&archive).await.unwrap();
+        let root = unpacked.path().unwrap().to_path_buf();
+        assert_eq!(
+            fs::read(root.join("docs/readme.md")).unwrap(),
+            content.to_vec()
+        );
+        // The directory is removed with the last handle
+        drop(unpacked);
+        assert!(!root.exists());
+    }
+
+    #[tokio::test]
+    async fn test_unpack_zip() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("docs.zip");
+
+        let mut zip = zip::ZipWriter::new(File::create(&archive).unwrap());
+        zip.start_file("docs/readme.md", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"# Readme").unwrap();
+        zip.finish().unwrap();
+
+        let unpacked = unpack(&archive).await.unwrap();
+        assert_eq!(
+            fs::read_to_string(unpacked.path().unwrap().join("docs/readme.md")).unwrap(),
+            "# Readme"
+        );
+    }
+
+    #[test]
+    fn test_size_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut budget = 4;
+        let dest = dir.path().join("file");
+
+        write_entry(&mut &b"1234"[..], &dest, &mut budget).unwrap();
+        assert_eq!(budget, 0);
+        assert!(write_entry(&mut &b"5"[..], &dest, &mut budget).is_err());
+    }
+}
@@ -0,0 +1,357 @@
+//! Support for objects in cloud storage buckets as inputs
+//!
+//! Inputs like `s3://bucket/docs/` check all Markdown and HTML objects whose
+//! key starts with the given prefix. Each provider is behind a feature flag
+//! and uses the official SDK with credentials taken from the environment:
+//!
+//! - `s3://bucket/prefix` (feature `s3`): the default AWS credential chain
+//!   (`AWS_ACCESS_KEY_ID`, `AWS_PROFILE`, instance metadata, ...)
+//! - `gs://bucket/prefix` (feature `gcs`): application default credentials
+//!   (`GOOGLE_APPLICATION_CREDENTIALS`, metadata server, ...)
+//! - `az://container/prefix` (feature `azure`): the storage account from
+//!   `AZURE_STORAGE_ACCOUNT` and either `AZURE_STORAGE_ACCESS_KEY`,
+//!   `AZURE_STORAGE_SAS_TOKEN` or anonymous access
+
+use async_trait::async_trait;
+use futures::{stream::BoxStream, Stream, StreamExt};
+use percent_encoding::percent_decode_str;
+use reqwest::Url;
+
+use crate::{ErrorKind, Result};
+
+/// URL schemes of bucket inputs
+const SCHEMES: &[&str] = &["s3", "gs", "az"];
+
+/// Returns `true` if `url` refers to objects in a storage bucket
+pub(crate) fn is_bucket_url(url: &Url) -> bool {
+    SCHEMES.contains(&url.scheme())
+}
+
+/// Operations on a bucket of one of the supported providers
+#[async_trait]
+trait Store: Send + Sync {
+    /// Keys of all objects starting with `prefix`, listed page by page
+    fn list<'a>(&'a self, prefix: &'a str) -> BoxStream<'a, std::result::Result<String, String>>;
+
+    /// Content of the object with the given `key`
+    async fn get(&self, key: &str) -> std::result::Result<Vec<u8>, String>;
+}
+
+/// A storage bucket and the prefix of the objects to check
+// Only constructed with the feature of a provider
+#[cfg_attr(
+    not(any(feature = "s3", feature = "gcs", feature = "azure")),
+    allow(dead_code)
+)]
+pub(crate) struct Bucket {
+    url: Url,
+    prefix: String,
+    store: Box<dyn Store>,
+}
+
+impl Bucket {
+    /// Create a client for the bucket of `url`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if lychee was built without support for the
+    /// provider or if the client can't be created.
+    #[cfg(any(feature = "s3", feature = "gcs", feature = "azure"))]
+    #[cfg_attr(not(any(feature = "s3", feature = "gcs")), allow(clippy::unused_async))]
+    pub(crate) async fn connect(url: &Url) -> Result<Self> {
+        let store: Box<dyn Store> = match url.scheme() {
+            #[cfg(feature = "s3")]
+            "s3" => Box::new(s3::S3::new(bucket_name(url)).await),
+            #[cfg(feature = "gcs")]
+            "gs" => Box::new(
+                gcs::Gcs::new(bucket_name(url))
+                    .await
+                    .map_err(|e| invalid(url, e))?,
+            ),
+            #[cfg(feature = "azure")]
+            "az" => Box::new(azure::Azure::new(bucket_name(url)).map_err(|e| invalid(url, e))?),
+            _ => return Err(missing_feature(url)),
+        };
+        Ok(Self {
+            url: url.clone(),
+            prefix: url.path().trim_start_matches('/').to_string(),
+            store,
+        })
+    }
+
+    /// Without the features of the providers, no bucket can be connected
+    ///
+    /// # Errors
+    ///
+    /// Always returns an error, as lychee was built without support for any
+    /// provider
+    #[cfg(not(any(feature = "s3", feature = "gcs", feature = "azure")))]
+    #[allow(clippy::unused_async)]
+    pub(crate) async fn connect(url: &Url) -> Result<Self> {
+        Err(missing_feature(url))
+    }
+
+    /// Stream the URLs of all objects in the bucket which start with the
+    /// prefix. Further pages of the listing are fetched as the stream is
+    /// consumed.
+    ///
+    /// # Errors
+    ///
+    /// The stream yields an error and ends if the objects can't be listed
+    pub(crate) fn objects(&self) -> impl Stream<Item = Result<Url>> + '_ {
+        self.store.list(&self.prefix).map(|key| {
+            key.map(|key| self.object_url(&key))
+                .map_err(|e| invalid(&self.url, e))
+        })
+    }
+
+    /// Download the object at `url`, as returned by [`Bucket::objects`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the object can't be downloaded
+    pub(crate) async fn get(&self, url: &Url) -> Result<Vec<u8>> {
+        let key = percent_decode_str(url.path().trim_start_matches('/')).decode_utf8_lossy();
+        self.store.get(&key).await.map_err(|e| invalid(url, e))
+    }
+
+    fn object_url(&self, key: &str) -> Url {
+        let mut url = self.url.clone();
+        url.set_path(key);
+        url
+    }
+}
+
+#[cfg(any(feature = "s3", feature = "gcs", feature = "azure"))]
+fn bucket_name(url: &Url) -> &str {
+    url.host_str().unwrap_or_default()
+}
+
+/// The error for a bucket of a provider whose feature is disabled
+fn missing_feature(url: &Url) -> ErrorKind {
+    let feature = match url.scheme() {
+        "gs" => "gcs",
+        "az" => "azure",
+        scheme => scheme,
+    };
+    invalid(
+        url,
+        format!("lychee was built without the `{feature}` feature"),
+    )
+}
+
+fn invalid(url: &Url, e: String) -> ErrorKind {
+    ErrorKind::InvalidBucket(url.to_string(), e)
+}
+
+#[cfg(feature = "s3")]
+mod s3 {
+    use async_trait::async_trait;
+    use aws_sdk_s3::{error::DisplayErrorContext, Client};
+    use futures::{stream::BoxStream, StreamExt};
+
+    use super::Store;
+    use crate::utils::paginate::{paginate, Page};
+
+    pub(super) struct S3 {
+        client: Client,
+        bucket: String,
+    }
+
+    impl S3 {
+        pub(super) async fn new(bucket: &str) -> Self {
+            let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+            Self {
+                client: Client::new(&config),
+                bucket: bucket.to_string(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Store for S3 {
+        fn list<'a>(&'a self, prefix: &'a str) -> BoxStream<'a, Result<String, String>> {
+            paginate(None, move |continuation_token| async move {
+                let page = self
+                    .client
+                    .list_objects_v2()
+                    .bucket(&self.bucket)
+                    .prefix(prefix)
+                    .set_continuation_token(continuation_token)
+                    .send()
+                    .await
+                    .map_err(|e| DisplayErrorContext(e).to_string())?;
+                let keys = page
+                    .contents()
+                    .iter()
+                    .filter_map(|o| o.key().map(String::from))
+                    .collect();
+                let next = page.next_continuation_token().map(|t| Some(t.to_string()));
+                Ok(Page::new(keys, next))
+            })
+            .boxed()
+        }
+
+        async fn get(&self, key: &str) -> Result<Vec<u8>, String> {
+            let object = self
+                .client
+                .get_object()
+                .bucket(&self.bucket)
+                .key(key)
+                .send()
+                .await
+                .map_err(|e| DisplayErrorContext(e).to_string())?;
+            let body = object.body.collect().await.map_err(|e| e.to_string())?;
+            Ok(body.into_bytes().to_vec())
+        }
+    }
+}
+
+#[cfg(feature = "gcs")]
+mod gcs {
+    use async_trait::async_trait;
+    use futures::{stream::BoxStream, StreamExt};
+    use google_cloud_storage::{
+        client::{Client, ClientConfig},
+        http::objects::{download::Range, get::GetObjectRequest, list::ListObjectsRequest},
+    };
+
+    use super::Store;
+    use crate::utils::paginate::{paginate, Page};
+
+    pub(super) struct Gcs {
+        client: Client,
+        bucket: String,
+    }
+
+    impl Gcs {
+        pub(super) async fn new(bucket: &str) -> Result<Self, String> {
+            let config = ClientConfig::default()
+                .with_auth()
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(Self {
+                client: Client::new(config),
+                bucket: bucket.to_string(),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl Store for Gcs {
+        fn list<'a>(&'a self, prefix: &'a str) -> BoxStream<'a, Result<String, String>> {
+            paginate(None, move |page_token| async move {
+                let request = ListObjectsRequest {
+                    bucket: self.bucket.clone(),
+                    prefix: Some(prefix.to_string()),
+                    page_token,
+                    ..Default::default()
+                };
+                let page = self
+                    .client
+                    .list_objects(&request)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let keys = page.items.unwrap_or_default().into_iter().map(|o| o.name);
+                Ok(Page::new(keys.collect(), page.next_page_token.map(Some)))
+            })
+            .boxed()
+        }
+
+        async fn get(&self, key: &str) -> Result<Vec<u8>, String> {
+            let request = GetObjectRequest {
+                bucket: self.bucket.clone(),
+                object: key.to_string(),
+                ..Default::default()
+            };
+            self.client
+                .download_object(&request, &Range::default())
+                .await
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+#[cfg(feature = "azure")]
+mod azure {
+    use async_stream::try_stream;
+    use async_trait::async_trait;
+    use azure_storage::StorageCredentials;
+    use azure_storage_blobs::prelude::{ClientBuilder, ContainerClient};
+    use futures::{stream::BoxStream, StreamExt};
+
+    use super::Store;
+
+    pub(super) struct Azure {
+        client: ContainerClient,
+    }
+
+    impl Azure {
+        pub(super) fn new(container: &str) -> Result<Self, String> {
+            let account = std::env::var("AZURE_STORAGE_ACCOUNT")
+                .map_err(|_| "`AZURE_STORAGE_ACCOUNT` is not set".to_string())?;
+            let credentials = if let Ok(key) = std::env::var("AZURE_STORAGE_ACCESS_KEY") {
+                StorageCredentials::access_key(account.clone(), key)
+            } else if let Ok(token) = std::env::var("AZURE_STORAGE_SAS_TOKEN") {
+                StorageCredentials::sas_token(token).map_err(|e| e.to_string())?
+            } else {
+                StorageCredentials::anonymous()
+            };
+            Ok(Self {
+                client: ClientBuilder::new(account, credentials).container_client(container),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl Store for Azure {
+        fn list<'a>(&'a self, prefix: &'a str) -> BoxStream<'a, Result<String, String>> {
+            // The SDK already fetches the pages of the listing on demand
+            let mut pages = self
+                .client
+                .list_blobs()
+                .prefix(prefix.to_string())
+                .into_stream();
+            try_stream! {
+                while let Some(page) = pages.next().await {
+                    let page = page.map_err(|e| e.to_string())?;
+                    for blob in page.blobs.blobs() {
+                        yield blob.name.clone();
+                    }
+                }
+            }
+            .boxed()
+        }
+
+        async fn get(&self, key: &str) -> Result<Vec<u8>, String> {
+            self.client
+                .blob_client(key)
+                .get_content()
+                .await
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_bucket_url() {
+        assert!(is_bucket_url(&Url::parse("s3://docs/site/").unwrap()));
+        assert!(is_bucket_url(&Url::parse("gs://docs").unwrap()));
+        assert!(is_bucket_url(&Url::parse("az://docs/site").unwrap()));
+        assert!(!is_bucket_url(&Url::parse("https://docs.com").unwrap()));
+    }
+
+    #[cfg(not(feature = "s3"))]
+    #[tokio::test]
+    async fn test_missing_feature() {
+        let url = Url::parse("s3://docs/site/").unwrap();
+        let Err(e) = Bucket::connect(&url).await else {
+            panic!("connected to bucket without `s3` feature");
+        };
+        assert!(e.to_string().contains("without the `s3` feature"));
+    }
+}
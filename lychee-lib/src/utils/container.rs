@@ -0,0 +1,572 @@
+//! Support for container images as inputs
+//!
+//! Images are read from a local [OCI image layout](https://github.com/opencontainers/image-spec/blob/main/image-layout.md)
+//! (`oci:path/to/layout`) or pulled from a registry (`docker://alpine:3.20`).
+//! Only the files matching the given glob patterns (by default
+//! `/usr/share/doc/**`) are unpacked from the layers of the image.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::{self, Read, SeekFrom},
+    path::{Component, Path, PathBuf},
+};
+
+use flate2::read::GzDecoder;
+use glob::Pattern;
+use http::{
+    header::{ACCEPT, WWW_AUTHENTICATE},
+    StatusCode,
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::{Client, Response, Url};
+use serde::Deserialize;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+use crate::{
+    utils::archive::{self, UnpackedDir},
+    ErrorKind, Result,
+};
+
+/// Files which are unpacked from container images by default
+pub(crate) const DEFAULT_IMAGE_PATHS: &[&str] = &["/usr/share/doc/**"];
+
+/// Prefix of inputs which refer to a local OCI image layout
+const OCI_PREFIX: &str = "oci:";
+
+/// Prefix of inputs which refer to an image in a registry
+const DOCKER_PREFIX: &str = "docker://";
+
+/// Registry used for image names without a registry, like `alpine`
+const DOCKER_HUB: &str = "registry-1.docker.io";
+
+/// Maximum nesting of image indexes
+const MAX_DEPTH: usize = 3;
+
+/// Manifest types accepted from registries
+const MANIFEST_TYPES: &str = "application/vnd.oci.image.index.v1+json, \
+    application/vnd.oci.image.manifest.v1+json, \
+    application/vnd.docker.distribution.manifest.list.v2+json, \
+    application/vnd.docker.distribution.manifest.v2+json";
+
+/// Matches the parameters of a `WWW-Authenticate` challenge,
+/// e.g. `realm="https://auth.docker.io/token"`
+static CHALLENGE_PARAM: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(\w+)="([^"]*)""#).unwrap());
+
+/// Returns `true` if `value` refers to a container image
+/// (`oci:<layout>` or `docker://<image>`)
+pub(crate) fn is_image_reference(value: &str) -> bool {
+    value.starts_with(OCI_PREFIX) || value.starts_with(DOCKER_PREFIX)
+}
+
+/// An image manifest or an image index (a manifest per platform)
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    /// Manifests of an image index
+    #[serde(default)]
+    manifests: Vec<Descriptor>,
+    /// Layers of an image manifest
+    #[serde(default)]
+    layers: Vec<Descriptor>,
+}
+
+/// Reference to a blob (a manifest or a layer)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Descriptor {
+    #[serde(default)]
+    media_type: String,
+    digest: String,
+    platform: Option<Platform>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Platform {
+    architecture: String,
+    os: String,
+}
+
+/// Response of a registry token endpoint
+#[derive(Debug, Deserialize)]
+struct Token {
+    token: Option<String>,
+    access_token: Option<String>,
+}
+
+/// Unpack the files matching `paths` from all layers of the container image
+/// `reference` into a new temporary directory.
+///
+/// Files deleted in upper layers (whiteouts) are removed again.
+///
+/// # Errors
+///
+/// Returns an error if a pattern is invalid, the image can't be read or if
+/// the unpacked files exceed [`archive::MAX_UNPACKED_SIZE`].
+pub(crate) async fn unpack(reference: &str, paths: &[String]) -> Result<UnpackedDir> {
+    let invalid =
+        |e: io::Error| ErrorKind::InvalidContainerImage(reference.to_string(), e.to_string());
+    let patterns = paths
+        .iter()
+        .map(|path| Pattern::new(path))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let mut source = Source::new(reference).map_err(invalid)?;
+    let dir = archive::temp_dir().map_err(invalid)?;
+    let dest = dir.path().to_path_buf();
+    let mut budget = archive::MAX_UNPACKED_SIZE;
+    for layer in source.layers().await.map_err(invalid)? {
+        let blob = source.blob(&layer.digest).await.map_err(invalid)?;
+        let (patterns, dest) = (patterns.clone(), dest.clone());
+        // Decompressing and writing the files blocks
+        budget = tokio::task::spawn_blocking(move || {
+            unpack_layer(blob, &layer.media_type, &patterns, &dest, &mut budget).map(|()| budget)
+        })
+        .await?
+        .map_err(invalid)?;
+    }
+    Ok(UnpackedDir::new(dir))
+}
+
+/// Where the manifests and layers of an image come from
+enum Source {
+    /// Directory with an OCI image layout
+    Layout(PathBuf),
+    /// Repository in a registry, using the distribution API
+    Registry(Registry),
+}
+
+impl Source {
+    fn new(reference: &str) -> io::Result<Self> {
+        if let Some(layout) = reference.strip_prefix(OCI_PREFIX) {
+            return Ok(Self::Layout(PathBuf::from(layout)));
+        }
+        let (repository, tag) = reference
+            .strip_prefix(DOCKER_PREFIX)
+            .and_then(repository_url)
+            .ok_or_else(|| invalid_data("invalid image reference"))?;
+        Ok(Self::Registry(Registry {
+            client: Client::new(),
+            repository,
+            tag,
+            token: None,
+        }))
+    }
+
+    /// Resolve the image (and image indexes) to the layers of a single
+    /// platform, preferring `linux/amd64`
+    async fn layers(&mut self) -> io::Result<Vec<Descriptor>> {
+        let mut manifest: Manifest = match self {
+            Self::Layout(layout) => {
+                serde_json::from_slice(&tokio::fs::read(layout.join("index.json")).await?)?
+            }
+            Self::Registry(registry) => {
+                let tag = registry.tag.clone();
+                registry.manifest(&tag).await?
+            }
+        };
+        for _ in 0..MAX_DEPTH {
+            let Some(first) = manifest.manifests.first() else {
+                return Ok(manifest.layers);
+            };
+            let digest = manifest
+                .manifests
+                .iter()
+                .find(|m| {
+                    m.platform
+                        .as_ref()
+                        .is_some_and(|p| p.os == "linux" && p.architecture == "amd64")
+                })
+                .unwrap_or(first)
+                .digest
+                .clone();
+            manifest = self.manifest(&digest).await?;
+        }
+        Err(invalid_data("too deeply nested image indexes"))
+    }
+
+    async fn manifest(&mut self, digest: &str) -> io::Result<Manifest> {
+        match self {
+            Self::Layout(layout) => Ok(serde_json::from_slice(
+                &tokio::fs::read(blob_path(layout, digest)?).await?,
+            )?),
+            Self::Registry(registry) => registry.manifest(digest).await,
+        }
+    }
+
+    async fn blob(&mut self, digest: &str) -> io::Result<File> {
+        match self {
+            Self::Layout(layout) => Ok(tokio::fs::File::open(blob_path(layout, digest)?)
+                .await?
+                .into_std()
+                .await),
+            Self::Registry(registry) => registry.blob(digest).await,
+        }
+    }
+}
+
+/// Repository in a registry
+struct Registry {
+    client: Client,
+    /// URL of the repository, e.g. `https://registry-1.docker.io/v2/library/alpine`
+    repository: String,
+    /// Tag or digest of the image
+    tag: String,
+    /// Bearer token for anonymous access, requested on demand
+    token: Option<String>,
+}
+
+impl Registry {
+    async fn manifest(&mut self, reference: &str) -> io::Result<Manifest> {
+        let url = format!("{}/manifests/{reference}", self.repository);
+        let response = self.get(&url, Some(MANIFEST_TYPES)).await?;
+        let body = response.bytes().await.map_err(io_error)?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Download a blob into an anonymous temporary file
+    async fn blob(&mut self, digest: &str) -> io::Result<File> {
+        let url = format!("{}/blobs/{digest}", self.repository);
+        let mut response = self.get(&url, None).await?;
+        let mut file = tokio::fs::File::from_std(tempfile::tempfile()?);
+        while let Some(chunk) = response.chunk().await.map_err(io_error)? {
+            file.write_all(&chunk).await?;
+        }
+        file.seek(SeekFrom::Start(0)).await?;
+        Ok(file.into_std().await)
+    }
+
+    /// Send a `GET` request, requesting a token if the registry asks for one
+    async fn get(&mut self, url: &str, accept: Option<&str>) -> io::Result<Response> {
+        let mut response = self.send(url, accept).await?;
+        if response.status() == StatusCode::UNAUTHORIZED && self.token.is_none() {
+            if let Some(challenge) = response
+                .headers()
+                .get(WWW_AUTHENTICATE)
+                .and_then(|value| value.to_str().ok())
+                .map(ToOwned::to_owned)
+            {
+                self.token = Some(self.authenticate(&challenge).await?);
+                response = self.send(url, accept).await?;
+            }
+        }
+        response.error_for_status().map_err(io_error)
+    }
+
+    async fn send(&self, url: &str, accept: Option<&str>) -> io::Result<Response> {
+        let mut request = self.client.get(url);
+        if let Some(accept) = accept {
+            request = request.header(ACCEPT, accept);
+        }
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        request.send().await.map_err(io_error)
+    }
+
+    /// Request an anonymous token as described by the `WWW-Authenticate`
+    /// `challenge` of the registry
+    async fn authenticate(&self, challenge: &str) -> io::Result<String> {
+        let params: HashMap<&str, &str> = CHALLENGE_PARAM
+            .captures_iter(challenge)
+            .filter_map(|c| Some((c.get(1)?.as_str(), c.get(2)?.as_str())))
+            .collect();
+        let realm = params
+            .get("realm")
+            .ok_or_else(|| invalid_data("missing realm in authentication challenge"))?;
+        let query = params
+            .iter()
+            .filter(|(key, _)| **key != "realm")
+            .map(|(key, value)| (*key, *value));
+        let url = Url::parse_with_params(realm, query).map_err(io_error)?;
+
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .and_then(Response::error_for_status)
+            .map_err(io_error)?;
+        let token: Token = serde_json::from_slice(&response.bytes().await.map_err(io_error)?)?;
+        token
+            .token
+            .or(token.access_token)
+            .ok_or_else(|| invalid_data("missing token in authentication response"))
+    }
+}
+
+/// Split an image name like `ghcr.io/owner/image:tag` into the URL of the
+/// repository and the tag (or digest)
+fn repository_url(image: &str) -> Option<(String, String)> {
+    let (name, tag) = match image.split_once('@') {
+        Some((name, digest)) => (name, digest),
+        None => match image.rsplit_once(':') {
+            Some((name, tag)) if !tag.contains('/') => (name, tag),
+            _ => (image, "latest"),
+        },
+    };
+    if name.is_empty() || tag.is_empty() {
+        return None;
+    }
+    let (registry, repository) = match name.split_once('/') {
+        Some(("docker.io", path)) => (DOCKER_HUB, path.to_string()),
+        Some((host, path)) if host.contains(['.', ':']) || host == "localhost" => {
+            (host, path.to_string())
+        }
+        _ => (DOCKER_HUB, name.to_string()),
+    };
+    // Official images on Docker Hub live in the `library` namespace
+    let repository = if registry == DOCKER_HUB && !repository.contains('/') {
+        format!("library/{repository}")
+    } else {
+        repository
+    };
+    // Local registries usually don't have a certificate
+    let scheme = if registry.starts_with("localhost") || registry.starts_with("127.0.0.1") {
+        "http"
+    } else {
+        "https"
+    };
+    Some((
+        format!("{scheme}://{registry}/v2/{repository}"),
+        tag.to_string(),
+    ))
+}
+
+/// Path of the blob with the given digest in an OCI image layout
+fn blob_path(layout: &Path, digest: &str) -> io::Result<PathBuf> {
+    match digest.split_once(':') {
+        Some((algorithm, hash))
+            if [algorithm, hash]
+                .iter()
+                .all(|s| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric())) =>
+        {
+            Ok(layout.join("blobs").join(algorithm).join(hash))
+        }
+        _ => Err(invalid_data("invalid digest")),
+    }
+}
+
+/// Unpack the regular files of a layer which match one of `patterns`
+fn unpack_layer(
+    blob: File,
+    media_type: &str,
+    patterns: &[Pattern],
+    dest: &Path,
+    budget: &mut u64,
+) -> io::Result<()> {
+    let reader: Box<dyn Read> = match media_type.rsplit(['+', '.']).next() {
+        Some("gzip") => Box::new(GzDecoder::new(blob)),
+        Some("tar") => Box::new(blob),
+        _ => {
+            return Err(invalid_data(&format!(
+                "unsupported layer type `{media_type}`"
+            )))
+        }
+    };
+    // Files of this layer, which are kept by opaque whiteouts
+    let mut written = HashSet::new();
+    for entry in tar::Archive::new(reader).entries()? {
+        let mut entry = entry?;
+        let Some(path) = archive::enclosed_path(&entry.path()?) else {
+            continue;
+        };
+        // Normalize `./usr/share` to `usr/share`
+        let path: PathBuf = path
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .collect();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        if let Some(deleted) = name.strip_prefix(".wh.") {
+            let parent = dest.join(path.parent().unwrap_or(Path::new("")));
+            if deleted == ".wh..opq" {
+                // Opaque whiteout: hide everything from lower layers
+                remove_lower(&parent, &written)?;
+            } else {
+                remove(&parent.join(deleted))?;
+            }
+            continue;
+        }
+
+        if !entry.header().entry_type().is_file()
+            || !patterns
+                .iter()
+                .any(|pattern| pattern.matches_path(&Path::new("/").join(&path)))
+        {
+            continue;
+        }
+        let dest = dest.join(&path);
+        archive::write_entry(&mut entry, &dest, budget)?;
+        written.insert(dest);
+    }
+    Ok(())
+}
+
+/// Remove the files below `dir` which were unpacked from lower layers, i.e.
+/// which are not in `written`
+fn remove_lower(dir: &Path, written: &HashSet<PathBuf>) -> io::Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        entries => entries?,
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            remove_lower(&path, written)?;
+        } else if !written.contains(&path) {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove a file or directory, if it exists
+fn remove(path: &Path) -> io::Result<()> {
+    let removed = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    match removed {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn io_error(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+
+    #[test]
+    fn test_repository_url() {
+        let check = |image, url: &str, tag: &str| {
+            assert_eq!(
+                repository_url(image),
+                Some((url.to_string(), tag.to_string())),
+                "{image}"
+            );
+        };
+        check(
+            "alpine",
+            "https://registry-1.docker.io/v2/library/alpine",
+            "latest",
+        );
+        check(
+            "docker.io/owner/image:1.0",
+            "https://registry-1.docker.io/v2/owner/image",
+            "1.0",
+        );
+        check(
+            "ghcr.io/owner/image@sha256:abc",
+            "https://ghcr.io/v2/owner/image",
+            "sha256:abc",
+        );
+        check(
+            "localhost:5000/image",
+            "http://localhost:5000/v2/image",
+            "latest",
+        );
+        assert_eq!(repository_url(":tag"), None);
+    }
+
+    #[test]
+    fn test_blob_path() {
+        let layout = Path::new("layout");
+        assert_eq!(
+            blob_path(layout, "sha256:abc").unwrap(),
+            layout.join("blobs").join("sha256").join("abc")
+        );
+        assert!(blob_path(layout, "sha256:../../etc").is_err());
+        assert!(blob_path(layout, "abc").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_oci_layout() {
+        let layout = tempfile::tempdir().unwrap();
+        let blobs = layout.path().join("blobs").join("sha256");
+        fs::create_dir_all(&blobs).unwrap();
+
+        let mut tar = tar::Builder::new(GzEncoder::new(
+            File::create(blobs.join("layer")).unwrap(),
+            Compression::default(),
+        ));
+        for (path, content) in [
+            ("usr/share/doc/pkg/README.md", "[home](https://example.com)"),
+            ("usr/share/doc/pkg/.wh.removed.md", ""),
+            ("etc/motd", "https://example.org"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_cksum();
+            tar.append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap();
+
+        fs::write(
+            blobs.join("manifest"),
+            r#"{"layers": [{"mediaType": "application/vnd.oci.image.layer.v1.tar+gzip", "digest": "sha256:layer"}]}"#,
+        )
+        .unwrap();
+        fs::write(
+            layout.path().join("index.json"),
+            r#"{"manifests": [{"mediaType": "application/vnd.oci.image.manifest.v1+json", "digest": "sha256:manifest"}]}"#,
+        )
+        .unwrap();
+
+        let reference = format!("oci:{}", layout.path().display());
+        let unpacked = unpack(&reference, &["/usr/share/doc/**".to_string()])
+            .await
+            .unwrap();
+        let root = unpacked.path().unwrap();
+        assert!(root.join("usr/share/doc/pkg/README.md").is_file());
+        assert!(!root.join("usr/share/doc/pkg/.wh.removed.md").exists());
+        assert!(!root.join("etc/motd").exists());
+    }
+
+    /// A tar layer with the given files
+    fn layer(files: &[(&str, &str)]) -> File {
+        let mut tar = tar::Builder::new(tempfile::tempfile().unwrap());
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_cksum();
+            tar.append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        let mut file = tar.into_inner().unwrap();
+        io::Seek::seek(&mut file, SeekFrom::Start(0)).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_opaque_whiteout() {
+        let dest = tempfile::tempdir().unwrap();
+        let patterns = [Pattern::new("/**").unwrap()];
+        let mut budget = archive::MAX_UNPACKED_SIZE;
+        let media_type = "application/vnd.oci.image.layer.v1.tar";
+
+        let lower = layer(&[("doc/old.md", "old"), ("doc/api/old.md", "old")]);
+        unpack_layer(lower, media_type, &patterns, dest.path(), &mut budget).unwrap();
+        // The whiteout follows a file of its own layer, which is kept
+        let upper = layer(&[("doc/new.md", "new"), ("doc/.wh..wh..opq", "")]);
+        unpack_layer(upper, media_type, &patterns, dest.path(), &mut budget).unwrap();
+
+        assert!(dest.path().join("doc/new.md").is_file());
+        assert!(!dest.path().join("doc/old.md").exists());
+        assert!(!dest.path().join("doc/api/old.md").exists());
+    }
+}
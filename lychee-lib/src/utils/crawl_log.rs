@@ -0,0 +1,227 @@
+//! Append-only log of a recursive crawl, so that crawls of large websites
+//! survive restarts.
+//!
+//! Each line of the log is an entry with fields separated by tabs:
+//!
+//! - `root <url>`: the crawl started from the remote input `url`
+//! - `page <depth> <root> <url>`: the page `url` was discovered from the
+//!   remote input `root` and is collected at `depth`
+//! - `done <depth>`: all pages up to `depth` were collected
+//!
+//! Entries are written as soon as they happen. A line which was only partially
+//! written when lychee stopped is ignored.
+//!
+//! The pages to collect at the next depth are read back from the log, so
+//! they aren't kept in memory.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use reqwest::Url;
+
+/// A page of the crawl, as recorded in the log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LoggedPage {
+    pub(crate) depth: usize,
+    pub(crate) root: Url,
+    pub(crate) url: Url,
+}
+
+/// The entries of previous runs
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Resumed {
+    /// The remote inputs from which the crawl started
+    pub(crate) roots: Vec<Url>,
+    /// The deepest depth up to which all pages were collected
+    pub(crate) done: Option<usize>,
+    pub(crate) pages: Vec<LoggedPage>,
+}
+
+/// The log of a crawl, which is appended to while pages are discovered
+#[derive(Debug)]
+pub(crate) struct CrawlLog {
+    path: PathBuf,
+    file: File,
+}
+
+impl CrawlLog {
+    /// Open the log at `path`, creating it if necessary, and read the entries
+    /// of previous runs
+    pub(crate) fn open(path: &Path) -> io::Result<(Self, Resumed)> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        // Don't continue a partially written line
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            file.write_all(b"\n")?;
+        }
+
+        let log = CrawlLog {
+            path: path.to_path_buf(),
+            file,
+        };
+        Ok((log, parse(&contents)))
+    }
+
+    /// The file of the log
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Discard the entries of previous runs, e.g. of a crawl of other inputs
+    pub(crate) fn clear(&mut self) -> io::Result<()> {
+        self.file.set_len(0)
+    }
+
+    /// Record that the crawl started from `roots`
+    pub(crate) fn roots(&mut self, roots: &[Url]) -> io::Result<()> {
+        let lines: String = roots.iter().map(|root| format!("root\t{root}\n")).collect();
+        self.append(&lines)
+    }
+
+    /// The pages which were logged at `depth`
+    pub(crate) fn pages_at(&self, depth: usize) -> io::Result<Vec<LoggedPage>> {
+        let mut pages = Vec::new();
+        for line in BufReader::new(File::open(&self.path)?).lines() {
+            let mut entry = Resumed::default();
+            parse_line(&line?, &mut entry);
+            pages.extend(entry.pages.into_iter().filter(|page| page.depth == depth));
+        }
+        Ok(pages)
+    }
+
+    /// Record that `url` was discovered from `root` and is collected at
+    /// `depth`
+    pub(crate) fn page(&mut self, depth: usize, root: &Url, url: &Url) -> io::Result<()> {
+        self.append(&format!("page\t{depth}\t{root}\t{url}\n"))
+    }
+
+    /// Record that all pages up to `depth` were collected
+    pub(crate) fn done(&mut self, depth: usize) -> io::Result<()> {
+        self.append(&format!("done\t{depth}\n"))
+    }
+
+    /// Remove the log once the crawl is complete, so that the next run starts
+    /// over
+    pub(crate) fn remove(self) -> io::Result<()> {
+        drop(self.file);
+        fs::remove_file(self.path)
+    }
+
+    fn append(&mut self, line: &str) -> io::Result<()> {
+        // A single write, so that interruptions only cut off the last line
+        self.file.write_all(line.as_bytes())
+    }
+}
+
+/// Read the entries of a log
+fn parse(contents: &str) -> Resumed {
+    let mut resumed = Resumed::default();
+    for line in contents.lines() {
+        parse_line(line, &mut resumed);
+    }
+    resumed
+}
+
+/// Add the entry of `line` to `resumed`, skipping lines which were only
+/// partially written
+fn parse_line(line: &str, resumed: &mut Resumed) {
+    let fields: Vec<&str> = line.split('\t').collect();
+    match fields.as_slice() {
+        ["root", root] => {
+            if let Ok(root) = Url::parse(root) {
+                resumed.roots.push(root);
+            }
+        }
+        ["page", depth, root, url] => {
+            if let (Ok(depth), Ok(root), Ok(url)) =
+                (depth.parse(), Url::parse(root), Url::parse(url))
+            {
+                resumed.pages.push(LoggedPage { depth, root, url });
+            }
+        }
+        ["done", depth] => {
+            if let Ok(depth) = depth.parse() {
+                resumed.done = resumed.done.max(Some(depth));
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn test_crawl_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("crawl.log");
+        let root = url("https://example.com/");
+
+        let (mut log, resumed) = CrawlLog::open(&path).unwrap();
+        assert_eq!(resumed, Resumed::default());
+        log.roots(&[root.clone()]).unwrap();
+        log.page(1, &root, &url("https://example.com/a")).unwrap();
+        log.done(0).unwrap();
+        log.page(2, &root, &url("https://example.com/b")).unwrap();
+        drop(log);
+
+        // Interrupted while writing an entry
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"page\t2\thttps://exa").unwrap();
+        drop(file);
+
+        let (mut log, resumed) = CrawlLog::open(&path).unwrap();
+        assert_eq!(resumed.roots, [root.clone()]);
+        assert_eq!(resumed.done, Some(0));
+        assert_eq!(
+            resumed.pages,
+            [
+                LoggedPage {
+                    depth: 1,
+                    root: root.clone(),
+                    url: url("https://example.com/a"),
+                },
+                LoggedPage {
+                    depth: 2,
+                    root: root.clone(),
+                    url: url("https://example.com/b"),
+                },
+            ]
+        );
+
+        assert_eq!(
+            log.pages_at(2).unwrap(),
+            [LoggedPage {
+                depth: 2,
+                root: root.clone(),
+                url: url("https://example.com/b"),
+            }]
+        );
+
+        log.done(1).unwrap();
+        drop(log);
+        let (mut log, resumed) = CrawlLog::open(&path).unwrap();
+        assert_eq!(resumed.done, Some(1));
+        assert_eq!(resumed.pages.len(), 2);
+
+        log.clear().unwrap();
+        drop(log);
+        let (log, resumed) = CrawlLog::open(&path).unwrap();
+        assert_eq!(resumed, Resumed::default());
+
+        log.remove().unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,244 @@
+//! Decoding of inputs which aren't UTF-8, e.g. Latin-1 or Shift-JIS
+//! documentation.
+
+use std::io::{self, Read};
+
+use encoding_rs::{Decoder, Encoding, UTF_8, WINDOWS_1252};
+use log::warn;
+
+/// Number of bytes searched for NUL bytes to detect binary files (like git)
+const BINARY_DETECTION_LENGTH: usize = 8000;
+
+/// Check if the content looks like a binary file (e.g. an image or a PDF)
+/// instead of text, because a NUL byte appears near its start
+pub(crate) fn is_binary(bytes: &[u8]) -> bool {
+    bytes
+        .iter()
+        .take(BINARY_DETECTION_LENGTH)
+        .any(|&byte| byte == 0)
+}
+
+/// Decode the raw bytes of an input into a string.
+///
+/// The encoding is detected in this order:
+/// 1. A byte order mark (BOM) for UTF-8 or UTF-16
+/// 2. The given `encoding` (e.g. from `--input-encoding`)
+/// 3. UTF-8, if the input is valid UTF-8
+/// 4. Windows-1252 (a superset of Latin-1) as fallback
+///
+/// Invalid byte sequences are replaced with `U+FFFD` instead of aborting,
+/// so that the remaining links can still be checked. `source` is only used
+/// for warnings.
+pub(crate) fn decode(bytes: &[u8], encoding: Option<&'static Encoding>, source: &str) -> String {
+    let (encoding, bytes) = match Encoding::for_bom(bytes) {
+        Some((encoding, bom_length)) => (encoding, &bytes[bom_length..]),
+        None => match encoding {
+            Some(encoding) => (encoding, bytes),
+            None => match std::str::from_utf8(bytes) {
+                Ok(content) => return content.to_string(),
+                Err(_) if is_binary(bytes) => (WINDOWS_1252, bytes),
+                Err(_) => {
+                    warn!(
+                        "{source} is not valid UTF-8, decoding it as {}. Use `--input-encoding` to set the encoding",
+                        WINDOWS_1252.name()
+                    );
+                    (WINDOWS_1252, bytes)
+                }
+            },
+        },
+    };
+
+    let (content, had_errors) = encoding.decode_without_bom_handling(bytes);
+    if had_errors {
+        warn!(
+            "{source} contains invalid {} sequences, which were replaced",
+            encoding.name()
+        );
+    }
+    content.into_owned()
+}
+
+/// Number of bytes decoded at once by a [`DecodingReader`]
+const DECODE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A reader which decodes another reader to UTF-8 on the fly, for inputs
+/// which are too large to be decoded at once with [`decode`].
+///
+/// The encoding is detected like in [`decode`], except that only the start
+/// of the input is checked for valid UTF-8.
+pub(crate) struct DecodingReader<R> {
+    inner: R,
+    decoder: Decoder,
+    /// Raw bytes which were read from `inner`, but not decoded yet
+    pending: Vec<u8>,
+    /// Decoded bytes, the ones before `position` were returned already
+    decoded: Vec<u8>,
+    position: usize,
+    eof: bool,
+    had_errors: bool,
+    source: String,
+}
+
+impl<R: Read> DecodingReader<R> {
+    /// Create a reader which decodes `inner`; `source` is only used for
+    /// warnings
+    pub(crate) fn new(
+        mut inner: R,
+        encoding: Option<&'static Encoding>,
+        source: &str,
+    ) -> io::Result<Self> {
+        let mut start = Vec::new();
+        inner
+            .by_ref()
+            .take(BINARY_DETECTION_LENGTH as u64)
+            .read_to_end(&mut start)?;
+
+        let encoding = encoding.unwrap_or_else(|| match std::str::from_utf8(&start) {
+            // The start might end in the middle of a character
+            Err(e) if e.error_len().is_some() => {
+                if !is_binary(&start) {
+                    warn!(
+                        "{source} is not valid UTF-8, decoding it as {}. Use `--input-encoding` to set the encoding",
+                        WINDOWS_1252.name()
+                    );
+                }
+                WINDOWS_1252
+            }
+            _ => UTF_8,
+        });
+
+        Ok(Self {
+            inner,
+            // A byte order mark takes precedence over the encoding
+            decoder: encoding.new_decoder(),
+            pending: start,
+            decoded: Vec::new(),
+            position: 0,
+            eof: false,
+            had_errors: false,
+            source: source.to_string(),
+        })
+    }
+
+    /// Check if the input looks like a binary file (see [`is_binary`])
+    pub(crate) fn is_binary(&self) -> bool {
+        is_binary(&self.pending)
+    }
+
+    /// Decode the next chunk of the input
+    fn decode_chunk(&mut self) -> io::Result<()> {
+        if self.pending.is_empty() {
+            self.pending.resize(DECODE_CHUNK_SIZE, 0);
+            match self.inner.read(&mut self.pending) {
+                Ok(read) => {
+                    self.pending.truncate(read);
+                    self.eof = read == 0;
+                }
+                Err(e) => {
+                    self.pending.clear();
+                    return Err(e);
+                }
+            }
+        }
+
+        let max_len = self
+            .decoder
+            .max_utf8_buffer_length(self.pending.len())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "input too large"))?;
+        self.decoded.resize(max_len, 0);
+        let (_, _, written, had_errors) =
+            self.decoder
+                .decode_to_utf8(&self.pending, &mut self.decoded, self.eof);
+        self.decoded.truncate(written);
+        self.position = 0;
+        self.pending.clear();
+
+        if had_errors && !self.had_errors {
+            self.had_errors = true;
+            warn!(
+                "{} contains invalid {} sequences, which were replaced",
+                self.source,
+                self.decoder.encoding().name()
+            );
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.decoded.len() {
+            if self.eof {
+                return Ok(0);
+            }
+            self.decode_chunk()?;
+        }
+        let len = (&self.decoded[self.position..]).read(buf)?;
+        self.position += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding_rs::SHIFT_JIS;
+
+    use super::*;
+
+    #[test]
+    fn test_is_binary() {
+        assert!(!is_binary(b"# Readme\nhttps://example.com"));
+        assert!(is_binary(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"));
+        // Only the start of the content is checked
+        let mut late_nul = vec![b'a'; BINARY_DETECTION_LENGTH];
+        late_nul.push(0);
+        assert!(!is_binary(&late_nul));
+    }
+
+    #[test]
+    fn test_decode_utf8() {
+        assert_eq!(decode("héllo".as_bytes(), None, "test"), "héllo");
+        // A UTF-8 BOM is stripped
+        assert_eq!(decode(b"\xEF\xBB\xBFhello", None, "test"), "hello");
+    }
+
+    #[test]
+    fn test_decode_bom_wins_over_encoding() {
+        let utf16 = b"\xFF\xFEh\0i\0";
+        assert_eq!(decode(utf16, Some(SHIFT_JIS), "test"), "hi");
+    }
+
+    #[test]
+    fn test_decode_latin1_fallback() {
+        assert_eq!(decode(b"caf\xE9", None, "test"), "café");
+    }
+
+    #[test]
+    fn test_decode_with_encoding() {
+        let (bytes, _, _) = SHIFT_JIS.encode("リンク https://example.com");
+        assert_eq!(
+            decode(&bytes, Some(SHIFT_JIS), "test"),
+            "リンク https://example.com"
+        );
+        // Without the encoding, the Shift-JIS input is decoded lossily,
+        // but the link is still intact
+        assert!(decode(&bytes, None, "test").ends_with("https://example.com"));
+    }
+
+    #[test]
+    fn test_decoding_reader() {
+        let mut content = String::new();
+        DecodingReader::new(&b"caf\xE9 https://example.com"[..], None, "test")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "café https://example.com");
+
+        let mut content = String::new();
+        DecodingReader::new("\u{FEFF}héllo".as_bytes(), Some(SHIFT_JIS), "test")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "héllo");
+    }
+}
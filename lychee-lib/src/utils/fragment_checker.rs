@@ -1,13 +1,19 @@
 use std::{
     collections::{hash_map::Entry, HashMap, HashSet},
-    path::Path,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 
 use crate::{
-    extract::{html::html5gum::extract_html_fragments, markdown::extract_markdown_fragments},
-    types::FileType,
-    Result,
+    extract::{
+        html::html5gum::{extract_html_fragment_spans, extract_html_fragments},
+        markdown::extract_markdown_fragment_spans,
+    },
+    types::{
+        uri::raw::{RawUriSpan, SourceSpanProvider},
+        FileType, ParsedFile,
+    },
+    AnchorCache, FragmentStyle, Result,
 };
 use percent_encoding::percent_decode_str;
 use tokio::{fs, sync::Mutex};
@@ -22,21 +28,41 @@ use url::Url;
 /// This cache is used to avoid having to re-parse the same file
 /// multiple times when checking if a given URL contains a fragment.
 ///
-/// The cache is stored in a `HashMap` with the URL as the key and
-/// a `HashSet` of fragments as the value.
+/// The cache is stored in a `HashMap` with the URL (or the canonical path
+/// of local files) as the key and a `HashSet` of fragments as the value.
+///
+/// Fragments which occur more than once in a local file are collected by
+/// path, because links to them are ambiguous.
 #[derive(Default, Clone, Debug)]
 pub(crate) struct FragmentChecker {
     cache: Arc<Mutex<HashMap<String, HashSet<String>>>>,
+    /// Duplicate fragments of the local files which were checked
+    duplicates: DuplicateFragments,
+    /// How the fragments of Markdown headings are generated
+    style: FragmentStyle,
+    /// Fragments of local files from previous runs, keyed by file content
+    anchor_cache: Option<AnchorCache>,
 }
 
 impl FragmentChecker {
-    /// Creates a new `FragmentChecker`.
-    pub(crate) fn new() -> Self {
+    /// Creates a new `FragmentChecker`, which generates the fragments of
+    /// Markdown headings according to `style`. The fragments of local files
+    /// are looked up in and added to `anchor_cache`, if given.
+    pub(crate) fn new(style: FragmentStyle, anchor_cache: Option<AnchorCache>) -> Self {
         Self {
             cache: Arc::default(),
+            duplicates: Arc::default(),
+            style,
+            anchor_cache,
         }
     }
 
+    /// The fragments which occur more than once in the local files which
+    /// were checked so far, with the positions of the repetitions
+    pub(crate) fn duplicates(&self) -> DuplicateFragments {
+        Arc::clone(&self.duplicates)
+    }
+
     /// Checks if the given path contains the given fragment.
     ///
     /// Returns false, if there is a fragment in the link and the path is to a
@@ -48,35 +74,182 @@ impl FragmentChecker {
             return Ok(true);
         };
         let mut fragment_decoded = percent_decode_str(fragment).decode_utf8()?;
-        let url_without_frag = Self::remove_fragment(url.clone());
 
         let file_type = FileType::from(path);
-        let extractor = match file_type {
-            FileType::Markdown => extract_markdown_fragments,
-            FileType::Html => extract_html_fragments,
-            FileType::Plaintext => return Ok(true),
-        };
-        if file_type == FileType::Markdown {
-            fragment_decoded = fragment_decoded.to_lowercase().into();
+        match file_type {
+            FileType::Markdown => fragment_decoded = fragment_decoded.to_lowercase().into(),
+            // Fragments of SVG images refer to the `id` of elements, like in HTML
+            FileType::Html | FileType::Svg => (),
+            FileType::Plaintext | FileType::Ipynb => return Ok(true),
         }
-        match self.cache.lock().await.entry(url_without_frag) {
+        // Keyed by path, so that the fragments can be forgotten when the file
+        // changes, no matter which URLs point to it
+        let key = Self::path_key(
+            &fs::canonicalize(path)
+                .await
+                .unwrap_or_else(|_| path.to_path_buf()),
+        );
+        match self.cache.lock().await.entry(key) {
             Entry::Vacant(entry) => {
                 let content = fs::read_to_string(path).await?;
-                let file_frags = extractor(&content);
+                let parse = |content: &str| {
+                    let fragments = if file_type == FileType::Markdown {
+                        extract_markdown_fragment_spans(content, &self.style)
+                    } else {
+                        extract_html_fragment_spans(
+                            content,
+                            &SourceSpanProvider::from_input(content),
+                        )
+                    };
+                    ParsedFile {
+                        duplicates: duplicates(&fragments),
+                        fragments: fragments
+                            .into_iter()
+                            .map(|(fragment, _)| fragment)
+                            .collect(),
+                    }
+                };
+                // Duplicates are kept in the anchor cache, so that they are
+                // reported in every run
+                let parsed = match &self.anchor_cache {
+                    Some(anchor_cache) => {
+                        anchor_cache.get_or_parse(file_type, &self.style, &content, parse)
+                    }
+                    None => parse(&content),
+                };
+                let mut duplicates = self.duplicates.lock().unwrap_or_else(|e| e.into_inner());
+                if parsed.duplicates.is_empty() {
+                    duplicates.remove(path);
+                } else {
+                    duplicates.insert(path.to_path_buf(), parsed.duplicates);
+                }
+                drop(duplicates);
                 let contains_fragment =
-                    file_frags.contains(fragment) || file_frags.contains(&fragment_decoded as &str);
-                entry.insert(file_frags);
+                    Self::contains(&parsed.fragments, fragment, &fragment_decoded);
+                entry.insert(parsed.fragments);
                 Ok(contains_fragment)
             }
-            Entry::Occupied(entry) => {
-                Ok(entry.get().contains(fragment)
-                    || entry.get().contains(&fragment_decoded as &str))
-            }
+            Entry::Occupied(entry) => Ok(Self::contains(entry.get(), fragment, &fragment_decoded)),
         }
     }
 
+    /// Checks if the given HTML document, downloaded from `url`, contains
+    /// the fragment of `url`.
+    ///
+    /// Fragments are matched against `id` and `name` attributes. The empty
+    /// fragment and `#top` always refer to the top of the document, so they
+    /// are considered valid.
+    ///
+    /// Returns true if there is no fragment in the URL.
+    pub(crate) async fn check_html(&self, url: &Url, content: &str) -> Result<bool> {
+        let Some(fragment) = url.fragment() else {
+            return Ok(true);
+        };
+        if fragment.is_empty() || fragment.eq_ignore_ascii_case("top") {
+            return Ok(true);
+        }
+        let fragment_decoded = percent_decode_str(fragment).decode_utf8()?;
+        let url_without_frag = Self::remove_fragment(url.clone());
+
+        let mut cache = self.cache.lock().await;
+        let frags = cache
+            .entry(url_without_frag)
+            .or_insert_with(|| extract_html_fragments(content));
+        // GitHub prefixes the ids of rendered Markdown headings with
+        // `user-content-` and resolves the unprefixed fragment via JavaScript.
+        Ok(Self::contains(frags, fragment, &fragment_decoded)
+            || frags.contains(&format!("user-content-{fragment_decoded}")))
+    }
+
+    /// Forgets the fragments of the local file at `path`, so that the file
+    /// is parsed again when its fragments are checked the next time, e.g.
+    /// after it was changed or removed.
+    pub(crate) async fn forget(&self, path: &Path) {
+        self.duplicates
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(path);
+        let mut cache = self.cache.lock().await;
+        cache.remove(&Self::path_key(path));
+        if let Ok(canonical) = fs::canonicalize(path).await {
+            cache.remove(&Self::path_key(&canonical));
+        }
+    }
+
+    fn path_key(path: &Path) -> String {
+        path.display().to_string()
+    }
+
+    fn contains(frags: &HashSet<String>, fragment: &str, fragment_decoded: &str) -> bool {
+        frags.contains(fragment) || frags.contains(fragment_decoded)
+    }
+
     fn remove_fragment(mut url: Url) -> String {
         url.set_fragment(None);
         url.into()
     }
 }
+
+/// Fragments which occur more than once in the local files which were
+/// checked, by path, with the positions of the repetitions
+pub(crate) type DuplicateFragments =
+    Arc<std::sync::Mutex<HashMap<PathBuf, Vec<(String, RawUriSpan)>>>>;
+
+/// The fragments which occur more than once, with the positions of the
+/// repetitions
+fn duplicates(fragments: &[(String, RawUriSpan)]) -> Vec<(String, RawUriSpan)> {
+    let mut seen = HashSet::new();
+    fragments
+        .iter()
+        .filter(|(fragment, _)| !seen.insert(fragment))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_forget() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("guide.md");
+        std::fs::write(&path, "# Install").unwrap();
+        let url = Url::from_file_path(&path).unwrap().join("#usage").unwrap();
+        let checker = FragmentChecker::default();
+
+        assert!(!checker.check(&path, &url).await.unwrap());
+        std::fs::write(&path, "# Install\n# Usage").unwrap();
+        assert!(!checker.check(&path, &url).await.unwrap());
+
+        checker.forget(&path).await;
+        assert!(checker.check(&path, &url).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("guide.md");
+        std::fs::write(
+            &path,
+            "# Usage\n# Usage\n## Setup {#install}\n<a id=\"install\"></a>\n",
+        )
+        .unwrap();
+        let url = Url::from_file_path(&path).unwrap().join("#usage").unwrap();
+        let anchor_cache = AnchorCache::default();
+
+        // Repeated headings get a suffix, so only `install` is ambiguous
+        for _ in 0..2 {
+            let checker =
+                FragmentChecker::new(FragmentStyle::default(), Some(anchor_cache.clone()));
+            assert!(checker.check(&path, &url).await.unwrap());
+            let duplicates = checker.duplicates();
+            let duplicates = duplicates.lock().unwrap();
+            let fragments: Vec<_> = duplicates[&path]
+                .iter()
+                .map(|(fragment, span)| (fragment.as_str(), span.to_string()))
+                .collect();
+            assert_eq!(fragments, [("install", "4:1".to_string())]);
+        }
+    }
+}
@@ -1,5 +1,12 @@
+pub(crate) mod archive;
+pub(crate) mod bucket;
+pub(crate) mod container;
+pub(crate) mod crawl_log;
+pub(crate) mod encoding;
 pub(crate) mod fragment_checker;
+pub(crate) mod paginate;
 pub(crate) mod path;
 pub(crate) mod request;
 pub(crate) mod reqwest;
+pub(crate) mod sitemap;
 pub(crate) mod url;
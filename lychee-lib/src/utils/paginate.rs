@@ -0,0 +1,184 @@
+//! Incremental fetching of paginated listings, like sitemaps or the objects
+//! of a storage bucket.
+//!
+//! Large listings can have tens of thousands of entries, so the pages are
+//! fetched one after another while the collector consumes the entries,
+//! instead of buffering the whole listing first. Requests which are rate
+//! limited by the server are retried with a backoff.
+
+use std::{collections::VecDeque, future::Future, time::Duration};
+
+use async_stream::try_stream;
+use futures::Stream;
+use http::header::RETRY_AFTER;
+use log::debug;
+use reqwest::{RequestBuilder, Response};
+
+use crate::retry::RetryExt;
+
+/// Maximum number of retries of a rate limited request
+const MAX_RETRIES: u32 = 5;
+
+/// Wait time before the first retry, doubled after each retry
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Maximum wait time between retries, also for `Retry-After` headers
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// One page of a paginated listing
+#[derive(Debug)]
+pub(crate) struct Page<T, C> {
+    /// The entries on this page
+    pub(crate) items: Vec<T>,
+    /// Cursors of the pages to fetch afterwards, e.g. a continuation token or
+    /// the sitemaps listed in a sitemap index
+    pub(crate) next: Vec<C>,
+}
+
+impl<T, C> Page<T, C> {
+    /// A page which is followed by the page at `next` (if any)
+    pub(crate) fn new(items: Vec<T>, next: Option<C>) -> Self {
+        Self {
+            items,
+            next: next.into_iter().collect(),
+        }
+    }
+}
+
+/// Stream the entries of a listing, starting with the page at `first`.
+///
+/// `fetch` is called with the cursor of each page. A page is only fetched
+/// once all entries of the previous pages were consumed, and pages are
+/// fetched in the order in which their cursors were returned. The stream
+/// ends after the first error.
+pub(crate) fn paginate<T, C, E, F, Fut>(
+    first: C,
+    mut fetch: F,
+) -> impl Stream<Item = std::result::Result<T, E>>
+where
+    F: FnMut(C) -> Fut,
+    Fut: Future<Output = std::result::Result<Page<T, C>, E>>,
+{
+    try_stream! {
+        let mut queue = VecDeque::from([first]);
+        while let Some(cursor) = queue.pop_front() {
+            let page = fetch(cursor).await?;
+            queue.extend(page.next);
+            for item in page.items {
+                yield item;
+            }
+        }
+    }
+}
+
+/// Send the request created by `request`, retrying it with an exponential
+/// backoff while the server responds with a retryable status like
+/// `429 Too Many Requests` or `503 Service Unavailable`.
+///
+/// A `Retry-After` header (in seconds) takes precedence over the backoff.
+/// The last response is returned as is once the retries are exhausted.
+///
+/// # Errors
+///
+/// Returns an error if the request can't be sent
+pub(crate) async fn send_with_backoff(
+    request: impl Fn() -> RequestBuilder,
+) -> reqwest::Result<Response> {
+    let mut backoff = INITIAL_BACKOFF;
+    for _ in 0..MAX_RETRIES {
+        let response = request().send().await?;
+        if !response.status().should_retry() {
+            return Ok(response);
+        }
+        let wait_time = retry_after(&response).unwrap_or(backoff).min(MAX_BACKOFF);
+        debug!(
+            "{} responded with {}, retrying in {wait_time:?}",
+            response.url(),
+            response.status()
+        );
+        tokio::time::sleep(wait_time).await;
+        backoff = backoff.saturating_mul(2).min(MAX_BACKOFF);
+    }
+    request().send().await
+}
+
+/// The wait time requested by the `Retry-After` header of `response`
+fn retry_after(response: &Response) -> Option<Duration> {
+    let seconds = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+    seconds.trim().parse().ok().map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use http::StatusCode;
+    use wiremock::{matchers::path, Mock, ResponseTemplate};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_paginate() {
+        let pages = paginate(0, |page| async move {
+            if page == 3 {
+                return Err("no such page");
+            }
+            let items = vec![page * 10, page * 10 + 1];
+            Ok(Page::new(items, (page < 2).then_some(page + 1)))
+        });
+        let items: Vec<_> = pages.collect().await;
+        assert_eq!(items, [Ok(0), Ok(1), Ok(10), Ok(11), Ok(20), Ok(21)]);
+    }
+
+    #[tokio::test]
+    async fn test_paginate_tree() {
+        // Like a sitemap index, a page can point to multiple other pages
+        let pages = paginate("index", |page| async move {
+            Ok::<_, ()>(match page {
+                "index" => Page {
+                    items: Vec::new(),
+                    next: vec!["a", "b"],
+                },
+                page => Page::new(vec![page], None),
+            })
+        });
+        let items: Vec<_> = pages.map(Result::unwrap).collect().await;
+        assert_eq!(items, ["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_paginate_stops_at_error() {
+        let pages = paginate(0, |page| async move {
+            if page == 1 {
+                Err("rate limited")
+            } else {
+                Ok(Page::new(vec![page], Some(page + 1)))
+            }
+        });
+        let items: Vec<_> = pages.collect().await;
+        assert_eq!(items, [Ok(0), Err("rate limited")]);
+    }
+
+    #[tokio::test]
+    async fn test_send_with_backoff() {
+        let server = wiremock::MockServer::start().await;
+        Mock::given(path("/listing"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::TOO_MANY_REQUESTS)
+                    .insert_header("Retry-After", "0"),
+            )
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(path("/listing"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = reqwest::Client::new();
+        let url = format!("{}/listing", server.uri());
+        let response = send_with_backoff(|| client.get(&url)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
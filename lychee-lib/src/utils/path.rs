@@ -16,6 +16,7 @@ static CURRENT_DIR: Lazy<PathBuf> =
 /// https://stackoverflow.com/a/54817755/270334
 #[cached]
 pub(crate) fn absolute_path(path: PathBuf) -> PathBuf {
+    let path = strip_verbatim_prefix(&path);
     if path.is_absolute() {
         path
     } else {
@@ -24,6 +25,42 @@ pub(crate) fn absolute_path(path: PathBuf) -> PathBuf {
     .clean()
 }
 
+/// Remove the verbatim prefix (`\\?\`) of a Windows path, e.g. turn
+/// `\\?\C:\docs` into `C:\docs` and `\\?\UNC\server\share` into
+/// `\\server\share`.
+///
+/// Verbatim paths are returned by `fs::canonicalize` and used for long paths.
+/// They are never normalized, so `..` components wouldn't be resolved and
+/// they wouldn't compare equal to the same path without the prefix.
+/// The standard library adds the prefix back for long paths when accessing
+/// the file system, so it can be removed safely.
+#[cfg(windows)]
+pub(crate) fn strip_verbatim_prefix(path: &Path) -> PathBuf {
+    use std::path::{Component, Prefix};
+
+    let mut components = path.components();
+    let Some(Component::Prefix(prefix)) = components.next() else {
+        return path.to_path_buf();
+    };
+    let mut stripped = match prefix.kind() {
+        Prefix::VerbatimDisk(letter) => PathBuf::from(format!("{}:", letter as char)),
+        Prefix::VerbatimUNC(server, share) => PathBuf::from(format!(
+            r"\\{}\{}",
+            server.to_string_lossy(),
+            share.to_string_lossy()
+        )),
+        _ => return path.to_path_buf(),
+    };
+    stripped.extend(components);
+    stripped
+}
+
+/// Paths don't have prefixes on other platforms
+#[cfg(not(windows))]
+pub(crate) fn strip_verbatim_prefix(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
 /// Resolve `dst` that was linked to from within `src`
 ///
 /// Returns Ok(None) in case of an absolute local link without a `base_url`
@@ -33,6 +70,15 @@ pub(crate) fn resolve(
     ignore_absolute_local_links: bool,
 ) -> Result<Option<PathBuf>> {
     let resolved = match dst {
+        // On Windows, paths like `\docs\index.md` have a root but no drive.
+        // They are relative to the root of the drive of `src`, so they are
+        // treated like absolute links on other platforms.
+        root_relative if dst.has_root() && !dst.is_absolute() => {
+            if ignore_absolute_local_links {
+                return Ok(None);
+            }
+            absolute_path(src.to_path_buf()).join(root_relative)
+        }
         relative if dst.is_relative() => {
             // Find `dst` in the parent directory of `src`
             let Some(parent) = src.parent() else {
@@ -115,6 +161,66 @@ mod test_path {
         Ok(())
     }
 
+    #[cfg(windows)]
+    #[test]
+    fn test_strip_verbatim_prefix() {
+        assert_eq!(
+            strip_verbatim_prefix(Path::new(r"\\?\C:\docs\index.md")),
+            PathBuf::from(r"C:\docs\index.md")
+        );
+        assert_eq!(
+            strip_verbatim_prefix(Path::new(r"\\?\UNC\server\share\index.md")),
+            PathBuf::from(r"\\server\share\index.md")
+        );
+        assert_eq!(
+            strip_verbatim_prefix(Path::new(r"C:\docs\index.md")),
+            PathBuf::from(r"C:\docs\index.md")
+        );
+    }
+
+    // \\?\C:\project\docs\index.html
+    // ../foo.html
+    #[cfg(windows)]
+    #[test]
+    fn test_resolve_from_verbatim() -> Result<()> {
+        let index = PathBuf::from(r"\\?\C:\project\docs\index.html");
+        let path = PathBuf::from("../foo.html");
+        assert_eq!(
+            resolve(&index, &path, true)?,
+            Some(PathBuf::from(r"C:\project\foo.html"))
+        );
+        Ok(())
+    }
+
+    // \\server\share\docs\index.html
+    // ./foo.html
+    #[cfg(windows)]
+    #[test]
+    fn test_resolve_from_unc() -> Result<()> {
+        let index = PathBuf::from(r"\\server\share\docs\index.html");
+        let path = PathBuf::from("./foo.html");
+        assert_eq!(
+            resolve(&index, &path, true)?,
+            Some(PathBuf::from(r"\\server\share\docs\foo.html"))
+        );
+        Ok(())
+    }
+
+    // C:\project\docs\index.html
+    // \foo.html
+    #[cfg(windows)]
+    #[test]
+    fn test_resolve_root_relative() -> Result<()> {
+        let index = PathBuf::from(r"C:\project\docs\index.html");
+        let path = PathBuf::from(r"\foo.html");
+        assert_eq!(resolve(&index, &path, true)?, None);
+        assert_eq!(
+            resolve(&index, &path, false)?,
+            Some(PathBuf::from(r"C:\foo.html"))
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_contains() {
         let parent_dir = tempfile::tempdir().unwrap();
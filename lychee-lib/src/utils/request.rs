@@ -3,6 +3,7 @@ use percent_encoding::percent_decode_str;
 use reqwest::Url;
 use std::{
     collections::HashSet,
+    ops::RangeInclusive,
     path::{Path, PathBuf},
 };
 
@@ -35,7 +36,7 @@ fn create_request(
     let attribute = raw_uri.attribute.clone();
     let credentials = extract_credentials(extractor, &uri);
 
-    Ok(Request::new(uri, source, element, attribute, credentials))
+    Ok(Request::new(uri, source, element, attribute, credentials).with_span(raw_uri.span))
 }
 
 /// Try to parse the raw URI into a `Uri`.
@@ -60,11 +61,11 @@ fn try_parse_into_uri(
         Ok(uri) => uri,
         Err(_) => match base {
             Some(base_url) => match base_url.join(&text) {
-                Some(url) => Uri { url },
+                Some(url) => Uri::from(url),
                 None => return Err(ErrorKind::InvalidBaseJoin(text.clone())),
             },
             None => match source {
-                InputSource::FsPath(root) => {
+                InputSource::FsPath(root) | InputSource::ArchiveEntry { unpacked: root, .. } => {
                     create_uri_from_file_path(root, &text, root_dir.is_none())?
                 }
                 _ => return Err(ErrorKind::UnsupportedUriType(text)),
@@ -107,9 +108,7 @@ fn create_uri_from_file_path(
     else {
         return Err(ErrorKind::InvalidPathToUri(target_path));
     };
-    Ok(Uri {
-        url: constructed_url,
-    })
+    Ok(Uri::from(constructed_url))
 }
 
 /// Truncate the source in case it gets too long
@@ -133,26 +132,89 @@ fn truncate_source(source: &InputSource) -> InputSource {
 ///
 /// If a URLs is ignored (because of the current settings),
 /// it will not be added to the `HashSet`.
+///
+/// Requests whose links are on the lines of the `generated` regions of the
+/// source are marked as generated. A link which occurs several times in the
+/// source is only marked as generated if all of its occurrences are.
 pub(crate) fn create(
     uris: Vec<RawUri>,
     source: &InputSource,
     root_dir: Option<&PathBuf>,
     base: Option<&Base>,
     extractor: Option<&BasicAuthExtractor>,
+    generated: &[RangeInclusive<usize>],
 ) -> HashSet<Request> {
     let base = base.cloned().or_else(|| Base::from_source(source));
-
-    uris.into_iter()
-        .filter_map(|raw_uri| {
-            match create_request(&raw_uri, source, root_dir, base.as_ref(), extractor) {
-                Ok(request) => Some(request),
-                Err(e) => {
-                    warn!("Error creating request: {:?}", e);
-                    None
-                }
+    // A `<base href>` element of an HTML document overrides the base
+    let document_base = document_base(&uris, source, root_dir, base.as_ref());
+
+    let mut requests: HashSet<Request> = HashSet::new();
+    for raw_uri in uris {
+        let base = link_base(&raw_uri, document_base.as_ref()).or(base.as_ref());
+        let mut request = match create_request(&raw_uri, source, root_dir, base, extractor) {
+            Ok(request) => request,
+            Err(e) => {
+                warn!("Error creating request: {:?}", e);
+                continue;
             }
-        })
-        .collect()
+        };
+        request.generated = request.span.is_some_and(|span| {
+            generated
+                .iter()
+                .any(|region| region.contains(&span.line.get()))
+        });
+        // Duplicates are only compared without their span and generated flag
+        if let Some(mut first) = requests.take(&request) {
+            first.generated &= request.generated;
+            request = first;
+        }
+        requests.insert(request);
+    }
+    requests
+}
+
+/// The base of a document with a `<base href>` element, which is resolved
+/// like the other links of the document.
+///
+/// Like in browsers, only the first `<base href>` element counts.
+fn document_base(
+    uris: &[RawUri],
+    source: &InputSource,
+    root_dir: Option<&PathBuf>,
+    base: Option<&Base>,
+) -> Option<Base> {
+    let raw_uri = uris.iter().find(|uri| {
+        uri.element.as_deref() == Some("base") && uri.attribute.as_deref() == Some("href")
+    })?;
+    let mut url = try_parse_into_uri(raw_uri, source, root_dir, base)
+        .ok()?
+        .url;
+    // Resolving local paths drops the trailing slash of directories
+    if raw_uri.text.ends_with('/') && !url.path().ends_with('/') {
+        url.set_path(&format!("{}/", url.path()));
+    }
+    // Local bases are kept as `file` URLs rather than paths, so that links
+    // are resolved relative to the directory of the base like in browsers,
+    // fragments and queries included
+    Some(Base::Remote(url))
+}
+
+/// The base of a link in a document whose `<base href>` element is
+/// `document_base`.
+///
+/// In local documents, fragment-only links still point into the document
+/// itself and absolute paths into the root directory, as they do without a
+/// `<base href>` element.
+fn link_base<'a>(raw_uri: &RawUri, document_base: Option<&'a Base>) -> Option<&'a Base> {
+    match document_base? {
+        Base::Remote(url)
+            if url.scheme() == "file"
+                && (is_anchor(&raw_uri.text) || raw_uri.text.starts_with('/')) =>
+        {
+            None
+        }
+        base => Some(base),
+    }
 }
 
 /// Create a URI from a path
@@ -221,13 +283,27 @@ mod tests {
         assert_eq!(result.as_str(), "file:///test+encoding");
     }
 
+    #[test]
+    fn test_relative_url_resolution_in_bucket() {
+        let url = Url::parse("s3://bucket/docs/readme.md").unwrap();
+        let source = InputSource::Bucket(Box::new(url));
+
+        let uris = vec![RawUri::from("guide.md")];
+        let requests = create(uris, &source, None, None, None, &[]);
+
+        assert_eq!(requests.len(), 1);
+        assert!(requests
+            .iter()
+            .any(|r| r.uri.url.as_str() == "s3://bucket/docs/guide.md"));
+    }
+
     #[test]
     fn test_relative_url_resolution() {
         let base = Base::try_from("https://example.com/path/page.html").unwrap();
         let source = InputSource::String(String::new());
 
         let uris = vec![RawUri::from("relative.html")];
-        let requests = create(uris, &source, None, Some(&base), None);
+        let requests = create(uris, &source, None, Some(&base), None, &[]);
 
         assert_eq!(requests.len(), 1);
         assert!(requests
@@ -241,7 +317,7 @@ mod tests {
         let source = InputSource::String(String::new());
 
         let uris = vec![RawUri::from("https://another.com/page")];
-        let requests = create(uris, &source, None, Some(&base), None);
+        let requests = create(uris, &source, None, Some(&base), None, &[]);
 
         assert_eq!(requests.len(), 1);
         assert!(requests
@@ -255,7 +331,7 @@ mod tests {
         let source = InputSource::String(String::new());
 
         let uris = vec![RawUri::from("/root-relative")];
-        let requests = create(uris, &source, None, Some(&base), None);
+        let requests = create(uris, &source, None, Some(&base), None, &[]);
 
         assert_eq!(requests.len(), 1);
         assert!(requests
@@ -269,7 +345,7 @@ mod tests {
         let source = InputSource::String(String::new());
 
         let uris = vec![RawUri::from("../parent")];
-        let requests = create(uris, &source, None, Some(&base), None);
+        let requests = create(uris, &source, None, Some(&base), None, &[]);
 
         assert_eq!(requests.len(), 1);
         assert!(requests
@@ -283,7 +359,7 @@ mod tests {
         let source = InputSource::String(String::new());
 
         let uris = vec![RawUri::from("#fragment")];
-        let requests = create(uris, &source, None, Some(&base), None);
+        let requests = create(uris, &source, None, Some(&base), None, &[]);
 
         assert_eq!(requests.len(), 1);
         assert!(requests
@@ -297,7 +373,7 @@ mod tests {
         let source = InputSource::FsPath(PathBuf::from("/some/page.html"));
 
         let uris = vec![RawUri::from("relative.html")];
-        let requests = create(uris, &source, Some(&root_dir), None, None);
+        let requests = create(uris, &source, Some(&root_dir), None, None, &[]);
 
         assert_eq!(requests.len(), 1);
         assert!(requests
@@ -311,7 +387,7 @@ mod tests {
         let source = InputSource::FsPath(PathBuf::from("/some/page.html"));
 
         let uris = vec![RawUri::from("https://another.com/page")];
-        let requests = create(uris, &source, Some(&root_dir), None, None);
+        let requests = create(uris, &source, Some(&root_dir), None, None, &[]);
 
         assert_eq!(requests.len(), 1);
         assert!(requests
@@ -325,7 +401,7 @@ mod tests {
         let source = InputSource::FsPath(PathBuf::from("/some/page.html"));
 
         let uris = vec![RawUri::from("/root-relative")];
-        let requests = create(uris, &source, Some(&root_dir), None, None);
+        let requests = create(uris, &source, Some(&root_dir), None, None, &[]);
 
         assert_eq!(requests.len(), 1);
         assert!(requests
@@ -339,7 +415,7 @@ mod tests {
         let source = InputSource::FsPath(PathBuf::from("/some/page.html"));
 
         let uris = vec![RawUri::from("../parent")];
-        let requests = create(uris, &source, Some(&root_dir), None, None);
+        let requests = create(uris, &source, Some(&root_dir), None, None, &[]);
 
         assert_eq!(requests.len(), 1);
         assert!(requests
@@ -353,7 +429,7 @@ mod tests {
         let source = InputSource::FsPath(PathBuf::from("/some/page.html"));
 
         let uris = vec![RawUri::from("#fragment")];
-        let requests = create(uris, &source, Some(&root_dir), None, None);
+        let requests = create(uris, &source, Some(&root_dir), None, None, &[]);
 
         assert_eq!(requests.len(), 1);
         assert!(requests
@@ -368,7 +444,7 @@ mod tests {
         let source = InputSource::FsPath(PathBuf::from("/some/page.html"));
 
         let uris = vec![RawUri::from("relative.html")];
-        let requests = create(uris, &source, Some(&root_dir), Some(&base), None);
+        let requests = create(uris, &source, Some(&root_dir), Some(&base), None, &[]);
 
         assert_eq!(requests.len(), 1);
         assert!(requests
@@ -383,7 +459,7 @@ mod tests {
         let source = InputSource::FsPath(PathBuf::from("/some/page.html"));
 
         let uris = vec![RawUri::from("https://another.com/page")];
-        let requests = create(uris, &source, Some(&root_dir), Some(&base), None);
+        let requests = create(uris, &source, Some(&root_dir), Some(&base), None, &[]);
 
         assert_eq!(requests.len(), 1);
         assert!(requests
@@ -398,7 +474,7 @@ mod tests {
         let source = InputSource::FsPath(PathBuf::from("/some/page.html"));
 
         let uris = vec![RawUri::from("/root-relative")];
-        let requests = create(uris, &source, Some(&root_dir), Some(&base), None);
+        let requests = create(uris, &source, Some(&root_dir), Some(&base), None, &[]);
 
         assert_eq!(requests.len(), 1);
         assert!(requests
@@ -413,7 +489,7 @@ mod tests {
         let source = InputSource::FsPath(PathBuf::from("/some/page.html"));
 
         let uris = vec![RawUri::from("../parent")];
-        let requests = create(uris, &source, Some(&root_dir), Some(&base), None);
+        let requests = create(uris, &source, Some(&root_dir), Some(&base), None, &[]);
 
         assert_eq!(requests.len(), 1);
         assert!(requests
@@ -428,7 +504,7 @@ mod tests {
         let source = InputSource::FsPath(PathBuf::from("/some/page.html"));
 
         let uris = vec![RawUri::from("#fragment")];
-        let requests = create(uris, &source, Some(&root_dir), Some(&base), None);
+        let requests = create(uris, &source, Some(&root_dir), Some(&base), None, &[]);
 
         assert_eq!(requests.len(), 1);
         assert!(requests
@@ -436,12 +512,78 @@ mod tests {
             .any(|r| r.uri.url.as_str() == "https://example.com/path/page.html#fragment"));
     }
 
+    #[test]
+    fn test_base_element_resolution() {
+        let base = Base::try_from("https://example.com/path/page.html").unwrap();
+        let source = InputSource::String(String::new());
+
+        let uris = vec![
+            RawUri {
+                text: "/docs/".to_string(),
+                element: Some("base".to_string()),
+                attribute: Some("href".to_string()),
+                span: None,
+            },
+            RawUri::from("relative.html"),
+        ];
+        let requests = create(uris, &source, None, Some(&base), None, &[]);
+
+        assert_eq!(requests.len(), 2);
+        assert!(requests
+            .iter()
+            .any(|r| r.uri.url.as_str() == "https://example.com/docs/relative.html"));
+    }
+
+    #[test]
+    fn test_local_base_element_resolution() {
+        let source = InputSource::FsPath(PathBuf::from("/tmp/lychee/page.html"));
+
+        let uris = vec![
+            RawUri {
+                text: "docs/index.html".to_string(),
+                element: Some("base".to_string()),
+                attribute: Some("href".to_string()),
+                span: None,
+            },
+            RawUri::from("relative.html"),
+        ];
+        let requests = create(uris, &source, None, None, None, &[]);
+
+        assert!(requests
+            .iter()
+            .any(|r| r.uri.url.as_str() == "file:///tmp/lychee/docs/relative.html"));
+    }
+
+    #[test]
+    fn test_local_base_element_fragment_and_absolute_resolution() {
+        let root_dir = PathBuf::from("/tmp/lychee");
+        let source = InputSource::FsPath(PathBuf::from("/tmp/lychee/page.html"));
+
+        let uris = vec![
+            RawUri {
+                text: "docs/".to_string(),
+                element: Some("base".to_string()),
+                attribute: Some("href".to_string()),
+                span: None,
+            },
+            RawUri::from("#top"),
+            RawUri::from("/about.html"),
+            RawUri::from("guide.html#install"),
+        ];
+        let requests = create(uris, &source, Some(&root_dir), None, None, &[]);
+        let urls: HashSet<_> = requests.iter().map(|r| r.uri.url.as_str()).collect();
+
+        assert!(urls.contains("file:///tmp/lychee/page.html#top"));
+        assert!(urls.contains("file:///tmp/lychee/about.html"));
+        assert!(urls.contains("file:///tmp/lychee/docs/guide.html#install"));
+    }
+
     #[test]
     fn test_no_base_url_resolution() {
         let source = InputSource::String(String::new());
 
         let uris = vec![RawUri::from("https://example.com/page")];
-        let requests = create(uris, &source, None, None, None);
+        let requests = create(uris, &source, None, None, None, &[]);
 
         assert_eq!(requests.len(), 1);
         assert!(requests
@@ -466,9 +608,7 @@ mod tests {
         assert_eq!(
             actual,
             Request::new(
-                Uri {
-                    url: Url::from_file_path("/tmp/lychee/file.html").unwrap()
-                },
+                Uri::from(Url::from_file_path("/tmp/lychee/file.html").unwrap()),
                 input_source,
                 None,
                 None,
@@ -495,9 +635,7 @@ mod tests {
         assert_eq!(
             actual,
             Request::new(
-                Uri {
-                    url: Url::from_file_path("/usr/local/share/doc/example.html").unwrap()
-                },
+                Uri::from(Url::from_file_path("/usr/local/share/doc/example.html").unwrap()),
                 input_source,
                 None,
                 None,
@@ -0,0 +1,304 @@
+//! Support for [sitemaps](https://www.sitemaps.org/protocol.html) as inputs.
+//!
+//! A sitemap lists all pages of a website, so that the links on every page
+//! can be checked from a single entry point without crawling the site.
+
+use std::{collections::HashSet, io::Read};
+
+use flate2::read::GzDecoder;
+use futures::Stream;
+use log::warn;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::Url;
+
+use super::paginate::{paginate, send_with_backoff, Page};
+use crate::{ErrorKind, Result};
+
+/// Maximum nesting of sitemap index files.
+///
+/// The protocol doesn't allow index files to reference other index files,
+/// but some sites do it anyway.
+const MAX_DEPTH: usize = 3;
+
+/// Magic bytes at the start of gzip compressed data
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Matches the content of `<loc>` elements, optionally wrapped in `CDATA`
+static LOC: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<loc>\s*(?:<!\[CDATA\[)?(.*?)(?:\]\]>)?\s*</loc>").unwrap());
+
+/// Matches the root element of sitemap index files
+static SITEMAP_INDEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"<sitemapindex[\s>]").unwrap());
+
+/// Matches the root element of sitemaps which list pages
+static URLSET: Lazy<Regex> = Lazy::new(|| Regex::new(r"<urlset[\s>]").unwrap());
+
+/// The entries of a sitemap file
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Sitemap {
+    /// A `<sitemapindex>`, which lists other sitemaps
+    Index(Vec<Url>),
+    /// A `<urlset>`, which lists pages of the website
+    UrlSet(Vec<Url>),
+}
+
+/// Returns `true` if the URL looks like it points to a sitemap,
+/// e.g. `https://example.com/sitemap.xml` or `sitemap_index.xml.gz`
+pub(crate) fn is_sitemap_url(url: &Url) -> bool {
+    let Some(name) = url.path_segments().and_then(Iterator::last) else {
+        return false;
+    };
+    let name = name.to_lowercase();
+    name.starts_with("sitemap") && (name.ends_with(".xml") || name.ends_with(".xml.gz"))
+}
+
+/// Replace the predefined XML entities
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Parse the `<loc>` entries of a sitemap.
+///
+/// Entries which aren't valid URLs are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the content is neither a sitemap index nor a list of
+/// pages.
+pub(crate) fn parse(url: &Url, content: &str) -> Result<Sitemap> {
+    let locs = LOC
+        .captures_iter(content)
+        .filter_map(|captures| Url::parse(&unescape(captures[1].trim())).ok())
+        .collect();
+
+    if SITEMAP_INDEX.is_match(content) {
+        Ok(Sitemap::Index(locs))
+    } else if URLSET.is_match(content) {
+        Ok(Sitemap::UrlSet(locs))
+    } else {
+        Err(ErrorKind::InvalidSitemap(
+            url.to_string(),
+            "Missing `<urlset>` or `<sitemapindex>` element".to_string(),
+        ))
+    }
+}
+
+/// Decode the body of a sitemap, which might be gzip compressed
+fn decode(url: &Url, body: &[u8]) -> Result<String> {
+    if !body.starts_with(&GZIP_MAGIC) {
+        return Ok(std::str::from_utf8(body)?.to_string());
+    }
+    let mut content = String::new();
+    GzDecoder::new(body)
+        .read_to_string(&mut content)
+        .map_err(|e| ErrorKind::InvalidSitemap(url.to_string(), e.to_string()))?;
+    Ok(content)
+}
+
+/// Fetch a sitemap and stream the URLs of all listed pages.
+///
+/// Sitemap index files are resolved recursively. The listed sitemaps are
+/// fetched one at a time while the pages are consumed, so large sites don't
+/// have to be listed up front.
+///
+/// # Errors
+///
+/// The stream yields an error and ends if a sitemap can't be fetched or
+/// parsed.
+pub(crate) fn page_urls(sitemap: &Url) -> impl Stream<Item = Result<Url>> {
+    let client = reqwest::Client::new();
+    let mut seen = HashSet::new();
+
+    paginate((sitemap.clone(), 0), move |(url, depth)| {
+        let first_visit = seen.insert(url.clone());
+        let client = client.clone();
+        async move {
+            if !first_visit {
+                return Ok(Page::new(Vec::new(), None));
+            }
+            let body = send_with_backoff(|| client.get(url.clone()))
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .map_err(ErrorKind::NetworkRequest)?
+                .bytes()
+                .await
+                .map_err(ErrorKind::ReadResponseBody)?;
+
+            Ok(match parse(&url, &decode(&url, &body)?)? {
+                Sitemap::Index(sitemaps) if depth < MAX_DEPTH => Page {
+                    items: Vec::new(),
+                    next: sitemaps
+                        .into_iter()
+                        .map(|sitemap| (sitemap, depth + 1))
+                        .collect(),
+                },
+                Sitemap::Index(_) => {
+                    warn!("Skipping sitemap index `{url}`: nested too deeply");
+                    Page::new(Vec::new(), None)
+                }
+                Sitemap::UrlSet(urls) => Page::new(urls, None),
+            })
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+    use futures::TryStreamExt;
+    use http::StatusCode;
+    use wiremock::{matchers::path, Mock, ResponseTemplate};
+
+    use super::*;
+    use crate::mock_server;
+
+    fn url(s: &str) -> Url {
+        Url::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_is_sitemap_url() {
+        assert!(is_sitemap_url(&url("https://example.com/sitemap.xml")));
+        assert!(is_sitemap_url(&url(
+            "https://example.com/Sitemap_Index.xml"
+        )));
+        assert!(is_sitemap_url(&url(
+            "https://example.com/blog/sitemap.xml.gz"
+        )));
+        assert!(!is_sitemap_url(&url("https://example.com/")));
+        assert!(!is_sitemap_url(&url("https://example.com/sitemap.html")));
+        assert!(!is_sitemap_url(&url("https://example.com/feed.xml")));
+    }
+
+    #[test]
+    fn test_parse_urlset() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+                <url><loc>https://example.com/</loc></url>
+                <url>
+                    <loc>
+                        https://example.com/search?q=a&amp;page=2
+                    </loc>
+                    <lastmod>2024-01-01</lastmod>
+                </url>
+                <url><loc><![CDATA[https://example.com/cdata]]></loc></url>
+                <url><loc>not a url</loc></url>
+            </urlset>"#;
+
+        assert_eq!(
+            parse(&url("https://example.com/sitemap.xml"), content).unwrap(),
+            Sitemap::UrlSet(vec![
+                url("https://example.com/"),
+                url("https://example.com/search?q=a&page=2"),
+                url("https://example.com/cdata"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_index() {
+        let content = r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+                <sitemap><loc>https://example.com/sitemap1.xml.gz</loc></sitemap>
+            </sitemapindex>"#;
+
+        assert_eq!(
+            parse(&url("https://example.com/sitemap.xml"), content).unwrap(),
+            Sitemap::Index(vec![url("https://example.com/sitemap1.xml.gz")])
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(matches!(
+            parse(&url("https://example.com/sitemap.xml"), "<html></html>"),
+            Err(ErrorKind::InvalidSitemap(..))
+        ));
+    }
+
+    #[test]
+    fn test_decode_gzip() {
+        let content = "<urlset></urlset>";
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content.as_bytes()).unwrap();
+        let body = encoder.finish().unwrap();
+
+        let sitemap = url("https://example.com/sitemap.xml.gz");
+        assert_eq!(decode(&sitemap, &body).unwrap(), content);
+        assert_eq!(decode(&sitemap, content.as_bytes()).unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn test_page_urls() {
+        let mock_server = mock_server!(
+            StatusCode::OK,
+            set_body_string(
+                "<urlset><url><loc>https://example.com/a</loc></url>\
+                 <url><loc>https://example.com/b</loc></url></urlset>"
+            )
+        );
+        let sitemap = url(&format!("{}/sitemap.xml", mock_server.uri()));
+
+        let pages: Vec<_> = page_urls(&sitemap).try_collect().await.unwrap();
+        assert_eq!(
+            pages,
+            vec![url("https://example.com/a"), url("https://example.com/b")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_page_urls_index() {
+        let mock_server = wiremock::MockServer::start().await;
+        let index = format!(
+            "<sitemapindex>\
+             <sitemap><loc>{0}/sitemap1.xml</loc></sitemap>\
+             <sitemap><loc>{0}/sitemap2.xml</loc></sitemap>\
+             <sitemap><loc>{0}/sitemap1.xml</loc></sitemap>\
+             </sitemapindex>",
+            mock_server.uri()
+        );
+        Mock::given(path("/sitemap.xml"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_string(index))
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/sitemap1.xml"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_string(
+                    "<urlset><url><loc>https://example.com/a</loc></url></urlset>",
+                ),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        // Rate limited once, then served
+        Mock::given(path("/sitemap2.xml"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::TOO_MANY_REQUESTS)
+                    .insert_header("Retry-After", "0"),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/sitemap2.xml"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_string(
+                    "<urlset><url><loc>https://example.com/b</loc></url></urlset>",
+                ),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let sitemap = url(&format!("{}/sitemap.xml", mock_server.uri()));
+        let pages: Vec<_> = page_urls(&sitemap).try_collect().await.unwrap();
+        assert_eq!(
+            pages,
+            vec![url("https://example.com/a"), url("https://example.com/b")]
+        );
+    }
+}